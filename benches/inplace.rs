@@ -0,0 +1,67 @@
+//! Benchmarks for pervasive math in loops, counting the memory it allocates
+//!
+//! Run with `cargo bench --bench inplace`.
+//! Math on an array that nothing else references should reuse its memory,
+//! so the bytes allocated per iteration should be much smaller than the array.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use uiua::Uiua;
+
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATED.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// The name, repeated function, initial value, and number of iterations of each loop
+const LOOPS: &[(&str, &str, &str, usize)] = &[
+    ("scalar +1", "+1", "0", 1000),
+    ("numbers +1", "+1", "⇡1e5", 1000),
+    ("numbers +1.5", "+1.5", "⇡1e5", 1000),
+    ("numbers ×2", "×2", "⇡1e5", 1000),
+    ("numbers ¯", "¯", "⇡1e5", 1000),
+    ("1e8 numbers ¯", "¯", "↯1e8 0.5", 10),
+    ("square ⍉", "⍉", "↯300_300⇡9e4", 1000),
+];
+
+fn run(code: &str) -> (Duration, usize) {
+    let mut env = Uiua::with_safe_sys();
+    let allocated = ALLOCATED.load(Ordering::Relaxed);
+    let start = Instant::now();
+    if let Err(e) = env.run_str(code) {
+        panic!("{}", e.report());
+    }
+    let elapsed = start.elapsed();
+    (elapsed, ALLOCATED.load(Ordering::Relaxed) - allocated)
+}
+
+fn main() {
+    println!("Pervasive math in loops:");
+    for &(name, f, init, iterations) in LOOPS {
+        let (base_time, base_bytes) = run(&format!("◌⍥({f}) 0 {init}"));
+        let (time, bytes) = run(&format!("◌⍥({f}) {iterations} {init}"));
+        let time = time.saturating_sub(base_time) / iterations as u32;
+        let bytes = bytes.saturating_sub(base_bytes) / iterations;
+        println!("  {name:<14} {time:>10.3?} and {bytes:>8} bytes allocated per iteration");
+    }
+}
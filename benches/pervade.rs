@@ -0,0 +1,52 @@
+//! Benchmarks for pervasive math on million-element arrays
+//!
+//! Run with `cargo bench --bench pervade`,
+//! then with `cargo bench --bench pervade --features simd` to compare.
+
+use std::time::{Duration, Instant};
+
+use uiua::Uiua;
+
+const REPETITIONS: usize = 100;
+
+const OPS: &[(&str, &str)] = &[
+    ("add", "+"),
+    ("multiply", "×"),
+    ("minimum", "↧"),
+    ("maximum", "↥"),
+    ("less than", "<"),
+];
+
+const TYPES: &[(&str, &str)] = &[
+    ("numbers", "X ← ⇡1e6\nY ← ⇌X"),
+    ("bytes", "X ← =0◿2⇡1e6\nY ← ⇌X"),
+];
+
+fn time(code: &str) -> Duration {
+    let mut env = Uiua::with_safe_sys();
+    let start = Instant::now();
+    if let Err(e) = env.run_str(code) {
+        panic!("{}", e.report());
+    }
+    start.elapsed()
+}
+
+fn main() {
+    let features = if cfg!(feature = "simd") {
+        "with simd"
+    } else {
+        "without simd"
+    };
+    println!("Pervasive math on 1e6 elements {features}:");
+    for (type_name, setup) in TYPES {
+        let setup_time = time(setup);
+        for (op_name, glyph) in OPS {
+            let code = format!("{setup}\n⍥(◌{glyph} X Y){REPETITIONS}");
+            let total = time(&code).saturating_sub(setup_time);
+            println!(
+                "  {op_name:>9} {type_name:<7} {:>10.3?} per call",
+                total / REPETITIONS as u32
+            );
+        }
+    }
+}
@@ -0,0 +1,229 @@
+/* The Uiua C API. Build the library with `cargo rustc --release --lib --features capi --crate-type cdylib`. */
+
+#ifndef UIUA_H
+#define UIUA_H
+
+/* This file is generated by cbindgen. Do not edit it by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The type of a Uiua value's elements
+ */
+typedef enum UiuaType {
+  /**
+   * Numbers
+   */
+  UIUA_TYPE_NUM,
+  /**
+   * Bytes
+   */
+  UIUA_TYPE_BYTE,
+  /**
+   * Complex numbers
+   */
+  UIUA_TYPE_COMPLEX,
+  /**
+   * Characters
+   */
+  UIUA_TYPE_CHAR,
+  /**
+   * Boxes
+   */
+  UIUA_TYPE_BOX,
+} UiuaType;
+
+/**
+ * A Uiua environment
+ */
+typedef struct UiuaEnv UiuaEnv;
+
+/**
+ * A Uiua value
+ */
+typedef struct UiuaValue UiuaValue;
+
+/**
+ * Callbacks that implement a system backend
+ *
+ * Any callback may be null, in which case the operation is not supported.
+ * `data` is passed to every callback.
+ * Strings and buffers returned by callbacks must stay valid until the next callback is called.
+ */
+typedef struct UiuaBackend {
+  /**
+   * Data passed to every callback
+   */
+  void *data;
+  /**
+   * Print a string to stdout
+   */
+  void (*print_stdout)(void *data, const char *s, size_t len);
+  /**
+   * Print a string to stderr
+   */
+  void (*print_stderr)(void *data, const char *s, size_t len);
+  /**
+   * Read a line from stdin without the newline, or return null at the end of input
+   */
+  const char *(*scan_line)(void *data);
+  /**
+   * Get an environment variable, or return null if it is not set
+   */
+  const char *(*var)(void *data, const char *name);
+  /**
+   * Read a whole file
+   */
+  int (*file_read_all)(void *data, const char *path, const uint8_t **out, size_t *out_len);
+  /**
+   * Write a whole file
+   */
+  int (*file_write_all)(void *data, const char *path, const uint8_t *contents, size_t len);
+  /**
+   * Get the current time in seconds since the Unix epoch
+   */
+  double (*now)(void *data);
+} UiuaBackend;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Create an environment with no IO capabilities
+ */
+UiuaEnv *uiua_new(void);
+
+/**
+ * Create an environment with full access to the native system
+ */
+UiuaEnv *uiua_new_native(void);
+
+/**
+ * Create an environment whose system operations call the given callbacks
+ *
+ * The callbacks are copied, but `backend->data` must stay valid until the environment is freed.
+ */
+UiuaEnv *uiua_new_with_backend(const UiuaBackend *backend);
+
+/**
+ * Free an environment
+ */
+void uiua_free(UiuaEnv *env);
+
+/**
+ * Run a string of Uiua code
+ *
+ * The values on the stack are kept between runs.
+ * On failure, the error message can be gotten with [`uiua_error`].
+ */
+int uiua_run_str(UiuaEnv *env, const char *code);
+
+/**
+ * Get the message of the last error, or null if the last run succeeded
+ *
+ * The string is valid until the next call to [`uiua_run_str`] or [`uiua_free`].
+ */
+const char *uiua_error(const UiuaEnv *env);
+
+/**
+ * Get the number of values on the stack
+ */
+size_t uiua_stack_len(const UiuaEnv *env);
+
+/**
+ * Push a value onto the stack, taking ownership of it
+ */
+void uiua_push(UiuaEnv *env, UiuaValue *value);
+
+/**
+ * Pop a value from the stack, or return null if the stack is empty
+ */
+UiuaValue *uiua_pop(UiuaEnv *env);
+
+/**
+ * Create a number array from a buffer of elements in row-major order
+ *
+ * The buffer must have as many elements as the product of the shape.
+ */
+UiuaValue *uiua_value_from_f64s(const double *data, const size_t *shape, size_t rank);
+
+/**
+ * Create a byte array from a buffer of elements in row-major order
+ *
+ * The buffer must have as many elements as the product of the shape.
+ */
+UiuaValue *uiua_value_from_u8s(const uint8_t *data, const size_t *shape, size_t rank);
+
+/**
+ * Create a string from a null-terminated UTF-8 string
+ */
+UiuaValue *uiua_value_from_str(const char *s);
+
+/**
+ * Free a value
+ */
+void uiua_value_free(UiuaValue *value);
+
+/**
+ * Get the type of a value's elements
+ */
+UiuaType uiua_value_type(const UiuaValue *value);
+
+/**
+ * Get the rank of a value
+ */
+size_t uiua_value_rank(const UiuaValue *value);
+
+/**
+ * Get a pointer to the `rank` dimensions of a value's shape
+ *
+ * The pointer is valid until the value is freed.
+ */
+const size_t *uiua_value_shape(const UiuaValue *value);
+
+/**
+ * Get the number of elements in a value
+ */
+size_t uiua_value_len(const UiuaValue *value);
+
+/**
+ * Copy the elements of a number or byte array into a buffer of at least `len` numbers
+ */
+int uiua_value_f64s(const UiuaValue *value, double *out, size_t len);
+
+/**
+ * Copy the elements of a byte array, or a number array of integers from 0 to 255,
+ * into a buffer of at least `len` bytes
+ */
+int uiua_value_u8s(const UiuaValue *value, uint8_t *out, size_t len);
+
+/**
+ * Get a character array of rank 0 or 1 as a null-terminated UTF-8 string,
+ * or return null if the value is not a string
+ *
+ * The string must be freed with [`uiua_string_free`].
+ */
+char *uiua_value_str(const UiuaValue *value);
+
+/**
+ * Free a string returned by [`uiua_value_str`]
+ */
+void uiua_string_free(char *s);
+
+/**
+ * Get a row of a value, or return null if the index is out of bounds
+ *
+ * Boxed rows are unboxed. The row must be freed with [`uiua_value_free`].
+ */
+UiuaValue *uiua_value_row(const UiuaValue *value, size_t index);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* UIUA_H */
@@ -8,7 +8,7 @@ use std::{
 };
 
 use leptos::*;
-use uiua::{example_ua, Report, SysBackend};
+use uiua::{example_ua, HttpResponse, Report, SysBackend};
 
 use crate::{editor::get_ast_time, weewuh};
 
@@ -17,6 +17,7 @@ pub struct WebBackend {
     pub stderr: Mutex<String>,
     pub trace: Mutex<String>,
     pub files: Mutex<HashMap<PathBuf, Vec<u8>>>,
+    pub audio_sample_rate: Mutex<u32>,
 }
 
 thread_local! {
@@ -41,6 +42,7 @@ impl Default for WebBackend {
             files: DROPPED_FILES
                 .with(|dropped_files| dropped_files.borrow().clone())
                 .into(),
+            audio_sample_rate: 44100.into(),
         }
     }
 }
@@ -143,6 +145,23 @@ impl SysBackend for WebBackend {
             .cloned()
             .ok_or_else(|| format!("File not found: {}", path.display()))
     }
+    fn random_bytes(&self, count: usize) -> Result<Vec<u8>, String> {
+        let crypto = window().crypto().map_err(|e| format!("{e:?}"))?;
+        let mut bytes = vec![0; count];
+        // The browser limits each call to 65536 bytes
+        for chunk in bytes.chunks_mut(65536) {
+            crypto
+                .get_random_values_with_u8_array(chunk)
+                .map_err(|e| format!("{e:?}"))?;
+        }
+        Ok(bytes)
+    }
+    fn set_clipboard(&self, contents: &str) -> Result<(), String> {
+        let clipboard = (window().navigator().clipboard())
+            .ok_or("The clipboard is not available in this browser")?;
+        _ = clipboard.write_text(contents);
+        Ok(())
+    }
     fn play_audio(&self, wav_bytes: Vec<u8>) -> Result<(), String> {
         self.stdout
             .lock()
@@ -150,10 +169,17 @@ impl SysBackend for WebBackend {
             .push(OutputItem::Audio(wav_bytes));
         Ok(())
     }
+    fn audio_sample_rate(&self) -> u32 {
+        *self.audio_sample_rate.lock().unwrap()
+    }
+    fn set_audio_sample_rate(&self, sample_rate: u32) -> Result<(), String> {
+        *self.audio_sample_rate.lock().unwrap() = sample_rate;
+        Ok(())
+    }
     fn stream_audio(&self, mut f: uiua::AudioStreamFn) -> Result<(), String> {
         let mut samples = Vec::new();
         let mut t = 0.0;
-        const SAMPLE_RATE: u32 = 44100;
+        let sample_rate = self.audio_sample_rate();
         const SAMPLES_PER_FRAME: usize = 10000;
         let mut times = Vec::with_capacity(SAMPLES_PER_FRAME);
         let ast_time = get_ast_time();
@@ -161,7 +187,7 @@ impl SysBackend for WebBackend {
             times.clear();
             for _ in 0..SAMPLES_PER_FRAME {
                 times.push(t);
-                t += 1.0 / SAMPLE_RATE as f64;
+                t += 1.0 / sample_rate as f64;
             }
             match f(&times) {
                 Ok(s) => samples.extend(s),
@@ -173,7 +199,7 @@ impl SysBackend for WebBackend {
             |s| (s * i16::MAX as f64) as i16,
             16,
             hound::SampleFormat::Int,
-            SAMPLE_RATE,
+            sample_rate,
         )?;
         self.play_audio(bytes)
     }
@@ -182,4 +208,50 @@ impl SysBackend for WebBackend {
         while (instant::now() - start) / 1000.0 < seconds {}
         Ok(())
     }
+    fn http_request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> Result<HttpResponse, String> {
+        let js_err = |e: wasm_bindgen::JsValue| format!("{e:?}");
+        let xhr = web_sys::XmlHttpRequest::new().map_err(js_err)?;
+        // The request is synchronous because the backend is synchronous
+        xhr.open_with_async(method, url, false).map_err(js_err)?;
+        // Receive the body as raw bytes
+        xhr.override_mime_type("text/plain; charset=x-user-defined")
+            .map_err(js_err)?;
+        for (name, value) in headers {
+            // The browser sets these itself
+            if ["host", "content-length", "connection"]
+                .iter()
+                .any(|h| name.eq_ignore_ascii_case(h))
+            {
+                continue;
+            }
+            xhr.set_request_header(name, value).map_err(js_err)?;
+        }
+        if body.is_empty() {
+            xhr.send()
+        } else {
+            xhr.send_with_opt_buffer_source(Some(&js_sys::Uint8Array::from(body)))
+        }
+        .map_err(js_err)?;
+        let headers = (xhr.get_all_response_headers().map_err(js_err)?)
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+            .collect();
+        let body = (xhr.response_text().map_err(js_err)?)
+            .unwrap_or_default()
+            .chars()
+            .map(|c| c as u32 as u8)
+            .collect();
+        Ok(HttpResponse {
+            status: xhr.status().map_err(js_err)?,
+            headers,
+            body,
+        })
+    }
 }
@@ -8,7 +8,7 @@ use leptos::{ev::keydown, *};
 use leptos_router::{use_navigate, BrowserIntegration, History, LocationChange, NavigateOptions};
 use uiua::{
     format::{format_str, FormatConfig},
-    is_ident_char, lex, Primitive, SysOp, Token,
+    is_ident_char, lex, Locale, Primitive, SysOp, Token,
 };
 use wasm_bindgen::{closure::Closure, JsCast, JsValue};
 use web_sys::{
@@ -180,6 +180,7 @@ pub fn Editor<'a>(
                 &FormatConfig {
                     trailing_newline: mode == EditorMode::Pad,
                     backend: Arc::new(WebBackend::default()),
+                    locale: get_locale(),
                     ..Default::default()
                 },
             ) {
@@ -274,16 +275,8 @@ pub fn Editor<'a>(
     // Replace the selected text in the editor with the given string
     let replace_code = move |inserted: &str| {
         if let Some((start, end)) = get_code_cursor() {
-            let (start, end) = (start.min(end), start.max(end) as usize);
-            let code = code_text();
-            let new: String = code
-                .chars()
-                .take(start as usize)
-                .chain(inserted.chars())
-                .chain(code.chars().skip(end))
-                .collect();
-            let offset = inserted.chars().count() as u32;
-            state().set_code(&new, Cursor::Set(start + offset, start + offset));
+            let (new, cursor) = insert_at_cursor(&code_text(), start, end, inserted);
+            state().set_code(&new, Cursor::Set(cursor, cursor));
         };
     };
 
@@ -670,9 +663,26 @@ pub fn Editor<'a>(
 
     // Glyph hover doc
     let (glyph_doc, set_glyph_doc) = create_signal(View::default());
-    let onmouseleave = move |_| {
+    let (glyph_doc_hovered, set_glyph_doc_hovered) = create_signal(false);
+    let hide_glyph_doc = move || {
         _ = glyph_doc_element().style().set_property("display", "none");
     };
+    // Wait a bit before hiding so that the mouse can move onto the doc
+    let onmouseleave = move |_| {
+        set_timeout(
+            move || {
+                if !glyph_doc_hovered.get_untracked() {
+                    hide_glyph_doc();
+                }
+            },
+            Duration::from_millis(300),
+        );
+    };
+    let on_glyph_doc_mouseenter = move |_| set_glyph_doc_hovered.set(true);
+    let on_glyph_doc_mouseleave = move |_| {
+        set_glyph_doc_hovered.set(false);
+        hide_glyph_doc();
+    };
 
     // Glyph buttons
     // These are the buttons that appear above the editor and allow the user to insert glyphs
@@ -681,7 +691,7 @@ pub fn Editor<'a>(
             .glyph()
             .map(Into::into)
             .or_else(|| prim.ascii().map(|s| s.to_string()))?;
-        let mut title = prim.name().to_string();
+        let mut title = localized_prim_name(prim);
         if let Some(ascii) = prim.ascii() {
             title = format!("({}) {}", ascii, title);
         }
@@ -704,6 +714,32 @@ pub fn Editor<'a>(
         };
         // Show the glyph doc on mouseover
         let onmouseover = move |_| {
+            // Example outputs are computed lazily and cached, so only the first hover is slow
+            let examples: Vec<_> = prim
+                .doc()
+                .featured_examples(2)
+                .map(|ex| {
+                    let output = match ex.output() {
+                        Ok(output) => output.join("\n"),
+                        Err(e) => e.clone(),
+                    };
+                    let insert_example = move |_| {
+                        replace_code(ex.input());
+                        hide_glyph_doc();
+                    };
+                    view! {
+                        <div class="glyph-doc-example">
+                            <div class="code" inner_html=code_html(ex.input())></div>
+                            <div class="output">{ output }</div>
+                            <button
+                                class="glyph-doc-insert-button"
+                                on:click=insert_example>
+                                "insert example"
+                            </button>
+                        </div>
+                    }
+                })
+                .collect();
             set_glyph_doc.set(
                 view! {
                     <Prim prim=prim/>
@@ -716,6 +752,7 @@ pub fn Editor<'a>(
                     ) }
                     <br/>
                     { prim.doc().short_text().into_owned() }
+                    { examples }
                 }
                 .into_view(),
             );
@@ -1074,6 +1111,10 @@ pub fn Editor<'a>(
         set_top_at_top(orientation);
         run(false, false);
     };
+    let on_select_locale = move |event: Event| {
+        let input: HtmlSelectElement = event.target().unwrap().dyn_into().unwrap();
+        set_locale_code(&input.value());
+    };
     set_font_name(&get_font_name());
     set_font_size(&get_font_size());
 
@@ -1149,6 +1190,16 @@ pub fn Editor<'a>(
                                 <option value="Uiua386" selected={get_font_name() == "Uiua386"}>"Uiua386"</option>
                             </select>
                         </div>
+                        <div title="The language of primitive names accepted by the formatter">
+                            "Names:"
+                            <select
+                                on:change=on_select_locale>
+                                <option value="" selected={get_locale_code().is_empty()}>"English"</option>
+                                { Locale::bundled_codes().map(|code| view! {
+                                    <option value=code selected={get_locale_code() == code}>{code}</option>
+                                }).collect::<Vec<_>>() }
+                            </select>
+                        </div>
                     </div>
                     <div id="settings-right">
                         <button
@@ -1173,7 +1224,12 @@ or \"embedpad\" to embed the editor"
                 </div>
                 <div class=editor_class>
                     <div id="code-area">
-                        <div id={glyph_doc_id} class="glyph-doc" style="display: none">
+                        <div
+                            id={glyph_doc_id}
+                            class="glyph-doc"
+                            style="display: none"
+                            on:mouseenter=on_glyph_doc_mouseenter
+                            on:mouseleave=on_glyph_doc_mouseleave>
                             { move || glyph_doc.get() }
                             <div class="glyph-doc-ctrl-click">"Shift+click for more info (Ctrl/⌘+click for new tab)"</div>
                         </div>
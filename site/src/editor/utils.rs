@@ -4,6 +4,7 @@ use std::{
     iter,
     mem::{replace, take},
     str::FromStr,
+    sync::Arc,
     time::Duration,
 };
 
@@ -13,8 +14,8 @@ use leptos::*;
 
 use uiua::{
     ast::Item, image_to_bytes, spans, value_to_gif_bytes, value_to_image, value_to_wav_bytes,
-    Compiler, DiagnosticKind, Inputs, Report, ReportFragment, ReportKind, SpanKind, SysBackend,
-    Uiua, UiuaResult, Value,
+    Compiler, DiagnosticKind, Inputs, Locale, Primitive, Report, ReportFragment, ReportKind,
+    SpanKind, SysBackend, Uiua, UiuaResult, Value,
 };
 use wasm_bindgen::JsCast;
 use web_sys::{HtmlBrElement, HtmlDivElement, HtmlStyleElement, Node};
@@ -211,6 +212,40 @@ pub fn set_font_size(size: &str) {
     update_style();
 }
 
+pub fn get_locale_code() -> String {
+    get_local_var("locale", String::new)
+}
+pub fn set_locale_code(code: &str) {
+    set_local_var("locale", code);
+}
+
+/// Get the locale whose primitive names are accepted by the formatter, if any
+pub fn get_locale() -> Option<Arc<Locale>> {
+    thread_local! {
+        static LOCALE: RefCell<Option<Arc<Locale>>> = RefCell::new(None);
+    }
+    let code = get_locale_code();
+    if code.is_empty() {
+        return None;
+    }
+    LOCALE.with(|cached| {
+        let mut cached = cached.borrow_mut();
+        if !cached.as_ref().is_some_and(|locale| locale.code() == code) {
+            *cached = Locale::bundled(&code).map(Arc::new);
+        }
+        cached.clone()
+    })
+}
+
+/// Get a primitive's name, prefixed with its localized name if a locale is active
+pub fn localized_prim_name(prim: Primitive) -> String {
+    let localized = get_locale().and_then(|locale| locale.names_of(prim).next().map(Into::into));
+    match localized {
+        Some(localized) => format!("{localized} / {}", prim.name()),
+        None => prim.name().into(),
+    }
+}
+
 pub fn get_autorun() -> bool {
     get_local_var("autorun", || true)
 }
@@ -457,11 +492,13 @@ fn set_code_cursor(id: &str, start: u32, end: u32) {
 
 fn set_code_html(id: &str, code: &str) {
     // log!("set_code_html({:?})", code);
+    element::<HtmlDivElement>(id).set_inner_html(&code_html(code));
+}
 
-    let elem = element::<HtmlDivElement>(id);
+/// Generate syntax-highlighted HTML for some code
+pub fn code_html(code: &str) -> String {
     if code.is_empty() {
-        elem.set_inner_html("<div class=\"code-line\"><br/></div>");
-        return;
+        return "<div class=\"code-line\"><br/></div>".into();
     }
 
     let mut html = "<div class=\"code-line\">".to_string();
@@ -539,7 +576,7 @@ fn set_code_html(id: &str, code: &str) {
                 }
                 html.push_str(&match kind {
                     SpanKind::Primitive(prim) => {
-                        let name = prim.name();
+                        let name = localized_prim_name(prim);
                         let mut title = format!("{}: {}", name, prim.doc().short_text());
                         if let Some(ascii) = prim.ascii() {
                             title = format!("({}) {}", ascii, title);
@@ -664,7 +701,41 @@ fn set_code_html(id: &str, code: &str) {
 
     // log!("html: {}", html);
 
-    elem.set_inner_html(&html);
+    html
+}
+
+/// Replace a character range of some code with a string
+///
+/// Returns the new code and the cursor position after the inserted text
+pub fn insert_at_cursor(code: &str, start: u32, end: u32, inserted: &str) -> (String, u32) {
+    let (start, end) = (start.min(end), start.max(end) as usize);
+    let new: String = code
+        .chars()
+        .take(start as usize)
+        .chain(inserted.chars())
+        .chain(code.chars().skip(end))
+        .collect();
+    let offset = inserted.chars().count() as u32;
+    (new, start + offset)
+}
+
+#[test]
+fn insert_at_cursor_test() {
+    for (code, start, end, inserted, expected, cursor) in [
+        ("", 0, 0, "+1 2", "+1 2", 4),
+        ("⇡5", 0, 0, "⇌", "⇌⇡5", 1),
+        ("⇡5", 2, 2, " ⇌", "⇡5 ⇌", 4),
+        ("+1 2", 1, 2, "⇡", "+⇡ 2", 2),
+        ("+1 2", 2, 1, "⇡", "+⇡ 2", 2),
+        ("×2 ⇡5", 3, 5, "⇌.[1 2 3]", "×2 ⇌.[1 2 3]", 12),
+        ("a\nb", 1, 1, "\n⇌\n", "a\n⇌\n\nb", 4),
+    ] {
+        assert_eq!(
+            insert_at_cursor(code, start, end, inserted),
+            (expected.to_string(), cursor),
+            "inserting {inserted:?} into {code:?} at {start}..{end}"
+        );
+    }
 }
 
 fn escape_html(s: &str) -> Cow<str> {
@@ -687,8 +758,10 @@ fn escape_html(s: &str) -> Cow<str> {
 }
 
 fn init_rt() -> Uiua {
+    const MEMORY_LIMIT: usize = 1 << 30;
     Uiua::with_backend(WebBackend::default())
         .with_execution_limit(Duration::from_secs_f64(get_execution_limit()))
+        .with_memory_limit(MEMORY_LIMIT)
 }
 
 fn just_values(code: &str) -> UiuaResult<Vec<Value>> {
@@ -1008,6 +1081,7 @@ pub fn progressive_strings(input: &str) -> Vec<String> {
             Item::Binding(binding) => {
                 lines.push(vec![binding.span().as_str(&inputs, |s| s.into())])
             }
+            Item::Import(import) => lines.push(vec![import.span().as_str(&inputs, |s| s.into())]),
             Item::TestScope(items) => lines.push(vec![items.span.as_str(&inputs, |s| s.into())]),
         }
     }
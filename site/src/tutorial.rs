@@ -602,8 +602,9 @@ fn TutorialTypes() -> impl IntoView {
         <p>"While complex numbers support all the same math operations as normal numbers, they are a distinct type and cannot be used in place of normal numbers."</p>
         <p>"You can convert a complex number to a normal number with "<Prim prim=Abs/>"."</p>
         <Editor example="⌵ ℂ3 4"/>
-        <p><Prim prim=Sqrt/>" only returns a complex number if it is called on a complex number. Beware of floating-point errors."</p>
-        <Editor example="√  ¯4\n√ℂ0¯4"/>
+        <p>"Math that leaves the real numbers, like taking the "<Prim prim=Sqrt/>" of a negative number, returns complex numbers. Beware of floating-point errors."</p>
+        <Editor example="√ [4 ¯4]"/>
+        <Editor example="ⁿ0.5 ¯4"/>
 
         <h2 id="characters">"Characters"</h2>
         <p>"Characters are represented as 32-bit Unicode codepoints."</p>
@@ -1434,6 +1435,19 @@ Sqr ← Ex \"Square\"
 Inc Sqr Dub 5"/>
         <p>"When you write code like this that imports several items, the formatter will automatically indent each item. Try it out!"</p>
 
+        <h2 id="tilde-imports">"Importing with "<code>"~"</code></h2>
+        <p>"Imports can also be written with "<code>"~"</code>". A "<code>"~"</code>" is followed by a file path and the names of the items to bring into scope."</p>
+        <Editor example="~ \"example.ua\" Increment Double Square\nIncrement Square Double 5"/>
+        <p>"A name can be put before the "<code>"~"</code>" to bind the module itself."</p>
+        <Editor example="Ex ~ \"example.ua\" Double\nEx \"Increment\" Double 5"/>
+        <p>"Each file is only compiled once, no matter how many times it is imported. Importing a file that is already being imported is an error."</p>
+        <p>"Items that a module imports with "<code>"~"</code>" can themselves be imported from that module. This lets a module re-export items from other modules."</p>
+
+        <h2 id="libraries">"Libraries"</h2>
+        <p>"The native interpreter can install libraries hosted in Git repositories. "<code>"uiua add <url>"</code>" clones a library into the "<code>"uiua-modules"</code>" directory and records its commit in "<code>"uiua-modules.lock"</code>"."</p>
+        <p>"Imports that are not found relative to the importing file are looked up in "<code>"uiua-modules"</code>". A library's directory resolves to its "<code>"lib.ua"</code>" file, so a library called "<code>"foo"</code>" can be imported with "<code>"~ \"foo\" Bar"</code>"."</p>
+        <p>"Running "<code>"uiua add"</code>" with no URL installs the libraries in the lockfile at their locked commits. "<code>"uiua update --modules"</code>" updates them to their latest commits."</p>
+
     }
 }
 
@@ -0,0 +1,276 @@
+//! Conversion between numbers and packed binary data
+
+use ecow::EcoVec;
+
+use crate::{grid_fmt::GridFmt, Array, Shape, Uiua, UiuaResult, Value};
+
+/// The type of a single packed field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    F32,
+    F64,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "u8" => Field::U8,
+            "i8" => Field::I8,
+            "u16" => Field::U16,
+            "i16" => Field::I16,
+            "u32" => Field::U32,
+            "i32" => Field::I32,
+            "u64" => Field::U64,
+            "i64" => Field::I64,
+            "f32" => Field::F32,
+            "f64" => Field::F64,
+            _ => return None,
+        })
+    }
+    fn width(self) -> usize {
+        match self {
+            Field::U8 | Field::I8 => 1,
+            Field::U16 | Field::I16 => 2,
+            Field::U32 | Field::I32 | Field::F32 => 4,
+            Field::U64 | Field::I64 | Field::F64 => 8,
+        }
+    }
+    fn name(self) -> &'static str {
+        match self {
+            Field::U8 => "u8",
+            Field::I8 => "i8",
+            Field::U16 => "u16",
+            Field::I16 => "i16",
+            Field::U32 => "u32",
+            Field::I32 => "i32",
+            Field::U64 => "u64",
+            Field::I64 => "i64",
+            Field::F32 => "f32",
+            Field::F64 => "f64",
+        }
+    }
+    /// The inclusive range of integers this field can hold
+    fn int_range(self) -> Option<(f64, f64)> {
+        Some(match self {
+            Field::U8 => (0.0, u8::MAX as f64),
+            Field::I8 => (i8::MIN as f64, i8::MAX as f64),
+            Field::U16 => (0.0, u16::MAX as f64),
+            Field::I16 => (i16::MIN as f64, i16::MAX as f64),
+            Field::U32 => (0.0, u32::MAX as f64),
+            Field::I32 => (i32::MIN as f64, i32::MAX as f64),
+            Field::U64 => (0.0, u64::MAX as f64),
+            Field::I64 => (i64::MIN as f64, i64::MAX as f64),
+            Field::F32 | Field::F64 => return None,
+        })
+    }
+    fn encode(self, n: f64, big_endian: bool, bytes: &mut EcoVec<u8>) {
+        macro_rules! put {
+            ($n:expr) => {
+                if big_endian {
+                    bytes.extend_from_slice(&$n.to_be_bytes())
+                } else {
+                    bytes.extend_from_slice(&$n.to_le_bytes())
+                }
+            };
+        }
+        match self {
+            Field::U8 => put!(n as u8),
+            Field::I8 => put!(n as i8),
+            Field::U16 => put!(n as u16),
+            Field::I16 => put!(n as i16),
+            Field::U32 => put!(n as u32),
+            Field::I32 => put!(n as i32),
+            Field::U64 => put!(n as u64),
+            Field::I64 => put!(n as i64),
+            Field::F32 => put!(n as f32),
+            Field::F64 => put!(n),
+        }
+    }
+    fn decode(self, bytes: &[u8], big_endian: bool) -> f64 {
+        macro_rules! get {
+            ($ty:ty) => {{
+                let bytes = bytes.try_into().unwrap();
+                (if big_endian {
+                    <$ty>::from_be_bytes(bytes)
+                } else {
+                    <$ty>::from_le_bytes(bytes)
+                }) as f64
+            }};
+        }
+        match self {
+            Field::U8 => get!(u8),
+            Field::I8 => get!(i8),
+            Field::U16 => get!(u16),
+            Field::I16 => get!(i16),
+            Field::U32 => get!(u32),
+            Field::I32 => get!(i32),
+            Field::U64 => get!(u64),
+            Field::I64 => get!(i64),
+            Field::F32 => get!(f32),
+            Field::F64 => get!(f64),
+        }
+    }
+}
+
+/// A parsed format spec
+struct Format {
+    big_endian: bool,
+    fields: Vec<Field>,
+}
+
+impl Format {
+    fn parse(spec: &Value, env: &Uiua) -> UiuaResult<Self> {
+        let spec = spec.as_string(env, "Binary format must be a string")?;
+        let (big_endian, rest) = if let Some(rest) = spec.strip_prefix('>') {
+            (true, rest)
+        } else {
+            (false, spec.strip_prefix('<').unwrap_or(&spec))
+        };
+        let mut fields = Vec::new();
+        for name in rest.split_whitespace() {
+            fields.push(Field::from_name(name).ok_or_else(|| {
+                env.error(format!(
+                    "Unknown binary field type {name:?}. \
+                    Valid types are u8, i8, u16, i16, u32, i32, u64, i64, f32, and f64"
+                ))
+            })?);
+        }
+        if fields.is_empty() {
+            return Err(env.error("Binary format must have at least one field"));
+        }
+        Ok(Format { big_endian, fields })
+    }
+    fn record_width(&self) -> usize {
+        self.fields.iter().map(|f| f.width()).sum()
+    }
+}
+
+impl Value {
+    /// Pack numbers into bytes according to a format spec
+    pub(crate) fn binary(&self, nums: &Self, env: &Uiua) -> UiuaResult<Value> {
+        let format = Format::parse(self, env)?;
+        let nums = match nums {
+            Value::Num(arr) => arr.clone(),
+            #[cfg(feature = "bytes")]
+            Value::Byte(arr) => arr.convert_ref(),
+            Value::Int(arr) => arr.convert_ref(),
+            val => {
+                return Err(env.error(format!(
+                    "Cannot encode {} as binary",
+                    val.type_name_plural()
+                )))
+            }
+        };
+        let field_count = format.fields.len();
+        let mut shape = nums.shape.clone();
+        if field_count == 1 {
+            match shape.last_mut() {
+                Some(last) => *last *= format.record_width(),
+                None => shape.push(format.record_width()),
+            }
+        } else {
+            match shape.last_mut() {
+                Some(last) if *last == field_count => *last = format.record_width(),
+                _ => {
+                    return Err(env.error(format!(
+                        "Binary format has {field_count} fields, \
+                        but the array's last axis is not that long. Its shape is {}",
+                        nums.shape
+                    )))
+                }
+            }
+        }
+        let mut bytes = EcoVec::with_capacity(shape.iter().product());
+        for (i, &n) in nums.data.iter().enumerate() {
+            let field = format.fields[i % field_count];
+            if let Some((min, max)) = field.int_range() {
+                if n.fract() != 0.0 || !(min..=max).contains(&n) {
+                    return Err(env.error(format!(
+                        "{} cannot be encoded as {}",
+                        n.grid_string(false),
+                        field.name()
+                    )));
+                }
+            }
+            field.encode(n, format.big_endian, &mut bytes);
+        }
+        Ok(Array::<u8>::new(shape, bytes).into())
+    }
+    /// Unpack bytes into numbers according to a format spec
+    pub(crate) fn inv_binary(&self, bytes: &Self, env: &Uiua) -> UiuaResult<Value> {
+        let format = Format::parse(self, env)?;
+        let bytes = match bytes {
+            #[cfg(feature = "bytes")]
+            Value::Byte(arr) => arr.clone(),
+            Value::Num(arr) => {
+                let mut bytes = EcoVec::with_capacity(arr.data.len());
+                for &n in &arr.data {
+                    if n.fract() != 0.0 || !(0.0..256.0).contains(&n) {
+                        return Err(env.error(format!(
+                            "Binary data must be bytes, but it contains {}",
+                            n.grid_string(false)
+                        )));
+                    }
+                    bytes.push(n as u8);
+                }
+                Array::new(arr.shape.clone(), bytes)
+            }
+            Value::Int(arr) => {
+                let mut bytes = EcoVec::with_capacity(arr.data.len());
+                for &n in &arr.data {
+                    let Ok(n) = u8::try_from(n) else {
+                        return Err(env.error(format!(
+                            "Binary data must be bytes, but it contains {}",
+                            n.grid_string(false)
+                        )));
+                    };
+                    bytes.push(n);
+                }
+                Array::new(arr.shape.clone(), bytes)
+            }
+            val => {
+                return Err(env.error(format!(
+                    "Cannot decode {} as binary",
+                    val.type_name_plural()
+                )))
+            }
+        };
+        if bytes.rank() == 0 {
+            return Err(env.error("Binary data must be at least rank 1"));
+        }
+        let record_width = format.record_width();
+        let len = *bytes.shape.last().unwrap();
+        if len % record_width != 0 {
+            return Err(env.error(format!(
+                "Binary data's last axis has length {len}, \
+                which is not a multiple of the format's width of {record_width}"
+            )));
+        }
+        let mut shape = Shape::from(&bytes.shape[..bytes.rank() - 1]);
+        // A last axis of exactly one record is unpacked into that record, which inverts packing
+        if format.fields.len() == 1 || len != record_width {
+            shape.push(len / record_width);
+        }
+        if format.fields.len() > 1 {
+            shape.push(format.fields.len());
+        }
+        let mut data = EcoVec::with_capacity(shape.iter().product());
+        for record in bytes.data.chunks_exact(record_width) {
+            let mut offset = 0;
+            for field in &format.fields {
+                let width = field.width();
+                data.push(field.decode(&record[offset..][..width], format.big_endian));
+                offset += width;
+            }
+        }
+        Ok(Array::<f64>::new(shape, data).into())
+    }
+}
@@ -0,0 +1,156 @@
+//! Conversion between values and CSV
+
+use std::mem::take;
+
+use ecow::EcoVec;
+
+use crate::{Array, Boxed, Uiua, UiuaResult, Value};
+
+impl Value {
+    /// Encode a rank-2 array as a CSV string
+    pub(crate) fn csv(&self, env: &Uiua) -> UiuaResult<String> {
+        // Strings are single cells, so character arrays have an extra axis
+        let string_axis = matches!(self, Value::Char(_)) as usize;
+        let rows: Vec<Value> = match self.rank() - string_axis.min(self.rank()) {
+            0 => vec![Array::from(Boxed(self.clone())).into()],
+            1 => vec![self.clone()],
+            2 => self.rows().collect(),
+            _ => {
+                return Err(env.error(format!(
+                    "Cannot encode a rank-{} {} array as CSV. \
+                    It must be a row or table of cells",
+                    self.rank(),
+                    self.type_name()
+                )))
+            }
+        };
+        let mut csv = String::new();
+        for row in rows {
+            for (i, cell) in row.into_rows().enumerate() {
+                if i > 0 {
+                    csv.push(',');
+                }
+                let cell = csv_cell(cell.unboxed(), env)?;
+                if cell.contains([',', '"', '\n', '\r']) {
+                    csv.push('"');
+                    csv.push_str(&cell.replace('"', "\"\""));
+                    csv.push('"');
+                } else {
+                    csv.push_str(&cell);
+                }
+            }
+            csv.push('\n');
+        }
+        Ok(csv)
+    }
+    /// Decode a CSV string into a rank-2 array of boxed strings
+    pub(crate) fn inv_csv(&self, env: &Uiua) -> UiuaResult<Self> {
+        let records = self.csv_records(env)?;
+        let width = records.iter().map(Vec::len).max().unwrap_or(0);
+        Ok(records_array(records, width))
+    }
+    /// Decode a CSV string into its header row and a rank-2 array of the remaining records
+    pub(crate) fn inv_csv_header(&self, env: &Uiua) -> UiuaResult<(Self, Self)> {
+        let mut records = self.csv_records(env)?;
+        if records.is_empty() {
+            return Err(env.error("CSV has no header row"));
+        }
+        let width = records.iter().map(Vec::len).max().unwrap_or(0);
+        let mut header = records.remove(0);
+        header.resize(width, String::new());
+        let header = header
+            .into_iter()
+            .map(|field| Boxed(field.into()))
+            .collect();
+        Ok((header, records_array(records, width)))
+    }
+    fn csv_records(&self, env: &Uiua) -> UiuaResult<Vec<Vec<String>>> {
+        let csv = self.as_string(env, "Argument to inverse csv must be a string")?;
+        parse_csv(&csv).map_err(|e| env.error(e))
+    }
+}
+
+fn records_array(records: Vec<Vec<String>>, width: usize) -> Value {
+    let height = records.len();
+    let mut data = EcoVec::with_capacity(height * width);
+    for mut record in records {
+        // Short records are padded with empty fields
+        record.resize(width, String::new());
+        data.extend(record.into_iter().map(|field| Boxed(field.into())));
+    }
+    Array::new([height, width], data).into()
+}
+
+fn csv_cell(cell: Value, env: &Uiua) -> UiuaResult<String> {
+    Ok(match cell {
+        Value::Char(arr) if arr.rank() <= 1 => arr.data.iter().collect(),
+        Value::Num(arr) if arr.rank() == 0 => arr.data[0].to_string(),
+        #[cfg(feature = "bytes")]
+        Value::Byte(arr) if arr.rank() == 0 => arr.data[0].to_string(),
+        cell => {
+            return Err(env.error(format!(
+                "CSV cells must be strings or scalar numbers, but one is a {} array",
+                cell.type_name()
+            )))
+        }
+    })
+}
+
+fn parse_csv(csv: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut chars = csv.chars().peekable();
+    let mut line = 1;
+    // Whether the current record has any content yet
+    let mut started = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if field.is_empty() => {
+                started = true;
+                let start_line = line;
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => {
+                            line += (c == '\n') as usize;
+                            field.push(c);
+                        }
+                        None => return Err(format!("Unclosed quote in CSV on line {start_line}")),
+                    }
+                }
+                if !matches!(chars.peek(), None | Some(',' | '\n' | '\r')) {
+                    return Err(format!(
+                        "Unexpected character after closing quote in CSV on line {line}"
+                    ));
+                }
+            }
+            ',' => {
+                started = true;
+                record.push(take(&mut field));
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                line += 1;
+                if started {
+                    record.push(take(&mut field));
+                    records.push(take(&mut record));
+                }
+                started = false;
+            }
+            c => {
+                started = true;
+                field.push(c);
+            }
+        }
+    }
+    if started {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
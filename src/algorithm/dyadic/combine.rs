@@ -1,9 +1,7 @@
 use std::{cmp::Ordering, mem::take};
 
-#[cfg(feature = "bytes")]
-use crate::algorithm::op2_bytes_retry_fill;
 use crate::{
-    algorithm::{max_shape, FillContext},
+    algorithm::{max_shape, op2_bytes_retry_fill, FillContext},
     cowslice::cowslice,
     Array, ArrayValue, FormatShape, Uiua, UiuaResult, Value,
 };
@@ -107,7 +105,7 @@ impl Value {
         Ok(match (self, other) {
             (Value::Num(a), Value::Num(b)) => a.join_impl(b, ctx)?.into(),
             #[cfg(feature = "bytes")]
-            (Value::Byte(a), Value::Byte(b)) => op2_bytes_retry_fill::<_, C>(
+            (Value::Byte(a), Value::Byte(b)) => op2_bytes_retry_fill::<_, _, C>(
                 a,
                 b,
                 ctx,
@@ -126,6 +124,15 @@ impl Value {
             (Value::Complex(a), Value::Byte(b)) => a.join_impl(b.convert(), ctx)?.into(),
             #[cfg(feature = "bytes")]
             (Value::Byte(a), Value::Complex(b)) => a.convert().join_impl(b, ctx)?.into(),
+            (Value::Int(a), Value::Int(b)) => op2_bytes_retry_fill::<_, _, C>(
+                a,
+                b,
+                ctx,
+                |a, b| Ok(a.join_impl(b, ctx)?.into()),
+                |a, b| Ok(a.join_impl(b, ctx)?.into()),
+            )?,
+            (Value::Int(a), b) => Value::Num(a.convert()).join_impl_impl(b, ctx)?,
+            (a, Value::Int(b)) => a.join_impl_impl(Value::Num(b.convert()), ctx)?,
             (a, b) => a.bin_coerce_to_boxes(
                 b,
                 ctx,
@@ -150,7 +157,7 @@ impl Value {
             (Value::Num(a), Value::Num(b)) => a.append(b, ctx)?,
             #[cfg(feature = "bytes")]
             (Value::Byte(a), Value::Byte(b)) => {
-                *self = op2_bytes_retry_fill::<_, C>(
+                *self = op2_bytes_retry_fill::<_, _, C>(
                     a.clone(),
                     b,
                     ctx,
@@ -188,6 +195,26 @@ impl Value {
                 a.append(b, ctx)?;
                 *self = a.into();
             }
+            (Value::Int(a), Value::Int(b)) => {
+                *self = op2_bytes_retry_fill::<_, _, C>(
+                    a.clone(),
+                    b,
+                    ctx,
+                    |mut a, b| {
+                        a.append(b, ctx)?;
+                        Ok(a.into())
+                    },
+                    |mut a, b| {
+                        a.append(b, ctx)?;
+                        Ok(a.into())
+                    },
+                )?
+            }
+            (Value::Int(a), b) => {
+                *self = Value::Num(a.convert_ref());
+                self.append_impl(b, ctx)?;
+            }
+            (_, Value::Int(b)) => self.append_impl(Value::Num(b.convert()), ctx)?,
             (a, b) => a.bin_coerce_to_boxes_mut(
                 b,
                 ctx,
@@ -208,6 +235,9 @@ impl Value {
             Value::Byte(a) => a
                 .unjoin(a_rank, b_rank, env)
                 .map(|(a, b)| (a.into(), b.into())),
+            Value::Int(a) => a
+                .unjoin(a_rank, b_rank, env)
+                .map(|(a, b)| (a.into(), b.into())),
             Value::Complex(a) => a
                 .unjoin(a_rank, b_rank, env)
                 .map(|(a, b)| (a.into(), b.into())),
@@ -415,7 +445,7 @@ impl Value {
             (Value::Num(a), Value::Num(b)) => a.couple_impl(b, ctx)?,
             #[cfg(feature = "bytes")]
             (Value::Byte(a), Value::Byte(b)) => {
-                *self = op2_bytes_retry_fill::<_, C>(
+                *self = op2_bytes_retry_fill::<_, _, C>(
                     a.clone(),
                     b,
                     ctx,
@@ -454,6 +484,26 @@ impl Value {
                 a.couple_impl(b, ctx)?;
                 *self = a.into();
             }
+            (Value::Int(a), Value::Int(b)) => {
+                *self = op2_bytes_retry_fill::<_, _, C>(
+                    a.clone(),
+                    b,
+                    ctx,
+                    |mut a, b| {
+                        a.couple_impl(b, ctx)?;
+                        Ok(a.into())
+                    },
+                    |mut a, b| {
+                        a.couple_impl(b, ctx)?;
+                        Ok(a.into())
+                    },
+                )?
+            }
+            (Value::Int(a), b) => {
+                *self = Value::Num(a.convert_ref());
+                self.couple_impl_impl(b, ctx)?;
+            }
+            (_, Value::Int(b)) => self.couple_impl_impl(Value::Num(b.convert()), ctx)?,
             (a, b) => a.bin_coerce_to_boxes_mut(
                 b,
                 ctx,
@@ -469,6 +519,7 @@ impl Value {
             Value::Num(a) => a.uncouple(env).map(|(a, b)| (a.into(), b.into())),
             #[cfg(feature = "bytes")]
             Value::Byte(a) => a.uncouple(env).map(|(a, b)| (a.into(), b.into())),
+            Value::Int(a) => a.uncouple(env).map(|(a, b)| (a.into(), b.into())),
             Value::Complex(a) => a.uncouple(env).map(|(a, b)| (a.into(), b.into())),
             Value::Char(a) => a.uncouple(env).map(|(a, b)| (a.into(), b.into())),
             Value::Box(a) => a.uncouple(env).map(|(a, b)| (a.into(), b.into())),
@@ -7,8 +7,8 @@ use std::{
     borrow::Cow,
     cmp::Ordering,
     collections::{hash_map::DefaultHasher, HashMap, HashSet},
-    hash::{Hash, Hasher},
-    iter::{once, repeat},
+    hash::Hasher,
+    iter::{once, repeat, repeat_n},
     mem::take,
 };
 
@@ -22,7 +22,7 @@ use crate::{
     Shape, Uiua, UiuaResult,
 };
 
-use super::{ArrayCmpSlice, FillContext};
+use super::{ArrayCmpSlice, FillContext, RowLookup};
 
 impl Value {
     pub(crate) fn bin_coerce_to_boxes<T, C: FillContext, E: ToString>(
@@ -146,6 +146,7 @@ impl Value {
                 Value::Num(a) => a.reshape_scalar(n),
                 #[cfg(feature = "bytes")]
                 Value::Byte(a) => a.reshape_scalar(n),
+                Value::Int(a) => a.reshape_scalar(n),
                 Value::Complex(a) => a.reshape_scalar(n),
                 Value::Char(a) => a.reshape_scalar(n),
                 Value::Box(a) => a.reshape_scalar(n),
@@ -169,6 +170,16 @@ impl Value {
                         a.reshape(&target_shape, env)
                     }
                 }
+                Value::Int(a) => {
+                    if env.num_fill().is_ok() && env.int_fill().is_err() {
+                        let mut arr: Array<f64> = a.convert_ref();
+                        arr.reshape(&target_shape, env)?;
+                        *self = arr.into();
+                        Ok(())
+                    } else {
+                        a.reshape(&target_shape, env)
+                    }
+                }
                 Value::Complex(a) => a.reshape(&target_shape, env),
                 Value::Char(a) => a.reshape(&target_shape, env),
                 Value::Box(a) => a.reshape(&target_shape, env),
@@ -411,6 +422,7 @@ impl Value {
                 Value::Num(a) => a.scalar_keep(counts[0]).into(),
                 #[cfg(feature = "bytes")]
                 Value::Byte(a) => a.scalar_keep(counts[0]).into(),
+                Value::Int(a) => a.scalar_keep(counts[0]).into(),
                 Value::Complex(a) => a.scalar_keep(counts[0]).into(),
                 Value::Char(a) => a.scalar_keep(counts[0]).into(),
                 Value::Box(a) => a.scalar_keep(counts[0]).into(),
@@ -420,6 +432,7 @@ impl Value {
                 Value::Num(a) => a.list_keep(&counts, env)?.into(),
                 #[cfg(feature = "bytes")]
                 Value::Byte(a) => a.list_keep(&counts, env)?.into(),
+                Value::Int(a) => a.list_keep(&counts, env)?.into(),
                 Value::Complex(a) => a.list_keep(&counts, env)?.into(),
                 Value::Char(a) => a.list_keep(&counts, env)?.into(),
                 Value::Box(a) => a.list_keep(&counts, env)?.into(),
@@ -442,6 +455,7 @@ impl Value {
             |a, b| a.unkeep(&counts, b, env).map(Into::into),
             |a, b| a.unkeep(&counts, b, env).map(Into::into),
             |a, b| a.unkeep(&counts, b, env).map(Into::into),
+            |a, b| a.unkeep(&counts, b, env).map(Into::into),
             |a, b| env.error(format!("Cannot unkeep {a} array with {b} array")),
         )
     }
@@ -628,10 +642,16 @@ impl Value {
                 rotated = bytes.convert_ref::<f64>().into();
             }
         }
+        if env.scalar_fill::<f64>().is_ok() && env.int_fill().is_err() {
+            if let Value::Int(ints) = &rotated {
+                rotated = ints.convert_ref::<f64>().into();
+            }
+        }
         match &mut rotated {
             Value::Num(a) => a.rotate(&by, env)?,
             #[cfg(feature = "bytes")]
             Value::Byte(a) => a.rotate(&by, env)?,
+            Value::Int(a) => a.rotate(&by, env)?,
             Value::Complex(a) => a.rotate(&by, env)?,
             Value::Char(a) => a.rotate(&by, env)?,
             Value::Box(a) => a.rotate(&by, env)?,
@@ -652,10 +672,16 @@ impl Value {
                 rotated = bytes.convert_ref::<f64>().into();
             }
         }
+        if env.scalar_fill::<f64>().is_ok() && env.int_fill().is_err() {
+            if let Value::Int(ints) = &rotated {
+                rotated = ints.convert_ref::<f64>().into();
+            }
+        }
         match &mut rotated {
             Value::Num(a) => a.rotate_depth(by, b_depth, a_depth, env)?,
             #[cfg(feature = "bytes")]
             Value::Byte(a) => a.rotate_depth(by, b_depth, a_depth, env)?,
+            Value::Int(a) => a.rotate_depth(by, b_depth, a_depth, env)?,
             Value::Complex(a) => a.rotate_depth(by, b_depth, a_depth, env)?,
             Value::Char(a) => a.rotate_depth(by, b_depth, a_depth, env)?,
             Value::Box(a) => a.rotate_depth(by, b_depth, a_depth, env)?,
@@ -759,12 +785,36 @@ fn fill_shift<T: Clone>(by: &[isize], shape: &[usize], data: &mut [T], fill: T)
 
 impl Value {
     /// Use this array to `windows` another
+    ///
+    /// If this array has rank 2, its rows are the window size and the stride.
     pub fn windows(&self, from: &Self, env: &Uiua) -> UiuaResult<Self> {
+        if self.rank() == 2 {
+            if self.row_count() != 2 {
+                return Err(env.error(format!(
+                    "A rank 2 window size must have 2 rows for the size and stride, \
+                    but its shape is {}",
+                    self.shape()
+                )));
+            }
+            let (size, stride) = (self.row(0), self.row(1));
+            let size_spec = size.as_ints(env, "Window size must be a list of integers")?;
+            let strides = stride.as_nats(env, "Window stride must be a list of natural numbers")?;
+            return Ok(match from {
+                Value::Num(a) => a.strided_windows(&size_spec, &strides, env)?.into(),
+                #[cfg(feature = "bytes")]
+                Value::Byte(a) => a.strided_windows(&size_spec, &strides, env)?.into(),
+                Value::Int(a) => a.strided_windows(&size_spec, &strides, env)?.into(),
+                Value::Complex(a) => a.strided_windows(&size_spec, &strides, env)?.into(),
+                Value::Char(a) => a.strided_windows(&size_spec, &strides, env)?.into(),
+                Value::Box(a) => a.strided_windows(&size_spec, &strides, env)?.into(),
+            });
+        }
         let size_spec = self.as_ints(env, "Window size must be a list of integers")?;
         Ok(match from {
             Value::Num(a) => a.windows(&size_spec, env)?.into(),
             #[cfg(feature = "bytes")]
             Value::Byte(a) => a.windows(&size_spec, env)?.into(),
+            Value::Int(a) => a.windows(&size_spec, env)?.into(),
             Value::Complex(a) => a.windows(&size_spec, env)?.into(),
             Value::Char(a) => a.windows(&size_spec, env)?.into(),
             Value::Box(a) => a.windows(&size_spec, env)?.into(),
@@ -775,9 +825,27 @@ impl Value {
 impl<T: ArrayValue> Array<T> {
     /// Get the `windows` of this array
     pub fn windows(&self, isize_spec: &[isize], env: &Uiua) -> UiuaResult<Self> {
+        self.strided_windows(isize_spec, &vec![1; isize_spec.len()], env)
+    }
+    /// Get the `windows` of this array, where each axis's windows start `strides` items apart
+    pub fn strided_windows(
+        &self,
+        isize_spec: &[isize],
+        strides: &[usize],
+        env: &Uiua,
+    ) -> UiuaResult<Self> {
         if isize_spec.iter().any(|&s| s == 0) {
             return Err(env.error("Window size cannot be zero"));
         }
+        if strides.len() != isize_spec.len() {
+            return Err(env.error(format!(
+                "Window size {isize_spec:?} and stride {strides:?} \
+                must have the same number of axes"
+            )));
+        }
+        if strides.contains(&0) {
+            return Err(env.error("Window stride cannot be zero"));
+        }
         if isize_spec.len() > self.shape.len() {
             return Err(env.error(format!(
                 "Window size {isize_spec:?} has too many axes for shape {}",
@@ -799,7 +867,10 @@ impl<T: ArrayValue> Array<T> {
         }
         // Determine the shape of the windows array
         let mut new_shape = Shape::with_capacity(self.shape.len() + size_spec.len());
-        new_shape.extend(self.shape.iter().zip(&size_spec).map(|(a, b)| a + 1 - *b));
+        new_shape.extend(
+            (self.shape.iter().zip(&size_spec).zip(strides))
+                .map(|((a, b), s)| (a + 1).saturating_sub(*b).div_ceil(*s)),
+        );
         new_shape.extend_from_slice(&size_spec);
         new_shape.extend_from_slice(&self.shape[size_spec.len()..]);
         // Check if the window size is too large
@@ -808,12 +879,21 @@ impl<T: ArrayValue> Array<T> {
                 return Ok(Self::new(new_shape, CowSlice::new()));
             }
         }
+        // Non-overlapping windows along the first axis are just a reshape
+        if let ([size], [stride]) = (size_spec.as_slice(), strides) {
+            if size == stride {
+                let len = new_shape[0] * size * self.row_len();
+                return Ok(Array::new(new_shape, self.data.slice(..len)));
+            }
+        }
         // Make a new window shape with the same rank as the windowed array
         let mut true_size: Vec<usize> = Vec::with_capacity(self.shape.len());
         true_size.extend(size_spec);
         if true_size.len() < self.shape.len() {
             true_size.extend(&self.shape[true_size.len()..]);
         }
+        let mut true_strides = strides.to_vec();
+        true_strides.resize(self.shape.len(), 1);
 
         let mut dst = EcoVec::from_elem(self.data[0].clone(), new_shape.iter().product());
         let dst_slice = dst.make_mut();
@@ -849,10 +929,10 @@ impl<T: ArrayValue> Array<T> {
             }
             // Go to the next corner
             for i in (0..corner.len()).rev() {
-                if corner[i] == self.shape[i] - true_size[i] {
+                if corner[i] + true_strides[i] > self.shape[i] - true_size[i] {
                     corner[i] = 0;
                 } else {
-                    corner[i] += 1;
+                    corner[i] += true_strides[i];
                     continue 'windows;
                 }
             }
@@ -861,6 +941,81 @@ impl<T: ArrayValue> Array<T> {
     }
 }
 
+impl Value {
+    /// Use this array as a window size to reconstruct an array from its `windows`
+    pub fn unwindows(&self, windows: Self, env: &Uiua) -> UiuaResult<Self> {
+        let size_spec = self.as_ints(env, "Window size must be a list of integers")?;
+        Ok(match windows {
+            Value::Num(a) => a.unwindows(&size_spec, env)?.into(),
+            #[cfg(feature = "bytes")]
+            Value::Byte(a) => a.unwindows(&size_spec, env)?.into(),
+            Value::Int(a) => a.unwindows(&size_spec, env)?.into(),
+            Value::Complex(a) => a.unwindows(&size_spec, env)?.into(),
+            Value::Char(a) => a.unwindows(&size_spec, env)?.into(),
+            Value::Box(a) => a.unwindows(&size_spec, env)?.into(),
+        })
+    }
+}
+
+impl<T: ArrayValue> Array<T> {
+    /// Reconstruct the array that these `windows` were taken from
+    ///
+    /// Each item comes from the last window that starts at or before it, so the result is
+    /// the first item of each window followed by the rest of the last window.
+    pub fn unwindows(&self, isize_spec: &[isize], env: &Uiua) -> UiuaResult<Self> {
+        let axes = isize_spec.len();
+        if self.rank() < 2 * axes {
+            return Err(env.error(format!(
+                "Cannot undo windows of size {isize_spec:?} on an array of shape {}",
+                self.shape()
+            )));
+        }
+        let counts = &self.shape[..axes];
+        let sizes = &self.shape[axes..2 * axes];
+        for (&spec, &size) in isize_spec.iter().zip(sizes) {
+            if spec >= 0 && spec as usize != size {
+                return Err(env.error(format!(
+                    "Cannot undo windows of size {spec} \
+                    because the windows now have size {size}"
+                )));
+            }
+        }
+        if counts.contains(&0) {
+            return Err(env.error("Cannot undo windows when there are no windows"));
+        }
+        let cell_shape = &self.shape[2 * axes..];
+        let cell_len: usize = cell_shape.iter().product();
+        let mut shape = Shape::with_capacity(self.rank() - axes);
+        shape.extend(counts.iter().zip(sizes).map(|(c, s)| c + s - 1));
+        shape.extend_from_slice(cell_shape);
+        let mut data = EcoVec::with_capacity(shape.iter().product());
+        let mut index = vec![0; axes];
+        if shape[..axes].iter().all(|&len| len > 0) {
+            'items: loop {
+                // Find the item in the last window that contains it
+                let mut src = 0;
+                for (&i, &count) in index.iter().zip(counts) {
+                    src = src * count + i.min(count - 1);
+                }
+                for ((&i, &count), &size) in index.iter().zip(counts).zip(sizes) {
+                    src = src * size + (i - i.min(count - 1));
+                }
+                data.extend_from_slice(&self.data[src * cell_len..][..cell_len]);
+                // Go to the next item
+                for i in (0..axes).rev() {
+                    index[i] += 1;
+                    if index[i] < shape[i] {
+                        continue 'items;
+                    }
+                    index[i] = 0;
+                }
+                break;
+            }
+        }
+        Ok(Array::new(shape, data))
+    }
+}
+
 impl Value {
     /// Try to `find` this value in another
     pub fn find(&self, searched: &Self, env: &Uiua) -> UiuaResult<Self> {
@@ -871,6 +1026,7 @@ impl Value {
             |a, b| a.find(b, env).map(Into::into),
             |a, b| a.find(b, env).map(Into::into),
             |a, b| a.find(b, env).map(Into::into),
+            |a, b| a.find(b, env).map(Into::into),
             |a, b| {
                 env.error(format!(
                     "Cannot find {} in {} array",
@@ -924,6 +1080,18 @@ impl<T: ArrayValue> Array<T> {
             .map(|(s, f)| s + 1 - f)
             .collect();
 
+        // Long lists are found by comparing rolling hashes of windows instead of every element
+        if searched.rank() == 1
+            && searched_for.rank() == 1
+            && searched_for.row_count() >= ROLLING_FIND_MIN_LEN
+        {
+            let data = rolling_find(&searched_for.data, &searched.data);
+            let mut arr = Array::new(temp_output_shape, data);
+            arr.fill_to_shape(&searched.shape, 0);
+            arr.meta_mut().flags.set(ArrayFlags::BOOLEAN, true);
+            return Ok(arr);
+        }
+
         let mut data = EcoVec::from_elem(0, temp_output_shape.iter().product());
         let data_slice = data.make_mut();
         let mut corner = vec![0; searched.shape.len()];
@@ -996,6 +1164,142 @@ impl<T: ArrayValue> Array<T> {
     }
 }
 
+/// The length of a searched-for list at which `find` uses rolling hashes
+const ROLLING_FIND_MIN_LEN: usize = 16;
+
+/// Mark the start of every window of a list that matches another list
+///
+/// Each window's hash is computed from the previous one's,
+/// so elements are only compared when the hashes match.
+fn rolling_find<T: ArrayValue>(searched_for: &[T], searched: &[T]) -> EcoVec<u8> {
+    const BASE: u64 = 0x100000001b3;
+    let hash = |elem: &T| {
+        let mut hasher = DefaultHasher::new();
+        elem.array_hash(&mut hasher);
+        hasher.finish()
+    };
+    let combine = |acc: u64, h: u64| acc.wrapping_mul(BASE).wrapping_add(h);
+    let len = searched_for.len();
+    if searched.len() < len {
+        return EcoVec::new();
+    }
+    let hashes: Vec<u64> = searched.iter().map(hash).collect();
+    let target = searched_for.iter().map(hash).fold(0, combine);
+    // The factor of the element leaving the window
+    let leaving = (1..len).fold(1u64, |acc, _| acc.wrapping_mul(BASE));
+    let mut curr = hashes[..len].iter().copied().fold(0, combine);
+    let mut data = EcoVec::with_capacity(searched.len() + 1 - len);
+    for start in 0..=searched.len() - len {
+        if start > 0 {
+            let prev = curr.wrapping_sub(hashes[start - 1].wrapping_mul(leaving));
+            curr = combine(prev, hashes[start + len - 1]);
+        }
+        let found = curr == target
+            && ArrayCmpSlice(&searched[start..][..len]) == ArrayCmpSlice(searched_for);
+        data.push(found as u8);
+    }
+    data
+}
+
+impl Value {
+    /// Mark the occurrences of this value in another with their indices
+    pub fn mask(&self, searched: &Self, env: &Uiua) -> UiuaResult<Self> {
+        self.generic_bin_ref(
+            searched,
+            |a, b| a.mask(b, env).map(Into::into),
+            |a, b| a.mask(b, env).map(Into::into),
+            |a, b| a.mask(b, env).map(Into::into),
+            |a, b| a.mask(b, env).map(Into::into),
+            |a, b| a.mask(b, env).map(Into::into),
+            |a, b| a.mask(b, env).map(Into::into),
+            |a, b| {
+                env.error(format!(
+                    "Cannot mask {} in {} array",
+                    a.type_name(),
+                    b.type_name()
+                ))
+            },
+        )
+    }
+}
+
+impl<T: ArrayValue> Array<T> {
+    /// Mark the occurrences of this array in another with their indices
+    ///
+    /// Occurrences are numbered from 1 in row-major order of their corners.
+    /// An occurrence that would overlap an earlier one is not marked.
+    pub fn mask(&self, searched: &Self, env: &Uiua) -> UiuaResult<Array<f64>> {
+        let searched_for = self;
+        if searched_for.rank() > searched.rank() {
+            return Err(env.error(format!(
+                "Cannot mask rank {} array in rank {} array",
+                searched_for.rank(),
+                searched.rank()
+            )));
+        }
+        let mut data = EcoVec::from_elem(0.0, searched.element_count());
+
+        // Pad the shape of the searched-for array
+        let mut searched_for_shape = searched_for.shape.clone();
+        while searched_for_shape.len() < searched.shape.len() {
+            searched_for_shape.insert(0, 1);
+        }
+        if searched_for.element_count() == 0
+            || (searched_for_shape.iter())
+                .zip(&searched.shape)
+                .any(|(f, s)| f > s)
+        {
+            return Ok(Array::new(searched.shape.clone(), data));
+        }
+
+        // Precompute the offset of each searched-for item from a window's corner
+        let rank = searched.rank();
+        let mut strides = vec![1; rank];
+        for i in (0..rank.saturating_sub(1)).rev() {
+            strides[i] = strides[i + 1] * searched.shape[i + 1];
+        }
+        let offsets: Vec<usize> = (0..searched_for.element_count())
+            .map(|mut i| {
+                let mut offset = 0;
+                for d in (0..rank).rev() {
+                    offset += (i % searched_for_shape[d]) * strides[d];
+                    i /= searched_for_shape[d];
+                }
+                offset
+            })
+            .collect();
+        let corner_shape: Vec<usize> = (searched.shape.iter())
+            .zip(&searched_for_shape)
+            .map(|(s, f)| s + 1 - f)
+            .collect();
+
+        let data_slice = data.make_mut();
+        let mut count = 0.0;
+        for c in 0..corner_shape.iter().product() {
+            let mut rem = c;
+            let mut start = 0;
+            for d in (0..rank).rev() {
+                start += (rem % corner_shape[d]) * strides[d];
+                rem /= corner_shape[d];
+            }
+            let matches = offsets
+                .iter()
+                .zip(&searched_for.data)
+                .all(|(&offset, item)| {
+                    data_slice[start + offset] == 0.0
+                        && searched.data[start + offset].array_eq(item)
+                });
+            if matches {
+                count += 1.0;
+                for &offset in &offsets {
+                    data_slice[start + offset] = count;
+                }
+            }
+        }
+        Ok(Array::new(searched.shape.clone(), data))
+    }
+}
+
 impl Value {
     /// Check which rows of this value are `member`s of another
     pub fn member(&self, of: &Self, env: &Uiua) -> UiuaResult<Self> {
@@ -1006,6 +1310,7 @@ impl Value {
             |a, b| a.member(b, env).map(Into::into),
             |a, b| a.member(b, env).map(Into::into),
             |a, b| a.member(b, env).map(Into::into),
+            |a, b| a.member(b, env).map(Into::into),
             |a, b| {
                 env.error(format!(
                     "Cannot look for members of {} array in {} array",
@@ -1023,17 +1328,22 @@ impl<T: ArrayValue> Array<T> {
         let elems = self;
         let mut arr = match elems.rank().cmp(&of.rank()) {
             Ordering::Equal => {
-                let mut result_data = EcoVec::with_capacity(elems.row_count());
-                let mut members = HashSet::with_capacity(of.row_count());
-                for of in of.row_slices() {
-                    members.insert(ArrayCmpSlice(of));
-                }
-                for elem in elems.row_slices() {
-                    result_data.push(members.contains(&ArrayCmpSlice(elem)) as u8);
-                }
+                let members = RowLookup::new(of, elems.row_count());
+                let result_data: EcoVec<u8> = (elems.row_slices())
+                    .map(|elem| members.get(elem).is_some() as u8)
+                    .collect();
                 let shape: Shape = self.shape.iter().cloned().take(1).collect();
                 Array::new(shape, result_data)
             }
+            Ordering::Greater if elems.element_count() > 0 => {
+                // Look up every cell in the same table instead of making one for each row
+                let (shape, cell_len) = lookup_cells_shape(elems, of);
+                let members = RowLookup::new(of, elems.element_count() / cell_len);
+                let result_data: EcoVec<u8> = (elems.data.chunks_exact(cell_len))
+                    .map(|elem| members.get(elem).is_some() as u8)
+                    .collect();
+                Array::new(shape, result_data)
+            }
             Ordering::Greater => {
                 let mut rows = Vec::with_capacity(elems.row_count());
                 for elem in elems.rows() {
@@ -1063,6 +1373,18 @@ impl<T: ArrayValue> Array<T> {
     }
 }
 
+/// Get the shape of the result of looking up the cells of one array in the rows of another
+/// of lower rank, along with the length of each cell
+fn lookup_cells_shape<T: ArrayValue>(
+    searched_for: &Array<T>,
+    searched_in: &Array<T>,
+) -> (Shape, usize) {
+    let rank = (searched_for.rank() + 1 - searched_in.rank()).min(searched_for.rank());
+    let shape = Shape::from(&searched_for.shape[..rank]);
+    let cell_len = searched_for.shape[rank..].iter().product();
+    (shape, cell_len)
+}
+
 impl Value {
     /// Get the `index of` the rows of this value in another
     pub fn index_of(&self, searched_in: &Value, env: &Uiua) -> UiuaResult<Value> {
@@ -1073,6 +1395,7 @@ impl Value {
             |a, b| a.index_of(b, env).map(Into::into),
             |a, b| a.index_of(b, env).map(Into::into),
             |a, b| a.index_of(b, env).map(Into::into),
+            |a, b| a.index_of(b, env).map(Into::into),
             |a, b| {
                 env.error(format!(
                     "Cannot look for indices of {} array in {} array",
@@ -1091,6 +1414,7 @@ impl Value {
             |a, b| a.progressive_index_of(b, env).map(Into::into),
             |a, b| a.progressive_index_of(b, env).map(Into::into),
             |a, b| a.progressive_index_of(b, env).map(Into::into),
+            |a, b| a.progressive_index_of(b, env).map(Into::into),
             |a, b| {
                 env.error(format!(
                     "Cannot look for indices of {} array in {} array",
@@ -1108,22 +1432,23 @@ impl<T: ArrayValue> Array<T> {
         let searched_for = self;
         Ok(match searched_for.rank().cmp(&searched_in.rank()) {
             Ordering::Equal => {
-                let mut result_data = EcoVec::with_capacity(searched_for.row_count());
-                let mut members = HashMap::with_capacity(searched_in.row_count());
-                for (i, of) in searched_in.row_slices().enumerate() {
-                    members.entry(ArrayCmpSlice(of)).or_insert(i);
-                }
-                for elem in searched_for.row_slices() {
-                    result_data.push(
-                        members
-                            .get(&ArrayCmpSlice(elem))
-                            .map(|i| *i as f64)
-                            .unwrap_or(searched_in.row_count() as f64),
-                    );
-                }
+                let indices = RowLookup::new(searched_in, searched_for.row_count());
+                let result_data: EcoVec<f64> = (searched_for.row_slices())
+                    .map(|elem| indices.get(elem).unwrap_or(searched_in.row_count()) as f64)
+                    .collect();
                 let shape: Shape = self.shape.iter().cloned().take(1).collect();
                 Array::new(shape, result_data)
             }
+            Ordering::Greater if searched_for.element_count() > 0 => {
+                // Look up every cell in the same table instead of making one for each row
+                let (shape, cell_len) = lookup_cells_shape(searched_for, searched_in);
+                let lookups = searched_for.element_count() / cell_len;
+                let indices = RowLookup::new(searched_in, lookups);
+                let result_data: EcoVec<f64> = (searched_for.data.chunks_exact(cell_len))
+                    .map(|elem| indices.get(elem).unwrap_or(searched_in.row_count()) as f64)
+                    .collect();
+                Array::new(shape, result_data)
+            }
             Ordering::Greater => {
                 let mut rows = Vec::with_capacity(searched_for.row_count());
                 for elem in searched_for.rows() {
@@ -1168,37 +1493,22 @@ impl<T: ArrayValue> Array<T> {
         let searched_for = self;
         Ok(match searched_for.rank().cmp(&searched_in.rank()) {
             Ordering::Equal => {
-                let mut used = HashSet::new();
-                let mut result_data = EcoVec::with_capacity(searched_for.row_count());
-                if searched_for.rank() == 1 {
-                    for elem in &searched_for.data {
-                        let mut hasher = DefaultHasher::new();
-                        elem.array_hash(&mut hasher);
-                        let hash = hasher.finish();
-                        result_data.push(
-                            searched_in
-                                .data
-                                .iter()
-                                .enumerate()
-                                .find(|&(i, of)| elem.array_eq(of) && used.insert((hash, i)))
-                                .map(|(i, _)| i)
-                                .unwrap_or(searched_in.row_count())
-                                as f64,
-                        );
-                    }
-                    return Ok(Array::from(result_data));
+                // Each distinct row has a queue of the indices where it appears
+                let mut indices: HashMap<_, (Vec<usize>, usize)> = HashMap::new();
+                for (i, of) in searched_in.row_slices().enumerate() {
+                    indices.entry(ArrayCmpSlice(of)).or_default().0.push(i);
                 }
-                'elem: for elem in searched_for.rows() {
-                    for (i, of) in searched_in.rows().enumerate() {
-                        let mut hasher = DefaultHasher::new();
-                        elem.hash(&mut hasher);
-                        let hash = hasher.finish();
-                        if elem == of && used.insert((hash, i)) {
-                            result_data.push(i as f64);
-                            continue 'elem;
-                        }
-                    }
-                    result_data.push(searched_in.row_count() as f64);
+                let mut result_data = EcoVec::with_capacity(searched_for.row_count());
+                for elem in searched_for.row_slices() {
+                    let index = indices
+                        .get_mut(&ArrayCmpSlice(elem))
+                        .and_then(|(indices, used)| {
+                            let index = indices.get(*used).copied();
+                            *used += 1;
+                            index
+                        })
+                        .unwrap_or(searched_in.row_count());
+                    result_data.push(index as f64);
                 }
                 let shape: Shape = self.shape.iter().cloned().take(1).collect();
                 Array::new(shape, result_data)
@@ -1240,3 +1550,221 @@ impl<T: ArrayValue> Array<T> {
         })
     }
 }
+
+#[derive(Clone, Copy)]
+enum SetOp {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl SetOp {
+    fn name(self) -> &'static str {
+        match self {
+            SetOp::Union => "union",
+            SetOp::Intersection => "intersection",
+            SetOp::Difference => "difference",
+        }
+    }
+}
+
+impl Value {
+    /// Get the `union` of the rows of two arrays
+    pub fn union(&self, other: &Self, env: &Uiua) -> UiuaResult<Self> {
+        self.set_op(other, SetOp::Union, env)
+    }
+    /// Get the `intersection` of the rows of two arrays
+    pub fn intersection(&self, other: &Self, env: &Uiua) -> UiuaResult<Self> {
+        self.set_op(other, SetOp::Intersection, env)
+    }
+    /// Get the `difference` of the rows of two arrays
+    pub fn difference(&self, other: &Self, env: &Uiua) -> UiuaResult<Self> {
+        self.set_op(other, SetOp::Difference, env)
+    }
+    fn set_op(&self, other: &Self, op: SetOp, env: &Uiua) -> UiuaResult<Self> {
+        self.generic_bin_ref(
+            other,
+            |a, b| a.set_op(b, op, env).map(Into::into),
+            |a, b| a.set_op(b, op, env).map(Into::into),
+            |a, b| a.set_op(b, op, env).map(Into::into),
+            |a, b| a.set_op(b, op, env).map(Into::into),
+            |a, b| a.set_op(b, op, env).map(Into::into),
+            |a, b| a.set_op(b, op, env).map(Into::into),
+            |a, b| {
+                env.error(format!(
+                    "Cannot take the {} of {} array and {} array",
+                    op.name(),
+                    a.type_name(),
+                    b.type_name(),
+                ))
+            },
+        )
+    }
+}
+
+impl<T: ArrayValue> Array<T> {
+    /// Get the `union` of the rows of two arrays
+    ///
+    /// Rows keep the order of their first occurrence, and duplicates are removed.
+    pub fn union(&self, other: &Self, env: &Uiua) -> UiuaResult<Self> {
+        self.set_op(other, SetOp::Union, env)
+    }
+    /// Get the `intersection` of the rows of two arrays
+    ///
+    /// Rows keep the order of their first occurrence, and duplicates are removed.
+    pub fn intersection(&self, other: &Self, env: &Uiua) -> UiuaResult<Self> {
+        self.set_op(other, SetOp::Intersection, env)
+    }
+    /// Get the `difference` of the rows of two arrays
+    ///
+    /// Rows keep the order of their first occurrence, and duplicates are removed.
+    pub fn difference(&self, other: &Self, env: &Uiua) -> UiuaResult<Self> {
+        self.set_op(other, SetOp::Difference, env)
+    }
+    fn set_op(&self, other: &Self, op: SetOp, env: &Uiua) -> UiuaResult<Self> {
+        // Scalars are treated as single rows
+        let row_shape = &self.shape[self.rank().min(1)..];
+        let other_row_shape = &other.shape[other.rank().min(1)..];
+        if row_shape != other_row_shape {
+            return Err(env.error(format!(
+                "Cannot take the {} of arrays with rows of shape {} and {}",
+                op.name(),
+                FormatShape(row_shape),
+                FormatShape(other_row_shape)
+            )));
+        }
+        let rows: Vec<&[T]> = match op {
+            SetOp::Union => self.row_slices().chain(other.row_slices()).collect(),
+            SetOp::Intersection | SetOp::Difference => {
+                let keep = matches!(op, SetOp::Intersection);
+                let others: HashSet<_> = other.row_slices().map(ArrayCmpSlice).collect();
+                (self.row_slices())
+                    .filter(|row| others.contains(&ArrayCmpSlice(row)) == keep)
+                    .collect()
+            }
+        };
+        let mut seen = HashSet::with_capacity(rows.len());
+        let mut data = EcoVec::new();
+        let mut row_count = 0;
+        for row in rows {
+            if seen.insert(ArrayCmpSlice(row)) {
+                data.extend_from_slice(row);
+                row_count += 1;
+            }
+        }
+        let mut shape = Shape::from(row_shape);
+        shape.insert(0, row_count);
+        Ok(Array::new(shape, data))
+    }
+}
+
+impl Value {
+    /// Get the digits of numbers in a base (LSB-first)
+    pub fn base(&self, of: &Self, env: &Uiua) -> UiuaResult<Self> {
+        let of = of.as_number_array(
+            env,
+            "Numbers to convert to a base must be finite and non-negative",
+            |_| true,
+            |n| n.is_finite() && n >= 0.0,
+            |n| n,
+        )?;
+        let mut shape = of.shape.clone();
+        Ok(match self.rank() {
+            0 => {
+                let radix = self.as_num(env, "Base must be a number")?;
+                if radix <= 1.0 || radix.is_infinite() {
+                    return Err(env.error(format!(
+                        "Base must be a finite number greater than 1, but it is {radix}"
+                    )));
+                }
+                let mut rows = Vec::with_capacity(of.data.len());
+                for &n in of.data.iter() {
+                    let mut n = n;
+                    let mut digits = Vec::new();
+                    while n > 0.0 {
+                        digits.push(n.rem_euclid(radix));
+                        n = (n / radix).floor();
+                    }
+                    rows.push(digits);
+                }
+                let max_len = rows.iter().map(Vec::len).max().unwrap_or(0);
+                let mut data = EcoVec::with_capacity(rows.len() * max_len);
+                for row in rows {
+                    let pad = max_len - row.len();
+                    data.extend(row);
+                    data.extend(repeat_n(0.0, pad));
+                }
+                shape.push(max_len);
+                Array::new(shape, data).into()
+            }
+            1 => {
+                let radices = self.as_number_list(
+                    env,
+                    "Mixed bases must all be positive",
+                    |n| n > 0.0,
+                    |n| n,
+                )?;
+                let mut data = EcoVec::with_capacity(of.data.len() * radices.len());
+                for &n in of.data.iter() {
+                    let mut n = n;
+                    for &radix in &radices {
+                        if radix.is_infinite() {
+                            data.push(n);
+                            n = 0.0;
+                        } else {
+                            data.push(n.rem_euclid(radix));
+                            n = (n / radix).floor();
+                        }
+                    }
+                }
+                shape.push(radices.len());
+                Array::new(shape, data).into()
+            }
+            rank => {
+                return Err(env.error(format!(
+                    "Base must be a scalar or list, but it is rank {rank}"
+                )))
+            }
+        })
+    }
+    /// Get numbers from their digits in a base (LSB-first)
+    pub(crate) fn inv_base(&self, digits: &Self, env: &Uiua) -> UiuaResult<Self> {
+        let digits = digits.as_number_array(
+            env,
+            "Digits must be numbers with at least 1 dimension",
+            |shape| !shape.is_empty(),
+            |_| true,
+            |n| n,
+        )?;
+        let radices = match self.rank() {
+            0 => vec![self.as_num(env, "Base must be a number")?],
+            1 => self.as_number_list(env, "Mixed bases must be numbers", |_| true, |n| n)?,
+            rank => {
+                return Err(env.error(format!(
+                    "Base must be a scalar or list, but it is rank {rank}"
+                )))
+            }
+        };
+        let mut shape = digits.shape.clone();
+        let row_len = shape.pop().unwrap();
+        if self.rank() == 1 && row_len > radices.len() {
+            return Err(env.error(format!(
+                "Cannot decode {row_len} digits with {} bases",
+                radices.len()
+            )));
+        }
+        let mut data = EcoVec::with_capacity(shape.iter().product());
+        if row_len == 0 {
+            data.extend(repeat_n(0.0, shape.iter().product()));
+        } else {
+            for row in digits.data.chunks_exact(row_len) {
+                let mut n = row[row_len - 1];
+                for i in (0..row_len - 1).rev() {
+                    n = n * radices[i % radices.len()] + row[i];
+                }
+                data.push(n);
+            }
+        }
+        Ok(Array::new(shape, data).into())
+    }
+}
@@ -4,7 +4,6 @@ use std::{
     mem::take,
 };
 
-#[cfg(feature = "bytes")]
 use crate::algorithm::{op_bytes_ref_retry_fill, op_bytes_retry_fill};
 use crate::{
     algorithm::FillContext,
@@ -35,6 +34,13 @@ impl Value {
                 }
                 (&arr.shape, index_data)
             }
+            Value::Int(arr) => {
+                let mut index_data = Vec::with_capacity(arr.element_count());
+                for &n in &arr.data {
+                    index_data.push(n as isize);
+                }
+                (&arr.shape, index_data)
+            }
             value => {
                 return Err(env.error(format!(
                     "Index must be an array of integers, not {}",
@@ -54,6 +60,11 @@ impl Value {
                 |a| Ok(a.pick(index_shape, &index_data, env)?.into()),
                 |a| Ok(a.pick(index_shape, &index_data, env)?.into()),
             )?,
+            Value::Int(a) => op_bytes_retry_fill(
+                a,
+                |a| Ok(a.pick(index_shape, &index_data, env)?.into()),
+                |a| Ok(a.pick(index_shape, &index_data, env)?.into()),
+            )?,
             Value::Complex(a) => Value::Complex(a.pick(index_shape, &index_data, env)?),
             Value::Char(a) => Value::Char(a.pick(index_shape, &index_data, env)?),
             Value::Box(a) => Value::Box(a.pick(index_shape, &index_data, env)?),
@@ -85,6 +96,7 @@ impl Value {
             |a, b| a.unpick(index_shape, &index_data, b, env).map(Into::into),
             |a, b| a.unpick(index_shape, &index_data, b, env).map(Into::into),
             |a, b| a.unpick(index_shape, &index_data, b, env).map(Into::into),
+            |a, b| a.unpick(index_shape, &index_data, b, env).map(Into::into),
             |a, b| {
                 env.error(format!(
                     "Cannot unpick {} array from {} array",
@@ -259,6 +271,11 @@ impl Value {
                 |a| Ok(a.take(&index, env)?.into()),
                 |a| Ok(a.take(&index, env)?.into()),
             )?,
+            Value::Int(a) => op_bytes_retry_fill(
+                a,
+                |a| Ok(a.take(&index, env)?.into()),
+                |a| Ok(a.take(&index, env)?.into()),
+            )?,
             Value::Complex(a) => Value::Complex(a.take(&index, env)?),
             Value::Char(a) => Value::Char(a.take(&index, env)?),
             Value::Box(a) => Value::Box(a.take(&index, env)?),
@@ -274,11 +291,42 @@ impl Value {
             Value::Num(a) => Value::Num(a.drop(&index, env)?),
             #[cfg(feature = "bytes")]
             Value::Byte(a) => Value::Byte(a.drop(&index, env)?),
+            Value::Int(a) => Value::Int(a.drop(&index, env)?),
             Value::Complex(a) => Value::Complex(a.drop(&index, env)?),
             Value::Char(a) => Value::Char(a.drop(&index, env)?),
             Value::Box(a) => Value::Box(a.drop(&index, env)?),
         })
     }
+    /// `take` from the `range` of a value without making the whole range
+    pub(crate) fn take_range(self, max: Self, env: &Uiua) -> UiuaResult<Self> {
+        if let (Some(taking), Some(len)) = (scalar_int(&self), range_len(&max)) {
+            if (0.0..=len).contains(&taking) {
+                return Ok(Value::from_indices(taking as usize, 0..taking as usize));
+            }
+            if taking < 0.0 && -taking <= len && len.is_finite() {
+                let start = (len + taking) as usize;
+                return Ok(Value::from_indices(
+                    len as usize - start,
+                    start..len as usize,
+                ));
+            }
+        }
+        let range = max.range(env)?;
+        self.take(range, env)
+    }
+    /// `pick` from the `range` of a value without making the whole range
+    pub(crate) fn pick_range(self, max: Self, env: &Uiua) -> UiuaResult<Self> {
+        if let (Some(index), Some(len)) = (scalar_int(&self), range_len(&max)) {
+            if (0.0..len).contains(&index) {
+                return Ok(Value::from_indices([], [index as usize]));
+            }
+            if index < 0.0 && -index <= len && len.is_finite() {
+                return Ok(Value::from_indices([], [(len + index) as usize]));
+            }
+        }
+        let range = max.range(env)?;
+        self.pick(range, env)
+    }
     pub(crate) fn untake(self, index: Self, into: Self, env: &Uiua) -> UiuaResult<Self> {
         let index = index.as_ints(env, "Index must be a list of integers")?;
         self.generic_bin_into(
@@ -288,6 +336,7 @@ impl Value {
             |a, b| a.untake(&index, b, env).map(Into::into),
             |a, b| a.untake(&index, b, env).map(Into::into),
             |a, b| a.untake(&index, b, env).map(Into::into),
+            |a, b| a.untake(&index, b, env).map(Into::into),
             |a, b| {
                 env.error(format!(
                     "Cannot untake {} into {}",
@@ -306,6 +355,7 @@ impl Value {
             |a, b| a.undrop(&index, b, env).map(Into::into),
             |a, b| a.undrop(&index, b, env).map(Into::into),
             |a, b| a.undrop(&index, b, env).map(Into::into),
+            |a, b| a.undrop(&index, b, env).map(Into::into),
             |a, b| {
                 env.error(format!(
                     "Cannot undrop {} into {}",
@@ -317,6 +367,26 @@ impl Value {
     }
 }
 
+/// Get a scalar number without erroring
+fn scalar_num(val: &Value) -> Option<f64> {
+    match val {
+        Value::Num(arr) if arr.rank() == 0 => Some(arr.data[0]),
+        #[cfg(feature = "bytes")]
+        Value::Byte(arr) if arr.rank() == 0 => Some(arr.data[0] as f64),
+        Value::Int(arr) if arr.rank() == 0 => Some(arr.data[0] as f64),
+        _ => None,
+    }
+}
+
+fn scalar_int(val: &Value) -> Option<f64> {
+    scalar_num(val).filter(|n| n.fract() == 0.0)
+}
+
+/// Get the length of the `range` of a scalar, which may be infinite
+fn range_len(max: &Value) -> Option<f64> {
+    scalar_num(max).filter(|&n| n >= 0.0 && (n.fract() == 0.0 || n == f64::INFINITY))
+}
+
 impl<T: ArrayValue> Array<T> {
     /// `take` from this array
     pub fn take(mut self, index: &[isize], env: &Uiua) -> UiuaResult<Self> {
@@ -326,63 +396,34 @@ impl<T: ArrayValue> Array<T> {
                 let row_len = self.row_len();
                 let row_count = self.row_count();
                 let abs_taking = taking.unsigned_abs();
-                let mut filled = false;
-                self.data.modify(|data| {
-                    if taking >= 0 {
-                        if abs_taking > row_count {
-                            match T::get_fill(env) {
-                                Ok(fill) => {
-                                    filled = true;
-                                    data.extend(
-                                        repeat(fill).take((abs_taking - row_count) * row_len),
-                                    );
-                                }
-                                Err(e) => {
-                                    return Err(env
-                                        .error(format!(
-                                            "Cannot take {} rows from array with {} row{} \
-                                            outside a fill context{e}",
-                                            abs_taking,
-                                            row_count,
-                                            if row_count == 1 { "" } else { "s" }
-                                        ))
-                                        .fill());
-                                }
-                            }
-                        } else {
-                            data.truncate(abs_taking * row_len);
-                        }
+                let filled = abs_taking > row_count;
+                if !filled {
+                    // Taking in bounds makes a view of the data without copying it
+                    self.data = if taking >= 0 {
+                        self.data.slice(..abs_taking * row_len)
                     } else {
-                        *data = if abs_taking > row_count {
-                            match T::get_fill(env) {
-                                Ok(fill) => {
-                                    filled = true;
-                                    repeat(fill)
-                                        .take((abs_taking - row_count) * row_len)
-                                        .chain(take(data))
-                                        .collect()
-                                }
-                                Err(e) => {
-                                    return Err(env
-                                        .error(format!(
-                                            "Cannot take {} rows from array with {} row{} \
-                                            outside a fill context{e}",
-                                            abs_taking,
-                                            row_count,
-                                            if row_count == 1 { "" } else { "s" }
-                                        ))
-                                        .fill());
-                                }
-                            }
+                        self.data.slice((row_count - abs_taking) * row_len..)
+                    };
+                } else {
+                    let fill = T::get_fill(env).map_err(|e| {
+                        env.error(format!(
+                            "Cannot take {} rows from array with {} row{} \
+                            outside a fill context{e}",
+                            abs_taking,
+                            row_count,
+                            if row_count == 1 { "" } else { "s" }
+                        ))
+                        .fill()
+                    })?;
+                    let fill = repeat(fill).take((abs_taking - row_count) * row_len);
+                    self.data.modify(|data| {
+                        if taking >= 0 {
+                            data.extend(fill);
                         } else {
-                            take(data)
-                                .into_iter()
-                                .skip((row_count - abs_taking) * row_len)
-                                .collect()
-                        };
-                    }
-                    Ok(())
-                })?;
+                            *data = fill.chain(take(data)).collect();
+                        }
+                    });
+                }
                 if let Some(s) = self.shape.get_mut(0) {
                     *s = if filled {
                         abs_taking
@@ -493,16 +534,13 @@ impl<T: ArrayValue> Array<T> {
                 let row_len = self.row_len();
                 let row_count = self.row_count();
                 let abs_dropping = dropping.unsigned_abs();
-                self.data.modify(|data| {
-                    if dropping >= 0 {
-                        *data = take(data)
-                            .into_iter()
-                            .skip(abs_dropping * row_len)
-                            .collect();
-                    } else {
-                        data.truncate((row_count.saturating_sub(abs_dropping)) * row_len);
-                    }
-                });
+                // Dropping makes a view of the data without copying it
+                self.data = if dropping >= 0 {
+                    self.data.slice(abs_dropping.min(row_count) * row_len..)
+                } else {
+                    self.data
+                        .slice(..row_count.saturating_sub(abs_dropping) * row_len)
+                };
                 if self.shape.is_empty() {
                     self.shape.push(1);
                 }
@@ -642,6 +680,11 @@ impl Value {
                 |a| Ok(a.select_impl(indices_shape, &indices_data, env)?.into()),
                 |a| Ok(a.select_impl(indices_shape, &indices_data, env)?.into()),
             )?,
+            Value::Int(a) => op_bytes_ref_retry_fill(
+                a,
+                |a| Ok(a.select_impl(indices_shape, &indices_data, env)?.into()),
+                |a| Ok(a.select_impl(indices_shape, &indices_data, env)?.into()),
+            )?,
             Value::Complex(a) => a.select_impl(indices_shape, &indices_data, env)?.into(),
             Value::Char(a) => a.select_impl(indices_shape, &indices_data, env)?.into(),
             Value::Box(a) => a.select_impl(indices_shape, &indices_data, env)?.into(),
@@ -675,6 +718,7 @@ impl Value {
             |a, b| a.unselect_impl(ind_shape, &ind, b, env).map(Into::into),
             |a, b| a.unselect_impl(ind_shape, &ind, b, env).map(Into::into),
             |a, b| a.unselect_impl(ind_shape, &ind, b, env).map(Into::into),
+            |a, b| a.unselect_impl(ind_shape, &ind, b, env).map(Into::into),
             |a, b| {
                 env.error(format!(
                     "Cannot untake {} into {}",
@@ -731,9 +775,34 @@ impl<T: ArrayValue> Array<T> {
         }
     }
     fn select(&self, indices: &[isize], env: &Uiua) -> UiuaResult<Self> {
-        let mut selected = CowSlice::with_capacity(self.row_len() * indices.len());
         let row_len = self.row_len();
         let row_count = self.row_count();
+        let mut shape = self.shape.clone();
+        if let Some(s) = shape.get_mut(0) {
+            *s = indices.len();
+        } else {
+            shape.push(indices.len());
+        }
+        // Contiguous indices select a view of the data without copying it
+        let normalize = |i: isize| {
+            if i >= 0 {
+                Some(i as usize)
+            } else {
+                row_count.checked_sub(i.unsigned_abs())
+            }
+        };
+        if let Some(start) = indices.first().and_then(|&i| normalize(i)) {
+            let end = start + indices.len();
+            if end <= row_count
+                && (indices.iter().enumerate()).all(|(k, &i)| normalize(i) == Some(start + k))
+            {
+                return Ok(Array::new(
+                    shape,
+                    self.data.slice(start * row_len..end * row_len),
+                ));
+            }
+        }
+        let mut selected = CowSlice::with_capacity(row_len * indices.len());
         for &i in indices {
             let i = if i >= 0 {
                 let ui = i as usize;
@@ -778,12 +847,6 @@ impl<T: ArrayValue> Array<T> {
             let end = start + row_len;
             selected.extend_from_slice(&self.data[start..end]);
         }
-        let mut shape = self.shape.clone();
-        if let Some(s) = shape.get_mut(0) {
-            *s = indices.len();
-        } else {
-            shape.push(indices.len());
-        }
         Ok(Array::new(shape, selected))
     }
     fn unselect(
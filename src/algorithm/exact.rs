@@ -0,0 +1,607 @@
+//! Exact arithmetic for the [`exact`](Primitive::Exact) and [`big`](Primitive::Big) modifiers
+
+use std::cmp::Ordering;
+
+use ecow::EcoVec;
+
+use crate::{Array, Boxed, FormatShape, ImplPrimitive, Primitive, Shape, Uiua, UiuaResult, Value};
+
+/// A number type used by an exact arithmetic modifier
+///
+/// Exact numbers are passed around as strings so that they survive between
+/// calls without losing precision.
+pub(crate) trait ExactNum: Clone + Ord + Sized {
+    /// Convert a float, if it can be represented exactly
+    fn from_f64(n: f64) -> Option<Self>;
+    /// Parse a string
+    fn parse(s: &str) -> Option<Self>;
+    /// Format as a string that [`ExactNum::parse`] accepts
+    fn format(&self) -> String;
+    /// Apply a monadic primitive
+    ///
+    /// Returns `None` if the primitive is not supported
+    fn monadic(prim: Primitive, a: &Self) -> Option<Result<Self, &'static str>>;
+    /// Apply a dyadic primitive
+    ///
+    /// `a` is the first argument, which is on top of the stack.
+    /// Returns `None` if the primitive is not supported
+    fn dyadic(prim: Primitive, a: &Self, b: &Self) -> Option<Result<Self, &'static str>>;
+    /// Get the bits of a natural number, least significant first
+    ///
+    /// Returns `None` if [`bits`](Primitive::Bits) is not supported
+    fn to_bits(&self) -> Option<Result<Vec<bool>, &'static str>> {
+        None
+    }
+    /// Build a natural number from its bits, least significant first
+    ///
+    /// Returns `None` if [`un bits`](ImplPrimitive::InverseBits) is not supported
+    fn from_bits(_bits: &[bool]) -> Option<Self> {
+        None
+    }
+}
+
+/// Run an exact arithmetic modifier
+pub(crate) fn run<T: ExactNum>(modifier: Primitive, env: &mut Uiua) -> UiuaResult {
+    let f = env.pop_function()?;
+    if let Some((ImplPrimitive::InverseBits, _)) = f.as_flipped_impl_primitive(&env.asm) {
+        return inverse_bits::<T>(modifier, env);
+    }
+    let Some((prim, flipped)) = f.as_flipped_primitive(&env.asm) else {
+        return Err(env.error(format!(
+            "{}'s function must be a single arithmetic primitive",
+            modifier.format()
+        )));
+    };
+    let unsupported = |env: &Uiua| {
+        env.error(format!(
+            "{} is not supported by {}",
+            prim.format(),
+            modifier.format()
+        ))
+    };
+    match prim.args() {
+        Some(1) => {
+            let (shape, a) = exact_elems::<T>(env.pop(1)?, modifier, env)?;
+            if prim == Primitive::Parse {
+                env.push(exact_value(shape, a));
+                return Ok(());
+            }
+            if prim == Primitive::Bits {
+                let mut rows = Vec::with_capacity(a.len());
+                for a in &a {
+                    match a.to_bits() {
+                        Some(res) => rows.push(res.map_err(|e| env.error(e))?),
+                        None => return Err(unsupported(env)),
+                    }
+                }
+                let max_bits = rows.iter().map(Vec::len).max().unwrap_or(0);
+                let mut data = EcoVec::with_capacity(rows.len() * max_bits);
+                for row in rows {
+                    let pad = max_bits - row.len();
+                    data.extend(row.into_iter().map(|b| b as u8 as f64));
+                    data.extend(std::iter::repeat_n(0.0, pad));
+                }
+                let mut shape = shape;
+                shape.push(max_bits);
+                let mut val = Value::from(Array::new(shape, data));
+                val.compress();
+                env.push(val);
+                return Ok(());
+            }
+            let mut results = Vec::with_capacity(a.len());
+            for a in &a {
+                match T::monadic(prim, a) {
+                    Some(res) => results.push(res.map_err(|e| env.error(e))?),
+                    None => return Err(unsupported(env)),
+                }
+            }
+            env.push(exact_value(shape, results));
+        }
+        Some(2) => {
+            let mut a = env.pop(1)?;
+            let mut b = env.pop(2)?;
+            if flipped {
+                (a, b) = (b, a);
+            }
+            let (a_shape, a) = exact_elems::<T>(a, modifier, env)?;
+            let (b_shape, b) = exact_elems::<T>(b, modifier, env)?;
+            let len = if a_shape.is_empty() { b.len() } else { a.len() };
+            let shape = match (a_shape.len(), b_shape.len()) {
+                (0, _) => b_shape,
+                (_, 0) => a_shape,
+                _ if a_shape == b_shape => a_shape,
+                _ => {
+                    return Err(env.error(format!(
+                        "Shapes {} and {} do not match",
+                        FormatShape(&a_shape),
+                        FormatShape(&b_shape)
+                    )))
+                }
+            };
+            let pairs = (0..len).map(|i| (&a[i % a.len()], &b[i % b.len()]));
+            let comparison: Option<fn(Ordering) -> bool> = match prim {
+                Primitive::Eq => Some(Ordering::is_eq),
+                Primitive::Ne => Some(Ordering::is_ne),
+                Primitive::Lt => Some(Ordering::is_lt),
+                Primitive::Le => Some(Ordering::is_le),
+                Primitive::Gt => Some(Ordering::is_gt),
+                Primitive::Ge => Some(Ordering::is_ge),
+                _ => None,
+            };
+            if let Some(compare) = comparison {
+                // Comparisons are between the second argument and the first
+                let data: EcoVec<f64> =
+                    pairs.map(|(a, b)| compare(b.cmp(a)) as u8 as f64).collect();
+                let mut val = Value::from(Array::new(shape, data));
+                val.compress();
+                env.push(val);
+                return Ok(());
+            }
+            let mut results = Vec::with_capacity(len);
+            for (a, b) in pairs {
+                let res = match prim {
+                    Primitive::Min => Ok(a.min(b).clone()),
+                    Primitive::Max => Ok(a.max(b).clone()),
+                    _ => T::dyadic(prim, a, b).ok_or_else(|| unsupported(env))?,
+                };
+                results.push(res.map_err(|e| env.error(e))?);
+            }
+            env.push(exact_value(shape, results));
+        }
+        _ => return Err(unsupported(env)),
+    }
+    Ok(())
+}
+
+/// Build exact naturals from the rows of a bit array
+fn inverse_bits<T: ExactNum>(modifier: Primitive, env: &mut Uiua) -> UiuaResult {
+    let val = env.pop(1)?;
+    let bits = match &val {
+        Value::Num(arr) => (arr.data.iter())
+            .map(|&n| (n == 0.0 || n == 1.0).then_some(n == 1.0))
+            .collect::<Option<Vec<_>>>(),
+        #[cfg(feature = "bytes")]
+        Value::Byte(arr) => (arr.data.iter())
+            .map(|&n| (n <= 1).then_some(n == 1))
+            .collect::<Option<Vec<_>>>(),
+        Value::Int(arr) => (arr.data.iter())
+            .map(|&n| (n <= 1).then_some(n == 1))
+            .collect::<Option<Vec<_>>>(),
+        _ => None,
+    };
+    let Some(bits) = bits.filter(|_| val.rank() > 0) else {
+        return Err(env.error(format!(
+            "Argument to {}{}{} must be an array of bits",
+            modifier.format(),
+            Primitive::Un.format(),
+            Primitive::Bits.format()
+        )));
+    };
+    let mut shape = val.shape().clone();
+    let row_len = shape.pop().unwrap_or(0);
+    let count: usize = shape.iter().product();
+    let mut elems = Vec::with_capacity(count);
+    for i in 0..count {
+        let Some(n) = T::from_bits(&bits[i * row_len..(i + 1) * row_len]) else {
+            return Err(env.error(format!(
+                "{}{} is not supported by {}",
+                Primitive::Un.format(),
+                Primitive::Bits.format(),
+                modifier.format()
+            )));
+        };
+        elems.push(n);
+    }
+    env.push(exact_value(shape, elems));
+    Ok(())
+}
+
+/// Get the exact elements of a value
+///
+/// Numbers are converted if they can be represented exactly.
+/// A string is a single element, and a box array contains one string or number per element.
+fn exact_elems<T: ExactNum>(
+    val: Value,
+    modifier: Primitive,
+    env: &Uiua,
+) -> UiuaResult<(Shape, Vec<T>)> {
+    let from_f64 = |n: f64| {
+        T::from_f64(n).ok_or_else(|| {
+            env.error(format!(
+                "{n} cannot be represented exactly by {}",
+                modifier.format()
+            ))
+        })
+    };
+    let parse = |s: &str| {
+        T::parse(s).ok_or_else(|| {
+            env.error(format!(
+                "{s:?} is not a valid number for {}",
+                modifier.format()
+            ))
+        })
+    };
+    Ok(match val {
+        Value::Num(arr) => {
+            let elems = arr.data.iter().map(|&n| from_f64(n));
+            (arr.shape.clone(), elems.collect::<UiuaResult<_>>()?)
+        }
+        #[cfg(feature = "bytes")]
+        Value::Byte(arr) => {
+            let elems = arr.data.iter().map(|&n| from_f64(n as f64));
+            (arr.shape.clone(), elems.collect::<UiuaResult<_>>()?)
+        }
+        Value::Int(arr) => {
+            let elems = arr.data.iter().map(|&n| from_f64(n as f64));
+            (arr.shape.clone(), elems.collect::<UiuaResult<_>>()?)
+        }
+        Value::Char(arr) if arr.rank() <= 1 => {
+            let s: String = arr.data.iter().collect();
+            (Shape::default(), vec![parse(&s)?])
+        }
+        Value::Box(arr) => {
+            let mut elems = Vec::with_capacity(arr.data.len());
+            for Boxed(val) in arr.data.iter() {
+                match val {
+                    Value::Char(s) if s.rank() <= 1 => {
+                        elems.push(parse(&s.data.iter().collect::<String>())?)
+                    }
+                    Value::Num(n) if n.rank() == 0 => elems.push(from_f64(n.data[0])?),
+                    #[cfg(feature = "bytes")]
+                    Value::Byte(n) if n.rank() == 0 => elems.push(from_f64(n.data[0] as f64)?),
+                    val => {
+                        return Err(env.error(format!(
+                            "Boxed arguments to {} must be strings or scalar numbers, \
+                            but one is {}",
+                            modifier.format(),
+                            val.type_name_plural()
+                        )))
+                    }
+                }
+            }
+            (arr.shape.clone(), elems)
+        }
+        val => {
+            return Err(env.error(format!(
+                "Arguments to {} must be numbers, strings, or boxed strings, but one is {}",
+                modifier.format(),
+                val.type_name_plural()
+            )))
+        }
+    })
+}
+
+/// Turn exact elements into a string, or a box array of strings
+fn exact_value<T: ExactNum>(shape: Shape, elems: Vec<T>) -> Value {
+    if shape.is_empty() {
+        return elems[0].format().into();
+    }
+    let data: EcoVec<Boxed> = (elems.iter()).map(|n| Boxed(n.format().into())).collect();
+    Array::new(shape, data).into()
+}
+
+/// An exact rational number
+///
+/// The numerator and denominator are kept in lowest terms and within the range of an `i64`,
+/// so that cross-multiplication never overflows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Rational {
+    num: i128,
+    den: i128,
+}
+
+const OVERFLOW: &str = "Exact arithmetic overflowed. \
+    Numerators and denominators are limited to 64 bits.";
+
+impl Rational {
+    fn new(num: i128, den: i128) -> Result<Self, &'static str> {
+        if den == 0 {
+            return Err("Division by zero");
+        }
+        let g = gcd(num, den);
+        let (mut num, mut den) = (num / g, den / g);
+        if den < 0 {
+            (num, den) = (-num, -den);
+        }
+        if num.unsigned_abs() > i64::MAX as u128 || den > i64::MAX as i128 {
+            return Err(OVERFLOW);
+        }
+        Ok(Rational { num, den })
+    }
+    fn int(n: i128) -> Result<Self, &'static str> {
+        Self::new(n, 1)
+    }
+    fn floor(&self) -> i128 {
+        self.num.div_euclid(self.den)
+    }
+    fn add(&self, other: &Self) -> Result<Self, &'static str> {
+        Self::new(
+            self.num * other.den + other.num * self.den,
+            self.den * other.den,
+        )
+    }
+    fn sub(&self, other: &Self) -> Result<Self, &'static str> {
+        self.add(&Rational {
+            num: -other.num,
+            den: other.den,
+        })
+    }
+    fn mul(&self, other: &Self) -> Result<Self, &'static str> {
+        Self::new(self.num * other.num, self.den * other.den)
+    }
+    fn div(&self, other: &Self) -> Result<Self, &'static str> {
+        Self::new(self.num * other.den, self.den * other.num)
+    }
+    fn pow(&self, exp: &Self) -> Result<Self, &'static str> {
+        if exp.den != 1 {
+            return Err("Exact exponents must be integers");
+        }
+        let mut base = if exp.num < 0 {
+            Self::int(1)?.div(self)?
+        } else {
+            *self
+        };
+        let mut exp = exp.num.unsigned_abs();
+        let mut acc = Self::int(1)?;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                acc = acc.mul(&base)?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.mul(&base)?;
+            }
+        }
+        Ok(acc)
+    }
+}
+
+fn gcd(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.abs().max(1)
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.num * other.den).cmp(&(other.num * self.den))
+    }
+}
+
+impl ExactNum for Rational {
+    fn from_f64(n: f64) -> Option<Self> {
+        // Use the shortest decimal representation, so that `0.1` is `1/10`
+        n.is_finite().then(|| Self::parse(&n.to_string())).flatten()
+    }
+    fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let (neg, s) = match s.strip_prefix(['-', '¯']) {
+            Some(s) => (true, s),
+            None => (false, s),
+        };
+        let r = if let Some((num, den)) = s.split_once('/') {
+            let num: i128 = num.trim().parse().ok()?;
+            let den: i128 = den.trim().parse().ok()?;
+            Self::new(num, den).ok()?
+        } else {
+            let (mantissa, exp) = match s.split_once(['e', 'E']) {
+                Some((m, e)) => (m, e.replace('¯', "-").parse::<i32>().ok()?),
+                None => (s, 0),
+            };
+            let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+            if int.is_empty() && frac.is_empty()
+                || !(int.chars().chain(frac.chars())).all(|c| c.is_ascii_digit())
+            {
+                return None;
+            }
+            let digits: i128 = format!("{int}{frac}").parse().ok()?;
+            let exp = exp - frac.len() as i32;
+            let scale = Self::int(10)
+                .ok()?
+                .pow(&Self::int(exp as i128).ok()?)
+                .ok()?;
+            Self::int(digits).ok()?.mul(&scale).ok()?
+        };
+        Some(if neg {
+            Rational { num: -r.num, ..r }
+        } else {
+            r
+        })
+    }
+    fn format(&self) -> String {
+        let sign = if self.num < 0 { "¯" } else { "" };
+        let num = self.num.unsigned_abs();
+        if self.den == 1 {
+            format!("{sign}{num}")
+        } else {
+            format!("{sign}{num}/{}", self.den)
+        }
+    }
+    fn monadic(prim: Primitive, a: &Self) -> Option<Result<Self, &'static str>> {
+        Some(match prim {
+            Primitive::Neg => Ok(Rational { num: -a.num, ..*a }),
+            Primitive::Abs => Ok(Rational {
+                num: a.num.abs(),
+                ..*a
+            }),
+            Primitive::Sign => Self::int(a.num.signum()),
+            Primitive::Floor => Self::int(a.floor()),
+            Primitive::Ceil => Self::int(-Rational { num: -a.num, ..*a }.floor()),
+            Primitive::Round => {
+                // Round half away from zero, like floats
+                let half = Rational { num: 1, den: 2 };
+                let abs = Rational {
+                    num: a.num.abs(),
+                    ..*a
+                };
+                abs.add(&half)
+                    .and_then(|r| Self::int(r.floor() * a.num.signum()))
+            }
+            _ => return None,
+        })
+    }
+    fn dyadic(prim: Primitive, a: &Self, b: &Self) -> Option<Result<Self, &'static str>> {
+        Some(match prim {
+            Primitive::Add => b.add(a),
+            Primitive::Sub => b.sub(a),
+            Primitive::Mul => b.mul(a),
+            Primitive::Div => b.div(a),
+            Primitive::Mod => {
+                if a.num == 0 {
+                    return Some(Err("Modulus by zero"));
+                }
+                // The result has the sign of the modulus
+                b.div(a)
+                    .and_then(|q| Self::int(q.floor()))
+                    .and_then(|q| a.mul(&q))
+                    .and_then(|m| b.sub(&m))
+            }
+            Primitive::Pow => b.pow(a),
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl ExactNum for num_bigint::BigInt {
+    fn from_f64(n: f64) -> Option<Self> {
+        (n.is_finite() && n.fract() == 0.0)
+            .then(|| format!("{n:.0}").parse().ok())
+            .flatten()
+    }
+    fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let (neg, digits) = match s.strip_prefix(['-', '¯']) {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let n: Self = digits.parse().ok()?;
+        Some(if neg { -n } else { n })
+    }
+    fn format(&self) -> String {
+        self.to_string().replace('-', "¯")
+    }
+    fn monadic(prim: Primitive, a: &Self) -> Option<Result<Self, &'static str>> {
+        use num_bigint::Sign;
+        Some(Ok(match prim {
+            Primitive::Neg => -a,
+            Primitive::Abs => Self::from(a.magnitude().clone()),
+            Primitive::Sign => Self::from(match a.sign() {
+                Sign::Minus => -1,
+                Sign::NoSign => 0,
+                Sign::Plus => 1,
+            }),
+            Primitive::Floor | Primitive::Ceil | Primitive::Round => a.clone(),
+            _ => return None,
+        }))
+    }
+    fn dyadic(prim: Primitive, a: &Self, b: &Self) -> Option<Result<Self, &'static str>> {
+        use num_bigint::Sign;
+        let zero = Self::default();
+        Some(match prim {
+            Primitive::Add => Ok(b + a),
+            Primitive::Sub => Ok(b - a),
+            Primitive::Mul => Ok(b * a),
+            // Division floors, so that results stay integers
+            Primitive::Div if *a == zero => Err("Division by zero"),
+            Primitive::Div => {
+                let q = b / a;
+                if (b % a != zero) && (b.sign() == Sign::Minus) != (a.sign() == Sign::Minus) {
+                    Ok(q - 1)
+                } else {
+                    Ok(q)
+                }
+            }
+            // The result has the sign of the modulus
+            Primitive::Mod if *a == zero => Err("Modulus by zero"),
+            Primitive::Mod => {
+                let r = b % a;
+                if r != zero && (r.sign() == Sign::Minus) != (a.sign() == Sign::Minus) {
+                    Ok(r + a)
+                } else {
+                    Ok(r)
+                }
+            }
+            Primitive::Pow => match u32::try_from(a) {
+                Ok(exp) => Ok(b.pow(exp)),
+                Err(_) if a.sign() == Sign::Minus => Err("Big exponents must be natural numbers"),
+                Err(_) => Err("Big exponent is too large"),
+            },
+            _ => return None,
+        })
+    }
+    fn to_bits(&self) -> Option<Result<Vec<bool>, &'static str>> {
+        Some(if self.sign() == num_bigint::Sign::Minus {
+            Err("Big bits only works on natural numbers")
+        } else {
+            Ok((0..self.bits()).map(|i| self.bit(i)).collect())
+        })
+    }
+    fn from_bits(bits: &[bool]) -> Option<Self> {
+        let mut n = Self::default();
+        for (i, &bit) in bits.iter().enumerate() {
+            n.set_bit(i as u64, bit);
+        }
+        Some(n)
+    }
+}
+
+#[test]
+fn rationals() {
+    let r = |s: &str| Rational::parse(s).unwrap();
+    assert_eq!(r("0.1").format(), "1/10");
+    assert_eq!(r("¯2/4").format(), "¯1/2");
+    assert_eq!(r("-1.5e2").format(), "¯150");
+    assert_eq!(r("25e-2").format(), "1/4");
+    assert_eq!(Rational::from_f64(0.1), Some(r("1/10")));
+    assert_eq!(Rational::parse("1/0"), None);
+    assert_eq!(Rational::parse("."), None);
+    let dy = |prim, a: &str, b: &str| Rational::dyadic(prim, &r(a), &r(b)).unwrap();
+    assert_eq!(dy(Primitive::Add, "1/3", "1/6"), Ok(r("1/2")));
+    assert_eq!(dy(Primitive::Sub, "1/3", "1"), Ok(r("2/3")));
+    assert_eq!(dy(Primitive::Mod, "3", "¯1/2"), Ok(r("5/2")));
+    assert_eq!(dy(Primitive::Pow, "¯2", "2/3"), Ok(r("9/4")));
+    assert_eq!(dy(Primitive::Div, "0", "1"), Err("Division by zero"));
+    assert_eq!(dy(Primitive::Pow, "64", "2"), Err(OVERFLOW));
+    let mon = |prim, a: &str| Rational::monadic(prim, &r(a)).unwrap();
+    assert_eq!(mon(Primitive::Round, "¯5/2"), Ok(r("¯3")));
+    assert_eq!(mon(Primitive::Floor, "¯1/2"), Ok(r("¯1")));
+    assert_eq!(mon(Primitive::Ceil, "¯1/2"), Ok(r("0")));
+}
+
+#[cfg(feature = "num-bigint")]
+#[test]
+fn big_ints() {
+    use num_bigint::BigInt;
+    let n = |s: &str| BigInt::parse(s).unwrap();
+    let dy = |prim, a: &str, b: &str| BigInt::dyadic(prim, &n(a), &n(b)).unwrap();
+    assert_eq!(
+        dy(Primitive::Pow, "100", "2").map(|n| n.format()),
+        Ok("1267650600228229401496703205376".into())
+    );
+    assert_eq!(dy(Primitive::Div, "2", "¯7"), Ok(n("¯4")));
+    assert_eq!(dy(Primitive::Mod, "3", "¯7"), Ok(n("2")));
+    assert_eq!(dy(Primitive::Mod, "¯3", "7"), Ok(n("¯2")));
+    assert!(dy(Primitive::Pow, "¯1", "2").is_err());
+    let bits = n("1267650600228229401496703205377")
+        .to_bits()
+        .unwrap()
+        .unwrap();
+    assert_eq!(bits.len(), 101);
+    assert_eq!(
+        BigInt::from_bits(&bits),
+        Some(n("1267650600228229401496703205377"))
+    );
+    assert_eq!(BigInt::from_f64(1e20), Some(n("100000000000000000000")));
+    assert_eq!(BigInt::from_f64(0.5), None);
+}
@@ -0,0 +1,53 @@
+//! Fast Fourier transforms
+
+use crate::{Array, Complex, Uiua, UiuaResult, Value};
+
+impl Value {
+    /// Take the discrete Fourier transform of each row along the last axis
+    pub(crate) fn fft(&self, env: &Uiua) -> UiuaResult<Array<Complex>> {
+        self.fft_impl(false, env)
+    }
+    /// Take the inverse discrete Fourier transform of each row along the last axis
+    pub(crate) fn inverse_fft(&self, env: &Uiua) -> UiuaResult<Array<Complex>> {
+        self.fft_impl(true, env)
+    }
+    #[cfg(not(feature = "rustfft"))]
+    fn fft_impl(&self, _inverse: bool, env: &Uiua) -> UiuaResult<Array<Complex>> {
+        Err(env.error("FFT support is not enabled"))
+    }
+    #[cfg(feature = "rustfft")]
+    fn fft_impl(&self, inverse: bool, env: &Uiua) -> UiuaResult<Array<Complex>> {
+        use rustfft::{num_complex::Complex64, FftPlanner};
+
+        let mut arr = match self {
+            Value::Num(arr) => arr.convert_ref_with(|n| Complex::new(n, 0.0)),
+            #[cfg(feature = "bytes")]
+            Value::Byte(arr) => arr.convert_ref_with(|n| Complex::new(n as f64, 0.0)),
+            Value::Int(arr) => arr.convert_ref_with(|n| Complex::new(n as f64, 0.0)),
+            Value::Complex(arr) => arr.clone(),
+            val => {
+                return Err(env.error(format!("Cannot take the FFT of {}", val.type_name_plural())))
+            }
+        };
+        let row_len = arr.shape.last().copied().unwrap_or(1);
+        if row_len == 0 {
+            return Ok(arr);
+        }
+        let mut planner = FftPlanner::new();
+        let fft = if inverse {
+            planner.plan_fft_inverse(row_len)
+        } else {
+            planner.plan_fft_forward(row_len)
+        };
+        let mut buffer: Vec<Complex64> = (arr.data.iter())
+            .map(|c| Complex64::new(c.re, c.im))
+            .collect();
+        fft.process(&mut buffer);
+        // rustfft does not normalize, so the inverse is scaled here
+        let scale = if inverse { 1.0 / row_len as f64 } else { 1.0 };
+        for (c, b) in arr.data.as_mut_slice().iter_mut().zip(buffer) {
+            *c = Complex::new(b.re * scale, b.im * scale);
+        }
+        Ok(arr)
+    }
+}
@@ -0,0 +1,61 @@
+//! Cryptographic hashes of byte arrays
+
+use crate::{Array, Primitive, Uiua, UiuaResult, Value};
+
+impl Value {
+    /// Get the bytes to hash, encoding strings as UTF-8
+    fn hash_input(&self, prim: Primitive, env: &Uiua) -> UiuaResult<Vec<u8>> {
+        const REQUIREMENT: &str = "Hashed data must be a string or a list of bytes";
+        match self {
+            Value::Char(arr) if arr.rank() <= 1 => Ok(arr.data.iter().collect::<String>().into()),
+            val if val.rank() <= 1 => val.as_bytes(env, REQUIREMENT),
+            val => Err(env.error(format!(
+                "Arguments to {} must be strings or lists of bytes, but one is rank {}",
+                prim.format(),
+                val.rank()
+            ))),
+        }
+    }
+    /// Hash a string or byte list with the algorithm named by `self`
+    pub(crate) fn hash_bytes(&self, data: &Self, env: &Uiua) -> UiuaResult<Array<u8>> {
+        let algorithm = self.as_string(env, "Hash algorithm must be a string")?;
+        let bytes = data.hash_input(Primitive::Hash, env)?;
+        #[cfg(feature = "crypto")]
+        {
+            use sha2::Digest;
+            Ok(match algorithm.as_str() {
+                "sha256" => sha2::Sha256::digest(bytes).as_slice().into(),
+                "md5" => md5::Md5::digest(bytes).as_slice().into(),
+                _ => {
+                    return Err(env.error(format!(
+                        "Unknown hash algorithm {algorithm:?}. \
+                        Valid algorithms are \"sha256\" and \"md5\""
+                    )))
+                }
+            })
+        }
+        #[cfg(not(feature = "crypto"))]
+        {
+            _ = (algorithm, bytes);
+            Err(env.error("Hashing is not enabled"))
+        }
+    }
+    /// Get the HMAC-SHA-256 of a message with `self` as the key
+    pub(crate) fn hmac(&self, message: &Self, env: &Uiua) -> UiuaResult<Array<u8>> {
+        let key = self.hash_input(Primitive::Hmac, env)?;
+        let message = message.hash_input(Primitive::Hmac, env)?;
+        #[cfg(feature = "crypto")]
+        {
+            use hmac::Mac;
+            // HMAC accepts keys of any length, so this cannot fail
+            let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(&key).unwrap();
+            mac.update(&message);
+            Ok(mac.finalize().into_bytes().as_slice().into())
+        }
+        #[cfg(not(feature = "crypto"))]
+        {
+            _ = (key, message);
+            Err(env.error("Hashing is not enabled"))
+        }
+    }
+}
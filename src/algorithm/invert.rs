@@ -28,6 +28,11 @@ fn prim_inverse(prim: Primitive, span: usize) -> Option<Instr> {
         Box => Instr::ImplPrim(InvBox, span),
         Where => Instr::ImplPrim(InvWhere, span),
         Utf => Instr::ImplPrim(InvUtf, span),
+        Utf16 => Instr::ImplPrim(InvUtf16, span),
+        Graphemes => Instr::ImplPrim(InvGraphemes, span),
+        Json => Instr::ImplPrim(InvJson, span),
+        Csv => Instr::ImplPrim(InvCsv, span),
+        Fft => Instr::ImplPrim(InvFft, span),
         Parse => Instr::ImplPrim(InvParse, span),
         Fix => Instr::ImplPrim(InvFix, span),
         Map => Instr::ImplPrim(InvMap, span),
@@ -48,6 +53,11 @@ fn impl_prim_inverse(prim: ImplPrimitive, span: usize) -> Option<Instr> {
         InverseBits => Instr::Prim(Bits, span),
         InvWhere => Instr::Prim(Where, span),
         InvUtf => Instr::Prim(Utf, span),
+        InvUtf16 => Instr::Prim(Utf16, span),
+        InvGraphemes => Instr::Prim(Graphemes, span),
+        InvJson => Instr::Prim(Json, span),
+        InvCsv => Instr::Prim(Csv, span),
+        InvFft => Instr::Prim(Fft, span),
         InvAtan => Instr::Prim(Atan, span),
         InvComplex => Instr::Prim(Complex, span),
         InvCouple => Instr::Prim(Couple, span),
@@ -112,6 +122,7 @@ pub(crate) fn invert_instrs(instrs: &[Instr], comp: &mut Compiler) -> Option<Eco
         &invert_primes_pattern,
         &(Val, invert_repeat_pattern),
         &(Val, ([Rotate], [Neg, Rotate])),
+        &(Val, ([Windows], [ImplPrimitive::Unwindows])),
         &([Rotate], [Neg, Rotate]),
         &pat!(Sqrt, (2, Pow)),
         &([Add], [Sub]),
@@ -125,6 +136,10 @@ pub(crate) fn invert_instrs(instrs: &[Instr], comp: &mut Compiler) -> Option<Eco
         &(Val, pat!(Pow, (1, Flip, Div, Pow))),
         &(Val, pat!((Flip, Pow), (Flip, 1, Flip, Div, Pow))),
         &(Val, ([Log], [Flip, Pow])),
+        &(Val, ([Base], [ImplPrimitive::InvBase])),
+        &(Val, ([Binary], [ImplPrimitive::InvBinary])),
+        &(Val, ([Split], [ImplPrimitive::InvSplit])),
+        &([Join, Csv], [ImplPrimitive::InvCsvHeader]),
         &(Val, ([Flip, Log], [Pow])),
         &pat!((Dup, Add), (2, Div)),
         &([Dup, Mul], [Sqrt]),
@@ -254,6 +269,7 @@ pub(crate) fn under_instrs(
             (PopTempN(1), Flip, PopTempN(1), Unkeep),
         )),
         &pat!(Rotate, (CopyToTempN(1), Rotate), (PopTempN(1), Neg, Rotate)),
+        &pat!(Windows, (CopyToTempN(1), Windows), (PopTempN(1), Unwindows)),
         &pat!(Abs, (CopyToTempN(1), Abs), (PopTempN(1), Sign, Mul)),
         &pat!(Sign, (Dup, Abs, PushTempN(1), Sign), (PopTempN(1), Mul)),
         &pat!(First, (CopyToTempN(1), First), (PopTempN(1), Unfirst)),
@@ -317,6 +333,11 @@ pub(crate) fn under_instrs(
             (Sys(SysOp::TcpConnect), CopyToTempN(1)),
             (PopTempN(1), Sys(SysOp::Close)),
         ),
+        &pat!(
+            Sys(SysOp::TlsConnect),
+            (Sys(SysOp::TlsConnect), CopyToTempN(1)),
+            (PopTempN(1), Sys(SysOp::Close)),
+        ),
         &pat!(
             Sys(SysOp::TcpAccept),
             (Sys(SysOp::TcpAccept), CopyToTempN(1)),
@@ -332,6 +353,21 @@ pub(crate) fn under_instrs(
             (CopyToTempN(1), Sys(SysOp::FReadAllBytes)),
             (PopTempN(1), Sys(SysOp::FWriteAll)),
         )),
+        &maybe_val!(pat!(
+            Sys(SysOp::ValueLoad),
+            (CopyToTempN(1), Sys(SysOp::ValueLoad)),
+            (PopTempN(1), Sys(SysOp::ValueSave)),
+        )),
+        &maybe_val!(pat!(
+            Sys(SysOp::ParquetRead),
+            (CopyToTempN(1), Sys(SysOp::ParquetRead)),
+            (PopTempN(1), Sys(SysOp::ParquetWrite)),
+        )),
+        &maybe_val!(pat!(
+            Sys(SysOp::ArrowRead),
+            (CopyToTempN(1), Sys(SysOp::ArrowRead)),
+            (PopTempN(1), Sys(SysOp::ArrowWrite)),
+        )),
         &UnderPatternFn(under_temp_pattern, "temp"),
         &UnderPatternFn(under_from_inverse_pattern, "from inverse"), // This must come last!
     ];
@@ -1014,19 +1050,27 @@ fn invert_scan_pattern<'a>(
     input: &'a [Instr],
     comp: &mut Compiler,
 ) -> Option<(&'a [Instr], EcoVec<Instr>)> {
-    let [Instr::PushFunc(f), Instr::Prim(Primitive::Scan, span), input @ ..] = input else {
-        return None;
+    let (f, span, right, input) = match input {
+        [Instr::PushFunc(f), Instr::Prim(Primitive::Scan, span), input @ ..] => {
+            (f, *span, false, input)
+        }
+        [Instr::PushFunc(f), Instr::ImplPrim(ImplPrimitive::ScanRight, span), input @ ..] => {
+            (f, *span, true, input)
+        }
+        _ => return None,
     };
     let instrs = f.instrs(comp).to_vec();
     let inverse = invert_instrs(&instrs, comp)?;
-    let inverse = make_fn(inverse, *span, comp)?;
-    Some((
-        input,
-        eco_vec![
-            Instr::PushFunc(inverse),
-            Instr::ImplPrim(ImplPrimitive::InvScan, *span)
-        ],
-    ))
+    let inverse = make_fn(inverse, span, comp)?;
+    let mut inverse = eco_vec![
+        Instr::PushFunc(inverse),
+        Instr::ImplPrim(ImplPrimitive::InvScan, span)
+    ];
+    if right {
+        inverse.insert(0, Instr::Prim(Primitive::Reverse, span));
+        inverse.push(Instr::Prim(Primitive::Reverse, span));
+    }
+    Some((input, inverse))
 }
 
 fn invert_repeat_pattern<'a>(
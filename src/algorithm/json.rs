@@ -0,0 +1,134 @@
+//! Conversion between values and JSON
+
+use serde_json::{Map, Number, Value as Json};
+
+use crate::{Array, ArrayFlags, Boxed, Uiua, UiuaResult, Value};
+
+impl Value {
+    /// Encode a value as a JSON string
+    pub(crate) fn json(&self, env: &Uiua) -> UiuaResult<String> {
+        Ok(self.to_json(env)?.to_string())
+    }
+    /// Decode a JSON string into a value
+    pub(crate) fn inv_json(&self, env: &Uiua) -> UiuaResult<Self> {
+        let json = self.as_string(env, "Argument to inverse json must be a string")?;
+        let json: Json =
+            serde_json::from_str(&json).map_err(|e| env.error(format!("Invalid JSON: {e}")))?;
+        Self::from_json(json, env)
+    }
+    fn to_json(&self, env: &Uiua) -> UiuaResult<Json> {
+        if self.meta().map_len.is_some() {
+            let (keys, values) = self.clone().unmap(env)?;
+            let mut pairs = Vec::with_capacity(keys.row_count());
+            for (key, value) in keys.into_rows().zip(values.into_rows()) {
+                let key = key.unboxed();
+                let key = key.as_string(env, "JSON object keys must be strings")?;
+                pairs.push((key, value.to_json(env)?));
+            }
+            // JSON objects are unordered, so sort keys for deterministic output
+            pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
+            return Ok(Json::Object(pairs.into_iter().collect::<Map<_, _>>()));
+        }
+        Ok(match self {
+            Value::Num(arr) => nested_json(&arr.shape, &arr.data, &|n| {
+                if n.fract() == 0.0 && n.abs() <= (1u64 << f64::MANTISSA_DIGITS) as f64 {
+                    Json::from(n as i64)
+                } else {
+                    Number::from_f64(n).map_or(Json::Null, Json::Number)
+                }
+            }),
+            #[cfg(feature = "bytes")]
+            Value::Byte(arr) if arr.meta().flags.is_boolean() => {
+                nested_json(&arr.shape, &arr.data, &|b| Json::Bool(b != 0))
+            }
+            #[cfg(feature = "bytes")]
+            Value::Byte(arr) => nested_json(&arr.shape, &arr.data, &Json::from),
+            Value::Int(arr) => nested_json(&arr.shape, &arr.data, &Json::from),
+            Value::Complex(_) => {
+                return Err(env.error("Complex numbers cannot be converted to JSON"))
+            }
+            Value::Char(arr) if arr.rank() <= 1 => Json::String(arr.data.iter().collect()),
+            Value::Box(arr) if arr.rank() == 0 => arr.data[0].0.to_json(env)?,
+            val => Json::Array(
+                val.rows()
+                    .map(|row| row.to_json(env))
+                    .collect::<UiuaResult<_>>()?,
+            ),
+        })
+    }
+    fn from_json(json: Json, env: &Uiua) -> UiuaResult<Self> {
+        Ok(match json {
+            Json::Null => f64::NAN.into(),
+            Json::Bool(b) => boolean(b),
+            Json::Number(n) => n.as_f64().unwrap_or(f64::NAN).into(),
+            Json::String(s) => s.into(),
+            Json::Array(items) => list(
+                items
+                    .into_iter()
+                    .map(|item| Self::from_json(item, env))
+                    .collect::<UiuaResult<_>>()?,
+            ),
+            Json::Object(object) => {
+                let mut keys = Vec::with_capacity(object.len());
+                let mut values = Vec::with_capacity(object.len());
+                for (key, value) in object {
+                    keys.push(Boxed(key.into()));
+                    values.push(Self::from_json(value, env)?);
+                }
+                let keys: Value = keys.into_iter().collect();
+                keys.map(list(values), env)?
+            }
+        })
+    }
+}
+
+fn nested_json<T: Copy>(shape: &[usize], data: &[T], f: &impl Fn(T) -> Json) -> Json {
+    match shape {
+        [] => f(data[0]),
+        [_, rest @ ..] => {
+            let row_len: usize = rest.iter().product();
+            Json::Array(if row_len == 0 {
+                vec![nested_json(rest, data, f); shape[0]]
+            } else {
+                (data.chunks_exact(row_len))
+                    .map(|row| nested_json(rest, row, f))
+                    .collect()
+            })
+        }
+    }
+}
+
+fn boolean(b: bool) -> Value {
+    #[cfg(feature = "bytes")]
+    let mut arr = Array::<u8>::from(b);
+    #[cfg(not(feature = "bytes"))]
+    let mut arr = Array::<f64>::from(b as u8 as f64);
+    arr.meta_mut().flags.set(ArrayFlags::BOOLEAN, true);
+    arr.into()
+}
+
+/// Make a list of JSON array items
+///
+/// Scalar numbers and booleans form a numeric list. Anything else is boxed.
+fn list(items: Vec<Value>) -> Value {
+    if items.is_empty() || !items.iter().all(is_numeric_scalar) {
+        return items.into_iter().map(Boxed).collect();
+    }
+    let all_booleans = (items.iter()).all(|val| val.meta().flags.is_boolean());
+    let mut list = Value::from_row_values_infallible(items);
+    if all_booleans {
+        list.meta_mut().flags.set(ArrayFlags::BOOLEAN, true);
+    }
+    list
+}
+
+fn is_numeric_scalar(val: &Value) -> bool {
+    val.rank() == 0
+        && match val {
+            Value::Num(_) => true,
+            #[cfg(feature = "bytes")]
+            Value::Byte(_) => true,
+            Value::Int(_) => true,
+            _ => false,
+        }
+}
@@ -0,0 +1,281 @@
+//! Linear algebra on rank-2 number arrays
+
+use ecow::EcoVec;
+
+use crate::{Array, Primitive, Uiua, UiuaResult, Value};
+
+/// A dense row-major matrix
+#[derive(Clone)]
+struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<f64>,
+}
+
+impl Matrix {
+    fn identity(n: usize) -> Self {
+        let mut data = vec![0.0; n * n];
+        for i in 0..n {
+            data[i * n + i] = 1.0;
+        }
+        Matrix {
+            rows: n,
+            cols: n,
+            data,
+        }
+    }
+    fn get(&self, i: usize, j: usize) -> f64 {
+        self.data[i * self.cols + j]
+    }
+    fn get_mut(&mut self, i: usize, j: usize) -> &mut f64 {
+        &mut self.data[i * self.cols + j]
+    }
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        for j in 0..self.cols {
+            self.data.swap(a * self.cols + j, b * self.cols + j);
+        }
+    }
+    fn into_array(self) -> Array<f64> {
+        Array::new(
+            [self.rows, self.cols],
+            self.data.into_iter().collect::<EcoVec<_>>(),
+        )
+    }
+}
+
+/// A pivoted LU decomposition
+struct Lu {
+    /// L below the diagonal and U on and above it
+    lu: Matrix,
+    /// The original row of each row
+    perm: Vec<usize>,
+    /// Whether the permutation is odd
+    odd: bool,
+    /// Whether a zero pivot was found
+    singular: bool,
+}
+
+impl Lu {
+    fn new(mut lu: Matrix) -> Self {
+        let n = lu.rows;
+        let mut perm: Vec<usize> = (0..n).collect();
+        let mut odd = false;
+        let mut singular = false;
+        for k in 0..n {
+            // Partial pivoting on the largest remaining magnitude
+            let pivot = (k..n)
+                .max_by(|&a, &b| lu.get(a, k).abs().total_cmp(&lu.get(b, k).abs()))
+                .unwrap();
+            if pivot != k {
+                lu.swap_rows(pivot, k);
+                perm.swap(pivot, k);
+                odd = !odd;
+            }
+            let p = lu.get(k, k);
+            if p == 0.0 {
+                singular = true;
+                continue;
+            }
+            for i in k + 1..n {
+                let factor = lu.get(i, k) / p;
+                *lu.get_mut(i, k) = factor;
+                for j in k + 1..n {
+                    let sub = factor * lu.get(k, j);
+                    *lu.get_mut(i, j) -= sub;
+                }
+            }
+        }
+        Lu {
+            lu,
+            perm,
+            odd,
+            singular,
+        }
+    }
+    fn det(&self) -> f64 {
+        let diag: f64 = (0..self.lu.rows).map(|i| self.lu.get(i, i)).product();
+        if self.odd {
+            -diag
+        } else {
+            diag
+        }
+    }
+    /// Solve for each column of `b`
+    fn solve(&self, b: &Matrix) -> Matrix {
+        let n = self.lu.rows;
+        let mut x = Matrix {
+            rows: n,
+            cols: b.cols,
+            data: Vec::with_capacity(n * b.cols),
+        };
+        for &p in &self.perm {
+            x.data.extend_from_slice(&b.data[p * b.cols..][..b.cols]);
+        }
+        for c in 0..b.cols {
+            // Forward substitution with the unit lower triangle
+            for i in 0..n {
+                let mut sum = x.get(i, c);
+                for k in 0..i {
+                    sum -= self.lu.get(i, k) * x.get(k, c);
+                }
+                *x.get_mut(i, c) = sum;
+            }
+            // Back substitution with the upper triangle
+            for i in (0..n).rev() {
+                let mut sum = x.get(i, c);
+                for k in i + 1..n {
+                    sum -= self.lu.get(i, k) * x.get(k, c);
+                }
+                *x.get_mut(i, c) = sum / self.lu.get(i, i);
+            }
+        }
+        x
+    }
+}
+
+impl Value {
+    fn matrix(&self, prim: Primitive, square: bool, env: &Uiua) -> UiuaResult<Matrix> {
+        let arr = match self {
+            Value::Num(arr) => arr.clone(),
+            #[cfg(feature = "bytes")]
+            Value::Byte(arr) => arr.convert_ref(),
+            Value::Int(arr) => arr.convert_ref(),
+            val => {
+                return Err(env.error(format!(
+                    "Argument to {} must be a matrix of numbers, but it is {}",
+                    prim.format(),
+                    val.type_name_plural()
+                )))
+            }
+        };
+        if arr.rank() != 2 {
+            return Err(env.error(format!(
+                "Argument to {} must be a rank 2 matrix, but it is rank {}",
+                prim.format(),
+                arr.rank()
+            )));
+        }
+        let (rows, cols) = (arr.shape[0], arr.shape[1]);
+        if square && rows != cols {
+            return Err(env.error(format!(
+                "Argument to {} must be a square matrix, but its shape is {}",
+                prim.format(),
+                arr.shape
+            )));
+        }
+        Ok(Matrix {
+            rows,
+            cols,
+            data: arr.data.iter().copied().collect(),
+        })
+    }
+    /// Get the determinant of a square matrix
+    pub(crate) fn det(&self, env: &Uiua) -> UiuaResult<f64> {
+        let m = self.matrix(Primitive::Det, true, env)?;
+        Ok(Lu::new(m).det())
+    }
+    /// Invert a square matrix
+    pub(crate) fn matrix_inverse(&self, env: &Uiua) -> UiuaResult<Array<f64>> {
+        let m = self.matrix(Primitive::MatrixInverse, true, env)?;
+        let n = m.rows;
+        let lu = Lu::new(m);
+        if lu.singular {
+            return Err(env.error("Cannot invert a singular matrix"));
+        }
+        Ok(lu.solve(&Matrix::identity(n)).into_array())
+    }
+    /// Solve the linear system `self × x = b` for `x`
+    pub(crate) fn solve(&self, b: &Self, env: &Uiua) -> UiuaResult<Value> {
+        let a = self.matrix(Primitive::Solve, true, env)?;
+        let b_rank = b.rank();
+        let b = match b_rank {
+            1 => {
+                let mut b = b.clone();
+                b.shape_mut().push(1);
+                b.matrix(Primitive::Solve, false, env)?
+            }
+            _ => b.matrix(Primitive::Solve, false, env)?,
+        };
+        if a.rows != b.rows {
+            return Err(env.error(format!(
+                "The matrix has {} rows, but the right-hand side has {}",
+                a.rows, b.rows
+            )));
+        }
+        let lu = Lu::new(a);
+        if lu.singular {
+            return Err(env.error("Cannot solve a system with a singular matrix"));
+        }
+        let mut x = lu.solve(&b).into_array();
+        if b_rank == 1 {
+            x.shape.pop();
+        }
+        Ok(x.into())
+    }
+    /// Get the pivoted LU decomposition of a square matrix
+    ///
+    /// Returns the permutation, lower, and upper matrices.
+    pub(crate) fn lu(&self, env: &Uiua) -> UiuaResult<(Array<f64>, Array<f64>, Array<f64>)> {
+        let m = self.matrix(Primitive::Lu, true, env)?;
+        let n = m.rows;
+        let Lu { lu, perm, .. } = Lu::new(m);
+        let mut p = Matrix {
+            rows: n,
+            cols: n,
+            data: vec![0.0; n * n],
+        };
+        let mut l = Matrix::identity(n);
+        let mut u = lu.clone();
+        for (i, &row) in perm.iter().enumerate() {
+            *p.get_mut(i, row) = 1.0;
+            for j in 0..i {
+                *l.get_mut(i, j) = lu.get(i, j);
+                *u.get_mut(i, j) = 0.0;
+            }
+        }
+        Ok((p.into_array(), l.into_array(), u.into_array()))
+    }
+    /// Get the QR decomposition of a matrix using Householder reflections
+    ///
+    /// Returns the orthogonal and upper triangular matrices.
+    pub(crate) fn qr(&self, env: &Uiua) -> UiuaResult<(Array<f64>, Array<f64>)> {
+        let mut r = self.matrix(Primitive::Qr, false, env)?;
+        let (m, n) = (r.rows, r.cols);
+        let mut q = Matrix::identity(m);
+        for k in 0..n.min(m.saturating_sub(1)) {
+            let norm = (k..m).map(|i| r.get(i, k).powi(2)).sum::<f64>().sqrt();
+            if norm == 0.0 {
+                continue;
+            }
+            let alpha = if r.get(k, k) < 0.0 { norm } else { -norm };
+            let mut v: Vec<f64> = (k..m).map(|i| r.get(i, k)).collect();
+            v[0] -= alpha;
+            let v_norm_sq: f64 = v.iter().map(|x| x * x).sum();
+            if v_norm_sq == 0.0 {
+                continue;
+            }
+            for j in 0..n {
+                let dot: f64 = (v.iter().enumerate())
+                    .map(|(i, vi)| vi * r.get(k + i, j))
+                    .sum();
+                let f = 2.0 * dot / v_norm_sq;
+                for (i, vi) in v.iter().enumerate() {
+                    *r.get_mut(k + i, j) -= f * vi;
+                }
+            }
+            for row in 0..m {
+                let dot: f64 = (v.iter().enumerate())
+                    .map(|(i, vi)| vi * q.get(row, k + i))
+                    .sum();
+                let f = 2.0 * dot / v_norm_sq;
+                for (i, vi) in v.iter().enumerate() {
+                    *q.get_mut(row, k + i) -= f * vi;
+                }
+            }
+            for i in k + 1..m {
+                *r.get_mut(i, k) = 0.0;
+            }
+        }
+        Ok((q.into_array(), r.into_array()))
+    }
+}
@@ -1,9 +1,11 @@
 //! Algorithms for looping modifiers
 
+use ecow::EcoVec;
+
 use crate::{
     array::{Array, ArrayValue},
     value::Value,
-    ExactDoubleIterator, Primitive, Signature, Uiua, UiuaResult,
+    ExactDoubleIterator, Function, Primitive, Signature, Uiua, UiuaResult,
 };
 
 use super::multi_output;
@@ -217,6 +219,10 @@ impl Value {
                 .partition_groups(markers, env)?
                 .map(Into::into)
                 .collect(),
+            Value::Int(arr) => arr
+                .partition_groups(markers, env)?
+                .map(Into::into)
+                .collect(),
             Value::Complex(arr) => arr
                 .partition_groups(markers, env)?
                 .map(Into::into)
@@ -279,6 +285,7 @@ impl Value {
             Value::Num(arr) => arr.group_groups(indices, env)?.map(Into::into).collect(),
             #[cfg(feature = "bytes")]
             Value::Byte(arr) => arr.group_groups(indices, env)?.map(Into::into).collect(),
+            Value::Int(arr) => arr.group_groups(indices, env)?.map(Into::into).collect(),
             Value::Complex(arr) => arr.group_groups(indices, env)?.map(Into::into).collect(),
             Value::Char(arr) => arr.group_groups(indices, env)?.map(Into::into).collect(),
             Value::Box(arr) => arr.group_groups(indices, env)?.map(Into::into).collect(),
@@ -310,6 +317,115 @@ impl<T: ArrayValue> Array<T> {
     }
 }
 
+pub fn group_by(env: &mut Uiua) -> UiuaResult {
+    crate::profile_function!();
+    let f = env.pop_function()?;
+    check_by_function(Primitive::GroupBy, &f, env)?;
+    let keys = env.pop(1)?;
+    let values = env.pop(2)?;
+    let indices = keys.classify();
+    let mut unique = keys;
+    unique.deduplicate();
+    env.push(values);
+    env.push(indices);
+    env.push_func(f);
+    group(env)?;
+    env.push(unique);
+    Ok(())
+}
+
+pub fn partition_by(env: &mut Uiua) -> UiuaResult {
+    crate::profile_function!();
+    let f = env.pop_function()?;
+    check_by_function(Primitive::PartitionBy, &f, env)?;
+    let keys = env.pop(1)?;
+    let values = env.pop(2)?;
+    // Each run of equal keys gets its own marker
+    let mut markers = EcoVec::with_capacity(keys.row_count());
+    let mut run_keys: Vec<Value> = Vec::new();
+    for key in keys.clone().into_rows() {
+        if run_keys.last() != Some(&key) {
+            run_keys.push(key);
+        }
+        markers.push(run_keys.len() as f64);
+    }
+    let unique = if run_keys.is_empty() {
+        keys.first_dim_zero()
+    } else {
+        Value::from_row_values(run_keys, env)?
+    };
+    env.push(values);
+    env.push(Array::from(markers));
+    env.push_func(f);
+    partition(env)?;
+    env.push(unique);
+    Ok(())
+}
+
+pub fn sort_by(env: &mut Uiua) -> UiuaResult {
+    crate::profile_function!();
+    let f = env.pop_function()?;
+    let sig = f.signature();
+    if sig.args != 1 || sig.outputs == 0 {
+        return Err(env.error(format!(
+            "{}'s function must take 1 argument and return at least 1 key, \
+            but its signature is {sig}",
+            Primitive::SortBy.format()
+        )));
+    }
+    let descending = env.pop(1)?.as_number_list(
+        env,
+        "Sort directions must be a list of 1s and ¯1s",
+        |n| n == 1.0 || n == -1.0,
+        |n| n < 0.0,
+    )?;
+    let xs = env.pop(2)?;
+    if xs.rank() == 0 {
+        return Err(env.error(format!("Cannot {} a scalar", Primitive::SortBy.format())));
+    }
+    if descending.len() != 1 && descending.len() != sig.outputs {
+        return Err(env.error(format!(
+            "{}'s function returns {} keys, but there are {} sort directions",
+            Primitive::SortBy.format(),
+            sig.outputs,
+            descending.len()
+        )));
+    }
+    env.push(xs.clone());
+    env.call(f)?;
+    // The key on top of the stack is the primary key
+    let mut keys = Vec::with_capacity(sig.outputs);
+    for i in 0..sig.outputs {
+        let key = env.pop(|| format!("{}'s key {}", Primitive::SortBy.format(), i + 1))?;
+        if key.rank() == 0 || key.row_count() != xs.row_count() {
+            return Err(env.error(format!(
+                "{}'s keys must have the same length as the array, \
+                but key {} has shape {} and the array has length {}",
+                Primitive::SortBy.format(),
+                i + 1,
+                key.shape(),
+                xs.row_count()
+            )));
+        }
+        let descending = descending[if descending.len() == 1 { 0 } else { i }];
+        keys.push((key, descending));
+    }
+    let indices: Value = (Value::rise_by_keys(xs.row_count(), &keys).into_iter()).collect();
+    env.push(indices.select(&xs, env)?);
+    Ok(())
+}
+
+fn check_by_function(prim: Primitive, f: &Function, env: &Uiua) -> UiuaResult {
+    let sig = f.signature();
+    if sig.args > 1 {
+        return Err(env.error(format!(
+            "{}'s function must take 0 or 1 arguments, but its signature is {sig}",
+            prim.format()
+        )));
+    }
+    Ok(())
+}
+
 fn collapse_groups(
     prim: Primitive,
     get_groups: impl Fn(Value, &[isize], &Uiua) -> UiuaResult<Vec<Value>>,
@@ -8,12 +8,15 @@ use std::{
 use ecow::EcoVec;
 
 use crate::{
-    algorithm::ArrayCmpSlice, cowslice::CowSlice, Array, ArrayMeta, ArrayValue, Boxed, Complex,
-    FormatShape, Uiua, UiuaResult, Value,
+    algorithm::ArrayCmpSlice, Array, ArrayMeta, ArrayValue, Boxed, Complex, FormatShape, Uiua,
+    UiuaResult, Value,
 };
 
 impl Value {
     /// Create a map array
+    ///
+    /// Entries keep the order of their first occurrence in the keys.
+    /// If a key appears more than once, its last value is used.
     pub fn map(self, values: Self, env: &Uiua) -> UiuaResult<Value> {
         if self.row_count() != values.row_count() {
             return Err(env.error(format!(
@@ -22,40 +25,41 @@ impl Value {
                 values.row_count()
             )));
         }
-        let capacity =
-            ((self.row_count() as f64 / LOAD_FACTOR).ceil() as usize).next_power_of_two();
+        let len = self.row_count();
         let mut kv = EcoVec::with_capacity(2);
         for x in [&self, &values] {
             kv.push(Boxed(match x {
-                Value::Num(_) => Array::<f64>::new(0, CowSlice::with_capacity(capacity)).into(),
+                Value::Num(_) => Array::<f64>::default().into(),
                 #[cfg(feature = "bytes")]
-                Value::Byte(_) => Array::<f64>::new(0, CowSlice::with_capacity(capacity)).into(),
-                Value::Complex(_) => {
-                    Array::<Complex>::new(0, CowSlice::with_capacity(capacity)).into()
-                }
-                _ => Array::<Boxed>::new(0, CowSlice::with_capacity(capacity)).into(),
+                Value::Byte(_) => Array::<f64>::default().into(),
+                Value::Complex(_) => Array::<Complex>::default().into(),
+                _ => Array::<Boxed>::default().into(),
             }))
         }
         let mut map = Value::Box(Array::new(2, kv));
         map.meta_mut().map_len = Some(0);
-        for (key, value) in self.into_rows().zip(values.into_rows()) {
-            map.insert(key, value, env)?;
-        }
+        map.meta_mut().map_order = Some(EcoVec::with_capacity(len));
+        with_pair_mut(&mut map, env, |mut pair| {
+            let mut pairs = self.into_rows().zip(values.into_rows());
+            // The first insertion fixes the shapes of the keys and values,
+            // so the table can only be sized for all of them after it
+            if let Some((key, value)) = pairs.next() {
+                pair.insert(key, value, env)?;
+                pair.reserve(len);
+            }
+            pairs.try_for_each(|(key, value)| pair.insert(key, value, env))
+        })??;
         Ok(map)
     }
-    /// Turn a map array into its keys and values
+    /// Turn a map array into its keys and values, in insertion order
     pub fn unmap(mut self, env: &Uiua) -> UiuaResult<(Value, Value)> {
-        with_pair_mut(&mut self, env, |_| {})?;
+        let order = with_pair_mut(&mut self, env, |mut pair| pair.order().clone())?;
         let mut rows = self.into_rows().map(|row| row.unboxed());
-        let keys = (rows.next().unwrap().into_rows())
-            .filter(|row| !(row.is_empty_cell() || row.is_tombstone()))
-            .collect::<Vec<_>>();
-        let values = (rows.next().unwrap().into_rows())
-            .filter(|row| !(row.is_empty_cell() || row.is_tombstone()))
-            .collect::<Vec<_>>();
+        let keys = rows.next().unwrap();
+        let values = rows.next().unwrap();
         Ok((
-            Value::from_row_values_infallible(keys),
-            Value::from_row_values_infallible(values),
+            Value::from_row_values_infallible(order.iter().map(|&slot| keys.row(slot))),
+            Value::from_row_values_infallible(order.iter().map(|&slot| values.row(slot))),
         ))
     }
     /// Get a value from a map array
@@ -85,6 +89,11 @@ impl Value {
 
 const LOAD_FACTOR: f64 = 0.75;
 
+/// The table capacity needed to hold `len` entries
+fn capacity_for(len: usize) -> usize {
+    ((len as f64 / LOAD_FACTOR).ceil() as usize).next_power_of_two()
+}
+
 // A NaN value used as empty, not the standard NaN.
 pub const EMPTY_NAN: f64 =
     unsafe { std::mem::transmute(0x7ff8_0000_0000_0000u64 | 0x0000_0000_0000_0001) };
@@ -92,6 +101,10 @@ pub const EMPTY_NAN: f64 =
 pub const TOMBSTONE_NAN: f64 =
     unsafe { std::mem::transmute(0x7ff8_0000_0000_0000u64 | 0x0000_0000_0000_0002) };
 
+/// Get the starting slot of a key
+///
+/// Keys are hashed with [`ArrayValue::array_hash`], so all NaNs hash the same,
+/// and `¯0` hashes the same as `0`. This matches how keys are compared.
 fn hash_start<T: ArrayValue>(arr: &Array<T>, capacity: usize) -> usize {
     let mut hasher = DefaultHasher::new();
     arr.hash(&mut hasher);
@@ -100,7 +113,7 @@ fn hash_start<T: ArrayValue>(arr: &Array<T>, capacity: usize) -> usize {
 
 fn coerce_values(
     a: &mut Value,
-    #[allow(unused_mut)] mut b: Value,
+    mut b: Value,
     action1: &'static str,
     action2: &'static str,
     action3: &'static str,
@@ -114,6 +127,12 @@ fn coerce_values(
             b = Value::Num(values.convert_ref());
         }
     }
+    if let Value::Int(keys) = a {
+        *a = Value::Num(keys.convert_ref());
+    }
+    if let Value::Int(values) = b {
+        b = Value::Num(values.convert_ref());
+    }
     match (&mut *a, b) {
         (Value::Num(arr), Value::Num(num)) if arr.row_count() == 0 => {
             let mut shape = num.shape.clone();
@@ -193,6 +212,34 @@ fn coerce_values(
     }
 }
 
+/// Get the slots of a map's entries in insertion order
+///
+/// Maps without a recorded order, such as ones built by hand,
+/// use the order of their slots.
+pub(crate) fn ordered_slots(meta: &ArrayMeta, keys: &Value) -> EcoVec<usize> {
+    if let Some(order) = &meta.map_order {
+        return order.clone();
+    }
+    fn live_slots<T: MapItem + ArrayValue>(arr: &Array<T>) -> EcoVec<usize> {
+        (arr.row_slices().enumerate())
+            .filter(|(_, row)| {
+                !row.first()
+                    .is_some_and(|k| k.is_empty_cell() || k.is_tombstone())
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+    match keys {
+        Value::Num(arr) => live_slots(arr),
+        #[cfg(feature = "bytes")]
+        Value::Byte(arr) => (0..arr.row_count()).collect(),
+        Value::Int(arr) => (0..arr.row_count()).collect(),
+        Value::Complex(arr) => live_slots(arr),
+        Value::Char(arr) => live_slots(arr),
+        Value::Box(arr) => live_slots(arr),
+    }
+}
+
 struct Pair<'a> {
     keys: &'a Value,
     values: &'a Value,
@@ -315,22 +362,24 @@ impl<'a> Pair<'a> {
             |arr| hash_start(arr, self.capacity()),
             |arr| hash_start(arr, self.capacity()),
             |arr| hash_start(arr, self.capacity()),
+            |arr| hash_start(arr, self.capacity()),
         )
     }
     fn get(&self, key: &Value) -> Option<Value> {
-        if self.keys.shape() == [0] {
+        if self.capacity() == 0 {
             return None;
         }
         let start = self.hash_start(key);
         let mut index = start;
         loop {
             let row_key = self.keys.row(index);
-            if key.unpacked_ref() == row_key.unpacked_ref() {
-                return Some(self.values.row(index));
-            }
+            // Check for empty cells first, as they are NaNs and would equal a NaN key
             if row_key.is_empty_cell() {
                 return None;
             }
+            if !row_key.is_tombstone() && key.unpacked_ref() == row_key.unpacked_ref() {
+                return Some(self.values.row(index));
+            }
             index = (index + 1) % self.capacity();
             if index == start {
                 break None;
@@ -353,6 +402,7 @@ impl<'a> PairMut<'a> {
                 .count(),
             #[cfg(feature = "bytes")]
             Value::Byte(arr) => arr.row_count(),
+            Value::Int(arr) => arr.row_count(),
             Value::Complex(arr) => (arr.rows())
                 .filter(|row| !(row.data[0].is_empty_cell() || row.data[0].is_tombstone()))
                 .count(),
@@ -366,93 +416,126 @@ impl<'a> PairMut<'a> {
         self.meta.map_len = Some(len);
         len
     }
+    /// Get the slots of the entries in insertion order
+    fn order(&mut self) -> &mut EcoVec<usize> {
+        let keys = &*self.keys;
+        (self.meta.map_order).get_or_insert_with(|| ordered_slots(&ArrayMeta::default(), keys))
+    }
+    /// Grow the table if it is too full
     fn grow(&mut self) {
         if self.capacity() == 0 || (self.len() as f64 / self.capacity() as f64) > LOAD_FACTOR {
-            fn grow_impl<K, V>(keys: &mut Array<K>, values: &mut Array<V>, new_capacity: usize)
-            where
-                K: MapItem + ArrayValue,
-                V: MapItem + ArrayValue,
-            {
-                let key_row_len = keys.row_len();
-                let value_row_len = values.row_len();
-                let mut keys_shape = keys.shape.clone();
-                keys_shape[0] = new_capacity;
-                let mut values_shape = values.shape.clone();
-                values_shape[0] = new_capacity;
-                let old_keys = take(keys).into_rows();
-                let old_values = take(values).into_rows();
-                *keys = Array::new(
-                    keys_shape,
-                    repeat(K::empty_cell())
-                        .take(new_capacity * key_row_len)
-                        .collect::<EcoVec<_>>(),
-                );
-                *values = Array::new(
-                    values_shape,
-                    repeat(V::empty_cell())
-                        .take(new_capacity * value_row_len)
-                        .collect::<EcoVec<_>>(),
-                );
-                let key_data = keys.data.as_mut_slice();
-                let value_data = values.data.as_mut_slice();
-                for (key, value) in old_keys.zip(old_values) {
-                    let start = hash_start(&key, new_capacity);
-                    let mut index = start;
-                    loop {
-                        let cell_key =
-                            &mut key_data[index * key_row_len..(index + 1) * key_row_len];
-                        if cell_key[0].is_empty_cell() {
-                            cell_key.clone_from_slice(&key.data);
-                            value_data[index * value_row_len..(index + 1) * value_row_len]
-                                .clone_from_slice(&value.data);
-                            break;
-                        }
-                        index = (index + 1) % new_capacity;
+            self.grow_to((self.capacity() * 2).max(1));
+        }
+    }
+    /// Make sure the table can hold `len` entries without growing
+    fn reserve(&mut self, len: usize) {
+        let capacity = capacity_for(len);
+        if capacity > self.capacity() {
+            self.grow_to(capacity);
+        }
+    }
+    /// Rehash the entries into a table with the given capacity
+    fn grow_to(&mut self, new_cap: usize) {
+        fn grow_impl<K, V>(
+            keys: &mut Array<K>,
+            values: &mut Array<V>,
+            order: &mut [usize],
+            new_capacity: usize,
+        ) where
+            K: MapItem + ArrayValue,
+            V: MapItem + ArrayValue,
+        {
+            let key_row_len = keys.row_len();
+            let value_row_len = values.row_len();
+            let mut keys_shape = keys.shape.clone();
+            keys_shape[0] = new_capacity;
+            let mut values_shape = values.shape.clone();
+            values_shape[0] = new_capacity;
+            let old_keys = take(keys);
+            let old_values = take(values);
+            *keys = Array::new(
+                keys_shape,
+                repeat(K::empty_cell())
+                    .take(new_capacity * key_row_len)
+                    .collect::<EcoVec<_>>(),
+            );
+            *values = Array::new(
+                values_shape,
+                repeat(V::empty_cell())
+                    .take(new_capacity * value_row_len)
+                    .collect::<EcoVec<_>>(),
+            );
+            let key_data = keys.data.as_mut_slice();
+            let value_data = values.data.as_mut_slice();
+            // Only live entries are moved, so tombstones are cleared
+            for slot in order {
+                let key = old_keys.row_slice(*slot);
+                let value = old_values.row_slice(*slot);
+                let start = hash_start(&old_keys.row(*slot), new_capacity);
+                let mut index = start;
+                loop {
+                    let cell_key = &mut key_data[index * key_row_len..(index + 1) * key_row_len];
+                    if cell_key[0].is_empty_cell() {
+                        cell_key.clone_from_slice(key);
+                        value_data[index * value_row_len..(index + 1) * value_row_len]
+                            .clone_from_slice(value);
+                        break;
                     }
+                    index = (index + 1) % new_capacity;
                 }
+                *slot = index;
             }
+        }
 
-            let new_cap = (self.capacity() * 2).max(1);
-            let len = self.len();
-            self.meta.map_len = Some(len);
-            #[cfg(feature = "bytes")]
-            {
-                if let Value::Byte(keys) = self.keys {
-                    *self.keys = Value::Num(keys.convert_ref());
-                }
-                if let Value::Byte(values) = self.values {
-                    *self.values = Value::Num(values.convert_ref());
-                }
+        let len = self.len();
+        self.meta.map_len = Some(len);
+        let mut order = take(self.order());
+        #[cfg(feature = "bytes")]
+        {
+            if let Value::Byte(keys) = self.keys {
+                *self.keys = Value::Num(keys.convert_ref());
             }
-            match (&mut *self.keys, &mut *self.values) {
-                (Value::Num(a), Value::Num(b)) => grow_impl(a, b, new_cap),
-                (Value::Num(a), Value::Complex(b)) => grow_impl(a, b, new_cap),
-                (Value::Num(a), Value::Char(b)) => grow_impl(a, b, new_cap),
-                (Value::Num(a), Value::Box(b)) => grow_impl(a, b, new_cap),
-                (Value::Complex(a), Value::Num(b)) => grow_impl(a, b, new_cap),
-                (Value::Complex(a), Value::Complex(b)) => grow_impl(a, b, new_cap),
-                (Value::Complex(a), Value::Char(b)) => grow_impl(a, b, new_cap),
-                (Value::Complex(a), Value::Box(b)) => grow_impl(a, b, new_cap),
-                (Value::Char(a), Value::Num(b)) => grow_impl(a, b, new_cap),
-                (Value::Char(a), Value::Complex(b)) => grow_impl(a, b, new_cap),
-                (Value::Char(a), Value::Char(b)) => grow_impl(a, b, new_cap),
-                (Value::Char(a), Value::Box(b)) => grow_impl(a, b, new_cap),
-                (Value::Box(a), Value::Num(b)) => grow_impl(a, b, new_cap),
-                (Value::Box(a), Value::Complex(b)) => grow_impl(a, b, new_cap),
-                (Value::Box(a), Value::Char(b)) => grow_impl(a, b, new_cap),
-                (Value::Box(a), Value::Box(b)) => grow_impl(a, b, new_cap),
-                #[cfg(feature = "bytes")]
-                (Value::Num(_), Value::Byte(_))
-                | (Value::Byte(_), Value::Num(_))
-                | (Value::Byte(_), Value::Byte(_))
-                | (Value::Byte(_), Value::Complex(_))
-                | (Value::Byte(_), Value::Char(_))
-                | (Value::Byte(_), Value::Box(_))
-                | (Value::Complex(_), Value::Byte(_))
-                | (Value::Char(_), Value::Byte(_))
-                | (Value::Box(_), Value::Byte(_)) => unreachable!(),
+            if let Value::Byte(values) = self.values {
+                *self.values = Value::Num(values.convert_ref());
             }
         }
+        if let Value::Int(keys) = self.keys {
+            *self.keys = Value::Num(keys.convert_ref());
+        }
+        if let Value::Int(values) = self.values {
+            *self.values = Value::Num(values.convert_ref());
+        }
+        let slots = order.make_mut();
+        match (&mut *self.keys, &mut *self.values) {
+            (Value::Num(a), Value::Num(b)) => grow_impl(a, b, slots, new_cap),
+            (Value::Num(a), Value::Complex(b)) => grow_impl(a, b, slots, new_cap),
+            (Value::Num(a), Value::Char(b)) => grow_impl(a, b, slots, new_cap),
+            (Value::Num(a), Value::Box(b)) => grow_impl(a, b, slots, new_cap),
+            (Value::Complex(a), Value::Num(b)) => grow_impl(a, b, slots, new_cap),
+            (Value::Complex(a), Value::Complex(b)) => grow_impl(a, b, slots, new_cap),
+            (Value::Complex(a), Value::Char(b)) => grow_impl(a, b, slots, new_cap),
+            (Value::Complex(a), Value::Box(b)) => grow_impl(a, b, slots, new_cap),
+            (Value::Char(a), Value::Num(b)) => grow_impl(a, b, slots, new_cap),
+            (Value::Char(a), Value::Complex(b)) => grow_impl(a, b, slots, new_cap),
+            (Value::Char(a), Value::Char(b)) => grow_impl(a, b, slots, new_cap),
+            (Value::Char(a), Value::Box(b)) => grow_impl(a, b, slots, new_cap),
+            (Value::Box(a), Value::Num(b)) => grow_impl(a, b, slots, new_cap),
+            (Value::Box(a), Value::Complex(b)) => grow_impl(a, b, slots, new_cap),
+            (Value::Box(a), Value::Char(b)) => grow_impl(a, b, slots, new_cap),
+            (Value::Box(a), Value::Box(b)) => grow_impl(a, b, slots, new_cap),
+            #[cfg(feature = "bytes")]
+            (Value::Num(_), Value::Byte(_))
+            | (Value::Byte(_), Value::Num(_))
+            | (Value::Byte(_), Value::Byte(_))
+            | (Value::Byte(_), Value::Complex(_))
+            | (Value::Byte(_), Value::Char(_))
+            | (Value::Byte(_), Value::Box(_))
+            | (Value::Complex(_), Value::Byte(_))
+            | (Value::Char(_), Value::Byte(_))
+            | (Value::Box(_), Value::Byte(_)) => unreachable!(),
+            (Value::Int(_), _) | (_, Value::Int(_)) => unreachable!(),
+        }
+        self.meta.map_order = Some(order);
     }
     fn insert(&mut self, key: Value, value: Value, env: &Uiua) -> UiuaResult {
         fn insert_impl<K, V>(
@@ -473,24 +556,38 @@ impl<'a> PairMut<'a> {
             let value_row_len = values.row_len();
             let key_data = keys.data.as_mut_slice();
             let value_data = values.data.as_mut_slice();
-            loop {
-                let cell_key = &mut key_data[index * key_row_len..(index + 1) * key_row_len];
-                let not_present = cell_key[0].is_empty_cell() || cell_key[0].is_tombstone();
-                if not_present || ArrayCmpSlice(cell_key) == ArrayCmpSlice(&key.data) {
-                    if not_present {
-                        let len = meta.map_len.unwrap();
-                        meta.map_len = Some(len + 1);
-                    }
-                    cell_key.clone_from_slice(&key.data);
+            // The key may be further along the probe sequence than a tombstone,
+            // so the first tombstone is only reused once the key is known to be absent
+            let mut tombstone = None;
+            let slot = loop {
+                let cell_key = &key_data[index * key_row_len..(index + 1) * key_row_len];
+                if cell_key[0].is_empty_cell() {
+                    break tombstone.unwrap_or(index);
+                }
+                if cell_key[0].is_tombstone() {
+                    tombstone = tombstone.or(Some(index));
+                } else if ArrayCmpSlice(cell_key) == ArrayCmpSlice(&key.data) {
+                    // Replacing a value keeps the entry's position in the order
                     value_data[index * value_row_len..(index + 1) * value_row_len]
                         .clone_from_slice(&value.data);
-                    break None;
+                    return None;
                 }
                 index = (index + 1) % capacity;
                 if index == start {
-                    break Some((key, value));
+                    match tombstone {
+                        Some(slot) => break slot,
+                        None => return Some((key, value)),
+                    }
                 }
+            };
+            key_data[slot * key_row_len..(slot + 1) * key_row_len].clone_from_slice(&key.data);
+            value_data[slot * value_row_len..(slot + 1) * value_row_len]
+                .clone_from_slice(&value.data);
+            meta.map_len = meta.map_len.map(|len| len + 1);
+            if let Some(order) = &mut meta.map_order {
+                order.push(slot);
             }
+            None
         }
         let key = coerce_values(self.keys, key, "insert", "key into map with", "keys")
             .map_err(|e| env.error(e))?;
@@ -505,6 +602,9 @@ impl<'a> PairMut<'a> {
         if self.capacity() == 0 {
             self.grow();
         }
+        // Make sure the length and order are known before insert_impl updates them
+        self.len();
+        self.order();
         let capacity = self.capacity();
         macro_rules! do_insert {
             ($(($k:ident, $v:ident),)*) => {
@@ -579,10 +679,19 @@ impl<'a> PairMut<'a> {
             let value_data = values.data.as_mut_slice();
             loop {
                 let cell_key = &mut key_data[index * key_row_len..(index + 1) * key_row_len];
-                if ArrayCmpSlice(cell_key) == ArrayCmpSlice(&key.data) {
+                // Check for empty cells first, as they are NaNs and would equal a NaN key
+                if cell_key[0].is_empty_cell() {
+                    break;
+                }
+                if !cell_key[0].is_tombstone()
+                    && ArrayCmpSlice(&*cell_key) == ArrayCmpSlice(&key.data)
+                {
                     if let Some(len) = meta.map_len {
                         meta.map_len = Some(len - 1);
                     }
+                    if let Some(order) = &mut meta.map_order {
+                        order.retain(|slot| *slot != index);
+                    }
                     for elem in cell_key {
                         *elem = K::tombstone_cell();
                     }
@@ -592,9 +701,6 @@ impl<'a> PairMut<'a> {
                     }
                     break;
                 }
-                if cell_key[0].is_empty_cell() {
-                    break;
-                }
                 index = (index + 1) % capacity;
                 if index == start {
                     break;
@@ -604,6 +710,10 @@ impl<'a> PairMut<'a> {
         let key = coerce_values(self.keys, key, "remove", "key from map with", "keys")
             .map_err(|e| env.error(e))?;
         let capacity = self.capacity();
+        if capacity == 0 {
+            return Ok(());
+        }
+        self.order();
         macro_rules! do_remove {
             ($(($k:ident, $v:ident),)*) => {
                 match ((&mut *self.keys, key), &mut *self.values) {
@@ -726,12 +836,13 @@ impl MapItem for Value {
     }
     fn is_empty_cell(&self) -> bool {
         match self {
-            Value::Num(num) => num.as_scalar().is_some_and(f64::is_empty_cell),
+            Value::Num(num) => num.data.first().is_some_and(f64::is_empty_cell),
             #[cfg(feature = "bytes")]
             Value::Byte(_) => false,
-            Value::Complex(num) => num.as_scalar().is_some_and(Complex::is_empty_cell),
-            Value::Char(num) => num.as_scalar().is_some_and(char::is_empty_cell),
-            Value::Box(num) => num.as_scalar().is_some_and(Boxed::is_empty_cell),
+            Value::Int(_) => false,
+            Value::Complex(num) => num.data.first().is_some_and(Complex::is_empty_cell),
+            Value::Char(num) => num.data.first().is_some_and(char::is_empty_cell),
+            Value::Box(num) => num.data.first().is_some_and(Boxed::is_empty_cell),
         }
     }
     fn tombstone_cell() -> Self {
@@ -739,12 +850,13 @@ impl MapItem for Value {
     }
     fn is_tombstone(&self) -> bool {
         match self {
-            Value::Num(num) => num.as_scalar().is_some_and(f64::is_tombstone),
+            Value::Num(num) => num.data.first().is_some_and(f64::is_tombstone),
             #[cfg(feature = "bytes")]
             Value::Byte(_) => false,
-            Value::Complex(num) => num.as_scalar().is_some_and(Complex::is_tombstone),
-            Value::Char(num) => num.as_scalar().is_some_and(char::is_tombstone),
-            Value::Box(num) => num.as_scalar().is_some_and(Boxed::is_tombstone),
+            Value::Int(_) => false,
+            Value::Complex(num) => num.data.first().is_some_and(Complex::is_tombstone),
+            Value::Char(num) => num.data.first().is_some_and(char::is_tombstone),
+            Value::Box(num) => num.data.first().is_some_and(Boxed::is_tombstone),
         }
     }
 }
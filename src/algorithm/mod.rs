@@ -2,6 +2,7 @@
 
 use std::{
     cmp::Ordering,
+    collections::HashMap,
     convert::Infallible,
     hash::{Hash, Hasher},
 };
@@ -13,14 +14,25 @@ use crate::{
     UiuaResult, Value,
 };
 
+mod binary;
+mod csv;
 mod dyadic;
+pub(crate) mod exact;
+mod fft;
+mod hash;
 pub(crate) mod invert;
+mod json;
+#[cfg(feature = "linalg")]
+mod linalg;
 pub mod loops;
 pub(crate) mod map;
 mod monadic;
 pub mod pervade;
 pub mod reduce;
 pub mod table;
+mod text;
+pub(crate) mod time;
+mod uval;
 pub mod zip;
 
 type MultiOutput<T> = TinyVec<[T; 1]>;
@@ -297,6 +309,20 @@ pub fn all(env: &mut Uiua) -> UiuaResult {
 }
 
 pub fn switch(count: usize, sig: Signature, env: &mut Uiua) -> UiuaResult {
+    if let Some(f) = switch_select(count, sig, env)? {
+        env.call(f)?;
+    }
+    Ok(())
+}
+
+/// Run a switch, except that a single selected function is returned rather than called
+///
+/// This lets a switch in tail position jump to its branch.
+pub(crate) fn switch_select(
+    count: usize,
+    sig: Signature,
+    env: &mut Uiua,
+) -> UiuaResult<Option<Function>> {
     // Get selector
     let selector = env
         .pop("switch index")?
@@ -333,7 +359,7 @@ pub fn switch(count: usize, sig: Signature, env: &mut Uiua) -> UiuaResult {
             return Err(env.error("Stack was empty when discarding excess switch arguments."));
         }
         env.rt.stack.drain(discard_start..discard_end);
-        env.call(f)
+        Ok(Some(f))
     } else {
         // Array
         // Collect arguments
@@ -388,15 +414,15 @@ pub fn switch(count: usize, sig: Signature, env: &mut Uiua) -> UiuaResult {
             *new_value.shape_mut() = new_shape;
             env.push(new_value);
         }
-        Ok(())
+        Ok(None)
     }
 }
 
-/// If a function fails on a byte array because no fill byte is defined,
-/// convert the byte array to a number array and try again.
-fn op_bytes_retry_fill<T>(
-    bytes: Array<u8>,
-    on_bytes: impl FnOnce(Array<u8>) -> UiuaResult<T>,
+/// If a function fails on a byte or index array because no fill of its type is defined,
+/// convert the array to a number array and try again.
+fn op_bytes_retry_fill<A: ArrayValue + Into<f64>, T>(
+    bytes: Array<A>,
+    on_bytes: impl FnOnce(Array<A>) -> UiuaResult<T>,
     on_nums: impl FnOnce(Array<f64>) -> UiuaResult<T>,
 ) -> UiuaResult<T> {
     match on_bytes(bytes.clone()) {
@@ -406,12 +432,11 @@ fn op_bytes_retry_fill<T>(
     }
 }
 
-/// If a function fails on a byte array because no fill byte is defined,
-/// convert the byte array to a number array and try again.
-#[cfg(feature = "bytes")]
-fn op_bytes_ref_retry_fill<T>(
-    bytes: &Array<u8>,
-    on_bytes: impl FnOnce(&Array<u8>) -> UiuaResult<T>,
+/// If a function fails on a byte or index array because no fill of its type is defined,
+/// convert the array to a number array and try again.
+fn op_bytes_ref_retry_fill<A: ArrayValue + Into<f64>, T>(
+    bytes: &Array<A>,
+    on_bytes: impl FnOnce(&Array<A>) -> UiuaResult<T>,
     on_nums: impl FnOnce(&Array<f64>) -> UiuaResult<T>,
 ) -> UiuaResult<T> {
     match on_bytes(bytes) {
@@ -421,14 +446,13 @@ fn op_bytes_ref_retry_fill<T>(
     }
 }
 
-/// If a function fails on 2 byte arrays because no fill byte is defined,
-/// convert the byte arrays to number arrays and try again.
-#[cfg(feature = "bytes")]
-fn op2_bytes_retry_fill<T, C: FillContext>(
-    a: Array<u8>,
-    b: Array<u8>,
+/// If a function fails on 2 byte or index arrays because no fill of their type is defined,
+/// convert the arrays to number arrays and try again.
+fn op2_bytes_retry_fill<A: ArrayValue + Into<f64>, T, C: FillContext>(
+    a: Array<A>,
+    b: Array<A>,
     ctx: &C,
-    on_bytes: impl FnOnce(Array<u8>, Array<u8>) -> Result<T, C::Error>,
+    on_bytes: impl FnOnce(Array<A>, Array<A>) -> Result<T, C::Error>,
     on_nums: impl FnOnce(Array<f64>, Array<f64>) -> Result<T, C::Error>,
 ) -> Result<T, C::Error> {
     if ctx.scalar_fill::<f64>().is_ok() {
@@ -476,3 +500,38 @@ impl<'a, T: ArrayValue> Hash for ArrayCmpSlice<'a, T> {
         }
     }
 }
+
+/// The number of row comparisons below which [`RowLookup`] does not hash
+const ROW_LOOKUP_SCAN_LIMIT: usize = 256;
+
+/// A way to find the first index of rows in an array
+///
+/// The rows are only hashed if comparing every looked-up row to every row would be slower.
+enum RowLookup<'a, T> {
+    Scan(&'a Array<T>),
+    Hash(HashMap<ArrayCmpSlice<'a, T>, usize>),
+}
+
+impl<'a, T: ArrayValue> RowLookup<'a, T> {
+    /// Make a lookup for the rows of an array that will be searched `lookups` times
+    fn new(arr: &'a Array<T>, lookups: usize) -> Self {
+        if arr.row_count().saturating_mul(lookups) <= ROW_LOOKUP_SCAN_LIMIT {
+            RowLookup::Scan(arr)
+        } else {
+            let mut indices = HashMap::with_capacity(arr.row_count());
+            for (i, row) in arr.row_slices().enumerate() {
+                indices.entry(ArrayCmpSlice(row)).or_insert(i);
+            }
+            RowLookup::Hash(indices)
+        }
+    }
+    /// Get the index of the first row that matches
+    fn get(&self, row: &[T]) -> Option<usize> {
+        match self {
+            RowLookup::Scan(arr) => {
+                (arr.row_slices()).position(|r| ArrayCmpSlice(r) == ArrayCmpSlice(row))
+            }
+            RowLookup::Hash(indices) => indices.get(&ArrayCmpSlice(row)).copied(),
+        }
+    }
+}
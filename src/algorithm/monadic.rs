@@ -46,6 +46,7 @@ impl Value {
             Value::Num(n) => n.deshape_depth(depth),
             #[cfg(feature = "bytes")]
             Value::Byte(b) => b.deshape_depth(depth),
+            Value::Int(b) => b.deshape_depth(depth),
             Value::Complex(c) => c.deshape_depth(depth),
             Value::Char(c) => c.deshape_depth(depth),
             Value::Box(b) => {
@@ -130,6 +131,13 @@ impl Value {
                     .collect();
                 Array::new(bytes.shape.clone(), new_data).into()
             }
+            Value::Int(ints) => {
+                let new_data: CowSlice<Boxed> = (ints.data.iter().map(|v| v.grid_string(false)))
+                    .map(Value::from)
+                    .map(Boxed)
+                    .collect();
+                Array::new(ints.shape.clone(), new_data).into()
+            }
             Value::Complex(complexes) => {
                 let new_data: CowSlice<Boxed> =
                     (complexes.data.iter().map(|v| v.grid_string(false)))
@@ -164,10 +172,10 @@ impl Value {
             or a list of natural numbers",
         )?;
         if self.rank() == 0 {
-            return Ok((0..shape[0]).collect());
+            return Ok(Value::from_indices(shape[0], 0..shape[0]));
         }
         if shape.is_empty() {
-            return Ok(Array::<f64>::new(0, CowSlice::new()).into());
+            return Ok(Array::<u32>::new(0, CowSlice::new()).into());
         }
         let mut shape = Shape::from(shape.as_slice());
         let data = range(&shape, env)?;
@@ -176,9 +184,9 @@ impl Value {
     }
 }
 
-fn range(shape: &[usize], env: &Uiua) -> UiuaResult<CowSlice<f64>> {
+fn range(shape: &[usize], env: &Uiua) -> UiuaResult<CowSlice<u32>> {
     if shape.is_empty() {
-        return Ok(cowslice![0.0]);
+        return Ok(cowslice![0]);
     }
     if shape.contains(&0) {
         return Ok(CowSlice::new());
@@ -197,13 +205,13 @@ fn range(shape: &[usize], env: &Uiua) -> UiuaResult<CowSlice<f64>> {
         }
         len = new;
     }
-    let mut data: EcoVec<f64> = eco_vec![0.0; len];
+    let mut data: EcoVec<u32> = eco_vec![0; len];
     let data_slice = data.make_mut();
     let mut curr = vec![0; shape.len()];
     let mut i = 0;
     loop {
         for d in &curr {
-            data_slice[i] = *d as f64;
+            data_slice[i] = *d as u32;
             i += 1;
         }
         let mut j = shape.len() - 1;
@@ -234,6 +242,13 @@ impl Value {
                     |a| a.first(env).map(Into::into),
                 )
             },
+            |a| {
+                op_bytes_retry_fill(
+                    a,
+                    |a| a.first(env).map(Into::into),
+                    |a| a.first(env).map(Into::into),
+                )
+            },
             |a| a.first(env).map(Into::into),
             |a| a.first(env).map(Into::into),
             |a| a.first(env).map(Into::into),
@@ -250,6 +265,13 @@ impl Value {
                     |a| a.last(env).map(Into::into),
                 )
             },
+            |a| {
+                op_bytes_retry_fill(
+                    a,
+                    |a| a.last(env).map(Into::into),
+                    |a| a.last(env).map(Into::into),
+                )
+            },
             |a| a.last(env).map(Into::into),
             |a| a.last(env).map(Into::into),
             |a| a.last(env).map(Into::into),
@@ -264,6 +286,7 @@ impl Value {
                 |a, b| a.unfirst(b, env).map(Into::into),
                 |a, b| a.unfirst(b, env).map(Into::into),
                 |a, b| a.unfirst(b, env).map(Into::into),
+                |a, b| a.unfirst(b, env).map(Into::into),
                 |a, b| {
                     env.error(format!(
                         "Cannot invert first of {} into {}",
@@ -283,6 +306,7 @@ impl Value {
                 |a, b| a.unlast(b, env).map(Into::into),
                 |a, b| a.unlast(b, env).map(Into::into),
                 |a, b| a.unlast(b, env).map(Into::into),
+                |a, b| a.unlast(b, env).map(Into::into),
                 |a, b| {
                     env.error(format!(
                         "Cannot invert last of {} into {}",
@@ -361,6 +385,7 @@ impl Value {
             |a| a.reverse_depth(depth),
             |a| a.reverse_depth(depth),
             |a| a.reverse_depth(depth),
+            |a| a.reverse_depth(depth),
         )
     }
 }
@@ -406,6 +431,7 @@ impl Value {
             Array::transpose,
             Array::transpose,
             Array::transpose,
+            Array::transpose,
         )
     }
     pub(crate) fn transpose_depth(&mut self, depth: usize, amnt: i32) {
@@ -413,6 +439,7 @@ impl Value {
             Value::Num(n) => n.transpose_depth(depth, amnt),
             #[cfg(feature = "bytes")]
             Value::Byte(b) => b.transpose_depth(depth, amnt),
+            Value::Int(b) => b.transpose_depth(depth, amnt),
             Value::Complex(c) => c.transpose_depth(depth, amnt),
             Value::Char(c) => c.transpose_depth(depth, amnt),
             Value::Box(b) => {
@@ -451,6 +478,19 @@ impl<T: ArrayValue> Array<T> {
             }
             return;
         }
+        // Square matrices can be transposed without a temporary buffer
+        if self.rank() - depth == 2 && count % 2 == 1 && self.shape[depth] == self.shape[depth + 1]
+        {
+            let n = self.shape[depth];
+            for data in self.data.as_mut_slice().chunks_exact_mut(n * n) {
+                for i in 0..n {
+                    for j in i + 1..n {
+                        data.swap(i * n + j, j * n + i);
+                    }
+                }
+            }
+            return;
+        }
         let data_slice = self.data.as_mut_slice();
         for data in data_slice.chunks_exact_mut(self.shape[depth..].iter().product()) {
             let mut temp = data.to_vec();
@@ -480,6 +520,68 @@ impl<T: ArrayValue> Array<T> {
     }
 }
 
+#[test]
+fn in_place_monadic() {
+    // The allocations themselves are counted in tests/inplace.rs
+    let nums = |shape: &[usize]| -> Value {
+        let len = shape.iter().product::<usize>();
+        Array::new(
+            shape,
+            (0..len).map(|i| i as f64 - 3.5).collect::<EcoVec<_>>(),
+        )
+        .into()
+    };
+    let chars = |shape: &[usize]| -> Value {
+        let len = shape.iter().product::<usize>();
+        let data = (0..len).map(|i| char::from(b'a' + (i % 26) as u8));
+        Array::new(shape, data.collect::<EcoVec<_>>()).into()
+    };
+    let mut cases: Vec<(&str, Value)> = Vec::new();
+    for shape in [&[0][..], &[5], &[4, 4], &[2, 3], &[3, 2, 2], &[2, 3, 3]] {
+        for prim in ["¯", "¬", "±", "⌵", "⇌"] {
+            cases.push((prim, nums(shape)));
+        }
+        cases.push(("⇌", chars(shape)));
+    }
+    for shape in [&[0, 0][..], &[1, 1], &[4, 4], &[7, 7], &[3, 5, 5]] {
+        cases.push(("⍉", nums(shape)));
+        cases.push(("⍉", chars(shape)));
+    }
+    #[cfg(feature = "bytes")]
+    for prim in ["±", "⌵", "⇌", "⍉"] {
+        let data: EcoVec<u8> = (0..16).collect();
+        cases.push((prim, Array::new([4, 4], data).into()));
+    }
+    for (prim, val) in cases {
+        // Shared data must be left alone
+        let shown = val.show();
+        let mut env = Uiua::with_safe_sys();
+        env.push(val.clone());
+        env.run_str(prim).unwrap();
+        let copied = env.pop(1).unwrap();
+        assert_eq!(val.show(), shown, "{prim} mutated shared data");
+        // Uniquely owned data must give the same result
+        let mut env = Uiua::with_safe_sys();
+        env.push(val);
+        env.run_str(prim).unwrap();
+        let mutated = env.pop(1).unwrap();
+        assert_eq!(mutated, copied, "{prim} in place differs from copying");
+    }
+    // Check square transposition against the definition
+    for n in 0..6 {
+        let data: EcoVec<f64> = (0..n * n).map(|i| i as f64).collect();
+        let square = Array::new([n, n], data);
+        let mut transposed = square.clone();
+        transposed.transpose();
+        assert_eq!(transposed.shape, square.shape);
+        for i in 0..n {
+            for j in 0..n {
+                assert_eq!(square.data[i * n + j], transposed.data[j * n + i]);
+            }
+        }
+    }
+}
+
 impl Value {
     /// Get the `rise` of the value
     pub fn rise(&self, env: &Uiua) -> UiuaResult<Vec<usize>> {
@@ -489,6 +591,7 @@ impl Value {
             Array::rise,
             Array::rise,
             Array::rise,
+            Array::rise,
             env,
         )
     }
@@ -500,6 +603,7 @@ impl Value {
             Array::fall,
             Array::fall,
             Array::fall,
+            Array::fall,
             env,
         )
     }
@@ -511,6 +615,7 @@ impl Value {
             |a| a.sort_up(env),
             |a| a.sort_up(env),
             |a| a.sort_up(env),
+            |a| a.sort_up(env),
         )
     }
     /// Sort the value descending
@@ -521,22 +626,70 @@ impl Value {
             |a| a.sort_down(env),
             |a| a.sort_down(env),
             |a| a.sort_down(env),
+            |a| a.sort_down(env),
         )
     }
+    /// Get the indices that sort rows by several keys
+    ///
+    /// Each key has a row for every index and a flag for whether it is descending.
+    /// Later keys only break ties in earlier ones, and the sort is stable.
+    pub(crate) fn rise_by_keys(len: usize, keys: &[(Value, bool)]) -> Vec<usize> {
+        fn cmp_rows<T: ArrayValue>(arr: &Array<T>, a: usize, b: usize) -> Ordering {
+            ArrayCmpSlice(arr.row_slice(a)).cmp(&ArrayCmpSlice(arr.row_slice(b)))
+        }
+        let mut indices: Vec<usize> = (0..len).collect();
+        indices.par_sort_by(|&a, &b| {
+            (keys.iter())
+                .map(|(key, descending)| {
+                    let ordering = match key {
+                        Value::Num(arr) => cmp_rows(arr, a, b),
+                        #[cfg(feature = "bytes")]
+                        Value::Byte(arr) => cmp_rows(arr, a, b),
+                        Value::Int(arr) => cmp_rows(arr, a, b),
+                        Value::Complex(arr) => cmp_rows(arr, a, b),
+                        Value::Char(arr) => cmp_rows(arr, a, b),
+                        Value::Box(arr) => cmp_rows(arr, a, b),
+                    };
+                    if *descending {
+                        ordering.reverse()
+                    } else {
+                        ordering
+                    }
+                })
+                .find(|&o| o != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        });
+        indices
+    }
     /// `classify` the rows of the value
     pub fn classify(&self) -> Self {
         if self.rank() == 0 {
-            return 0.into();
+            return 0u32.into();
         }
-        self.generic_ref(
+        let indices = self.generic_ref(
             Array::classify,
             Array::classify,
             Array::classify,
             Array::classify,
             Array::classify,
-        )
-        .into_iter()
-        .collect()
+            Array::classify,
+        );
+        Value::from_indices(indices.len(), indices)
+    }
+    /// Count the previous `occurrences` of each row of the value
+    pub fn occurrences(&self) -> Self {
+        if self.rank() == 0 {
+            return 0u32.into();
+        }
+        let indices = self.generic_ref(
+            Array::occurrences,
+            Array::occurrences,
+            Array::occurrences,
+            Array::occurrences,
+            Array::occurrences,
+            Array::occurrences,
+        );
+        Value::from_indices(indices.len(), indices)
     }
     /// `deduplicate` the rows of the value
     pub fn deduplicate(&mut self) {
@@ -546,6 +699,7 @@ impl Value {
             Array::deduplicate,
             Array::deduplicate,
             Array::deduplicate,
+            Array::deduplicate,
         )
     }
     /// Mask the `unique` rows of the value
@@ -556,6 +710,7 @@ impl Value {
             Array::unique,
             Array::unique,
             Array::unique,
+            Array::unique,
         )
         .into()
     }
@@ -651,6 +806,17 @@ impl<T: ArrayValue> Array<T> {
         }
         classified
     }
+    /// Count the previous `occurrences` of each row of the array
+    pub fn occurrences(&self) -> Vec<usize> {
+        let mut counts: HashMap<_, usize> = HashMap::new();
+        let mut occurrences = Vec::with_capacity(self.row_count());
+        for row in self.row_slices() {
+            let count = counts.entry(ArrayCmpSlice(row)).or_default();
+            occurrences.push(*count);
+            *count += 1;
+        }
+        occurrences
+    }
     /// `deduplicate` the rows of the array
     pub fn deduplicate(&mut self) {
         if self.rank() == 0 {
@@ -693,6 +859,7 @@ impl Value {
         match self {
             #[cfg(feature = "bytes")]
             Value::Byte(n) => n.convert_ref().bits(env),
+            Value::Int(n) => n.convert_ref().bits(env),
             Value::Num(n) => n.bits(env),
             _ => Err(env.error("Argument to bits must be an array of natural numbers")),
         }
@@ -703,6 +870,7 @@ impl Value {
             #[cfg(feature = "bytes")]
             Value::Byte(n) => n.inverse_bits(env),
             Value::Num(n) => n.convert_ref_with(|n| n as u8).inverse_bits(env),
+            Value::Int(n) => n.convert_ref_with(|n| n as u8).inverse_bits(env),
             _ => Err(env.error("Argument to inverse_bits must be an array of naturals")),
         }
     }
@@ -798,35 +966,23 @@ impl Array<u8> {
 
 impl Value {
     /// Get the indices `where` the value is nonzero
-    pub fn wher(&self, env: &Uiua) -> UiuaResult<Array<f64>> {
+    pub fn wher(&self, env: &Uiua) -> UiuaResult<Value> {
         Ok(if self.rank() <= 1 {
             let counts = self.as_nats(env, "Argument to where must be an array of naturals")?;
             let total: usize = counts.iter().fold(0, |acc, &b| acc.saturating_add(b));
-            let mut data = EcoVec::with_capacity(total);
-            for (i, &b) in counts.iter().enumerate() {
-                for _ in 0..b {
-                    let i = i as f64;
-                    data.push(i);
-                }
-            }
-            Array::from(data)
+            let indices = (counts.iter().enumerate()).flat_map(|(i, &b)| (0..b).map(move |_| i));
+            Value::from_indices(total, indices)
         } else {
             let counts =
                 self.as_natural_array(env, "Argument to where must be an array of naturals")?;
             let total: usize = counts.data.iter().fold(0, |acc, &b| acc.saturating_add(b));
-            let mut data = EcoVec::with_capacity(total);
-            for (i, &b) in counts.data.iter().enumerate() {
-                for _ in 0..b {
-                    let mut i = i;
-                    let start = data.len();
-                    for &d in counts.shape.iter().rev() {
-                        data.insert(start, (i % d) as f64);
-                        i /= d;
-                    }
-                }
-            }
-            let shape = Shape::from([total, counts.rank()].as_ref());
-            Array::new(shape, data)
+            let strides: Vec<usize> = (1..=counts.rank())
+                .map(|d| counts.shape[d..].iter().product())
+                .collect();
+            let indices = (counts.data.iter().enumerate())
+                .flat_map(|(i, &b)| (0..b).map(move |_| i))
+                .flat_map(|i| (strides.iter().zip(&*counts.shape)).map(move |(s, d)| i / s % d));
+            Value::from_indices([total, counts.rank()], indices)
         })
     }
     /// Get the `first` index `where` the value is nonzero
@@ -857,6 +1013,16 @@ impl Value {
                         .map(Array::scalar)
                         .map_err(|e| env.error(format!("Cannot take first of an empty array{e}")))
                 }
+                Value::Int(ints) => {
+                    for (i, n) in ints.data.iter().enumerate() {
+                        if *n != 0 {
+                            return Ok(Array::scalar(i as f64));
+                        }
+                    }
+                    env.scalar_fill::<f64>()
+                        .map(Array::scalar)
+                        .map_err(|e| env.error(format!("Cannot take first of an empty array{e}")))
+                }
                 value => Err(env.error(format!(
                     "Argument to where must be an array of naturals, but it is {}",
                     value.type_name_plural()
@@ -900,6 +1066,22 @@ impl Value {
                         .map(Array::scalar)
                         .map_err(|e| env.error(format!("Cannot take first of an empty array{e}")))
                 }
+                Value::Int(ints) => {
+                    for (i, n) in ints.data.iter().enumerate() {
+                        if *n != 0 {
+                            let mut i = i;
+                            let mut res = Vec::with_capacity(ints.rank());
+                            for &d in ints.shape.iter().rev() {
+                                res.insert(0, (i % d) as f64);
+                                i /= d;
+                            }
+                            return Ok(Array::from_iter(res));
+                        }
+                    }
+                    env.scalar_fill::<f64>()
+                        .map(Array::scalar)
+                        .map_err(|e| env.error(format!("Cannot take first of an empty array{e}")))
+                }
                 value => Err(env.error(format!(
                     "Argument to where must be an array of naturals, but it is {}",
                     value.type_name_plural()
@@ -989,11 +1171,206 @@ impl Value {
         Ok(Array::<u8>::from_iter(s.into_bytes()).into())
     }
     /// Convert a list of UTF-8 bytes to a string value
+    ///
+    /// By default, invalid UTF-8 is an error.
+    /// If a scalar character fill is set, invalid sequences are replaced with it.
+    /// If an empty string fill is set, invalid sequences are skipped.
     pub fn inv_utf8(&self, env: &Uiua) -> UiuaResult<Self> {
-        let bytes = self.as_bytes(env, "Argument to inverse utf must be a list of bytes")?;
-        let s = String::from_utf8(bytes).map_err(|e| env.error(e))?;
+        let bytes = self.utf8_bytes(env)?;
+        let decoding = match env.value_fill() {
+            Some(Value::Char(c)) if c.rank() == 0 => Utf8Decoding::Replace(c.data[0]),
+            Some(Value::Char(c)) if c.rank() == 1 && c.row_count() == 0 => Utf8Decoding::Skip,
+            _ => Utf8Decoding::Strict,
+        };
+        let s = decode_utf8(&bytes, decoding).map_err(|e| env.error(e))?;
+        Ok(s.into())
+    }
+    fn utf8_bytes(&self, env: &Uiua) -> UiuaResult<Vec<u8>> {
+        const REQUIREMENT: &str = "Argument to inverse utf must be a list of bytes";
+        if self.rank() > 1 {
+            return Err(env.error(format!("{REQUIREMENT}, but its rank is {}", self.rank())));
+        }
+        Ok(match self {
+            Value::Num(nums) => {
+                let mut bytes = Vec::with_capacity(nums.element_count());
+                for (i, &n) in nums.data.iter().enumerate() {
+                    if n.fract() != 0.0 || !(0.0..256.0).contains(&n) {
+                        return Err(env.error(format!(
+                            "{REQUIREMENT}, but element {i} is {}",
+                            n.grid_string(false)
+                        )));
+                    }
+                    bytes.push(n as u8);
+                }
+                bytes
+            }
+            #[cfg(feature = "bytes")]
+            Value::Byte(bytes) => bytes.data.to_vec(),
+            Value::Int(ints) => {
+                let mut bytes = Vec::with_capacity(ints.element_count());
+                for (i, &n) in ints.data.iter().enumerate() {
+                    let Ok(n) = u8::try_from(n) else {
+                        return Err(env.error(format!("{REQUIREMENT}, but element {i} is {n}")));
+                    };
+                    bytes.push(n);
+                }
+                bytes
+            }
+            value => {
+                return Err(env.error(format!(
+                    "{REQUIREMENT}, but it is {}",
+                    value.type_name_plural()
+                )))
+            }
+        })
+    }
+    /// Convert a string value to a list of UTF-16 code units
+    pub fn utf16(&self, env: &Uiua) -> UiuaResult<Self> {
+        let s = self.as_string(env, "Argument to utfsixteen must be a string")?;
+        Ok(Array::<f64>::from_iter(s.encode_utf16().map(|u| u as f64)).into())
+    }
+    /// Convert a list of UTF-16 code units to a string value
+    ///
+    /// Fills are handled the same way as for [`Value::inv_utf8`].
+    pub fn inv_utf16(&self, env: &Uiua) -> UiuaResult<Self> {
+        let units = self.as_number_list(
+            env,
+            "Argument to inverse utfsixteen must be a list of UTF-16 code units",
+            |n| n.fract() == 0.0 && (0.0..65536.0).contains(&n),
+            |n| n as u16,
+        )?;
+        let decoding = match env.value_fill() {
+            Some(Value::Char(c)) if c.rank() == 0 => Utf8Decoding::Replace(c.data[0]),
+            Some(Value::Char(c)) if c.rank() == 1 && c.row_count() == 0 => Utf8Decoding::Skip,
+            _ => Utf8Decoding::Strict,
+        };
+        let mut s = String::with_capacity(units.len());
+        let mut offset = 0;
+        for c in char::decode_utf16(units.iter().copied()) {
+            match (c, decoding) {
+                (Ok(c), _) => {
+                    s.push(c);
+                    offset += c.len_utf16();
+                }
+                (Err(e), Utf8Decoding::Strict) => {
+                    return Err(env.error(format!(
+                        "Unpaired surrogate {} at code unit offset {offset}",
+                        e.unpaired_surrogate()
+                    )))
+                }
+                (Err(_), Utf8Decoding::Replace(c)) => {
+                    s.push(c);
+                    offset += 1;
+                }
+                (Err(_), Utf8Decoding::Skip) => offset += 1,
+            }
+        }
         Ok(s.into())
     }
+    /// Split a string value into a list of boxed grapheme clusters
+    pub fn graphemes(&self, env: &Uiua) -> UiuaResult<Self> {
+        use unicode_segmentation::UnicodeSegmentation;
+        let s = self.as_string(env, "Argument to graphemes must be a string")?;
+        let graphemes = s.graphemes(true).map(String::from);
+        Ok(Array::<Boxed>::from_iter(graphemes).into())
+    }
+    /// Join a list of boxed strings into a single string value
+    pub fn inv_graphemes(&self, env: &Uiua) -> UiuaResult<Self> {
+        const REQUIREMENT: &str = "Argument to inverse graphemes must be a list of strings";
+        if self.rank() > 1 {
+            return Err(env.error(format!("{REQUIREMENT}, but its rank is {}", self.rank())));
+        }
+        let mut s = String::new();
+        for row in self.rows() {
+            s.push_str(&row.unboxed().as_string(env, REQUIREMENT)?);
+        }
+        Ok(s.into())
+    }
+}
+
+/// How to handle invalid sequences when decoding UTF-8
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Utf8Decoding {
+    /// Error on the first invalid sequence
+    Strict,
+    /// Replace each invalid sequence with a character
+    Replace(char),
+    /// Drop invalid sequences
+    Skip,
+}
+
+pub(crate) fn decode_utf8(bytes: &[u8], decoding: Utf8Decoding) -> Result<String, String> {
+    let mut s = String::with_capacity(bytes.len());
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let rest = &bytes[offset..];
+        let (valid_len, error_len) = match std::str::from_utf8(rest) {
+            Ok(valid) => {
+                s.push_str(valid);
+                break;
+            }
+            Err(e) => (e.valid_up_to(), e.error_len()),
+        };
+        let invalid_len = error_len.unwrap_or(rest.len() - valid_len);
+        s.push_str(std::str::from_utf8(&rest[..valid_len]).unwrap());
+        let invalid = &rest[valid_len..][..invalid_len];
+        match decoding {
+            Utf8Decoding::Strict => {
+                let invalid = invalid.iter().map(|b| b.to_string());
+                let invalid = invalid.collect::<Vec<_>>().join(" ");
+                let reason = if error_len.is_none() {
+                    " (unexpected end of input)"
+                } else {
+                    ""
+                };
+                return Err(format!(
+                    "Invalid UTF-8 at byte offset {}: [{invalid}]{reason}",
+                    offset + valid_len,
+                ));
+            }
+            Utf8Decoding::Replace(c) => s.push(c),
+            Utf8Decoding::Skip => {}
+        }
+        offset += valid_len + invalid_len;
+    }
+    Ok(s)
+}
+
+#[test]
+fn decode_utf8_test() {
+    use Utf8Decoding::*;
+    for (bytes, strict, replaced, skipped) in [
+        (&[104, 105, 33][..], Ok("hi!"), "hi!", "hi!"),
+        (&[104, 105, 255, 33][..], Err(2), "hi?!", "hi!"),
+        (&[104, 226, 156][..], Err(1), "h?", "h"),
+        (
+            &[128, 226, 156, 168, 192][..],
+            Err(0),
+            "?\u{2728}?",
+            "\u{2728}",
+        ),
+        (
+            &[226, 156, 168, 255, 254][..],
+            Err(3),
+            "\u{2728}??",
+            "\u{2728}",
+        ),
+    ] {
+        match (decode_utf8(bytes, Strict), strict) {
+            (Ok(s), Ok(expected)) => assert_eq!(s, expected),
+            (Err(e), Err(offset)) => assert!(
+                e.contains(&format!("byte offset {offset}:")),
+                "{e:?} does not name offset {offset}"
+            ),
+            (res, expected) => panic!("{bytes:?} decoded to {res:?}, expected {expected:?}"),
+        }
+        assert_eq!(decode_utf8(bytes, Replace('?')).unwrap(), replaced);
+        assert_eq!(decode_utf8(bytes, Skip).unwrap(), skipped);
+    }
+    for c in (0..0x11_0000).step_by(97).filter_map(char::from_u32) {
+        let s: String = [c, 'a', c].into_iter().collect();
+        assert_eq!(decode_utf8(s.as_bytes(), Strict).unwrap(), s);
+    }
 }
 
 impl Value {
@@ -1004,6 +1381,7 @@ impl Value {
             Array::first_min_index,
             Array::first_min_index,
             Array::first_min_index,
+            Array::first_min_index,
             env,
         )
         .map(Into::into)
@@ -1015,6 +1393,7 @@ impl Value {
             Array::first_max_index,
             Array::first_max_index,
             Array::first_max_index,
+            Array::first_max_index,
             env,
         )
         .map(Into::into)
@@ -1026,6 +1405,7 @@ impl Value {
             Array::last_min_index,
             Array::last_min_index,
             Array::last_min_index,
+            Array::last_min_index,
             env,
         )
         .map(Into::into)
@@ -1037,6 +1417,7 @@ impl Value {
             Array::last_max_index,
             Array::last_max_index,
             Array::last_max_index,
+            Array::last_max_index,
             env,
         )
         .map(Into::into)
@@ -1124,6 +1505,7 @@ impl Value {
             Value::Num(n) => n.primes(env),
             #[cfg(feature = "bytes")]
             Value::Byte(b) => b.convert_ref::<f64>().primes(env),
+            Value::Int(i) => i.convert_ref::<f64>().primes(env),
             value => Err(env.error(format!("Cannot get primes of {} array", value.type_name()))),
         }
     }
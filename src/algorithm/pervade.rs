@@ -241,18 +241,10 @@ where
                 *a = f(*a, *a);
             }
         } else if b.data.is_unique() {
-            let a_data = a.data.as_slice();
-            let b_data = b.data.as_mut_slice();
-            for (a, b) in a_data.iter().zip(b_data) {
-                *b = f(*a, *b);
-            }
+            zip_into_right(a.data.as_slice(), b.data.as_mut_slice(), f);
             *a = b;
         } else {
-            let a_data = a.data.as_mut_slice();
-            let b_data = b.data.as_slice();
-            for (a, b) in a_data.iter_mut().zip(b_data) {
-                *a = f(*a, *b);
-            }
+            zip_into_left(a.data.as_mut_slice(), b.data.as_slice(), f);
         }
     } else if ash.contains(&0) || bsh.contains(&0) {
         if ash.len() < bsh.len() {
@@ -281,6 +273,119 @@ where
     Ok(())
 }
 
+/// Pervade with a function that returns the type of the left array
+///
+/// The result is written into the left array's buffer if it has the shape of the result.
+pub fn bin_pervade_reuse_left<A, B>(
+    mut a: Array<A>,
+    mut b: Array<B>,
+    a_depth: usize,
+    b_depth: usize,
+    env: &Uiua,
+    f: impl Fn(A, B) -> A + Copy,
+) -> UiuaResult<Array<A>>
+where
+    A: ArrayValue + Copy,
+    B: ArrayValue + Copy,
+{
+    // Account for depths
+    reshape_depths(&mut a, &mut b, a_depth, b_depth);
+    // Fill
+    fill_array_shapes(&mut a, &mut b, env)?;
+    // Pervade
+    let ash = a.shape.dims();
+    let bsh = b.shape.dims();
+    if ash == bsh {
+        zip_into_left(a.data.as_mut_slice(), b.data.as_slice(), f);
+    } else if ash.len() > bsh.len() && !ash.contains(&0) {
+        let a_data = a.data.as_mut_slice();
+        bin_pervade_recursive_mut_left(a_data, ash, b.data.as_slice(), bsh, f);
+    } else {
+        return bin_pervade(a, b, 0, 0, env, InfalliblePervasiveFn::new(f));
+    }
+    Ok(a)
+}
+
+/// Pervade with a function that returns the type of the right array
+///
+/// The result is written into the right array's buffer if it has the shape of the result.
+pub fn bin_pervade_reuse_right<A, B>(
+    mut a: Array<A>,
+    mut b: Array<B>,
+    a_depth: usize,
+    b_depth: usize,
+    env: &Uiua,
+    f: impl Fn(A, B) -> B + Copy,
+) -> UiuaResult<Array<B>>
+where
+    A: ArrayValue + Copy,
+    B: ArrayValue + Copy,
+{
+    // Account for depths
+    reshape_depths(&mut a, &mut b, a_depth, b_depth);
+    // Fill
+    fill_array_shapes(&mut a, &mut b, env)?;
+    // Pervade
+    let ash = a.shape.dims();
+    let bsh = b.shape.dims();
+    if ash == bsh {
+        zip_into_right(a.data.as_slice(), b.data.as_mut_slice(), f);
+    } else if bsh.len() > ash.len() && !bsh.contains(&0) {
+        let b_data = b.data.as_mut_slice();
+        bin_pervade_recursive_mut_right(a.data.as_slice(), ash, b_data, bsh, f);
+    } else {
+        return bin_pervade(a, b, 0, 0, env, InfalliblePervasiveFn::new(f));
+    }
+    Ok(b)
+}
+
+/// The number of elements processed together by the `simd` fast paths
+#[cfg(feature = "simd")]
+const LANES: usize = 16;
+
+/// Combine same-length slices, writing the results into the left one
+#[inline]
+fn zip_into_left<A: Copy, B: Copy>(a: &mut [A], b: &[B], f: impl Fn(A, B) -> A + Copy) {
+    #[cfg(feature = "simd")]
+    let (a, b) = {
+        // Fixed-size chunks have no bounds checks, so the compiler can vectorize them
+        let mut a_chunks = a.chunks_exact_mut(LANES);
+        let mut b_chunks = b.chunks_exact(LANES);
+        for (a, b) in (&mut a_chunks).zip(&mut b_chunks) {
+            let a: &mut [A; LANES] = a.try_into().unwrap();
+            let b: &[B; LANES] = b.try_into().unwrap();
+            for (a, b) in a.iter_mut().zip(b) {
+                *a = f(*a, *b);
+            }
+        }
+        (a_chunks.into_remainder(), b_chunks.remainder())
+    };
+    for (a, b) in a.iter_mut().zip(b) {
+        *a = f(*a, *b);
+    }
+}
+
+/// Combine same-length slices, writing the results into the right one
+#[inline]
+fn zip_into_right<A: Copy, B: Copy>(a: &[A], b: &mut [B], f: impl Fn(A, B) -> B + Copy) {
+    #[cfg(feature = "simd")]
+    let (a, b) = {
+        let mut a_chunks = a.chunks_exact(LANES);
+        let mut b_chunks = b.chunks_exact_mut(LANES);
+        for (a, b) in (&mut a_chunks).zip(&mut b_chunks) {
+            let a: &[A; LANES] = a.try_into().unwrap();
+            let b: &mut [B; LANES] = b.try_into().unwrap();
+            for (a, b) in a.iter().zip(b) {
+                *b = f(*a, *b);
+            }
+        }
+        (a_chunks.remainder(), b_chunks.into_remainder())
+    };
+    for (a, b) in a.iter().zip(b) {
+        *b = f(*a, *b);
+    }
+}
+
 fn bin_pervade_recursive_mut<T>(
     a_data: &mut [T],
     a_shape: &[usize],
@@ -319,14 +424,15 @@ fn bin_pervade_recursive_mut<T>(
     }
 }
 
-fn bin_pervade_recursive_mut_left<T>(
-    a_data: &mut [T],
+fn bin_pervade_recursive_mut_left<A, B>(
+    a_data: &mut [A],
     a_shape: &[usize],
-    b_data: &[T],
+    b_data: &[B],
     b_shape: &[usize],
-    f: impl Fn(T, T) -> T + Copy,
+    f: impl Fn(A, B) -> A + Copy,
 ) where
-    T: Copy,
+    A: Copy,
+    B: Copy,
 {
     match (a_shape, b_shape) {
         ([], _) => {
@@ -351,14 +457,15 @@ fn bin_pervade_recursive_mut_left<T>(
     }
 }
 
-fn bin_pervade_recursive_mut_right<T>(
-    a_data: &[T],
+fn bin_pervade_recursive_mut_right<A, B>(
+    a_data: &[A],
     a_shape: &[usize],
-    b_data: &mut [T],
+    b_data: &mut [B],
     b_shape: &[usize],
-    f: impl Fn(T, T) -> T + Copy,
+    f: impl Fn(A, B) -> B + Copy,
 ) where
-    T: Copy,
+    A: Copy,
+    B: Copy,
 {
     match (a_shape, b_shape) {
         (_, []) => {
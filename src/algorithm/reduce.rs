@@ -1,19 +1,19 @@
 //! Algorithms for reducing modifiers
 
-use std::convert::identity;
+use std::{collections::VecDeque, convert::identity};
 
-use ecow::EcoVec;
+use ecow::{eco_vec, EcoVec};
 
 use crate::{
-    algorithm::{loops::flip, pervade::*},
+    algorithm::{loops::flip, pervade::*, zip},
     cowslice::cowslice,
-    Array, ArrayValue, Function, ImplPrimitive, Primitive, Shape, Uiua, UiuaResult, Value,
+    Array, ArrayValue, Function, ImplPrimitive, Instr, Primitive, Shape, Uiua, UiuaResult, Value,
 };
 
 pub fn reduce(env: &mut Uiua) -> UiuaResult {
     crate::profile_function!();
     let f = env.pop_function()?;
-    let xs = env.pop(1)?;
+    let xs = env.pop(1)?.promote_ints();
 
     match (f.as_flipped_primitive(env), xs) {
         (Some((Primitive::Join, false)), mut xs)
@@ -226,106 +226,279 @@ fn generic_reduce_impl(
     Ok(())
 }
 
-pub fn scan(env: &mut Uiua) -> UiuaResult {
+/// `≡/f◫`, computed with a sliding window when possible
+pub fn windowed_reduce(env: &mut Uiua) -> UiuaResult {
     crate::profile_function!();
+    let g = env.pop_function()?;
+    let size = env.pop(1)?;
+    let xs = env.pop(2)?;
+    let prim = match g.instrs(env) {
+        [Instr::PushFunc(f), Instr::Prim(Primitive::Reduce, _)] => {
+            f.as_flipped_primitive(env).map(|(prim, _)| prim)
+        }
+        _ => None,
+    };
+    let n = (size.rank() == 0)
+        .then(|| size.as_nat(env, "").ok())
+        .flatten()
+        .filter(|&n| n > 0 && n <= xs.row_count());
+    let nums = match (&xs, env.value_fill()) {
+        (Value::Num(nums), None) => Some(nums.clone()),
+        #[cfg(feature = "bytes")]
+        (Value::Byte(bytes), None) => Some(bytes.convert_ref()),
+        (Value::Int(ints), None) => Some(ints.convert_ref()),
+        _ => None,
+    };
+    match (prim, n, nums) {
+        (Some(prim), Some(n), Some(nums)) if nums.data.iter().all(|x| x.is_finite()) => {
+            let reduced = match prim {
+                Primitive::Add => sliding_sum(nums, n),
+                Primitive::Max => sliding_extreme(nums, n, |a, b| a >= b),
+                Primitive::Min => sliding_extreme(nums, n, |a, b| a <= b),
+                _ => return windowed_reduce_fallback(g, size, xs, env),
+            };
+            env.push(reduced);
+            Ok(())
+        }
+        _ => windowed_reduce_fallback(g, size, xs, env),
+    }
+}
+
+fn windowed_reduce_fallback(g: Function, size: Value, xs: Value, env: &mut Uiua) -> UiuaResult {
+    let windows = size.windows(&xs, env)?;
+    env.push(windows);
+    env.push_func(g);
+    zip::rows(env)
+}
+
+fn sliding_sum(arr: Array<f64>, n: usize) -> Array<f64> {
+    let row_len = arr.row_len();
+    let row_count = arr.row_count() + 1 - n;
+    let mut acc = vec![0.0; row_len];
+    for row in arr.data.chunks_exact(row_len.max(1)).take(n) {
+        for (a, b) in acc.iter_mut().zip(row) {
+            *a += b;
+        }
+    }
+    let mut data = EcoVec::with_capacity(row_count * row_len);
+    data.extend_from_slice(&acc);
+    for i in 1..row_count {
+        let leaving = &arr.data[(i - 1) * row_len..][..row_len];
+        let entering = &arr.data[(i + n - 1) * row_len..][..row_len];
+        for ((a, l), e) in acc.iter_mut().zip(leaving).zip(entering) {
+            *a += e - l;
+        }
+        data.extend_from_slice(&acc);
+    }
+    let mut shape = arr.shape;
+    shape[0] = row_count;
+    Array::new(shape, data)
+}
+
+/// Get the extreme of each window, where `keep(a, b)` means that `a` is at least as extreme as `b`
+fn sliding_extreme(arr: Array<f64>, n: usize, keep: impl Fn(f64, f64) -> bool) -> Array<f64> {
+    let row_len = arr.row_len();
+    let row_count = arr.row_count() + 1 - n;
+    let mut data = eco_vec![0.0; row_count * row_len];
+    let slice = data.make_mut();
+    let mut deque = VecDeque::with_capacity(n);
+    for j in 0..row_len {
+        // Indices of rows whose values might still be the extreme of a window,
+        // with their values in order of extremeness
+        deque.clear();
+        for i in 0..arr.row_count() {
+            let x = arr.data[i * row_len + j];
+            while deque
+                .back()
+                .is_some_and(|&k| !keep(arr.data[k * row_len + j], x))
+            {
+                deque.pop_back();
+            }
+            deque.push_back(i);
+            if deque.front().is_some_and(|&k| k + n <= i) {
+                deque.pop_front();
+            }
+            if i + 1 >= n {
+                slice[(i + 1 - n) * row_len + j] = arr.data[deque[0] * row_len + j];
+            }
+        }
+    }
+    let mut shape = arr.shape;
+    shape[0] = row_count;
+    Array::new(shape, data)
+}
+
+pub fn scan(env: &mut Uiua) -> UiuaResult {
+    let f = env.pop_function()?;
+    let xs = env.pop(1)?;
+    scan_impl(f, xs, None, false, env)
+}
+
+pub fn scan_right(env: &mut Uiua) -> UiuaResult {
     let f = env.pop_function()?;
     let xs = env.pop(1)?;
+    scan_impl(f, xs, None, true, env)
+}
+
+pub fn scan_exclusive(env: &mut Uiua) -> UiuaResult {
+    let f = env.pop_function()?;
+    let init = env.pop(1)?;
+    let xs = env.pop(2)?;
+    if xs.rank() > 0 && *init.shape() == xs.shape()[1..] {
+        return scan_impl(f, xs, Some(init), false, env);
+    }
+    // The initial value is not a row, so let join handle it
+    scan_impl(f, init.join(xs, env)?, None, false, env)?;
+    let scanned = env.pop("scanned array")?;
+    env.push(Value::from(-1).drop(scanned, env)?);
+    Ok(())
+}
+
+/// Scan the rows of an array
+///
+/// If there is an initial value, the scan is exclusive,
+/// so it starts with the initial value and does not include the last row.
+fn scan_impl(
+    f: Function,
+    xs: Value,
+    init: Option<Value>,
+    reverse: bool,
+    env: &mut Uiua,
+) -> UiuaResult {
+    crate::profile_function!();
     if xs.rank() == 0 {
         return Err(env.error(format!("Cannot {} rank 0 array", Primitive::Scan.format())));
     }
-    match (f.as_flipped_primitive(env), xs) {
+    let num_init = match &init {
+        Some(Value::Num(arr)) if arr.rank() == 0 => Some(arr.data[0]),
+        #[cfg(feature = "bytes")]
+        Some(Value::Byte(arr)) if arr.rank() == 0 => Some(arr.data[0] as f64),
+        Some(Value::Int(arr)) if arr.rank() == 0 => Some(arr.data[0] as f64),
+        Some(_) => return generic_scan(f, xs, init, reverse, env),
+        None => None,
+    };
+    match (f.as_flipped_primitive(env), xs.promote_ints()) {
+        #[cfg(feature = "bytes")]
+        (Some((prim, _)), Value::Byte(bytes)) => match prim {
+            Primitive::Eq if init.is_none() => {
+                env.push(fast_scan(bytes, None, reverse, is_eq::generic));
+                Ok(())
+            }
+            Primitive::Ne if init.is_none() => {
+                env.push(fast_scan(bytes, None, reverse, is_ne::generic));
+                Ok(())
+            }
+            Primitive::Max if init.is_none() => {
+                env.push(fast_scan(bytes, None, reverse, u8::max));
+                Ok(())
+            }
+            Primitive::Min if init.is_none() => {
+                env.push(fast_scan(bytes, None, reverse, u8::min));
+                Ok(())
+            }
+            Primitive::Eq
+            | Primitive::Ne
+            | Primitive::Add
+            | Primitive::Sub
+            | Primitive::Mul
+            | Primitive::Div
+            | Primitive::Mod
+            | Primitive::Atan
+            | Primitive::Max
+            | Primitive::Min => scan_impl(f, Value::Num(bytes.convert()), init, reverse, env),
+            _ => generic_scan(f, Value::Byte(bytes), init, reverse, env),
+        },
         (Some((prim, flipped)), Value::Num(nums)) => {
             let arr = match prim {
-                Primitive::Eq => fast_scan(nums, |a, b| is_eq::num_num(a, b) as f64),
-                Primitive::Ne => fast_scan(nums, |a, b| is_ne::num_num(a, b) as f64),
-                Primitive::Add => fast_scan(nums, add::num_num),
-                Primitive::Sub if flipped => fast_scan(nums, flip(sub::num_num)),
-                Primitive::Sub => fast_scan(nums, sub::num_num),
-                Primitive::Mul => fast_scan(nums, mul::num_num),
-                Primitive::Div if flipped => fast_scan(nums, flip(div::num_num)),
-                Primitive::Div => fast_scan(nums, div::num_num),
-                Primitive::Mod if flipped => fast_scan(nums, flip(modulus::num_num)),
-                Primitive::Mod => fast_scan(nums, modulus::num_num),
-                Primitive::Atan if flipped => fast_scan(nums, flip(atan2::num_num)),
-                Primitive::Atan => fast_scan(nums, atan2::num_num),
-                Primitive::Max => fast_scan(nums, max::num_num),
-                Primitive::Min => fast_scan(nums, min::num_num),
-                _ => return generic_scan(f, Value::Num(nums), env),
-            };
-            env.push(arr);
-            Ok(())
-        }
-        #[cfg(feature = "bytes")]
-        (Some((prim, flipped)), Value::Byte(bytes)) => {
-            match prim {
-                Primitive::Eq => env.push(fast_scan(bytes, is_eq::generic)),
-                Primitive::Ne => env.push(fast_scan(bytes, is_ne::generic)),
-                Primitive::Add => env.push(fast_scan::<f64>(bytes.convert(), add::num_num)),
-                Primitive::Sub if flipped => {
-                    env.push(fast_scan::<f64>(bytes.convert(), flip(sub::num_num)))
+                Primitive::Eq => {
+                    fast_scan(nums, num_init, reverse, |a, b| is_eq::num_num(a, b) as f64)
                 }
-                Primitive::Sub => env.push(fast_scan::<f64>(bytes.convert(), sub::num_num)),
-                Primitive::Mul => env.push(fast_scan::<f64>(bytes.convert(), mul::num_num)),
-                Primitive::Div if flipped => {
-                    env.push(fast_scan::<f64>(bytes.convert(), flip(div::num_num)))
+                Primitive::Ne => {
+                    fast_scan(nums, num_init, reverse, |a, b| is_ne::num_num(a, b) as f64)
                 }
-                Primitive::Div => env.push(fast_scan::<f64>(bytes.convert(), div::num_num)),
+                Primitive::Add => fast_scan(nums, num_init, reverse, add::num_num),
+                Primitive::Sub if flipped => fast_scan(nums, num_init, reverse, flip(sub::num_num)),
+                Primitive::Sub => fast_scan(nums, num_init, reverse, sub::num_num),
+                Primitive::Mul => fast_scan(nums, num_init, reverse, mul::num_num),
+                Primitive::Div if flipped => fast_scan(nums, num_init, reverse, flip(div::num_num)),
+                Primitive::Div => fast_scan(nums, num_init, reverse, div::num_num),
                 Primitive::Mod if flipped => {
-                    env.push(fast_scan::<f64>(bytes.convert(), flip(modulus::num_num)))
+                    fast_scan(nums, num_init, reverse, flip(modulus::num_num))
                 }
-                Primitive::Mod => env.push(fast_scan::<f64>(bytes.convert(), modulus::num_num)),
+                Primitive::Mod => fast_scan(nums, num_init, reverse, modulus::num_num),
                 Primitive::Atan if flipped => {
-                    env.push(fast_scan::<f64>(bytes.convert(), flip(atan2::num_num)))
+                    fast_scan(nums, num_init, reverse, flip(atan2::num_num))
                 }
-                Primitive::Atan => env.push(fast_scan::<f64>(bytes.convert(), atan2::num_num)),
-                Primitive::Max => env.push(fast_scan(bytes, u8::max)),
-                Primitive::Min => env.push(fast_scan(bytes, u8::min)),
-                _ => return generic_scan(f, Value::Byte(bytes), env),
-            }
+                Primitive::Atan => fast_scan(nums, num_init, reverse, atan2::num_num),
+                Primitive::Max => fast_scan(nums, num_init, reverse, max::num_num),
+                Primitive::Min => fast_scan(nums, num_init, reverse, min::num_num),
+                _ => return generic_scan(f, Value::Num(nums), init, reverse, env),
+            };
+            env.push(arr);
             Ok(())
         }
-        (_, xs) => generic_scan(f, xs, env),
+        (_, xs) => generic_scan(f, xs, init, reverse, env),
     }
 }
 
-fn fast_scan<T>(mut arr: Array<T>, f: impl Fn(T, T) -> T) -> Array<T>
+/// Scan the rows of an array in place
+fn fast_scan<T>(
+    mut arr: Array<T>,
+    init: Option<T>,
+    reverse: bool,
+    f: impl Fn(T, T) -> T,
+) -> Array<T>
 where
     T: ArrayValue + Copy,
 {
-    match arr.shape.len() {
-        0 => unreachable!("fast_scan called on unit array, should have been guarded against"),
-        1 => {
-            if arr.row_count() == 0 {
-                return arr;
-            }
-            let mut acc = arr.data[0];
-            for val in arr.data.as_mut_slice().iter_mut().skip(1) {
-                acc = f(acc, *val);
-                *val = acc;
+    let row_len = arr.row_len();
+    if row_len == 0 || arr.row_count() == 0 {
+        return arr;
+    }
+    let rows = arr.data.as_mut_slice().chunks_exact_mut(row_len);
+    if reverse {
+        fast_scan_rows(rows.rev(), row_len, init, f);
+    } else {
+        fast_scan_rows(rows, row_len, init, f);
+    }
+    arr
+}
+
+fn fast_scan_rows<'a, T>(
+    mut rows: impl Iterator<Item = &'a mut [T]>,
+    row_len: usize,
+    init: Option<T>,
+    f: impl Fn(T, T) -> T,
+) where
+    T: Copy + 'a,
+{
+    if let Some(init) = init {
+        let mut acc = vec![init; row_len];
+        for row in rows {
+            for (acc, val) in acc.iter_mut().zip(row) {
+                let next = f(*acc, *val);
+                *val = *acc;
+                *acc = next;
             }
-            arr
         }
-        _ => {
-            let row_len: usize = arr.row_len();
-            if arr.row_count() == 0 {
-                return arr;
-            }
-            let shape = arr.shape.clone();
-            let mut new_data = EcoVec::with_capacity(arr.data.len());
-            let mut rows = arr.into_rows();
-            new_data.extend(rows.next().unwrap().data);
-            for row in rows {
-                let start = new_data.len() - row_len;
-                for (i, r) in row.data.into_iter().enumerate() {
-                    new_data.push(f(new_data[start + i], r));
-                }
+    } else {
+        let mut acc = rows.next().unwrap().to_vec();
+        for row in rows {
+            for (acc, val) in acc.iter_mut().zip(row) {
+                *acc = f(*acc, *val);
+                *val = *acc;
             }
-            Array::new(shape, new_data)
         }
     }
 }
 
-fn generic_scan(f: Function, xs: Value, env: &mut Uiua) -> UiuaResult {
+fn generic_scan(
+    f: Function,
+    xs: Value,
+    init: Option<Value>,
+    reverse: bool,
+    env: &mut Uiua,
+) -> UiuaResult {
     let sig = f.signature();
     if sig != (2, 1) {
         return Err(env.error(format!(
@@ -338,12 +511,16 @@ fn generic_scan(f: Function, xs: Value, env: &mut Uiua) -> UiuaResult {
         return Ok(());
     }
     let row_count = xs.row_count();
-    let mut rows = xs.into_rows();
-    let mut acc = rows.next().unwrap();
+    let mut rows: Box<dyn Iterator<Item = Value>> = if reverse {
+        Box::new(xs.into_rows().rev())
+    } else {
+        Box::new(xs.into_rows())
+    };
+    let mut acc = init.unwrap_or_else(|| rows.next().unwrap());
     let mut scanned = Vec::with_capacity(row_count);
     scanned.push(acc.clone());
     env.without_fill(|env| -> UiuaResult {
-        for row in rows.by_ref() {
+        for row in rows.take(row_count - 1) {
             env.push(row);
             env.push(acc.clone());
             env.call(f.clone())?;
@@ -352,6 +529,9 @@ fn generic_scan(f: Function, xs: Value, env: &mut Uiua) -> UiuaResult {
         }
         Ok(())
     })?;
+    if reverse {
+        scanned.reverse();
+    }
     let val = Value::from_row_values(scanned, env)?;
     env.push(val);
     Ok(())
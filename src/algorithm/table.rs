@@ -3,8 +3,8 @@
 use ecow::{eco_vec, EcoVec};
 
 use crate::{
-    algorithm::pervade::*, function::Function, random, value::Value, Array, ArrayValue,
-    ImplPrimitive, Primitive, Shape, Uiua, UiuaResult,
+    algorithm::pervade::*, function::Function, value::Value, Array, ArrayValue, ImplPrimitive,
+    Instr, Primitive, Shape, Uiua, UiuaResult,
 };
 
 use super::{loops::flip, multi_output};
@@ -13,18 +13,80 @@ pub fn table(env: &mut Uiua) -> UiuaResult {
     crate::profile_function!();
     let f = env.pop_function()?;
     let sig = f.signature();
-    let xs = env.pop(1)?;
-    let ys = env.pop(2)?;
+    let xs = env.pop(1)?.promote_ints();
+    let ys = env.pop(2)?.promote_ints();
     match sig.args {
         0 | 1 => Err(env.error(format!(
             "{}'s function must take at least 2 arguments, but its signature is {sig}",
             Primitive::Table.format()
         ))),
         2 if xs.rank() <= 1 && ys.rank() <= 1 => table_list(f, xs, ys, env),
+        2 if is_dot_product(&f, env) => match (xs, ys) {
+            (Value::Num(xs), Value::Num(ys)) => matrix_product(f, xs, ys, env),
+            #[cfg(feature = "bytes")]
+            (Value::Num(xs), Value::Byte(ys)) => matrix_product(f, xs, ys.convert(), env),
+            #[cfg(feature = "bytes")]
+            (Value::Byte(xs), Value::Num(ys)) => matrix_product(f, xs.convert(), ys, env),
+            #[cfg(feature = "bytes")]
+            (Value::Byte(xs), Value::Byte(ys)) => {
+                matrix_product(f, xs.convert(), ys.convert(), env)
+            }
+            (xs, ys) => generic_table(f, xs, ys, env),
+        },
         _ => generic_table(f, xs, ys, env),
     }
 }
 
+/// Check if a function is `(/+×)`
+fn is_dot_product(f: &Function, env: &Uiua) -> bool {
+    match f.instrs(env) {
+        [Instr::Prim(Primitive::Mul, _), Instr::PushFunc(g), Instr::Prim(Primitive::Reduce, _)] => {
+            g.as_flipped_primitive(env) == Some((Primitive::Add, false))
+        }
+        _ => false,
+    }
+}
+
+/// `⊞(/+×)` on two rank-2 arrays with the same row length
+///
+/// This is a matrix product where the second matrix is already transposed,
+/// so every dot product is between two contiguous rows.
+fn matrix_product(f: Function, xs: Array<f64>, ys: Array<f64>, env: &mut Uiua) -> UiuaResult {
+    crate::profile_function!();
+    if xs.rank() != 2 || ys.rank() != 2 || xs.row_len() != ys.row_len() {
+        return generic_table(f, xs.into(), ys.into(), env);
+    }
+    const BLOCK: usize = 64;
+    let (m, n, k) = (xs.row_count(), ys.row_count(), xs.row_len());
+    let mut data = eco_vec![0.0; m * n];
+    let slice = data.make_mut();
+    if k > 0 {
+        // Work in blocks so that rows of the second matrix stay in cache
+        for i0 in (0..m).step_by(BLOCK) {
+            for j0 in (0..n).step_by(BLOCK) {
+                for i in i0..(i0 + BLOCK).min(m) {
+                    let x = &xs.data[i * k..][..k];
+                    for j in j0..(j0 + BLOCK).min(n) {
+                        let y = &ys.data[j * k..][..k];
+                        // Sum in order so that results match the unoptimized reduction
+                        let mut sum = x[0] * y[0];
+                        for (a, b) in x[1..].iter().zip(&y[1..]) {
+                            sum += a * b;
+                        }
+                        slice[i * n + j] = sum;
+                    }
+                }
+            }
+        }
+    }
+    env.push(Array::new([m, n], data));
+    Ok(())
+}
+
+/// The minimum number of calls for a pure [`table ⊞`](Primitive::Table) function
+/// to be run on the thread pool
+const PAR_TABLE_THRESHOLD: usize = 1024;
+
 fn generic_table(f: Function, xs: Value, ys: Value, env: &mut Uiua) -> UiuaResult {
     let sig = f.signature();
     match sig.args {
@@ -32,8 +94,25 @@ fn generic_table(f: Function, xs: Value, ys: Value, env: &mut Uiua) -> UiuaResul
             let new_shape = Shape::from([xs.row_count(), ys.row_count()]);
             let outputs = sig.outputs;
             let mut items = multi_output(outputs, Value::builder(xs.row_count() * ys.row_count()));
+            let calls = xs.row_count() * ys.row_count();
             let y_rows = ys.into_rows().collect::<Vec<_>>();
             env.without_fill(|env| -> UiuaResult {
+                if calls >= PAR_TABLE_THRESHOLD && env.can_par_call(&f, xs.row_count()) {
+                    // The calls are split into contiguous chunks of rows of the result,
+                    // so the results come back in the same order as the sequential loop
+                    let mut args = Vec::with_capacity(calls);
+                    for x_row in xs.into_rows() {
+                        for y_row in y_rows.iter().cloned() {
+                            args.push(vec![y_row, x_row.clone()]);
+                        }
+                    }
+                    for outs in env.par_call(&f, args, "tabled function result")? {
+                        for (i, item) in outs.into_iter().enumerate() {
+                            items[i].add_row(item, env)?;
+                        }
+                    }
+                    return Ok(());
+                }
                 for x_row in xs.into_rows() {
                     for y_row in y_rows.iter().cloned() {
                         env.push(y_row);
@@ -215,7 +294,7 @@ pub fn table_list(f: Function, xs: Value, ys: Value, env: &mut Uiua) -> UiuaResu
                 let shape = [xs.row_count(), ys.row_count()];
                 let mut data = eco_vec![0.0; xs.row_count() * ys.row_count()];
                 for n in data.make_mut() {
-                    *n = random();
+                    *n = env.rand();
                 }
                 env.push(Array::new(shape, data));
             }
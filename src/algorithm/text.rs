@@ -0,0 +1,162 @@
+//! Case mapping, normalization, and splitting of character arrays
+
+use crate::{Array, Boxed, Primitive, Uiua, UiuaResult, Value};
+
+/// A Unicode normalization form
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NormalForm {
+    Nfc,
+    Nfd,
+    Nfkc,
+    Nfkd,
+}
+
+impl NormalForm {
+    fn parse(form: &Value, env: &Uiua) -> UiuaResult<Self> {
+        let form = form.as_string(env, "Normalization form must be a string")?;
+        Ok(match form.as_str() {
+            "nfc" => NormalForm::Nfc,
+            "nfd" => NormalForm::Nfd,
+            "nfkc" => NormalForm::Nfkc,
+            "nfkd" => NormalForm::Nfkd,
+            _ => {
+                return Err(env.error(format!(
+                    "Unknown normalization form {form:?}. \
+                    Valid forms are \"nfc\", \"nfd\", \"nfkc\", and \"nfkd\""
+                )))
+            }
+        })
+    }
+    fn apply(self, s: &str) -> String {
+        use unicode_normalization::UnicodeNormalization;
+        match self {
+            NormalForm::Nfc => s.nfc().collect(),
+            NormalForm::Nfd => s.nfd().collect(),
+            NormalForm::Nfkc => s.nfkc().collect(),
+            NormalForm::Nfkd => s.nfkd().collect(),
+        }
+    }
+}
+
+impl Value {
+    /// Map each string along the last axis of a character array
+    ///
+    /// Mappings may change the length of a string, so rows of
+    /// different lengths must be filled to be combined.
+    fn map_strings(
+        &self,
+        prim: Primitive,
+        env: &Uiua,
+        f: impl Fn(&str) -> String + Copy,
+    ) -> UiuaResult<Self> {
+        let Value::Char(arr) = self else {
+            return Err(env.error(format!(
+                "Cannot {} {}",
+                prim.format(),
+                self.type_name_plural()
+            )));
+        };
+        map_char_array(arr, env, f)
+    }
+    /// Convert a character array to uppercase
+    pub fn upper(&self, env: &Uiua) -> UiuaResult<Self> {
+        self.map_strings(Primitive::Upper, env, str::to_uppercase)
+    }
+    /// Convert a character array to lowercase
+    pub fn lower(&self, env: &Uiua) -> UiuaResult<Self> {
+        self.map_strings(Primitive::Lower, env, str::to_lowercase)
+    }
+    /// Normalize a character array to the Unicode normalization form named by `self`
+    pub fn normalize(&self, text: &Self, env: &Uiua) -> UiuaResult<Self> {
+        let form = NormalForm::parse(self, env)?;
+        text.map_strings(Primitive::Normalize, env, |s| form.apply(s))
+    }
+    /// Get the delimiter for splitting or joining
+    fn delimiter(&self, prim: Primitive, env: &Uiua) -> UiuaResult<String> {
+        let delim = match self {
+            Value::Char(arr) if arr.rank() <= 1 => arr.data.iter().collect::<String>(),
+            _ => {
+                return Err(env.error(format!(
+                    "Delimiter for {} must be a string or character",
+                    prim.format()
+                )))
+            }
+        };
+        if delim.is_empty() {
+            return Err(env.error(format!("Delimiter for {} cannot be empty", prim.format())));
+        }
+        Ok(delim)
+    }
+    /// Split a string by the delimiter `self` into a list of boxed strings
+    ///
+    /// Higher-rank character arrays are split row by row.
+    pub fn split(&self, text: &Self, env: &Uiua) -> UiuaResult<Self> {
+        let delim = self.delimiter(Primitive::Split, env)?;
+        let Value::Char(arr) = text else {
+            return Err(env.error(format!("Cannot split {}", text.type_name_plural())));
+        };
+        split_char_array(arr, &delim, env)
+    }
+    /// Join a list of boxed strings with the delimiter `self`
+    pub fn inv_split(&self, parts: &Self, env: &Uiua) -> UiuaResult<Self> {
+        const REQUIREMENT: &str = "Argument to inverse split must be a list of strings";
+        let delim = self.delimiter(Primitive::Split, env)?;
+        if parts.rank() > 1 {
+            let rows = parts
+                .rows()
+                .map(|row| self.inv_split(&row, env))
+                .collect::<UiuaResult<Vec<_>>>()?;
+            return Value::from_row_values(rows, env);
+        }
+        let mut s = String::new();
+        for (i, row) in parts.rows().enumerate() {
+            if i > 0 {
+                s.push_str(&delim);
+            }
+            s.push_str(&row.unboxed().as_string(env, REQUIREMENT)?);
+        }
+        Ok(s.into())
+    }
+}
+
+fn split_char_array(arr: &Array<char>, delim: &str, env: &Uiua) -> UiuaResult<Value> {
+    if arr.rank() <= 1 {
+        let s: String = arr.data.iter().collect();
+        let parts = s.split(delim).map(String::from);
+        return Ok(Array::<Boxed>::from_iter(parts).into());
+    }
+    if arr.row_count() == 0 {
+        return Ok(Array::<Boxed>::default().into());
+    }
+    let rows = arr
+        .rows()
+        .map(|row| split_char_array(&row, delim, env))
+        .collect::<UiuaResult<Vec<_>>>()?;
+    Value::from_row_values(rows, env)
+}
+
+fn map_char_array(
+    arr: &Array<char>,
+    env: &Uiua,
+    f: impl Fn(&str) -> String + Copy,
+) -> UiuaResult<Value> {
+    match arr.rank() {
+        0 => {
+            let mapped = f(&arr.data[0].to_string());
+            let mut chars = mapped.chars();
+            Ok(match (chars.next(), chars.next()) {
+                (Some(c), None) => c.into(),
+                _ => mapped.into(),
+            })
+        }
+        1 => Ok(f(&arr.data.iter().collect::<String>()).into()),
+        _ if arr.row_count() == 0 => Ok(arr.clone().into()),
+        _ => {
+            let rows = arr
+                .rows()
+                .map(|row| map_char_array(&row, env, f))
+                .collect::<UiuaResult<Vec<_>>>()?;
+            Value::from_row_values(rows, env)
+        }
+    }
+}
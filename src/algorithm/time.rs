@@ -0,0 +1,411 @@
+//! Conversion between epoch seconds and calendar dates
+//!
+//! All times are UTC.
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+const SECONDS_PER_DAY: f64 = 86400.0;
+
+/// A UTC date and time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct DateTime {
+    pub year: i64,
+    /// 1-12
+    pub month: u32,
+    /// 1-31
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    /// May have a fractional part
+    pub second: f64,
+}
+
+impl DateTime {
+    /// Get the date and time at some seconds since the Unix epoch
+    pub fn from_epoch(secs: f64) -> Result<Self, String> {
+        if !secs.is_finite() || secs.abs() > 1e15 {
+            return Err(format!("{secs} is not a valid time"));
+        }
+        let days = (secs / SECONDS_PER_DAY).floor();
+        let mut rem = secs - days * SECONDS_PER_DAY;
+        let (year, month, day) = civil_from_days(days as i64);
+        let hour = (rem / 3600.0).floor();
+        rem -= hour * 3600.0;
+        let minute = (rem / 60.0).floor();
+        rem -= minute * 60.0;
+        Ok(DateTime {
+            year,
+            month,
+            day,
+            hour: hour as u32,
+            minute: minute as u32,
+            second: rem,
+        })
+    }
+    /// Get the seconds since the Unix epoch
+    pub fn to_epoch(self) -> f64 {
+        let days = days_from_civil(self.year, self.month, self.day);
+        days as f64 * SECONDS_PER_DAY
+            + self.hour as f64 * 3600.0
+            + self.minute as f64 * 60.0
+            + self.second
+    }
+    /// Get the day of the week, where Sunday is 0
+    fn weekday(&self) -> usize {
+        (days_from_civil(self.year, self.month, self.day) + 4).rem_euclid(7) as usize
+    }
+    /// Get the day of the year, starting at 1
+    fn ordinal(&self) -> i64 {
+        days_from_civil(self.year, self.month, self.day) - days_from_civil(self.year, 1, 1) + 1
+    }
+    /// Format with `strftime`-style directives
+    pub fn format(&self, format: &str) -> Result<String, String> {
+        let mut s = String::new();
+        let mut chars = format.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                s.push(c);
+                continue;
+            }
+            let Some(directive) = chars.next() else {
+                return Err("Time format ends with an incomplete directive".into());
+            };
+            let whole_second = self.second.floor();
+            match directive {
+                'Y' => s.push_str(&self.year.to_string()),
+                'y' => s.push_str(&format!("{:02}", self.year.rem_euclid(100))),
+                'm' => s.push_str(&format!("{:02}", self.month)),
+                'd' => s.push_str(&format!("{:02}", self.day)),
+                'e' => s.push_str(&format!("{:>2}", self.day)),
+                'H' => s.push_str(&format!("{:02}", self.hour)),
+                'I' => s.push_str(&format!("{:02}", (self.hour + 11) % 12 + 1)),
+                'p' => s.push_str(if self.hour < 12 { "AM" } else { "PM" }),
+                'M' => s.push_str(&format!("{:02}", self.minute)),
+                'S' => s.push_str(&format!("{:02}", whole_second)),
+                'f' => s.push_str(&format!(
+                    "{:06}",
+                    ((self.second - whole_second) * 1e6).floor()
+                )),
+                'j' => s.push_str(&format!("{:03}", self.ordinal())),
+                'B' => s.push_str(MONTHS[self.month as usize - 1]),
+                'b' => s.push_str(&MONTHS[self.month as usize - 1][..3]),
+                'A' => s.push_str(WEEKDAYS[self.weekday()]),
+                'a' => s.push_str(&WEEKDAYS[self.weekday()][..3]),
+                'u' => s.push_str(&((self.weekday() + 6) % 7 + 1).to_string()),
+                'w' => s.push_str(&self.weekday().to_string()),
+                's' => s.push_str(&self.to_epoch().floor().to_string()),
+                'z' => s.push_str("+0000"),
+                'Z' => s.push_str("UTC"),
+                'F' => s.push_str(&self.format("%Y-%m-%d")?),
+                'T' => s.push_str(&self.format("%H:%M:%S")?),
+                'D' => s.push_str(&self.format("%m/%d/%y")?),
+                '%' => s.push('%'),
+                c => return Err(format!("Unknown time format directive %{c}")),
+            }
+        }
+        Ok(s)
+    }
+    /// Parse with `strftime`-style directives
+    pub fn parse(s: &str, format: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            input: s,
+            year: 1970,
+            month: 1,
+            day: 1,
+            ordinal: None,
+            hour: 0,
+            pm: None,
+            minute: 0,
+            second: 0.0,
+            offset: 0.0,
+            epoch: None,
+        };
+        parser.parse(format)?;
+        if !parser.input.is_empty() {
+            return Err(format!("Unexpected {:?} after parsing time", parser.input));
+        }
+        parser.finish()
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    year: i64,
+    month: u32,
+    day: u32,
+    ordinal: Option<i64>,
+    hour: u32,
+    pm: Option<bool>,
+    minute: u32,
+    second: f64,
+    offset: f64,
+    epoch: Option<f64>,
+}
+
+impl<'a> Parser<'a> {
+    fn parse(&mut self, format: &str) -> Result<(), String> {
+        let mut chars = format.chars();
+        while let Some(c) = chars.next() {
+            if c.is_whitespace() {
+                self.input = self.input.trim_start();
+                continue;
+            }
+            if c != '%' {
+                match self.input.strip_prefix(c) {
+                    Some(rest) => self.input = rest,
+                    None => return Err(self.expected(&format!("{c:?}"))),
+                }
+                continue;
+            }
+            let Some(directive) = chars.next() else {
+                return Err("Time format ends with an incomplete directive".into());
+            };
+            match directive {
+                'Y' => {
+                    let neg = self.sign();
+                    let year = self.number(1, 4, "a year")? as i64;
+                    self.year = if neg { -year } else { year };
+                }
+                'y' => {
+                    let year = self.number(2, 2, "a 2-digit year")? as i64;
+                    self.year = if year < 69 { 2000 + year } else { 1900 + year };
+                }
+                'm' => self.month = self.number_in(1, 12, "a month")?,
+                'd' | 'e' => {
+                    self.input = self.input.trim_start();
+                    self.day = self.number_in(1, 31, "a day")?;
+                }
+                'H' => self.hour = self.number_in(0, 23, "an hour")?,
+                'I' => self.hour = self.number_in(1, 12, "an hour")? % 12,
+                'p' => {
+                    let lower = self.input.get(..2).map(|s| s.to_ascii_lowercase());
+                    self.pm = Some(match lower.as_deref() {
+                        Some("am") => false,
+                        Some("pm") => true,
+                        _ => return Err(self.expected("AM or PM")),
+                    });
+                    self.input = &self.input[2..];
+                }
+                'M' => self.minute = self.number_in(0, 59, "a minute")?,
+                'S' => self.second = self.number_in(0, 60, "a second")? as f64,
+                'f' => {
+                    let digits = self.digits(1, 9, "a fraction of a second")?;
+                    self.second += digits.parse::<f64>().unwrap() / 10f64.powi(digits.len() as i32);
+                }
+                'j' => self.ordinal = Some(self.number_in(1, 366, "a day of the year")? as i64),
+                'B' | 'b' | 'h' => {
+                    let i = self.name(&MONTHS, "a month name")?;
+                    self.month = i as u32 + 1;
+                }
+                'A' | 'a' => {
+                    self.name(&WEEKDAYS, "a weekday name")?;
+                }
+                'u' => _ = self.number_in(1, 7, "a weekday")?,
+                'w' => _ = self.number_in(0, 6, "a weekday")?,
+                's' => {
+                    let neg = self.sign();
+                    let secs = self.number(1, 18, "seconds")?;
+                    self.epoch = Some(if neg { -secs } else { secs });
+                }
+                'z' => self.offset()?,
+                'Z' => {
+                    let end = (self.input)
+                        .find(|c: char| !c.is_ascii_alphabetic())
+                        .unwrap_or(self.input.len());
+                    match &self.input[..end] {
+                        "UTC" | "GMT" | "Z" => self.input = &self.input[end..],
+                        _ => return Err(self.expected("UTC")),
+                    }
+                }
+                'F' => self.parse("%Y-%m-%d")?,
+                'T' => self.parse("%H:%M:%S")?,
+                'D' => self.parse("%m/%d/%y")?,
+                '%' => match self.input.strip_prefix('%') {
+                    Some(rest) => self.input = rest,
+                    None => return Err(self.expected("'%'")),
+                },
+                c => return Err(format!("Unknown time format directive %{c}")),
+            }
+        }
+        Ok(())
+    }
+    fn finish(self) -> Result<DateTime, String> {
+        if let Some(epoch) = self.epoch {
+            return DateTime::from_epoch(epoch);
+        }
+        let hour = match self.pm {
+            Some(true) => self.hour % 12 + 12,
+            Some(false) => self.hour % 12,
+            None => self.hour,
+        };
+        let base = DateTime {
+            year: self.year,
+            month: self.month,
+            day: self.day,
+            hour,
+            minute: self.minute,
+            second: self.second,
+        };
+        let mut epoch = base.to_epoch() - self.offset;
+        if let Some(ordinal) = self.ordinal {
+            epoch += (ordinal - base.ordinal()) as f64 * SECONDS_PER_DAY;
+        } else if DateTime::from_epoch(base.to_epoch())?.day != self.day {
+            return Err(format!(
+                "{} {} has no day {}",
+                MONTHS[self.month as usize - 1],
+                self.year,
+                self.day
+            ));
+        }
+        DateTime::from_epoch(epoch)
+    }
+    fn expected(&self, what: &str) -> String {
+        let found: String = self.input.chars().take(10).collect();
+        if found.is_empty() {
+            format!("Expected {what}, but the time ended")
+        } else {
+            format!("Expected {what} at {found:?}")
+        }
+    }
+    fn sign(&mut self) -> bool {
+        for (sign, neg) in [('-', true), ('¯', true), ('+', false)] {
+            if let Some(rest) = self.input.strip_prefix(sign) {
+                self.input = rest;
+                return neg;
+            }
+        }
+        false
+    }
+    fn digits(&mut self, min: usize, max: usize, what: &str) -> Result<&'a str, String> {
+        let len = (self.input)
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.input.len())
+            .min(max);
+        if len < min {
+            return Err(self.expected(what));
+        }
+        let (digits, rest) = self.input.split_at(len);
+        self.input = rest;
+        Ok(digits)
+    }
+    fn number(&mut self, min: usize, max: usize, what: &str) -> Result<f64, String> {
+        Ok(self.digits(min, max, what)?.parse().unwrap())
+    }
+    fn number_in(&mut self, low: u32, high: u32, what: &str) -> Result<u32, String> {
+        let start = self.input;
+        let n = self.number(1, 2.max(high.to_string().len()), what)? as u32;
+        if n < low || n > high {
+            self.input = start;
+            return Err(self.expected(what));
+        }
+        Ok(n)
+    }
+    fn name(&mut self, names: &[&str], what: &str) -> Result<usize, String> {
+        for (i, name) in names.iter().enumerate() {
+            for len in [name.len(), 3] {
+                let matches =
+                    (self.input.get(..len)).is_some_and(|s| s.eq_ignore_ascii_case(&name[..len]));
+                if matches {
+                    self.input = &self.input[len..];
+                    return Ok(i);
+                }
+            }
+        }
+        Err(self.expected(what))
+    }
+    fn offset(&mut self) -> Result<(), String> {
+        if let Some(rest) = self.input.strip_prefix('Z') {
+            self.input = rest;
+            return Ok(());
+        }
+        let neg = match self.input.chars().next() {
+            Some('+') => false,
+            Some('-' | '¯') => true,
+            _ => return Err(self.expected("a UTC offset")),
+        };
+        self.sign();
+        let hours = self.number_in(0, 23, "an offset hour")?;
+        if let Some(rest) = self.input.strip_prefix(':') {
+            self.input = rest;
+        }
+        let minutes = self.number_in(0, 59, "an offset minute")?;
+        let offset = (hours * 3600 + minutes * 60) as f64;
+        self.offset = if neg { -offset } else { offset };
+        Ok(())
+    }
+}
+
+// These two algorithms are from Howard Hinnant's date algorithms
+// http://howardhinnant.github.io/date_algorithms.html
+
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[test]
+fn time_round_trip() {
+    let t = DateTime::from_epoch(951782400.0).unwrap();
+    assert_eq!((t.year, t.month, t.day), (2000, 2, 29));
+    assert_eq!(
+        t.format("%F %T %a %j").unwrap(),
+        "2000-02-29 00:00:00 Tue 060"
+    );
+    let t = DateTime::from_epoch(-1.5).unwrap();
+    assert_eq!(t.format("%F %T.%f").unwrap(), "1969-12-31 23:59:58.500000");
+    for (s, format) in [
+        ("2024-03-05 14:07:09", "%Y-%m-%d %H:%M:%S"),
+        (
+            "Tuesday, 5 March 2024 2:07:09 PM",
+            "%A, %e %B %Y %I:%M:%S %p",
+        ),
+        ("2024-03-05T15:07:09+01:00", "%FT%T%z"),
+        ("1709647629", "%s"),
+        ("2024 065 14:07:09", "%Y %j %T"),
+    ] {
+        let t = DateTime::parse(s, format).unwrap();
+        assert_eq!(t.to_epoch(), 1709647629.0, "{s}");
+    }
+    assert!(DateTime::parse("2023-02-29", "%F").is_err());
+    assert!(DateTime::parse("2024-13-01", "%F").is_err());
+    assert!(DateTime::parse("2024-01-01 extra", "%F").is_err());
+}
@@ -0,0 +1,371 @@
+//! A compact binary format for persisting values
+
+use ecow::EcoVec;
+
+use crate::{Array, ArrayFlags, ArrayMeta, ArrayValue, Boxed, Complex, Shape, Value};
+
+const UVAL_MAGIC: &[u8] = b"UVAL";
+/// The version of the `.uval` format
+///
+/// This is incremented whenever the encoding of a [`Value`] changes
+const UVAL_VERSION: u16 = 2;
+
+/// Type tags
+const NUM: u8 = 0;
+const BYTE: u8 = 1;
+const COMPLEX: u8 = 2;
+const CHAR: u8 = 3;
+const BOX: u8 = 4;
+
+/// Metadata presence flags
+const HAS_LABEL: u8 = 1;
+const HAS_MAP_LEN: u8 = 2;
+const HAS_MAP_ORDER: u8 = 4;
+
+/// The maximum depth of nested boxes that will be decoded
+const MAX_DEPTH: usize = 1000;
+
+const TRUNCATED: &str = "Serialized Uiua value is truncated";
+
+impl Value {
+    /// Serialize the value into the versioned `.uval` format
+    ///
+    /// The format is the magic bytes `UVAL`, the little-endian format version as a `u16`,
+    /// and then the encoded value. Each array is encoded as its type, shape, metadata, and data.
+    pub fn to_uval(&self) -> Vec<u8> {
+        let mut bytes = UVAL_MAGIC.to_vec();
+        bytes.extend(UVAL_VERSION.to_le_bytes());
+        self.encode(&mut bytes);
+        bytes
+    }
+    /// Deserialize a value from the versioned `.uval` format
+    pub fn from_uval(bytes: &[u8]) -> Result<Self, String> {
+        let bytes = bytes
+            .strip_prefix(UVAL_MAGIC)
+            .ok_or("Not a serialized Uiua value")?;
+        if bytes.len() < 2 {
+            return Err(TRUNCATED.into());
+        }
+        let (version, bytes) = bytes.split_at(2);
+        let version = u16::from_le_bytes([version[0], version[1]]);
+        // Version 1 is version 2 without map orders
+        if !(1..=UVAL_VERSION).contains(&version) {
+            return Err(format!(
+                "Serialized Uiua value has format version {version}, \
+                but this version of Uiua reads versions up to {UVAL_VERSION}"
+            ));
+        }
+        let mut decoder = Decoder { bytes };
+        let value = decoder.value(0)?;
+        if !decoder.bytes.is_empty() {
+            return Err("Serialized Uiua value has trailing data".into());
+        }
+        Ok(value)
+    }
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        match self {
+            Value::Num(arr) => {
+                encode_header(arr, NUM, bytes);
+                for n in &arr.data {
+                    bytes.extend(n.to_le_bytes());
+                }
+            }
+            #[cfg(feature = "bytes")]
+            Value::Byte(arr) => {
+                encode_header(arr, BYTE, bytes);
+                bytes.extend_from_slice(&arr.data);
+            }
+            Value::Int(arr) => Value::Num(arr.convert_ref()).encode(bytes),
+            Value::Complex(arr) => {
+                encode_header(arr, COMPLEX, bytes);
+                for c in &arr.data {
+                    bytes.extend(c.re.to_le_bytes());
+                    bytes.extend(c.im.to_le_bytes());
+                }
+            }
+            Value::Char(arr) => {
+                encode_header(arr, CHAR, bytes);
+                let s: String = arr.data.iter().collect();
+                write_uvarint(s.len(), bytes);
+                bytes.extend_from_slice(s.as_bytes());
+            }
+            Value::Box(arr) => {
+                encode_header(arr, BOX, bytes);
+                for Boxed(value) in &arr.data {
+                    value.encode(bytes);
+                }
+            }
+        }
+    }
+}
+
+fn write_uvarint(mut n: usize, bytes: &mut Vec<u8>) {
+    while n >= 0x80 {
+        bytes.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    bytes.push(n as u8);
+}
+
+fn encode_header<T: ArrayValue>(arr: &Array<T>, tag: u8, bytes: &mut Vec<u8>) {
+    bytes.push(tag);
+    write_uvarint(arr.rank(), bytes);
+    for &dim in arr.shape.iter() {
+        write_uvarint(dim, bytes);
+    }
+    let meta = arr.meta();
+    let mut present = 0;
+    if meta.label.is_some() {
+        present |= HAS_LABEL;
+    }
+    if meta.map_len.is_some() {
+        present |= HAS_MAP_LEN;
+    }
+    if meta.map_order.is_some() {
+        present |= HAS_MAP_ORDER;
+    }
+    bytes.push(present);
+    bytes.push(meta.flags.bits());
+    if let Some(label) = &meta.label {
+        write_uvarint(label.len(), bytes);
+        bytes.extend_from_slice(label.as_bytes());
+    }
+    if let Some(len) = meta.map_len {
+        write_uvarint(len, bytes);
+    }
+    if let Some(order) = &meta.map_order {
+        write_uvarint(order.len(), bytes);
+        for &slot in order {
+            write_uvarint(slot, bytes);
+        }
+    }
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() < n {
+            return Err(TRUNCATED.into());
+        }
+        let (taken, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(taken)
+    }
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+    fn uvarint(&mut self) -> Result<usize, String> {
+        const TOO_LARGE: &str = "Serialized Uiua value has an integer that is too large";
+        let mut n = 0usize;
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.byte()?;
+            let part = (byte & 0x7f) as usize;
+            if shift > 0 && part >> (usize::BITS - shift) != 0 {
+                return Err(TOO_LARGE.into());
+            }
+            n |= part << shift;
+            if byte & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        Err(TOO_LARGE.into())
+    }
+    /// Decode an array's header, checking that there is enough data left
+    /// for `min_width` bytes per element
+    fn header(&mut self, min_width: usize) -> Result<(Shape, Option<ArrayMeta>), String> {
+        let rank = self.uvarint()?;
+        if rank > self.bytes.len() {
+            return Err(TRUNCATED.into());
+        }
+        let mut dims = Vec::with_capacity(rank);
+        for _ in 0..rank {
+            dims.push(self.uvarint()?);
+        }
+        let count = (dims.iter())
+            .try_fold(1usize, |acc, &dim| acc.checked_mul(dim))
+            .ok_or("Serialized Uiua value's shape is too large")?;
+        let present = self.byte()?;
+        let flags = ArrayFlags::from_bits_truncate(self.byte()?);
+        let mut meta = ArrayMeta {
+            flags,
+            ..ArrayMeta::default()
+        };
+        if present & HAS_LABEL != 0 {
+            let len = self.uvarint()?;
+            let label = std::str::from_utf8(self.take(len)?)
+                .map_err(|_| "Serialized Uiua value has an invalid label")?;
+            meta.label = Some(label.into());
+        }
+        if present & HAS_MAP_LEN != 0 {
+            meta.map_len = Some(self.uvarint()?);
+        }
+        if present & HAS_MAP_ORDER != 0 {
+            let len = self.uvarint()?;
+            if len > self.bytes.len() {
+                return Err(TRUNCATED.into());
+            }
+            let order = (0..len)
+                .map(|_| self.uvarint())
+                .collect::<Result<EcoVec<_>, _>>()?;
+            meta.map_order = Some(order);
+        }
+        if count.saturating_mul(min_width) > self.bytes.len() {
+            return Err(TRUNCATED.into());
+        }
+        let is_default = meta.label.is_none()
+            && meta.map_len.is_none()
+            && meta.map_order.is_none()
+            && flags.is_empty();
+        Ok((Shape::from(dims.as_slice()), (!is_default).then_some(meta)))
+    }
+    fn value(&mut self, depth: usize) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return Err("Serialized Uiua value is nested too deeply".into());
+        }
+        let tag = self.byte()?;
+        Ok(match tag {
+            NUM => {
+                let (shape, meta) = self.header(8)?;
+                let count: usize = shape.iter().product();
+                let data: EcoVec<f64> = (self.take(count * 8)?.chunks_exact(8))
+                    .map(|b| f64::from_le_bytes(b.try_into().unwrap()))
+                    .collect();
+                with_meta(Array::new(shape, data), meta).into()
+            }
+            BYTE => {
+                let (shape, meta) = self.header(1)?;
+                let count: usize = shape.iter().product();
+                let data = self.take(count)?;
+                #[cfg(feature = "bytes")]
+                {
+                    with_meta(Array::<u8>::new(shape, data), meta).into()
+                }
+                #[cfg(not(feature = "bytes"))]
+                {
+                    let data: EcoVec<f64> = data.iter().map(|&b| b as f64).collect();
+                    with_meta(Array::new(shape, data), meta).into()
+                }
+            }
+            COMPLEX => {
+                let (shape, meta) = self.header(16)?;
+                let count: usize = shape.iter().product();
+                let data: EcoVec<Complex> = (self.take(count * 16)?.chunks_exact(16))
+                    .map(|b| {
+                        let re = f64::from_le_bytes(b[..8].try_into().unwrap());
+                        let im = f64::from_le_bytes(b[8..].try_into().unwrap());
+                        Complex::new(re, im)
+                    })
+                    .collect();
+                with_meta(Array::new(shape, data), meta).into()
+            }
+            CHAR => {
+                let (shape, meta) = self.header(1)?;
+                let count: usize = shape.iter().product();
+                let len = self.uvarint()?;
+                let s = std::str::from_utf8(self.take(len)?)
+                    .map_err(|_| "Serialized Uiua value has invalid UTF-8")?;
+                let data: EcoVec<char> = s.chars().collect();
+                if data.len() != count {
+                    return Err("Serialized Uiua value has the wrong number of characters".into());
+                }
+                with_meta(Array::new(shape, data), meta).into()
+            }
+            BOX => {
+                // Each boxed value takes at least 4 bytes
+                let (shape, meta) = self.header(4)?;
+                let count: usize = shape.iter().product();
+                let mut data = EcoVec::with_capacity(count);
+                for _ in 0..count {
+                    data.push(Boxed(self.value(depth + 1)?));
+                }
+                if let Some(order) = meta.as_ref().and_then(|meta| meta.map_order.as_ref()) {
+                    let slots = data.first().map_or(0, |keys| keys.0.row_count());
+                    if order.iter().any(|&slot| slot >= slots) {
+                        return Err("Serialized Uiua value has an invalid map order".into());
+                    }
+                }
+                with_meta(Array::new(shape, data), meta).into()
+            }
+            tag => return Err(format!("Serialized Uiua value has unknown type tag {tag}")),
+        })
+    }
+}
+
+fn with_meta<T: ArrayValue>(mut arr: Array<T>, meta: Option<ArrayMeta>) -> Array<T> {
+    if let Some(meta) = meta {
+        *arr.meta_mut() = meta;
+    }
+    arr
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Uiua;
+
+    fn round_trip(value: Value) {
+        let decoded = Value::from_uval(&value.to_uval()).unwrap();
+        assert_eq!(value, decoded);
+        assert_eq!(value.shape(), decoded.shape());
+        assert_eq!(value.type_name(), decoded.type_name());
+    }
+
+    #[test]
+    fn uval_round_trip() {
+        round_trip(Value::from(5.0));
+        round_trip(
+            Array::<f64>::new([2, 3], [1.0, -0.5, f64::NAN, f64::INFINITY, 0.1, 1e300]).into(),
+        );
+        round_trip(Array::<f64>::new([0, 3], []).into());
+        #[cfg(feature = "bytes")]
+        round_trip(Array::<u8>::new(3, [1, 2, 255]).into());
+        round_trip(Array::<Complex>::new(2, [Complex::new(1.0, 2.0), Complex::I]).into());
+        round_trip("héllo 👋\0\n".into());
+        round_trip(Array::<char>::new([2, 2], ['a', 'b', char::MAX, '\u{1}']).into());
+        let boxes: Array<Boxed> = [
+            Boxed(Value::from(1.0)),
+            Boxed("two".into()),
+            Boxed(Value::from(Array::<Boxed>::new(
+                1,
+                [Boxed(Value::from(3.0))],
+            ))),
+        ]
+        .into_iter()
+        .collect();
+        round_trip(boxes.into());
+    }
+
+    #[test]
+    fn uval_meta() {
+        let mut labeled = Value::from(Array::<f64>::new(2, [1.0, 2.0]));
+        labeled.meta_mut().label = Some("nums".into());
+        let decoded = Value::from_uval(&labeled.to_uval()).unwrap();
+        assert_eq!(decoded.meta().label.as_deref(), Some("nums"));
+
+        let map = Value::from(Array::<f64>::new(2, [1.0, 2.0]))
+            .map("ab".into(), &Uiua::with_safe_sys())
+            .unwrap();
+        let decoded = Value::from_uval(&map.to_uval()).unwrap();
+        assert_eq!(decoded.meta().map_len, Some(2));
+        assert_eq!(map, decoded);
+        let (keys, values) = decoded.unmap(&Uiua::with_safe_sys()).unwrap();
+        assert_eq!(keys, Value::from(Array::<f64>::new(2, [1.0, 2.0])));
+        assert_eq!(values, Value::from("ab"));
+    }
+
+    #[test]
+    fn uval_errors() {
+        assert!(Value::from_uval(b"nope").is_err());
+        let mut bytes = Value::from(1.0).to_uval();
+        bytes[4] = 99;
+        assert!(Value::from_uval(&bytes).unwrap_err().contains("version 99"));
+        let bytes = Value::from(Array::<f64>::new(3, [1.0, 2.0, 3.0])).to_uval();
+        assert!(Value::from_uval(&bytes[..bytes.len() - 1]).is_err());
+        let mut bytes = bytes;
+        bytes.push(0);
+        assert!(Value::from_uval(&bytes).is_err());
+    }
+}
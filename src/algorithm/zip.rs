@@ -2,11 +2,11 @@
 
 use std::slice;
 
-use ecow::eco_vec;
+use ecow::{eco_vec, EcoVec};
 
 use crate::{
-    algorithm::pervade::bin_pervade_generic, function::Function, random, value::Value, Array,
-    FormatShape, ImplPrimitive, Instr, Primitive, Uiua, UiuaResult,
+    algorithm::pervade::bin_pervade_generic, function::Function, value::Value, Array, Boxed,
+    FormatShape, ImplPrimitive, Instr, Primitive, Shape, Uiua, UiuaResult,
 };
 
 use super::{multi_output, MultiOutput};
@@ -55,12 +55,12 @@ fn impl_prim_un_fast_fn(prim: ImplPrimitive, span: usize) -> Option<ValueUnFn> {
             Value::transpose_depth(&mut v, d, n);
             Ok(v)
         }),
-        ReplaceRand => spanned_un_fn(span, |v, d, _| {
+        ReplaceRand => spanned_un_fn(span, |v, d, env| {
             let shape = &v.shape()[..d.min(v.rank())];
             let elem_count: usize = shape.iter().product();
             let mut data = eco_vec![0.0; elem_count];
             for n in data.make_mut() {
-                *n = random();
+                *n = env.rand();
             }
             Ok(Array::new(shape, data).into())
         }),
@@ -198,6 +198,13 @@ fn each1(f: Function, xs: Value, env: &mut Uiua) -> UiuaResult {
             for i in 0..outputs {
                 new_values[i].push(env.pop("each's function result")?);
             }
+        } else if env.can_par_call(&f, xs.element_count()) {
+            let args = xs.into_elements().map(|val| vec![val]).collect();
+            for outs in env.par_call(&f, args, "each's function result")? {
+                for (i, val) in outs.into_iter().enumerate() {
+                    new_values[i].push(val);
+                }
+            }
         } else {
             for val in xs.into_elements() {
                 env.push(val);
@@ -411,6 +418,13 @@ fn rows1(f: Function, xs: Value, env: &mut Uiua) -> UiuaResult {
             for i in 0..outputs {
                 new_rows[i].push(env.pop("rows' function result")?);
             }
+        } else if env.can_par_call(&f, xs.row_count()) {
+            let args = xs.into_rows().map(|row| vec![row]).collect();
+            for outs in env.par_call(&f, args, "rows' function result")? {
+                for (i, row) in outs.into_iter().enumerate() {
+                    new_rows[i].push(row);
+                }
+            }
         } else {
             for row in xs.into_rows() {
                 env.push(row);
@@ -455,6 +469,15 @@ fn rows2(f: Function, xs: Value, ys: Value, env: &mut Uiua) -> UiuaResult {
                     for i in 0..outputs {
                         new_rows[i].push(env.pop("rows's function result")?);
                     }
+                } else if env.can_par_call(&f, xs.row_count()) {
+                    let args = (xs.into_rows().zip(ys.into_rows()))
+                        .map(|(x, y)| vec![y, x])
+                        .collect();
+                    for outs in env.par_call(&f, args, "rows's function result")? {
+                        for (i, row) in outs.into_iter().enumerate() {
+                            new_rows[i].push(row);
+                        }
+                    }
                 } else {
                     for (x, y) in xs.into_rows().zip(ys.into_rows()) {
                         env.push(y);
@@ -614,3 +637,205 @@ fn rowsn(f: Function, args: Vec<Value>, env: &mut Uiua) -> UiuaResult {
     }
     Ok(())
 }
+
+pub fn level(env: &mut Uiua) -> UiuaResult {
+    crate::profile_function!();
+    let f = env.pop_function()?;
+    let sig = f.signature();
+    if sig.args == 0 {
+        return Err(env.error(format!(
+            "{}'s function must take at least 1 argument",
+            Primitive::Level.format()
+        )));
+    }
+    let ranks = env.pop(1)?.as_number_list(
+        env,
+        "Ranks must be a list of integers or infinity",
+        |n| n.fract() == 0.0 || n.is_infinite(),
+        |n| n as isize,
+    )?;
+    if ranks.len() != 1 && ranks.len() != sig.args {
+        return Err(env.error(format!(
+            "{}'s function takes {} arguments, but there are {} ranks",
+            Primitive::Level.format(),
+            sig.args,
+            ranks.len()
+        )));
+    }
+    // Each argument is split into rows until its cells have the requested rank
+    let mut args = Vec::with_capacity(sig.args);
+    for i in 0..sig.args {
+        let arg = env.pop(i + 2)?;
+        let rank = ranks[if ranks.len() == 1 { 0 } else { i }];
+        let cell_rank = if rank < 0 {
+            arg.rank().saturating_sub(rank.unsigned_abs())
+        } else {
+            arg.rank().min(rank as usize)
+        };
+        let depth = arg.rank() - cell_rank;
+        args.push((arg, depth));
+    }
+    // Splitting every argument once is just rows
+    if args.iter().all(|(_, depth)| *depth == 1) {
+        let mut args = args.into_iter().map(|(arg, _)| arg);
+        return match sig.args {
+            1 => rows1(f, args.next().unwrap(), env),
+            2 => rows2(f, args.next().unwrap(), args.next().unwrap(), env),
+            _ => rowsn(f, args.collect(), env),
+        };
+    }
+    for val in level_impl(&f, args, false, env)?.into_iter().rev() {
+        env.push(val);
+    }
+    Ok(())
+}
+
+/// Call a function on the cells of its arguments
+///
+/// Each argument is paired with the number of times it still needs to be split into rows.
+/// Arguments that are already cells are reused for every row of the others.
+fn level_impl(
+    f: &Function,
+    args: Vec<(Value, usize)>,
+    proxy: bool,
+    env: &mut Uiua,
+) -> UiuaResult<Vec<Value>> {
+    let outputs = f.signature().outputs;
+    if args.iter().all(|(_, depth)| *depth == 0) {
+        for (arg, _) in args.into_iter().rev() {
+            env.push(arg);
+        }
+        env.without_fill(|env| {
+            if proxy {
+                _ = env.call_maintain_sig(f.clone());
+                Ok(())
+            } else {
+                env.call(f.clone())
+            }
+        })?;
+        let mut results = Vec::with_capacity(outputs);
+        for _ in 0..outputs {
+            results.push(env.pop("level's function result")?);
+        }
+        return Ok(results);
+    }
+    // As with rows, an argument with exactly one row is reused for each row of the others
+    let mut row_count = None;
+    for (arg, depth) in &args {
+        if *depth == 0 || arg.row_count() == 1 {
+            continue;
+        }
+        match row_count {
+            Some(n) if n != arg.row_count() => {
+                return Err(env.error(format!(
+                    "Cannot {} arrays with different number of rows {n} and {}",
+                    Primitive::Level.format(),
+                    arg.row_count(),
+                )))
+            }
+            _ => row_count = Some(arg.row_count()),
+        }
+    }
+    let row_count = row_count.unwrap_or(1);
+    let is_empty = outputs > 0 && row_count == 0;
+    let mut arg_rows: Vec<_> = (args.into_iter())
+        .map(|(arg, depth)| {
+            if depth == 0 {
+                Err((arg, 0))
+            } else if arg.row_count() == 1 {
+                Err((arg.into_rows().next().unwrap(), depth - 1))
+            } else {
+                let proxy = is_empty.then(|| arg.proxy_row(env));
+                Ok((arg.into_rows().chain(proxy), depth - 1))
+            }
+        })
+        .collect();
+    let mut new_rows = multi_output(outputs, Vec::with_capacity(row_count + is_empty as usize));
+    for _ in 0..row_count + is_empty as usize {
+        let cell_args = (arg_rows.iter_mut())
+            .map(|arg| match arg {
+                Ok((rows, depth)) => (rows.next().unwrap(), *depth),
+                Err((row, depth)) => (row.clone(), *depth),
+            })
+            .collect();
+        let results = level_impl(f, cell_args, proxy || is_empty, env)?;
+        for (i, val) in results.into_iter().enumerate() {
+            new_rows[i].push(val);
+        }
+    }
+    new_rows
+        .into_iter()
+        .map(|new_rows| {
+            let mut val = Value::from_row_values(new_rows, env)?;
+            if is_empty {
+                val.pop_row();
+            }
+            Ok(val)
+        })
+        .collect()
+}
+
+pub fn inventory(env: &mut Uiua) -> UiuaResult {
+    crate::profile_function!();
+    let f = env.pop_function()?;
+    let sig = f.signature();
+    if sig.args == 0 {
+        return Err(env.error(format!(
+            "{}'s function must take at least 1 argument",
+            Primitive::Inventory.format()
+        )));
+    }
+    let mut args = Vec::with_capacity(sig.args);
+    for i in 0..sig.args {
+        args.push(env.pop(i + 1)?);
+    }
+    // Scalar arguments are reused for every element of the others
+    let mut shape: Option<Shape> = None;
+    for arg in &args {
+        if arg.rank() == 0 {
+            continue;
+        }
+        match &shape {
+            Some(sh) if sh != arg.shape() => {
+                return Err(env.error(format!(
+                    "Cannot {} arrays of shapes {} and {}",
+                    Primitive::Inventory.format(),
+                    sh,
+                    arg.shape()
+                )))
+            }
+            _ => shape = Some(arg.shape().clone()),
+        }
+    }
+    let shape = shape.unwrap_or_default();
+    let elem_count = shape.iter().product();
+    let mut arg_elems: Vec<_> = (args.into_iter())
+        .map(|arg| {
+            if arg.rank() == 0 {
+                Err(arg.unboxed())
+            } else {
+                Ok(arg.into_elements().map(Value::unboxed))
+            }
+        })
+        .collect();
+    let mut new_values = multi_output(sig.outputs, EcoVec::with_capacity(elem_count));
+    env.without_fill(|env| -> UiuaResult {
+        for _ in 0..elem_count {
+            for arg in arg_elems.iter_mut().rev() {
+                match arg {
+                    Ok(elems) => env.push(elems.next().unwrap()),
+                    Err(elem) => env.push(elem.clone()),
+                }
+            }
+            env.call(f.clone())?;
+            for i in 0..sig.outputs {
+                new_values[i].push(Boxed(env.pop("inventory's function result")?));
+            }
+        }
+        Ok(())
+    })?;
+    for new_values in new_values.into_iter().rev() {
+        env.push(Array::new(shape.clone(), new_values));
+    }
+    Ok(())
+}
@@ -68,6 +68,9 @@ pub struct ArrayMeta {
     /// The length of a map array
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub map_len: Option<usize>,
+    /// The slots of a map array's entries in insertion order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub map_order: Option<EcoVec<usize>>,
 }
 
 bitflags! {
@@ -93,10 +96,12 @@ impl ArrayFlags {
 }
 
 /// Default metadata for an array
+#[doc(hidden)]
 pub static DEFAULT_META: ArrayMeta = ArrayMeta {
     label: None,
     flags: ArrayFlags::NONE,
     map_len: None,
+    map_order: None,
 };
 
 impl<T: ArrayValue> Default for Array<T> {
@@ -243,6 +248,7 @@ impl<T> Array<T> {
             let meta = Arc::make_mut(meta);
             meta.flags &= other.flags;
             meta.map_len = None;
+            meta.map_order = None;
         }
     }
 }
@@ -624,6 +630,21 @@ impl ArrayValue for u8 {
     }
 }
 
+impl ArrayValue for u32 {
+    const NAME: &'static str = "number";
+    const SYMBOL: char = 'ℝ';
+    const TYPE_ID: u8 = 0;
+    fn get_fill(env: &Uiua) -> Result<Self, &'static str> {
+        env.int_fill()
+    }
+    fn array_hash<H: Hasher>(&self, hasher: &mut H) {
+        (*self as f64).to_bits().hash(hasher)
+    }
+    fn proxy() -> Self {
+        0
+    }
+}
+
 impl ArrayValue for char {
     const NAME: &'static str = "character";
     const SYMBOL: char = '@';
@@ -697,6 +718,7 @@ impl ArrayValue for Complex {
 }
 
 /// Trait for comparing array elements
+#[doc(hidden)]
 pub trait ArrayCmp<U = Self> {
     /// Compare two elements
     fn array_cmp(&self, other: &U) -> Ordering;
@@ -719,6 +741,12 @@ impl ArrayCmp for u8 {
     }
 }
 
+impl ArrayCmp for u32 {
+    fn array_cmp(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+}
+
 impl ArrayCmp for Complex {
     fn array_cmp(&self, other: &Self) -> Ordering {
         self.partial_cmp(other).unwrap_or_else(|| {
@@ -751,7 +779,20 @@ impl ArrayCmp<u8> for f64 {
     }
 }
 
+impl ArrayCmp<f64> for u32 {
+    fn array_cmp(&self, other: &f64) -> Ordering {
+        (*self as f64).array_cmp(other)
+    }
+}
+
+impl ArrayCmp<u32> for f64 {
+    fn array_cmp(&self, other: &u32) -> Ordering {
+        self.array_cmp(&(*other as f64))
+    }
+}
+
 /// A formattable shape
+#[doc(hidden)]
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct FormatShape<'a>(pub &'a [usize]);
 
@@ -773,3 +814,142 @@ impl<'a> fmt::Display for FormatShape<'a> {
         write!(f, "]")
     }
 }
+
+#[cfg(feature = "ndarray")]
+mod ndarray_impls {
+    use ndarray::{ArrayBase, ArrayD, Data, Dimension, IxDyn, OwnedRepr, ViewRepr};
+
+    use super::*;
+
+    /// Elements are copied in logical order, so arrays with any strides or memory order are supported
+    impl<T: ArrayValue, S: Data<Elem = T>, D: Dimension> From<ArrayBase<S, D>> for Array<T> {
+        fn from(arr: ArrayBase<S, D>) -> Self {
+            let shape = Shape::from(arr.shape());
+            let data: CowSlice<T> = match arr.as_slice() {
+                Some(slice) => slice.into(),
+                None => arr.iter().cloned().collect(),
+            };
+            Array::new(shape, data)
+        }
+    }
+
+    impl<T: ArrayValue> From<Array<T>> for ArrayD<T> {
+        fn from(arr: Array<T>) -> Self {
+            ArrayD::from_shape_vec(IxDyn(&arr.shape), arr.data.into())
+                .expect("array shape should match its data")
+        }
+    }
+
+    macro_rules! value_from_ndarray {
+        ($($ty:ty => $f:expr),* $(,)?) => {$(
+            impl<D: Dimension> From<ArrayBase<OwnedRepr<$ty>, D>> for Value {
+                fn from(arr: ArrayBase<OwnedRepr<$ty>, D>) -> Self {
+                    Array::from(arr.mapv($f)).into()
+                }
+            }
+            impl<'a, D: Dimension> From<ArrayBase<ViewRepr<&'a $ty>, D>> for Value {
+                fn from(arr: ArrayBase<ViewRepr<&'a $ty>, D>) -> Self {
+                    Array::from(arr.mapv($f)).into()
+                }
+            }
+        )*};
+    }
+
+    value_from_ndarray!(
+        f64 => |x| x,
+        f32 => |x| x as f64,
+        i8 => |x| x as f64,
+        i16 => |x| x as f64,
+        i32 => |x| x as f64,
+        i64 => |x| x as f64,
+        isize => |x| x as f64,
+        u16 => |x| x as f64,
+        u32 => |x| x as f64,
+        u64 => |x| x as f64,
+        usize => |x| x as f64,
+        Complex => |x| x,
+    );
+
+    #[cfg(feature = "bytes")]
+    value_from_ndarray!(u8 => |x| x, bool => u8::from);
+    #[cfg(not(feature = "bytes"))]
+    value_from_ndarray!(u8 => |x| x as f64, bool => |x| u8::from(x) as f64);
+
+    /// Number and byte arrays can be converted to arrays of any real type.
+    /// Conversion to an integer type fails if any element is not an integer in its range.
+    /// On failure, the value is returned unchanged.
+    macro_rules! ndarray_try_from_value {
+        ($($ty:ty => $f:expr),* $(,)?) => {$(
+            impl TryFrom<Value> for ArrayD<$ty> {
+                type Error = Value;
+                fn try_from(value: Value) -> Result<Self, Value> {
+                    let convert: fn(f64) -> Option<$ty> = $f;
+                    let (shape, data) = match &value {
+                        Value::Num(arr) => (
+                            &arr.shape,
+                            arr.data.iter().map(|&x| convert(x)).collect::<Option<Vec<_>>>(),
+                        ),
+                        #[cfg(feature = "bytes")]
+                        Value::Byte(arr) => (
+                            &arr.shape,
+                            arr.data.iter().map(|&x| convert(x as f64)).collect(),
+                        ),
+                        _ => return Err(value),
+                    };
+                    match data {
+                        Some(data) => Ok(ArrayD::from_shape_vec(IxDyn(shape), data)
+                            .expect("array shape should match its data")),
+                        None => Err(value),
+                    }
+                }
+            }
+        )*};
+    }
+
+    macro_rules! int_from_f64 {
+        ($ty:ty) => {
+            |x| {
+                (x.fract() == 0.0 && x >= <$ty>::MIN as f64 && x <= <$ty>::MAX as f64)
+                    .then_some(x as $ty)
+            }
+        };
+    }
+
+    ndarray_try_from_value!(
+        f64 => Some,
+        f32 => |x| Some(x as f32),
+        i8 => int_from_f64!(i8),
+        i16 => int_from_f64!(i16),
+        i32 => int_from_f64!(i32),
+        i64 => int_from_f64!(i64),
+        isize => int_from_f64!(isize),
+        u8 => int_from_f64!(u8),
+        u16 => int_from_f64!(u16),
+        u32 => int_from_f64!(u32),
+        u64 => int_from_f64!(u64),
+        usize => int_from_f64!(usize),
+    );
+}
+
+#[test]
+#[cfg(feature = "ndarray")]
+fn ndarray_conversions() {
+    use ndarray::{arr2, ArrayD};
+
+    let matrix = arr2(&[[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+    let transposed = Array::<f64>::from(matrix.t());
+    assert_eq!(transposed.shape(), &[3, 2]);
+    assert_eq!(transposed.data, [1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+
+    let mut env = Uiua::with_safe_sys();
+    env.push(Value::from(arr2(&[[1i32, 2], [3, 4]])));
+    env.run_str("⇌/+").unwrap();
+    let result = ArrayD::<f64>::try_from(env.pop(()).unwrap()).unwrap();
+    assert_eq!(result.shape(), &[2]);
+    assert_eq!(result.as_slice().unwrap(), &[6.0, 4.0]);
+
+    let ints = ArrayD::<u8>::try_from(Value::from(transposed)).unwrap();
+    assert_eq!(ints.shape(), &[3, 2]);
+    assert!(ArrayD::<i32>::try_from(Value::from(1.5)).is_err());
+    assert!(ArrayD::<f64>::try_from(Value::from("abc")).is_err());
+}
@@ -118,8 +118,43 @@ impl Assembly {
     pub fn remove_top_level(&mut self) {
         self.top_slices.clear();
     }
+    /// Serialize the assembly into the versioned `.uasm` format
+    ///
+    /// The format is the magic bytes `UASM`, the little-endian [`UASM_VERSION`] as a `u16`,
+    /// and then the instructions, bindings, spans, and inputs encoded as [CBOR](https://cbor.io).
+    pub fn to_uasm(&self) -> Vec<u8> {
+        let mut bytes = UASM_MAGIC.to_vec();
+        bytes.extend(UASM_VERSION.to_le_bytes());
+        ciborium::into_writer(self, &mut bytes).expect("failed to serialize assembly");
+        bytes
+    }
+    /// Deserialize an assembly from the versioned `.uasm` format
+    pub fn from_uasm(bytes: &[u8]) -> Result<Self, String> {
+        let bytes = bytes
+            .strip_prefix(UASM_MAGIC)
+            .ok_or("Not a compiled Uiua assembly")?;
+        if bytes.len() < 2 {
+            return Err("Compiled Uiua assembly is truncated".into());
+        }
+        let (version, bytes) = bytes.split_at(2);
+        let version = u16::from_le_bytes([version[0], version[1]]);
+        if version != UASM_VERSION {
+            return Err(format!(
+                "Compiled Uiua assembly has format version {version}, \
+                but this version of Uiua reads version {UASM_VERSION}. \
+                Try rebuilding it."
+            ));
+        }
+        ciborium::from_reader(bytes).map_err(|e| format!("Invalid compiled Uiua assembly: {e}"))
+    }
 }
 
+const UASM_MAGIC: &[u8] = b"UASM";
+/// The version of the `.uasm` format
+///
+/// This is incremented whenever the serialized form of an [`Assembly`] changes
+pub const UASM_VERSION: u16 = 2;
+
 impl AsRef<Assembly> for Assembly {
     fn as_ref(&self) -> &Self {
         self
@@ -266,7 +301,7 @@ enum InstrRep {
     Comment(Ident),
     CallGlobal(usize, bool, Signature),
     BindGlobal(usize, usize),
-    BeginArray(()),
+    BeginArray,
     EndArray(bool, usize),
     Call(usize),
     PushFunc(Function),
@@ -275,7 +310,7 @@ enum InstrRep {
     Label(EcoString, usize),
     Dynamic(DynamicFunction),
     PushLocals(usize, usize),
-    PopLocals(()),
+    PopLocals,
     GetLocal(usize, usize),
     Unpack(usize, usize, bool),
     PushTempFunctions(usize),
@@ -288,7 +323,7 @@ enum InstrRep {
     CopyFromTemp(TempStack, usize, usize, usize),
     DropTemp(TempStack, usize, usize),
     PushSig(Signature),
-    PopSig(()),
+    PopSig,
     SetOutputComment(usize, usize),
     #[serde(untagged)]
     Push(Value),
@@ -305,7 +340,7 @@ impl From<Instr> for InstrRep {
             Instr::Push(value) => Self::Push(value),
             Instr::CallGlobal { index, call, sig } => Self::CallGlobal(index, call, sig),
             Instr::BindGlobal { span, index } => Self::BindGlobal(span, index),
-            Instr::BeginArray => Self::BeginArray(()),
+            Instr::BeginArray => Self::BeginArray,
             Instr::EndArray { boxed, span } => Self::EndArray(boxed, span),
             Instr::Prim(prim, span) => Self::Prim(prim, span),
             Instr::ImplPrim(prim, span) => Self::ImplPrim(prim, span),
@@ -316,7 +351,7 @@ impl From<Instr> for InstrRep {
             Instr::Label { label, span } => Self::Label(label, span),
             Instr::Dynamic(func) => Self::Dynamic(func),
             Instr::PushLocals { count, span } => Self::PushLocals(count, span),
-            Instr::PopLocals => Self::PopLocals(()),
+            Instr::PopLocals => Self::PopLocals,
             Instr::GetLocal { index, span } => Self::GetLocal(index, span),
             Instr::Unpack { count, span, unbox } => Self::Unpack(count, span, unbox),
             Instr::PushTempFunctions(count) => Self::PushTempFunctions(count),
@@ -336,7 +371,7 @@ impl From<Instr> for InstrRep {
             } => Self::CopyFromTemp(stack, offset, count, span),
             Instr::DropTemp { stack, count, span } => Self::DropTemp(stack, count, span),
             Instr::PushSig(sig) => Self::PushSig(sig),
-            Instr::PopSig => Self::PopSig(()),
+            Instr::PopSig => Self::PopSig,
             Instr::SetOutputComment { i, n } => Self::SetOutputComment(i, n),
         }
     }
@@ -349,7 +384,7 @@ impl From<InstrRep> for Instr {
             InstrRep::Push(value) => Self::Push(value),
             InstrRep::CallGlobal(index, call, sig) => Self::CallGlobal { index, call, sig },
             InstrRep::BindGlobal(span, index) => Self::BindGlobal { span, index },
-            InstrRep::BeginArray(()) => Self::BeginArray,
+            InstrRep::BeginArray => Self::BeginArray,
             InstrRep::EndArray(boxed, span) => Self::EndArray { boxed, span },
             InstrRep::Prim(prim, span) => Self::Prim(prim, span),
             InstrRep::ImplPrim(prim, span) => Self::ImplPrim(prim, span),
@@ -360,7 +395,7 @@ impl From<InstrRep> for Instr {
             InstrRep::Label(label, span) => Self::Label { label, span },
             InstrRep::Dynamic(func) => Self::Dynamic(func),
             InstrRep::PushLocals(count, span) => Self::PushLocals { count, span },
-            InstrRep::PopLocals(()) => Self::PopLocals,
+            InstrRep::PopLocals => Self::PopLocals,
             InstrRep::GetLocal(index, span) => Self::GetLocal { index, span },
             InstrRep::Unpack(count, span, unbox) => Self::Unpack { count, span, unbox },
             InstrRep::PushTempFunctions(count) => Self::PushTempFunctions(count),
@@ -380,7 +415,7 @@ impl From<InstrRep> for Instr {
             },
             InstrRep::DropTemp(stack, count, span) => Self::DropTemp { stack, count, span },
             InstrRep::PushSig(sig) => Self::PushSig(sig),
-            InstrRep::PopSig(()) => Self::PopSig,
+            InstrRep::PopSig => Self::PopSig,
             InstrRep::SetOutputComment(i, n) => Self::SetOutputComment { i, n },
         }
     }
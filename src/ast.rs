@@ -17,6 +17,8 @@ pub enum Item {
     Words(Vec<Vec<Sp<Word>>>),
     /// A binding
     Binding(Binding),
+    /// An import
+    Import(Import),
     /// A test scope
     TestScope(Sp<Vec<Item>>),
 }
@@ -38,6 +40,7 @@ impl Item {
                 first.merge(last)
             }
             Item::Binding(binding) => binding.span(),
+            Item::Import(import) => import.span(),
         }
     }
 }
@@ -66,6 +69,34 @@ impl Binding {
     }
 }
 
+/// An import
+///
+/// `Name ~ "path" Item₁ Item₂`
+#[derive(Debug, Clone)]
+pub struct Import {
+    /// The name given to the imported module
+    pub name: Option<Sp<Ident>>,
+    /// The span of the tilde
+    pub tilde_span: CodeSpan,
+    /// The path of the imported file
+    pub path: Sp<String>,
+    /// The names of the imported items
+    pub items: Vec<Sp<Ident>>,
+}
+
+impl Import {
+    /// Get the span of this import
+    pub fn span(&self) -> CodeSpan {
+        let first = (self.name.as_ref())
+            .map(|name| name.span.clone())
+            .unwrap_or_else(|| self.tilde_span.clone());
+        let last = (self.items.last())
+            .map(|item| item.span.clone())
+            .unwrap_or_else(|| self.path.span.clone());
+        first.merge(last)
+    }
+}
+
 /// A word
 #[derive(Clone)]
 #[allow(missing_docs)]
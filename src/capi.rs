@@ -0,0 +1,514 @@
+//! The C API for embedding Uiua
+//!
+//! These functions are exported with C linkage when the `capi` feature is enabled.
+//! Build a shared library with `cargo rustc --release --lib --features capi --crate-type cdylib`.
+//! The header is `include/uiua.h`, generated with `cbindgen --config cbindgen.toml -o include/uiua.h`.
+//!
+//! Environments and values are opaque pointers that must be freed with [`uiua_free`] and [`uiua_value_free`].
+//! Functions that can fail return `0` on success and `-1` on failure.
+
+#![allow(clippy::missing_safety_doc)]
+
+use std::{
+    any::Any,
+    ffi::{c_char, c_int, c_void, CStr, CString},
+    path::Path,
+    ptr, slice,
+};
+
+use ecow::EcoVec;
+
+use crate::{Array, NativeSys, SafeSys, SysBackend, Uiua, Value};
+
+/// A Uiua environment
+pub struct UiuaEnv {
+    uiua: Uiua,
+    error: Option<CString>,
+}
+
+/// A Uiua value
+pub struct UiuaValue {
+    value: Value,
+}
+
+/// The type of a Uiua value's elements
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UiuaType {
+    /// Numbers
+    Num,
+    /// Bytes
+    Byte,
+    /// Complex numbers
+    Complex,
+    /// Characters
+    Char,
+    /// Boxes
+    Box,
+}
+
+/// Callbacks that implement a system backend
+///
+/// Any callback may be null, in which case the operation is not supported.
+/// `data` is passed to every callback.
+/// Strings and buffers returned by callbacks must stay valid until the next callback is called.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct UiuaBackend {
+    /// Data passed to every callback
+    pub data: *mut c_void,
+    /// Print a string to stdout
+    pub print_stdout: Option<unsafe extern "C" fn(data: *mut c_void, s: *const c_char, len: usize)>,
+    /// Print a string to stderr
+    pub print_stderr: Option<unsafe extern "C" fn(data: *mut c_void, s: *const c_char, len: usize)>,
+    /// Read a line from stdin without the newline, or return null at the end of input
+    pub scan_line: Option<unsafe extern "C" fn(data: *mut c_void) -> *const c_char>,
+    /// Get an environment variable, or return null if it is not set
+    pub var: Option<unsafe extern "C" fn(data: *mut c_void, name: *const c_char) -> *const c_char>,
+    /// Read a whole file
+    pub file_read_all: Option<
+        unsafe extern "C" fn(
+            data: *mut c_void,
+            path: *const c_char,
+            out: *mut *const u8,
+            out_len: *mut usize,
+        ) -> c_int,
+    >,
+    /// Write a whole file
+    pub file_write_all: Option<
+        unsafe extern "C" fn(
+            data: *mut c_void,
+            path: *const c_char,
+            contents: *const u8,
+            len: usize,
+        ) -> c_int,
+    >,
+    /// Get the current time in seconds since the Unix epoch
+    pub now: Option<unsafe extern "C" fn(data: *mut c_void) -> f64>,
+}
+
+/// A system backend that calls the callbacks of a [`UiuaBackend`]
+struct CallbackSys(UiuaBackend);
+
+// Hosts are responsible for making their callbacks safe to call from any thread
+unsafe impl Send for CallbackSys {}
+unsafe impl Sync for CallbackSys {}
+
+fn c_string(s: &str) -> CString {
+    CString::new(s.replace('\0', "")).unwrap()
+}
+
+unsafe fn str_from_ptr<'a>(s: *const c_char) -> Option<&'a str> {
+    (!s.is_null())
+        .then(|| CStr::from_ptr(s).to_str().ok())
+        .flatten()
+}
+
+impl SysBackend for CallbackSys {
+    fn any(&self) -> &dyn Any {
+        self
+    }
+    fn any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn print_str_stdout(&self, s: &str) -> Result<(), String> {
+        let Some(f) = self.0.print_stdout else {
+            return SafeSys.print_str_stdout(s);
+        };
+        unsafe { f(self.0.data, s.as_ptr().cast(), s.len()) };
+        Ok(())
+    }
+    fn print_str_stderr(&self, s: &str) -> Result<(), String> {
+        let Some(f) = self.0.print_stderr else {
+            return SafeSys.print_str_stderr(s);
+        };
+        unsafe { f(self.0.data, s.as_ptr().cast(), s.len()) };
+        Ok(())
+    }
+    fn scan_line_stdin(&self) -> Result<Option<String>, String> {
+        let Some(f) = self.0.scan_line else {
+            return SafeSys.scan_line_stdin();
+        };
+        unsafe {
+            let line = f(self.0.data);
+            if line.is_null() {
+                return Ok(None);
+            }
+            Ok(Some(CStr::from_ptr(line).to_string_lossy().into_owned()))
+        }
+    }
+    fn var(&self, name: &str) -> Option<String> {
+        let f = self.0.var?;
+        let name = c_string(name);
+        unsafe { str_from_ptr(f(self.0.data, name.as_ptr())).map(Into::into) }
+    }
+    fn file_read_all(&self, path: &Path) -> Result<Vec<u8>, String> {
+        let Some(f) = self.0.file_read_all else {
+            return SafeSys.file_read_all(path);
+        };
+        let c_path = c_string(&path.to_string_lossy());
+        let mut out = ptr::null();
+        let mut len = 0;
+        unsafe {
+            if f(self.0.data, c_path.as_ptr(), &mut out, &mut len) != 0 {
+                return Err(format!("Failed to read {}", path.display()));
+            }
+            Ok(if out.is_null() {
+                Vec::new()
+            } else {
+                slice::from_raw_parts(out, len).to_vec()
+            })
+        }
+    }
+    fn file_write_all(&self, path: &Path, contents: &[u8]) -> Result<(), String> {
+        let Some(f) = self.0.file_write_all else {
+            return SafeSys.file_write_all(path, contents);
+        };
+        let c_path = c_string(&path.to_string_lossy());
+        let (data, len) = (contents.as_ptr(), contents.len());
+        if unsafe { f(self.0.data, c_path.as_ptr(), data, len) } != 0 {
+            return Err(format!("Failed to write {}", path.display()));
+        }
+        Ok(())
+    }
+    fn now(&self) -> f64 {
+        match self.0.now {
+            Some(f) => unsafe { f(self.0.data) },
+            None => SafeSys.now(),
+        }
+    }
+}
+
+fn new_env(uiua: Uiua) -> *mut UiuaEnv {
+    Box::into_raw(Box::new(UiuaEnv { uiua, error: None }))
+}
+
+fn new_value(value: Value) -> *mut UiuaValue {
+    Box::into_raw(Box::new(UiuaValue { value }))
+}
+
+/// Create an environment with no IO capabilities
+#[no_mangle]
+pub extern "C" fn uiua_new() -> *mut UiuaEnv {
+    new_env(Uiua::with_safe_sys())
+}
+
+/// Create an environment with full access to the native system
+#[no_mangle]
+pub extern "C" fn uiua_new_native() -> *mut UiuaEnv {
+    new_env(Uiua::with_backend(NativeSys))
+}
+
+/// Create an environment whose system operations call the given callbacks
+///
+/// The callbacks are copied, but `backend->data` must stay valid until the environment is freed.
+#[no_mangle]
+pub unsafe extern "C" fn uiua_new_with_backend(backend: *const UiuaBackend) -> *mut UiuaEnv {
+    if backend.is_null() {
+        return ptr::null_mut();
+    }
+    new_env(Uiua::with_backend(CallbackSys(*backend)))
+}
+
+/// Free an environment
+#[no_mangle]
+pub unsafe extern "C" fn uiua_free(env: *mut UiuaEnv) {
+    if !env.is_null() {
+        drop(Box::from_raw(env));
+    }
+}
+
+/// Run a string of Uiua code
+///
+/// The values on the stack are kept between runs.
+/// On failure, the error message can be gotten with [`uiua_error`].
+#[no_mangle]
+pub unsafe extern "C" fn uiua_run_str(env: *mut UiuaEnv, code: *const c_char) -> c_int {
+    let Some(env) = env.as_mut() else {
+        return -1;
+    };
+    let Some(code) = str_from_ptr(code) else {
+        env.error = Some(c_string("Code must be valid UTF-8"));
+        return -1;
+    };
+    match env.uiua.run_str(code) {
+        Ok(_) => {
+            env.error = None;
+            0
+        }
+        Err(e) => {
+            env.error = Some(c_string(&e.to_string()));
+            -1
+        }
+    }
+}
+
+/// Get the message of the last error, or null if the last run succeeded
+///
+/// The string is valid until the next call to [`uiua_run_str`] or [`uiua_free`].
+#[no_mangle]
+pub unsafe extern "C" fn uiua_error(env: *const UiuaEnv) -> *const c_char {
+    (env.as_ref())
+        .and_then(|env| env.error.as_ref())
+        .map_or(ptr::null(), |e| e.as_ptr())
+}
+
+/// Get the number of values on the stack
+#[no_mangle]
+pub unsafe extern "C" fn uiua_stack_len(env: *const UiuaEnv) -> usize {
+    env.as_ref().map_or(0, |env| env.uiua.stack().len())
+}
+
+/// Push a value onto the stack, taking ownership of it
+#[no_mangle]
+pub unsafe extern "C" fn uiua_push(env: *mut UiuaEnv, value: *mut UiuaValue) {
+    if env.is_null() || value.is_null() {
+        return;
+    }
+    (*env).uiua.push(Box::from_raw(value).value);
+}
+
+/// Pop a value from the stack, or return null if the stack is empty
+#[no_mangle]
+pub unsafe extern "C" fn uiua_pop(env: *mut UiuaEnv) -> *mut UiuaValue {
+    match env.as_mut().and_then(|env| env.uiua.pop(()).ok()) {
+        Some(value) => new_value(value),
+        None => ptr::null_mut(),
+    }
+}
+
+unsafe fn array_from<T: Clone>(
+    data: *const T,
+    shape: *const usize,
+    rank: usize,
+) -> Option<Array<T>> {
+    let shape: &[usize] = if rank == 0 {
+        &[]
+    } else if shape.is_null() {
+        return None;
+    } else {
+        slice::from_raw_parts(shape, rank)
+    };
+    let len = shape.iter().product();
+    if data.is_null() && len > 0 {
+        return None;
+    }
+    let data: EcoVec<T> = if len == 0 {
+        EcoVec::new()
+    } else {
+        slice::from_raw_parts(data, len).iter().cloned().collect()
+    };
+    Some(Array::new(shape, data))
+}
+
+/// Create a number array from a buffer of elements in row-major order
+///
+/// The buffer must have as many elements as the product of the shape.
+#[no_mangle]
+pub unsafe extern "C" fn uiua_value_from_f64s(
+    data: *const f64,
+    shape: *const usize,
+    rank: usize,
+) -> *mut UiuaValue {
+    array_from(data, shape, rank).map_or(ptr::null_mut(), |arr| new_value(arr.into()))
+}
+
+/// Create a byte array from a buffer of elements in row-major order
+///
+/// The buffer must have as many elements as the product of the shape.
+#[no_mangle]
+pub unsafe extern "C" fn uiua_value_from_u8s(
+    data: *const u8,
+    shape: *const usize,
+    rank: usize,
+) -> *mut UiuaValue {
+    array_from(data, shape, rank).map_or(ptr::null_mut(), |arr| new_value(arr.into()))
+}
+
+/// Create a string from a null-terminated UTF-8 string
+#[no_mangle]
+pub unsafe extern "C" fn uiua_value_from_str(s: *const c_char) -> *mut UiuaValue {
+    str_from_ptr(s).map_or(ptr::null_mut(), |s| new_value(s.into()))
+}
+
+/// Free a value
+#[no_mangle]
+pub unsafe extern "C" fn uiua_value_free(value: *mut UiuaValue) {
+    if !value.is_null() {
+        drop(Box::from_raw(value));
+    }
+}
+
+/// Get the type of a value's elements
+#[no_mangle]
+pub unsafe extern "C" fn uiua_value_type(value: *const UiuaValue) -> UiuaType {
+    match &(*value).value {
+        Value::Num(_) | Value::Int(_) => UiuaType::Num,
+        #[cfg(feature = "bytes")]
+        Value::Byte(_) => UiuaType::Byte,
+        Value::Complex(_) => UiuaType::Complex,
+        Value::Char(_) => UiuaType::Char,
+        Value::Box(_) => UiuaType::Box,
+    }
+}
+
+/// Get the rank of a value
+#[no_mangle]
+pub unsafe extern "C" fn uiua_value_rank(value: *const UiuaValue) -> usize {
+    (*value).value.rank()
+}
+
+/// Get a pointer to the `rank` dimensions of a value's shape
+///
+/// The pointer is valid until the value is freed.
+#[no_mangle]
+pub unsafe extern "C" fn uiua_value_shape(value: *const UiuaValue) -> *const usize {
+    (*value).value.shape().as_ptr()
+}
+
+/// Get the number of elements in a value
+#[no_mangle]
+pub unsafe extern "C" fn uiua_value_len(value: *const UiuaValue) -> usize {
+    (*value).value.element_count()
+}
+
+/// Copy the elements of a number or byte array into a buffer of at least `len` numbers
+#[no_mangle]
+pub unsafe extern "C" fn uiua_value_f64s(
+    value: *const UiuaValue,
+    out: *mut f64,
+    len: usize,
+) -> c_int {
+    let value = &(*value).value;
+    if out.is_null() || len < value.element_count() {
+        return -1;
+    }
+    let out = slice::from_raw_parts_mut(out, len);
+    match value {
+        Value::Num(arr) => out[..arr.data.len()].copy_from_slice(&arr.data),
+        #[cfg(feature = "bytes")]
+        Value::Byte(arr) => {
+            for (o, &b) in out.iter_mut().zip(&arr.data) {
+                *o = b as f64;
+            }
+        }
+        Value::Int(arr) => {
+            for (o, &i) in out.iter_mut().zip(&arr.data) {
+                *o = i as f64;
+            }
+        }
+        _ => return -1,
+    }
+    0
+}
+
+/// Copy the elements of a byte array, or a number array of integers from 0 to 255,
+/// into a buffer of at least `len` bytes
+#[no_mangle]
+pub unsafe extern "C" fn uiua_value_u8s(
+    value: *const UiuaValue,
+    out: *mut u8,
+    len: usize,
+) -> c_int {
+    let value = &(*value).value;
+    if out.is_null() || len < value.element_count() {
+        return -1;
+    }
+    let out = slice::from_raw_parts_mut(out, len);
+    match value {
+        Value::Num(arr) => {
+            if !(arr.data.iter()).all(|&n| n.fract() == 0.0 && (0.0..=255.0).contains(&n)) {
+                return -1;
+            }
+            for (o, &n) in out.iter_mut().zip(&arr.data) {
+                *o = n as u8;
+            }
+        }
+        #[cfg(feature = "bytes")]
+        Value::Byte(arr) => out[..arr.data.len()].copy_from_slice(&arr.data),
+        Value::Int(arr) => {
+            if !(arr.data.iter()).all(|&i| i <= 255) {
+                return -1;
+            }
+            for (o, &i) in out.iter_mut().zip(&arr.data) {
+                *o = i as u8;
+            }
+        }
+        _ => return -1,
+    }
+    0
+}
+
+/// Get a character array of rank 0 or 1 as a null-terminated UTF-8 string,
+/// or return null if the value is not a string
+///
+/// The string must be freed with [`uiua_string_free`].
+#[no_mangle]
+pub unsafe extern "C" fn uiua_value_str(value: *const UiuaValue) -> *mut c_char {
+    match &(*value).value {
+        Value::Char(arr) if arr.rank() <= 1 => {
+            c_string(&arr.data.iter().collect::<String>()).into_raw()
+        }
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Free a string returned by [`uiua_value_str`]
+#[no_mangle]
+pub unsafe extern "C" fn uiua_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Get a row of a value, or return null if the index is out of bounds
+///
+/// Boxed rows are unboxed. The row must be freed with [`uiua_value_free`].
+#[no_mangle]
+pub unsafe extern "C" fn uiua_value_row(value: *const UiuaValue, index: usize) -> *mut UiuaValue {
+    let value = &(*value).value;
+    if value.rank() == 0 || index >= value.row_count() {
+        return ptr::null_mut();
+    }
+    new_value(value.row(index).unpacked())
+}
+
+#[test]
+fn capi() {
+    unsafe extern "C" fn print(data: *mut c_void, s: *const c_char, len: usize) {
+        let out = &mut *data.cast::<Vec<u8>>();
+        out.extend_from_slice(std::slice::from_raw_parts(s.cast(), len));
+    }
+
+    unsafe {
+        let mut printed = Vec::<u8>::new();
+        let backend = UiuaBackend {
+            data: (&mut printed as *mut Vec<u8>).cast(),
+            print_stdout: Some(print),
+            print_stderr: None,
+            scan_line: None,
+            var: None,
+            file_read_all: None,
+            file_write_all: None,
+            now: None,
+        };
+        let env = uiua_new_with_backend(&backend);
+        let shape = [2, 3];
+        let data = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        uiua_push(env, uiua_value_from_f64s(data.as_ptr(), shape.as_ptr(), 2));
+        assert_eq!(uiua_run_str(env, c"&p \"hi\" /+".as_ptr()), 0);
+        assert_eq!(printed, b"hi\n");
+        let value = uiua_pop(env);
+        assert_eq!(uiua_value_type(value), UiuaType::Num);
+        assert_eq!(uiua_value_rank(value), 1);
+        assert_eq!(*uiua_value_shape(value), 3);
+        let mut out = [0.0; 3];
+        assert_eq!(uiua_value_f64s(value, out.as_mut_ptr(), 3), 0);
+        assert_eq!(out, [5.0, 7.0, 9.0]);
+        uiua_value_free(value);
+        assert_eq!(uiua_stack_len(env), 0);
+        assert_eq!(uiua_run_str(env, c"+ 1".as_ptr()), -1);
+        assert!(!CStr::from_ptr(uiua_error(env)).to_bytes().is_empty());
+        uiua_free(env);
+    }
+}
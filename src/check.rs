@@ -1,4 +1,4 @@
-use std::{borrow::Cow, cmp::Ordering, fmt};
+use std::{borrow::Cow, cmp::Ordering, fmt, ops::Range};
 
 use enum_iterator::Sequence;
 
@@ -25,6 +25,30 @@ pub(crate) fn instrs_signature(instrs: &[Instr]) -> Result<Signature, SigCheckEr
     Ok(env.sig())
 }
 
+/// The signature of a sequence of instructions up to the end of one step
+#[derive(Debug, Clone)]
+pub(crate) struct SigStep {
+    /// The range of instructions in the step
+    ///
+    /// Inlined functions are a single step.
+    pub range: Range<usize>,
+    /// The signature of the instructions up to the end of this step
+    pub sig: Signature,
+}
+
+/// Count the number of arguments and outputs of a function, recording the signature after
+/// each step
+///
+/// If checking fails, the steps before the failing one are still returned.
+pub(crate) fn instrs_signature_steps(
+    instrs: &[Instr],
+) -> (Vec<SigStep>, Result<Signature, SigCheckError>) {
+    let mut env = VirtualEnv::new();
+    env.steps = Some(Vec::new());
+    let res = env.instrs(instrs).map(|_| env.sig());
+    (env.steps.unwrap_or_default(), res)
+}
+
 pub(crate) fn instrs_temp_signatures(
     instrs: &[Instr],
 ) -> Result<[Signature; TempStack::CARDINALITY], SigCheckError> {
@@ -41,6 +65,8 @@ struct VirtualEnv<'a> {
     min_height: usize,
     temp_min_heights: [usize; TempStack::CARDINALITY],
     popped: Vec<usize>,
+    /// The signature after each step, if it is being recorded
+    steps: Option<Vec<SigStep>>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -101,6 +127,7 @@ impl BasicValue {
                 Value::Num(n) => n.data.iter().map(|n| BasicValue::Num(*n)).collect(),
                 #[cfg(feature = "bytes")]
                 Value::Byte(b) => b.data.iter().map(|b| BasicValue::Num(*b as f64)).collect(),
+                Value::Int(b) => b.data.iter().map(|b| BasicValue::Num(*b as f64)).collect(),
                 Value::Complex(c) => c.data.iter().map(|_| BasicValue::Other).collect(),
                 Value::Char(c) => c.data.iter().map(|_| BasicValue::Other).collect(),
                 Value::Box(b) => b.data.iter().map(|_| BasicValue::Other).collect(),
@@ -128,12 +155,12 @@ fn derive_sig(min_height: usize, final_height: usize) -> Signature {
 }
 
 impl<'a> VirtualEnv<'a> {
-    fn from_instrs(instrs: &'a [Instr]) -> Result<Self, SigCheckError> {
+    fn new() -> Self {
         let mut temp_stacks = <[_; TempStack::CARDINALITY]>::default();
         for stack in temp_stacks.iter_mut() {
             *stack = vec![BasicValue::Other; START_HEIGHT];
         }
-        let mut env = VirtualEnv {
+        VirtualEnv {
             stack: (0..START_HEIGHT).rev().map(BasicValue::Unknown).collect(),
             temp_stacks,
             function_stack: Vec::new(),
@@ -141,7 +168,11 @@ impl<'a> VirtualEnv<'a> {
             min_height: START_HEIGHT,
             temp_min_heights: [START_HEIGHT; TempStack::CARDINALITY],
             popped: Vec::new(),
-        };
+            steps: None,
+        }
+    }
+    fn from_instrs(instrs: &'a [Instr]) -> Result<Self, SigCheckError> {
+        let mut env = VirtualEnv::new();
         env.instrs(instrs)?;
         Ok(env)
     }
@@ -162,6 +193,7 @@ impl<'a> VirtualEnv<'a> {
     fn instrs(&mut self, instrs: &'a [Instr]) -> Result<(), SigCheckError> {
         let mut i = 0;
         while i < instrs.len() {
+            let start = i;
             match &instrs[i] {
                 Instr::PushSig(sig) => {
                     let mut depth = 0;
@@ -191,6 +223,14 @@ impl<'a> VirtualEnv<'a> {
                 instr => self.instr(instr)?,
             }
             i += 1;
+            if let Some(steps) = &mut self.steps {
+                if !matches!(instrs[start], Instr::Comment(_)) {
+                    steps.push(SigStep {
+                        range: start..i.min(instrs.len()),
+                        sig: derive_sig(self.min_height, self.stack.len()),
+                    });
+                }
+            }
         }
         Ok(())
     }
@@ -327,10 +367,14 @@ impl<'a> VirtualEnv<'a> {
                     };
                     self.handle_args_outputs(1, outputs)?;
                 }
-                Each | Rows => {
+                Each | Rows | Inventory => {
                     let sig = self.pop_func()?.signature();
                     self.handle_sig(sig)?
                 }
+                Level => {
+                    let sig = self.pop_func()?.signature();
+                    self.handle_args_outputs(sig.args + 1, sig.outputs)?;
+                }
                 Table | Cross => {
                     let sig = self.pop_func()?.signature();
                     self.handle_sig(sig)?;
@@ -339,6 +383,14 @@ impl<'a> VirtualEnv<'a> {
                     let sig = self.pop_func()?.signature();
                     self.handle_args_outputs(2, sig.outputs)?;
                 }
+                GroupBy | PartitionBy => {
+                    let sig = self.pop_func()?.signature();
+                    self.handle_args_outputs(2, sig.outputs + 1)?;
+                }
+                SortBy => {
+                    self.pop_func()?;
+                    self.handle_args_outputs(2, 1)?;
+                }
                 Spawn => {
                     let sig = self.pop_func()?.signature();
                     self.handle_args_outputs(sig.args, 1)?;
@@ -446,7 +498,12 @@ impl<'a> VirtualEnv<'a> {
                     let f = self.pop_func()?;
                     self.handle_sig(f.signature())?;
                 }
-                Unpack | Content | Memo | Comptime => {
+                Seed => {
+                    let f = self.pop_func()?;
+                    let _seed = self.pop()?;
+                    self.handle_sig(f.signature())?;
+                }
+                Unpack | Content | Memo | Comptime | Exact | Big => {
                     let f = self.pop_func()?;
                     self.handle_sig(f.signature())?;
                 }
@@ -679,4 +736,21 @@ mod test {
             ])
         );
     }
+    #[test]
+    fn instrs_signature_steps() {
+        let instrs = [push(1), Prim(Add, 0), Prim(Dup, 0)];
+        let (steps, res) = super::instrs_signature_steps(&instrs);
+        assert_eq!(Ok(Signature::new(1, 2)), res);
+        let steps: Vec<_> = (steps.into_iter())
+            .map(|step| (step.range, step.sig))
+            .collect();
+        assert_eq!(
+            vec![
+                (0..1, Signature::new(0, 1)),
+                (1..2, Signature::new(1, 1)),
+                (2..3, Signature::new(1, 2)),
+            ],
+            steps
+        );
+    }
 }
@@ -0,0 +1,203 @@
+//! Conversion between tables and Arrow IPC and Parquet files
+//!
+//! A table is a rank-2 box array.
+//! Its first row is the column names, and its second row is the columns.
+
+use std::{io::Cursor, iter::repeat, sync::Arc};
+
+use arrow::{
+    array::{Array as _, ArrayRef, AsArray, Float64Array, StringArray},
+    compute::{cast, concat_batches},
+    datatypes::{DataType, Field, Float64Type, Schema, SchemaRef, UInt8Type},
+    ipc::{reader::FileReader, writer::FileWriter},
+    record_batch::RecordBatch,
+};
+use ecow::EcoVec;
+
+use crate::{Array, Boxed, Value};
+
+/// Decode an Arrow IPC file into a table
+pub fn arrow_to_value(bytes: &[u8]) -> Result<Value, String> {
+    let reader = FileReader::try_new(Cursor::new(bytes), None).map_err(|e| e.to_string())?;
+    let schema = reader.schema();
+    let batches = (reader.collect::<Result<Vec<_>, _>>()).map_err(|e| e.to_string())?;
+    batches_to_value(&schema, &batches)
+}
+
+/// Encode a table as an Arrow IPC file
+pub fn value_to_arrow(value: &Value) -> Result<Vec<u8>, String> {
+    let batch = value_to_batch(value)?;
+    let mut bytes = Vec::new();
+    {
+        let mut writer =
+            FileWriter::try_new(&mut bytes, &batch.schema()).map_err(|e| e.to_string())?;
+        writer.write(&batch).map_err(|e| e.to_string())?;
+        writer.finish().map_err(|e| e.to_string())?;
+    }
+    Ok(bytes)
+}
+
+/// Decode a Parquet file into a table
+#[cfg(feature = "parquet")]
+pub fn parquet_to_value(bytes: &[u8]) -> Result<Value, String> {
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    let bytes = bytes_crate::Bytes::from(bytes.to_vec());
+    let builder = ParquetRecordBatchReaderBuilder::try_new(bytes).map_err(|e| e.to_string())?;
+    let schema = builder.schema().clone();
+    let reader = builder.build().map_err(|e| e.to_string())?;
+    let batches = (reader.collect::<Result<Vec<_>, _>>()).map_err(|e| e.to_string())?;
+    batches_to_value(&schema, &batches)
+}
+
+/// Encode a table as a Parquet file
+#[cfg(feature = "parquet")]
+pub fn value_to_parquet(value: &Value) -> Result<Vec<u8>, String> {
+    use parquet::arrow::ArrowWriter;
+    let batch = value_to_batch(value)?;
+    let mut bytes = Vec::new();
+    let mut writer =
+        ArrowWriter::try_new(&mut bytes, batch.schema(), None).map_err(|e| e.to_string())?;
+    writer.write(&batch).map_err(|e| e.to_string())?;
+    writer.close().map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+fn batches_to_value(schema: &SchemaRef, batches: &[RecordBatch]) -> Result<Value, String> {
+    let batch = concat_batches(schema, batches).map_err(|e| e.to_string())?;
+    let mut names = EcoVec::new();
+    let mut columns = EcoVec::new();
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        let value =
+            column_to_value(column).map_err(|e| format!("Column {:?}: {e}", field.name()))?;
+        names.push(Boxed(field.name().as_str().into()));
+        columns.push(Boxed(value));
+    }
+    let width = names.len();
+    names.extend(columns);
+    Ok(Array::new([2, width], names).into())
+}
+
+fn column_to_value(column: &ArrayRef) -> Result<Value, String> {
+    Ok(match column.data_type() {
+        DataType::Boolean => {
+            let column = column.as_boolean();
+            let bits: Vec<bool> = (0..column.len())
+                .map(|i| column.is_valid(i) && column.value(i))
+                .collect();
+            Array::<u8>::from(bits).into()
+        }
+        DataType::UInt8 => {
+            let column = column.as_primitive::<UInt8Type>();
+            let bytes: Array<u8> = column.iter().map(Option::unwrap_or_default).collect();
+            bytes.into()
+        }
+        DataType::Utf8 => strings(column.as_string::<i32>().iter()),
+        DataType::LargeUtf8 => strings(column.as_string::<i64>().iter()),
+        DataType::Binary => binaries(column.as_binary::<i32>().iter()),
+        DataType::LargeBinary => binaries(column.as_binary::<i64>().iter()),
+        DataType::Dictionary(_, values)
+            if matches!(**values, DataType::Utf8 | DataType::LargeUtf8) =>
+        {
+            column_to_value(&cast(column, values).map_err(|e| e.to_string())?)?
+        }
+        DataType::Null => Array::from_iter(repeat(f64::NAN).take(column.len())).into(),
+        ty if ty.is_numeric() || ty.is_temporal() => {
+            let column = cast(column, &DataType::Float64).map_err(|e| e.to_string())?;
+            let column = column.as_primitive::<Float64Type>();
+            let nums: Array<f64> = column.iter().map(|x| x.unwrap_or(f64::NAN)).collect();
+            nums.into()
+        }
+        ty => return Err(format!("columns of type {ty} are not supported")),
+    })
+}
+
+fn strings<'a>(iter: impl Iterator<Item = Option<&'a str>>) -> Value {
+    Array::from_iter(iter.map(|s| Boxed(s.unwrap_or_default().into()))).into()
+}
+
+fn binaries<'a>(iter: impl Iterator<Item = Option<&'a [u8]>>) -> Value {
+    Array::from_iter(iter.map(|b| Boxed(Array::<u8>::from(b.unwrap_or_default()).into()))).into()
+}
+
+fn value_to_batch(value: &Value) -> Result<RecordBatch, String> {
+    const SHAPE: &str = "A table must be a rank 2 box array with 2 rows";
+    let Value::Box(table) = value else {
+        return Err(SHAPE.into());
+    };
+    if table.rank() != 2 || table.shape[0] != 2 {
+        return Err(SHAPE.into());
+    }
+    let (names, columns) = table.data.split_at(table.shape[1]);
+    let mut fields = Vec::with_capacity(names.len());
+    let mut arrays = Vec::with_capacity(names.len());
+    for (Boxed(name), Boxed(column)) in names.iter().zip(columns) {
+        let name: String = match name {
+            Value::Char(arr) if arr.rank() <= 1 => arr.data.iter().collect(),
+            _ => return Err("Column names must be strings".into()),
+        };
+        let array = value_to_column(column).map_err(|e| format!("Column {name:?}: {e}"))?;
+        fields.push(Field::new(name, array.data_type().clone(), false));
+        arrays.push(array);
+    }
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays).map_err(|e| e.to_string())
+}
+
+fn value_to_column(value: &Value) -> Result<ArrayRef, String> {
+    Ok(match value {
+        Value::Char(arr) if arr.rank() == 2 => Arc::new(StringArray::from_iter_values(
+            arr.rows().map(|row| row.data.iter().collect::<String>()),
+        )),
+        value if value.rank() != 1 => {
+            return Err(format!(
+                "columns must be rank 1, but this one is rank {}",
+                value.rank()
+            ))
+        }
+        Value::Num(arr) => Arc::new(Float64Array::from(arr.data.to_vec())),
+        #[cfg(feature = "bytes")]
+        Value::Byte(arr) => Arc::new(arrow::array::UInt8Array::from(arr.data.to_vec())),
+        Value::Int(arr) => Arc::new(arrow::array::UInt32Array::from(arr.data.to_vec())),
+        Value::Char(arr) => Arc::new(StringArray::from_iter_values(
+            arr.data.iter().map(|c| c.to_string()),
+        )),
+        Value::Box(arr) => {
+            let strings = (arr.data.iter())
+                .map(|Boxed(val)| match val {
+                    Value::Char(s) if s.rank() <= 1 => Ok(s.data.iter().collect::<String>()),
+                    _ => Err("box columns must contain only strings".to_string()),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Arc::new(StringArray::from(strings))
+        }
+        Value::Complex(_) => return Err("complex columns are not supported".into()),
+    })
+}
+
+#[test]
+#[cfg(feature = "parquet")]
+fn parquet_arrow() {
+    use crate::{MemFs, Uiua};
+
+    let mut env = Uiua::with_backend(MemFs::new());
+    let code = "\
+# Experimental!
+T ← ⊟{\"id\" \"name\" \"score\"}{[1 2 3] {\"a\" \"bc\" \"\"} [0.5 ¯1 ∞]}
+&parquetw \"t.parquet\" T
+&arroww \"t.arrow\" T
+≍T &parquetr \"t.parquet\"
+≍T &arrowr \"t.arrow\"
+⍜&parquetr⍜(⊢⊢)(□\"key\"◌) \"t.parquet\"
+⊢⊢&parquetr \"t.parquet\"";
+    env.run_str(code).unwrap();
+    assert_eq!(
+        env.take_stack(),
+        [
+            Value::from(true),
+            Value::from(true),
+            Boxed("key".into()).into()
+        ]
+    );
+    assert!(env
+        .run_str("# Experimental!\n&parquetw \"u.parquet\" ⊟{\"a\" \"b\"}{[1 2] [3]}")
+        .is_err());
+}
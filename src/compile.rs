@@ -12,17 +12,22 @@ use ecow::{eco_vec, EcoString, EcoVec};
 use crate::{
     algorithm::invert::{invert_instrs, under_instrs},
     ast::*,
-    check::{instrs_signature, SigCheckError},
+    check::{instrs_signature, instrs_signature_steps, SigCheckError},
     constants, example_ua,
     function::*,
     lex::{CodeSpan, Sp, Span},
     optimize::{optimize_instrs, optimize_instrs_mut},
     parse::{count_placeholders, ident_modifier_args, parse, split_words, unsplit_words},
     Array, Assembly, Boxed, Diagnostic, DiagnosticKind, Global, Ident, ImplPrimitive, InputSrc,
-    IntoInputSrc, IntoSysBackend, Primitive, RunMode, SafeSys, SysBackend, SysOp, Uiua, UiuaError,
-    UiuaResult, Value,
+    IntoInputSrc, IntoSysBackend, Lint, LintLevel, Primitive, RunMode, SafeSys, SysBackend, SysOp,
+    Uiua, UiuaError, UiuaResult, Value,
 };
 
+/// The directory that libraries are installed into
+///
+/// Imports that are not found relative to the importing file are looked up here.
+pub const MODULES_DIR: &str = "uiua-modules";
+
 /// The Uiua compiler
 #[derive(Clone)]
 pub struct Compiler {
@@ -46,6 +51,12 @@ pub struct Compiler {
     pub(crate) diagnostics: BTreeSet<Diagnostic>,
     /// Print diagnostics as they are encountered
     pub(crate) print_diagnostics: bool,
+    /// The configured levels of lints
+    pub(crate) lint_levels: HashMap<Lint, LintLevel>,
+    /// The levels of lints set by directives in each file
+    pub(crate) lint_directives: HashMap<InputSrc, HashMap<Lint, LintLevel>>,
+    /// Whether to explain signature errors step by step
+    pub(crate) explain_signatures: bool,
     /// The backend used to run comptime code
     pub(crate) backend: Arc<dyn SysBackend>,
 }
@@ -64,6 +75,9 @@ impl Default for Compiler {
             errors: Vec::new(),
             diagnostics: BTreeSet::new(),
             print_diagnostics: false,
+            lint_levels: HashMap::new(),
+            lint_directives: HashMap::new(),
+            explain_signatures: false,
             backend: Arc::new(SafeSys),
         }
     }
@@ -81,6 +95,32 @@ impl AsMut<Assembly> for Compiler {
     }
 }
 
+/// Compiler state kept between compilations of the same file
+///
+/// See [`Compiler::load_file_cached`]
+#[derive(Default)]
+pub struct CompileCache {
+    path: PathBuf,
+    mode: Option<RunMode>,
+    checkpoints: Vec<Checkpoint>,
+}
+
+/// The state of a compiler after an item that imports a module
+struct Checkpoint {
+    /// The source of the file up to the end of the item's line
+    prefix: EcoString,
+    /// The number of items compiled
+    items: usize,
+    compiler: Compiler,
+}
+
+impl Checkpoint {
+    fn imports_unchanged(&self) -> bool {
+        (self.compiler.asm.import_inputs.iter())
+            .all(|(path, input)| fs::read_to_string(path).is_ok_and(|s| s == input.as_str()))
+    }
+}
+
 #[derive(Clone)]
 pub(crate) struct Scope {
     /// Map local names to global indices
@@ -137,6 +177,24 @@ impl Compiler {
         self.print_diagnostics = print_diagnostics;
         self
     }
+    /// Set the level of a lint
+    ///
+    /// Levels set with `#!allow(...)`, `#!warn(...)`, or `#!deny(...)` comments in a file
+    /// take precedence for diagnostics in that file.
+    pub fn lint_level(&mut self, lint: Lint, level: LintLevel) -> &mut Self {
+        self.lint_levels.insert(lint, level);
+        self
+    }
+    /// Set whether to explain signature errors
+    ///
+    /// When enabled, signature mismatch and inference errors list the signature of a function
+    /// after each of its parts and point out where it diverged from the declared signature.
+    ///
+    /// Defaults to false
+    pub fn explain_signatures(&mut self, explain_signatures: bool) -> &mut Self {
+        self.explain_signatures = explain_signatures;
+        self
+    }
     /// Set the run mode
     pub fn mode(&mut self, mode: RunMode) -> &mut Self {
         self.mode = mode;
@@ -149,17 +207,59 @@ impl Compiler {
             .map_err(|e| UiuaError::Load(path.into(), e.into()))?
             .into();
         self.asm.inputs.files.insert(path.into(), input.clone());
-        self.load_impl(&input, InputSrc::File(path.into()))
+        self.load_impl(&input, InputSrc::File(path.into()), None)
+    }
+    /// Compile a Uiua file from a file at a path, reusing work from a previous compilation
+    ///
+    /// The compiler's state is saved in the cache after each item that imports a module.
+    /// When the same file is compiled again with the same cache, the latest saved state
+    /// whose preceding source and imported files are unchanged is restored, and only the
+    /// items after it are compiled.
+    ///
+    /// Code before a saved state is not rerun, so changes to anything it reads other than
+    /// imported files will not be seen.
+    pub fn load_file_cached<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        cache: &mut CompileCache,
+    ) -> UiuaResult<&mut Self> {
+        let path = path.as_ref();
+        let input: EcoString = fs::read_to_string(path)
+            .map_err(|e| UiuaError::Load(path.into(), e.into()))?
+            .into();
+        if cache.path != path || cache.mode != Some(self.mode) {
+            *cache = CompileCache {
+                path: path.into(),
+                mode: Some(self.mode),
+                checkpoints: Vec::new(),
+            };
+        }
+        let valid = (cache.checkpoints.iter())
+            .take_while(|cp| input.starts_with(cp.prefix.as_str()) && cp.imports_unchanged())
+            .count();
+        cache.checkpoints.truncate(valid);
+        let skip = if let Some(cp) = cache.checkpoints.last() {
+            let print_diagnostics = self.print_diagnostics;
+            let lint_levels = take(&mut self.lint_levels);
+            *self = cp.compiler.clone();
+            self.print_diagnostics = print_diagnostics;
+            self.lint_levels = lint_levels;
+            cp.items
+        } else {
+            0
+        };
+        self.asm.inputs.files.insert(path.into(), input.clone());
+        self.load_impl(&input, InputSrc::File(path.into()), Some((skip, cache)))
     }
     /// Compile a Uiua file from a string
     pub fn load_str(&mut self, input: &str) -> UiuaResult<&mut Self> {
         let src = self.asm.inputs.add_src((), input);
-        self.load_impl(input, src)
+        self.load_impl(input, src, None)
     }
     /// Compile a Uiua file from a string with a path for error reporting
     pub fn load_str_src(&mut self, input: &str, src: impl IntoInputSrc) -> UiuaResult<&mut Self> {
         let src = self.asm.inputs.add_src(src, input);
-        self.load_impl(input, src)
+        self.load_impl(input, src, None)
     }
     /// Run in a scoped context. Names defined in this context will be removed when the scope ends.
     ///
@@ -179,35 +279,56 @@ impl Compiler {
         res?;
         Ok(scope.names)
     }
-    fn load_impl(&mut self, input: &str, src: InputSrc) -> UiuaResult<&mut Self> {
+    fn load_impl(
+        &mut self,
+        input: &str,
+        src: InputSrc,
+        cache: Option<(usize, &mut CompileCache)>,
+    ) -> UiuaResult<&mut Self> {
         let instrs_start = self.asm.instrs.len();
         let top_slices_start = self.asm.top_slices.len();
         let (items, errors, diagnostics) = parse(input, src.clone(), &mut self.asm.inputs);
+        self.lint_directives(&items, &src);
+        for diagnostic in diagnostics {
+            self.add_diagnostic(diagnostic);
+        }
         if self.print_diagnostics {
-            for diagnostic in diagnostics {
+            for diagnostic in self.take_diagnostics() {
                 println!("{}", diagnostic.report());
             }
-        } else {
-            self.diagnostics.extend(diagnostics);
         }
-        if !errors.is_empty() {
-            return Err(UiuaError::Parse(errors, self.asm.inputs.clone().into()));
+        // Items that parsed are still compiled so that their errors are reported too
+        let parse_failed = !errors.is_empty();
+        if parse_failed {
+            self.errors
+                .push(UiuaError::Parse(errors, self.asm.inputs.clone().into()));
         }
         if let InputSrc::File(path) = &src {
             self.current_imports.push(path.to_path_buf());
         }
 
-        let res = self.catching_crash(input, |env| env.items(items, false));
+        let res = self.catching_crash(input, |env| match cache {
+            Some((skip, cache)) => env.items_cached(items, skip, input, cache),
+            None => env.items(items, false),
+        });
 
         if let InputSrc::File(_) = &src {
             self.current_imports.pop();
         }
+        // Bindings in imported files may be used by the importer
+        if self.higher_scopes.is_empty() {
+            self.unused_bindings(&src);
+        }
         match res {
             Err(e) | Ok(Err(e)) => {
                 self.asm.instrs.truncate(instrs_start);
                 self.asm.top_slices.truncate(top_slices_start);
                 self.errors.push(e);
             }
+            _ if parse_failed => {
+                self.asm.instrs.truncate(instrs_start);
+                self.asm.top_slices.truncate(top_slices_start);
+            }
             _ => {}
         }
         match self.errors.len() {
@@ -243,6 +364,34 @@ code:
         }
         Ok(())
     }
+    /// Compile items after the first `skip`, saving the compiler's state after each import
+    fn items_cached(
+        &mut self,
+        items: Vec<Item>,
+        skip: usize,
+        input: &str,
+        cache: &mut CompileCache,
+    ) -> UiuaResult {
+        let mut prev_comment = None;
+        for (i, item) in items.into_iter().enumerate().skip(skip) {
+            let import_count = self.asm.import_inputs.len();
+            let end = item.span().end.byte_pos as usize;
+            if let Err(e) = self.item(item, false, &mut prev_comment) {
+                self.errors.push(e);
+            }
+            if self.errors.is_empty() && self.asm.import_inputs.len() > import_count {
+                let line_end = (input[end..].find('\n')).map_or(input.len(), |j| end + j + 1);
+                let mut compiler = self.clone();
+                compiler.current_imports.pop();
+                cache.checkpoints.push(Checkpoint {
+                    prefix: input[..line_end].into(),
+                    items: i + 1,
+                    compiler,
+                });
+            }
+        }
+        Ok(())
+    }
     fn item(
         &mut self,
         item: Item,
@@ -326,6 +475,41 @@ code:
                     self.binding(binding, prev_com)?;
                 }
             }
+            Item::Import(import) => self.import(import, prev_com)?,
+        }
+        Ok(())
+    }
+    fn import(&mut self, import: Import, comment: Option<Arc<str>>) -> UiuaResult {
+        let span = import.span();
+        let module = self.import_compile(import.path.value.as_ref(), &span)?;
+        // Bind the module itself
+        if let Some(name) = &import.name {
+            let global_index = self.next_global;
+            self.next_global += 1;
+            self.asm.add_global_at(
+                global_index,
+                Global::Module {
+                    module: module.clone(),
+                },
+                Some(name.span.clone()),
+                comment,
+            );
+            self.scope.names.insert(name.value.clone(), global_index);
+        }
+        // Bring items into scope, which also re-exports them
+        for item in import.items {
+            if let Some(&index) = self.imports[&module].get(&item.value) {
+                (self.asm.global_references).insert(item.clone(), index);
+                self.scope.names.insert(item.value, index);
+            } else {
+                self.add_error(
+                    item.span.clone(),
+                    format!(
+                        "Item `{}` not found in module `{}`",
+                        item.value, import.path.value
+                    ),
+                );
+            }
         }
         Ok(())
     }
@@ -350,6 +534,15 @@ code:
     fn binding(&mut self, binding: Binding, comment: Option<Arc<str>>) -> UiuaResult {
         let name = binding.name.value;
         let span = &binding.name.span;
+        if self.scope.names.contains_key(&name)
+            || (self.higher_scopes.last()).is_some_and(|scope| scope.names.contains_key(&name))
+        {
+            self.emit_lint(
+                Lint::ShadowedName,
+                format!("`{name}` shadows an earlier binding"),
+                span.clone(),
+            );
+        }
         let placeholder_count = count_placeholders(&binding.words);
 
         let make_fn = |mut instrs: EcoVec<Instr>, sig: Signature, comp: &mut Self| {
@@ -485,11 +678,16 @@ code:
                     if declared_sig.value == sig_to_check {
                         sig = declared_sig.value;
                     } else {
+                        let explanation = if let [Instr::PushFunc(f)] = instrs.as_slice() {
+                            self.explain_signature(f.instrs(self), Some(declared_sig.value))
+                        } else {
+                            self.explain_signature(&instrs, Some(declared_sig.value))
+                        };
                         self.add_error(
                             declared_sig.span.clone(),
                             format!(
-                                "Function signature mismatch: declared {} but inferred {}",
-                                declared_sig.value, sig_to_check
+                                "Function signature mismatch: declared {} but inferred {}{}",
+                                declared_sig.value, sig_to_check, explanation
                             ),
                         );
                     }
@@ -542,10 +740,11 @@ code:
                     let func = make_fn(instrs, sig.value, self);
                     self.compile_bind_function(&name, global_index, func, span_index, comment)?;
                 } else {
+                    let explanation = self.explain_signature(&instrs, None);
                     self.add_error(
                         binding.name.span.clone(),
                         format!(
-                            "Cannot infer function signature: {e}{}",
+                            "Cannot infer function signature: {e}{}{explanation}",
                             if e.ambiguous {
                                 ". A signature can be declared after the `←`."
                             } else {
@@ -627,12 +826,31 @@ code:
         Ok(path)
     }
     /// Resolve a declared import path relative to the path of the file that is being executed
+    ///
+    /// If no such file exists, the path is looked up in the nearest [`MODULES_DIR`]
+    /// in the current directory or its ancestors instead.
+    /// Library directories resolve to their `lib.ua` file.
     pub(crate) fn resolve_import_path(&self, path: &Path) -> PathBuf {
-        let target = if let Some(parent) = self.current_imports.last().and_then(|p| p.parent()) {
+        let parent = self.current_imports.last().and_then(|p| p.parent());
+        let mut target = if let Some(parent) = parent {
             parent.join(path)
         } else {
             path.to_path_buf()
         };
+        let modules_dir = || {
+            let current = std::env::current_dir().ok()?;
+            (current.ancestors())
+                .map(|dir| dir.join(MODULES_DIR))
+                .find(|dir| dir.is_dir())
+        };
+        if let Some(modules_dir) = modules_dir().filter(|_| !target.exists()) {
+            let lib = modules_dir.join(path);
+            if lib.is_dir() {
+                target = lib.join("lib.ua");
+            } else if lib.exists() {
+                target = lib;
+            }
+        }
         let base = Path::new(".");
         if let (Ok(canon_target), Ok(canon_base)) = (target.canonicalize(), base.canonicalize()) {
             pathdiff::diff_paths(canon_target, canon_base).unwrap_or(target)
@@ -648,8 +866,10 @@ code:
 
         self.new_functions.push(EcoVec::new());
         self.words(words, call)?;
+        let instrs = self.new_functions.pop().unwrap();
+        self.dead_code(&instrs);
         self.flush_diagnostics();
-        Ok(self.new_functions.pop().unwrap())
+        Ok(instrs)
     }
     fn flush_diagnostics(&mut self) {
         if self.print_diagnostics {
@@ -719,6 +939,18 @@ code:
                                         ),
                                     )
                                 })?;
+                                // Record the item name without its quotes as a reference
+                                if word.span.as_str(&self.asm.inputs, |s| s.starts_with('"')) {
+                                    let mut item_span = word.span.clone();
+                                    item_span.start.char_pos += 1;
+                                    item_span.start.byte_pos += 1;
+                                    item_span.start.col += 1;
+                                    item_span.end.char_pos -= 1;
+                                    item_span.end.byte_pos -= 1;
+                                    item_span.end.col -= 1;
+                                    (self.asm.global_references)
+                                        .insert(item_span.sp(item_name.as_str().into()), index);
+                                }
                                 self.global_index(index, next.span.clone(), false);
                                 words.next();
                                 continue;
@@ -738,15 +970,21 @@ code:
                 if let (Word::Primitive(Primitive::Select), Word::Primitive(Primitive::First)) =
                     (&word.value, &next.value)
                 {
-                    self.emit_diagnostic(
+                    let diagnostic = Diagnostic::new(
                         format!(
                             "Flip the order of {} and {} to improve performance",
                             Primitive::First.format(),
                             Primitive::Select.format()
                         ),
-                        DiagnosticKind::Advice,
                         word.span.clone(),
+                        DiagnosticKind::Advice,
+                        self.asm.inputs.clone(),
+                    )
+                    .with_fix(
+                        next.span.clone().merge(word.span.clone()),
+                        format!("{}{}", Primitive::Select, Primitive::First),
                     );
+                    self.add_diagnostic(diagnostic);
                 }
             }
             self.word(word, call)?;
@@ -758,9 +996,55 @@ code:
     /// Also performs some optimizations if the instruction and the previous
     /// instruction form some known pattern
     fn push_instr(&mut self, instr: Instr) {
+        if self.fuse_loops(&instr) {
+            return;
+        }
         let instrs = self.new_functions.last_mut().unwrap();
         optimize_instrs_mut(instrs, instr, false);
     }
+    /// Fuse a loop with the loop before it if they traverse the same array
+    ///
+    /// `≡g≡f` becomes `≡(g f)`, so the intermediate array is never built.
+    /// `∵g∵f` is only fused if `f` always returns a scalar for a scalar.
+    /// Both functions must be pure so that the order of their calls does not matter.
+    fn fuse_loops(&mut self, instr: &Instr) -> bool {
+        let &Instr::Prim(prim @ (Primitive::Rows | Primitive::Each), span) = instr else {
+            return false;
+        };
+        let instrs = self.new_functions.last().unwrap();
+        let [.., Instr::PushFunc(f), Instr::Prim(prev, _), Instr::PushFunc(g)] = instrs.as_slice()
+        else {
+            return false;
+        };
+        let monadic = Signature::new(1, 1);
+        if *prev != prim
+            || f.signature() != monadic
+            || g.signature() != monadic
+            || !f.is_pure(&self.asm)
+            || !g.is_pure(&self.asm)
+        {
+            return false;
+        }
+        if prim == Primitive::Each
+            && !f.instrs(&self.asm).iter().all(|instr| match instr {
+                Instr::Prim(prim, _) => prim.class().is_pervasive(),
+                Instr::Push(val) => val.rank() == 0,
+                _ => false,
+            })
+        {
+            return false;
+        }
+        let fused_instrs: EcoVec<Instr> = (f.instrs(&self.asm).iter())
+            .chain(g.instrs(&self.asm))
+            .cloned()
+            .collect();
+        let fused = self.add_function(FunctionId::Unnamed, monadic, fused_instrs);
+        let instrs = self.new_functions.last_mut().unwrap();
+        instrs.truncate(instrs.len() - 3);
+        optimize_instrs_mut(instrs, Instr::PushFunc(fused), false);
+        optimize_instrs_mut(instrs, Instr::Prim(prim, span), false);
+        true
+    }
     fn push_all_instrs(&mut self, instrs: impl IntoIterator<Item = Instr>) {
         for instr in instrs {
             self.push_instr(instr);
@@ -1145,11 +1429,12 @@ code:
                     if declared_sig.value == sig {
                         sig = declared_sig.value;
                     } else {
+                        let explanation = self.explain_signature(&instrs, Some(declared_sig.value));
                         return Err(self.fatal_error(
                             declared_sig.span.clone(),
                             format!(
-                                "Function signature mismatch: declared {} but inferred {}",
-                                declared_sig.value, sig
+                                "Function signature mismatch: declared {} but inferred {}{}",
+                                declared_sig.value, sig, explanation
                             ),
                         ));
                     }
@@ -1160,10 +1445,11 @@ code:
                 if let Some(declared_sig) = &func.signature {
                     declared_sig.value
                 } else {
+                    let explanation = self.explain_signature(&instrs, None);
                     return Err(self.fatal_error(
                         span,
                         format!(
-                            "Cannot infer function signature: {e}{}",
+                            "Cannot infer function signature: {e}{}{explanation}",
                             if e.ambiguous {
                                 ". A signature can be declared after the opening `(`."
                             } else {
@@ -1220,10 +1506,11 @@ code:
             let sig = match instrs_signature(&instrs) {
                 Ok(sig) => sig,
                 Err(e) => {
+                    let explanation = self.explain_signature(&instrs, None);
                     return Err(self.fatal_error(
                         span,
                         format!(
-                            "Cannot infer function signature: {e}{}",
+                            "Cannot infer function signature: {e}{}{explanation}",
                             if e.ambiguous {
                                 ". A signature can be declared after the opening `(`."
                             } else {
@@ -1417,23 +1704,36 @@ code:
             (&modified.modifier.value, instrs.as_slice())
         {
             if f.signature().args == 1 {
-                self.emit_diagnostic(
+                self.emit_lint(
+                    Lint::Deprecated,
                     format!(
                         "{} with a monadic function is deprecated. \
                         Prefer {} with stack array notation.",
                         Primitive::Reduce.format(),
                         Primitive::Un.format()
                     ),
-                    DiagnosticKind::Warning,
                     modified.modifier.span.clone(),
                 );
             }
         }
 
+        // Check the signatures of operands that must fit together
+        if let Modifier::Primitive(
+            prim @ (Primitive::SetInverse | Primitive::SetUnder | Primitive::Fold),
+        ) = modified.modifier.value
+        {
+            self.check_operand_signatures(prim, &instrs, &modified.modifier.span);
+        }
+
         if call {
             self.push_all_instrs(instrs);
             match modified.modifier.value {
-                Modifier::Primitive(prim) => self.primitive(prim, modified.modifier.span, true),
+                Modifier::Primitive(prim) => {
+                    self.primitive(prim, modified.modifier.span, true);
+                    if prim == Primitive::Rows {
+                        self.fuse_windowed_reduce();
+                    }
+                }
                 Modifier::Ident(ident) => self.ident(ident, modified.modifier.span, true)?,
             }
         } else {
@@ -1441,7 +1741,10 @@ code:
             self.push_all_instrs(instrs);
             match modified.modifier.value {
                 Modifier::Primitive(prim) => {
-                    self.primitive(prim, modified.modifier.span.clone(), true)
+                    self.primitive(prim, modified.modifier.span.clone(), true);
+                    if prim == Primitive::Rows {
+                        self.fuse_windowed_reduce();
+                    }
                 }
                 Modifier::Ident(ident) => {
                     self.ident(ident, modified.modifier.span.clone(), true)?
@@ -1467,6 +1770,92 @@ code:
         }
         Ok(())
     }
+    /// Replace `≡/f◫` with [`ImplPrimitive::WindowedReduce`]
+    /// if `f` can be computed with a sliding window
+    fn fuse_windowed_reduce(&mut self) {
+        let instrs = self.new_functions.last_mut().unwrap();
+        let [.., Instr::Prim(Primitive::Windows, _), Instr::PushFunc(g), Instr::Prim(Primitive::Rows, span)] =
+            instrs.as_slice()
+        else {
+            return;
+        };
+        let [Instr::PushFunc(f), Instr::Prim(Primitive::Reduce, _)] = g.instrs(&self.asm) else {
+            return;
+        };
+        let Some((Primitive::Add | Primitive::Max | Primitive::Min, _)) =
+            f.as_flipped_primitive(&self.asm)
+        else {
+            return;
+        };
+        let (g, span) = (g.clone(), *span);
+        let len = instrs.len();
+        instrs.truncate(len - 3);
+        instrs.push(Instr::PushFunc(g));
+        instrs.push(Instr::ImplPrim(ImplPrimitive::WindowedReduce, span));
+    }
+    /// Check that the functions passed to some modifiers
+    /// have signatures that can be used together
+    fn check_operand_signatures(&mut self, prim: Primitive, instrs: &[Instr], span: &CodeSpan) {
+        let message = match (prim, instrs) {
+            (Primitive::Fold, [Instr::PushFunc(f)]) => {
+                let sig = f.signature();
+                if sig.args > sig.outputs {
+                    return;
+                }
+                format!(
+                    "{}'s function must take more values than it returns, \
+                    but its signature is {sig}. Each returned value is an \
+                    accumulator, and each extra argument is an iterated array.",
+                    prim.format()
+                )
+            }
+            (Primitive::SetInverse, [Instr::PushFunc(inv), Instr::PushFunc(normal)]) => {
+                let (inv, normal) = (inv.signature(), normal.signature());
+                if inv.args <= normal.outputs {
+                    return;
+                }
+                format!(
+                    "{}'s inverse has signature {inv}, but it cannot take more than \
+                    the {} output(s) of the function, which has signature {normal}. \
+                    Inverses that need context can be set with {}.",
+                    prim.format(),
+                    normal.outputs,
+                    Primitive::SetUnder.format()
+                )
+            }
+            (
+                Primitive::SetUnder,
+                [Instr::PushFunc(after), Instr::PushFunc(before), Instr::PushFunc(normal)],
+            ) => {
+                let (after, before, normal) =
+                    (after.signature(), before.signature(), normal.signature());
+                if before.args != normal.args {
+                    format!(
+                        "{}'s second function has signature {before}, but it must take \
+                        the same number of arguments as the first, which has signature {normal}",
+                        prim.format()
+                    )
+                } else if before.outputs < normal.outputs {
+                    format!(
+                        "{}'s second function has signature {before}, but it must return \
+                        at least as many values as the first, which has signature {normal}",
+                        prim.format()
+                    )
+                } else if after.args > before.outputs {
+                    format!(
+                        "{}'s third function has signature {after}, but it cannot take \
+                        more than the {} value(s) returned by the second function",
+                        prim.format(),
+                        before.outputs
+                    )
+                } else {
+                    return;
+                }
+            }
+            _ => return,
+        };
+        self.add_error(span.clone(), message);
+    }
     fn inline_modifier(&mut self, modified: &Modified, call: bool) -> UiuaResult<bool> {
         use Primitive::*;
         let Modifier::Primitive(prim) = modified.modifier.value else {
@@ -1536,33 +1925,55 @@ code:
                     self.compile_operand_words(vec![operands.next().unwrap()])?;
                 let span = self.add_span(modified.modifier.span.clone());
                 let count = a_sig.args.max(b_sig.args);
-                let mut instrs = vec![Instr::PushTemp {
-                    stack: TempStack::Inline,
-                    count,
-                    span,
-                }];
-                if b_sig.args > 0 {
-                    instrs.push(Instr::CopyFromTemp {
+                let mut instrs = Vec::new();
+                if a_instrs == b_instrs && instrs_are_pure(&a_instrs, &self.asm) {
+                    // Identical pure functions only need to be called once
+                    instrs.extend(a_instrs);
+                    match a_sig.outputs {
+                        0 => {}
+                        1 => instrs.push(Instr::Prim(Dup, span)),
+                        n => {
+                            instrs.push(Instr::CopyToTemp {
+                                stack: TempStack::Inline,
+                                count: n,
+                                span,
+                            });
+                            instrs.push(Instr::PopTemp {
+                                stack: TempStack::Inline,
+                                count: n,
+                                span,
+                            });
+                        }
+                    }
+                } else {
+                    instrs.push(Instr::PushTemp {
                         stack: TempStack::Inline,
-                        offset: count - b_sig.args,
-                        count: b_sig.args,
+                        count,
                         span,
                     });
-                }
-                instrs.extend(b_instrs);
-                if count - a_sig.args > 0 {
-                    instrs.push(Instr::DropTemp {
+                    if b_sig.args > 0 {
+                        instrs.push(Instr::CopyFromTemp {
+                            stack: TempStack::Inline,
+                            offset: count - b_sig.args,
+                            count: b_sig.args,
+                            span,
+                        });
+                    }
+                    instrs.extend(b_instrs);
+                    if count - a_sig.args > 0 {
+                        instrs.push(Instr::DropTemp {
+                            stack: TempStack::Inline,
+                            count: count - a_sig.args,
+                            span,
+                        });
+                    }
+                    instrs.push(Instr::PopTemp {
                         stack: TempStack::Inline,
-                        count: count - a_sig.args,
+                        count: a_sig.args,
                         span,
                     });
+                    instrs.extend(a_instrs);
                 }
-                instrs.push(Instr::PopTemp {
-                    stack: TempStack::Inline,
-                    count: a_sig.args,
-                    span,
-                });
-                instrs.extend(a_instrs);
                 let sig = Signature::new(a_sig.args.max(b_sig.args), a_sig.outputs + b_sig.outputs);
                 if call {
                     self.push_instr(Instr::PushSig(sig));
@@ -1898,13 +2309,13 @@ code:
             } else {
                 format!(", {suggestion}")
             };
-            self.emit_diagnostic(
+            self.emit_lint(
+                Lint::Deprecated,
                 format!(
                     "{} is deprecated and will be removed in a future version{}",
                     prim.format(),
                     suggestion
                 ),
-                DiagnosticKind::Warning,
                 span.clone(),
             );
         }
@@ -1982,7 +2393,11 @@ code:
         for instr in instrs {
             match instr {
                 Instr::Prim(
-                    Primitive::Trace | Primitive::Dump | Primitive::Stack | Primitive::Assert,
+                    Primitive::Trace
+                    | Primitive::Dump
+                    | Primitive::Stack
+                    | Primitive::Breakpoint
+                    | Primitive::Assert,
                     _,
                 ) => return true,
                 Instr::ImplPrim(
@@ -2016,12 +2431,178 @@ code:
         kind: DiagnosticKind,
         span: impl Into<Span>,
     ) {
-        self.diagnostics.insert(Diagnostic::new(
-            message.into(),
-            span,
-            kind,
-            self.asm.inputs.clone(),
-        ));
+        let diagnostic = Diagnostic::new(message.into(), span, kind, self.asm.inputs.clone());
+        self.add_diagnostic(diagnostic);
+    }
+    /// Construct and add a diagnostic for a lint
+    pub fn emit_lint(&mut self, lint: Lint, message: impl Into<String>, span: impl Into<Span>) {
+        let diagnostic = Diagnostic::from_lint(lint, message, span, self.asm.inputs.clone());
+        self.add_diagnostic(diagnostic);
+    }
+    /// Add a diagnostic, reporting it according to the level of its lint
+    fn add_diagnostic(&mut self, diagnostic: Diagnostic) {
+        let Some(lint) = diagnostic.lint() else {
+            self.diagnostics.insert(diagnostic);
+            return;
+        };
+        let directive = match &diagnostic.span {
+            Span::Code(span) => (self.lint_directives.get(&span.src))
+                .and_then(|levels| levels.get(&lint))
+                .copied(),
+            Span::Builtin => None,
+        };
+        let level = directive
+            .or_else(|| self.lint_levels.get(&lint).copied())
+            .unwrap_or_else(|| lint.default_level());
+        match level {
+            LintLevel::Allow => {}
+            LintLevel::Warn => {
+                self.diagnostics.insert(diagnostic);
+            }
+            LintLevel::Deny => {
+                let message = format!("{}\n(denied by `{lint}`)", diagnostic.message);
+                self.add_error(diagnostic.span, message);
+            }
+        }
+    }
+    /// Read the lint levels set by `#!allow(...)`, `#!warn(...)`, and `#!deny(...)` comments
+    fn lint_directives(&mut self, items: &[Item], src: &InputSrc) {
+        let mut levels = HashMap::new();
+        let comments = items.iter().filter_map(|item| match item {
+            Item::Words(lines) => Some(lines.iter().flatten()),
+            _ => None,
+        });
+        for word in comments.flatten() {
+            let Word::Comment(comment) = &word.value else {
+                continue;
+            };
+            let Some((level, rest)) = comment.strip_prefix('!').and_then(|c| c.split_once('('))
+            else {
+                continue;
+            };
+            let level = match level.trim() {
+                "allow" => LintLevel::Allow,
+                "warn" => LintLevel::Warn,
+                "deny" => LintLevel::Deny,
+                _ => continue,
+            };
+            let Some(names) = rest.trim_end().strip_suffix(')') else {
+                self.add_error(word.span.clone(), "Lint directive is missing a closing `)`");
+                continue;
+            };
+            for name in (names.split(',').map(str::trim)).filter(|name| !name.is_empty()) {
+                match name.parse::<Lint>() {
+                    Ok(lint) => {
+                        levels.insert(lint, level);
+                    }
+                    Err(e) => self.emit_diagnostic(e, DiagnosticKind::Warning, word.span.clone()),
+                }
+            }
+        }
+        self.lint_directives.insert(src.clone(), levels);
+    }
+    /// Emit lints for bindings in a file that are never referenced
+    fn unused_bindings(&mut self, src: &InputSrc) {
+        let referenced: HashSet<usize> = self.asm.global_references.values().copied().collect();
+        let unused: Vec<CodeSpan> = (self.asm.bindings.iter().enumerate())
+            .filter(|(i, _)| !referenced.contains(i))
+            .filter_map(|(_, binding)| binding.span.clone())
+            .filter(|span| &span.src == src)
+            .collect();
+        for span in unused {
+            let name = span.as_str(&self.asm.inputs, |s| s.to_string());
+            self.emit_lint(
+                Lint::UnusedBinding,
+                format!("Binding `{name}` is never used"),
+                span,
+            );
+        }
+    }
+    /// Explain how the signature of some instructions was inferred
+    ///
+    /// Returns an empty string if signature explanations are disabled.
+    fn explain_signature(&self, instrs: &[Instr], declared: Option<Signature>) -> String {
+        if !self.explain_signatures {
+            return String::new();
+        }
+        let label = |instrs: &[Instr]| {
+            let span = (instrs.iter().find_map(Instr::span)).map(|i| self.get_span(i));
+            let label = match span {
+                Some(Span::Code(span)) => {
+                    let text = span.as_str(&self.asm.inputs, |s| s.to_string());
+                    format!("`{text}` at {}:{}", span.start.line, span.start.col)
+                }
+                _ => instrs.iter().map(|instr| instr.to_string()).collect(),
+            };
+            if let [Instr::PushSig(_), ..] = instrs {
+                format!("inlined function {label}")
+            } else {
+                label
+            }
+        };
+        let (steps, res) = instrs_signature_steps(instrs);
+        let mut explanation = String::from("\nSignature after each step:");
+        let mut diverged = None;
+        for step in &steps {
+            let step_label = label(&instrs[step.range.clone()]);
+            explanation.push_str(&format!("\n  {step_label} → {}", step.sig));
+            if let Some(declared) = declared {
+                if diverged.is_none() && step.sig.args > declared.args {
+                    diverged = Some(format!(
+                        "{step_label} needs {} arguments, but only {} are declared",
+                        step.sig.args, declared.args
+                    ));
+                }
+            }
+        }
+        let end = steps.last().map_or(0, |step| step.range.end);
+        let note = match (res, declared) {
+            (Err(e), _) if end < instrs.len() => Some(format!(
+                "Inference failed at {}: {e}",
+                label(&instrs[end..=end])
+            )),
+            (Err(e), _) => Some(format!("Inference failed: {e}")),
+            (Ok(_), _) if diverged.is_some() => diverged,
+            (Ok(sig), Some(declared)) if sig.args < declared.args => Some(format!(
+                "The function only uses {} arguments, but {} are declared",
+                sig.args, declared.args
+            )),
+            (Ok(sig), Some(declared)) if sig.outputs != declared.outputs => Some(format!(
+                "The function leaves {} values on the stack, but {} are declared",
+                sig.outputs, declared.outputs
+            )),
+            (Ok(_), _) => None,
+        };
+        if let Some(note) = note {
+            explanation.push('\n');
+            explanation.push_str(&note);
+        }
+        explanation
+    }
+    /// Emit a lint for code that follows an assertion that always fails
+    fn dead_code(&mut self, instrs: &[Instr]) {
+        for (i, window) in instrs.windows(3).enumerate() {
+            let [Instr::Push(cond), Instr::Push(_), Instr::Prim(Primitive::Assert, span)] = window
+            else {
+                continue;
+            };
+            let always_fails = match cond {
+                Value::Num(arr) => arr.as_scalar().is_some_and(|&n| n != 1.0),
+                #[cfg(feature = "bytes")]
+                Value::Byte(arr) => arr.as_scalar().is_some_and(|&n| n != 1),
+                Value::Int(arr) => arr.as_scalar().is_some_and(|&n| n != 1),
+                _ => false,
+            };
+            let rest = &instrs[i + 3..];
+            if always_fails && (rest.iter()).any(|instr| !matches!(instr, Instr::Comment(_))) {
+                self.emit_lint(
+                    Lint::DeadCode,
+                    "This assertion always fails, so the code after it will never run",
+                    self.get_span(*span),
+                );
+                break;
+            }
+        }
     }
     fn add_error(&mut self, span: impl Into<Span>, message: impl ToString) {
         let e = UiuaError::Run(
@@ -2101,3 +2682,16 @@ fn words_look_pervasive(words: &[Sp<Word>]) -> bool {
         _ => false,
     })
 }
+
+#[test]
+fn import_references() {
+    let asm = Compiler::new()
+        .load_str("Ex ~ \"example.ua\" Increment\nIncrement Ex \"Double\" 2")
+        .unwrap()
+        .finish();
+    let mut names: Vec<String> = (asm.global_references.keys())
+        .map(|name| name.span.as_str(&asm.inputs, str::to_string))
+        .collect();
+    names.sort();
+    assert_eq!(names, ["Double", "Increment", "Increment"]);
+}
@@ -107,7 +107,7 @@ impl Complex {
     pub fn powc(self, power: impl Into<Self>) -> Self {
         let power = power.into();
         if power.im == 0.0 {
-            if self.im == 0.0 {
+            if self.im == 0.0 && (self.re >= 0.0 || power.re.fract() == 0.0) {
                 return Self::new(self.re.powf(power.re), 0.0);
             }
             if power.re == 0.0 {
@@ -183,6 +183,12 @@ impl From<u8> for Complex {
     }
 }
 
+impl From<u32> for Complex {
+    fn from(value: u32) -> Self {
+        f64::from(value).into()
+    }
+}
+
 impl fmt::Display for Complex {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.im == 0.0 {
@@ -1,10 +1,10 @@
-use std::{convert::Infallible, error::Error, fmt, io, path::PathBuf, sync::Arc};
+use std::{convert::Infallible, error::Error, fmt, io, path::PathBuf, str::FromStr, sync::Arc};
 
 use colored::*;
 
 use crate::{
     function::FunctionId,
-    lex::{Sp, Span},
+    lex::{CodeSpan, Sp, Span},
     parse::ParseError,
     value::Value,
     InputSrc, Inputs,
@@ -33,6 +33,10 @@ pub enum UiuaError {
     Throw(Box<Value>, Span, Box<Inputs>),
     /// Maximum execution time exceeded
     Timeout(Span, Box<Inputs>),
+    /// An instruction, memory, or recursion limit was exceeded
+    LimitExceeded(Limit, Span, Box<Inputs>),
+    /// Execution was cancelled with a [`CancelHandle`](crate::CancelHandle)
+    Cancelled(Span, Box<Inputs>),
     /// A wrapper marking this error as being fill-related
     Fill(Box<Self>),
     /// The interpreter panicked
@@ -44,6 +48,31 @@ pub enum UiuaError {
 /// Uiua's result type
 pub type UiuaResult<T = ()> = Result<T, UiuaError>;
 
+/// A limit on the resources used by a program
+///
+/// See [`Uiua::with_instruction_limit`](crate::Uiua::with_instruction_limit),
+/// [`Uiua::with_memory_limit`](crate::Uiua::with_memory_limit),
+/// and [`Uiua::with_recursion_limit`](crate::Uiua::with_recursion_limit)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// A maximum number of executed instructions
+    Instructions(usize),
+    /// A maximum number of bytes of values on the stack
+    Memory(usize),
+    /// A maximum depth of nested function calls
+    Recursion(usize),
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::Instructions(n) => write!(f, "Maximum of {n} instructions exceeded"),
+            Limit::Memory(n) => write!(f, "Memory limit of {n} bytes exceeded"),
+            Limit::Recursion(n) => write!(f, "Maximum recursion depth of {n} exceeded"),
+        }
+    }
+}
+
 /// A frame in a trace
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TraceFrame {
@@ -78,6 +107,8 @@ impl fmt::Display for UiuaError {
             }
             UiuaError::Throw(value, span, _) => write!(f, "{span}: {value}"),
             UiuaError::Timeout(..) => write!(f, "Maximum execution time exceeded"),
+            UiuaError::LimitExceeded(limit, ..) => limit.fmt(f),
+            UiuaError::Cancelled(..) => write!(f, "Execution was cancelled"),
             UiuaError::Fill(error) => error.fmt(f),
             UiuaError::Panic(message) => message.fmt(f),
             UiuaError::Multi(errors) => {
@@ -106,6 +137,70 @@ impl UiuaError {
             error => error.message().into(),
         }
     }
+    /// Get the error as a JSON array of diagnostics
+    ///
+    /// Each diagnostic is an object with a `severity`, a `message`, the `file` and `span` it
+    /// refers to if any, and a list of related `notes`, such as the frames of a stack trace.
+    /// Spans have 0-based `start` and `end` byte offsets and 1-based lines and columns.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut entries = Vec::new();
+        self.json_entries(&mut entries);
+        entries.into()
+    }
+    fn json_entries(&self, entries: &mut Vec<serde_json::Value>) {
+        let error = |message: &str, span: &Span| json_diagnostic("error", message, span);
+        match self {
+            UiuaError::Parse(errors, _) => entries.extend(
+                (errors.iter()).map(|e| error(&e.value.to_string(), &e.span.clone().into())),
+            ),
+            UiuaError::Run(e, _) => entries.push(error(&e.value, &e.span)),
+            UiuaError::Traced { error, trace } => {
+                let start = entries.len();
+                error.json_entries(entries);
+                let notes: Vec<_> = (trace.iter())
+                    .filter(|frame| frame.id != FunctionId::Main)
+                    .map(|frame| json_note(&format!("in {}", frame.id), &frame.span))
+                    .collect();
+                for entry in &mut entries[start..] {
+                    if let Some(serde_json::Value::Array(entry_notes)) = entry.get_mut("notes") {
+                        entry_notes.extend(notes.iter().cloned());
+                    }
+                }
+            }
+            UiuaError::Throw(value, span, _) => entries.push(error(&value.to_string(), span)),
+            UiuaError::Timeout(span, _) | UiuaError::Cancelled(span, _) => {
+                entries.push(error(&self.to_string(), span))
+            }
+            UiuaError::LimitExceeded(limit, span, _) => {
+                entries.push(error(&limit.to_string(), span))
+            }
+            UiuaError::Fill(error) => error.json_entries(entries),
+            UiuaError::Multi(errors) => {
+                for error in errors {
+                    error.json_entries(entries);
+                }
+            }
+            UiuaError::Load(..) | UiuaError::Format(..) | UiuaError::Panic(_) => {
+                entries.push(error(&self.to_string(), &Span::Builtin))
+            }
+        }
+    }
+    /// Check if the error is from execution being cancelled
+    pub fn is_cancelled(&self) -> bool {
+        match self {
+            UiuaError::Traced { error, .. } | UiuaError::Fill(error) => error.is_cancelled(),
+            UiuaError::Cancelled(..) => true,
+            _ => false,
+        }
+    }
+    /// Check if the error is from a failed assertion
+    pub(crate) fn is_assertion(&self) -> bool {
+        match self {
+            UiuaError::Traced { error, .. } => error.is_assertion(),
+            UiuaError::Throw(..) => true,
+            _ => false,
+        }
+    }
     /// Check if the error is fill-related
     pub(crate) fn is_fill(&self) -> bool {
         match self {
@@ -184,6 +279,12 @@ impl UiuaError {
                 inputs,
                 [("Maximum execution time exceeded", span.clone())],
             ),
+            UiuaError::LimitExceeded(limit, span, inputs) => {
+                Report::new_multi(kind, inputs, [(limit.to_string(), span.clone())])
+            }
+            UiuaError::Cancelled(span, inputs) => {
+                Report::new_multi(kind, inputs, [("Execution was cancelled", span.clone())])
+            }
             UiuaError::Fill(error) => error.report(),
             UiuaError::Panic(message) => Report::new(kind, message),
             UiuaError::Load(..) | UiuaError::Format(..) => Report::new(kind, self.to_string()),
@@ -214,8 +315,12 @@ pub struct Diagnostic {
     pub message: String,
     /// What kind of diagnostic this is
     pub kind: DiagnosticKind,
+    /// The lint that produced this diagnostic, if it is not determined by its kind
+    pub lint: Option<Lint>,
     /// The inputs of the program
     pub inputs: Inputs,
+    /// Replacement code that fixes the diagnostic, along with the span it replaces
+    pub fix: Option<Sp<String>>,
 }
 
 impl PartialEq for Diagnostic {
@@ -249,6 +354,150 @@ pub enum DiagnosticKind {
     Style,
 }
 
+/// A category of diagnostic whose level can be configured
+///
+/// Levels can be set for a whole program with [`Compiler::lint_level`](crate::Compiler::lint_level)
+/// or for a single file with `#!allow(...)`, `#!warn(...)`, or `#!deny(...)` comments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Lint {
+    /// A binding that is never referenced
+    UnusedBinding,
+    /// A binding that shadows another binding with the same name
+    ShadowedName,
+    /// Code that comes after an error that is always thrown
+    DeadCode,
+    /// Use of a deprecated primitive or feature
+    Deprecated,
+    /// Any diagnostic of kind [`DiagnosticKind::Advice`]
+    Advice,
+    /// Any diagnostic of kind [`DiagnosticKind::Style`]
+    Style,
+}
+
+impl Lint {
+    /// All lints
+    pub const ALL: [Self; 6] = [
+        Lint::UnusedBinding,
+        Lint::ShadowedName,
+        Lint::DeadCode,
+        Lint::Deprecated,
+        Lint::Advice,
+        Lint::Style,
+    ];
+    /// Get the name used to refer to the lint in directives and on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::UnusedBinding => "unused-binding",
+            Lint::ShadowedName => "shadowed-name",
+            Lint::DeadCode => "dead-code",
+            Lint::Deprecated => "deprecated",
+            Lint::Advice => "advice",
+            Lint::Style => "style",
+        }
+    }
+    /// Get the kind of diagnostic the lint produces
+    pub fn kind(&self) -> DiagnosticKind {
+        match self {
+            Lint::Advice => DiagnosticKind::Advice,
+            Lint::Style => DiagnosticKind::Style,
+            _ => DiagnosticKind::Warning,
+        }
+    }
+    /// Get the level of the lint if none is configured
+    ///
+    /// Unused and shadowed bindings are common in scratch code, so they must be opted into.
+    pub fn default_level(&self) -> LintLevel {
+        match self {
+            Lint::UnusedBinding | Lint::ShadowedName => LintLevel::Allow,
+            _ => LintLevel::Warn,
+        }
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.name().fmt(f)
+    }
+}
+
+impl FromStr for Lint {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        (Lint::ALL.into_iter())
+            .find(|lint| lint.name() == s)
+            .ok_or_else(|| format!("Unknown lint `{s}`"))
+    }
+}
+
+/// How a [`Lint`] is reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LintLevel {
+    /// Don't report the lint
+    Allow,
+    /// Report the lint as a diagnostic
+    Warn,
+    /// Report the lint as an error
+    Deny,
+}
+
+impl Diagnostic {
+    /// Get the diagnostic as a JSON object
+    ///
+    /// The format is the same as that of the entries of [`UiuaError::to_json`].
+    pub fn to_json(&self) -> serde_json::Value {
+        let severity = match self.kind {
+            DiagnosticKind::Warning => "warning",
+            DiagnosticKind::Advice => "advice",
+            DiagnosticKind::Style => "style",
+        };
+        let mut diagnostic = json_diagnostic(severity, &self.message, &self.span);
+        diagnostic["lint"] = self.lint().map(|lint| lint.name()).into();
+        diagnostic["fix"] = (self.fix.as_ref())
+            .map(|fix| {
+                serde_json::json!({
+                    "replacement": fix.value,
+                    "start": fix.span.start.byte_pos,
+                    "end": fix.span.end.byte_pos,
+                })
+            })
+            .into();
+        diagnostic
+    }
+}
+
+fn json_note(message: &str, span: &Span) -> serde_json::Value {
+    let (file, span) = match span {
+        Span::Code(span) => {
+            let file = match &span.src {
+                InputSrc::File(path) => Some(path.to_string_lossy().into_owned()),
+                InputSrc::Str(_) => None,
+            };
+            let span = serde_json::json!({
+                "start": span.start.byte_pos,
+                "end": span.end.byte_pos,
+                "start_line": span.start.line,
+                "start_col": span.start.col,
+                "end_line": span.end.line,
+                "end_col": span.end.col,
+            });
+            (file, Some(span))
+        }
+        Span::Builtin => (None, None),
+    };
+    serde_json::json!({
+        "message": message,
+        "file": file,
+        "span": span,
+    })
+}
+
+fn json_diagnostic(severity: &str, message: &str, span: &Span) -> serde_json::Value {
+    let mut diagnostic = json_note(message, span);
+    diagnostic["severity"] = severity.into();
+    diagnostic["notes"] = serde_json::Value::Array(Vec::new());
+    diagnostic
+}
+
 impl fmt::Display for Diagnostic {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.message.fmt(f)
@@ -267,9 +516,38 @@ impl Diagnostic {
             message: message.into(),
             span: span.into(),
             kind,
+            lint: None,
             inputs,
+            fix: None,
         }
     }
+    /// Add replacement code for a span that fixes the diagnostic
+    pub fn with_fix(self, span: CodeSpan, replacement: impl Into<String>) -> Self {
+        Self {
+            fix: Some(span.sp(replacement.into())),
+            ..self
+        }
+    }
+    /// Create a new diagnostic for a lint
+    pub fn from_lint(
+        lint: Lint,
+        message: impl Into<String>,
+        span: impl Into<Span>,
+        inputs: Inputs,
+    ) -> Self {
+        Self {
+            lint: Some(lint),
+            ..Self::new(message, span, lint.kind(), inputs)
+        }
+    }
+    /// Get the lint that controls whether the diagnostic is reported
+    pub fn lint(&self) -> Option<Lint> {
+        self.lint.or(match self.kind {
+            DiagnosticKind::Advice => Some(Lint::Advice),
+            DiagnosticKind::Style => Some(Lint::Style),
+            DiagnosticKind::Warning => None,
+        })
+    }
     /// Get a rich-text report for the diagnostic
     pub fn report(&self) -> Report {
         Report::new_multi(
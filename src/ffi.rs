@@ -1,6 +1,14 @@
-use std::str::FromStr;
+use std::{
+    cell::RefCell,
+    mem::take,
+    str::FromStr,
+    sync::atomic::{self, AtomicBool},
+};
+
+use crate::{Signature, Uiua, UiuaError, UiuaResult, Value};
 
 /// Types for FFI
+#[doc(hidden)]
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[allow(missing_docs)]
 pub enum FfiType {
@@ -26,6 +34,13 @@ pub enum FfiType {
         len_index: usize,
         inner: Box<Self>,
     },
+    Struct {
+        fields: Vec<Self>,
+    },
+    Func {
+        ret: Box<Self>,
+        args: Vec<Self>,
+    },
 }
 
 impl FromStr for FfiType {
@@ -37,9 +52,8 @@ impl FromStr for FfiType {
             s = t;
             mutable = false;
         }
-        if let Some((mut a, mut b)) = s.split_once(':') {
-            a = a.trim();
-            b = b.trim();
+        if let Some(i) = find_top_level(s, ':') {
+            let (a, b) = (s[..i].trim(), s[i + 1..].trim());
             let len_index = b
                 .parse()
                 .map_err(|e| format!("Invalid length index: {e}"))?;
@@ -56,6 +70,30 @@ impl FromStr for FfiType {
                 inner: Box::new(s.parse()?),
             });
         }
+        if let Some(fields) = (s.strip_prefix('{')).and_then(|s| s.strip_suffix('}')) {
+            let fields = split_top_level(fields)
+                .into_iter()
+                .map(str::parse)
+                .collect::<Result<Vec<_>, _>>()?;
+            if fields.is_empty() {
+                return Err("FFI struct types must have at least one field".into());
+            }
+            return Ok(FfiType::Struct { fields });
+        }
+        if let Some(t) = s.strip_suffix(')') {
+            let i = find_top_level(t, '(').ok_or_else(|| format!("Unknown FFI type: {s}"))?;
+            let args = match t[i + 1..].trim() {
+                "void" => Vec::new(),
+                args => split_top_level(args)
+                    .into_iter()
+                    .map(str::parse)
+                    .collect::<Result<_, _>>()?,
+            };
+            return Ok(FfiType::Func {
+                ret: Box::new(t[..i].parse()?),
+                args,
+            });
+        }
         Ok(match s {
             "void" => FfiType::Void,
             "char" => FfiType::Char,
@@ -75,15 +113,124 @@ impl FromStr for FfiType {
     }
 }
 
+/// Find the first occurrence of a character that is not nested in braces or parentheses
+fn find_top_level(s: &str, target: char) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        match c {
+            '{' | '(' if depth == 0 && c == target => return Some(i),
+            '{' | '(' => depth += 1,
+            '}' | ')' => depth = depth.saturating_sub(1),
+            c if c == target && depth == 0 => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Split a comma-separated list of types that may contain nested types
+fn split_top_level(mut s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    while let Some(i) = find_top_level(s, ',') {
+        parts.push(&s[..i]);
+        s = &s[i + 1..];
+    }
+    if !s.trim().is_empty() {
+        parts.push(s);
+    }
+    parts
+}
+
+thread_local! {
+    /// The interpreter making the FFI call that is running on this thread
+    static CALLBACK_ENV: RefCell<Option<Uiua>> = const { RefCell::new(None) };
+    static CALLBACK_ERROR: RefCell<Option<UiuaError>> = const { RefCell::new(None) };
+}
+
+/// Whether a callback was called from a thread other than the one making the FFI call
+static STRAY_CALLBACK: AtomicBool = AtomicBool::new(false);
+
+/// Run an FFI call during which callbacks registered with `&ffcb` can call back into the interpreter
+///
+/// Callbacks are only valid synchronously, on the thread making the call, until it returns.
+/// The interpreter is moved into a thread-local for the duration of the call,
+/// so a callback is its only user.
+/// A callback that is called from another thread does nothing,
+/// and the next FFI call to return reports an error.
+/// A callback's function pointer is freed when the call returns.
+pub(crate) fn with_ffi_callbacks<T>(env: &mut Uiua, f: impl FnOnce() -> T) -> UiuaResult<T> {
+    let prev = CALLBACK_ENV.with(|cell| cell.replace(Some(take(env))));
+    let res = f();
+    *env = (CALLBACK_ENV.with(|cell| cell.replace(prev)))
+        .expect("FFI callbacks should return the interpreter");
+    if STRAY_CALLBACK.swap(false, atomic::Ordering::Relaxed) {
+        return Err(env.error(
+            "An FFI callback was called from a thread \
+            other than the one that made the FFI call",
+        ));
+    }
+    match CALLBACK_ERROR.with(|error| error.borrow_mut().take()) {
+        Some(error) => Err(error),
+        None => Ok(res),
+    }
+}
+
+/// Call a function registered as an FFI callback
+///
+/// If the function fails, the error is reported when the FFI call returns.
+#[cfg_attr(not(feature = "ffi"), allow(dead_code))]
+pub(crate) fn call_ffi_callback(
+    id: usize,
+    args: Result<Vec<Value>, String>,
+    returns: bool,
+    write_result: impl FnOnce(Value) -> Result<(), String>,
+) {
+    let Some(mut env) = CALLBACK_ENV.with(|cell| cell.borrow_mut().take()) else {
+        STRAY_CALLBACK.store(true, atomic::Ordering::Relaxed);
+        return;
+    };
+    let res = (|env: &mut Uiua| {
+        let f = (env.rt.ffi_callbacks.get(id).cloned())
+            .ok_or_else(|| env.error(format!("{id} is not a valid FFI callback")))?;
+        let args = args.map_err(|e| env.error(e))?;
+        let expected = Signature::new(args.len(), returns as usize);
+        if f.signature() != expected {
+            return Err(env.error(format!(
+                "FFI callback's signature should be {expected}, but it is {}",
+                f.signature()
+            )));
+        }
+        for arg in args.into_iter().rev() {
+            env.push(arg);
+        }
+        env.call(f)?;
+        if returns {
+            let val = env.pop("callback result")?;
+            write_result(val).map_err(|e| env.error(e))?;
+        }
+        Ok(())
+    })(&mut env);
+    CALLBACK_ENV.with(|cell| *cell.borrow_mut() = Some(env));
+    if let Err(e) = res {
+        CALLBACK_ERROR.with(|error| {
+            error.borrow_mut().get_or_insert(e);
+        });
+    }
+}
+
 #[cfg(feature = "ffi")]
 pub(crate) use enabled::*;
 #[cfg(feature = "ffi")]
 mod enabled {
     use core::slice;
-    use std::{any::Any, ffi::*};
+    use std::{
+        any::Any,
+        ffi::*,
+        mem::{align_of, size_of},
+    };
 
     use ecow::EcoVec;
-    use libffi::middle::*;
+    use libffi::{low, middle::*, raw};
 
     use super::*;
     use crate::{Array, Boxed, Value};
@@ -102,16 +249,23 @@ mod enabled {
         let mut cif_arg_tys = Vec::new();
         let mut bindings = FfiBindings::default();
         let mut lengths: Vec<Option<usize>> = vec![None; arg_tys.len()];
+        // Out-parameters to read after the call, with their lengths if they are lists
+        let mut outputs: Vec<(usize, FfiType, Option<usize>)> = Vec::new();
         // Collect lengths of lists
         for (i, arg_ty) in arg_tys.iter().enumerate() {
             if let FfiType::List {
-                len_index: length, ..
+                len_index: length,
+                inner,
+                ..
             } = arg_ty
             {
                 *lengths
                     .get_mut(*length)
                     .ok_or_else(|| format!("Invalid length index: {length}"))? =
-                    args.get(i).map(Value::element_count);
+                    args.get(i).map(|arg| match **inner {
+                        FfiType::Struct { .. } => arg.row_count(),
+                        _ => arg.element_count(),
+                    });
             }
         }
         // Bind arguments
@@ -139,7 +293,7 @@ mod enabled {
                 }
             } else {
                 // Bind normal argument
-                let arg = args.next().ok_or("Not enough arguments")?;
+                let arg = &(args.next().ok_or("Not enough arguments")?.clone()).promote_ints();
                 // println!("bind {i} arg: {arg:?}");
                 macro_rules! scalar {
                     ($arr:expr, $ty:ty) => {{
@@ -154,6 +308,25 @@ mod enabled {
                 }
                 match (arg_ty, arg) {
                     (FfiType::Void, _) => return Err("Cannot pass void to a function".into()),
+                    (FfiType::Struct { .. }, val) => {
+                        let mut buf = buffer(size_align(arg_ty).0);
+                        unsafe {
+                            write_value(arg_ty, val, buf.as_mut_ptr().cast(), &mut bindings)?
+                        };
+                        bindings.push_struct(buf);
+                    }
+                    (
+                        FfiType::Func {
+                            ret,
+                            args: cb_arg_tys,
+                        },
+                        val,
+                    ) => {
+                        let id = scalar_num(val)
+                            .filter(|n| *n >= 0.0 && n.fract() == 0.0)
+                            .ok_or_else(|| invalid(arg_ty, val))?;
+                        bindings.push_closure(FfiClosure::new(id as usize, ret, cb_arg_tys)?);
+                    }
                     (FfiType::Char | FfiType::UChar, Value::Char(arr)) if arr.rank() == 0 => {
                         scalar!(arr, i8)
                     }
@@ -196,19 +369,57 @@ mod enabled {
                     {
                         scalar!(arr, c_longlong)
                     }
-                    (FfiType::Ptr { inner, .. }, val) => match (&**inner, val) {
+                    (FfiType::Float, Value::Num(arr)) if arr.rank() == 0 => {
+                        scalar!(arr, c_float)
+                    }
+                    #[cfg(feature = "bytes")]
+                    (FfiType::Float, Value::Byte(arr)) if arr.rank() == 0 => {
+                        scalar!(arr, c_float)
+                    }
+                    (FfiType::Double, Value::Num(arr)) if arr.rank() == 0 => {
+                        scalar!(arr, c_double)
+                    }
+                    #[cfg(feature = "bytes")]
+                    (FfiType::Double, Value::Byte(arr)) if arr.rank() == 0 => {
+                        scalar!(arr, c_double)
+                    }
+                    (FfiType::Ptr { mutable, inner }, val) => match (&**inner, val) {
                         (FfiType::Char, Value::Char(arr)) => {
                             bindings.push_string(arr.data.iter().copied().collect())
                         }
-                        (ty, arg) => {
-                            return Err(format!(
-                                "Array of {} with shape {} is not a valid \
-                                argument {i} for FFI type {ty:?}",
-                                arg.type_name_plural(),
-                                arg.shape()
-                            ))
+                        (inner, val) => {
+                            let mut buf = buffer(size_align(inner).0);
+                            unsafe {
+                                write_value(inner, val, buf.as_mut_ptr().cast(), &mut bindings)?
+                            };
+                            bindings.push_list(buf);
+                            if *mutable {
+                                outputs.push((i, inner.clone(), None));
+                            }
                         }
                     },
+                    (FfiType::List { mutable, inner, .. }, val)
+                        if matches!(**inner, FfiType::Struct { .. })
+                            || *mutable && !matches!(**inner, FfiType::Char | FfiType::UChar) =>
+                    {
+                        let (size, _) = size_align(inner);
+                        let rows: Vec<Value> = if let FfiType::Struct { .. } = **inner {
+                            val.rows().map(Value::unpacked).collect()
+                        } else {
+                            let mut val = val.clone();
+                            val.deshape();
+                            val.rows().collect()
+                        };
+                        let mut buf = buffer(size * rows.len());
+                        let ptr: *mut u8 = buf.as_mut_ptr().cast();
+                        for (j, row) in rows.iter().enumerate() {
+                            unsafe { write_value(inner, row, ptr.add(j * size), &mut bindings)? };
+                        }
+                        bindings.push_list(buf);
+                        if *mutable {
+                            outputs.push((i, (**inner).clone(), Some(rows.len())));
+                        }
+                    }
                     (FfiType::List { inner, .. }, val) => match (&**inner, val) {
                         (FfiType::Char | FfiType::UChar, Value::Char(arr)) => list!(arr, c_char),
                         (FfiType::Short | FfiType::UShort, Value::Num(arr)) => list!(arr, c_short),
@@ -288,12 +499,37 @@ mod enabled {
                     let s = CStr::from_ptr(ptr).to_str().map_err(|e| e.to_string())?;
                     results.push(Value::from(s))
                 },
-                _ => {
+                FfiType::Void
+                | FfiType::Ptr { .. }
+                | FfiType::List { .. }
+                | FfiType::Func { .. } => {
                     return Err(format!(
                         "Invalid or unsupported FFI return type {return_ty:?}"
                     ))
                 }
+                inner => unsafe {
+                    let ptr = cif.call::<*const u8>(fptr, args);
+                    if ptr.is_null() {
+                        return Err("FFI function returned a null pointer".into());
+                    }
+                    results.push(read_value(inner, ptr)?)
+                },
             },
+            FfiType::Struct { .. } => unsafe {
+                let mut buf = buffer(size_align(&return_ty).0);
+                raw::ffi_call(
+                    cif.as_raw_ptr(),
+                    Some(*fptr.as_safe_fun()),
+                    buf.as_mut_ptr().cast(),
+                    args.as_ptr() as *mut *mut c_void,
+                );
+                results.push(read_value(&return_ty, buf.as_ptr().cast())?)
+            },
+            FfiType::Func { .. } => {
+                return Err(format!(
+                    "Invalid or unsupported FFI return type {return_ty:?}"
+                ))
+            }
             FfiType::List {
                 len_index, inner, ..
             } => {
@@ -334,6 +570,17 @@ mod enabled {
             }
         }
 
+        // Read out-parameters
+        for (index, ty, len) in outputs {
+            let ptr: *const u8 = bindings.get::<(*const u64, Vec<u64>)>(index).0.cast();
+            results.push(unsafe {
+                match len {
+                    Some(len) => read_list(&ty, ptr, len)?,
+                    None => read_value(&ty, ptr)?,
+                }
+            });
+        }
+
         // Bindings must live until after the call
         drop(bindings);
 
@@ -348,6 +595,8 @@ mod enabled {
     struct FfiBindings {
         data: Vec<Box<dyn Any>>,
         args: Vec<Arg>,
+        /// Strings pointed to by struct fields
+        strings: Vec<CString>,
     }
 
     impl FfiBindings {
@@ -381,6 +630,29 @@ mod enabled {
                     .0,
             ));
         }
+        fn push_struct(&mut self, buf: Vec<u64>) {
+            self.data.push(Box::new(buf));
+            self.args.push(Arg::new(
+                &self
+                    .data
+                    .last()
+                    .unwrap()
+                    .downcast_ref::<Vec<u64>>()
+                    .unwrap()[0],
+            ));
+        }
+        fn push_closure(&mut self, closure: FfiClosure) {
+            self.data.push(Box::new((closure.code, closure)));
+            self.args.push(Arg::new(
+                &self
+                    .data
+                    .last()
+                    .unwrap()
+                    .downcast_ref::<(*const c_void, FfiClosure)>()
+                    .unwrap()
+                    .0,
+            ));
+        }
         fn get<T: Any>(&self, index: usize) -> &T {
             let any = &self.data[index];
             any.downcast_ref::<T>()
@@ -407,6 +679,295 @@ mod enabled {
             FfiType::Double => Type::f64(),
             FfiType::Ptr { .. } => Type::pointer(),
             FfiType::List { .. } => Type::pointer(),
+            FfiType::Struct { fields } => Type::structure(fields.iter().map(ffity_to_cty)),
+            FfiType::Func { .. } => Type::pointer(),
+        }
+    }
+
+    /// Get the size and alignment of a type
+    fn size_align(ty: &FfiType) -> (usize, usize) {
+        macro_rules! of {
+            ($ty:ty) => {
+                (size_of::<$ty>(), align_of::<$ty>())
+            };
+        }
+        match ty {
+            FfiType::Void => (0, 1),
+            FfiType::Char | FfiType::UChar => of!(c_char),
+            FfiType::Short | FfiType::UShort => of!(c_short),
+            FfiType::Int | FfiType::UInt => of!(c_int),
+            FfiType::Long | FfiType::ULong => of!(c_long),
+            FfiType::LongLong | FfiType::ULongLong => of!(c_longlong),
+            FfiType::Float => of!(c_float),
+            FfiType::Double => of!(c_double),
+            FfiType::Ptr { .. } | FfiType::List { .. } | FfiType::Func { .. } => {
+                of!(*const c_void)
+            }
+            FfiType::Struct { fields } => {
+                let (_, size, align) = struct_layout(fields);
+                (size, align)
+            }
+        }
+    }
+
+    /// Get the offsets of a struct's fields and the struct's size and alignment
+    fn struct_layout(fields: &[FfiType]) -> (Vec<usize>, usize, usize) {
+        let mut offsets = Vec::with_capacity(fields.len());
+        let mut size: usize = 0;
+        let mut align = 1;
+        for field in fields {
+            let (field_size, field_align) = size_align(field);
+            size = size.next_multiple_of(field_align);
+            offsets.push(size);
+            size += field_size;
+            align = align.max(field_align);
+        }
+        (offsets, size.next_multiple_of(align), align)
+    }
+
+    /// Allocate zeroed memory that is aligned for any supported type
+    fn buffer(size: usize) -> Vec<u64> {
+        vec![0; size.div_ceil(8).max(1)]
+    }
+
+    fn invalid(ty: &FfiType, val: &Value) -> String {
+        format!(
+            "Array of {} with shape {} is not a valid value for FFI type {ty:?}",
+            val.type_name_plural(),
+            val.shape()
+        )
+    }
+
+    fn scalar_num(val: &Value) -> Option<f64> {
+        match val {
+            Value::Num(arr) if arr.rank() == 0 => Some(arr.data[0]),
+            #[cfg(feature = "bytes")]
+            Value::Byte(arr) if arr.rank() == 0 => Some(arr.data[0] as f64),
+            Value::Int(arr) if arr.rank() == 0 => Some(arr.data[0] as f64),
+            Value::Char(arr) if arr.rank() == 0 => Some(arr.data[0] as u32 as f64),
+            _ => None,
+        }
+    }
+
+    /// Write a value to memory with the layout of a type
+    ///
+    /// # Safety
+    /// `ptr` must be valid for writes of the type's size
+    unsafe fn write_value(
+        ty: &FfiType,
+        val: &Value,
+        ptr: *mut u8,
+        bindings: &mut FfiBindings,
+    ) -> Result<(), String> {
+        macro_rules! scalar {
+            ($c_ty:ty) => {{
+                let n = scalar_num(val).ok_or_else(|| invalid(ty, val))?;
+                ptr.cast::<$c_ty>().write_unaligned(n as $c_ty)
+            }};
+        }
+        match ty {
+            FfiType::Char => scalar!(c_schar),
+            FfiType::Short => scalar!(c_short),
+            FfiType::Int => scalar!(c_int),
+            FfiType::Long => scalar!(c_long),
+            FfiType::LongLong => scalar!(c_longlong),
+            FfiType::UChar => scalar!(c_uchar),
+            FfiType::UShort => scalar!(c_ushort),
+            FfiType::UInt => scalar!(c_uint),
+            FfiType::ULong => scalar!(c_ulong),
+            FfiType::ULongLong => scalar!(c_ulonglong),
+            FfiType::Float => scalar!(c_float),
+            FfiType::Double => scalar!(c_double),
+            FfiType::Ptr { inner, .. } if **inner == FfiType::Char => match val {
+                Value::Char(arr) if arr.rank() <= 1 => {
+                    let s: String = arr.data.iter().take_while(|&&c| c != '\0').collect();
+                    let s = CString::new(s).unwrap();
+                    ptr.cast::<*const c_char>().write_unaligned(s.as_ptr());
+                    bindings.strings.push(s);
+                }
+                val => return Err(invalid(ty, val)),
+            },
+            FfiType::Struct { fields } => {
+                if val.rank() != 1 || val.row_count() != fields.len() {
+                    return Err(format!(
+                        "FFI struct has {} fields, but the value has shape {}",
+                        fields.len(),
+                        val.shape()
+                    ));
+                }
+                let (offsets, ..) = struct_layout(fields);
+                for ((field, offset), row) in fields.iter().zip(offsets).zip(val.rows()) {
+                    write_value(field, &row.unpacked(), ptr.add(offset), bindings)?;
+                }
+            }
+            ty => return Err(format!("{ty:?} is not supported as an FFI value here")),
+        }
+        Ok(())
+    }
+
+    /// Read a number from memory with the layout of a type
+    ///
+    /// # Safety
+    /// `ptr` must be valid for reads of the type's size
+    unsafe fn read_num(ty: &FfiType, ptr: *const u8) -> Option<f64> {
+        macro_rules! scalar {
+            ($c_ty:ty) => {
+                ptr.cast::<$c_ty>().read_unaligned() as f64
+            };
+        }
+        Some(match ty {
+            FfiType::Char => scalar!(c_schar),
+            FfiType::Short => scalar!(c_short),
+            FfiType::Int => scalar!(c_int),
+            FfiType::Long => scalar!(c_long),
+            FfiType::LongLong => scalar!(c_longlong),
+            FfiType::UChar => scalar!(c_uchar),
+            FfiType::UShort => scalar!(c_ushort),
+            FfiType::UInt => scalar!(c_uint),
+            FfiType::ULong => scalar!(c_ulong),
+            FfiType::ULongLong => scalar!(c_ulonglong),
+            FfiType::Float => scalar!(c_float),
+            FfiType::Double => scalar!(c_double),
+            _ => return None,
+        })
+    }
+
+    /// Read a value from memory with the layout of a type
+    ///
+    /// Structs are read as lists of boxed fields.
+    ///
+    /// # Safety
+    /// `ptr` must be valid for reads of the type's size
+    unsafe fn read_value(ty: &FfiType, ptr: *const u8) -> Result<Value, String> {
+        if let Some(n) = read_num(ty, ptr) {
+            return Ok(n.into());
+        }
+        Ok(match ty {
+            FfiType::Ptr { inner, .. } if **inner == FfiType::Char => {
+                let s = ptr.cast::<*const c_char>().read_unaligned();
+                if s.is_null() {
+                    Value::from("")
+                } else {
+                    Value::from(CStr::from_ptr(s).to_str().map_err(|e| e.to_string())?)
+                }
+            }
+            FfiType::Struct { fields } => {
+                let (offsets, ..) = struct_layout(fields);
+                let fields = (fields.iter().zip(offsets))
+                    .map(|(field, offset)| read_value(field, ptr.add(offset)).map(Boxed))
+                    .collect::<Result<EcoVec<_>, _>>()?;
+                Array::new(fields.len(), fields).into()
+            }
+            ty => return Err(format!("{ty:?} is not supported as an FFI value here")),
+        })
+    }
+
+    /// Read a list of values from memory with the layout of a type
+    ///
+    /// # Safety
+    /// `ptr` must be valid for reads of `len` values of the type
+    unsafe fn read_list(ty: &FfiType, ptr: *const u8, len: usize) -> Result<Value, String> {
+        let (size, _) = size_align(ty);
+        if let FfiType::Struct { .. } = ty {
+            let rows = (0..len)
+                .map(|i| read_value(ty, ptr.add(i * size)).map(Boxed))
+                .collect::<Result<EcoVec<_>, _>>()?;
+            return Ok(Array::new(len, rows).into());
         }
+        let nums = (0..len)
+            .map(|i| read_num(ty, ptr.add(i * size)))
+            .collect::<Option<EcoVec<_>>>()
+            .ok_or_else(|| format!("{ty:?} is not supported as an FFI list element"))?;
+        Ok(Array::new(len, nums).into())
+    }
+
+    fn contains_pointer(ty: &FfiType) -> bool {
+        match ty {
+            FfiType::Ptr { .. } | FfiType::List { .. } | FfiType::Func { .. } => true,
+            FfiType::Struct { fields } => fields.iter().any(contains_pointer),
+            _ => false,
+        }
+    }
+
+    /// A C function pointer that calls a function registered with `&ffcb`
+    struct FfiClosure {
+        closure: *mut low::ffi_closure,
+        code: *const c_void,
+        _cif: Box<Cif>,
+        _callback: Box<FfiCallback>,
+    }
+
+    struct FfiCallback {
+        id: usize,
+        ret: FfiType,
+        args: Vec<FfiType>,
+    }
+
+    impl FfiClosure {
+        fn new(id: usize, ret: &FfiType, args: &[FfiType]) -> Result<Self, String> {
+            if contains_pointer(ret) {
+                return Err(format!(
+                    "FFI callbacks cannot return pointers, but the return type is {ret:?}"
+                ));
+            }
+            let cif = Box::new(Cif::new(args.iter().map(ffity_to_cty), ffity_to_cty(ret)));
+            let callback = Box::new(FfiCallback {
+                id,
+                ret: ret.clone(),
+                args: args.to_vec(),
+            });
+            let (closure, code) = low::closure_alloc();
+            let res = unsafe {
+                low::prep_closure(closure, cif.as_raw_ptr(), call_callback, &*callback, code)
+            };
+            if let Err(e) = res {
+                unsafe { low::closure_free(closure) };
+                return Err(format!("Failed to create FFI callback: {e:?}"));
+            }
+            Ok(FfiClosure {
+                closure,
+                code: code.as_ptr(),
+                _cif: cif,
+                _callback: callback,
+            })
+        }
+    }
+
+    impl Drop for FfiClosure {
+        fn drop(&mut self) {
+            unsafe { low::closure_free(self.closure) }
+        }
+    }
+
+    unsafe extern "C" fn call_callback(
+        _cif: &low::ffi_cif,
+        result: &mut u64,
+        args: *const *const c_void,
+        callback: &FfiCallback,
+    ) {
+        *result = 0;
+        let result: *mut u64 = result;
+        let args = (callback.args.iter().enumerate())
+            .map(|(i, ty)| read_value(ty, (*args.add(i)).cast()))
+            .collect();
+        let returns = callback.ret != FfiType::Void;
+        call_ffi_callback(callback.id, args, returns, |val| match &callback.ret {
+            // Integer results are widened to a full register
+            FfiType::Char | FfiType::Short | FfiType::Int | FfiType::Long | FfiType::LongLong => {
+                let n = scalar_num(&val).ok_or_else(|| invalid(&callback.ret, &val))?;
+                *result = n as i64 as u64;
+                Ok(())
+            }
+            FfiType::UChar
+            | FfiType::UShort
+            | FfiType::UInt
+            | FfiType::ULong
+            | FfiType::ULongLong => {
+                let n = scalar_num(&val).ok_or_else(|| invalid(&callback.ret, &val))?;
+                *result = n as u64;
+                Ok(())
+            }
+            ty => write_value(ty, &val, result.cast(), &mut FfiBindings::default()),
+        });
     }
 }
@@ -15,13 +15,15 @@ use paste::paste;
 
 use crate::{
     ast::*,
+    check::instrs_signature,
     function::Signature,
     grid_fmt::GridFmt,
     lex::{is_ident_char, CodeSpan, Loc, Sp},
+    locale::{with_locale, Locale},
     parse::{parse, split_words, trim_spaces, unsplit_words},
     value::Value,
-    Compiler, FunctionId, Ident, InputSrc, Inputs, Primitive, RunMode, SafeSys, SysBackend, SysOp,
-    Uiua, UiuaError, UiuaResult,
+    Compiler, FunctionId, Ident, InputSrc, Inputs, Instr, Primitive, RunMode, SafeSys, SysBackend,
+    SysOp, Uiua, UiuaError, UiuaResult,
 };
 
 trait ConfigValue: Sized {
@@ -203,6 +205,8 @@ The following configuration options are available:
             pub inputs: Inputs,
             /// The system backend used for output comments
             pub backend: Arc<dyn SysBackend>,
+            /// The locale whose localized primitive names are accepted as input
+            pub locale: Option<Arc<Locale>>,
         }
 
         paste! {
@@ -216,6 +220,10 @@ The following configuration options are available:
                         }
                     }
                 )*
+                /// Set the locale whose localized primitive names are accepted as input
+                pub fn with_locale(self, locale: Option<Arc<Locale>>) -> Self {
+                    Self { locale, ..self }
+                }
             }
         }
 
@@ -227,6 +235,7 @@ The following configuration options are available:
                     )*
                     inputs: Inputs::default(),
                     backend: Arc::new(SafeSys),
+                    locale: None,
                 }
             }
         }
@@ -239,6 +248,7 @@ The following configuration options are available:
                     )*
                     inputs: Inputs::default(),
                     backend: Arc::new(SafeSys),
+                    locale: None,
                 }
             }
         }
@@ -268,6 +278,10 @@ create_config!(
     (align_comments, bool, true),
     /// Whether to indent item imports
     (indent_item_imports, bool, true),
+    /// Whether to add end-of-line comments with the inferred signatures of single-line bindings and top-level lines
+    ///
+    /// Existing signature comments are updated. Bindings with declared signatures are skipped.
+    (signature_comments, bool, false),
 );
 
 /// The source from which to populate the formatter configuration.
@@ -404,7 +418,9 @@ pub fn format_str(input: &str, config: &FormatConfig) -> UiuaResult<FormatOutput
 
 fn format_impl(input: &str, src: InputSrc, config: &FormatConfig) -> UiuaResult<FormatOutput> {
     let mut inputs = Inputs::default();
-    let (items, errors, _) = parse(input, src.clone(), &mut inputs);
+    let (items, errors, _) = with_locale(config.locale.clone(), || {
+        parse(input, src.clone(), &mut inputs)
+    });
     if errors.is_empty() {
         Ok(Formatter {
             src,
@@ -415,6 +431,7 @@ fn format_impl(input: &str, src: InputSrc, config: &FormatConfig) -> UiuaResult<
             end_of_line_comments: Vec::new(),
             prev_import_function: None,
             output_comments: None,
+            signatures: HashMap::new(),
         }
         .format_top_items(&items))
     } else {
@@ -446,6 +463,84 @@ pub fn format_file<P: AsRef<Path>>(
     Ok(formatted)
 }
 
+/// Apply the fixes suggested by diagnostics to Uiua code
+///
+/// The code is compiled to find the diagnostics.
+/// The path is used to resolve imports, whose diagnostics are not fixed.
+/// Fixes that overlap an earlier fix are skipped.
+///
+/// Returns the fixed code and the number of fixes that were applied.
+pub fn fix_str(input: &str, path: Option<&Path>) -> (String, usize) {
+    let mut comp = Compiler::new();
+    comp.mode(RunMode::All);
+    // Imported files are never string sources
+    let is_input = |src: &InputSrc| match (src, path) {
+        (InputSrc::File(file), Some(path)) => **file == *path,
+        (InputSrc::Str(_), None) => true,
+        _ => false,
+    };
+    match path {
+        Some(path) => _ = comp.load_str_src(input, path),
+        None => _ = comp.load_str(input),
+    }
+    let mut fixes: Vec<Sp<String>> = (comp.take_diagnostics().into_iter())
+        .filter_map(|diagnostic| diagnostic.fix)
+        .filter(|fix| is_input(&fix.span.src))
+        .collect();
+    fixes.sort_by_key(|fix| fix.span.start.byte_pos);
+    let mut output = String::new();
+    let mut pos = 0;
+    let mut count = 0;
+    for fix in fixes {
+        let range = fix.span.byte_range();
+        if range.start < pos {
+            continue;
+        }
+        output.push_str(&input[pos..range.start]);
+        output.push_str(&fix.value);
+        // Keep the code on either side of a removal from merging into one token
+        let before = output.chars().next_back();
+        let after = input[range.end..].chars().next();
+        if fix.value.is_empty()
+            && before.zip(after).is_some_and(|(a, b)| {
+                (a.is_ascii_digit() || is_ident_char(a)) && (b.is_ascii_digit() || is_ident_char(b))
+            })
+        {
+            output.push(' ');
+        }
+        pos = range.end;
+        count += 1;
+    }
+    output.push_str(&input[pos..]);
+    (output, count)
+}
+
+/// Apply the fixes suggested by diagnostics to Uiua code in a file at the given path, then format it
+///
+/// This modifies the file.
+/// Returns the formatted output and the number of fixes that were applied.
+pub fn fix_file<P: AsRef<Path>>(
+    path: P,
+    config: &FormatConfig,
+    dont_write: bool,
+) -> UiuaResult<(FormatOutput, usize)> {
+    let path = path.as_ref();
+    let input =
+        fs::read_to_string(path).map_err(|e| UiuaError::Load(path.to_path_buf(), e.into()))?;
+    let (fixed, count) = fix_str(&input, Some(path));
+    let formatted = format(&fixed, path, config)?;
+    if formatted.output == input {
+        return Ok((formatted, count));
+    }
+    let is_no_format_set = env::var("UIUA_NO_FORMAT").is_ok_and(|val| val == "1");
+    let should_write = !dont_write && !is_no_format_set;
+    if should_write {
+        fs::write(path, &formatted.output)
+            .map_err(|e| UiuaError::Format(path.to_path_buf(), e.into()))?;
+    }
+    Ok((formatted, count))
+}
+
 struct Formatter<'a> {
     src: InputSrc,
     config: &'a FormatConfig,
@@ -455,10 +550,18 @@ struct Formatter<'a> {
     end_of_line_comments: Vec<(usize, String)>,
     prev_import_function: Option<Ident>,
     output_comments: Option<HashMap<usize, Vec<Vec<Value>>>>,
+    /// Inferred signatures for signature comments, keyed by line
+    signatures: HashMap<usize, Signature>,
 }
 
 impl<'a> Formatter<'a> {
     fn format_top_items(mut self, items: &[Item]) -> FormatOutput {
+        if self.config.signature_comments {
+            let input = self.inputs.get(&self.src);
+            let mut comp = Compiler::new();
+            comp.mode(RunMode::All);
+            infer_signatures(&mut comp, &input, items, &mut self.signatures);
+        }
         self.format_items(items);
         let mut output = self.output;
         while output.ends_with('\n') {
@@ -537,16 +640,35 @@ impl<'a> Formatter<'a> {
             }
             Item::Words(lines) => {
                 self.prev_import_function = None;
-                let lines = unsplit_words(lines.iter().cloned().flat_map(split_words));
+                let lines = (lines.iter())
+                    .map(|line| self.signature_commented(line))
+                    .flat_map(split_words);
+                let lines = unsplit_words(lines);
                 self.format_multiline_words(&lines, false, false, 0);
             }
+            Item::Import(import) => {
+                self.prev_import_function = None;
+                if let Some(name) = &import.name {
+                    self.output.push_str(&name.value);
+                    self.output.push(' ');
+                }
+                self.output.push_str("~ ");
+                let path = (import.path.span.clone()).sp(Word::String(import.path.value.clone()));
+                self.format_word(&path, 0);
+                for item in &import.items {
+                    self.output.push(' ');
+                    self.output.push_str(&item.value);
+                }
+            }
             Item::Binding(binding) => {
-                match binding.words.first().map(|w| &w.value) {
+                let words = if binding.signature.is_none() {
+                    self.signature_commented(&binding.words)
+                } else {
+                    binding.words.clone()
+                };
+                match words.first().map(|w| &w.value) {
                     Some(Word::Primitive(Primitive::Sys(SysOp::Import)))
-                        if (binding.words.iter())
-                            .filter(|word| word.value.is_code())
-                            .count()
-                            == 2 =>
+                        if (words.iter()).filter(|word| word.value.is_code()).count() == 2 =>
                     {
                         self.prev_import_function = Some(binding.name.value.clone());
                     }
@@ -564,20 +686,18 @@ impl<'a> Formatter<'a> {
 
                 self.output.push_str(&binding.name.value);
                 self.output.push_str(" ←");
-                if !binding.words.is_empty() || binding.signature.is_some() {
+                if !words.is_empty() || binding.signature.is_some() {
                     self.output.push(' ');
                 }
                 if let Some(sig) = &binding.signature {
                     self.format_signature('|', sig.value, true);
                 }
-                let span = binding
-                    .words
-                    .first()
-                    .zip(binding.words.last())
+                let span = (words.first())
+                    .zip(words.last())
                     .map(|(first, last)| first.span.clone().merge(last.span.clone()))
                     .or_else(|| binding.signature.as_ref().map(|sig| sig.span.clone()))
                     .unwrap_or_else(|| binding.arrow_span.clone());
-                let mut lines = unsplit_words(split_words(binding.words.clone()));
+                let mut lines = unsplit_words(split_words(words));
                 if lines.len() == 1 {
                     self.format_words(&lines[0], true, 0);
                 } else {
@@ -981,6 +1101,42 @@ impl<'a> Formatter<'a> {
             self.glyph_map.insert(span.clone(), (start, end));
         }
     }
+    /// Add or update the signature comment at the end of a line of words
+    ///
+    /// The line is unchanged if there is no inferred signature for it
+    /// or if it ends with a different comment.
+    fn signature_commented(&self, line: &[Sp<Word>]) -> Vec<Sp<Word>> {
+        let mut line = line.to_vec();
+        let Some(first) = line.iter().find(|word| word.value.is_code()) else {
+            return line;
+        };
+        let Some(sig) = self.signatures.get(&(first.span.start.line as usize)) else {
+            return line;
+        };
+        while let Some(Word::Spaces) = line.last().map(|word| &word.value) {
+            line.pop();
+        }
+        match line.last().map(|word| &word.value) {
+            Some(Word::Comment(comment)) if is_signature_comment(comment) => {
+                line.pop();
+                while let Some(Word::Spaces) = line.last().map(|word| &word.value) {
+                    line.pop();
+                }
+            }
+            Some(Word::Comment(_)) => return line,
+            _ => {}
+        }
+        let Some(last) = line.last() else {
+            return line;
+        };
+        let end = CodeSpan {
+            start: last.span.end,
+            ..last.span.clone()
+        };
+        line.push(end.clone().sp(Word::Spaces));
+        line.push(end.sp(Word::Comment(sig.to_string())));
+        line
+    }
     fn output_comment(&mut self, index: usize) -> Vec<Vec<Value>> {
         let values = self.output_comments.get_or_insert_with(|| {
             let mut env = Uiua::with_backend(self.config.backend.clone())
@@ -1001,6 +1157,73 @@ impl<'a> Formatter<'a> {
     }
 }
 
+/// Infer the signatures of single-line bindings and top-level lines, keyed by line
+///
+/// Each item is compiled in turn so that a failure does not prevent inference for the rest.
+fn infer_signatures(
+    comp: &mut Compiler,
+    input: &str,
+    items: &[Item],
+    signatures: &mut HashMap<usize, Signature>,
+) {
+    for item in items {
+        match item {
+            Item::Words(lines) => {
+                for line in lines {
+                    let mut code = line.iter().filter(|word| word.value.is_code());
+                    let Some(first) = code.next() else {
+                        continue;
+                    };
+                    let last = code.next_back().unwrap_or(first);
+                    let span = first.span.clone().merge(last.span.clone());
+                    let start = comp.assembly().top_slices.len();
+                    if comp.load_str(&input[span.byte_range()]).is_err()
+                        || span.start.line != span.end.line
+                    {
+                        continue;
+                    }
+                    let asm = comp.assembly();
+                    let instrs: Vec<Instr> = (asm.top_slices[start..].iter())
+                        .flat_map(|&slice| asm.instrs(slice))
+                        .cloned()
+                        .collect();
+                    if let Ok(sig) = instrs_signature(&instrs) {
+                        signatures.insert(span.start.line as usize, sig);
+                    }
+                }
+            }
+            Item::Binding(binding) => {
+                let span = item.span();
+                let count = comp.assembly().bindings.len();
+                if comp.load_str(&input[span.byte_range()]).is_err()
+                    || binding.signature.is_some()
+                    || span.start.line != span.end.line
+                    || comp.assembly().bindings.len() == count
+                {
+                    continue;
+                }
+                let bindings = &comp.assembly().bindings;
+                if let Some(sig) = bindings.last().and_then(|b| b.global.signature()) {
+                    signatures.insert(span.start.line as usize, sig);
+                }
+            }
+            Item::Import(_) | Item::TestScope(_) => {
+                _ = comp.load_str(&input[item.span().byte_range()]);
+            }
+        }
+    }
+}
+
+/// Check if a comment is a signature comment like `|2.1`
+fn is_signature_comment(comment: &str) -> bool {
+    comment.trim().strip_prefix('|').is_some_and(|sig| {
+        let (args, outputs) = sig.split_once('.').unwrap_or((sig, "1"));
+        [args, outputs]
+            .iter()
+            .all(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+    })
+}
+
 fn word_is_multiline(word: &Word) -> bool {
     match word {
         Word::Number(..) => false,
@@ -1059,3 +1282,30 @@ fn end_loc(s: &str) -> Loc {
         byte_pos,
     }
 }
+
+#[test]
+fn fix_idioms() {
+    assert_eq!(fix_str("⇌⇌[1 2 3]", None), ("[1 2 3]".into(), 1));
+    assert_eq!(fix_str("¬=1 2", None), ("≠1 2".into(), 1));
+    assert_eq!(fix_str("⊢⊏[0] [1 2]", None), ("⊏⊢[0] [1 2]".into(), 1));
+    assert_eq!(fix_str("+∘1 ¯∘2", None), ("+1 ¯2".into(), 2));
+    // Removals do not merge the code around them
+    assert_eq!(fix_str("1::2", None), ("1 2".into(), 1));
+    assert_eq!(fix_str("+1 2", None), ("+1 2".into(), 0));
+}
+
+#[test]
+fn signature_comments() {
+    let config = FormatConfig::default().with_signature_comments(true);
+    let expected = "F ← +1 # |1.1\nF 2    # |0.1\n";
+    let formatted = format_str("F ← +1\nF 2", &config).unwrap().output;
+    assert_eq!(formatted, expected);
+    // Existing signature comments are updated instead of duplicated
+    let formatted = format_str(expected, &config).unwrap().output;
+    assert_eq!(formatted, expected);
+    let formatted = format_str("F ← +1 # |2.1\nF 2", &config).unwrap().output;
+    assert_eq!(formatted, expected);
+    // Other comments are kept
+    let formatted = format_str("F ← +1 # inc\nF 2", &config).unwrap().output;
+    assert_eq!(formatted, "F ← +1 # inc\nF 2    # |0.1\n");
+}
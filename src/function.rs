@@ -14,10 +14,11 @@ use crate::{
     lex::CodeSpan,
     primitive::{ImplPrimitive, Primitive},
     value::Value,
-    Assembly, Ident,
+    Assembly, Global, Ident,
 };
 
 /// A Uiua bytecode instruction
+#[doc(hidden)]
 #[derive(Clone)]
 #[repr(u8)]
 #[allow(missing_docs)]
@@ -132,6 +133,7 @@ pub enum Instr {
 }
 
 /// A type of temporary stacks
+#[doc(hidden)]
 #[derive(
     Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Sequence, Serialize, Deserialize,
 )]
@@ -151,6 +153,40 @@ impl fmt::Display for TempStack {
     }
 }
 
+/// Check if some instructions are pure
+///
+/// Pure instructions have no side effects and do not depend on interpreter state.
+pub(crate) fn instrs_are_pure(instrs: &[Instr], asm: &Assembly) -> bool {
+    instrs_are_pure_impl(instrs, asm, &mut Vec::new())
+}
+
+fn instrs_are_pure_impl(instrs: &[Instr], asm: &Assembly, visited: &mut Vec<FuncSlice>) -> bool {
+    instrs.iter().all(|instr| match instr {
+        Instr::Prim(prim, _) => prim.is_pure(),
+        Instr::ImplPrim(prim, _) => prim.is_pure(),
+        Instr::PushFunc(f) => func_is_pure(f, asm, visited),
+        // A global that is not bound yet could be anything
+        &Instr::CallGlobal { index, .. } => match asm.bindings.get(index).map(|b| &b.global) {
+            Some(Global::Func(f)) => func_is_pure(f, asm, visited),
+            Some(Global::Const(_) | Global::Module { .. }) => true,
+            Some(Global::Sig(_)) | None => false,
+        },
+        Instr::BindGlobal { .. }
+        | Instr::Dynamic(_)
+        | Instr::GetTempFunction { .. }
+        | Instr::SetOutputComment { .. } => false,
+        _ => true,
+    })
+}
+
+fn func_is_pure(f: &Function, asm: &Assembly, visited: &mut Vec<FuncSlice>) -> bool {
+    if visited.contains(&f.slice) {
+        return true;
+    }
+    visited.push(f.slice);
+    instrs_are_pure_impl(f.instrs(asm), asm, visited)
+}
+
 impl PartialEq for Instr {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
@@ -160,6 +196,14 @@ impl PartialEq for Instr {
             (Self::Prim(a, _), Self::Prim(b, _)) => a == b,
             (Self::ImplPrim(a, _), Self::ImplPrim(b, _)) => a == b,
             (Self::Call(a), Self::Call(b)) => a == b,
+            (
+                Self::CallGlobal {
+                    index: a, call: ac, ..
+                },
+                Self::CallGlobal {
+                    index: b, call: bc, ..
+                },
+            ) => a == b && ac == bc,
             (Self::Format { parts: a, .. }, Self::Format { parts: b, .. }) => a == b,
             (Self::PushFunc(a), Self::PushFunc(b)) => a == b,
             (Self::PushTemp { count: a, .. }, Self::PushTemp { count: b, .. }) => a == b,
@@ -203,6 +247,28 @@ impl Instr {
     pub(crate) fn is_compile_only(&self) -> bool {
         matches!(self, Self::PushSig(_) | Self::PopSig)
     }
+    /// Get the index of the instruction's span, if it has one
+    pub(crate) fn span(&self) -> Option<usize> {
+        match self {
+            Self::Prim(_, span) | Self::ImplPrim(_, span) => Some(*span),
+            Self::BindGlobal { span, .. }
+            | Self::EndArray { span, .. }
+            | Self::Switch { span, .. }
+            | Self::Format { span, .. }
+            | Self::Label { span, .. }
+            | Self::PushLocals { span, .. }
+            | Self::GetLocal { span, .. }
+            | Self::Unpack { span, .. }
+            | Self::GetTempFunction { span, .. }
+            | Self::TouchStack { span, .. }
+            | Self::PushTemp { span, .. }
+            | Self::PopTemp { span, .. }
+            | Self::CopyToTemp { span, .. }
+            | Self::CopyFromTemp { span, .. }
+            | Self::DropTemp { span, .. } => Some(*span),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Debug for Instr {
@@ -292,6 +358,7 @@ pub struct Function {
 }
 
 /// A range of compiled instructions
+#[doc(hidden)]
 #[derive(
     Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
 )]
@@ -506,6 +573,13 @@ impl Function {
     pub fn instrs_mut<'a>(&self, env: &'a mut impl AsMut<Assembly>) -> &'a mut [Instr] {
         env.as_mut().instrs_mut(self.slice)
     }
+    /// Check if this function is pure
+    ///
+    /// A pure function has no side effects and does not depend on interpreter state,
+    /// so calls to it can be run on any thread.
+    pub(crate) fn is_pure(&self, asm: &Assembly) -> bool {
+        func_is_pure(self, asm, &mut Vec::new())
+    }
     /// Try to get a lone primitive from this function
     pub fn as_primitive(&self, env: &impl AsRef<Assembly>) -> Option<(Primitive, usize)> {
         match self.instrs(env.as_ref()) {
@@ -10,7 +10,7 @@ use std::{
 };
 
 use crate::{
-    algorithm::map::{MapItem, EMPTY_NAN, TOMBSTONE_NAN},
+    algorithm::map::{ordered_slots, EMPTY_NAN, TOMBSTONE_NAN},
     array::{Array, ArrayValue},
     boxed::Boxed,
     value::Value,
@@ -45,6 +45,14 @@ impl GridFmt for u8 {
     }
 }
 
+impl GridFmt for u32 {
+    fn fmt_grid(&self, boxed: bool, _label: bool) -> Grid {
+        vec![boxed_scalar(boxed)
+            .chain(self.to_string().chars())
+            .collect()]
+    }
+}
+
 const ROUND_TO: f64 = 3.0 * f64::EPSILON;
 
 impl GridFmt for f64 {
@@ -129,6 +137,7 @@ impl GridFmt for Value {
             Value::Num(n) => n.fmt_grid(boxed, label),
             #[cfg(feature = "bytes")]
             Value::Byte(b) => b.fmt_grid(boxed, label),
+            Value::Int(b) => b.fmt_grid(boxed, label),
             Value::Complex(c) => c.fmt_grid(boxed, label),
             Value::Box(v) => v.fmt_grid(boxed, label),
             Value::Char(c) => c.fmt_grid(boxed, label),
@@ -169,6 +178,7 @@ impl GridFmt for Boxed {
             Value::Num(array) => array.fmt_grid(true, label),
             #[cfg(feature = "bytes")]
             Value::Byte(array) => array.fmt_grid(true, label),
+            Value::Int(array) => array.fmt_grid(true, label),
             Value::Complex(array) => array.fmt_grid(true, label),
             Value::Char(array) => array.fmt_grid(true, label),
             Value::Box(array) => array.fmt_grid(true, label),
@@ -203,15 +213,12 @@ impl<T: GridFmt + ArrayValue> GridFmt for Array<T> {
                     self.data[0].nested_value().zip(self.data[1].nested_value())
                 {
                     if keys.row_count() > 0 && keys.row_count() == values.row_count() {
-                        let mut empty_entries = 0;
+                        let slots = ordered_slots(self.meta(), keys);
+                        let empty_entries = keys.row_count() - slots.len();
                         let metagrid = metagrid.get_or_insert_with(Metagrid::new);
-                        for (key, value) in keys.rows().zip(values.rows()) {
-                            if key.is_empty_cell() || key.is_tombstone() {
-                                empty_entries += 1;
-                                continue;
-                            }
-                            let key = key.fmt_grid(false, label);
-                            let value = value.fmt_grid(false, label);
+                        for &slot in &slots {
+                            let key = keys.row(slot).fmt_grid(false, label);
+                            let value = values.row(slot).fmt_grid(false, label);
                             metagrid.push(vec![key, vec![" → ".chars().collect()], value]);
                         }
                         if empty_entries > 0 {
@@ -464,3 +471,195 @@ fn pad_grid_min(width: usize, height: usize, grid: &mut Grid) {
         }
     }
 }
+
+/// How to format a value on a single line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineMode {
+    /// Formatted like the grid format, but on one line
+    Compact,
+    /// Formatted as Uiua code that evaluates to the value
+    Repr,
+}
+
+impl Value {
+    /// Get a single-line string representation of the value
+    pub fn show_compact(&self) -> String {
+        let mut s = String::new();
+        self.fmt_line(LineMode::Compact, &mut s);
+        s
+    }
+    /// Get Uiua code that evaluates to the value
+    pub fn repr_code(&self) -> String {
+        let mut s = String::new();
+        self.fmt_line(LineMode::Repr, &mut s);
+        s
+    }
+    fn fmt_line(&self, mode: LineMode, s: &mut String) {
+        match self {
+            Value::Num(arr) => fmt_array_line(arr, mode, s),
+            #[cfg(feature = "bytes")]
+            Value::Byte(arr) => fmt_array_line(arr, mode, s),
+            Value::Int(arr) => fmt_array_line(arr, mode, s),
+            Value::Complex(arr) if mode == LineMode::Repr && arr.element_count() == 0 => {
+                // An empty list literal would be real, so build it from its parts
+                let parts = Array::<f64>::new(arr.shape.clone(), []);
+                s.push(Primitive::Complex.glyph().unwrap());
+                fmt_array_line(&parts, mode, s);
+                s.push(' ');
+                fmt_array_line(&parts, mode, s);
+            }
+            Value::Complex(arr) => fmt_array_line(arr, mode, s),
+            Value::Char(arr) => fmt_array_line(arr, mode, s),
+            Value::Box(arr) => {
+                // Hashmap
+                if arr.meta().map_len.is_some() && arr.shape == [2] {
+                    let (keys, values) = (&arr.data[0].0, &arr.data[1].0);
+                    if keys.row_count() == values.row_count() {
+                        let slots = ordered_slots(arr.meta(), keys);
+                        s.push_str("map ");
+                        Value::from_row_values_infallible(slots.iter().map(|&i| keys.row(i)))
+                            .fmt_line(mode, s);
+                        s.push(' ');
+                        Value::from_row_values_infallible(slots.iter().map(|&i| values.row(i)))
+                            .fmt_line(mode, s);
+                        return;
+                    }
+                }
+                fmt_array_line(arr, mode, s)
+            }
+        }
+    }
+}
+
+trait LineFmt: ArrayValue {
+    /// The delimiters and separator of a list
+    const LINE_DELIMS: (&'static str, &'static str, &'static str) = ("[", " ", "]");
+    /// Format a scalar on its own
+    fn fmt_scalar(&self, mode: LineMode, s: &mut String);
+    /// Format an item of a list
+    fn fmt_item(&self, mode: LineMode, s: &mut String) {
+        self.fmt_scalar(mode, s)
+    }
+}
+
+fn repr_num(n: f64, s: &mut String) {
+    if n.is_nan() {
+        s.push_str("NaN");
+        return;
+    }
+    if n.is_sign_negative() {
+        s.push('¯');
+    }
+    let positive = n.abs();
+    if positive.is_infinite() {
+        s.push('∞');
+    } else if positive != 0.0 && !(1e-5..1e16).contains(&positive) {
+        s.push_str(&format!("{positive:e}"));
+    } else {
+        s.push_str(&positive.to_string());
+    }
+}
+
+impl LineFmt for f64 {
+    fn fmt_scalar(&self, mode: LineMode, s: &mut String) {
+        match mode {
+            LineMode::Compact => s.push_str(&self.grid_string(false)),
+            LineMode::Repr => repr_num(*self, s),
+        }
+    }
+}
+
+impl LineFmt for u8 {
+    fn fmt_scalar(&self, _mode: LineMode, s: &mut String) {
+        s.push_str(&self.to_string());
+    }
+}
+
+impl LineFmt for u32 {
+    fn fmt_scalar(&self, _mode: LineMode, s: &mut String) {
+        s.push_str(&self.to_string());
+    }
+}
+
+impl LineFmt for Complex {
+    fn fmt_scalar(&self, mode: LineMode, s: &mut String) {
+        match mode {
+            LineMode::Compact => s.push_str(&self.grid_string(false)),
+            LineMode::Repr => {
+                s.push(Primitive::Complex.glyph().unwrap());
+                repr_num(self.im, s);
+                s.push(' ');
+                repr_num(self.re, s);
+            }
+        }
+    }
+}
+
+/// Escape a character so that it can be parsed in a string or character literal
+fn escape_char(c: char, in_string: bool, s: &mut String) {
+    match c {
+        char::MAX => s.push_str("\\_"),
+        '"' if in_string => s.push_str("\\\""),
+        ' ' if !in_string => s.push_str("\\s"),
+        c if c as u32 > 0xffff && format!("{c:?}").starts_with("'\\u{") => {
+            s.push_str(&format!("\\u{{{:x}}}", c as u32))
+        }
+        c => s.push_str(&format_char_inner(c)),
+    }
+}
+
+impl LineFmt for char {
+    const LINE_DELIMS: (&'static str, &'static str, &'static str) = ("\"", "", "\"");
+    fn fmt_scalar(&self, _mode: LineMode, s: &mut String) {
+        s.push('@');
+        escape_char(*self, false, s);
+    }
+    fn fmt_item(&self, _mode: LineMode, s: &mut String) {
+        escape_char(*self, true, s);
+    }
+}
+
+impl LineFmt for Boxed {
+    const LINE_DELIMS: (&'static str, &'static str, &'static str) = ("{", " ", "}");
+    fn fmt_scalar(&self, mode: LineMode, s: &mut String) {
+        s.push(Primitive::Box.glyph().unwrap());
+        self.fmt_item(mode, s);
+    }
+    fn fmt_item(&self, mode: LineMode, s: &mut String) {
+        self.0.fmt_line(mode, s);
+    }
+}
+
+fn fmt_array_line<T: LineFmt>(arr: &Array<T>, mode: LineMode, s: &mut String) {
+    let (open, sep, close) = T::LINE_DELIMS;
+    match arr.rank() {
+        0 => arr.data[0].fmt_scalar(mode, s),
+        1 => {
+            s.push_str(open);
+            for (i, item) in arr.data.iter().enumerate() {
+                if i > 0 {
+                    s.push_str(sep);
+                }
+                item.fmt_item(mode, s);
+            }
+            s.push_str(close);
+        }
+        _ if arr.element_count() == 0 => {
+            s.push(Primitive::Reshape.glyph().unwrap());
+            let shape = Array::<f64>::from_iter(arr.shape.iter().map(|&d| d as f64));
+            fmt_array_line(&shape, mode, s);
+            s.push_str(open);
+            s.push_str(close);
+        }
+        _ => {
+            s.push('[');
+            for (i, row) in arr.rows().enumerate() {
+                if i > 0 {
+                    s.push(' ');
+                }
+                fmt_array_line(&row, mode, s);
+            }
+            s.push(']');
+        }
+    }
+}
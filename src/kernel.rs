@@ -0,0 +1,1022 @@
+//! A Jupyter kernel
+//!
+//! The kernel implements the [Jupyter messaging protocol](https://jupyter-client.readthedocs.io/en/latest/messaging.html).
+//! Code runs on its own thread, so the kernel can answer heartbeats and interrupts while a cell is running.
+
+use std::{
+    any::Any,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{self, AtomicBool},
+        Arc,
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use bytes_crate::Bytes;
+use crossbeam_channel::{Receiver, Sender};
+use hmac::{Hmac, Mac};
+use serde::*;
+use serde_json::{json, Value as Json};
+use sha2::Sha256;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use zeromq::{PubSocket, RepSocket, RouterSocket, Socket, SocketRecv, SocketSend, ZmqMessage};
+
+use crate::{
+    algorithm::time::DateTime, is_ident_char, lsp::prim_doc_markdown, spans, Assembly,
+    AudioStreamFn, CancelHandle, Compiler, FfiType, Handle, HttpResponse, NativeSys, Primitive,
+    SpanKind, SysBackend, Uiua, Value,
+};
+
+const PROTOCOL_VERSION: &str = "5.3";
+const DELIMITER: &[u8] = b"<IDS|MSG>";
+
+/// The contents of a Jupyter connection file
+#[derive(Deserialize)]
+struct ConnectionInfo {
+    transport: String,
+    ip: String,
+    shell_port: u16,
+    iopub_port: u16,
+    stdin_port: u16,
+    control_port: u16,
+    hb_port: u16,
+    key: String,
+    signature_scheme: String,
+}
+
+/// Run a Jupyter kernel with the given connection file
+#[doc(hidden)]
+pub fn run_kernel(connection_file: &Path) -> Result<(), String> {
+    let info = fs::read_to_string(connection_file).map_err(|e| e.to_string())?;
+    let info: ConnectionInfo = serde_json::from_str(&info).map_err(|e| e.to_string())?;
+    colored::control::set_override(true);
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())?
+        .block_on(serve(info))
+}
+
+/// Install a kernel spec that runs this executable
+///
+/// Returns the directory the kernel spec was written to.
+#[doc(hidden)]
+pub fn install_kernel_spec() -> Result<PathBuf, String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let data_dir = if let Some(dir) = std::env::var_os("JUPYTER_DATA_DIR") {
+        PathBuf::from(dir)
+    } else if cfg!(windows) {
+        let app_data = std::env::var_os("APPDATA").ok_or("APPDATA is not set")?;
+        PathBuf::from(app_data).join("jupyter")
+    } else {
+        let home = std::env::var_os("HOME").ok_or("HOME is not set")?;
+        if cfg!(target_os = "macos") {
+            PathBuf::from(home).join("Library").join("Jupyter")
+        } else if let Some(data) = std::env::var_os("XDG_DATA_HOME") {
+            PathBuf::from(data).join("jupyter")
+        } else {
+            PathBuf::from(home)
+                .join(".local")
+                .join("share")
+                .join("jupyter")
+        }
+    };
+    let dir = data_dir.join("kernels").join("uiua");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let spec = json!({
+        "argv": [exe, "kernel", "{connection_file}"],
+        "display_name": "Uiua",
+        "language": "uiua",
+    });
+    let spec = serde_json::to_string_pretty(&spec).map_err(|e| e.to_string())?;
+    fs::write(dir.join("kernel.json"), spec).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// A message in the Jupyter protocol
+#[derive(Debug, Clone)]
+struct Message {
+    identities: Vec<Bytes>,
+    header: Json,
+    parent_header: Json,
+    metadata: Json,
+    content: Json,
+}
+
+impl Message {
+    fn msg_type(&self) -> &str {
+        self.header["msg_type"].as_str().unwrap_or_default()
+    }
+}
+
+/// Signs, verifies, and creates messages
+struct Session {
+    id: String,
+    key: Option<Hmac<Sha256>>,
+}
+
+impl Session {
+    fn new(key: &str, scheme: &str) -> Result<Self, String> {
+        let key = if key.is_empty() {
+            None
+        } else if scheme == "hmac-sha256" {
+            Some(Hmac::new_from_slice(key.as_bytes()).map_err(|e| e.to_string())?)
+        } else {
+            return Err(format!("Unsupported signature scheme: {scheme}"));
+        };
+        Ok(Session { id: new_id(), key })
+    }
+    fn mac(&self, parts: [&[u8]; 4]) -> Option<Hmac<Sha256>> {
+        let mut mac = self.key.clone()?;
+        for part in parts {
+            mac.update(part);
+        }
+        Some(mac)
+    }
+    fn sign(&self, parts: [&[u8]; 4]) -> String {
+        let Some(mac) = self.mac(parts) else {
+            return String::new();
+        };
+        let bytes = mac.finalize().into_bytes();
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+    /// Check a hex signature in constant time
+    fn verify(&self, parts: [&[u8]; 4], signature: &[u8]) -> bool {
+        let Some(mac) = self.mac(parts) else {
+            return signature.is_empty();
+        };
+        let Ok(signature) = std::str::from_utf8(signature) else {
+            return false;
+        };
+        let bytes: Option<Vec<u8>> = (0..signature.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(signature.get(i..i + 2)?, 16).ok())
+            .collect();
+        bytes.is_some_and(|bytes| mac.verify_slice(&bytes).is_ok())
+    }
+    fn decode(&self, message: ZmqMessage) -> Result<Message, String> {
+        let frames = message.into_vec();
+        let delimiter = (frames.iter().position(|frame| frame.as_ref() == DELIMITER))
+            .ok_or("Message has no delimiter")?;
+        let (identities, rest) = frames.split_at(delimiter);
+        let [_, signature, header, parent_header, metadata, content, ..] = rest else {
+            return Err("Message is missing parts".into());
+        };
+        if !self.verify([header, parent_header, metadata, content], signature) {
+            return Err("Message has an invalid signature".into());
+        }
+        let parse =
+            |frame: &Bytes| serde_json::from_slice::<Json>(frame).map_err(|e| e.to_string());
+        Ok(Message {
+            identities: identities.to_vec(),
+            header: parse(header)?,
+            parent_header: parse(parent_header)?,
+            metadata: parse(metadata)?,
+            content: parse(content)?,
+        })
+    }
+    fn encode(&self, message: &Message) -> ZmqMessage {
+        let parts = [
+            &message.header,
+            &message.parent_header,
+            &message.metadata,
+            &message.content,
+        ]
+        .map(|part| Bytes::from(part.to_string()));
+        let signature = self.sign([&parts[0], &parts[1], &parts[2], &parts[3]]);
+        let mut frames = message.identities.clone();
+        frames.push(Bytes::from_static(DELIMITER));
+        frames.push(Bytes::from(signature));
+        frames.extend(parts);
+        ZmqMessage::try_from(frames).expect("message should have frames")
+    }
+    /// Create a message in response to another
+    fn reply(&self, parent: &Message, msg_type: &str, content: Json) -> Message {
+        let date = DateTime::from_epoch(now())
+            .map(|dt| {
+                format!(
+                    "{:04}-{:02}-{:02}T{:02}:{:02}:{:06.3}Z",
+                    dt.year, dt.month, dt.day, dt.hour, dt.minute, dt.second
+                )
+            })
+            .unwrap_or_default();
+        Message {
+            identities: parent.identities.clone(),
+            header: json!({
+                "msg_id": new_id(),
+                "session": self.id,
+                "username": "kernel",
+                "date": date,
+                "msg_type": msg_type,
+                "version": PROTOCOL_VERSION,
+            }),
+            parent_header: parent.header.clone(),
+            metadata: json!({}),
+            content,
+        }
+    }
+}
+
+fn new_id() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
+
+fn now() -> f64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+fn zmq_error(e: zeromq::ZmqError) -> String {
+    e.to_string()
+}
+
+async fn serve(info: ConnectionInfo) -> Result<(), String> {
+    let session = Arc::new(Session::new(&info.key, &info.signature_scheme)?);
+    let endpoint = |port: u16| format!("{}://{}:{}", info.transport, info.ip, port);
+    let mut shell = RouterSocket::new();
+    shell
+        .bind(&endpoint(info.shell_port))
+        .await
+        .map_err(zmq_error)?;
+    let mut control = RouterSocket::new();
+    control
+        .bind(&endpoint(info.control_port))
+        .await
+        .map_err(zmq_error)?;
+    let mut iopub = PubSocket::new();
+    iopub
+        .bind(&endpoint(info.iopub_port))
+        .await
+        .map_err(zmq_error)?;
+    // Input requests are not supported, but frontends expect to be able to connect
+    let mut stdin = RouterSocket::new();
+    stdin
+        .bind(&endpoint(info.stdin_port))
+        .await
+        .map_err(zmq_error)?;
+    let mut heartbeat = RepSocket::new();
+    heartbeat
+        .bind(&endpoint(info.hb_port))
+        .await
+        .map_err(zmq_error)?;
+
+    tokio::spawn(async move {
+        while let Ok(message) = heartbeat.recv().await {
+            if heartbeat.send(message).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let executor = Executor::start();
+    let cancel = executor.cancel.clone();
+    let running = executor.running.clone();
+    let control_session = session.clone();
+    tokio::spawn(async move {
+        while let Ok(message) = control.recv().await {
+            let Ok(request) = control_session.decode(message) else {
+                continue;
+            };
+            let content = match request.msg_type() {
+                "kernel_info_request" => kernel_info(),
+                "interrupt_request" => {
+                    // Cancelling while idle would cancel the next cell instead
+                    if running.load(atomic::Ordering::Relaxed) {
+                        cancel.cancel();
+                    }
+                    json!({"status": "ok"})
+                }
+                "shutdown_request" => request.content.clone(),
+                _ => continue,
+            };
+            let msg_type = request.msg_type().replace("_request", "_reply");
+            let reply = control_session.reply(&request, &msg_type, content);
+            _ = control.send(control_session.encode(&reply)).await;
+            if request.msg_type() == "shutdown_request" {
+                std::process::exit(0);
+            }
+        }
+    });
+
+    let mut kernel = Kernel {
+        session,
+        shell,
+        iopub,
+        executor,
+        execution_count: 0,
+    };
+    loop {
+        let message = kernel.shell.recv().await.map_err(zmq_error)?;
+        let Ok(request) = kernel.session.decode(message) else {
+            continue;
+        };
+        kernel
+            .publish(&request, "status", json!({"execution_state": "busy"}))
+            .await?;
+        kernel.handle(&request).await?;
+        kernel
+            .publish(&request, "status", json!({"execution_state": "idle"}))
+            .await?;
+    }
+}
+
+fn kernel_info() -> Json {
+    json!({
+        "status": "ok",
+        "protocol_version": PROTOCOL_VERSION,
+        "implementation": "uiua",
+        "implementation_version": crate::VERSION,
+        "language_info": {
+            "name": "uiua",
+            "version": crate::VERSION,
+            "mimetype": "text/x-uiua",
+            "file_extension": ".ua",
+        },
+        "banner": format!("Uiua {}", crate::VERSION),
+        "help_links": [{"text": "Uiua documentation", "url": "https://uiua.org/docs"}],
+    })
+}
+
+struct Kernel {
+    session: Arc<Session>,
+    shell: RouterSocket,
+    iopub: PubSocket,
+    executor: Executor,
+    execution_count: usize,
+}
+
+impl Kernel {
+    async fn publish(
+        &mut self,
+        parent: &Message,
+        msg_type: &str,
+        content: Json,
+    ) -> Result<(), String> {
+        let mut message = self.session.reply(parent, msg_type, content);
+        message.identities = vec![Bytes::from(msg_type.to_string())];
+        let message = self.session.encode(&message);
+        self.iopub.send(message).await.map_err(zmq_error)
+    }
+    async fn reply(&mut self, request: &Message, content: Json) -> Result<(), String> {
+        let msg_type = request.msg_type().replace("_request", "_reply");
+        let reply = self.session.reply(request, &msg_type, content);
+        let reply = self.session.encode(&reply);
+        self.shell.send(reply).await.map_err(zmq_error)
+    }
+    async fn handle(&mut self, request: &Message) -> Result<(), String> {
+        let content = &request.content;
+        match request.msg_type() {
+            "kernel_info_request" => self.reply(request, kernel_info()).await,
+            "execute_request" => self.execute(request).await,
+            "inspect_request" => {
+                let code = content["code"].as_str().unwrap_or_default().to_string();
+                let cursor = content["cursor_pos"].as_u64().unwrap_or_default() as usize;
+                let markdown = match self.executor.call(Job::Inspect(code, cursor)).await {
+                    Event::Inspected(markdown) => markdown,
+                    _ => None,
+                };
+                let data = match &markdown {
+                    Some(md) => json!({"text/markdown": md, "text/plain": md}),
+                    None => json!({}),
+                };
+                let reply = json!({
+                    "status": "ok",
+                    "found": markdown.is_some(),
+                    "data": data,
+                    "metadata": {},
+                });
+                self.reply(request, reply).await
+            }
+            "complete_request" => {
+                let code = content["code"].as_str().unwrap_or_default().to_string();
+                let cursor = content["cursor_pos"].as_u64().unwrap_or_default() as usize;
+                let (matches, start, end) =
+                    match self.executor.call(Job::Complete(code, cursor)).await {
+                        Event::Completed(matches, start, end) => (matches, start, end),
+                        _ => (Vec::new(), cursor, cursor),
+                    };
+                let reply = json!({
+                    "status": "ok",
+                    "matches": matches,
+                    "cursor_start": start,
+                    "cursor_end": end,
+                    "metadata": {},
+                });
+                self.reply(request, reply).await
+            }
+            "is_complete_request" => {
+                let reply = json!({"status": "complete"});
+                self.reply(request, reply).await
+            }
+            "comm_info_request" => {
+                let reply = json!({"status": "ok", "comms": {}});
+                self.reply(request, reply).await
+            }
+            "history_request" => {
+                let reply = json!({"status": "ok", "history": []});
+                self.reply(request, reply).await
+            }
+            "shutdown_request" => {
+                self.reply(request, content.clone()).await?;
+                std::process::exit(0);
+            }
+            _ => Ok(()),
+        }
+    }
+    async fn execute(&mut self, request: &Message) -> Result<(), String> {
+        let code = request.content["code"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let silent = request.content["silent"].as_bool().unwrap_or(false);
+        let store_history = request.content["store_history"].as_bool().unwrap_or(true);
+        if !silent && store_history {
+            self.execution_count += 1;
+        }
+        let count = self.execution_count;
+        if !silent {
+            let input = json!({"code": code, "execution_count": count});
+            self.publish(request, "execute_input", input).await?;
+        }
+        self.executor.send(Job::Execute(code));
+        let result = loop {
+            match self.executor.events.recv().await {
+                Some(Event::Stream(name, text)) if !silent => {
+                    let content = json!({"name": name, "text": text});
+                    self.publish(request, "stream", content).await?;
+                }
+                Some(Event::Display(data)) if !silent => {
+                    let content = json!({"data": data, "metadata": {}, "transient": {}});
+                    self.publish(request, "display_data", content).await?;
+                }
+                Some(Event::Executed(result)) => break result,
+                Some(_) => {}
+                None => return Err("The executor stopped".into()),
+            }
+        };
+        match result {
+            Ok(mut bundles) => {
+                let top = bundles.pop();
+                if !silent {
+                    for data in bundles {
+                        let content = json!({"data": data, "metadata": {}, "transient": {}});
+                        self.publish(request, "display_data", content).await?;
+                    }
+                    if let Some(data) = top {
+                        let content = json!({
+                            "execution_count": count,
+                            "data": data,
+                            "metadata": {},
+                        });
+                        self.publish(request, "execute_result", content).await?;
+                    }
+                }
+                let reply = json!({
+                    "status": "ok",
+                    "execution_count": count,
+                    "user_expressions": {},
+                    "payload": [],
+                });
+                self.reply(request, reply).await
+            }
+            Err(report) => {
+                let mut error = json!({
+                    "ename": "UiuaError",
+                    "evalue": "",
+                    "traceback": report.lines().collect::<Vec<_>>(),
+                });
+                if !silent {
+                    self.publish(request, "error", error.clone()).await?;
+                }
+                error["status"] = "error".into();
+                error["execution_count"] = count.into();
+                self.reply(request, error).await
+            }
+        }
+    }
+}
+
+/// A request to the executor thread
+enum Job {
+    Execute(String),
+    Inspect(String, usize),
+    Complete(String, usize),
+}
+
+/// Something sent back from the executor thread
+enum Event {
+    Stream(&'static str, String),
+    Display(Json),
+    /// The MIME bundles of the values on the stack, or an error report
+    Executed(Result<Vec<Json>, String>),
+    Inspected(Option<String>),
+    Completed(Vec<String>, usize, usize),
+}
+
+/// A handle to the thread that owns the interpreter
+struct Executor {
+    jobs: Sender<Job>,
+    events: UnboundedReceiver<Event>,
+    cancel: CancelHandle,
+    running: Arc<AtomicBool>,
+}
+
+impl Executor {
+    fn start() -> Self {
+        let (job_send, job_recv) = crossbeam_channel::unbounded();
+        let (event_send, event_recv) = unbounded_channel();
+        let (cancel_send, cancel_recv) = crossbeam_channel::bounded(1);
+        let running = Arc::new(AtomicBool::new(false));
+        let thread_running = running.clone();
+        thread::spawn(move || {
+            let env = Uiua::with_backend(KernelSys {
+                events: event_send.clone(),
+            });
+            _ = cancel_send.send(env.cancel_handle());
+            let mut state = ExecutorState {
+                env,
+                compiler: Compiler::new(),
+                events: event_send,
+                running: thread_running,
+            };
+            state.run(job_recv);
+        });
+        let cancel = cancel_recv.recv().expect("executor thread should start");
+        Executor {
+            jobs: job_send,
+            events: event_recv,
+            cancel,
+            running,
+        }
+    }
+    fn send(&self, job: Job) {
+        _ = self.jobs.send(job);
+    }
+    /// Send a job and wait for its result, ignoring any output
+    async fn call(&mut self, job: Job) -> Event {
+        self.send(job);
+        loop {
+            match self.events.recv().await {
+                Some(Event::Stream(..) | Event::Display(_)) => {}
+                Some(event) => break event,
+                None => break Event::Executed(Err("The executor stopped".into())),
+            }
+        }
+    }
+}
+
+struct ExecutorState {
+    env: Uiua,
+    compiler: Compiler,
+    events: UnboundedSender<Event>,
+    running: Arc<AtomicBool>,
+}
+
+impl ExecutorState {
+    fn run(&mut self, jobs: Receiver<Job>) {
+        for job in jobs {
+            let event = match job {
+                Job::Execute(code) => {
+                    self.running.store(true, atomic::Ordering::Relaxed);
+                    let result = self.execute(&code);
+                    self.running.store(false, atomic::Ordering::Relaxed);
+                    Event::Executed(result)
+                }
+                Job::Inspect(code, cursor) => {
+                    Event::Inspected(inspect(&code, cursor, self.compiler.assembly()))
+                }
+                Job::Complete(code, cursor) => {
+                    let (matches, start, end) = complete(&code, cursor, self.compiler.assembly());
+                    Event::Completed(matches, start, end)
+                }
+            };
+            if self.events.send(event).is_err() {
+                break;
+            }
+        }
+    }
+    /// Run a cell
+    ///
+    /// Bindings are kept between cells, but the stack is cleared.
+    fn execute(&mut self, code: &str) -> Result<Vec<Json>, String> {
+        let backup = self.compiler.clone();
+        let env = &mut self.env;
+        let res = (self.compiler.load_str(code)).and_then(|comp| env.run_asm(comp.finish()));
+        for diagnostic in self.compiler.take_diagnostics() {
+            let text = format!("{}\n", diagnostic.report());
+            _ = self.events.send(Event::Stream("stderr", text));
+        }
+        let stack = self.env.take_stack();
+        match res {
+            Ok(mut asm) => {
+                asm.remove_top_level();
+                *self.compiler.assembly_mut() = asm;
+                let sample_rate = self.env.rt.backend.audio_sample_rate();
+                Ok(stack
+                    .iter()
+                    .map(|val| value_bundle(val, sample_rate))
+                    .collect())
+            }
+            Err(e) => {
+                self.compiler = backup;
+                Err(e.report().to_string())
+            }
+        }
+    }
+}
+
+/// Get Markdown documentation for the primitive or binding at the cursor
+fn inspect(code: &str, cursor: usize, asm: &Assembly) -> Option<String> {
+    let (spans, inputs) = spans(code);
+    let contains = |start: u32, end: u32| start as usize <= cursor && cursor <= end as usize;
+    let sp = (spans.iter())
+        .filter(|sp| !matches!(sp.value, SpanKind::Whitespace))
+        .find(|sp| contains(sp.span.start.char_pos, sp.span.end.char_pos))?;
+    match sp.value {
+        SpanKind::Primitive(prim) => Some(prim_doc_markdown(prim)),
+        SpanKind::Ident => {
+            let name = sp.span.as_str(&inputs, str::to_string);
+            let binding = asm.bindings.iter().rev().find(|binding| {
+                (binding.span.as_ref()).is_some_and(|span| span.as_str(&asm.inputs, |s| s == name))
+            })?;
+            let mut md = format!("`{name}`");
+            if let Some(sig) = binding.global.signature() {
+                md.push_str(&format!(" `{sig}`"));
+            }
+            if let Some(comment) = &binding.comment {
+                md.push_str("\n\n");
+                md.push_str(comment);
+            }
+            Some(md)
+        }
+        _ => None,
+    }
+}
+
+/// Get the primitive and binding names that complete the word before the cursor
+///
+/// Returns the names and the range of characters they replace.
+fn complete(code: &str, cursor: usize, asm: &Assembly) -> (Vec<String>, usize, usize) {
+    let chars: Vec<char> = code.chars().collect();
+    let cursor = cursor.min(chars.len());
+    let mut start = cursor;
+    while start > 0 && is_ident_char(chars[start - 1]) {
+        start -= 1;
+    }
+    if start > 0 && chars[start - 1] == '&' {
+        start -= 1;
+    }
+    let prefix: String = chars[start..cursor].iter().collect();
+    if prefix.is_empty() {
+        return (Vec::new(), cursor, cursor);
+    }
+    let prims = Primitive::non_deprecated().map(|prim| prim.name().to_string());
+    let bindings = (asm.bindings.iter())
+        .filter_map(|binding| binding.span.as_ref())
+        .map(|span| span.as_str(&asm.inputs, str::to_string));
+    let mut matches: Vec<String> = (prims.chain(bindings))
+        .filter(|name| name.starts_with(&prefix))
+        .collect();
+    matches.sort();
+    matches.dedup();
+    (matches, start, cursor)
+}
+
+#[cfg(any(feature = "image", feature = "gif"))]
+const MIN_AUTO_IMAGE_DIM: usize = 30;
+
+/// Get the MIME bundle for a value
+///
+/// Values that look like audio, images, or gifs are rendered as such, like on the website.
+#[allow(unused_variables)]
+fn value_bundle(value: &Value, sample_rate: u32) -> Json {
+    #[cfg(feature = "audio_encode")]
+    if value
+        .shape()
+        .last()
+        .is_some_and(|&n| n >= sample_rate as usize / 4)
+    {
+        if let Ok(bytes) = crate::value_to_wav_bytes(value, sample_rate) {
+            return audio_bundle(&bytes);
+        }
+    }
+    #[cfg(feature = "image")]
+    if let Ok(image) = crate::value_to_image(value) {
+        if image.width() >= MIN_AUTO_IMAGE_DIM as u32 && image.height() >= MIN_AUTO_IMAGE_DIM as u32
+        {
+            if let Ok(bytes) = crate::image_to_bytes(&image, image::ImageOutputFormat::Png) {
+                return image_bundle(&bytes);
+            }
+        }
+    }
+    #[cfg(feature = "gif")]
+    if let &[f, h, w] | &[f, h, w, _] = value.shape().dims() {
+        if h >= MIN_AUTO_IMAGE_DIM && w >= MIN_AUTO_IMAGE_DIM && f >= 5 {
+            if let Ok(bytes) = crate::value_to_gif_bytes(value, 16.0) {
+                return gif_bundle(&bytes);
+            }
+        }
+    }
+    json!({"text/plain": value.show()})
+}
+
+#[cfg(feature = "image")]
+fn image_bundle(png: &[u8]) -> Json {
+    json!({"image/png": base64(png), "text/plain": "<image>"})
+}
+
+fn gif_bundle(gif: &[u8]) -> Json {
+    json!({"image/gif": base64(gif), "text/plain": "<gif>"})
+}
+
+fn audio_bundle(wav: &[u8]) -> Json {
+    let html = format!(
+        "<audio controls src=\"data:audio/wav;base64,{}\"></audio>",
+        base64(wav)
+    );
+    json!({"text/html": html, "text/plain": "<audio>"})
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const CHARS: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut s = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n =
+            (chunk.iter().enumerate()).fold(0u32, |n, (i, &b)| n | ((b as u32) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                s.push(CHARS[((n >> (18 - 6 * i)) & 63) as usize] as char);
+            } else {
+                s.push('=');
+            }
+        }
+    }
+    s
+}
+
+/// A backend that sends output to the frontend
+///
+/// Everything else is done natively.
+struct KernelSys {
+    events: UnboundedSender<Event>,
+}
+
+impl KernelSys {
+    fn stream(&self, name: &'static str, s: &str) -> Result<(), String> {
+        (self.events.send(Event::Stream(name, s.into()))).map_err(|_| "The kernel stopped".into())
+    }
+    fn display(&self, data: Json) -> Result<(), String> {
+        (self.events.send(Event::Display(data))).map_err(|_| "The kernel stopped".into())
+    }
+}
+
+impl SysBackend for KernelSys {
+    fn any(&self) -> &dyn Any {
+        self
+    }
+    fn any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn print_str_stdout(&self, s: &str) -> Result<(), String> {
+        self.stream("stdout", s)
+    }
+    fn print_str_stderr(&self, s: &str) -> Result<(), String> {
+        self.stream("stderr", s)
+    }
+    fn print_str_trace(&self, s: &str) {
+        _ = self.stream("stderr", s);
+    }
+    fn scan_line_stdin(&self) -> Result<Option<String>, String> {
+        Err("Reading from stdin is not supported in the Jupyter kernel".into())
+    }
+    fn var(&self, name: &str) -> Option<String> {
+        NativeSys.var(name)
+    }
+    fn clipboard(&self) -> Result<String, String> {
+        NativeSys.clipboard()
+    }
+    fn set_clipboard(&self, contents: &str) -> Result<(), String> {
+        NativeSys.set_clipboard(contents)
+    }
+    fn random_bytes(&self, count: usize) -> Result<Vec<u8>, String> {
+        NativeSys.random_bytes(count)
+    }
+    fn file_exists(&self, path: &str) -> bool {
+        NativeSys.file_exists(path)
+    }
+    fn list_dir(&self, path: &str) -> Result<Vec<String>, String> {
+        NativeSys.list_dir(path)
+    }
+    fn is_file(&self, path: &str) -> Result<bool, String> {
+        NativeSys.is_file(path)
+    }
+    fn watch_path(&self, path: &str, timeout: Option<Duration>) -> Result<Option<String>, String> {
+        NativeSys.watch_path(path, timeout)
+    }
+    fn delete(&self, path: &str) -> Result<(), String> {
+        NativeSys.delete(path)
+    }
+    fn trash(&self, path: &str) -> Result<(), String> {
+        NativeSys.trash(path)
+    }
+    fn read(&self, handle: Handle, count: usize) -> Result<Vec<u8>, String> {
+        NativeSys.read(handle, count)
+    }
+    fn read_until(&self, handle: Handle, delim: &[u8]) -> Result<Vec<u8>, String> {
+        NativeSys.read_until(handle, delim)
+    }
+    fn write(&self, handle: Handle, contents: &[u8]) -> Result<(), String> {
+        match handle {
+            Handle::STDOUT => self.stream("stdout", &String::from_utf8_lossy(contents)),
+            Handle::STDERR => self.stream("stderr", &String::from_utf8_lossy(contents)),
+            _ => NativeSys.write(handle, contents),
+        }
+    }
+    fn create_file(&self, path: &Path) -> Result<Handle, String> {
+        NativeSys.create_file(path)
+    }
+    fn open_file(&self, path: &Path) -> Result<Handle, String> {
+        NativeSys.open_file(path)
+    }
+    fn file_read_all(&self, path: &Path) -> Result<Vec<u8>, String> {
+        NativeSys.file_read_all(path)
+    }
+    fn file_write_all(&self, path: &Path, contents: &[u8]) -> Result<(), String> {
+        NativeSys.file_write_all(path, contents)
+    }
+    fn sleep(&self, seconds: f64) -> Result<(), String> {
+        NativeSys.sleep(seconds)
+    }
+    fn now(&self) -> f64 {
+        NativeSys.now()
+    }
+    fn rng_seed(&self) -> Option<u64> {
+        NativeSys.rng_seed()
+    }
+    #[cfg(feature = "image")]
+    fn show_image(&self, image: image::DynamicImage) -> Result<(), String> {
+        let bytes = crate::image_to_bytes(&image, image::ImageOutputFormat::Png)?;
+        self.display(image_bundle(&bytes))
+    }
+    fn show_gif(&self, gif_bytes: Vec<u8>) -> Result<(), String> {
+        self.display(gif_bundle(&gif_bytes))
+    }
+    fn play_audio(&self, wave_bytes: Vec<u8>) -> Result<(), String> {
+        self.display(audio_bundle(&wave_bytes))
+    }
+    fn audio_sample_rate(&self) -> u32 {
+        NativeSys.audio_sample_rate()
+    }
+    fn set_audio_sample_rate(&self, sample_rate: u32) -> Result<(), String> {
+        NativeSys.set_audio_sample_rate(sample_rate)
+    }
+    fn stream_audio(&self, f: AudioStreamFn) -> Result<(), String> {
+        NativeSys.stream_audio(f)
+    }
+    fn tcp_listen(&self, addr: &str) -> Result<Handle, String> {
+        NativeSys.tcp_listen(addr)
+    }
+    fn tcp_accept(&self, handle: Handle) -> Result<Handle, String> {
+        NativeSys.tcp_accept(handle)
+    }
+    fn tcp_connect(&self, addr: &str) -> Result<Handle, String> {
+        NativeSys.tcp_connect(addr)
+    }
+    fn tls_connect(&self, addr: &str) -> Result<Handle, String> {
+        NativeSys.tls_connect(addr)
+    }
+    fn tcp_addr(&self, handle: Handle) -> Result<String, String> {
+        NativeSys.tcp_addr(handle)
+    }
+    fn tcp_set_non_blocking(&self, handle: Handle, non_blocking: bool) -> Result<(), String> {
+        NativeSys.tcp_set_non_blocking(handle, non_blocking)
+    }
+    fn tcp_set_read_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        NativeSys.tcp_set_read_timeout(handle, timeout)
+    }
+    fn tcp_set_write_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        NativeSys.tcp_set_write_timeout(handle, timeout)
+    }
+    fn close(&self, handle: Handle) -> Result<(), String> {
+        NativeSys.close(handle)
+    }
+    fn invoke(&self, path: &str) -> Result<(), String> {
+        NativeSys.invoke(path)
+    }
+    fn run_command_inherit(&self, command: &str, args: &[&str]) -> Result<i32, String> {
+        NativeSys.run_command_inherit(command, args)
+    }
+    fn run_command_capture(
+        &self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<(i32, String, String), String> {
+        NativeSys.run_command_capture(command, args)
+    }
+    fn run_command_stream(
+        &self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<(Handle, [Handle; 3]), String> {
+        NativeSys.run_command_stream(command, args)
+    }
+    fn wait_process(&self, handle: Handle) -> Result<i32, String> {
+        NativeSys.wait_process(handle)
+    }
+    fn kill_process(&self, handle: Handle) -> Result<(), String> {
+        NativeSys.kill_process(handle)
+    }
+    fn change_directory(&self, path: &str) -> Result<(), String> {
+        NativeSys.change_directory(path)
+    }
+    fn https_get(&self, request: &str, handle: Handle) -> Result<String, String> {
+        NativeSys.https_get(request, handle)
+    }
+    fn http_request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> Result<HttpResponse, String> {
+        NativeSys.http_request(method, url, headers, body)
+    }
+    fn ffi(
+        &self,
+        file: &str,
+        result_ty: FfiType,
+        name: &str,
+        arg_tys: &[FfiType],
+        args: &[Value],
+    ) -> Result<Value, String> {
+        NativeSys.ffi(file, result_ty, name, arg_tys, args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_encoding() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn message_round_trip() {
+        let session = Session::new("secret", "hmac-sha256").unwrap();
+        let request = Message {
+            identities: vec![Bytes::from_static(b"client")],
+            header: json!({"msg_type": "kernel_info_request"}),
+            parent_header: json!({}),
+            metadata: json!({}),
+            content: json!({}),
+        };
+        let reply = session.reply(&request, "kernel_info_reply", kernel_info());
+        let decoded = session.decode(session.encode(&reply)).unwrap();
+        assert_eq!(decoded.msg_type(), "kernel_info_reply");
+        assert_eq!(decoded.identities, request.identities);
+        assert_eq!(decoded.parent_header, request.header);
+        assert_eq!(decoded.content["implementation"], "uiua");
+
+        let other = Session::new("other", "hmac-sha256").unwrap();
+        assert!(other.decode(session.encode(&reply)).is_err());
+        // Truncated signatures are rejected
+        let mut frames = session.encode(&reply).into_vec();
+        frames[2] = frames[2].slice(..frames[2].len() - 1);
+        let truncated = ZmqMessage::try_from(frames).unwrap();
+        assert!(session.decode(truncated).is_err());
+    }
+
+    #[test]
+    fn completion_and_inspection() {
+        let mut compiler = Compiler::new();
+        compiler.load_str("# Add one\nIncr ← +1").unwrap();
+        let asm = compiler.finish();
+        let (matches, start, end) = complete("⇌ rev", 5, &asm);
+        assert_eq!((matches, start, end), (vec!["reverse".to_string()], 2, 5));
+        let (matches, _, _) = complete("In", 2, &asm);
+        assert!(matches.contains(&"Incr".to_string()));
+        let (matches, _, _) = complete("&fra", 4, &asm);
+        assert_eq!(matches, ["&frab", "&fras"]);
+        assert!(inspect("Incr 2", 2, &asm).unwrap().contains("Add one"));
+        assert!(inspect("⇌ 1_2", 0, &asm).unwrap().contains("reverse"));
+    }
+}
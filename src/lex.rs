@@ -478,6 +478,7 @@ pub enum AsciiToken {
     TripleMinus,
     Quote,
     Quote2,
+    Tilde,
 }
 
 impl fmt::Display for AsciiToken {
@@ -503,6 +504,7 @@ impl fmt::Display for AsciiToken {
             AsciiToken::TripleMinus => write!(f, "---"),
             AsciiToken::Quote => write!(f, "'"),
             AsciiToken::Quote2 => write!(f, "''"),
+            AsciiToken::Tilde => write!(f, "~"),
         }
     }
 }
@@ -651,6 +653,7 @@ impl<'a> Lexer<'a> {
                 ">" if self.next_char_exact("=") => self.end(GreaterEqual, start),
                 "!" if self.next_char_exact("=") => self.end(BangEqual, start),
                 "←" => self.end(LeftArrow, start),
+                "~" => self.end(Tilde, start),
                 // Comments
                 "#" => {
                     let mut n = 0;
@@ -1012,6 +1015,7 @@ pub fn is_ident_char(c: char) -> bool {
 }
 
 /// Whether a string is a custom glyph
+#[doc(hidden)]
 pub fn is_custom_glyph(c: &str) -> bool {
     match c.chars().count() {
         0 => false,
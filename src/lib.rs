@@ -90,6 +90,14 @@ let formatted = format_str(input, &config).unwrap().output;
 assert_eq!(formatted, "↯3_4⇡12");
 ```
 
+# Stability
+
+Uiua is not yet stable, so any release may change the public API. Changes are still made deliberately: the public items of the crate and their signatures are recorded in `public_api.txt` in the repository, and a test fails if they change without that file being updated.
+
+The [`mod@format`] module, the runtime ([`Uiua`], [`Value`], [`Array`], [`Compiler`], [`Assembly`]), and the error and diagnostic types are intended for embedders.
+Items hidden from these docs, such as the `profile` and `stand` modules and the compiled instruction types, are implementation details and may change in any release.
+The lexing and span utilities ([`lex`](fn@lex), [`spans`]) exist mainly for the website and language server, and are less stable than the rest of the API.
+
 # Features
 
 The `uiua` crate has the following noteable feature flags:
@@ -105,6 +113,13 @@ The `uiua` crate has the following noteable feature flags:
 - `invoke`: Enables the `&invk` system function
 - `trash`: Enables the `&ftr` system function
 - `raw_mode`: Enables the `&raw` system function
+- `tokio_sys`: Enables the [`TokioSys`] backend, for running Uiua inside a tokio runtime
+- `kernel`: Enables the Jupyter kernel run by `uiua kernel`
+- `capi`: Enables the `capi` module, a C API for embedding Uiua in other languages
+- `pyo3`: Builds the `uiua` Python module, with a `run` function that converts NumPy arrays to and from Uiua values
+- `arrow`: Enables the `&arrowr` and `&arroww` system functions for reading and writing Arrow IPC files
+- `parquet`: Enables the `&parquetr` and `&parquetw` system functions for reading and writing Parquet files, as well as `arrow`
+- `ndarray`: Enables conversions between [`Array`]s and [`ndarray`](https://docs.rs/ndarray) arrays
 */
 
 #![allow(clippy::single_match, clippy::needless_range_loop)]
@@ -115,7 +130,11 @@ mod array;
 mod assembly;
 pub mod ast;
 mod boxed;
+#[cfg(feature = "capi")]
+pub mod capi;
 mod check;
+#[cfg(feature = "arrow")]
+mod columnar;
 mod compile;
 mod complex;
 mod cowslice;
@@ -124,21 +143,36 @@ mod ffi;
 pub mod format;
 mod function;
 mod grid_fmt;
+#[cfg(feature = "kernel")]
+mod kernel;
 mod lex;
+mod locale;
 mod lsp;
+pub mod markdown;
 mod optimize;
+#[cfg(feature = "native_sys")]
+#[doc(hidden)]
+pub mod package;
 mod parse;
 mod primitive;
 #[doc(hidden)]
 pub mod profile;
+mod profiler;
+#[cfg(feature = "pyo3")]
+mod python;
 mod run;
 mod shape;
 #[cfg(feature = "stand")]
 #[doc(hidden)]
 pub mod stand;
 mod sys;
+mod sys_mem;
 #[cfg(feature = "native_sys")]
 mod sys_native;
+mod sys_policy;
+mod sys_record;
+#[cfg(feature = "tokio_sys")]
+mod sys_tokio;
 mod value;
 
 #[allow(unused_imports)]
@@ -152,18 +186,27 @@ pub use self::{
     function::*,
     lex::is_ident_char,
     lex::*,
+    locale::*,
     lsp::*,
     lsp::{spans, SpanKind},
     parse::{parse, ParseError},
     primitive::*,
+    profiler::{Profile, ProfileEntry},
     run::*,
     shape::*,
     sys::*,
+    sys_mem::*,
+    sys_policy::*,
+    sys_record::*,
     value::*,
 };
 
+#[cfg(feature = "kernel")]
+pub use self::kernel::{install_kernel_spec, run_kernel};
 #[cfg(feature = "native_sys")]
 pub use self::sys_native::*;
+#[cfg(feature = "tokio_sys")]
+pub use self::sys_tokio::*;
 
 pub use complex::*;
 use ecow::EcoString;
@@ -204,6 +247,169 @@ mod tests {
         }
     }
 
+    #[test]
+    fn recursion_limit() {
+        use super::*;
+        let code = "# Experimental!\n↬((|1 ×↫-1.|1)<2.) 10";
+        // There is no limit by default
+        Uiua::with_safe_sys().run_str(code).unwrap();
+        let mut env = Uiua::with_safe_sys().with_recursion_limit(5);
+        let Err(err) = env.run_str(code) else {
+            panic!("the recursion limit was not enforced");
+        };
+        assert_eq!(err.message(), "Maximum recursion depth of 5 exceeded");
+        // The error can be caught
+        let mut env = Uiua::with_safe_sys().with_recursion_limit(5);
+        env.run_str("# Experimental!\n⍣(↬((|1 ×↫-1.|1)<2.) 100000)⋅1")
+            .unwrap();
+        assert_eq!(env.take_stack(), [Value::from(1)]);
+    }
+
+    #[test]
+    fn resource_limits() {
+        use super::*;
+        let error = |mut env: Uiua, code: &str| env.run_str(code).err().map(|e| e.message());
+        let instructions = || Uiua::with_safe_sys().with_instruction_limit(2000);
+        assert_eq!(error(instructions(), "⍥(+1)10 0"), None);
+        let exceeded = Some("Maximum of 2000 instructions exceeded".into());
+        assert_eq!(error(instructions(), "⍥(+1)2000 0"), exceeded);
+        // The workers of parallel calls share the count
+        let code = "≡(⍥(+1)10) ⇡100";
+        assert_eq!(error(instructions(), code), exceeded);
+        assert_eq!(error(instructions().with_thread_pool(4), code), exceeded);
+        let memory = || Uiua::with_safe_sys().with_memory_limit(1_000_000);
+        assert_eq!(error(memory(), "⇡1000"), None);
+        let exceeded = Some("Memory limit of 1000000 bytes exceeded".into());
+        assert_eq!(error(memory(), "⇡1e6"), exceeded);
+        assert_eq!(error(memory(), "□□⇡1e6"), exceeded);
+        // Values that have been popped do not count
+        assert_eq!(error(memory(), "⍥(◌⇡1e4)1000 0"), None);
+        assert_eq!(error(memory(), "⍥(⊂⇡1e4)100 []"), exceeded);
+    }
+
+    #[test]
+    fn run_reader() {
+        use super::*;
+        use std::io::{self, Read};
+        let code = "\
+Double ← ×2
+F ← (
+  +1
+  Double
+)
+S ← $ Hello
+    $ World
+F 3
+⧻S
+[1
+ 2]";
+        let mut env = Uiua::with_safe_sys();
+        env.run_reader(code.as_bytes()).unwrap();
+        let stack: Vec<String> = env.take_stack().iter().map(Value::show).collect();
+        assert_eq!(stack, ["8", "11", "[1 2]"]);
+
+        // Items that were read in full run before a later read error
+        struct Failing<'a>(&'a [u8]);
+        impl Read for Failing<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.0.is_empty() {
+                    return Err(io::Error::new(io::ErrorKind::Other, "broken pipe"));
+                }
+                self.0.read(buf)
+            }
+        }
+        let mut env = Uiua::with_safe_sys();
+        let res = env.run_reader(Failing(b"1\n2\n"));
+        assert!(matches!(res, Err(UiuaError::Load(..))));
+        assert_eq!(env.stack().len(), 2);
+        // A multiline string waits for the next line
+        let mut env = Uiua::with_safe_sys();
+        let res = env.run_reader(Failing(b"1\n$ a\n"));
+        assert!(matches!(res, Err(UiuaError::Load(..))));
+        assert_eq!(env.stack().len(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "native_sys")]
+    fn open_reader() {
+        use super::*;
+        let mut env = Uiua::with_native_sys();
+        let handle = env.open_reader(std::io::Cursor::new("1 2\n3 4\n")).unwrap();
+        let mut read = |code: &str| {
+            env.run_str(&format!("{code} {}", handle.0)).unwrap();
+            env.pop_string().unwrap()
+        };
+        assert_eq!(read("&ru \"\\n\""), "1 2\n");
+        assert_eq!(read("&rs 2"), "3 ");
+        assert_eq!(read("&rs 10"), "4\n");
+        env.run_str(&format!("&cl {}", handle.0)).unwrap();
+        // Other backends do not support it
+        assert!(Uiua::with_safe_sys().open_reader(std::io::empty()).is_err());
+    }
+
+    #[test]
+    fn test_runner() {
+        use super::*;
+        let code = "\
+---
+⍤\"first\" ≍ 1 2
+⍤\"second\" =1 1
+⍤\"third\" =1 2 ≍ 3 4
+---
+TestPass ← ⍤\"pass\" ≍ 5 5
+TestFail ← ⍤\"fail\" ≍ 6 7
+TestArgs ← ⍤\"args\" ≍ 8
+";
+        let mut comp = Compiler::new();
+        comp.mode(RunMode::Test).load_str(code).unwrap();
+        let results = Uiua::with_safe_sys().run_asm_tests(comp.finish());
+        assert_eq!(results.passed, 2);
+        let failures: Vec<_> = (results.failures.iter())
+            .map(|failure| {
+                let mismatch = (failure.mismatch.as_ref()).map(|(a, b)| (a.show(), b.show()));
+                (failure.error.message(), mismatch)
+            })
+            .collect();
+        let mismatch = |a: &str, b: &str| Some((a.to_string(), b.to_string()));
+        assert_eq!(
+            failures,
+            [
+                ("2:1: first".to_string(), mismatch("1", "2")),
+                // The failed match is not the one that was asserted
+                ("4:1: third".to_string(), None),
+                ("7:12: fail".to_string(), mismatch("6", "7")),
+            ]
+        );
+    }
+
+    #[test]
+    fn spawn_pool() {
+        use super::*;
+        // Threads that wait on other threads must not use up the pool
+        let code = "\
+wait≡spawn(/+⇡) ⇡1000
+wait≡spawn(wait≡spawn(×2)) ↯10_10 ⇡100
+&threads 0";
+        let run = |mut env: Uiua| {
+            env.run_str(code).unwrap();
+            env.take_stack()
+        };
+        let unpooled = run(Uiua::with_safe_sys());
+        let pooled = run(Uiua::with_safe_sys().with_spawn_limit(2));
+        assert_eq!(unpooled[..2], pooled[..2]);
+        assert_eq!(unpooled[2], Value::from(f64::INFINITY));
+        assert_eq!(pooled[2], Value::from(2.0));
+        // A task waiting in recv must let other tasks run
+        let mut env = Uiua::with_safe_sys().with_spawn_limit(1);
+        env.run_str(
+            "\
+spawn(×2 recv 0)
+wait spawn(+1) 2
+wait ⊃∘(send 5) :",
+        )
+        .unwrap();
+        assert_eq!(env.take_stack(), [Value::from(3.0), Value::from(10.0)]);
+    }
     #[test]
     fn no_dbgs() {
         fn recurse_dirs(dir: &std::path::Path, f: &impl Fn(&std::path::Path)) {
@@ -234,4 +440,92 @@ mod tests {
             }
         });
     }
+
+    #[test]
+    fn memo_cache() {
+        use super::*;
+        let code = "F ← memo(+⚂)\n[F 1 F 2 F 1 F 3 F 1 F 2]";
+        let run = |mut env: Uiua| {
+            env.run_str(code).unwrap();
+            let val = env.pop("result").unwrap();
+            let Value::Num(arr) = val else {
+                panic!("expected numbers, found {val:?}")
+            };
+            arr.data.to_vec()
+        };
+        let calls = run(Uiua::with_safe_sys());
+        assert_eq!(calls[0], calls[2]);
+        assert_eq!(calls[1], calls[5]);
+        // With room for only one call, F 1 is evicted by F 2
+        let calls = run(Uiua::with_safe_sys().with_memo_limit(1));
+        assert_ne!(calls[0], calls[2]);
+        // With room for two calls, using F 1 again makes F 3 evict F 2 instead
+        let calls = run(Uiua::with_safe_sys().with_memo_limit(2));
+        assert_eq!(calls[0], calls[2]);
+        assert_eq!(calls[0], calls[4]);
+        assert_ne!(calls[1], calls[5]);
+    }
+
+    #[test]
+    #[cfg(feature = "native_sys")]
+    fn memo_file() {
+        use super::*;
+        let code = "F ← memo(+⚂)\nF 1";
+        let path = std::env::temp_dir().join(format!("uiua-memo-{}.uval", std::process::id()));
+        let mut env = Uiua::with_native_sys();
+        env.run_str(code).unwrap();
+        let saved = env.take_stack();
+        env.save_memo(&path).unwrap();
+        let mut env = Uiua::with_native_sys();
+        env.load_memo(&path).unwrap();
+        env.run_str(code).unwrap();
+        assert_eq!(env.take_stack(), saved);
+        // A different program does not use the saved results
+        let mut env = Uiua::with_native_sys();
+        env.load_memo(&path).unwrap();
+        env.run_str("F ← memo(+⚂)\nF 1\n2").unwrap();
+        assert_ne!(env.take_stack()[0], saved[0]);
+        _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn load_compiled() {
+        use super::*;
+        let code = "Sq ← ×.\n[Sq 3 Sq 4]\n{\"ab\" 1_2}\nℂ1 2\n⍜⇌(↘1)⇡4\n⍥(+1)3 0\n°⊟ 1_2";
+        let asm = Compiler::new().load_str(code).unwrap().finish();
+        let bytes = asm.to_uasm();
+        assert!(!bytes[6..].starts_with(b"{"));
+        let mut env = Uiua::with_safe_sys();
+        env.load_compiled(&bytes).unwrap();
+        let loaded = env.take_stack();
+        env.run_str(code).unwrap();
+        assert_eq!(loaded, env.take_stack());
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = wrong_version[4].wrapping_add(1);
+        assert!(env.load_compiled(&wrong_version).is_err());
+        assert!(env.load_compiled(b"[1 2 3]").is_err());
+    }
+
+    #[test]
+    fn thread_pool() {
+        use super::*;
+        let code = "\
+            ≡/+ ↯100_10 ⇡1000\n\
+            ∵(×2) ↯10_10 ⇡100\n\
+            ≡⊂ ⇡50 ⇡50\n\
+            ≡(⊃+× 1) ⇡20\n\
+            ⊞(⊂+1) ⇡40 ⇡40\n\
+            ≡(>0.5 ⚂◌) ⇡20";
+        let run = |mut env: Uiua| {
+            env.run_str(code).unwrap();
+            env.take_stack()
+        };
+        let sequential = run(Uiua::with_safe_sys());
+        let parallel = run(Uiua::with_safe_sys().with_thread_pool(4));
+        let len = sequential.len();
+        // The last line is random, so only compare its shape
+        assert_eq!(sequential[..len - 1], parallel[..len - 1]);
+        assert_eq!(sequential[len - 1].shape(), parallel[len - 1].shape());
+    }
 }
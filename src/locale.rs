@@ -0,0 +1,259 @@
+//! Localized primitive names
+//!
+//! A [`Locale`] maps localized names to primitives so that the formatter can accept them as input.
+//! Localized names are always formatted to glyphs, so formatted source stays the same in every language.
+
+use std::{cell::RefCell, collections::BTreeMap, error::Error, fmt, sync::Arc};
+
+use crate::Primitive;
+
+/// A set of localized primitive names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locale {
+    code: String,
+    names: BTreeMap<String, Primitive>,
+}
+
+/// An error encountered while loading a [`Locale`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocaleError {
+    /// A line is not of the form `localized = english`
+    InvalidLine(usize, String),
+    /// The English name does not refer to a primitive
+    UnknownPrimitive(usize, String),
+    /// A localized name is not a valid lowercase identifier
+    InvalidName(usize, String),
+    /// A localized name is used more than once
+    Duplicate(usize, String),
+    /// A localized name is the English name of a different primitive
+    EnglishCollision(usize, String, Primitive),
+}
+
+impl fmt::Display for LocaleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocaleError::InvalidLine(line, text) => {
+                write!(
+                    f,
+                    "Line {line}: expected `localized = english`, found {text:?}"
+                )
+            }
+            LocaleError::UnknownPrimitive(line, name) => {
+                write!(f, "Line {line}: unknown primitive {name:?}")
+            }
+            LocaleError::InvalidName(line, name) => {
+                write!(f, "Line {line}: {name:?} is not a valid lowercase name")
+            }
+            LocaleError::Duplicate(line, name) => {
+                write!(f, "Line {line}: {name:?} is already defined")
+            }
+            LocaleError::EnglishCollision(line, name, prim) => write!(
+                f,
+                "Line {line}: {name:?} collides with the English name of {}",
+                prim.format()
+            ),
+        }
+    }
+}
+
+impl Error for LocaleError {}
+
+static BUNDLED: &[(&str, &str)] = &[("es", include_str!("locales/es.txt"))];
+
+impl Locale {
+    /// Parse a locale from its table
+    ///
+    /// Each non-empty line that does not start with `#` must be of the form `localized = english`.
+    pub fn parse(code: impl Into<String>, table: &str) -> Result<Self, LocaleError> {
+        let mut names = BTreeMap::new();
+        for (i, line) in table.lines().enumerate() {
+            let line_num = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((localized, english)) = line.split_once('=') else {
+                return Err(LocaleError::InvalidLine(line_num, line.into()));
+            };
+            let (localized, english) = (localized.trim(), english.trim());
+            if localized.is_empty() || english.is_empty() {
+                return Err(LocaleError::InvalidLine(line_num, line.into()));
+            }
+            let Some(prim) = Primitive::non_deprecated().find(|p| p.name() == english) else {
+                return Err(LocaleError::UnknownPrimitive(line_num, english.into()));
+            };
+            if localized.chars().count() < 2
+                || !localized
+                    .chars()
+                    .all(|c| crate::is_ident_char(c) && !c.is_uppercase())
+            {
+                return Err(LocaleError::InvalidName(line_num, localized.into()));
+            }
+            if let Some(other) = Primitive::non_deprecated().find(|p| p.name() == localized) {
+                if other != prim {
+                    return Err(LocaleError::EnglishCollision(
+                        line_num,
+                        localized.into(),
+                        other,
+                    ));
+                }
+            }
+            if names.insert(localized.to_string(), prim).is_some() {
+                return Err(LocaleError::Duplicate(line_num, localized.into()));
+            }
+        }
+        Ok(Locale {
+            code: code.into(),
+            names,
+        })
+    }
+    /// Load one of the locales bundled with the interpreter
+    pub fn bundled(code: &str) -> Option<Self> {
+        let (code, table) = BUNDLED.iter().find(|(c, _)| *c == code)?;
+        Some(Locale::parse(*code, table).expect("bundled locale is invalid"))
+    }
+    /// Get the codes of all bundled locales
+    pub fn bundled_codes() -> impl Iterator<Item = &'static str> {
+        BUNDLED.iter().map(|(code, _)| *code)
+    }
+    /// Get the locale's code
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+    /// Get the primitive with the given localized name
+    pub fn primitive(&self, name: &str) -> Option<Primitive> {
+        self.names.get(name).copied()
+    }
+    /// Get the localized names of a primitive
+    pub fn names_of(&self, prim: Primitive) -> impl Iterator<Item = &str> {
+        self.names
+            .iter()
+            .filter(move |(_, p)| **p == prim)
+            .map(|(name, _)| name.as_str())
+    }
+    /// Iterate over all localized names and their primitives
+    pub fn names(&self) -> impl Iterator<Item = (&str, Primitive)> {
+        self.names.iter().map(|(name, prim)| (name.as_str(), *prim))
+    }
+}
+
+thread_local! {
+    static ACTIVE: RefCell<Option<Arc<Locale>>> = const { RefCell::new(None) };
+}
+
+/// Run a function with the given locale active
+///
+/// While a locale is active, primitive name parsing also accepts its localized names.
+pub fn with_locale<T>(locale: Option<Arc<Locale>>, f: impl FnOnce() -> T) -> T {
+    /// Restores the previous locale when dropped, even if the function panics
+    struct Restore(Option<Arc<Locale>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let prev = self.0.take();
+            ACTIVE.with(|active| *active.borrow_mut() = prev);
+        }
+    }
+    let _restore = Restore(ACTIVE.with(|active| active.replace(locale)));
+    f()
+}
+
+/// Get the currently active locale
+pub fn active_locale() -> Option<Arc<Locale>> {
+    ACTIVE.with(|active| active.borrow().clone())
+}
+
+#[test]
+fn bundled_locales_are_valid() {
+    for code in Locale::bundled_codes() {
+        Locale::bundled(code).unwrap();
+    }
+}
+
+#[test]
+fn locale_validation() {
+    assert_eq!(
+        Locale::parse("xx", "invertir = reverse\ninvertir = negate"),
+        Err(LocaleError::Duplicate(2, "invertir".into()))
+    );
+    assert_eq!(
+        Locale::parse("xx", "# comment\n\nreverse = negate"),
+        Err(LocaleError::EnglishCollision(
+            3,
+            "reverse".into(),
+            Primitive::Reverse
+        ))
+    );
+    assert_eq!(
+        Locale::parse("xx", "invertir = reversal"),
+        Err(LocaleError::UnknownPrimitive(1, "reversal".into()))
+    );
+    assert_eq!(
+        Locale::parse("xx", "invertir reverse"),
+        Err(LocaleError::InvalidLine(1, "invertir reverse".into()))
+    );
+    assert_eq!(
+        Locale::parse("xx", "Invertir = reverse"),
+        Err(LocaleError::InvalidName(1, "Invertir".into()))
+    );
+    let locale = Locale::parse("xx", "reverse = reverse\ninvertir = reverse").unwrap();
+    assert_eq!(
+        locale.names_of(Primitive::Reverse).collect::<Vec<_>>(),
+        ["invertir", "reverse"]
+    );
+}
+
+#[test]
+fn localized_formatting() {
+    use crate::format::{format_str, FormatConfig};
+    let spanish = "invertir forma [1 2 3]\nreducir sumar cada(multiplicar 2) [4 5 6]";
+    let english = "reverse shape [1 2 3]\nreduce add each(multiply 2) [4 5 6]";
+    let config = FormatConfig::default();
+    let expected = format_str(english, &config).unwrap().output;
+    let es = Arc::new(Locale::bundled("es").unwrap());
+    let config = config.with_locale(Some(es));
+    assert_eq!(format_str(spanish, &config).unwrap().output, expected);
+    assert_eq!(format_str(english, &config).unwrap().output, expected);
+}
+
+#[test]
+fn localized_names_inactive() {
+    use crate::format::{format_str, FormatConfig};
+    assert_eq!(Primitive::from_format_name("invertir"), None);
+    assert_eq!(Primitive::from_format_name("inver"), None);
+    let es = Arc::new(Locale::bundled("es").unwrap());
+    with_locale(Some(es), || {
+        assert_eq!(
+            Primitive::from_format_name("invertir"),
+            Some(Primitive::Reverse)
+        );
+        // Localized names take part in prefix matching
+        assert_eq!(
+            Primitive::from_format_name("inver"),
+            Some(Primitive::Reverse)
+        );
+        assert_eq!(
+            Primitive::from_format_name_multi("invertirtomar")
+                .unwrap()
+                .into_iter()
+                .map(|(p, _)| p)
+                .collect::<Vec<_>>(),
+            [Primitive::Reverse, Primitive::Take]
+        );
+        // English names are unaffected
+        assert_eq!(Primitive::from_format_name("rev"), Some(Primitive::Reverse));
+    });
+    assert_eq!(active_locale(), None);
+    let formatted = format_str("invertir 5", &FormatConfig::default())
+        .unwrap()
+        .output;
+    assert_eq!(formatted, "invertir 5\n");
+}
+
+#[test]
+fn locale_restored_after_panic() {
+    let es = Arc::new(Locale::bundled("es").unwrap());
+    let res = std::panic::catch_unwind(|| with_locale(Some(es), || panic!("in locale")));
+    assert!(res.is_err());
+    assert_eq!(active_locale(), None);
+    assert_eq!(Primitive::from_format_name("invertir"), None);
+}
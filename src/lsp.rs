@@ -4,7 +4,7 @@ use crate::{
     ast::{Item, Modifier, Word},
     lex::{CodeSpan, Loc, Sp},
     parse::parse,
-    InputSrc, Inputs, Primitive,
+    InputSrc, Inputs, PrimClass, Primitive,
 };
 
 /// Kinds of span in Uiua code, meant to be used in the language server or other IDE tools
@@ -59,6 +59,16 @@ impl Spanner {
                     }
                     spans.extend(self.words_spans(&binding.words));
                 }
+                Item::Import(import) => {
+                    if let Some(name) = &import.name {
+                        spans.push(name.span.clone().sp(SpanKind::Ident));
+                    }
+                    spans.push(import.tilde_span.clone().sp(SpanKind::Delimiter));
+                    spans.push(import.path.span.clone().sp(SpanKind::String));
+                    for item in &import.items {
+                        spans.push(item.span.clone().sp(SpanKind::Ident));
+                    }
+                }
             }
         }
         spans
@@ -175,13 +185,210 @@ impl Spanner {
     }
 }
 
+/// Kinds of token in Uiua code, meant to be used for syntax highlighting
+///
+/// Primitives are classified the same way the website colors them.
+/// Whitespace is not a token.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    StackFunction,
+    NoadicFunction,
+    MonadicFunction,
+    DyadicFunction,
+    TriadicFunction,
+    VariadicFunction,
+    MonadicModifier,
+    DyadicModifier,
+    TriadicModifier,
+    String,
+    Number,
+    Comment,
+    Strand,
+    Ident,
+    Label,
+    Signature,
+    Placeholder,
+    Delimiter,
+}
+
+impl TokenKind {
+    /// Get the token kind of a primitive
+    pub fn primitive(prim: Primitive) -> Self {
+        if let Some(margs) = prim.modifier_args() {
+            return match margs {
+                0 | 1 => TokenKind::MonadicModifier,
+                2 => TokenKind::DyadicModifier,
+                _ => TokenKind::TriadicModifier,
+            };
+        }
+        if prim == Primitive::Identity || prim.class() == PrimClass::Stack {
+            return TokenKind::StackFunction;
+        }
+        match prim.args() {
+            Some(0) => TokenKind::NoadicFunction,
+            Some(1) => TokenKind::MonadicFunction,
+            Some(2) => TokenKind::DyadicFunction,
+            Some(3) => TokenKind::TriadicFunction,
+            _ => TokenKind::VariadicFunction,
+        }
+    }
+    /// Get the kebab-case name of the token kind, suitable for a CSS class
+    pub fn name(&self) -> &'static str {
+        match self {
+            TokenKind::StackFunction => "stack-function",
+            TokenKind::NoadicFunction => "noadic-function",
+            TokenKind::MonadicFunction => "monadic-function",
+            TokenKind::DyadicFunction => "dyadic-function",
+            TokenKind::TriadicFunction => "triadic-function",
+            TokenKind::VariadicFunction => "variadic-function",
+            TokenKind::MonadicModifier => "monadic-modifier",
+            TokenKind::DyadicModifier => "dyadic-modifier",
+            TokenKind::TriadicModifier => "triadic-modifier",
+            TokenKind::String => "string",
+            TokenKind::Number => "number",
+            TokenKind::Comment => "comment",
+            TokenKind::Strand => "strand",
+            TokenKind::Ident => "ident",
+            TokenKind::Label => "label",
+            TokenKind::Signature => "signature",
+            TokenKind::Placeholder => "placeholder",
+            TokenKind::Delimiter => "delimiter",
+        }
+    }
+}
+
+/// Get the tokens in Uiua code and their kinds for syntax highlighting
+///
+/// The spans are in order and can be used to slice the code with [`CodeSpan::byte_range`].
+/// Code that does not parse is still highlighted as well as possible.
+pub fn highlight(input: &str) -> Vec<(CodeSpan, TokenKind)> {
+    let (spans, _) = spans(input);
+    let mut tokens: Vec<_> = (spans.into_iter())
+        .filter_map(|sp| {
+            let kind = match sp.value {
+                SpanKind::Primitive(prim) => TokenKind::primitive(prim),
+                SpanKind::String => TokenKind::String,
+                SpanKind::Number => TokenKind::Number,
+                SpanKind::Comment => TokenKind::Comment,
+                SpanKind::Strand => TokenKind::Strand,
+                SpanKind::Ident => TokenKind::Ident,
+                SpanKind::Label => TokenKind::Label,
+                SpanKind::Signature => TokenKind::Signature,
+                SpanKind::Placeholder => TokenKind::Placeholder,
+                SpanKind::Delimiter => TokenKind::Delimiter,
+                SpanKind::Whitespace => return None,
+            };
+            Some((trim_spaces(sp.span, input), kind))
+        })
+        .collect();
+    tokens.sort_by(|(a, _), (b, _)| a.cmp(b));
+    tokens
+}
+
+/// Shrink a span so that it does not include the spaces around its token
+///
+/// Some spans, like those of binding arrows, include the spaces around them.
+fn trim_spaces(mut span: CodeSpan, input: &str) -> CodeSpan {
+    let text = &input[span.byte_range()];
+    let trimmed = text.trim_start_matches([' ', '\t']);
+    let leading = (text.len() - trimmed.len()) as u32;
+    let trailing = (trimmed.len() - trimmed.trim_end_matches([' ', '\t']).len()) as u32;
+    span.start.byte_pos += leading;
+    span.start.char_pos += leading;
+    span.start.col += leading as u16;
+    span.end.byte_pos -= trailing;
+    span.end.char_pos -= trailing;
+    span.end.col -= trailing as u16;
+    span
+}
+
+/// Get Markdown documentation for a primitive
+///
+/// This includes its signature, its documentation, and the output of its examples.
+#[cfg(any(feature = "lsp", feature = "kernel"))]
+pub(crate) fn prim_doc_markdown(prim: Primitive) -> String {
+    use crate::PrimDocLine;
+    let sig = prim
+        .signature()
+        .map(|sig| format!(" {}", sig))
+        .unwrap_or_default();
+    let mut value = format!("```uiua\n{}{}\n```", prim.format(), sig);
+    let doc = prim.doc();
+    value.push_str("\n\n");
+    for frag in &doc.short {
+        doc_frag_markdown(&mut value, frag);
+    }
+    value.push_str("\n\n");
+    value.push_str(&format!(
+        "[Documentation](https://uiua.org/docs/{})",
+        prim.name()
+    ));
+    value.push_str("\n\n");
+    for line in &doc.lines {
+        match line {
+            PrimDocLine::Text(frags) => {
+                for frag in frags {
+                    doc_frag_markdown(&mut value, frag);
+                }
+                value.push('\n');
+            }
+            PrimDocLine::Example(ex) => {
+                value.push_str(&format!(
+                    "\
+```uiua
+{}
+```
+> ```
+",
+                    ex.input()
+                ));
+                match ex.output() {
+                    Ok(lines) => {
+                        for line in lines.iter().flat_map(|l| l.lines()) {
+                            value.push_str(&format!("> {line}\n"));
+                        }
+                    }
+                    Err(err) => value.push_str(&format!("> Error: {err}\n")),
+                }
+                value.push_str("> ```\n\n");
+            }
+        }
+    }
+    value
+}
+
+#[cfg(any(feature = "lsp", feature = "kernel"))]
+fn doc_frag_markdown(md: &mut String, frag: &crate::primitive::PrimDocFragment) {
+    use crate::primitive::PrimDocFragment;
+    match frag {
+        PrimDocFragment::Text(text) => md.push_str(text),
+        PrimDocFragment::Code(text) => md.push_str(&format!("`{}`", text)),
+        PrimDocFragment::Emphasis(text) => md.push_str(&format!("*{}*", text)),
+        PrimDocFragment::Strong(text) => md.push_str(&format!("**{}**", text)),
+        PrimDocFragment::Link { text, url } => md.push_str(&format!("[{}]({})", text, url)),
+        PrimDocFragment::Primitive { prim, named } => {
+            let text = if *named {
+                format!("`{}`", prim.format())
+            } else {
+                prim.to_string()
+            };
+            md.push_str(&format!(
+                "[{}](https://uiua.org/docs/{})",
+                text,
+                prim.name()
+            ))
+        }
+    }
+}
+
 #[cfg(feature = "lsp")]
 #[doc(hidden)]
 pub use server::run_language_server;
 
 #[cfg(feature = "lsp")]
 mod server {
-    use std::sync::Arc;
+    use std::{collections::HashMap, path::Path, sync::Arc};
 
     use dashmap::DashMap;
     use tower_lsp::{
@@ -193,10 +400,12 @@ mod server {
     use super::*;
 
     use crate::{
+        ast::Binding,
+        check::instrs_signature,
         format::{format_str, FormatConfig},
-        lex::Loc,
-        primitive::{PrimClass, PrimDocFragment},
-        Assembly, BindingInfo, Compiler, PrimDocLine, Uiua,
+        lex::{is_ident_char, Loc, Span},
+        primitive::PrimClass,
+        Assembly, Compiler, DiagnosticKind, Global, Ident, Instr, Signature, Uiua,
     };
 
     pub struct LspDoc {
@@ -204,23 +413,260 @@ mod server {
         pub items: Vec<Item>,
         pub spans: Vec<Sp<SpanKind>>,
         pub asm: Assembly,
+        /// The source the document was compiled as
+        pub src: InputSrc,
+        pub index: BindingIndex,
+        /// The compiler after compiling the document, used to check code in its scope
+        pub compiler: Compiler,
+        /// The diagnostics from compiling the document
+        pub diagnostics: Vec<crate::Diagnostic>,
+    }
+
+    /// An index of where bindings are used
+    ///
+    /// It is built when a document is compiled and kept until the document changes.
+    pub struct BindingIndex {
+        /// The spans of the uses of each binding, in order
+        pub uses: HashMap<usize, Vec<CodeSpan>>,
+    }
+
+    impl BindingIndex {
+        fn new(asm: &Assembly) -> Self {
+            let mut uses: HashMap<usize, Vec<CodeSpan>> = HashMap::new();
+            for (name, index) in &asm.global_references {
+                uses.entry(*index).or_default().push(name.span.clone());
+            }
+            for spans in uses.values_mut() {
+                spans.sort();
+            }
+            Self { uses }
+        }
     }
 
     impl LspDoc {
-        fn new(input: String) -> Self {
+        fn new(uri: &Url, input: String) -> Self {
             let (items, _, _) = parse(&input, InputSrc::Str(0), &mut Inputs::default());
             let spanner = Spanner::new(&input);
             let spans = spanner.items_spans(&items);
-            let compiler = &mut Compiler::new();
-            _ = compiler.load_str(&input);
-            let asm = compiler.finish();
+            let mut compiler = Compiler::new();
+            // Documents on disk are compiled as files so that their imports resolve
+            let src = if let Ok(path) = uri.to_file_path() {
+                _ = compiler.load_str_src(&input, path.as_path());
+                InputSrc::File(path.into())
+            } else {
+                _ = compiler.load_str(&input);
+                InputSrc::Str(0)
+            };
+            let asm = compiler.assembly().clone();
+            let index = BindingIndex::new(&asm);
+            let diagnostics = compiler.take_diagnostics().into_iter().collect();
             Self {
                 input,
                 items,
                 spans,
                 asm,
+                src,
+                index,
+                compiler,
+                diagnostics,
             }
         }
+        /// Get the diagnostics in this document and their spans
+        fn code_diagnostics(&self) -> impl Iterator<Item = (&crate::Diagnostic, &CodeSpan)> {
+            self.diagnostics.iter().filter_map(|diag| match &diag.span {
+                Span::Code(span) if span.src == self.src => Some((diag, span)),
+                _ => None,
+            })
+        }
+        /// Get the inferred signature of some code as if it were at the end of the document
+        fn code_signature(&self, code: &str) -> Option<Signature> {
+            let mut compiler = self.compiler.clone();
+            let start = compiler.assembly().top_slices.len();
+            compiler.load_str(code).ok()?;
+            let asm = compiler.assembly();
+            let instrs: Vec<Instr> = (asm.top_slices[start..].iter())
+                .flat_map(|&slice| asm.instrs(slice))
+                .cloned()
+                .collect();
+            instrs_signature(&instrs).ok()
+        }
+        /// Get completions for the word that ends at a position
+        ///
+        /// `before` is the text of the line before the position.
+        fn completions(&self, before: &[char], position: Position) -> Vec<CompletionItem> {
+            let mut start = before.len();
+            while start > 0 && is_ident_char(before[start - 1]) {
+                start -= 1;
+            }
+            if start > 0 && before[start - 1] == '&' {
+                start -= 1;
+            }
+            let prefix: String = before[start..].iter().collect();
+            let range = Range::new(
+                Position::new(
+                    position.line,
+                    position.character - (before.len() - start) as u32,
+                ),
+                position,
+            );
+            let item = |label: String, kind, detail: Option<String>, text: String, rank: usize| {
+                CompletionItem {
+                    sort_text: Some(format!("{rank:02} {label}")),
+                    text_edit: Some(CompletionTextEdit::Edit(TextEdit::new(range, text))),
+                    label,
+                    kind: Some(kind),
+                    detail,
+                    ..Default::default()
+                }
+            };
+            let global_item = |name: &Ident, global: &Global, rank: usize| {
+                let kind = match global {
+                    Global::Const(_) => CompletionItemKind::CONSTANT,
+                    Global::Func(_) | Global::Sig(_) => CompletionItemKind::FUNCTION,
+                    Global::Module { .. } => CompletionItemKind::MODULE,
+                };
+                let detail = global.signature().map(|sig| sig.to_string());
+                item(name.to_string(), kind, detail, name.to_string(), rank)
+            };
+            // Members of a module
+            if let Some(module) = self.module_at(&before[..start], position) {
+                let mut items: Vec<CompletionItem> = (self.compiler.imports.get(module))
+                    .into_iter()
+                    .flatten()
+                    .filter(|(name, _)| name.starts_with(&prefix))
+                    .map(|(name, &index)| global_item(name, &self.asm.bindings[index].global, 0))
+                    .collect();
+                items.sort_by(|a, b| a.label.cmp(&b.label));
+                return items;
+            }
+            if prefix.is_empty() {
+                return Vec::new();
+            }
+            let mut items = Vec::new();
+            // Bindings in scope
+            for (name, &index) in &self.compiler.scope.names {
+                if name.starts_with(&prefix) {
+                    items.push(global_item(name, &self.asm.bindings[index].global, 0));
+                }
+            }
+            // Planet notation
+            if let Some(prims) = Primitive::from_format_name_multi(&prefix)
+                .filter(|prims| prims.len() > 1)
+                .filter(|prims| {
+                    (prims.iter())
+                        .all(|(p, _)| *p == Primitive::Pop || p.class() == PrimClass::Planet)
+                })
+            {
+                // Each modifier fills one operand slot and opens its own
+                let mut slots: usize = 0;
+                let mut text = String::new();
+                for (prim, _) in prims {
+                    slots = slots.saturating_sub(1) + prim.modifier_args().unwrap_or(0);
+                    text.push_str(&prim.to_string());
+                }
+                for i in 1..=slots {
+                    text.push_str(&format!("(${i})"));
+                }
+                items.push(CompletionItem {
+                    insert_text_format: Some(InsertTextFormat::SNIPPET),
+                    ..item(prefix.clone(), CompletionItemKind::SNIPPET, None, text, 1)
+                });
+            }
+            // Primitives, ranked by class
+            for prim in Primitive::non_deprecated() {
+                if !prim.name().starts_with(&prefix) {
+                    continue;
+                }
+                let class_rank = PrimClass::all().position(|class| class == prim.class());
+                let rank = 2 + class_rank.unwrap_or(0);
+                let kind = if prim.modifier_args().is_some() {
+                    CompletionItemKind::OPERATOR
+                } else {
+                    CompletionItemKind::FUNCTION
+                };
+                let detail = format!("{} {}", prim, prim.doc().short_text());
+                items.push(item(
+                    prim.name().into(),
+                    kind,
+                    Some(detail),
+                    prim.to_string(),
+                    rank,
+                ));
+            }
+            items
+        }
+        /// Get the words that a selection covers, along with the line of the top-level item
+        /// they are in
+        ///
+        /// The selection may not cover only part of a word.
+        fn selected_words(&self, range: Range) -> Option<(&[Sp<Word>], usize)> {
+            let start = lsp_pos_to_uiua(range.start);
+            let end = lsp_pos_to_uiua(range.end);
+            if start == end {
+                return None;
+            }
+            let mut lines = Vec::new();
+            top_level_lines(&self.items, &mut lines);
+            (lines.into_iter()).find_map(|(words, line)| Some((words_in(words, start, end)?, line)))
+        }
+        /// Get the path of the module whose members are being named at a position
+        ///
+        /// This is either a module binding followed by a string,
+        /// or the item list of an import.
+        fn module_at(&self, before: &[char], position: Position) -> Option<&Path> {
+            if let [rest @ .., '"'] = before {
+                let end = rest.iter().rposition(|&c| c != ' ').map_or(0, |i| i + 1);
+                let name_start = (rest[..end].iter())
+                    .rposition(|&c| !is_ident_char(c))
+                    .map_or(0, |i| i + 1);
+                let name: Ident = rest[name_start..end].iter().copied().collect();
+                let index = *self.compiler.scope.names.get(&name)?;
+                return match &self.asm.bindings[index].global {
+                    Global::Module { module } => Some(module),
+                    _ => None,
+                };
+            }
+            let (line, col) = lsp_pos_to_uiua(position);
+            self.items.iter().find_map(|item| {
+                let Item::Import(import) = item else {
+                    return None;
+                };
+                let path = &import.path;
+                if path.span.end.line as usize != line || (path.span.end.col as usize) >= col {
+                    return None;
+                }
+                let path = Path::new(path.value.as_str());
+                (self.compiler.imports.keys())
+                    .find(|module| {
+                        module.ends_with(path)
+                            || module.ends_with("lib.ua")
+                                && module.parent().is_some_and(|dir| dir.ends_with(path))
+                    })
+                    .map(|module| module.as_path())
+            })
+        }
+        /// Get the index of the binding that is defined or used at a location in this document
+        fn binding_at(&self, line: usize, col: usize) -> Option<usize> {
+            let here = |span: &CodeSpan| span.src == self.src && span.contains_line_col(line, col);
+            (self.asm.bindings.iter())
+                .position(|binding| binding.span.as_ref().is_some_and(here))
+                .or_else(|| {
+                    (self.index.uses.iter())
+                        .find_map(|(index, spans)| spans.iter().any(here).then_some(*index))
+                })
+        }
+        /// Get the location of a span in this document or one of its imports
+        fn location(&self, uri: &Url, span: &CodeSpan) -> Option<Location> {
+            let uri = match &span.src {
+                src if src == &self.src => uri.clone(),
+                InputSrc::File(path) => Url::from_file_path(path.canonicalize().ok()?).ok()?,
+                InputSrc::Str(_) => return None,
+            };
+            Some(Location {
+                uri,
+                range: uiua_span_to_lsp(span),
+            })
+        }
     }
 
     #[doc(hidden)]
@@ -247,6 +693,66 @@ mod server {
         docs: DashMap<Url, LspDoc>,
     }
 
+    impl Backend {
+        /// Store a document and publish its diagnostics
+        async fn update_doc(&self, uri: Url, doc: LspDoc) {
+            let diagnostics = (doc.code_diagnostics())
+                .map(|(diag, span)| Diagnostic {
+                    range: uiua_span_to_lsp(span),
+                    severity: Some(match diag.kind {
+                        DiagnosticKind::Warning => DiagnosticSeverity::WARNING,
+                        DiagnosticKind::Advice => DiagnosticSeverity::INFORMATION,
+                        DiagnosticKind::Style => DiagnosticSeverity::HINT,
+                    }),
+                    source: Some("uiua".into()),
+                    message: diag.message.clone(),
+                    ..Default::default()
+                })
+                .collect();
+            self.docs.insert(uri.clone(), doc);
+            self.client
+                .publish_diagnostics(uri, diagnostics, None)
+                .await;
+        }
+        /// Get the location of the definition of the binding at a position
+        /// and the locations of all its uses
+        ///
+        /// Uses are collected from every open document, so a binding from an imported file
+        /// is also found in the documents that import it.
+        fn binding_locations(
+            &self,
+            uri: &Url,
+            position: Position,
+        ) -> Option<(Location, Vec<Location>)> {
+            let (line, col) = lsp_pos_to_uiua(position);
+            let definition = {
+                let doc = self.docs.get(uri)?;
+                let index = doc.binding_at(line, col)?;
+                doc.location(uri, doc.asm.bindings[index].span.as_ref()?)?
+            };
+            let mut uses: Vec<Location> = Vec::new();
+            for entry in self.docs.iter() {
+                let (doc_uri, doc) = entry.pair();
+                for (index, binding) in doc.asm.bindings.iter().enumerate() {
+                    let span = binding.span.as_ref();
+                    if span.and_then(|span| doc.location(doc_uri, span)).as_ref()
+                        != Some(&definition)
+                    {
+                        continue;
+                    }
+                    for span in doc.index.uses.get(&index).into_iter().flatten() {
+                        if let Some(location) = doc.location(doc_uri, span) {
+                            if !uses.contains(&location) {
+                                uses.push(location);
+                            }
+                        }
+                    }
+                }
+            }
+            Some((definition, uses))
+        }
+    }
+
     const STACK_FUNCTION_STT: SemanticTokenType = SemanticTokenType::new("stack_function");
     const NOADIC_FUNCTION_STT: SemanticTokenType = SemanticTokenType::new("noadic_function");
     const MONADIC_FUNCTION_STT: SemanticTokenType = SemanticTokenType::new("monadic_function");
@@ -300,6 +806,13 @@ mod server {
                     ),
                     rename_provider: Some(OneOf::Left(true)),
                     definition_provider: Some(OneOf::Left(true)),
+                    references_provider: Some(OneOf::Left(true)),
+                    inlay_hint_provider: Some(OneOf::Left(true)),
+                    code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                    completion_provider: Some(CompletionOptions {
+                        trigger_characters: Some(vec!["\"".into(), "&".into()]),
+                        ..Default::default()
+                    }),
                     ..Default::default()
                 },
                 ..Default::default()
@@ -313,17 +826,14 @@ mod server {
         }
 
         async fn did_open(&self, param: DidOpenTextDocumentParams) {
-            self.docs.insert(
-                param.text_document.uri,
-                LspDoc::new(param.text_document.text),
-            );
+            let doc = LspDoc::new(&param.text_document.uri, param.text_document.text);
+            self.update_doc(param.text_document.uri, doc).await;
         }
 
         async fn did_change(&self, params: DidChangeTextDocumentParams) {
-            self.docs.insert(
-                params.text_document.uri,
-                LspDoc::new(params.content_changes[0].text.clone()),
-            );
+            let text = params.content_changes[0].text.clone();
+            let doc = LspDoc::new(&params.text_document.uri, text);
+            self.update_doc(params.text_document.uri, doc).await;
         }
 
         async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
@@ -380,52 +890,7 @@ mod server {
                 }
             }
             Ok(Some(if let Some((prim, range)) = prim_range {
-                let sig = prim
-                    .signature()
-                    .map(|sig| format!(" {}", sig))
-                    .unwrap_or_default();
-                let mut value = format!("```uiua\n{}{}\n```", prim.format(), sig);
-                let doc = prim.doc();
-                value.push_str("\n\n");
-                for frag in &doc.short {
-                    doc_frag_markdown(&mut value, frag);
-                }
-                value.push_str("\n\n");
-                value.push_str(&format!(
-                    "[Documentation](https://uiua.org/docs/{})",
-                    prim.name()
-                ));
-                value.push_str("\n\n");
-                for line in &doc.lines {
-                    match line {
-                        PrimDocLine::Text(frags) => {
-                            for frag in frags {
-                                doc_frag_markdown(&mut value, frag);
-                            }
-                            value.push('\n');
-                        }
-                        PrimDocLine::Example(ex) => {
-                            value.push_str(&format!(
-                                "\
-```uiua
-{}
-```
-> ```
-",
-                                ex.input()
-                            ));
-                            match ex.output() {
-                                Ok(lines) => {
-                                    for line in lines.iter().flat_map(|l| l.lines()) {
-                                        value.push_str(&format!("> {line}\n"));
-                                    }
-                                }
-                                Err(err) => value.push_str(&format!("> Error: {err}\n")),
-                            }
-                            value.push_str("> ```\n\n");
-                        }
-                    }
-                }
+                let value = prim_doc_markdown(prim);
                 Hover {
                     contents: HoverContents::Markup(MarkupContent {
                         kind: MarkupKind::Markdown,
@@ -585,91 +1050,371 @@ mod server {
         }
 
         async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
-            let doc = if let Some(doc) = self
-                .docs
-                .get(&params.text_document_position.text_document.uri)
-            {
-                doc
-            } else {
-                return Ok(None);
-            };
-            let position = params.text_document_position.position;
-            let (line, col) = lsp_pos_to_uiua(position);
-            let mut binding: Option<(&BindingInfo, usize)> = None;
-            // Check for span in bindings
-            for (i, gb) in doc.asm.bindings.iter().enumerate() {
-                if let Some(span) = &gb.span {
-                    if span.contains_line_col(line, col) {
-                        binding = Some((gb, i));
-                        break;
-                    }
-                }
-            }
-            // Check for span in binding references
-            if binding.is_none() {
-                for (name, index) in &doc.asm.global_references {
-                    if name.span.contains_line_col(line, col) {
-                        binding = Some((&doc.asm.bindings[*index], *index));
-                        break;
-                    }
-                }
+            if params.new_name.is_empty() || !params.new_name.chars().all(is_ident_char) {
+                return Err(Error::invalid_params(format!(
+                    "{:?} is not a valid binding name",
+                    params.new_name
+                )));
             }
-            let Some((binding, index)) = binding else {
+            let uri = &params.text_document_position.text_document.uri;
+            let position = params.text_document_position.position;
+            let Some((definition, uses)) = self.binding_locations(uri, position) else {
                 return Ok(None);
             };
             // Collect edits
-            let mut edits = vec![TextEdit {
-                range: uiua_span_to_lsp(binding.span.as_ref().unwrap()),
-                new_text: params.new_name.clone(),
-            }];
-            for (name, idx) in &doc.asm.global_references {
-                if *idx == index {
-                    edits.push(TextEdit {
-                        range: uiua_span_to_lsp(&name.span),
-                        new_text: params.new_name.clone(),
-                    });
-                }
+            let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+            for location in [definition].into_iter().chain(uses) {
+                changes.entry(location.uri).or_default().push(TextEdit {
+                    range: location.range,
+                    new_text: params.new_name.clone(),
+                });
             }
             Ok(Some(WorkspaceEdit {
-                changes: Some([(params.text_document_position.text_document.uri, edits)].into()),
+                changes: Some(changes),
                 document_changes: None,
                 change_annotations: None,
             }))
         }
 
+        async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+            let uri = &params.text_document_position.text_document.uri;
+            let position = params.text_document_position.position;
+            let Some((definition, uses)) = self.binding_locations(uri, position) else {
+                return Ok(None);
+            };
+            let mut locations = Vec::with_capacity(uses.len() + 1);
+            if params.context.include_declaration {
+                locations.push(definition);
+            }
+            locations.extend(uses);
+            Ok(Some(locations))
+        }
+
         async fn goto_definition(
             &self,
             params: GotoDefinitionParams,
         ) -> Result<Option<GotoDefinitionResponse>> {
-            let doc = if let Some(doc) = self
-                .docs
-                .get(&params.text_document_position_params.text_document.uri)
-            {
-                doc
-            } else {
+            let uri = &params.text_document_position_params.text_document.uri;
+            let Some(doc) = self.docs.get(uri) else {
                 return Ok(None);
             };
-            let position = params.text_document_position_params.position;
-            let (line, col) = lsp_pos_to_uiua(position);
+            let (line, col) = lsp_pos_to_uiua(params.text_document_position_params.position);
             for (name, idx) in &doc.asm.global_references {
-                if name.span.contains_line_col(line, col) {
+                if name.span.src == doc.src && name.span.contains_line_col(line, col) {
                     let binding = &doc.asm.bindings[*idx];
-                    if let Some(span) = &binding.span {
-                        return Ok(Some(GotoDefinitionResponse::Scalar(Location {
-                            uri: params.text_document_position_params.text_document.uri,
-                            range: uiua_span_to_lsp(span),
-                        })));
+                    if let Some(location) =
+                        (binding.span.as_ref()).and_then(|span| doc.location(uri, span))
+                    {
+                        return Ok(Some(GotoDefinitionResponse::Scalar(location)));
                     }
                 }
             }
             Ok(None)
         }
 
+        async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+            let uri = &params.text_document_position.text_document.uri;
+            let Some(doc) = self.docs.get(uri) else {
+                return Ok(None);
+            };
+            let position = params.text_document_position.position;
+            let line = doc.input.lines().nth(position.line as usize);
+            let before: Vec<char> = (line.unwrap_or_default().chars())
+                .take(position.character as usize)
+                .collect();
+            let items = doc.completions(&before, position);
+            Ok(Some(CompletionResponse::Array(items)))
+        }
+
+        async fn code_action(
+            &self,
+            params: CodeActionParams,
+        ) -> Result<Option<CodeActionResponse>> {
+            let uri = &params.text_document.uri;
+            let Some(doc) = self.docs.get(uri) else {
+                return Ok(None);
+            };
+            let action = |title: String, kind: CodeActionKind, edits: Vec<TextEdit>| {
+                CodeActionOrCommand::CodeAction(CodeAction {
+                    title,
+                    kind: Some(kind),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some([(uri.clone(), edits)].into()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+            };
+            let mut actions = Vec::new();
+            // Fixes for diagnostics
+            for (diag, span) in doc.code_diagnostics() {
+                let Some(fix) = &diag.fix else {
+                    continue;
+                };
+                let range = uiua_span_to_lsp(span);
+                if range.end < params.range.start || params.range.end < range.start {
+                    continue;
+                }
+                let replace = if fix.value.is_empty() {
+                    "Remove".into()
+                } else {
+                    format!("Replace with `{}`", fix.value)
+                };
+                let edit = TextEdit::new(uiua_span_to_lsp(&fix.span), fix.value.clone());
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: replace,
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some([(uri.clone(), vec![edit])].into()),
+                        ..Default::default()
+                    }),
+                    is_preferred: Some(true),
+                    ..Default::default()
+                }));
+            }
+            // Actions on the selected words
+            if let Some((words, top_line)) = doc.selected_words(params.range) {
+                let span = (words[0].span.clone()).merge(words.last().unwrap().span.clone());
+                let range = uiua_span_to_lsp(&span);
+                let text = &doc.input[span.byte_range()];
+                let operand = if words.iter().filter(|word| word.value.is_code()).count() == 1 {
+                    text.to_string()
+                } else {
+                    format!("({text})")
+                };
+                // Extract binding
+                let name = (["Extracted".to_string()].into_iter())
+                    .chain(('B'..='Z').map(|c| format!("Extracted{c}")))
+                    .find(|name| !doc.compiler.scope.names.contains_key(name.as_str()))
+                    .unwrap_or_default();
+                let binding = format_snippet(&format!("{name} ← {text}"));
+                let above = Position::new(top_line as u32 - 1, 0);
+                actions.push(action(
+                    format!("Extract to binding `{name}`"),
+                    CodeActionKind::REFACTOR_EXTRACT,
+                    vec![
+                        TextEdit::new(Range::new(above, above), format!("{binding}\n")),
+                        TextEdit::new(range, name),
+                    ],
+                ));
+                // Wrap in modifiers
+                for (prim, rest) in [
+                    (Primitive::Un, ""),
+                    (Primitive::Under, "()"),
+                    (Primitive::Dip, ""),
+                    (Primitive::Rows, ""),
+                    (Primitive::Each, ""),
+                ] {
+                    let wrapped = format_snippet(&format!("{prim}{operand}{rest}"));
+                    actions.push(action(
+                        format!("Wrap in {} {prim}", prim.name()),
+                        CodeActionKind::REFACTOR_REWRITE,
+                        vec![TextEdit::new(range, wrapped)],
+                    ));
+                }
+            }
+            // Inline binding
+            let (line, col) = lsp_pos_to_uiua(params.range.start);
+            if let Some(index) = doc.binding_at(line, col) {
+                let info = &doc.asm.bindings[index];
+                let mut bindings = Vec::new();
+                collect_bindings(&doc.items, &mut bindings);
+                let binding = (info.span.as_ref())
+                    .filter(|span| span.src == doc.src)
+                    .and_then(|span| {
+                        bindings.into_iter().find(|binding| {
+                            let name = &binding.name.span;
+                            (name.start.line, name.start.col) == (span.start.line, span.start.col)
+                        })
+                    })
+                    .filter(|binding| !binding.name.value.ends_with('!'));
+                let code_words: Vec<&Sp<Word>> = (binding.iter())
+                    .flat_map(|binding| &binding.words)
+                    .filter(|word| word.value.is_code())
+                    .collect();
+                if let (Some(binding), Some(first), Some(last)) =
+                    (binding, code_words.first(), code_words.last())
+                {
+                    let body = &doc.input
+                        [first.span.start.byte_pos as usize..last.span.end.byte_pos as usize];
+                    let replacement = match &binding.signature {
+                        Some(sig) => format!("({} {body})", &doc.input[sig.span.byte_range()]),
+                        None if code_words.len() == 1 => body.into(),
+                        None => format!("({body})"),
+                    };
+                    let replacement = format_snippet(&replacement);
+                    let start = binding.name.span.start.line as u32 - 1;
+                    let end = last.span.end.line as u32;
+                    let mut edits = vec![TextEdit::new(
+                        Range::new(Position::new(start, 0), Position::new(end, 0)),
+                        String::new(),
+                    )];
+                    for span in doc.index.uses.get(&index).into_iter().flatten() {
+                        if span.src == doc.src {
+                            edits.push(TextEdit::new(uiua_span_to_lsp(span), replacement.clone()));
+                        }
+                    }
+                    actions.push(action(
+                        format!("Inline binding `{}`", binding.name.value),
+                        CodeActionKind::REFACTOR_INLINE,
+                        edits,
+                    ));
+                }
+            }
+            Ok(Some(actions))
+        }
+
+        async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+            let Some(doc) = self.docs.get(&params.text_document.uri) else {
+                return Ok(None);
+            };
+            let range = params.range;
+            let mut hints = Vec::new();
+            // Signatures of bindings without declared signatures
+            let mut bindings = Vec::new();
+            collect_bindings(&doc.items, &mut bindings);
+            for binding in bindings {
+                if binding.signature.is_some() {
+                    continue;
+                }
+                let name = &binding.name.span;
+                let Some(sig) = (doc.asm.bindings.iter())
+                    .find(|info| {
+                        (info.span.as_ref()).is_some_and(|span| {
+                            span.src == doc.src
+                                && (span.start.line, span.start.col)
+                                    == (name.start.line, name.start.col)
+                        })
+                    })
+                    .and_then(|info| info.global.signature())
+                else {
+                    continue;
+                };
+                let end = (binding.words.iter().rev())
+                    .find(|word| word.value.is_code())
+                    .map_or(binding.arrow_span.end, |word| word.span.end);
+                let position = uiua_loc_to_lsp(end);
+                if range.start <= position && position <= range.end {
+                    hints.push(signature_hint(position, sig));
+                }
+            }
+            // The signature of a selected expression
+            if range.start.line == range.end.line && range.start != range.end {
+                let selected: Option<String> = (doc.input.lines().nth(range.start.line as usize))
+                    .map(|line| {
+                        let start = range.start.character as usize;
+                        let end = range.end.character as usize;
+                        line.chars().skip(start).take(end - start).collect()
+                    });
+                if let Some(sig) = selected.and_then(|code| doc.code_signature(&code)) {
+                    hints.push(signature_hint(range.end, sig));
+                }
+            }
+            Ok(Some(hints))
+        }
+
         async fn shutdown(&self) -> Result<()> {
             Ok(())
         }
     }
 
+    fn collect_bindings<'a>(items: &'a [Item], bindings: &mut Vec<&'a Binding>) {
+        for item in items {
+            match item {
+                Item::Binding(binding) => bindings.push(binding),
+                Item::TestScope(items) => collect_bindings(&items.value, bindings),
+                _ => {}
+            }
+        }
+    }
+
+    /// Collect the lines of words of top-level items along with the line each item starts on
+    fn top_level_lines<'a>(items: &'a [Item], lines: &mut Vec<(&'a [Sp<Word>], usize)>) {
+        for item in items {
+            match item {
+                Item::Words(words) => {
+                    for line in words {
+                        if let Some(first) = line.first() {
+                            lines.push((line, first.span.start.line as usize));
+                        }
+                    }
+                }
+                Item::Binding(binding) => {
+                    lines.push((&binding.words, binding.name.span.start.line as usize))
+                }
+                Item::TestScope(items) => top_level_lines(&items.value, lines),
+                Item::Import(_) => {}
+            }
+        }
+    }
+
+    /// Get the words in a line that are between two locations
+    ///
+    /// If a single word contains both locations, its inner words are searched instead.
+    fn words_in(
+        words: &[Sp<Word>],
+        start: (usize, usize),
+        end: (usize, usize),
+    ) -> Option<&[Sp<Word>]> {
+        let mut covered = None;
+        for (i, word) in words.iter().enumerate() {
+            if !word.value.is_code() {
+                continue;
+            }
+            let word_start = (word.span.start.line as usize, word.span.start.col as usize);
+            let word_end = (word.span.end.line as usize, word.span.end.col as usize);
+            if word_end <= start || end <= word_start {
+                continue;
+            }
+            if start <= word_start && word_end <= end {
+                covered = Some(covered.map_or((i, i), |(first, _)| (first, i)));
+            } else if covered.is_none() && word_start <= start && end <= word_end {
+                let inner: Vec<&[Sp<Word>]> = match &word.value {
+                    Word::Func(func) => func.lines.iter().map(Vec::as_slice).collect(),
+                    Word::Array(arr) => arr.lines.iter().map(Vec::as_slice).collect(),
+                    Word::Switch(sw) => (sw.branches.iter())
+                        .flat_map(|branch| &branch.value.lines)
+                        .map(Vec::as_slice)
+                        .collect(),
+                    Word::Modified(m) => vec![m.operands.as_slice()],
+                    Word::Strand(items) => vec![items.as_slice()],
+                    _ => Vec::new(),
+                };
+                return (inner.into_iter()).find_map(|words| words_in(words, start, end));
+            } else {
+                return None;
+            }
+        }
+        covered.map(|(first, last)| &words[first..=last])
+    }
+
+    /// Format a generated piece of code
+    ///
+    /// If the code cannot be formatted, it is returned as is.
+    fn format_snippet(code: &str) -> String {
+        let config = FormatConfig {
+            backend: Arc::new(crate::NativeSys),
+            ..FormatConfig::find().unwrap_or_default()
+        };
+        match format_str(code, &config) {
+            Ok(formatted) => formatted.output.trim_end().into(),
+            Err(_) => code.into(),
+        }
+    }
+
+    fn signature_hint(position: Position, sig: Signature) -> InlayHint {
+        InlayHint {
+            position,
+            label: InlayHintLabel::String(sig.to_string()),
+            kind: Some(InlayHintKind::TYPE),
+            text_edits: None,
+            tooltip: None,
+            padding_left: Some(true),
+            padding_right: None,
+            data: None,
+        }
+    }
+
     fn lsp_pos_to_uiua(pos: Position) -> (usize, usize) {
         (pos.line as usize + 1, pos.character as usize + 1)
     }
@@ -685,26 +1430,26 @@ mod server {
     fn uiua_span_to_lsp(span: &CodeSpan) -> Range {
         uiua_locs_to_lsp(span.start, span.end)
     }
+}
 
-    fn doc_frag_markdown(md: &mut String, frag: &PrimDocFragment) {
-        match frag {
-            PrimDocFragment::Text(text) => md.push_str(text),
-            PrimDocFragment::Code(text) => md.push_str(&format!("`{}`", text)),
-            PrimDocFragment::Emphasis(text) => md.push_str(&format!("*{}*", text)),
-            PrimDocFragment::Strong(text) => md.push_str(&format!("**{}**", text)),
-            PrimDocFragment::Link { text, url } => md.push_str(&format!("[{}]({})", text, url)),
-            PrimDocFragment::Primitive { prim, named } => {
-                let text = if *named {
-                    format!("`{}`", prim.format())
-                } else {
-                    prim.to_string()
-                };
-                md.push_str(&format!(
-                    "[{}](https://uiua.org/docs/{})",
-                    text,
-                    prim.name()
-                ))
-            }
-        }
-    }
+#[test]
+fn highlight_tokens() {
+    use TokenKind::*;
+    let input = "F ← ≡⇌+1 \"a\" # c";
+    let tokens: Vec<_> = (highlight(input).into_iter())
+        .map(|(span, kind)| (&input[span.byte_range()], kind))
+        .collect();
+    assert_eq!(
+        tokens,
+        [
+            ("F", Ident),
+            ("←", Delimiter),
+            ("≡", MonadicModifier),
+            ("⇌", MonadicFunction),
+            ("+", DyadicFunction),
+            ("1", Number),
+            ("\"a\"", String),
+            ("# c", Comment),
+        ]
+    );
 }
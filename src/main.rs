@@ -2,6 +2,7 @@
 compile_error!("To compile the uiua interpreter binary, you must enable the `binary` feature flag");
 
 use std::{
+    collections::{BTreeSet, HashMap},
     env, fmt, fs,
     io::{self, stderr, Write},
     path::{Path, PathBuf},
@@ -19,8 +20,10 @@ use once_cell::sync::Lazy;
 use parking_lot::Mutex;
 use rustyline::{error::ReadlineError, DefaultEditor};
 use uiua::{
-    format::{format_file, format_str, FormatConfig, FormatConfigSource},
-    spans, Assembly, Compiler, NativeSys, PrimClass, RunMode, SpanKind, Uiua, UiuaError,
+    format::{fix_file, format_file, format_str, FormatConfig, FormatConfigSource},
+    markdown::{run_markdown_file, MarkdownStyle},
+    package, spans, CompileCache, Compiler, DebugAction, DebugPause, Debugger, Diagnostic, Lint,
+    LintLevel, NativeSys, PrimClass, RecordSys, ReplaySys, RunMode, SpanKind, Uiua, UiuaError,
     UiuaResult, Value,
 };
 
@@ -77,6 +80,7 @@ fn run() -> UiuaResult {
             }
             App::Fmt {
                 path,
+                fix,
                 formatter_options,
             } => {
                 let config = FormatConfig::from_source(
@@ -85,18 +89,47 @@ fn run() -> UiuaResult {
                 )?;
 
                 if let Some(path) = path {
-                    format_single_file(path, &config, formatter_options.stdout)?;
+                    format_single_file(path, &config, formatter_options.stdout, fix)?;
                 } else {
-                    format_multi_files(&config, formatter_options.stdout)?;
+                    format_multi_files(&config, formatter_options.stdout, fix)?;
                 }
             }
+            App::Md { path, html, check } => {
+                let style = if html {
+                    MarkdownStyle::Html
+                } else {
+                    MarkdownStyle::Comments
+                };
+                let output = run_markdown_file(&path, style, check)?;
+                for (line, error) in &output.errors {
+                    eprintln!("Code block at {}:{line} failed", path.display());
+                    eprintln!("{}", error.report());
+                }
+                let stale = check && output.output != fs::read_to_string(&path).unwrap_or_default();
+                if stale {
+                    eprintln!("The outputs in {} are out of date", path.display());
+                }
+                if !output.errors.is_empty() || stale {
+                    exit(1);
+                }
+                println!("Ran {} code blocks", output.blocks);
+            }
             App::Run {
                 path,
                 no_format,
                 no_color,
                 formatter_options,
                 time_instrs,
+                profile,
+                threads,
+                memo_file,
+                memo_limit,
                 mode,
+                message_format,
+                lint_options,
+                explain_signatures,
+                record,
+                replay,
                 #[cfg(feature = "audio")]
                 audio_options,
                 args,
@@ -114,42 +147,97 @@ fn run() -> UiuaResult {
                 };
                 #[cfg(feature = "audio")]
                 setup_audio(audio_options);
-                let mut rt = Uiua::with_native_sys()
-                    .with_file_path(&path)
-                    .with_args(args)
-                    .time_instrs(time_instrs);
-                if path.extension().is_some_and(|ext| ext == "uasm") {
-                    let json = match fs::read_to_string(&path) {
+                let rt = if let Some(replay) = &replay {
+                    let json = match fs::read_to_string(replay) {
                         Ok(json) => json,
                         Err(e) => {
-                            eprintln!("Failed to read assembly: {e}");
+                            eprintln!("Failed to read recording: {e}");
                             return Ok(());
                         }
                     };
-                    let assembly = match serde_json::from_str::<Assembly>(&json) {
-                        Ok(assembly) => assembly,
+                    match ReplaySys::wrap(NativeSys, &json) {
+                        Ok(backend) => Uiua::with_backend(backend),
                         Err(e) => {
-                            eprintln!("Failed to parse assembly: {e}");
+                            eprintln!("Failed to load recording: {e}");
+                            return Ok(());
+                        }
+                    }
+                } else if record.is_some() {
+                    Uiua::with_backend(RecordSys::new(NativeSys))
+                } else {
+                    Uiua::with_native_sys()
+                };
+                let mut rt = rt
+                    .with_file_path(&path)
+                    .with_args(args)
+                    .time_instrs(time_instrs)
+                    .profile(profile);
+                if let Some(threads) = threads {
+                    rt = rt.with_thread_pool(threads);
+                }
+                if let Some(limit) = memo_limit {
+                    rt = rt.with_memo_limit(limit);
+                }
+                if let Some(memo_file) = memo_file.as_ref().filter(|path| path.exists()) {
+                    if let Err(e) = rt.load_memo(memo_file) {
+                        eprintln!("Failed to load memo file: {}", e.message());
+                    }
+                }
+                let json = message_format == MessageFormat::Json;
+                let mut comp = Compiler::with_backend(NativeSys);
+                comp.mode(mode.unwrap_or(RunMode::Normal))
+                    .print_diagnostics(!json)
+                    .explain_signatures(explain_signatures);
+                lint_options.apply(&mut comp);
+                let res = if path.extension().is_some_and(|ext| ext == "uasm") {
+                    let bytes = match fs::read(&path) {
+                        Ok(bytes) => bytes,
+                        Err(e) => {
+                            eprintln!("Failed to read assembly: {e}");
                             return Ok(());
                         }
                     };
-                    rt.run_asm(assembly)?;
+                    rt.load_compiled(&bytes).map(drop)
                 } else {
-                    if !no_format {
-                        let config = FormatConfig::from_source(
-                            formatter_options.format_config_source,
-                            Some(&path),
-                        )?;
-                        format_file(&path, &config, false)?;
+                    (|| -> UiuaResult {
+                        if !no_format {
+                            let config = FormatConfig::from_source(
+                                formatter_options.format_config_source,
+                                Some(&path),
+                            )?;
+                            format_file(&path, &config, false)?;
+                        }
+                        let asm = comp.load_file(&path)?.finish();
+                        rt.run_asm(asm).map(drop)
+                    })()
+                };
+                write_profile(&mut rt, &path);
+                if let Some(record) = &record {
+                    let backend = rt.downcast_backend::<RecordSys<NativeSys>>().unwrap();
+                    if let Err(e) = backend.save(record) {
+                        eprintln!("Failed to save recording: {e}");
                     }
-                    let mode = mode.unwrap_or(RunMode::Normal);
-                    rt.compile_run(|comp| {
-                        comp.mode(mode).print_diagnostics(true).load_file(&path)
-                    })?;
                 }
+                if let Some(memo_file) = &memo_file {
+                    if let Err(e) = rt.save_memo(memo_file) {
+                        eprintln!("Failed to save memo file: {}", e.message());
+                    }
+                }
+                if json {
+                    print_json_messages(&comp.take_diagnostics(), res.as_ref().err());
+                    if res.is_err() {
+                        exit(1);
+                    }
+                }
+                res?;
                 print_stack(&rt.take_stack(), !no_color);
             }
-            App::Build { path, output } => {
+            App::Build {
+                path,
+                output,
+                lint_options,
+                explain_signatures,
+            } => {
                 let path = if let Some(path) = path {
                     path
                 } else {
@@ -161,21 +249,57 @@ fn run() -> UiuaResult {
                         }
                     }
                 };
-                let assembly = Compiler::with_backend(NativeSys)
-                    .print_diagnostics(true)
-                    .load_file(&path)?
-                    .finish();
+                let mut comp = Compiler::with_backend(NativeSys);
+                comp.print_diagnostics(true)
+                    .explain_signatures(explain_signatures);
+                lint_options.apply(&mut comp);
+                let assembly = comp.load_file(&path)?.finish();
                 let output = output.unwrap_or_else(|| path.with_extension("uasm"));
-                let json = match serde_json::to_string(&assembly) {
-                    Ok(json) => json,
-                    Err(e) => {
-                        eprintln!("Failed to serialize assembly: {e}");
-                        return Ok(());
+                if let Err(e) = fs::write(output, assembly.to_uasm()) {
+                    eprintln!("Failed to write assembly: {e}");
+                }
+            }
+            App::Debug {
+                path,
+                breakpoints,
+                args,
+            } => {
+                let path = if let Some(path) = path {
+                    path
+                } else {
+                    match working_file_path() {
+                        Ok(path) => path,
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            return Ok(());
+                        }
                     }
                 };
-                if let Err(e) = fs::write(output, json) {
-                    eprintln!("Failed to write assembly: {e}");
+                let mut parsed: Vec<(usize, Option<usize>)> = Vec::with_capacity(breakpoints.len());
+                for bp in &breakpoints {
+                    let (line, col) = match bp.split_once(':') {
+                        Some((line, col)) => (line, Some(col)),
+                        None => (bp.as_str(), None),
+                    };
+                    match (line.parse(), col.map(str::parse).transpose()) {
+                        (Ok(line), Ok(col)) => parsed.push((line, col)),
+                        _ => {
+                            eprintln!("Invalid breakpoint {bp:?}, expected LINE or LINE:COL");
+                            return Ok(());
+                        }
+                    }
                 }
+                let start_paused = parsed.is_empty();
+                let mut rt = Uiua::with_native_sys()
+                    .with_file_path(&path)
+                    .with_args(args)
+                    .with_debugger(CliDebugger, parsed, start_paused);
+                rt.compile_run(|comp| {
+                    comp.mode(RunMode::Normal)
+                        .print_diagnostics(true)
+                        .load_file(&path)
+                })?;
+                print_stack(&rt.take_stack(), true);
             }
             App::Eval {
                 code,
@@ -197,6 +321,8 @@ fn run() -> UiuaResult {
             App::Test {
                 path,
                 formatter_options,
+                lint_options,
+                explain_signatures,
             } => {
                 let path = if let Some(path) = path {
                     path
@@ -212,13 +338,48 @@ fn run() -> UiuaResult {
                 let config =
                     FormatConfig::from_source(formatter_options.format_config_source, Some(&path))?;
                 format_file(&path, &config, false)?;
+                let mut comp = Compiler::with_backend(NativeSys);
+                comp.mode(RunMode::Test)
+                    .print_diagnostics(true)
+                    .explain_signatures(explain_signatures);
+                lint_options.apply(&mut comp);
+                comp.load_file(path)?;
                 let mut rt = Uiua::with_native_sys();
-                rt.compile_run(|comp| {
-                    comp.mode(RunMode::Test)
-                        .print_diagnostics(true)
-                        .load_file(path)
-                })?;
-                println!("No failures!");
+                let results = rt.run_asm_tests(comp.finish());
+                for failure in &results.failures {
+                    eprintln!("{}", failure.error.report());
+                    if let Some((expected, actual)) = &failure.mismatch {
+                        eprintln!("{} {}", "expected:".bright_green(), expected.show());
+                        eprintln!("{} {}", "  actual:".bright_red(), actual.show());
+                    }
+                    eprintln!();
+                }
+                let failed = results.failures.len();
+                if failed == 0 {
+                    println!("{} passed, no failures!", results.passed);
+                } else {
+                    println!("{} passed, {} failed", results.passed, failed);
+                    exit(1);
+                }
+            }
+            App::Bench {
+                path,
+                iterations,
+                baseline,
+                save,
+            } => {
+                let path = if let Some(path) = path {
+                    path
+                } else {
+                    match working_file_path() {
+                        Ok(path) => path,
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            return Ok(());
+                        }
+                    }
+                };
+                bench(&path, iterations, baseline.as_deref(), save.as_deref())?;
             }
             App::Watch {
                 no_format,
@@ -242,7 +403,34 @@ fn run() -> UiuaResult {
             }
             #[cfg(feature = "lsp")]
             App::Lsp => uiua::run_language_server(),
+            #[cfg(feature = "kernel")]
+            App::Kernel {
+                connection_file,
+                install,
+            } => {
+                if install {
+                    match uiua::install_kernel_spec() {
+                        Ok(dir) => println!("Installed the kernel spec in {}", dir.display()),
+                        Err(e) => {
+                            eprintln!("Failed to install the kernel spec: {e}");
+                            exit(1);
+                        }
+                    }
+                } else if let Some(path) = connection_file {
+                    if let Err(e) = uiua::run_kernel(&path) {
+                        eprintln!("{e}");
+                        exit(1);
+                    }
+                } else {
+                    eprintln!(
+                        "Expected a connection file. \
+                         Use --install to install the kernel spec for Jupyter."
+                    );
+                    exit(1);
+                }
+            }
             App::Repl {
+                clear,
                 formatter_options,
                 #[cfg(feature = "audio")]
                 audio_options,
@@ -258,9 +446,40 @@ fn run() -> UiuaResult {
                 let rt = Uiua::with_native_sys().with_args(args);
                 let mut compiler = Compiler::new();
                 compiler.mode(RunMode::Normal).print_diagnostics(true);
-                repl(rt, compiler, true, config);
+                repl(rt, compiler, true, clear, config);
+            }
+            App::Update { modules: true, .. } => match package::update() {
+                Ok(changed) if changed.is_empty() => println!("All libraries are up to date"),
+                Ok(changed) => {
+                    for (lib, old) in changed {
+                        let (old, new) = (short_hash(&old), short_hash(&lib.commit));
+                        println!("Updated {} {old} → {new}", lib.name);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("{e}");
+                    exit(1);
+                }
+            },
+            App::Update { main, check, .. } => update(main, check),
+            App::Add { url } => {
+                let res = if let Some(url) = url {
+                    package::add(&url).map(|lib| vec![lib])
+                } else {
+                    package::install()
+                };
+                match res {
+                    Ok(libs) => {
+                        for lib in libs {
+                            println!("Installed {} at {}", lib.name, short_hash(&lib.commit));
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("{e}");
+                        exit(1);
+                    }
+                }
             }
-            App::Update { main, check } => update(main, check),
             #[cfg(feature = "stand")]
             App::Stand { main, name } => {
                 let main = main.unwrap_or_else(|| "main.ua".into());
@@ -429,7 +648,10 @@ fn watch(
         socket.set_nonblocking(true)?;
         (socket, port)
     };
-    let run = |path: &Path, stdin_file: Option<&PathBuf>| -> io::Result<()> {
+    // Compile in this process so that unchanged imports can be reused between runs
+    let mut caches: HashMap<PathBuf, CompileCache> = HashMap::new();
+    let asm_path = env::temp_dir().join(format!("uiua-watch-{}.uasm", std::process::id()));
+    let mut run = |path: &Path, stdin_file: Option<&PathBuf>| -> io::Result<()> {
         if let Some(mut child) = WATCH_CHILD.lock().take() {
             _ = child.kill();
             print_watching();
@@ -449,6 +671,18 @@ fn watch(
                         return Ok(());
                     }
                     clear_watching();
+                    let mut comp = Compiler::with_backend(NativeSys);
+                    comp.mode(RunMode::All).print_diagnostics(true);
+                    let cache = caches.entry(path.to_path_buf()).or_default();
+                    let asm = match comp.load_file_cached(path, cache) {
+                        Ok(comp) => comp.finish(),
+                        Err(e) => {
+                            println!("{}", e.report());
+                            print_watching();
+                            return Ok(());
+                        }
+                    };
+                    fs::write(&asm_path, asm.to_uasm())?;
                     #[cfg(feature = "audio")]
                     let audio_time =
                         f64::from_bits(audio_time_clone.load(std::sync::atomic::Ordering::Relaxed))
@@ -461,12 +695,10 @@ fn watch(
                     *WATCH_CHILD.lock() = Some(
                         Command::new(env::current_exe().unwrap())
                             .arg("run")
-                            .arg(path)
+                            .arg(&asm_path)
                             .args((!color).then_some("--no-color"))
                             .args([
                                 "--no-format",
-                                "--mode",
-                                "all",
                                 #[cfg(feature = "audio")]
                                 "--audio-time",
                                 #[cfg(feature = "audio")]
@@ -555,19 +787,76 @@ enum App {
         formatter_options: FormatterOptions,
         #[clap(long, help = "Emit the duration of each instruction's execution")]
         time_instrs: bool,
+        #[clap(
+            long,
+            help = "Write the time spent in each primitive and function to a .folded file \
+                    and print the locations that took the most time"
+        )]
+        profile: bool,
+        #[clap(
+            long,
+            help = "Run pure rows, each, and table functions on this many threads"
+        )]
+        threads: Option<usize>,
+        #[clap(
+            long,
+            help = "Load memo caches from this file and save them to it after running"
+        )]
+        memo_file: Option<PathBuf>,
+        #[clap(long, help = "Cache at most this many calls to each memoized function")]
+        memo_limit: Option<usize>,
         #[clap(long, help = "Run the file in a specific mode")]
         mode: Option<RunMode>,
+        #[clap(
+            long = "format",
+            default_value = "text",
+            help = "The format of errors and diagnostics (text or json). \
+                    JSON messages are written to stderr as a single array."
+        )]
+        message_format: MessageFormat,
+        #[clap(flatten)]
+        lint_options: LintOptions,
+        #[clap(long, help = "Explain signature errors step by step")]
+        explain_signatures: bool,
+        #[clap(
+            long,
+            conflicts_with = "replay",
+            help = "Record the results of system calls to this file"
+        )]
+        record: Option<PathBuf>,
+        #[clap(
+            long,
+            help = "Replay the results of system calls recorded to this file"
+        )]
+        replay: Option<PathBuf>,
         #[cfg(feature = "audio")]
         #[clap(flatten)]
         audio_options: AudioOptions,
-        #[clap(trailing_var_arg = true)]
+        #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
     },
-    #[clap(about = "Build an assembly (the .uasm format is currently unstable)")]
+    #[clap(about = "Compile a file to a .uasm assembly that can be run without reparsing")]
     Build {
         path: Option<PathBuf>,
         #[clap(short, long, help = "The path to the output file")]
         output: Option<PathBuf>,
+        #[clap(flatten)]
+        lint_options: LintOptions,
+        #[clap(long, help = "Explain signature errors step by step")]
+        explain_signatures: bool,
+    },
+    #[clap(about = "Run a file in the step debugger")]
+    Debug {
+        path: Option<PathBuf>,
+        #[clap(
+            long = "break",
+            value_name = "LINE[:COL]",
+            help = "Pause at a line or at the code starting at a line and column. \
+                    If no breakpoints are given, execution pauses at the start."
+        )]
+        breakpoints: Vec<String>,
+        #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
     },
     #[clap(about = "Evaluate an expression and print its output")]
     Eval {
@@ -577,7 +866,7 @@ enum App {
         #[cfg(feature = "audio")]
         #[clap(flatten)]
         audio_options: AudioOptions,
-        #[clap(trailing_var_arg = true)]
+        #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
     },
     #[clap(about = "Format and test a file")]
@@ -585,6 +874,25 @@ enum App {
         path: Option<PathBuf>,
         #[clap(flatten)]
         formatter_options: FormatterOptions,
+        #[clap(flatten)]
+        lint_options: LintOptions,
+        #[clap(long, help = "Explain signature errors step by step")]
+        explain_signatures: bool,
+    },
+    #[clap(about = "Time each top-level line of a file")]
+    Bench {
+        path: Option<PathBuf>,
+        #[clap(
+            short = 'n',
+            long,
+            default_value_t = 20,
+            help = "The number of times to run each line"
+        )]
+        iterations: usize,
+        #[clap(long, help = "Compare against a baseline saved with --save")]
+        baseline: Option<PathBuf>,
+        #[clap(long, help = "Save the timings as a baseline file")]
+        save: Option<PathBuf>,
     },
     #[clap(about = "Run .ua files in the current directory when they change")]
     Watch {
@@ -598,26 +906,53 @@ enum App {
         clear: bool,
         #[clap(long, help = "Read stdin from file")]
         stdin_file: Option<PathBuf>,
-        #[clap(trailing_var_arg = true)]
+        #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
     },
     #[clap(about = "Format a Uiua file or all files in the current directory")]
     Fmt {
         path: Option<PathBuf>,
+        #[clap(long, help = "Apply fixes for suboptimal idioms before formatting")]
+        fix: bool,
         #[clap(flatten)]
         formatter_options: FormatterOptions,
     },
+    #[clap(about = "Run the Uiua code blocks in a Markdown file and write their outputs into it")]
+    Md {
+        #[clap(help = "The Markdown file")]
+        path: PathBuf,
+        #[clap(
+            long,
+            help = "Write outputs as HTML elements after code blocks instead of as comments"
+        )]
+        html: bool,
+        #[clap(
+            long,
+            help = "Check that the outputs in the file are up to date without writing it"
+        )]
+        check: bool,
+    },
     #[cfg(feature = "lsp")]
     #[clap(about = "Run the Language Server")]
     Lsp,
+    #[cfg(feature = "kernel")]
+    #[clap(about = "Run a Jupyter kernel")]
+    Kernel {
+        #[clap(help = "The connection file given by Jupyter")]
+        connection_file: Option<PathBuf>,
+        #[clap(long, help = "Install the kernel spec so Jupyter can find the kernel")]
+        install: bool,
+    },
     #[clap(about = "Run the Uiua interpreter in a REPL")]
     Repl {
+        #[clap(long, help = "Clear the stack after each line")]
+        clear: bool,
         #[clap(flatten)]
         formatter_options: FormatterOptions,
         #[cfg(feature = "audio")]
         #[clap(flatten)]
         audio_options: AudioOptions,
-        #[clap(trailing_var_arg = true)]
+        #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
     },
     #[clap(about = "Update Uiua by installing with Cargo")]
@@ -626,6 +961,17 @@ enum App {
         main: bool,
         #[clap(long, help = "Only check for updates")]
         check: bool,
+        #[clap(
+            long,
+            help = "Update the libraries in uiua-modules instead of Uiua itself"
+        )]
+        modules: bool,
+    },
+    #[clap(about = "Add a Git-hosted library to uiua-modules")]
+    Add {
+        #[clap(help = "The Git URL of the library. \
+                       If omitted, the libraries in the lockfile are installed.")]
+        url: Option<String>,
     },
     #[cfg(feature = "stand")]
     #[clap(about = "Create a standalone executable")]
@@ -637,6 +983,72 @@ enum App {
     },
 }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MessageFormat {
+    Text,
+    Json,
+}
+
+impl std::str::FromStr for MessageFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(MessageFormat::Text),
+            "json" => Ok(MessageFormat::Json),
+            _ => Err(format!("unknown message format `{s}`")),
+        }
+    }
+}
+
+fn print_json_messages(diagnostics: &BTreeSet<Diagnostic>, error: Option<&UiuaError>) {
+    let mut messages: Vec<serde_json::Value> =
+        diagnostics.iter().map(Diagnostic::to_json).collect();
+    if let Some(serde_json::Value::Array(errors)) = error.map(UiuaError::to_json) {
+        messages.extend(errors);
+    }
+    eprintln!("{}", serde_json::Value::from(messages));
+}
+
+#[derive(clap::Args)]
+struct LintOptions {
+    #[clap(
+        short = 'A',
+        long = "allow",
+        value_name = "LINT",
+        help = "Don't report a lint (one of unused-binding, shadowed-name, \
+                dead-code, deprecated, advice, or style)"
+    )]
+    allow: Vec<Lint>,
+    #[clap(
+        short = 'W',
+        long = "warn",
+        value_name = "LINT",
+        help = "Report a lint as a diagnostic"
+    )]
+    warn: Vec<Lint>,
+    #[clap(
+        short = 'D',
+        long = "deny",
+        value_name = "LINT",
+        help = "Report a lint as an error"
+    )]
+    deny: Vec<Lint>,
+}
+
+impl LintOptions {
+    fn apply(&self, comp: &mut Compiler) {
+        for (lints, level) in [
+            (&self.allow, LintLevel::Allow),
+            (&self.warn, LintLevel::Warn),
+            (&self.deny, LintLevel::Deny),
+        ] {
+            for &lint in lints {
+                comp.lint_level(lint, level);
+            }
+        }
+    }
+}
+
 #[derive(clap::Args)]
 struct FormatterOptions {
     #[clap(
@@ -778,18 +1190,44 @@ fn update(main: bool, check: bool) {
     }
 }
 
-fn format_single_file(path: PathBuf, config: &FormatConfig, stdout: bool) -> Result<(), UiuaError> {
-    let output = format_file(path, config, stdout)?.output;
+fn short_hash(hash: &str) -> &str {
+    hash.get(..7).unwrap_or(hash)
+}
+
+fn format_or_fix_file(
+    path: PathBuf,
+    config: &FormatConfig,
+    stdout: bool,
+    fix: bool,
+) -> Result<String, UiuaError> {
+    if !fix {
+        return Ok(format_file(path, config, stdout)?.output);
+    }
+    let (formatted, count) = fix_file(&path, config, stdout)?;
+    if count > 0 {
+        let s = if count == 1 { "" } else { "es" };
+        eprintln!("Applied {count} fix{s} to {}", path.display());
+    }
+    Ok(formatted.output)
+}
+
+fn format_single_file(
+    path: PathBuf,
+    config: &FormatConfig,
+    stdout: bool,
+    fix: bool,
+) -> Result<(), UiuaError> {
+    let output = format_or_fix_file(path, config, stdout, fix)?;
     if stdout {
         println!("{output}");
     }
     Ok(())
 }
 
-fn format_multi_files(config: &FormatConfig, stdout: bool) -> Result<(), UiuaError> {
+fn format_multi_files(config: &FormatConfig, stdout: bool, fix: bool) -> Result<(), UiuaError> {
     for path in uiua_files() {
         let path_as_string = path.to_string_lossy().into_owned();
-        let output = format_file(path, config, stdout)?.output;
+        let output = format_or_fix_file(path, config, stdout, fix)?;
         if stdout {
             println!("{path_as_string}");
             println!("{output}");
@@ -798,6 +1236,172 @@ fn format_multi_files(config: &FormatConfig, stdout: bool) -> Result<(), UiuaErr
     Ok(())
 }
 
+struct CliDebugger;
+
+impl Debugger for CliDebugger {
+    fn pause(&self, env: &Uiua, pause: &DebugPause) -> DebugAction {
+        let location = (pause.span.as_ref()).map_or_else(String::new, |span| span.to_string());
+        let marker = if pause.breakpoint { "●" } else { "→" };
+        println!(
+            "{} {}{} {}",
+            marker.bright_red(),
+            "  ".repeat(pause.call_depth.saturating_sub(1)),
+            pause.instr.bright_yellow(),
+            location.bright_black()
+        );
+        let stack = env.stack();
+        loop {
+            print!("{} ", "debug>".bright_black());
+            _ = io::stdout().flush();
+            let mut line = String::new();
+            if io::stdin().read_line(&mut line).is_err() || line.is_empty() {
+                return DebugAction::Abort;
+            }
+            let mut words = line.split_whitespace();
+            match words.next().unwrap_or("step") {
+                "s" | "step" => return DebugAction::Step,
+                "c" | "continue" => return DebugAction::Continue,
+                "q" | "quit" => return DebugAction::Abort,
+                "stack" => print_stack(stack, true),
+                "p" | "print" => {
+                    let n = words.next().map_or(Ok(1), str::parse::<usize>);
+                    match n {
+                        Ok(n) if (1..=stack.len()).contains(&n) => {
+                            println!("{}", stack[stack.len() - n].show())
+                        }
+                        Ok(n) => println!("There is no value {n} on a stack of {}", stack.len()),
+                        Err(_) => println!("Expected a stack position"),
+                    }
+                }
+                _ => println!(
+                    "Commands:\n  \
+                    s, step      run the next instruction (also an empty line)\n  \
+                    c, continue  run until the next breakpoint\n  \
+                    stack        show the whole stack\n  \
+                    p, print N   show the Nth value from the top of the stack (default 1)\n  \
+                    q, quit      stop the program"
+                ),
+            }
+        }
+    }
+}
+
+fn write_profile(rt: &mut Uiua, path: &Path) {
+    const TOP: usize = 20;
+    let Some(profile) = rt.take_profile() else {
+        return;
+    };
+    let folded_path = path.with_extension("folded");
+    if let Err(e) = fs::write(&folded_path, profile.folded()) {
+        eprintln!("Failed to write profile: {e}");
+        return;
+    }
+    eprintln!("{:>10} {:>10} {:>8}  location", "self", "total", "count");
+    for entry in profile.top(TOP) {
+        let location = entry.span.map(|span| span.to_string()).unwrap_or_default();
+        eprintln!(
+            "{:>8.2}ms {:>8.2}ms {:>8}  {} {}",
+            entry.self_ms,
+            entry.total_ms,
+            entry.count,
+            entry.name,
+            location.bright_black()
+        );
+    }
+    eprintln!("Folded stacks written to {}", folded_path.display());
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BenchStats {
+    code: String,
+    mean: f64,
+    median: f64,
+    stddev: f64,
+}
+
+impl BenchStats {
+    fn new(code: String, samples: &[f64]) -> Self {
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let mid = sorted.len() / 2;
+        let median = if sorted.len() % 2 == 0 {
+            (sorted[mid - 1] + sorted[mid]) / 2.0
+        } else {
+            sorted[mid]
+        };
+        let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n;
+        BenchStats {
+            code,
+            mean,
+            median,
+            stddev: variance.sqrt(),
+        }
+    }
+}
+
+fn bench(
+    path: &Path,
+    iterations: usize,
+    baseline: Option<&Path>,
+    save: Option<&Path>,
+) -> UiuaResult {
+    let baseline: HashMap<String, f64> = match baseline {
+        Some(baseline) => {
+            let json = fs::read_to_string(baseline)
+                .map_err(|e| UiuaError::Load(baseline.to_path_buf(), e.into()))?;
+            match serde_json::from_str::<Vec<BenchStats>>(&json) {
+                Ok(stats) => stats.into_iter().map(|s| (s.code, s.mean)).collect(),
+                Err(e) => {
+                    eprintln!("Failed to read baseline: {e}");
+                    return Ok(());
+                }
+            }
+        }
+        None => HashMap::new(),
+    };
+    let asm = Compiler::with_backend(NativeSys)
+        .print_diagnostics(true)
+        .load_file(path)?
+        .finish();
+    let mut rt = Uiua::with_native_sys();
+    let timings = rt.run_asm_bench(asm, iterations)?;
+    let mut all_stats = Vec::with_capacity(timings.len());
+    for timing in timings {
+        let Some(span) = timing.span else {
+            continue;
+        };
+        let code = span.as_str(rt.inputs(), |s| s.lines().next().unwrap_or("").to_string());
+        let stats = BenchStats::new(code, &timing.samples);
+        println!("{:>4}: {}", span.start.line, color_code(&stats.code));
+        print!(
+            "      mean {:.3}ms  median {:.3}ms  stddev {:.3}ms",
+            stats.mean, stats.median, stats.stddev
+        );
+        if let Some(&base) = baseline.get(&stats.code) {
+            let change = (stats.mean - base) / base * 100.0;
+            let change = format!("{change:+.1}% vs baseline");
+            if stats.mean > base + stats.stddev {
+                print!("  {}", change.bright_red());
+            } else if stats.mean < base - stats.stddev {
+                print!("  {}", change.bright_green());
+            } else {
+                print!("  {change}");
+            }
+        }
+        println!();
+        all_stats.push(stats);
+    }
+    if let Some(save) = save {
+        let json = serde_json::to_string_pretty(&all_stats).unwrap();
+        if let Err(e) = fs::write(save, json) {
+            eprintln!("Failed to save baseline: {e}");
+        }
+    }
+    Ok(())
+}
+
 fn print_stack(stack: &[Value], color: bool) {
     if stack.len() == 1 || !color {
         for value in stack {
@@ -821,7 +1425,7 @@ fn print_stack(stack: &[Value], color: bool) {
     }
 }
 
-fn repl(mut rt: Uiua, mut compiler: Compiler, color: bool, config: FormatConfig) {
+fn repl(mut rt: Uiua, mut compiler: Compiler, color: bool, clear: bool, config: FormatConfig) {
     let mut line_reader = DefaultEditor::new().expect("Failed to read from Stdin");
     let mut repl = |rt: &mut Uiua| -> Result<bool, UiuaError> {
         let mut code = match line_reader.readline("» ") {
@@ -848,18 +1452,30 @@ fn repl(mut rt: Uiua, mut compiler: Compiler, color: bool, config: FormatConfig)
         println!("{}", color_code(&code));
 
         let backup = compiler.clone();
+        let stack_backup = rt.stack().to_vec();
         let res = compiler
             .load_str(&code)
             .and_then(|comp| rt.run_asm(comp.finish()));
-        print_stack(&rt.take_stack(), color);
         match res {
             Ok(mut asm) => {
+                if clear {
+                    print_stack(&rt.take_stack(), color);
+                } else {
+                    print_stack(rt.stack(), color);
+                }
                 asm.remove_top_level();
                 *compiler.assembly_mut() = asm;
                 Ok(true)
             }
             Err(e) => {
+                // Restore the stack from before the failed line
                 compiler = backup;
+                rt.take_stack();
+                if !clear {
+                    for value in stack_backup {
+                        rt.push(value);
+                    }
+                }
                 Err(e)
             }
         }
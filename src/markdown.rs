@@ -0,0 +1,220 @@
+//! Run the Uiua code blocks in Markdown documents
+//!
+//! Every code block whose info string starts with `uiua` is run with the safe backend,
+//! and its output is written into the document.
+//! Running a document again replaces the outputs from the last run,
+//! so examples in documentation can be kept verified.
+
+use std::{fs, path::Path};
+
+use instant::Duration;
+
+use crate::{Uiua, UiuaError, UiuaResult, Value};
+
+/// How long a single code block may run
+const EXECUTION_LIMIT: Duration = Duration::from_secs(10);
+
+const HTML_OUTPUT: &str = "<pre class=\"uiua-output";
+
+/// How the outputs of code blocks are written into a Markdown document
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MarkdownStyle {
+    /// `##` output comments at the end of each code block
+    #[default]
+    Comments,
+    /// A `<pre class="uiua-output">` element after each code block
+    Html,
+}
+
+/// The result of running the code blocks in a Markdown document
+#[derive(Debug)]
+pub struct MarkdownOutput {
+    /// The document with the outputs of its code blocks
+    pub output: String,
+    /// The number of code blocks that were run
+    pub blocks: usize,
+    /// The errors of blocks that failed, along with the 1-based line of each block's opening fence
+    pub errors: Vec<(usize, UiuaError)>,
+}
+
+/// Run the Uiua code blocks in a Markdown document and write their outputs into it
+///
+/// Each block is run in a new environment.
+/// A block that fails has its error written as its output.
+pub fn run_markdown(input: &str, style: MarkdownStyle) -> MarkdownOutput {
+    let lines: Vec<&str> = input.split_inclusive('\n').collect();
+    let mut output = String::with_capacity(input.len());
+    let mut blocks = 0;
+    let mut errors = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        output.push_str(line);
+        i += 1;
+        let Some((indent, fence, info)) = opening_fence(line) else {
+            continue;
+        };
+        let fence_line = i;
+        // Find the closing fence
+        let start = i;
+        while i < lines.len() && !is_closing_fence(lines[i], fence) {
+            i += 1;
+        }
+        let end = i;
+        let closing = lines.get(end).copied().unwrap_or_default();
+        i = end + 1;
+        if info.split([' ', ',']).next() != Some("uiua") {
+            output.push_str(&lines[start..end].concat());
+            output.push_str(closing);
+            continue;
+        }
+        // Remove the output comments from the last run
+        let mut code_end = end;
+        if style == MarkdownStyle::Comments {
+            while code_end > start && lines[code_end - 1].trim_start().starts_with("##") {
+                code_end -= 1;
+            }
+        }
+        let code = lines[start..code_end].concat();
+        blocks += 1;
+        let (outputs, failed) = match run_block(&code) {
+            Ok(values) => (values.iter().map(Value::show).collect(), false),
+            Err(e) => {
+                let message = e.to_string().lines().next().unwrap_or_default().into();
+                errors.push((fence_line, e));
+                (vec![message], true)
+            }
+        };
+        output.push_str(&code);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        match style {
+            MarkdownStyle::Comments => {
+                for line in outputs.iter().flat_map(|s| s.lines()) {
+                    output.push_str(indent);
+                    output.push_str("##");
+                    if !line.is_empty() {
+                        output.push(' ');
+                        output.push_str(line);
+                    }
+                    output.push('\n');
+                }
+                output.push_str(closing);
+            }
+            MarkdownStyle::Html if closing.is_empty() => {}
+            MarkdownStyle::Html => {
+                output.push_str(closing);
+                if !output.ends_with('\n') {
+                    output.push('\n');
+                }
+                output.push_str(indent);
+                output.push_str(HTML_OUTPUT);
+                if failed {
+                    output.push_str(" uiua-error");
+                }
+                output.push_str("\">");
+                output.push_str(&escape_html(&outputs.join("\n")));
+                output.push_str("</pre>\n");
+                // Remove the output element from the last run
+                if (lines.get(i)).is_some_and(|line| line.trim_start().starts_with(HTML_OUTPUT)) {
+                    while i < lines.len() && !lines[i].contains("</pre>") {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+        }
+    }
+    MarkdownOutput {
+        output,
+        blocks,
+        errors,
+    }
+}
+
+/// Run the Uiua code blocks in a Markdown file and write their outputs into it
+///
+/// This modifies the file
+pub fn run_markdown_file<P: AsRef<Path>>(
+    path: P,
+    style: MarkdownStyle,
+    dont_write: bool,
+) -> UiuaResult<MarkdownOutput> {
+    let path = path.as_ref();
+    let input =
+        fs::read_to_string(path).map_err(|e| UiuaError::Load(path.to_path_buf(), e.into()))?;
+    let output = run_markdown(&input, style);
+    if output.output != input && !dont_write {
+        fs::write(path, &output.output)
+            .map_err(|e| UiuaError::Format(path.to_path_buf(), e.into()))?;
+    }
+    Ok(output)
+}
+
+fn run_block(code: &str) -> UiuaResult<Vec<Value>> {
+    let mut env = Uiua::with_safe_sys().with_execution_limit(EXECUTION_LIMIT);
+    env.run_str(code)?;
+    Ok(env.take_stack())
+}
+
+/// Get the indentation, fence, and info string of a line that opens a code block
+fn opening_fence(line: &str) -> Option<(&str, &str, &str)> {
+    let trimmed = line.trim_start_matches(' ');
+    let indent = &line[..line.len() - trimmed.len()];
+    if indent.len() > 3 {
+        return None;
+    }
+    let c = trimmed.chars().next().filter(|&c| c == '`' || c == '~')?;
+    let len = trimmed.len() - trimmed.trim_start_matches(c).len();
+    if len < 3 {
+        return None;
+    }
+    let (fence, info) = trimmed.split_at(len);
+    let info = info.trim();
+    if c == '`' && info.contains('`') {
+        return None;
+    }
+    Some((indent, fence, info))
+}
+
+fn is_closing_fence(line: &str, fence: &str) -> bool {
+    let c = fence.chars().next().unwrap();
+    let trimmed = line.trim();
+    trimmed.len() >= fence.len() && trimmed.chars().all(|d| d == c)
+}
+
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[test]
+fn markdown_blocks() {
+    let input = "# Doc\n```uiua\n+1 2\n```\n\n```rust\n1\n```\n";
+    let expected = "# Doc\n```uiua\n+1 2\n## 3\n```\n\n```rust\n1\n```\n";
+    let output = run_markdown(input, MarkdownStyle::Comments);
+    assert_eq!(output.output, expected);
+    assert_eq!(output.blocks, 1);
+    // Outputs from the last run are replaced
+    let output = run_markdown(expected, MarkdownStyle::Comments);
+    assert_eq!(output.output, expected);
+    let expected = "```uiua\n1_2 3\n```\n<pre class=\"uiua-output\">3\n[1 2]</pre>\n";
+    let output = run_markdown("```uiua\n1_2 3\n```\n", MarkdownStyle::Html);
+    assert_eq!(output.output, expected);
+    let output = run_markdown(expected, MarkdownStyle::Html);
+    assert_eq!(output.output, expected);
+    // Errors are reported with the line of the block
+    let output = run_markdown("Text\n\n```uiua\n⊢[]\n```\n", MarkdownStyle::Comments);
+    assert_eq!(output.errors.len(), 1);
+    assert_eq!(output.errors[0].0, 3);
+}
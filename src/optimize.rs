@@ -2,7 +2,7 @@ use std::fmt;
 
 use ecow::EcoVec;
 
-use crate::{ImplPrimitive, Instr, Primitive};
+use crate::{ImplPrimitive, Instr, Primitive, Uiua, Value};
 
 pub(crate) fn optimize_instrs_mut(instrs: &mut EcoVec<Instr>, mut new: Instr, maximal: bool) {
     use ImplPrimitive::*;
@@ -44,6 +44,45 @@ pub(crate) fn optimize_instrs_mut(instrs: &mut EcoVec<Instr>, mut new: Instr, ma
             instrs.pop();
             instrs.push(Instr::ImplPrim(Last, span))
         }
+        // Reverse Scan Reverse = ScanRight
+        (
+            [.., Instr::Prim(Reverse, _), Instr::PushFunc(_), Instr::Prim(Scan, span)],
+            Instr::Prim(Reverse, _),
+        ) => {
+            let span = *span;
+            instrs.pop();
+            let f = instrs.pop().unwrap();
+            instrs.pop();
+            instrs.push(f);
+            instrs.push(Instr::ImplPrim(ScanRight, span));
+        }
+        // Drop ¯1 Scan Join = ScanExclusive
+        (
+            [.., Instr::Push(_), Instr::Prim(Join, _), Instr::PushFunc(_), Instr::Prim(Scan, span), Instr::Push(n)],
+            Instr::Prim(Drop, _),
+        ) if matches!(&*n, Value::Num(n) if n.rank() == 0 && n.data[0] == -1.0) => {
+            let span = *span;
+            instrs.pop();
+            instrs.pop();
+            let f = instrs.pop().unwrap();
+            instrs.pop();
+            instrs.push(f);
+            instrs.push(Instr::ImplPrim(ScanExclusive, span));
+        }
+        // Take Range = TakeRange
+        ([.., Instr::Prim(Range, _), Instr::Push(_)], Instr::Prim(Take, span)) => {
+            let n = instrs.pop().unwrap();
+            instrs.pop();
+            instrs.push(n);
+            instrs.push(Instr::ImplPrim(TakeRange, span));
+        }
+        // Pick Range = PickRange
+        ([.., Instr::Prim(Range, _), Instr::Push(_)], Instr::Prim(Pick, span)) => {
+            let i = instrs.pop().unwrap();
+            instrs.pop();
+            instrs.push(i);
+            instrs.push(Instr::ImplPrim(PickRange, span));
+        }
         // Combine push temps
         (
             [.., Instr::PushTemp {
@@ -139,10 +178,46 @@ pub(crate) fn optimize_instrs_mut(instrs: &mut EcoVec<Instr>, mut new: Instr, ma
             instrs.pop();
             instrs.push(Instr::ImplPrim(ReplaceRand, span));
         }
+        // Constant folding
+        (_, Instr::Prim(prim, span)) => {
+            if let Some(val) = fold_constant(instrs, prim) {
+                let args = prim.args().unwrap_or(0);
+                instrs.truncate(instrs.len() - args);
+                optimize_instrs_mut(instrs, Instr::push(val), maximal);
+            } else {
+                instrs.push(Instr::Prim(prim, span));
+            }
+        }
         (_, instr) => instrs.push(instr),
     }
 }
 
+/// Try to evaluate a primitive whose arguments are all constants
+///
+/// Only primitives whose output is no larger than their inputs are folded.
+/// If evaluation fails, nothing is folded so that the error occurs at runtime.
+fn fold_constant(instrs: &[Instr], prim: Primitive) -> Option<Value> {
+    use Primitive::*;
+    let foldable = prim.class().is_pervasive()
+        || matches!(prim, Reverse | Deshape | Transpose | First | Len | Shape);
+    if !foldable || !prim.is_pure() || prim.outputs() != Some(1) {
+        return None;
+    }
+    let args = prim.args()?;
+    if args == 0 || instrs.len() < args {
+        return None;
+    }
+    let mut env = Uiua::with_safe_sys();
+    for instr in &instrs[instrs.len() - args..] {
+        let Instr::Push(val) = instr else {
+            return None;
+        };
+        env.push(val.clone());
+    }
+    prim.run(&mut env).ok()?;
+    env.take_stack().pop()
+}
+
 pub(crate) fn optimize_instrs<I>(instrs: I, maximal: bool) -> EcoVec<Instr>
 where
     I: IntoIterator<Item = Instr> + fmt::Debug,
@@ -0,0 +1,229 @@
+//! Git-hosted Uiua libraries
+//!
+//! Libraries are cloned into [`MODULES_DIR`], and the commit of each one is
+//! recorded in [`LOCK_FILE`] so that they can be reinstalled exactly.
+
+use std::{
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+pub use crate::compile::MODULES_DIR;
+
+/// The file that records the URL and commit of each installed library
+pub const LOCK_FILE: &str = "uiua-modules.lock";
+
+/// A locked library
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locked {
+    /// The name of the library's directory
+    pub name: String,
+    /// The Git URL the library was cloned from
+    pub url: String,
+    /// The hash of the checked out commit
+    pub commit: String,
+}
+
+/// Get the root directory of the current project
+///
+/// This is the nearest ancestor of the current directory that has a [`LOCK_FILE`] or a
+/// [`MODULES_DIR`], or the current directory itself if there is none.
+pub fn project_root() -> Result<PathBuf, String> {
+    let current =
+        std::env::current_dir().map_err(|e| format!("Failed to get the current directory: {e}"))?;
+    let root = current
+        .ancestors()
+        .find(|dir| dir.join(LOCK_FILE).is_file() || dir.join(MODULES_DIR).is_dir())
+        .unwrap_or(&current);
+    Ok(root.into())
+}
+
+/// Read the lockfile of the current project
+pub fn read_lock() -> Result<Vec<Locked>, String> {
+    let text = match fs::read_to_string(project_root()?.join(LOCK_FILE)) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {LOCK_FILE}: {e}")),
+    };
+    let mut locked = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(name), Some(url), Some(commit), None)
+                if lib_name(name).is_ok_and(|n| n == name) && is_commit_hash(commit) =>
+            {
+                locked.push(Locked {
+                    name: name.into(),
+                    url: url.into(),
+                    commit: commit.into(),
+                })
+            }
+            _ => return Err(format!("Invalid entry on line {} of {LOCK_FILE}", i + 1)),
+        }
+    }
+    Ok(locked)
+}
+
+/// Write the lockfile of the current project
+pub fn write_lock(locked: &[Locked]) -> Result<(), String> {
+    let mut locked = locked.to_vec();
+    locked.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut text =
+        String::from("# This file is generated by `uiua add`. Do not edit it by hand.\n");
+    for lib in &locked {
+        _ = writeln!(text, "{} {} {}", lib.name, lib.url, lib.commit);
+    }
+    fs::write(project_root()?.join(LOCK_FILE), text)
+        .map_err(|e| format!("Failed to write {LOCK_FILE}: {e}"))
+}
+
+/// Get the name of the directory a library will be cloned into
+pub fn lib_name(url: &str) -> Result<String, String> {
+    // Only the path after the host names the library
+    let path = if let Some((_, rest)) = url.split_once("://") {
+        rest.split_once('/').map_or("", |(_, path)| path)
+    } else if let Some((_, path)) = url.split_once(':') {
+        path
+    } else {
+        url
+    };
+    let name = (path.trim_end_matches('/').rsplit('/').next())
+        .unwrap_or_default()
+        .trim_end_matches(".git");
+    if name.is_empty() || name == "." || name == ".." || name.contains(char::is_whitespace) {
+        return Err(format!("Unable to determine a library name from {url:?}"));
+    }
+    Ok(name.into())
+}
+
+/// Clone a library and record it in the lockfile
+pub fn add(url: &str) -> Result<Locked, String> {
+    let name = lib_name(url)?;
+    let mut locked = read_lock()?;
+    if locked.iter().any(|lib| lib.name == name) {
+        return Err(format!("A library named {name} has already been added"));
+    }
+    let dir = lib_dir(&name)?;
+    if dir.exists() {
+        return Err(format!("{} already exists", dir.display()));
+    }
+    fs::create_dir_all(dir.parent().unwrap())
+        .map_err(|e| format!("Failed to create {MODULES_DIR}: {e}"))?;
+    git(
+        None,
+        &["clone", "--quiet", "--", url, &dir.to_string_lossy()],
+    )?;
+    let lib = Locked {
+        commit: head(&dir)?,
+        name,
+        url: url.into(),
+    };
+    locked.push(lib.clone());
+    write_lock(&locked)?;
+    Ok(lib)
+}
+
+/// Clone any locked libraries that are missing and check out their locked commits
+pub fn install() -> Result<Vec<Locked>, String> {
+    let locked = read_lock()?;
+    for lib in &locked {
+        let dir = lib_dir(&lib.name)?;
+        if !dir.exists() {
+            git(
+                None,
+                &["clone", "--quiet", "--", &lib.url, &dir.to_string_lossy()],
+            )?;
+        } else if head(&dir)? != lib.commit {
+            git(Some(&dir), &["fetch", "--quiet"])?;
+        }
+        checkout(&dir, &lib.commit)?;
+    }
+    Ok(locked)
+}
+
+/// Pull the latest commit of each locked library and update the lockfile
+///
+/// Returns the libraries whose commit changed, along with their previous commits
+pub fn update() -> Result<Vec<(Locked, String)>, String> {
+    let mut locked = install()?;
+    let mut changed = Vec::new();
+    for lib in &mut locked {
+        let dir = lib_dir(&lib.name)?;
+        git(Some(&dir), &["fetch", "--quiet"])?;
+        checkout(&dir, "origin/HEAD")?;
+        let commit = head(&dir)?;
+        if commit != lib.commit {
+            let old = std::mem::replace(&mut lib.commit, commit);
+            changed.push((lib.clone(), old));
+        }
+    }
+    write_lock(&locked)?;
+    Ok(changed)
+}
+
+fn lib_dir(name: &str) -> Result<PathBuf, String> {
+    Ok(project_root()?.join(MODULES_DIR).join(name))
+}
+
+fn is_commit_hash(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn checkout(dir: &Path, rev: &str) -> Result<String, String> {
+    // The trailing `--` keeps the revision from being read as a path
+    git(
+        Some(dir),
+        &["checkout", "--quiet", "--end-of-options", rev, "--"],
+    )
+}
+
+fn head(dir: &Path) -> Result<String, String> {
+    git(Some(dir), &["rev-parse", "HEAD"]).map(|s| s.trim().into())
+}
+
+fn git(dir: Option<&Path>, args: &[&str]) -> Result<String, String> {
+    let mut command = Command::new("git");
+    if let Some(dir) = dir {
+        command.arg("-C").arg(dir);
+    }
+    let output = (command.args(args).output())
+        .map_err(|e| format!("Failed to run `git {}`: {e}", args.join(" ")))?;
+    if !output.status.success() {
+        return Err(format!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into())
+}
+
+#[test]
+fn lib_names() {
+    for url in [
+        "https://github.com/uiua-lang/lib",
+        "https://github.com/uiua-lang/lib.git",
+        "https://github.com/uiua-lang/lib/",
+        "git@github.com:uiua-lang/lib.git",
+        "lib",
+    ] {
+        assert_eq!(lib_name(url).as_deref(), Ok("lib"), "{url}");
+    }
+    for url in [
+        "https://github.com/",
+        "https://github.com",
+        "git@github.com:",
+        "..",
+    ] {
+        assert!(lib_name(url).is_err(), "{url}");
+    }
+    assert!(is_commit_hash("0123456789abcdef"));
+    assert!(!is_commit_hash("--upload-pack=x"));
+    assert!(!is_commit_hash(""));
+}
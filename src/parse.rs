@@ -30,6 +30,7 @@ pub enum ParseError {
 pub enum Expectation {
     Term,
     ArgOutCount,
+    ImportPath,
     Simple(AsciiToken),
 }
 
@@ -44,6 +45,7 @@ impl fmt::Display for Expectation {
         match self {
             Expectation::Term => write!(f, "term"),
             Expectation::ArgOutCount => write!(f, "argument/output count"),
+            Expectation::ImportPath => write!(f, "import path"),
             Expectation::Simple(s) => write!(f, "`{s}`"),
         }
     }
@@ -276,7 +278,10 @@ impl<'i> Parser<'i> {
                 Some(item) => items.push(item),
                 None => {
                     if self.try_exact(Newline).is_none() {
-                        break;
+                        if !self.recover_line(parse_scopes) {
+                            break;
+                        }
+                        continue;
                     }
                     self.try_spaces();
                     let mut extra_newlines = false;
@@ -292,10 +297,38 @@ impl<'i> Parser<'i> {
         }
         items
     }
+    /// Skip past a token that cannot start an item, along with the rest of its line
+    ///
+    /// Returns `false` if the end of the input or the end of the current scope was reached
+    fn recover_line(&mut self, parse_scopes: bool) -> bool {
+        let Some(token) = self.tokens.get(self.index) else {
+            return false;
+        };
+        if !parse_scopes && token.value == Simple(TripleMinus) {
+            return false;
+        }
+        self.errors.push(
+            token
+                .span
+                .clone()
+                .sp(ParseError::Unexpected(token.value.clone())),
+        );
+        self.index += 1;
+        while let Some(token) = self.tokens.get(self.index) {
+            match token.value {
+                Newline => break,
+                Simple(TripleMinus) if !parse_scopes => break,
+                _ => self.index += 1,
+            }
+        }
+        true
+    }
     fn try_item(&mut self, parse_scopes: bool) -> Option<Item> {
         self.try_spaces();
         Some(if let Some(binding) = self.try_binding() {
             Item::Binding(binding)
+        } else if let Some(import) = self.try_import() {
+            Item::Import(import)
         } else {
             let lines = self.multiline_words();
             // Convert multiline words into multiple items
@@ -418,6 +451,44 @@ impl<'i> Parser<'i> {
             signature,
         })
     }
+    fn try_import_init(&mut self) -> Option<(Option<Sp<Ident>>, CodeSpan)> {
+        let start = self.index;
+        // Module name
+        let name = self.try_ident();
+        if name.is_some() {
+            self.try_spaces();
+        }
+        // Tilde
+        let Some(tilde_span) = self.try_exact(Tilde) else {
+            self.index = start;
+            return None;
+        };
+        self.try_spaces();
+        Some((name, tilde_span))
+    }
+    fn try_import(&mut self) -> Option<Import> {
+        let (name, tilde_span) = self.try_import_init()?;
+        // Path
+        let path = if let Some(path) = self.next_token_map(Token::as_string) {
+            path.map(Into::into)
+        } else {
+            self.errors.push(self.expected([Expectation::ImportPath]));
+            tilde_span.clone().sp(String::new())
+        };
+        // Items
+        let mut items = Vec::new();
+        while self.try_spaces().is_some() {
+            if let Some(item) = self.try_ident() {
+                items.push(item);
+            }
+        }
+        Some(Import {
+            name,
+            tilde_span,
+            path,
+            items,
+        })
+    }
     fn try_ident(&mut self) -> Option<Sp<Ident>> {
         let span = self.try_exact(Token::Ident)?;
         let s: Ident = self.input[span.byte_range()].into();
@@ -489,32 +560,63 @@ impl<'i> Parser<'i> {
                 match (&prev.value, &word.value) {
                     (Word::Primitive(a), Word::Primitive(b)) => {
                         match (a, b) {
-                            (Flip, Over) => self.diagnostics.push(Diagnostic::new(
-                                format!("Prefer `{Dip}{Dup}` over `{Flip}{Over}` for clarity"),
-                                span(),
-                                DiagnosticKind::Style,
-                                self.inputs.clone(),
-                            )),
+                            (Flip, Over) => self.diagnostics.push(
+                                Diagnostic::new(
+                                    format!("Prefer `{Dip}{Dup}` over `{Flip}{Over}` for clarity"),
+                                    span(),
+                                    DiagnosticKind::Style,
+                                    self.inputs.clone(),
+                                )
+                                .with_fix(span(), format!("{Dip}{Dup}")),
+                            ),
                             // Not comparisons
                             (Not, prim) => {
                                 for (a, b) in [(Eq, Ne), (Lt, Ge), (Gt, Le)] {
-                                    if *prim == a {
-                                        self.diagnostics.push(Diagnostic::new(
-                                            format!("Prefer `{b}` over `{Not}{prim}` for clarity"),
-                                            span(),
-                                            DiagnosticKind::Style,
-                                            self.inputs.clone(),
-                                        ));
+                                    let replacement = if *prim == a {
+                                        b
                                     } else if *prim == b {
-                                        self.diagnostics.push(Diagnostic::new(
-                                            format!("Prefer `{a}` over `{Not}{prim}` for clarity"),
+                                        a
+                                    } else {
+                                        continue;
+                                    };
+                                    self.diagnostics.push(
+                                        Diagnostic::new(
+                                            format!(
+                                                "Prefer `{replacement}` over `{Not}{prim}` for clarity"
+                                            ),
                                             span(),
                                             DiagnosticKind::Style,
                                             self.inputs.clone(),
-                                        ));
-                                    }
+                                        )
+                                        .with_fix(span(), replacement.to_string()),
+                                    );
                                 }
                             }
+                            // Pairs that do nothing
+                            (Flip, Flip) | (Reverse, Reverse) | (Pop, Dup) => {
+                                self.diagnostics.push(
+                                    Diagnostic::new(
+                                        format!("`{a}{b}` does nothing and can be removed"),
+                                        span(),
+                                        DiagnosticKind::Style,
+                                        self.inputs.clone(),
+                                    )
+                                    .with_fix(span(), ""),
+                                )
+                            }
+                            // Redundant identity
+                            (prim, Identity)
+                                if prim.modifier_args().is_none()
+                                    && prim.args().is_some_and(|n| n > 0) =>
+                            {
+                                self.redundant_identity(word.span.clone())
+                            }
+                            (Identity, prim)
+                                if prim.modifier_args().is_none()
+                                    && prim.outputs().is_some_and(|n| n > 0) =>
+                            {
+                                self.redundant_identity(prev.span.clone())
+                            }
                             _ => {}
                         }
                     }
@@ -530,12 +632,26 @@ impl<'i> Parser<'i> {
             Some(words)
         }
     }
+    fn redundant_identity(&mut self, span: CodeSpan) {
+        self.diagnostics.push(
+            Diagnostic::new(
+                format!(
+                    "This `{}` does nothing and can be removed",
+                    Primitive::Identity
+                ),
+                span.clone(),
+                DiagnosticKind::Style,
+                self.inputs.clone(),
+            )
+            .with_fix(span, ""),
+        );
+    }
     fn multiline_words(&mut self) -> Vec<Vec<Sp<Word>>> {
         let mut lines = Vec::new();
         while self.try_spaces().is_some() {}
         loop {
             let curr = self.index;
-            if self.try_binding_init().is_some() {
+            if self.try_binding_init().is_some() || self.try_import_init().is_some() {
                 self.index = curr;
                 break;
             }
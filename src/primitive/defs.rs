@@ -261,10 +261,9 @@ primitive!(
     ///
     /// ex: √4
     /// ex: √[1 4 9 16]
+    /// The square root of a negative number is complex.
     /// ex: √¯1
-    /// You can only take the square root of a negative number if it is complex.
-    /// ex: √  ¯4
-    ///   : √ℂ0¯4
+    /// ex: √[4 ¯4]
     (1, Sqrt, MonadicPervasive, ("sqrt", '√')),
     /// Get the sine of a number
     ///
@@ -428,6 +427,8 @@ primitive!(
     /// ex: ⁿ2 3
     /// ex: ⁿ2 [1 2 3]
     /// ex: ⁿ [1 2 3] [4 5 6]
+    /// A negative number to a fractional power is complex.
+    /// ex: ⁿ0.5 ¯4
     (2, Pow, DyadicPervasive, ("power", 'ⁿ')),
     /// Get the based logarithm of a number
     ///
@@ -435,6 +436,8 @@ primitive!(
     /// ex: ₙ2 8
     /// ex: ₙ2 [8 16 32]
     /// ex: ₙ [2 3 4] [16 27 1024]
+    /// The logarithm of a negative number is complex.
+    /// ex: ₙe ¯1
     (2, Log, DyadicPervasive, ("logarithm", 'ₙ')),
     /// Take the minimum of two arrays
     ///
@@ -505,6 +508,12 @@ primitive!(
     ///   :    △[1_2_3 4_5_6]
     ///   :   ⇡△[1_2_3 4_5_6]
     ///   : ⊡⇡△.[1_2_3 4_5_6]
+    ///
+    /// [take]ing or [pick]ing from a scalar range with a constant index is optimized to not make the whole range.
+    /// This even works for infinite ranges.
+    /// ex: ↙5 ⇡1e9
+    /// ex: ⊡¯3 ⇡1e9
+    /// ex: ↙5 ⇡∞
     (1, Range, MonadicArray, ("range", '⇡')),
     /// Get the first row of an array
     ///
@@ -658,6 +667,25 @@ primitive!(
     /// Here, we deduplicate by the [absolute value] of the elements.
     /// ex: ▽◰⌵. [1 ¯2 ¯5 2 3 1 5]
     (1, Unique, MonadicArray, ("unique", '◰')),
+    /// Count how many times each row of an array has occurred before
+    ///
+    /// The first occurrence of a row is `0`, the second is `1`, and so on.
+    /// ex: # Experimental!
+    ///   : occurrences [1 4 3 3 2 1 3 5 2 1]
+    /// ex: # Experimental!
+    ///   : occurrences "lollipop"
+    /// The rows with an [occurrences] of `0` are the [unique] ones.
+    /// ex: # Experimental!
+    ///   : =0 occurrences . "lollipop"
+    ///   : ◰ :
+    /// [occurrences] can keep only the first `n` copies of each row.
+    /// ex: # Experimental!
+    ///   : ▽<2 occurrences . [1 2 1 1 3 2 2 1]
+    ///
+    /// [occurrences] is the index of each row among the rows equal to it, like [classify] is the index of each row among the distinct rows.
+    ///
+    /// See also: [progressiveindexof]
+    (1, Occurrences, MonadicArray, "occurrences"),
     /// Turn an array into a box
     ///
     /// This is Uiua's primary way to create nested or mixed-type arrays.
@@ -916,6 +944,10 @@ primitive!(
     /// ex: ⬚0↻ 2 [1 2 3 4 5]
     ///   :   ↻ 2 [1 2 3 4 5]
     /// ex: ⬚0↻ 1_2 .↯4_5⇡20
+    /// This makes [fill][rotate] a shift, which can lag or lead a series.
+    /// ex: ⬚0↻¯1 [1 2 3 4 5]
+    ///   : ⬚0↻1  [1 2 3 4 5]
+    /// ex: ⬚@ ↻¯2 "hello"
     (2, Rotate, DyadicArray, ("rotate", '↻')),
     /// The n-wise windows of an array
     ///
@@ -930,6 +962,17 @@ primitive!(
     /// ex: ◫¯3 ↯4_4⇡16
     /// This can be useful when you want to get horizontal windows.
     /// ex: ◫¯1_2 ↯4_4⇡16
+    ///
+    /// If the window size is a rank-2 array with 2 rows, the first row is the window size and the second row is the stride.
+    /// The stride is how far apart the windows start along each axis.
+    /// ex: ◫[[3] [2]] ⇡7
+    /// A stride equal to the window size splits an array into non-overlapping blocks.
+    /// ex: ◫[2_2 2_2] ↯4_4⇡16
+    ///
+    /// [rows][reduce] over [windows] with [add], [maximum], or [minimum] is optimized to not make the windows.
+    /// This makes moving sums fast on long arrays.
+    /// ex: ≡/+◫3 [1 2 3 4 5 6]
+    ///   : ≡/↥◫2 [3 1 4 1 5 9]
     (2, Windows, DyadicArray, ("windows", '◫')),
     /// Discard or copy some rows of an array
     ///
@@ -961,6 +1004,26 @@ primitive!(
     /// ex: ⌕ 1_2 . ↯4_4⇡3
     /// ex: ⌕ [1_2 2_0] . ↯4_4⇡3
     (2, Find, DyadicArray, ("find", '⌕')),
+    /// Mark each occurence of one array in another with its index
+    ///
+    /// Every item of the searched array that is part of an occurence of the first array is marked with that occurence's index, starting at `1`. All other items are `0`.
+    /// ex: # Experimental!
+    ///   : mask "ab" "abracadabra"
+    /// ex: # Experimental!
+    ///   : mask [1 2] [0 1 2 3 1 2 1]
+    /// Occurences are found from the start and do not overlap.
+    /// ex: # Experimental!
+    ///   : mask "aa" "aaaaa"
+    /// Unlike [find], which only marks the start of each occurence, [mask] marks every item of it. This makes it useful for replacing substrings with [under].
+    /// ex: # Experimental!
+    ///   : ⍜▽∵⋅@- ±mask "ab" . "abracadabra"
+    /// Because each occurence has a different index, [mask] works with [partition] to split by a multi-character delimiter.
+    /// ex: # Experimental!
+    ///   : ⊜□ ¬±mask ", " . "a, b, c"
+    /// If the searched array is multidimensional, occurences are marked as blocks.
+    /// ex: # Experimental!
+    ///   : mask [1_2 2_0] . ↯4_4⇡3
+    (2, Mask, DyadicArray, "mask"),
     /// Check if each row of one array exists in another
     ///
     /// ex: ∊ 2 [1 2 3]
@@ -993,25 +1056,88 @@ primitive!(
     ///
     /// [indexof] is closely related to [member].
     (2, IndexOf, DyadicArray, ("indexof", '⊗')),
-    // /// Find sequential indices of each row of one array in another
-    // ///
-    // /// Unlike [indexof], [progressive indexof] will return the sequential indices of each row of the first array in the second array; the same index will not be used twice.
-    // /// When a searched-for row runs out of indices in the searched-in array, the length of the searched-in array is returned.
-    // /// Note here where the results are the same and where they are different:
-    // /// ex: ⊗ [1 1 2 2 3 3 4 4] [2 2 1 4 1 2 3 4]
-    // ///   : ⊘ [1 1 2 2 3 3 4 4] [2 2 1 4 1 2 3 4]
-    // ///
-    // /// One use of this is to find the first occurence of each row.
-    // /// ex: > ⊃⊘⋅⧻ ⊃∘◴ . [1 4 3 3 2 1 3 5 2 1]
-    // ///
-    // /// The [progressive indexof] an array in itself is the [range][length] of the array.
-    // /// ex: ⊘. [1 4 3 3 2 1 3 5 2 1]
-    // (
-    //     2,
-    //     ProgressiveIndexOf,
-    //     DyadicArray,
-    //     ("progressive indexof", '⊘')
-    // ),
+    /// Get the rows that are in either of two arrays
+    ///
+    /// Rows keep the order of their first occurrence, and duplicates are removed.
+    /// ex: # Experimental!
+    ///   : union [1 2 3 2] [4 2 5]
+    /// ex: # Experimental!
+    ///   : union "hello" "world"
+    /// Rows of the arrays must have the same shape. A scalar is treated as a single row.
+    /// ex: # Experimental!
+    ///   : union [1_2 3_4] [3_4 5_6]
+    /// ex: # Experimental!
+    ///   : union 5 [1 2]
+    ///
+    /// [union] is equivalent to [deduplicate][join], but it does not build the joined array.
+    ///
+    /// See also: [intersection], [difference]
+    (2, Union, DyadicArray, "union"),
+    /// Get the rows of one array that are also in another
+    ///
+    /// Rows keep the order of their first occurrence in the first array, and duplicates are removed.
+    /// ex: # Experimental!
+    ///   : intersection [3 1 4 1 5] [1 5 9]
+    /// ex: # Experimental!
+    ///   : intersection "abracadabra" "that's really cool"
+    /// ex: # Experimental!
+    ///   : intersection [1_2 3_4 5_6] [5_6 1_2]
+    ///
+    /// See also: [union], [difference]
+    (2, Intersection, DyadicArray, "intersection"),
+    /// Get the rows of one array that are not in another
+    ///
+    /// Rows keep the order of their first occurrence in the first array, and duplicates are removed.
+    /// ex: # Experimental!
+    ///   : difference [3 1 4 1 5] [1 5 9]
+    /// ex: # Experimental!
+    ///   : difference "abracadabra" "abc"
+    /// ex: # Experimental!
+    ///   : difference [1_2 3_4 5_6] [5_6 1_2]
+    ///
+    /// See also: [union], [intersection]
+    (2, Difference, DyadicArray, "difference"),
+    /// Get the digits of a number in a base (LSB-first)
+    ///
+    /// The result will always be 1 rank higher than the number.
+    /// ex: # Experimental!
+    ///   : base 10 1234
+    /// ex: # Experimental!
+    ///   : base 16 [255 4096]
+    /// [base] with a base of 2 is the same as [bits].
+    /// ex: # Experimental!
+    ///   : base 2 27
+    ///   : ⋯27
+    /// If the base is a list, each digit uses the corresponding base. This is useful for mixed-radix systems like time.
+    /// Excess is discarded from the last digit unless its base is [infinity].
+    /// ex: # Experimental!
+    ///   : base [60 60 24] 100000
+    ///   : base [60 60 24 ∞] 1000000
+    ///
+    /// [un][base] decodes digits back into numbers.
+    /// ex: # Experimental!
+    ///   : °(base 10) [4 3 2 1]
+    ///   : °(base [60 60 24 ∞]) [40 46 13 11]
+    (2, Base, DyadicArray, "base"),
+    /// Find sequential indices of each row of one array in another
+    ///
+    /// Unlike [indexof], [progressiveindexof] will return the sequential indices of each row of the first array in the second array; the same index will not be used twice.
+    /// When a searched-for row runs out of indices in the searched-in array, the length of the searched-in array is returned.
+    /// Note here where the results are the same and where they are different:
+    /// ex: # Experimental!
+    ///   : ⊗ [1 1 2 2 3 3 4 4] [2 2 1 4 1 2 3 4]
+    ///   : ⊘ [1 1 2 2 3 3 4 4] [2 2 1 4 1 2 3 4]
+    ///
+    /// One use of this is to find the first occurence of each row.
+    /// ex: # Experimental!
+    ///   : > ⊃⊘⋅⧻ ⊃∘◴ . [1 4 3 3 2 1 3 5 2 1]
+    ///
+    /// The [progressiveindexof] an array in itself is the [range][length] of the array.
+    /// ex: # Experimental!
+    ///   : ⊘. [1 4 3 3 2 1 3 5 2 1]
+    ///
+    /// See also: [occurrences]
+    (2, ProgressiveIndexOf, DyadicArray, ("progressiveindexof", '⊘')),
     /// Apply a reducing function to an array
     ///
     /// For reducing with an initial value, see [fold].
@@ -1062,6 +1188,10 @@ primitive!(
     ///
     /// Multiple accumulators can be used
     /// ex: ∧(⊃+(×⊙⋅∘)) +1⇡5 0 1
+    /// The accumulators are passed to the function below the rows, in the same order they are on the stack.
+    /// The function's first output becomes the first accumulator of the next iteration, and so on.
+    /// The accumulators can have different shapes, and their shapes can change between iterations.
+    /// ex: ∧(⊃+(⊂⊙⋅∘)) [1 2 3] 0 []
     /// If the iterated array is already on the stack, you can use [dip] to place the accumulators below it.
     /// ex: ∧(⊃+(×⊙⋅∘))⊙(0 1) +1⇡5
     ///
@@ -1080,6 +1210,12 @@ primitive!(
     /// ex: \+   1_2_3_4
     /// ex: \-   1_2_3_4
     /// ex: \(-:) 1_2_3_4
+    /// To [scan] from the right, [reverse] the array before and after.
+    /// This is optimized to not actually reverse the array.
+    /// ex: ⇌\+⇌ 1_2_3_4
+    /// For an exclusive [scan] that starts with an initial value, [join] the value and [drop] the last row.
+    /// This is also optimized to not make copies.
+    /// ex: ↘¯1\+⊂0 1_2_3_4
     /// [scan] is often used to do something with masks.
     /// [scan]ning with [minimum] or [maximum] will propogate `0`s or `1`s.
     /// ex: ▽\↧≠@ . "Hello World!"
@@ -1128,6 +1264,46 @@ primitive!(
     ///
     /// [rows] is one of a few modifiers that uses [proxy values](/docs/functions#proxy).
     ([1], Rows, IteratingModifier, ("rows", '≡')),
+    /// Apply a function to the cells of a given rank of an array or arrays
+    ///
+    /// Takes a function and a list of ranks, one for each argument of the function.
+    /// A non-negative rank selects cells of that rank. A negative rank counts down from the rank of the argument.
+    /// `∞` uses the whole argument. A single rank is used for every argument.
+    /// ex: # Experimental!
+    ///   : level□ 1 ↯2_2_3⇡12
+    /// This replaces towers of [rows].
+    /// ex: ≡≡□ ↯2_2_3⇡12
+    /// A rank of `¯1` is the same as [rows], and a rank of `0` is like [each].
+    /// ex: # Experimental!
+    ///   : level(/+) ¯1 [1_2_3 4_5_6]
+    ///   : level(+1) 0 [1_2_3 4_5_6]
+    /// Each argument can have a different rank.
+    /// Here, each row of the first array is joined with the whole second array.
+    /// ex: # Experimental!
+    ///   : level⊂ [¯1 ∞] [1_2 3_4] [5 6]
+    /// As with [rows], an argument with exactly one row at some level is reused for each row of the others.
+    ///
+    /// Like [rows], [level] uses [proxy values](/docs/functions#proxy) when an argument is empty.
+    ([1], Level, IteratingModifier, "level"),
+    /// Apply a function to the contents of each box of an array and box the results
+    ///
+    /// The shape of the array is preserved.
+    /// ex: # Experimental!
+    ///   : inventory⇌ {1_2_3 4_5 6}
+    /// This is the same as [each] with [content] and [box], but shorter.
+    /// ex: ∵(□⇌°□) {1_2_3 4_5 6}
+    /// Unboxed values are treated as if they were boxed.
+    /// ex: # Experimental!
+    ///   : inventory(⊂.) [1 2 3]
+    /// If the function takes multiple arguments, the arrays must have the same shape.
+    /// Scalars are reused for every box of the others.
+    /// ex: # Experimental!
+    ///   : inventory⊂ {"a" "bc"} {"de" "f"}
+    ///   : inventory⊂ □"!" {"a" "bc"}
+    /// If the function returns multiple values, each is boxed separately.
+    /// ex: # Experimental!
+    ///   : inventory(⊃⊢⇌) {1_2_3 4_5}
+    ([1], Inventory, IteratingModifier, "inventory"),
     /// Apply a function to each combination of rows of two arrays
     ///
     /// This is often what you want instead of [each].
@@ -1231,6 +1407,57 @@ primitive!(
     ///
     /// [partition] is closely related to [group].
     (2[1], Partition, AggregatingModifier, ("partition", '⊜')),
+    /// Group elements of an array into buckets by key, and also return the keys
+    ///
+    /// Takes a function and two arrays.
+    /// The arrays must be the same [length].
+    /// Rows in the second array are grouped by the matching rows of the first array, which can be any keys.
+    /// The function is called on each group in the order that its key first appears, like iterating [group].
+    /// The function's outputs are followed by the [deduplicate]d keys.
+    /// ex: # Experimental!
+    ///   : groupby⧻ "abacba" [1 2 3 4 5 6]
+    /// This is like using [group] with [classify], but the keys do not have to be computed again.
+    /// ex: # Experimental!
+    ///   : groupby(/+) {"a" "b" "a" "c"} [1 2 3 4]
+    ///   : map
+    ///
+    /// The function must take 0 or 1 arguments.
+    /// For reducing over all groups, use [group].
+    (2[1], GroupBy, AggregatingModifier, "groupby"),
+    /// Group runs of equal keys in an array, and also return the keys
+    ///
+    /// Takes a function and two arrays.
+    /// The arrays must be the same [length].
+    /// Consecutive rows in the second array whose rows in the first array are the same are grouped together.
+    /// Unlike [partition], there are no omitted keys.
+    /// The function is called on each group in order, like iterating [partition].
+    /// The function's outputs are followed by the key of each group.
+    /// ex: # Experimental!
+    ///   : partitionby⧻ [1 1 2 2 2 1 3] [1 2 3 4 5 6 7]
+    /// ex: # Experimental!
+    ///   : partitionby□ "aabccc" "uiua!!"
+    ///
+    /// The function must take 0 or 1 arguments.
+    (2[1], PartitionBy, AggregatingModifier, "partitionby"),
+    /// Sort the rows of an array by one or more keys
+    ///
+    /// Takes a function, a list of sort directions, and an array.
+    /// The function is called once on the whole array, and each of its outputs is a key with one row for each row of the array.
+    /// The key on top of the stack is the primary key, and each later key only breaks ties in the ones before it.
+    /// Each direction is `1` for ascending or `¯1` for descending. A single direction applies to every key.
+    /// ex: # Experimental!
+    ///   : sortby⊃(⊢⍉|⊡1⍉) [1 ¯1] [1_3 2_1 1_5 2_4]
+    /// Keys can have different types.
+    /// ex: # Experimental!
+    ///   : Names ← {"Bob" "Alice" "Carol" "Dave"}
+    ///   : Ages ← [30 25 30 25]
+    ///   : sortby⊃(⋅Ages|∘) [¯1 1] Names
+    /// The sort is stable, so rows with equal keys keep their order.
+    /// ex: # Experimental!
+    ///   : sortby(◿2) 1 [5 2 3 8 1 4]
+    ///
+    /// This is a single sort, so it is faster than sorting by each key in turn with [select] and [rise].
+    (2[1], SortBy, OtherModifier, "sortby"),
     /// Apply a function with implicit unboxing
     ///
     /// When working with [box]ed data, [unpack] will automatically [un][box] the data for functions like [join].
@@ -1373,7 +1600,7 @@ primitive!(
     ///   : ◌⍜F∘ @C
     ///
     /// Unlike built-in functions, [setinv] cannot properly make inverses that save context for use in [under].
-    /// This can lead to errors if you are unaware of it.
+    /// Because of this, the inverse may not take more arguments than the first function returns.
     /// ex! # Experimental!
     ///   : F ← setinv+-
     ///   : ⍜F∘ 3 5
@@ -1404,6 +1631,8 @@ primitive!(
     ///   : ◌F 5
     ///   : ◌⍜F(×10) 5
     ///
+    /// The second function must take the same number of arguments as the first and return at least as many values. The third function may not take more arguments than the second function returns.
+    ///
     /// Inverses set with [setund] cannot be used with [un]. For simpler inverse defining, see [setinv].
     ([3], SetUnder, InversionModifier, "setund"),
     /// Apply a function under another
@@ -1642,10 +1871,15 @@ primitive!(
     /// The presence of a [recur] prevents the signature checker from working, so a signature must always be provided at the innermost function that contains a [recur].
     /// ex: # Experimental!
     ///   : ↬((+∩(|2 ↫ -)1,2|1)<2.) 5
+    ///
+    /// A [recur] that is the last thing its function does is a tail call. Tail calls do not grow the call stack, so they can recur any number of times.
+    /// This also applies to switch functions and named functions called last.
+    /// ex: # Experimental!
+    ///   : ↬((|2 ↫-1⊙+.|⋅∘)=0.) 100000 0
     (0(None), Recur, Misc, ("recur", '↫')),
     /// Generate a random number in the range `[0, 1)`
     ///
-    /// If you need a seeded random number, use [gen].
+    /// If you need a seeded random number, use [gen] or [seed].
     ///
     /// ex: ⚂
     /// ex: [⚂⚂⚂]
@@ -1680,7 +1914,66 @@ primitive!(
     /// ex: F ← memo(+⌊×10⚂)
     ///   : ∵F [1 1 2 2 3 3]
     /// In general, this should only be used with functions that perform a potentially expensive calculation.
+    ///
+    /// By default, caches grow without bound. [&memoclr] clears them.
+    /// ex: F ← memo(+⌊×10⚂)
+    ///   : [F 1 &memoclr F 1]
     ([1], Memo, OtherModifier, "memo"),
+    /// Call a function with a seeded random number generator
+    ///
+    /// Expects a seed after the function's arguments.
+    /// Every [random] in the function, including in threads it [spawn]s, uses the seeded generator, so the results are the same for the same seed.
+    /// ex: # Experimental!
+    ///   : seed[⚂⚂⚂] 0
+    ///   : seed[⚂⚂⚂] 0
+    ///   : seed[⚂⚂⚂] 1
+    /// The generator is restored afterwards, so [random] outside of [seed] is unaffected.
+    /// ex: # Experimental!
+    ///   : [⚂ seed⚂ 0 ⚂]
+    ([1], Seed, OtherModifier, "seed"),
+    /// Do exact rational arithmetic
+    ///
+    /// [exact]'s function must be a single arithmetic or comparison primitive.
+    /// Arguments may be numbers, strings of rational numbers, or box arrays of either.
+    /// Results are strings, or box arrays of strings, so they can be passed to [exact] again without losing precision.
+    /// ex: # Experimental!
+    ///   : + 0.1 0.2
+    ///   : exact+ 0.1 0.2
+    /// ex: # Experimental!
+    ///   : exact÷ 3 1
+    ///   : exact× 3 exact÷ 3 1
+    /// Rational strings can be written as integers, decimals, or fractions.
+    /// ex: # Experimental!
+    ///   : exact- "1/3" {"1" "0.5" "¯2/3"}
+    /// [parse] normalizes rational strings.
+    /// ex: # Experimental!
+    ///   : exact⋕ {"2/4" "1.25" "6/3"}
+    /// Comparisons return booleans.
+    /// ex: # Experimental!
+    ///   : exact< "1/3" 0.3
+    /// Numerators and denominators are limited to 64 bits.
+    ([1], Exact, OtherModifier, "exact"),
+    /// Do arbitrary-precision integer arithmetic
+    ///
+    /// [big]'s function must be a single arithmetic or comparison primitive, [bits], or [un][bits].
+    /// Arguments may be integers, strings of integers, or box arrays of either.
+    /// Results are strings, or box arrays of strings, so they can be passed to [big] again without losing precision.
+    /// ex: # Experimental!
+    ///   : ⁿ 100 2
+    ///   : big ⁿ 100 2
+    /// [divide] floors its result, and [modulus] takes the sign of the modulus.
+    /// ex: # Experimental!
+    ///   : big÷ 7 "100000000000000000000"
+    ///   : big◿ 7 "100000000000000000000"
+    /// [bits] and [un][bits] convert to and from lists of bits.
+    /// ex: # Experimental!
+    ///   : big⋯ "1180591620717411303424"
+    ///   : big°⋯ ⊂↯70 0 1
+    /// Comparisons return booleans.
+    /// ex: # Experimental!
+    ///   : big> {"18446744073709551616" 5} "18446744073709551615"
+    /// [big] is only available when Uiua is built with the `num-bigint` feature.
+    ([1], Big, OtherModifier, "big"),
     /// Run a function at compile time
     ///
     /// ex: F ← (⌊×10[⚂⚂⚂])
@@ -1785,18 +2078,313 @@ primitive!(
     ///
     /// Uiua uses the [Rust regex crate](https://docs.rs/regex/latest/regex/) internally.
     (2, Regex, Misc, "regex"),
+    /// Replace all matches of a regex pattern
+    ///
+    /// The first argument is the pattern, the second is the replacement, and the third is the string.
+    /// ex: # Experimental!
+    ///   : regexreplace "[aeiou]" "_" "hello world"
+    /// The replacement may refer to capture groups by number with `$1` or by name with `${name}`. Use `$$` for a literal `$`.
+    /// ex: # Experimental!
+    ///   : regexreplace "(\\w+) (\\w+)" "$2 $1" "hello world"
+    /// ex: # Experimental!
+    ///   : regexreplace "(?<y>\\d{4})-(?<m>\\d{2})" "${m}/${y}" "2024-01"
+    (3, RegexReplace, Misc, "regexreplace"),
+    /// Split a string by a regex pattern
+    ///
+    /// The result is a list of [box]ed strings. Empty fields are kept.
+    /// ex: # Experimental!
+    ///   : regexsplit "\\s*,\\s*" "a, b ,c,,d"
+    /// ex: # Experimental!
+    ///   : regexsplit "\\d+" "abc123def45ghi"
+    (2, RegexSplit, Misc, "regexsplit"),
+    /// Get the named capture groups of all matches of a regex pattern
+    ///
+    /// Returns a map array from each group's name to a list of [box]ed strings, with one string per match.
+    /// ex: # Experimental!
+    ///   : regexnamed "(?<key>\\w+)=(?<value>\\w+)" "a=1 b=2"
+    ///   : get "value"
+    /// Named groups that do not participate in a match can be given a default with [fill].
+    /// ex: # Experimental!
+    ///   : ⬚(□"")regexnamed "(?<n>\\d+)(?<unit>px)?" "10px 20"
+    (2, RegexNamed, Misc, "regexnamed"),
+    /// Convert a value to Uiua code that evaluates to it
+    ///
+    /// Unlike the pretty-printed output, the result is a single line of valid Uiua code.
+    /// ex: # Experimental!
+    ///   : repr [1_2 3_4]
+    /// ex: # Experimental!
+    ///   : repr {"Hello" @, [1 2 3]}
+    /// ex: # Experimental!
+    ///   : repr "\"quoted\"\n"
+    /// Numbers are written exactly, so the code round-trips even when the printed number would be rounded.
+    /// ex: # Experimental!
+    ///   : repr [π 0.1 ¯∞ NaN]
+    /// Maps and complex numbers can also be represented.
+    /// ex: # Experimental!
+    ///   : repr map [1] "a"
+    /// ex: # Experimental!
+    ///   : repr ℂ1 2
+    /// This is useful for generating code or writing golden files for tests.
+    (1, Repr, Misc, "repr"),
     /// Convert a string to UTF-8 bytes
     ///
     /// ex: utf "hello!"
     /// ex: utf "❤️"
     /// You can use [un] to convert UTF-8 bytes back to a string.
     /// ex: °utf [226 156 168 32 119 111 119 33]
+    /// By default, [un][utf] errors on invalid UTF-8. The error includes the byte offset of the invalid sequence.
+    /// ex! °utf [104 105 255 33]
+    /// If a character [fill] is set, each invalid sequence is replaced with that character.
+    /// ex: ⬚@\uFFFD°utf [104 105 255 33]
+    /// If an empty string [fill] is set, invalid sequences are skipped.
+    /// ex: ⬚""°utf [104 105 255 33]
     ///
     /// [utf] is different from just [add]ing or [subtracting] `@\0`.
     /// Character math can only convert to and from UTF-32.
     /// ex: -@\0 "👩🏽‍👩🏻‍👦🏻‍👧🏽"
     /// ex: utf "👩🏽‍👩🏻‍👦🏻‍👧🏽"
     (1, Utf, Misc, "utf"),
+    /// Convert a string to a list of UTF-16 code units
+    ///
+    /// ex: # Experimental!
+    ///   : utfsixteen "Hi! 😀"
+    /// You can use [un] to convert UTF-16 code units back to a string.
+    /// Like [un][utf], invalid code units can be replaced or skipped with a [fill].
+    /// ex: # Experimental!
+    ///   : °utfsixteen [72 105 33 32 55357 56832]
+    /// ex: # Experimental!
+    ///   : ⬚@?°utfsixteen [72 105 55357]
+    ///
+    /// To convert between characters and Unicode code points, [subtract] or [add] `@\0`.
+    /// ex: -@\0 "Hi! 😀"
+    /// ex: +@\0 [72 105 33 32 128512]
+    (1, Utf16, Misc, "utfsixteen"),
+    /// Split a string into a list of boxed grapheme clusters
+    ///
+    /// A grapheme cluster is what a reader would consider a single character.
+    /// It may be made of several code points, such as a letter with combining accents or an emoji sequence.
+    /// ex: # Experimental!
+    ///   : graphemes "ñ👩🏽‍🚀!"
+    /// ex: # Experimental!
+    ///   : ⧻graphemes "👩🏽‍🚀"
+    ///   : ⧻"👩🏽‍🚀"
+    /// [under][graphemes] lets you operate on grapheme clusters while keeping them intact.
+    /// ex: # Experimental!
+    ///   : ⍜graphemes⇌ "ñ👩🏽‍🚀!"
+    (1, Graphemes, Misc, "graphemes"),
+    /// Convert a string to uppercase
+    ///
+    /// Case mapping uses the full Unicode case tables and does not depend on the locale.
+    /// Some characters map to several characters, so the result may be longer than the input.
+    /// ex: # Experimental!
+    ///   : upper "Hello, World!"
+    /// ex: # Experimental!
+    ///   : upper "straße"
+    /// Arrays of strings are mapped row by row. Rows whose lengths change differently must be [fill]ed.
+    /// ex: # Experimental!
+    ///   : upper ["abc" "def"]
+    (1, Upper, Misc, "upper"),
+    /// Convert a string to lowercase
+    ///
+    /// Like [upper], this uses the full Unicode case tables and does not depend on the locale.
+    /// ex: # Experimental!
+    ///   : lower "Hello, World!"
+    /// ex: # Experimental!
+    ///   : lower "ΟΔΟΣ"
+    (1, Lower, Misc, "lower"),
+    /// Normalize a string to a Unicode normalization form
+    ///
+    /// The first argument is the name of the form: `"nfc"`, `"nfd"`, `"nfkc"`, or `"nfkd"`.
+    /// The composed forms combine base characters and accents into single code points where possible. The decomposed forms split them apart.
+    /// ex: # Experimental!
+    ///   : ⧻normalize "nfd" "é"
+    ///   : ⧻normalize "nfc" normalize "nfd" "é"
+    /// Normalizing strings before comparing them makes visually identical strings [match].
+    /// ex: # Experimental!
+    ///   : ≍ "é" "e\u0301"
+    ///   : ≍ ∩(normalize "nfc") "é" "e\u0301"
+    /// The compatibility forms also replace characters with their plain equivalents.
+    /// ex: # Experimental!
+    ///   : normalize "nfkc" "ﬁ①"
+    (2, Normalize, Misc, "normalize"),
+    /// Split a string by a delimiter
+    ///
+    /// The first argument is the delimiter. It may be a character or a string of any length.
+    /// The result is a list of boxed strings. Empty fields are kept.
+    /// ex: # Experimental!
+    ///   : split "," "a,b,,c"
+    /// ex: # Experimental!
+    ///   : split ", " "Hello, World, !"
+    /// ex: # Experimental!
+    ///   : split @; ";x;"
+    /// Each row of a higher-rank character array is split separately. Rows with different numbers of fields must be [fill]ed.
+    /// ex: # Experimental!
+    ///   : split "-" ["a-b" "c-d"]
+    /// ex: # Experimental!
+    ///   : ⬚"" split "-" ["a-bc" "d--e"]
+    ///
+    /// [un][split] joins a list of strings with the delimiter.
+    /// ex: # Experimental!
+    ///   : °(split ", ") {"Hello" "World"}
+    /// [under][split] lets you modify the fields of a string.
+    /// ex: # Experimental!
+    ///   : ⍜(split ",")⇌ "a,b,c"
+    (2, Split, Misc, "split"),
+    /// Encode an array as a JSON string
+    ///
+    /// ex: # Experimental!
+    ///   : json [1 2 3]
+    /// ex: # Experimental!
+    ///   : json {"hello" [4 5] 6}
+    /// Character lists become strings, and boxes are unboxed.
+    /// Map arrays with string keys become objects.
+    /// ex: # Experimental!
+    ///   : json map {"name" "tags"} {"Uiua" {"array" "stack"}}
+    ///
+    /// [un][json] decodes a JSON string.
+    /// ex: # Experimental!
+    ///   : °json "[1, 2, 3]"
+    /// Lists of numbers or booleans become arrays. Other lists become boxed lists.
+    /// Objects become map arrays with boxed string keys.
+    /// ex: # Experimental!
+    ///   : °json $ {"name": "Uiua", "tags": ["array", "stack"], "version": 0.8}
+    /// `null` becomes `NaN`.
+    /// ex: # Experimental!
+    ///   : °json "[1, null, 3]"
+    (1, Json, Misc, "json"),
+    /// Encode an array as a CSV string
+    ///
+    /// Each row of a rank-2 array becomes a record. A rank-1 array is a single record.
+    /// Cells may be strings or scalar numbers, and may be boxed.
+    /// ex: # Experimental!
+    ///   : csv [{"name" "age"} {"Alice" 30} {"Bob, Jr." 25}]
+    /// Fields that contain commas, quotes, or newlines are quoted.
+    /// Strings are single cells, so a list of strings is a single record.
+    /// ex: # Experimental!
+    ///   : csv ["ab" "cd"]
+    ///
+    /// [un][csv] decodes a CSV string into a rank-2 array of boxed strings.
+    /// ex: # Experimental!
+    ///   : °csv "a,b\n1,2\n3,4"
+    /// Quoted fields may contain commas, escaped quotes, and newlines.
+    /// ex: # Experimental!
+    ///   : °csv "\"x, y\",\"say \"\"hi\"\"\"\n\"two\nlines\",z"
+    /// Short records are padded with empty fields.
+    /// ex: # Experimental!
+    ///   : °csv "a,b,c\n1\n2,3"
+    /// [csv][join] writes a header row before the records.
+    /// ex: # Experimental!
+    ///   : csv⊂ {"name" "age"} [{"Alice" 30} {"Bob" 25}]
+    /// [un]([csv][join]) reads the header row as a separate value.
+    /// ex: # Experimental!
+    ///   : °(csv⊂) "name,age\nAlice,30\nBob,25"
+    (1, Csv, Misc, "csv"),
+    /// Pack numbers into bytes according to a binary format
+    ///
+    /// The format is a string of field types separated by spaces.
+    /// The types are `u8`, `i8`, `u16`, `i16`, `u32`, `i32`, `u64`, `i64`, `f32`, and `f64`.
+    /// The format may start with `<` for little-endian or `>` for big-endian. The default is little-endian.
+    /// ex: # Experimental!
+    ///   : binary "u16" [1 2 258]
+    /// ex: # Experimental!
+    ///   : binary ">u16" [1 2 258]
+    /// ex: # Experimental!
+    ///   : binary "f32" 1.5
+    /// If the format has multiple fields, the last axis of the array must have one element per field.
+    /// ex: # Experimental!
+    ///   : binary "<u8 i16 f32" [1_¯2_0.5 3_¯4_0.25]
+    ///
+    /// [un][binary] unpacks bytes into numbers. This is useful for parsing binary file formats.
+    /// ex: # Experimental!
+    ///   : °(binary ">u16") [1 2 3 4]
+    /// ex: # Experimental!
+    ///   : °(binary "u8 u16") [1 2 0 3 4 0]
+    /// If the last axis of the bytes is exactly one record long, it is unpacked into a single record.
+    /// ex: # Experimental!
+    ///   : °(binary "u8 u16") [1_2_0 3_4_0]
+    (2, Binary, Misc, "binary"),
+    /// Hash a string or list of bytes
+    ///
+    /// The first argument is the name of the algorithm, either `"sha256"` or `"md5"`.
+    /// Strings are encoded as UTF-8. The result is a list of bytes.
+    /// ex: # Experimental!
+    ///   : hash "sha256" "Uiua"
+    /// MD5 is not secure. It is only provided for interoperability with existing formats.
+    /// ex: # Experimental!
+    ///   : hash "md5" "Uiua"
+    /// [hash] is only available when Uiua is built with the `crypto` feature.
+    (2, Hash, Misc, "hash"),
+    /// Get the HMAC-SHA-256 of a message with a key
+    ///
+    /// The key and message may be strings or lists of bytes. The result is a list of 32 bytes.
+    /// ex: # Experimental!
+    ///   : hmac "key" "The quick brown fox jumps over the lazy dog"
+    /// [hmac] is only available when Uiua is built with the `crypto` feature.
+    (2, Hmac, Misc, "hmac"),
+    /// Take the fast Fourier transform of each row along the last axis
+    ///
+    /// The result is always complex.
+    /// ex: # Experimental!
+    ///   : fft [1 0 0 0]
+    /// ex: # Experimental!
+    ///   : ⁅ fft [0 1 0 ¯1]
+    /// ex: # Experimental!
+    ///   : fft [1_2 3_4]
+    /// [un][fft] takes the inverse transform.
+    /// ex: # Experimental!
+    ///   : ⁅ °fft fft [1 2 3 4]
+    /// [fft] is only available when Uiua is built with the `rustfft` feature.
+    (1, Fft, Misc, "fft"),
+    /// Get the determinant of a square matrix
+    ///
+    /// ex: # Experimental!
+    ///   : det [3_8 4_6]
+    /// ex: # Experimental!
+    ///   : det [1_2_3 4_5_6 7_8_9]
+    /// [det] is only available when Uiua is built with the `linalg` feature.
+    (1, Det, Misc, "det"),
+    /// Invert a square matrix
+    ///
+    /// ex: # Experimental!
+    ///   : inverse [4_7 2_6]
+    /// The product of a matrix and its [inverse] is the identity matrix.
+    /// ex: # Experimental!
+    ///   : ⁅ ⊞(/+×)⊙⍉ inverse. [4_7 2_6]
+    /// It is an error to invert a singular matrix.
+    /// ex! # Experimental!
+    ///   : inverse [1_2 2_4]
+    /// [inverse] is only available when Uiua is built with the `linalg` feature.
+    (1, MatrixInverse, Misc, "inverse"),
+    /// Solve a system of linear equations
+    ///
+    /// The first argument is a square matrix of coefficients. The second argument is the right-hand side.
+    /// ex: # Experimental!
+    ///   : solve [2_1 1_3] [3 5]
+    /// If the right-hand side is a matrix, each of its columns is solved for.
+    /// ex: # Experimental!
+    ///   : solve [2_1 1_3] [3_1 5_2]
+    /// [solve] is only available when Uiua is built with the `linalg` feature.
+    (2, Solve, Misc, "solve"),
+    /// Get the LU decomposition of a square matrix
+    ///
+    /// Returns a permutation matrix P on top, then a unit lower triangular matrix L, then an upper triangular matrix U.
+    /// The product of P and the original matrix is the product of L and U.
+    /// ex: # Experimental!
+    ///   : lu [1_2 3_4]
+    /// ex: # Experimental!
+    ///   : ⊞(/+×)⊙⍉ ⋅∘ lu [1_2 3_4]
+    /// [lu] is only available when Uiua is built with the `linalg` feature.
+    (1(3), Lu, Misc, "lu"),
+    /// Get the QR decomposition of a matrix
+    ///
+    /// Returns an orthogonal matrix Q on top, then an upper triangular matrix R.
+    /// Their product is the original matrix.
+    /// ex: # Experimental!
+    ///   : qr [3_1 4_2]
+    /// ex: # Experimental!
+    ///   : ⁅ ⊞(/+×)⊙⍉ qr [3_1 4_2]
+    /// [qr] is only available when Uiua is built with the `linalg` feature.
+    (1(2), Qr, Misc, "qr"),
     /// Generate a unique tag
     ///
     /// Tags are just numbers and are unique across multiple threads, but not across multiple runs.
@@ -1873,6 +2461,21 @@ primitive!(
     ///   : insert 7 8_9
     ///   : °map .
     ///
+    /// Map entries are kept in insertion order, which is the order used by [un][map] and when formatting.
+    /// If a key appears more than once, its position is that of its first occurrence, but its value is the last one.
+    /// ex: # Experimental!
+    ///   : map "cab" 1_2_3
+    ///   : °map insert @a 4
+    ///   : °map map "abca" 1_2_3_4
+    /// [remove]ing a key removes it from the order, so [insert]ing it again puts it at the end.
+    /// ex: # Experimental!
+    ///   : °map insert @a 5 remove @a map "abc" 1_2_3
+    ///
+    /// Keys are compared like [match] compares arrays. All `NaN`s are the same key, and `¯0` is the same key as `0`.
+    /// ex: # Experimental!
+    ///   : map [NaN ¯0] 1_2
+    ///   : [fork(get NaN|get 0)] .
+    ///
     /// Map array are just normal box arrays. Their shape is usually `[2]`; one element is the boxed keys, and the other is the boxed values.
     /// Performing non-map operations on a map array will work, but it will usually break the mapping.
     /// ex! # Experimental!
@@ -1889,7 +2492,8 @@ primitive!(
     ///   : insert 1 2
     ///   : insert 3 4
     ///   : insert 5 6
-    /// If the key is already present, it is replaced.
+    /// New keys are added to the end of the map's order.
+    /// If the key is already present, its value is replaced, and it keeps its position.
     /// ex: # Experimental!
     ///   : {}
     ///   : insert 1 2
@@ -1946,7 +2550,7 @@ primitive!(
     ///
     /// See [map] for an overview of map arrays.
     ///
-    /// If the key is present, it is replaced with a tombstone NaN value.
+    /// If the key is present, it is replaced with a tombstone NaN value and removed from the map's order.
     /// If the key is not present, the array is unchanged.
     /// ex: # Experimental!
     ///   : map 1_2 3_4
@@ -2003,8 +2607,87 @@ primitive!(
     /// ex: 1_2_3 4 5_6_7
     ///   : dump⊢
     (0(0)[1], Dump, Stack, "dump"),
+    /// Pause execution in the debugger
+    ///
+    /// When running with `uiua debug`, execution pauses before [breakpoint] so that the stack can be inspected and the following instructions stepped through.
+    /// Otherwise, [breakpoint] does nothing.
+    /// ex: # Experimental!
+    ///   : 1 2 breakpoint
+    ///   : +
+    (0(0), Breakpoint, Stack, "breakpoint"),
 );
 
+impl Primitive {
+    /// Get the names of the roles of the primitive's arguments
+    ///
+    /// The first name is for the argument on the top of the stack.
+    /// Modifiers' function arguments are not included.
+    pub fn arg_names(&self) -> &'static [&'static str] {
+        use Primitive::*;
+        const VALUE: &[&str] = &["the value"];
+        const ARRAY: &[&str] = &["the array"];
+        const TWO_VALUES: &[&str] = &["the first value", "the second value"];
+        const TWO_ARRAYS: &[&str] = &["the first array", "the second array"];
+        match self {
+            Dup | Pop | Identity | Type | Trace => VALUE,
+            Over => &["the top value", "the value to copy"],
+            Flip => &["the top value", "the second value"],
+            Not | Sign | Neg | Abs | Sqrt | Sin | Floor | Ceil | Round => VALUE,
+            Eq | Ne | Add | Mul | Min | Max | Match | Both => TWO_VALUES,
+            Lt | Le | Gt | Ge => &["the value to compare against", "the value to compare"],
+            Sub => &["the amount to subtract", "the value to subtract from"],
+            Div => &["the divisor", "the dividend"],
+            Mod => &["the modulus", "the value"],
+            Pow => &["the exponent", "the base"],
+            Log => &["the base", "the value"],
+            Atan => &["the y-coordinate", "the x-coordinate"],
+            Complex => &["the imaginary part", "the real part"],
+            Len | Shape | First | Reverse | Deshape | Fix | Bits | Transpose | Rise | Fall
+            | Where | Classify | Deduplicate | Unique | Occurrences | Box => ARRAY,
+            Range => &["the range bound"],
+            Parse | Utf | Utf16 | Graphemes | Upper | Lower => &["the string"],
+            Json | Csv | Repr => &["the value"],
+            Binary => &["the format", "the numbers"],
+            Hash => &["the algorithm", "the data"],
+            Normalize => &["the form", "the string"],
+            Split => &["the delimiter", "the string"],
+            Hmac => &["the key", "the message"],
+            Fft => &["the signal"],
+            Det | MatrixInverse | Lu | Qr => &["the matrix"],
+            Solve => &["the matrix", "the right-hand side"],
+            Couple | Join | Table | Cross => TWO_ARRAYS,
+            Select => &["the indices", "the array"],
+            Pick => &["the index", "the array"],
+            Reshape => &["the shape", "the array"],
+            Rerank => &["the rank", "the array"],
+            Take | Drop | Rotate => &["the amount", "the array"],
+            Windows => &["the window size", "the array"],
+            Keep => &["the counts", "the array"],
+            Find | Mask => &["the pattern", "the array to search"],
+            Member | IndexOf | ProgressiveIndexOf => &["the elements", "the array to search"],
+            Union | Intersection | Difference => &["the array", "the other array"],
+            Base => &["the base", "the number"],
+            Reduce | Scan => ARRAY,
+            Group => &["the indices", "the array"],
+            Partition => &["the markers", "the array"],
+            GroupBy | PartitionBy => &["the keys", "the array"],
+            SortBy => &["the directions", "the array"],
+            Assert => &["the message", "the test value"],
+            Wait | Recv | TryRecv => &["the thread id"],
+            Send => &["the value to send", "the thread id"],
+            Gen => &["the seed"],
+            Deal => &["the seed", "the array"],
+            Regex | RegexSplit | RegexNamed => &["the pattern", "the string"],
+            RegexReplace => &["the pattern", "the replacement", "the string"],
+            Map => &["the keys", "the values"],
+            Insert => &["the key", "the value", "the map"],
+            Has | Get | Remove => &["the key", "the map"],
+            Sys(op) => op.arg_names(),
+            _ => &[],
+        }
+    }
+}
+
 macro_rules! impl_primitive {
     ($(
         (
@@ -2054,6 +2737,15 @@ impl_primitive!(
     (1, InvWhere),
     (1(2), InvCouple),
     (1, InvUtf),
+    (1, InvUtf16),
+    (1, InvGraphemes),
+    (1, InvJson),
+    (1, InvCsv),
+    (1(2), InvCsvHeader),
+    (1, InvFft),
+    (2, InvBase),
+    (2, InvBinary),
+    (2, InvSplit),
     (1(2), InvAtan),
     (1(2), InvComplex),
     (1, InvParse),
@@ -2075,6 +2767,7 @@ impl_primitive!(
     (3, Unkeep),
     (3, Unrerank),
     (2, Unreshape),
+    (2, Unwindows),
     (3(2), Unjoin),
     (3[1], Unpartition),
     (3[1], Ungroup),
@@ -2089,6 +2782,11 @@ impl_primitive!(
     (1, SortUp),
     (1, SortDown),
     (1[1], ReduceContent),
+    (1[1], ScanRight),
+    (2[1], ScanExclusive),
+    (2, TakeRange),
+    (2, PickRange),
+    (2[1], WindowedReduce),
     (1, ReplaceRand),
     (2, ReplaceRand2),
 );
@@ -6,7 +6,7 @@ mod defs;
 pub use defs::*;
 
 use std::{
-    borrow::{BorrowMut, Cow},
+    borrow::Cow,
     cell::RefCell,
     f64::{
         consts::{PI, TAU},
@@ -30,9 +30,10 @@ use crate::{
     boxed::Boxed,
     check::instrs_signature,
     lex::AsciiToken,
+    locale::active_locale,
     sys::*,
     value::*,
-    FunctionId, Signature, Uiua, UiuaError, UiuaResult,
+    FunctionId, MemFs, Signature, Uiua, UiuaError, UiuaResult,
 };
 
 /// Categories of primitives
@@ -137,6 +138,15 @@ impl fmt::Display for ImplPrimitive {
             InvAtan => write!(f, "{Un}{Atan}"),
             InvComplex => write!(f, "{Un}{Complex}"),
             InvUtf => write!(f, "{Un}{Utf}"),
+            InvUtf16 => write!(f, "{Un}{Utf16}"),
+            InvGraphemes => write!(f, "{Un}{Graphemes}"),
+            InvJson => write!(f, "{Un}{Json}"),
+            InvCsv => write!(f, "{Un}{Csv}"),
+            InvCsvHeader => write!(f, "{Un}({Csv}{Join})"),
+            InvFft => write!(f, "{Un}{Fft}"),
+            InvBase => write!(f, "{Un}{Base}"),
+            InvBinary => write!(f, "{Un}{Binary}"),
+            InvSplit => write!(f, "{Un}{Split}"),
             InvParse => write!(f, "{Un}{Parse}"),
             InvFix => write!(f, "{Un}{Fix}"),
             InvScan => write!(f, "{Un}{Scan}"),
@@ -158,6 +168,7 @@ impl fmt::Display for ImplPrimitive {
             Unkeep => write!(f, "{Un}{Keep}"),
             Unrerank => write!(f, "{Un}{Rerank}"),
             Unreshape => write!(f, "{Un}{Reshape}"),
+            Unwindows => write!(f, "{Un}{Windows}"),
             Ungroup => write!(f, "{Un}{Group}"),
             Unjoin => write!(f, "{Un}{Join}"),
             FirstMinIndex => write!(f, "{First}{Rise}"),
@@ -171,6 +182,11 @@ impl fmt::Display for ImplPrimitive {
             ReplaceRand => write!(f, "{Gap}{Rand}"),
             ReplaceRand2 => write!(f, "{Gap}{Gap}{Rand}"),
             ReduceContent => write!(f, "{Reduce}{Content}"),
+            ScanRight => write!(f, "{Reverse}{Scan}{Reverse}"),
+            ScanExclusive => write!(f, "{Drop}¯1{Scan}{Join}"),
+            TakeRange => write!(f, "{Take}{Range}"),
+            PickRange => write!(f, "{Pick}{Range}"),
+            WindowedReduce => write!(f, "{Rows}{Reduce}{Windows}"),
             &TransposeN(n) => {
                 if n < 0 {
                     write!(f, "{Un}(")?;
@@ -312,7 +328,73 @@ impl Primitive {
                 | Get
                 | Remove
                 | Bind
+                | Exact
+                | Big
+                | Fft
+                | Base
+                | Binary
+                | Utf16
+                | Graphemes
+                | Upper
+                | Lower
+                | Normalize
+                | Split
+                | Mask
+                | RegexReplace
+                | RegexSplit
+                | RegexNamed
+                | Repr
+                | Json
+                | Csv
+                | Hash
+                | Hmac
+                | Det
+                | MatrixInverse
+                | Solve
+                | Lu
+                | Qr
+                | Breakpoint
+                | GroupBy
+                | PartitionBy
+                | SortBy
+                | ProgressiveIndexOf
+                | Occurrences
+                | Level
+                | Inventory
+                | Seed
+                | Union
+                | Intersection
+                | Difference
                 | Sys(SysOp::FFI)
+                | Sys(SysOp::FfiCallback)
+                | Sys(SysOp::ParquetRead)
+                | Sys(SysOp::ParquetWrite)
+                | Sys(SysOp::ArrowRead)
+                | Sys(SysOp::ArrowWrite)
+        )
+    }
+    /// Check if this primitive is pure
+    ///
+    /// Pure primitives have no side effects and do not depend on interpreter state,
+    /// so they can be run on any thread.
+    pub(crate) fn is_pure(&self) -> bool {
+        use Primitive::*;
+        !matches!(
+            self,
+            Rand | Tag
+                | Now
+                | This
+                | Recur
+                | Spawn
+                | Wait
+                | Send
+                | Recv
+                | TryRecv
+                | Stack
+                | Trace
+                | Dump
+                | Breakpoint
+                | Sys(_)
         )
     }
     /// Check if this primitive is deprecated
@@ -339,14 +421,25 @@ impl Primitive {
         if let Some(prim) = Primitive::non_deprecated().find(|p| p.name() == name) {
             return Some(prim);
         }
+        let locale = active_locale();
+        if let Some(prim) = locale.as_ref().and_then(|locale| locale.primitive(name)) {
+            return Some(prim);
+        }
         if name.len() < 3 {
             return None;
         }
+        let has_glyph = |p: &Primitive| p.glyph().is_some_and(|u| !u.is_ascii());
         let mut matching = Primitive::non_deprecated()
-            .filter(|p| p.glyph().is_some_and(|u| !u.is_ascii()) && p.name().starts_with(name));
+            .filter(|p| has_glyph(p) && p.name().starts_with(name))
+            .chain(locale.iter().flat_map(|locale| {
+                locale
+                    .names()
+                    .filter(|(n, p)| has_glyph(p) && n.starts_with(name))
+                    .map(|(_, p)| p)
+            }));
         let res = matching.next()?;
         let exact_match = res.name() == name;
-        (exact_match || matching.next().is_none()).then_some(res)
+        (exact_match || matching.all(|p| p == res)).then_some(res)
     }
     /// Try to parse multiple primitives from the concatenation of their name prefixes
     pub fn from_format_name_multi(name: &str) -> Option<Vec<(Self, &str)>> {
@@ -456,7 +549,15 @@ impl Primitive {
             Primitive::Max => env.dyadic_oo_00_env(Value::max)?,
             Primitive::Atan => env.dyadic_oo_00_env(Value::atan2)?,
             Primitive::Complex => env.dyadic_oo_00_env(Value::complex)?,
-            Primitive::Match => env.dyadic_rr(|a, b| a == b)?,
+            Primitive::Match if !env.rt.track_mismatches => env.dyadic_rr(|a, b| a == b)?,
+            Primitive::Match => {
+                let a = env.pop(1)?;
+                let b = env.pop(2)?;
+                let matches = a == b;
+                let height = env.rt.stack.len();
+                env.rt.last_mismatch = (!matches).then(|| Box::new((height, a, b)));
+                env.push(matches);
+            }
             Primitive::Join => env.dyadic_oo_env(Value::join)?,
             Primitive::Transpose => env.monadic_mut(Value::transpose)?,
             Primitive::Keep => env.dyadic_ro_env(Value::keep)?,
@@ -464,8 +565,12 @@ impl Primitive {
             Primitive::Drop => env.dyadic_oo_env(Value::drop)?,
             Primitive::Rotate => env.dyadic_ro_env(Value::rotate)?,
             Primitive::Couple => env.dyadic_oo_env(Value::couple)?,
-            Primitive::Rise => env.monadic_ref_env(|v, env| v.rise(env).map(Array::from))?,
-            Primitive::Fall => env.monadic_ref_env(|v, env| v.fall(env).map(Array::from))?,
+            Primitive::Rise => {
+                env.monadic_ref_env(|v, env| v.rise(env).map(|r| Value::from_indices(r.len(), r)))?
+            }
+            Primitive::Fall => {
+                env.monadic_ref_env(|v, env| v.fall(env).map(|f| Value::from_indices(f.len(), f)))?
+            }
             Primitive::Pick => env.dyadic_oo_env(Value::pick)?,
             Primitive::Select => env.dyadic_rr_env(Value::select)?,
             Primitive::Windows => env.dyadic_rr_env(Value::windows)?,
@@ -473,16 +578,62 @@ impl Primitive {
             Primitive::Classify => env.monadic_ref(Value::classify)?,
             Primitive::Deduplicate => env.monadic_mut(Value::deduplicate)?,
             Primitive::Unique => env.monadic_ref(Value::unique)?,
+            Primitive::Occurrences => env.monadic_ref(Value::occurrences)?,
             Primitive::Member => env.dyadic_rr_env(Value::member)?,
             Primitive::Find => env.dyadic_rr_env(Value::find)?,
+            Primitive::Mask => env.dyadic_rr_env(Value::mask)?,
+            Primitive::Union => env.dyadic_rr_env(Value::union)?,
+            Primitive::Intersection => env.dyadic_rr_env(Value::intersection)?,
+            Primitive::Difference => env.dyadic_rr_env(Value::difference)?,
+            Primitive::Base => env.dyadic_rr_env(Value::base)?,
             Primitive::IndexOf => env.dyadic_rr_env(Value::index_of)?,
-            // Primitive::ProgressiveIndexOf => env.dyadic_rr_env(Value::progressive_index_of)?,
+            Primitive::ProgressiveIndexOf => env.dyadic_rr_env(Value::progressive_index_of)?,
             Primitive::Box => {
                 let val = env.pop(1)?;
                 env.push(Boxed(val));
             }
             Primitive::Parse => env.monadic_ref_env(Value::parse_num)?,
+            Primitive::Repr => env.monadic_ref(Value::repr_code)?,
             Primitive::Utf => env.monadic_ref_env(Value::utf8)?,
+            Primitive::Utf16 => env.monadic_ref_env(Value::utf16)?,
+            Primitive::Graphemes => env.monadic_ref_env(Value::graphemes)?,
+            Primitive::Upper => env.monadic_ref_env(Value::upper)?,
+            Primitive::Lower => env.monadic_ref_env(Value::lower)?,
+            Primitive::Normalize => env.dyadic_rr_env(Value::normalize)?,
+            Primitive::Split => env.dyadic_rr_env(Value::split)?,
+            Primitive::Json => env.monadic_ref_env(Value::json)?,
+            Primitive::Csv => env.monadic_ref_env(Value::csv)?,
+            Primitive::Binary => env.dyadic_rr_env(Value::binary)?,
+            Primitive::Hash => env.dyadic_rr_env(Value::hash_bytes)?,
+            Primitive::Hmac => env.dyadic_rr_env(Value::hmac)?,
+            Primitive::Fft => env.monadic_ref_env(Value::fft)?,
+            #[cfg(feature = "linalg")]
+            Primitive::Det => env.monadic_ref_env(Value::det)?,
+            #[cfg(feature = "linalg")]
+            Primitive::MatrixInverse => env.monadic_ref_env(Value::matrix_inverse)?,
+            #[cfg(feature = "linalg")]
+            Primitive::Solve => env.dyadic_rr_env(Value::solve)?,
+            #[cfg(feature = "linalg")]
+            Primitive::Lu => {
+                let matrix = env.pop(1)?;
+                let (p, l, u) = matrix.lu(env)?;
+                env.push(u);
+                env.push(l);
+                env.push(p);
+            }
+            #[cfg(feature = "linalg")]
+            Primitive::Qr => {
+                let matrix = env.pop(1)?;
+                let (q, r) = matrix.qr(env)?;
+                env.push(r);
+                env.push(q);
+            }
+            #[cfg(not(feature = "linalg"))]
+            Primitive::Det
+            | Primitive::MatrixInverse
+            | Primitive::Solve
+            | Primitive::Lu
+            | Primitive::Qr => return Err(env.error("Linear algebra support is not enabled")),
             Primitive::Range => env.monadic_ref_env(Value::range)?,
             Primitive::Reverse => env.monadic_mut(Value::reverse)?,
             Primitive::Deshape => env.monadic_mut(Value::deshape)?,
@@ -496,6 +647,7 @@ impl Primitive {
                     Array::shape,
                     Array::shape,
                     Array::shape,
+                    Array::shape,
                 )
                 .iter()
                 .copied()
@@ -507,10 +659,15 @@ impl Primitive {
             Primitive::Fold => reduce::fold(env)?,
             Primitive::Each => zip::each(env)?,
             Primitive::Rows => zip::rows(env)?,
+            Primitive::Level => zip::level(env)?,
+            Primitive::Inventory => zip::inventory(env)?,
             Primitive::Table | Primitive::Cross => table::table(env)?,
             Primitive::Repeat => loops::repeat(env)?,
             Primitive::Do => loops::do_(env)?,
             Primitive::Group => loops::group(env)?,
+            Primitive::GroupBy => loops::group_by(env)?,
+            Primitive::PartitionBy => loops::partition_by(env)?,
+            Primitive::SortBy => loops::sort_by(env)?,
             Primitive::Partition => loops::partition(env)?,
             Primitive::Reshape => {
                 let shape = env.pop(1)?;
@@ -602,6 +759,9 @@ impl Primitive {
                 let f_args = f.signature().args;
                 let backup = env.clone_stack_top(f_args);
                 if let Err(e) = env.call_clean_stack(f) {
+                    if e.is_cancelled() {
+                        return Err(e);
+                    }
                     env.rt
                         .backend
                         .save_error_color(e.message(), e.report().to_string());
@@ -613,9 +773,13 @@ impl Primitive {
                 }
             }
             Primitive::Assert => {
+                let mismatch = env.rt.last_mismatch.take();
                 let msg = env.pop(1)?;
                 let cond = env.pop(2)?;
                 if !cond.as_nat(env, "").is_ok_and(|n| n == 1) {
+                    // Only keep the mismatch if it is the condition
+                    let height = env.rt.stack.len();
+                    env.rt.last_mismatch = mismatch.filter(|mismatch| mismatch.0 == height);
                     return Err(UiuaError::Throw(
                         msg.into(),
                         env.span().clone(),
@@ -623,7 +787,12 @@ impl Primitive {
                     ));
                 }
             }
-            Primitive::Rand => env.push(random()),
+            Primitive::Rand => env.push(env.rand()),
+            Primitive::Seed => {
+                let f = env.pop_function()?;
+                let seed = env.pop("seed")?.as_num(env, "Seed must be a number")?;
+                env.with_rng_seed(seed, |env| env.call(f))?;
+            }
             Primitive::Gen => {
                 let seed = env.pop(1)?;
                 let mut rng =
@@ -656,30 +825,25 @@ impl Primitive {
                 for i in 0..sig.args {
                     args.push(env.pop(i + 1)?);
                 }
-                let mut memo = env.rt.memo.get_or_default().borrow_mut();
-                if let Some(f_memo) = memo.get_mut(&f.id) {
-                    if let Some(outputs) = f_memo.get(&args) {
-                        let outputs = outputs.clone();
-                        drop(memo);
-                        for val in outputs {
-                            env.push(val);
-                        }
-                        return Ok(());
+                if let Some(outputs) = env.memo_get(&f, &args) {
+                    for val in outputs {
+                        env.push(val);
                     }
+                    return Ok(());
                 }
-                drop(memo);
                 for arg in args.iter().rev() {
                     env.push(arg.clone());
                 }
                 let id = f.id.clone();
                 env.call(f)?;
                 let outputs = env.clone_stack_top(sig.outputs);
-                let mut memo = env.rt.memo.get_or_default().borrow_mut();
-                memo.borrow_mut()
-                    .entry(id)
-                    .or_default()
-                    .insert(args, outputs.clone());
+                env.memo_insert(id, args, outputs);
             }
+            Primitive::Exact => algorithm::exact::run::<algorithm::exact::Rational>(*self, env)?,
+            #[cfg(feature = "num-bigint")]
+            Primitive::Big => algorithm::exact::run::<num_bigint::BigInt>(*self, env)?,
+            #[cfg(not(feature = "num-bigint"))]
+            Primitive::Big => return Err(env.error("Big integer support is not enabled")),
             Primitive::Comptime => {
                 return Err(env.error("Comptime was not inlined. This is a bug in the interpreter"));
             }
@@ -704,7 +868,7 @@ impl Primitive {
                 let id = env.pop(1)?;
                 env.try_recv(id)?;
             }
-            Primitive::Now => env.push(instant::now() / 1000.0),
+            Primitive::Now => env.push(env.rt.backend.now()),
             Primitive::Rectify => {
                 let f = env.pop_function()?;
                 env.call(f)?;
@@ -721,39 +885,43 @@ impl Primitive {
                 env.call(f)?;
             }
             Primitive::Insert => {
-                let key = env.pop("key")?;
-                let val = env.pop("value")?;
-                let mut map = env.pop("map")?;
+                let key = env.pop(1)?;
+                let val = env.pop(2)?;
+                let mut map = env.pop(3)?;
                 map.insert(key, val, env)?;
                 env.push(map);
             }
             Primitive::Has => {
-                let key = env.pop("key")?;
-                let map = env.pop("map")?;
+                let key = env.pop(1)?;
+                let map = env.pop(2)?;
                 env.push(map.has_key(&key, env)?);
             }
             Primitive::Get => {
-                let key = env.pop("key")?;
-                let map = env.pop("map")?;
+                let key = env.pop(1)?;
+                let map = env.pop(2)?;
                 let val = map.get(&key, env)?;
                 env.push(val);
             }
             Primitive::Remove => {
-                let key = env.pop("key")?;
-                let mut map = env.pop("map")?;
+                let key = env.pop(1)?;
+                let mut map = env.pop(2)?;
                 map.remove(key, env)?;
                 env.push(map);
             }
             Primitive::Map => {
-                let keys = env.pop("keys")?;
-                let vals = env.pop("values")?;
+                let keys = env.pop(1)?;
+                let vals = env.pop(2)?;
                 let map = keys.map(vals, env)?;
                 env.push(map);
             }
             Primitive::Trace => trace(env, false)?,
             Primitive::Stack => stack(env, false)?,
             Primitive::Dump => dump(env, false)?,
-            Primitive::Regex => regex(env)?,
+            Primitive::Breakpoint => {}
+            Primitive::Regex
+            | Primitive::RegexReplace
+            | Primitive::RegexSplit
+            | Primitive::RegexNamed => regex(env, *self)?,
             Primitive::Sys(io) => io.run(env)?,
         }
         Ok(())
@@ -761,6 +929,14 @@ impl Primitive {
 }
 
 impl ImplPrimitive {
+    /// Check if this primitive is pure
+    pub(crate) fn is_pure(&self) -> bool {
+        use ImplPrimitive::*;
+        !matches!(
+            self,
+            InvTrace | InvStack | InvDump | ReplaceRand | ReplaceRand2
+        )
+    }
     pub(crate) fn run(&self, env: &mut Uiua) -> UiuaResult {
         match self {
             ImplPrimitive::Asin => env.monadic_env(Value::asin)?,
@@ -795,6 +971,11 @@ impl ImplPrimitive {
                 env.push(vals);
                 env.push(keys);
             }
+            ImplPrimitive::Unwindows => {
+                let size = env.pop(1)?;
+                let windows = env.pop(2)?;
+                env.push(size.unwindows(windows, env)?);
+            }
             ImplPrimitive::Unpick => {
                 let index = env.pop(1)?;
                 let into = env.pop(2)?;
@@ -832,6 +1013,20 @@ impl ImplPrimitive {
             }
             ImplPrimitive::InvWhere => env.monadic_ref_env(Value::inverse_where)?,
             ImplPrimitive::InvUtf => env.monadic_ref_env(Value::inv_utf8)?,
+            ImplPrimitive::InvUtf16 => env.monadic_ref_env(Value::inv_utf16)?,
+            ImplPrimitive::InvGraphemes => env.monadic_ref_env(Value::inv_graphemes)?,
+            ImplPrimitive::InvJson => env.monadic_ref_env(Value::inv_json)?,
+            ImplPrimitive::InvCsv => env.monadic_ref_env(Value::inv_csv)?,
+            ImplPrimitive::InvCsvHeader => {
+                let csv = env.pop(1)?;
+                let (header, records) = csv.inv_csv_header(env)?;
+                env.push(records);
+                env.push(header);
+            }
+            ImplPrimitive::InvFft => env.monadic_ref_env(Value::inverse_fft)?,
+            ImplPrimitive::InvBase => env.dyadic_rr_env(Value::inv_base)?,
+            ImplPrimitive::InvBinary => env.dyadic_rr_env(Value::inv_binary)?,
+            ImplPrimitive::InvSplit => env.dyadic_rr_env(Value::inv_split)?,
             ImplPrimitive::InverseBits => env.monadic_ref_env(Value::inv_bits)?,
             ImplPrimitive::Unpartition => loops::unpartition(env)?,
             ImplPrimitive::Ungroup => loops::ungroup(env)?,
@@ -879,14 +1074,19 @@ impl ImplPrimitive {
             ImplPrimitive::SortUp => env.monadic_mut_env(Value::sort_up)?,
             ImplPrimitive::SortDown => env.monadic_mut_env(Value::sort_down)?,
             ImplPrimitive::ReduceContent => reduce::reduce_content(env)?,
+            ImplPrimitive::ScanRight => reduce::scan_right(env)?,
+            ImplPrimitive::ScanExclusive => reduce::scan_exclusive(env)?,
+            ImplPrimitive::TakeRange => env.dyadic_oo_env(Value::take_range)?,
+            ImplPrimitive::PickRange => env.dyadic_oo_env(Value::pick_range)?,
+            ImplPrimitive::WindowedReduce => reduce::windowed_reduce(env)?,
             ImplPrimitive::ReplaceRand => {
                 env.pop(1)?;
-                env.push(random());
+                env.push(env.rand());
             }
             ImplPrimitive::ReplaceRand2 => {
                 env.pop(1)?;
                 env.pop(2)?;
-                env.push(random());
+                env.push(env.rand());
             }
             &ImplPrimitive::TransposeN(n) => env.monadic_mut(|val| val.transpose_depth(0, n))?,
         }
@@ -895,12 +1095,12 @@ impl ImplPrimitive {
 }
 
 #[cfg(not(feature = "regex"))]
-fn regex(env: &mut Uiua) -> UiuaResult {
+fn regex(env: &mut Uiua, _prim: Primitive) -> UiuaResult {
     Err(env.error("Regex support is not enabled"))
 }
 
 #[cfg(feature = "regex")]
-fn regex(env: &mut Uiua) -> UiuaResult {
+fn regex(env: &mut Uiua, prim: Primitive) -> UiuaResult {
     use std::collections::HashMap;
 
     use ecow::EcoVec;
@@ -910,8 +1110,13 @@ fn regex(env: &mut Uiua) -> UiuaResult {
         pub static REGEX_CACHE: RefCell<HashMap<String, Regex>> = RefCell::new(HashMap::new());
     }
     let pattern = env.pop(1)?.as_string(env, "Pattern must be a string")?;
+    let replacement = if prim == Primitive::RegexReplace {
+        Some(env.pop(2)?.as_string(env, "Replacement must be a string")?)
+    } else {
+        None
+    };
     let target = env
-        .pop(1)?
+        .pop(if replacement.is_some() { 3 } else { 2 })?
         .as_string(env, "Matching target must be a string")?;
     REGEX_CACHE.with(|cache| -> UiuaResult {
         let mut cache = cache.borrow_mut();
@@ -923,21 +1128,64 @@ fn regex(env: &mut Uiua) -> UiuaResult {
             cache.entry(pattern.clone()).or_insert(regex.clone())
         };
 
-        let mut matches: Value =
-            Array::<Boxed>::new([0, regex.captures_len()].as_slice(), []).into();
+        let result: Value = match prim {
+            Primitive::RegexReplace => {
+                let replacement = replacement.unwrap();
+                regex
+                    .replace_all(&target, replacement.as_str())
+                    .as_ref()
+                    .into()
+            }
+            Primitive::RegexSplit => {
+                Array::<Boxed>::from_iter(regex.split(&target).map(String::from)).into()
+            }
+            Primitive::RegexNamed => {
+                let names: Vec<&str> = regex.capture_names().flatten().collect();
+                let mut columns = vec![EcoVec::<Boxed>::new(); names.len()];
+                for caps in regex.captures_iter(&target) {
+                    for (name, column) in names.iter().zip(&mut columns) {
+                        let cap = match caps.name(name) {
+                            Some(m) => Boxed(Value::from(m.as_str())),
+                            None => env
+                                .value_fill()
+                                .cloned()
+                                .map(Value::boxed_if_not)
+                                .ok_or_else(|| {
+                                    env.error(format!(
+                                        "Capture group {name:?} did not match. \
+                                    Use {} to provide a default.",
+                                        Primitive::Fill.format()
+                                    ))
+                                })?,
+                        };
+                        column.push(cap);
+                    }
+                }
+                let keys = Array::<Boxed>::from_iter(names.iter().map(|&name| String::from(name)));
+                let values: Array<Boxed> = (columns.into_iter())
+                    .map(|column| Boxed(Array::<Boxed>::from(column).into()))
+                    .collect();
+                Value::from(keys).map(values.into(), env)?
+            }
+            _ => {
+                let mut matches: Value =
+                    Array::<Boxed>::new([0, regex.captures_len()].as_slice(), []).into();
 
-        for caps in regex.captures_iter(&target) {
-            let row: EcoVec<Boxed> = caps
-                .iter()
-                .flat_map(|m| {
-                    m.map(|m| Boxed(Value::from(m.as_str())))
-                        .or_else(|| env.value_fill().cloned().map(Value::boxed_if_not))
-                })
-                .collect();
-            matches.append(row.into(), env)?;
-        }
+                for caps in regex.captures_iter(&target) {
+                    let row: EcoVec<Boxed> = caps
+                        .iter()
+                        .flat_map(|m| {
+                            m.map(|m| Boxed(Value::from(m.as_str())))
+                                .or_else(|| env.value_fill().cloned().map(Value::boxed_if_not))
+                        })
+                        .collect();
+                    matches.append(row.into(), env)?;
+                }
+                matches
+            }
+        };
 
-        env.push(matches);
+        env.push(result);
         Ok(())
     })
 }
@@ -1139,6 +1387,18 @@ impl PrimDoc {
         }
         Cow::Owned(s)
     }
+    /// Get the examples that are suitable to show alongside the short description
+    ///
+    /// Examples that should error or that should not be run automatically are skipped.
+    pub fn featured_examples(&self, max: usize) -> impl Iterator<Item = &PrimExample> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                PrimDocLine::Example(ex) if !ex.should_error() && ex.should_run() => Some(ex),
+                _ => None,
+            })
+            .take(max)
+    }
     pub(crate) fn from_lines(s: &str) -> Self {
         let mut short = Vec::new();
         let mut lines = Vec::new();
@@ -1220,14 +1480,14 @@ impl PrimExample {
     }
     /// Check whether the example should run automatically in certain contexts
     pub fn should_run(&self) -> bool {
-        !["&sl", "&tcpc", "&ast", "&p", "&fwa"]
+        !["&sl", "&tcpc", "&ast", "&p", "&assr", "&rndb"]
             .iter()
             .any(|prim| self.input.contains(prim))
     }
     /// Get the example's output
     pub fn output(&self) -> &Result<Vec<String>, String> {
         self.output.get_or_init(|| {
-            let mut env = Uiua::with_safe_sys();
+            let mut env = example_env();
             match env.run_str(&self.input) {
                 Ok(_) => Ok(env.take_stack().into_iter().map(|val| val.show()).collect()),
                 Err(e) => Err(e
@@ -1244,6 +1504,14 @@ impl PrimExample {
     }
 }
 
+/// Make an environment for running examples
+///
+/// Files are kept in memory, and `example.ua` is already there.
+fn example_env() -> Uiua {
+    let example = example_ua(|ex| ex.clone());
+    Uiua::with_backend(MemFs::new().with_file("example.ua", example))
+}
+
 /// A line in a primitive's documentation
 #[derive(Debug)]
 pub enum PrimDocLine {
@@ -1398,7 +1666,7 @@ mod tests {
                         continue;
                     }
                     println!("{prim} example:\n{}", ex.input);
-                    match Uiua::with_safe_sys().run_str(&ex.input) {
+                    match example_env().run_str(&ex.input) {
                         Ok(mut comp) => {
                             if let Some(diag) = comp.take_diagnostics().into_iter().next() {
                                 if !ex.should_error {
@@ -1419,6 +1687,76 @@ mod tests {
         }
     }
 
+    #[test]
+    fn featured_examples() {
+        let featured = |doc: &PrimDoc, max| -> Vec<String> {
+            doc.featured_examples(max)
+                .map(|ex| ex.input().to_string())
+                .collect()
+        };
+        let doc = PrimDoc::from_lines("Short\nex: 1\nText\nex! ⊢[]\nex: &p 2\nex: 3\n: 4\nex: 5");
+        assert!(featured(&doc, 0).is_empty());
+        assert_eq!(featured(&doc, 1), ["1"]);
+        assert_eq!(featured(&doc, 2), ["1", "3\n4"]);
+        assert_eq!(featured(&doc, 5), ["1", "3\n4", "5"]);
+        let doc = Primitive::First.doc();
+        assert_eq!(featured(doc, 2), ["⊢1_2_3", "⊢[1_2 3_4 5_6]"]);
+        assert_eq!(
+            featured(doc, 3),
+            ["⊢1_2_3", "⊢[1_2 3_4 5_6]", "⊢⇌ [1 8 4 9 2 3]"]
+        );
+    }
+
+    #[test]
+    fn arg_names() {
+        for prim in Primitive::all() {
+            let Some(args) = prim.args() else {
+                continue;
+            };
+            assert_eq!(
+                prim.arg_names().len(),
+                args,
+                "{prim:?} has the wrong number of argument names"
+            );
+        }
+    }
+
+    #[test]
+    fn stack_underflow_messages() {
+        fn message(code: &str) -> String {
+            match Uiua::with_safe_sys().run_str(code) {
+                Ok(_) => panic!("{code:?} did not fail"),
+                Err(UiuaError::Run(message, _)) => message.value,
+                Err(UiuaError::Traced { error, .. }) => match *error {
+                    UiuaError::Run(message, _) => message.value,
+                    error => panic!("unexpected error: {error}"),
+                },
+                Err(error) => panic!("unexpected error: {error}"),
+            }
+        }
+        assert_eq!(
+            message("⇌"),
+            "⇌ reverse expects 1 argument: the array; the stack was empty"
+        );
+        assert_eq!(
+            message("↙ 2"),
+            "↙ take expects 2 arguments: the amount and the array; only 1 was on the stack"
+        );
+        assert_eq!(
+            message("# Experimental!\ninsert 1 2"),
+            "insert expects 3 arguments: the key, the value, and the map; \
+            only 2 were on the stack"
+        );
+        assert_eq!(
+            message("/+"),
+            "/ reduce expects 1 argument besides its function: the array; the stack was empty"
+        );
+        assert_eq!(
+            message("∵⊂ [1 2]"),
+            "∵ each's function expects at least 2 arguments; only 1 was on the stack"
+        );
+    }
+
     #[test]
     fn primitive_from_name() {
         for prim in Primitive::non_deprecated() {
@@ -1845,7 +2183,8 @@ if has ('nvim')
 endif
 
 let b:current_syntax='uiua'
-"##);
+"##
+        );
 
         std::fs::write("uiua.vim", text).expect("Failed to write syntax file");
     }
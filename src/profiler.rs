@@ -0,0 +1,238 @@
+//! Attributing run time to primitives and code locations
+
+use std::{collections::HashMap, fmt::Write};
+
+use crate::{Assembly, CodeSpan, ImplPrimitive, Instr, Primitive, Span};
+
+/// A profiled instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ProfileKey {
+    Prim(Primitive, usize),
+    ImplPrim(ImplPrimitive, usize),
+    Global(usize),
+}
+
+impl ProfileKey {
+    fn from_instr(instr: &Instr) -> Option<Self> {
+        Some(match *instr {
+            Instr::Prim(prim, span) => ProfileKey::Prim(prim, span),
+            Instr::ImplPrim(prim, span) => ProfileKey::ImplPrim(prim, span),
+            Instr::CallGlobal {
+                index, call: true, ..
+            } => ProfileKey::Global(index),
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ProfileNode {
+    key: Option<ProfileKey>,
+    parent: usize,
+    children: HashMap<ProfileKey, usize>,
+    count: usize,
+    total_ms: f64,
+    self_ms: f64,
+}
+
+impl ProfileNode {
+    fn new(key: Option<ProfileKey>, parent: usize) -> Self {
+        ProfileNode {
+            key,
+            parent,
+            children: HashMap::new(),
+            count: 0,
+            total_ms: 0.0,
+            self_ms: 0.0,
+        }
+    }
+}
+
+/// Records the time spent in each instruction as a call tree
+#[derive(Debug, Clone)]
+pub(crate) struct Profiler {
+    nodes: Vec<ProfileNode>,
+    /// The node, start time, and time spent in callees of each running instruction
+    open: Vec<(usize, f64, f64)>,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Profiler {
+            nodes: vec![ProfileNode::new(None, 0)],
+            open: Vec::new(),
+        }
+    }
+}
+
+impl Profiler {
+    /// Start timing an instruction
+    ///
+    /// Returns whether the instruction is profiled
+    pub(crate) fn enter(&mut self, instr: &Instr) -> bool {
+        let Some(key) = ProfileKey::from_instr(instr) else {
+            return false;
+        };
+        let parent = self.open.last().map_or(0, |&(node, ..)| node);
+        let node = if let Some(&node) = self.nodes[parent].children.get(&key) {
+            node
+        } else {
+            let node = self.nodes.len();
+            self.nodes.push(ProfileNode::new(Some(key), parent));
+            self.nodes[parent].children.insert(key, node);
+            node
+        };
+        self.open.push((node, instant::now(), 0.0));
+        true
+    }
+    /// Stop timing the most recently entered instruction
+    pub(crate) fn exit(&mut self) {
+        let Some((node, start, callee_ms)) = self.open.pop() else {
+            return;
+        };
+        let elapsed = instant::now() - start;
+        let node = &mut self.nodes[node];
+        node.count += 1;
+        node.total_ms += elapsed;
+        node.self_ms += elapsed - callee_ms;
+        if let Some((.., parent_callee_ms)) = self.open.last_mut() {
+            *parent_callee_ms += elapsed;
+        }
+    }
+    /// Resolve the recorded call tree into a [`Profile`]
+    pub(crate) fn finish(&self, asm: &Assembly) -> Profile {
+        let span = |index: usize| match &asm.spans[index] {
+            Span::Code(span) => Some(span.clone()),
+            Span::Builtin => None,
+        };
+        let entries = (self.nodes.iter().enumerate().skip(1))
+            .map(|(i, node)| {
+                let (name, span) = match node.key.unwrap() {
+                    ProfileKey::Prim(prim, s) => (prim.format().to_string(), span(s)),
+                    ProfileKey::ImplPrim(prim, s) => (prim.to_string(), span(s)),
+                    ProfileKey::Global(index) => {
+                        let span = asm.bindings[index].span.clone();
+                        let name = (span.as_ref())
+                            .map(|span| span.as_str(&asm.inputs, |s| s.to_string()))
+                            .unwrap_or_else(|| format!("binding {index}"));
+                        (name, span)
+                    }
+                };
+                debug_assert!(node.parent < i);
+                ProfileEntry {
+                    name,
+                    span,
+                    parent: node.parent.checked_sub(1),
+                    count: node.count,
+                    total_ms: node.total_ms,
+                    self_ms: node.self_ms,
+                }
+            })
+            .collect();
+        Profile { entries }
+    }
+}
+
+/// A record of where time was spent while running
+///
+/// Entries form a call tree, where each entry is a primitive or function called from a
+/// specific location along a specific call path.
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    entries: Vec<ProfileEntry>,
+}
+
+/// An entry in a [`Profile`]
+#[derive(Debug, Clone)]
+pub struct ProfileEntry {
+    /// The name of the primitive or function
+    pub name: String,
+    /// Where it was called
+    pub span: Option<CodeSpan>,
+    /// The index of the caller's entry
+    pub parent: Option<usize>,
+    /// The number of times it ran
+    pub count: usize,
+    /// The total time in milliseconds, including callees
+    pub total_ms: f64,
+    /// The time in milliseconds, excluding callees
+    pub self_ms: f64,
+}
+
+impl ProfileEntry {
+    fn label(&self) -> String {
+        match &self.span {
+            Some(span) => format!("{} {span}", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+impl Profile {
+    /// Get the call tree entries
+    ///
+    /// Callers always come before their callees.
+    pub fn entries(&self) -> &[ProfileEntry] {
+        &self.entries
+    }
+    /// Format the profile as folded stacks
+    ///
+    /// Each line is a `;`-separated call path followed by its self time in microseconds.
+    /// This is the input format of flamegraph tools.
+    pub fn folded(&self) -> String {
+        let labels: Vec<String> = self.entries.iter().map(|e| e.label()).collect();
+        let mut paths: Vec<String> = Vec::with_capacity(self.entries.len());
+        let mut folded = String::new();
+        for (i, entry) in self.entries.iter().enumerate() {
+            let path = match entry.parent {
+                Some(parent) => format!("{};{}", paths[parent], labels[i]),
+                None => labels[i].clone(),
+            };
+            let micros = (entry.self_ms * 1000.0).round() as u64;
+            if micros > 0 {
+                _ = writeln!(folded, "{path} {micros}");
+            }
+            paths.push(path);
+        }
+        folded
+    }
+    /// Get the `n` locations with the most self time
+    ///
+    /// Entries for the same primitive or function at the same location are merged across
+    /// call paths. Time spent in recursive calls is only counted once in the total.
+    pub fn top(&self, n: usize) -> Vec<ProfileEntry> {
+        let labels: Vec<String> = self.entries.iter().map(|e| e.label()).collect();
+        let mut merged: Vec<ProfileEntry> = Vec::new();
+        let mut indices: HashMap<&str, usize> = HashMap::new();
+        for (i, entry) in self.entries.iter().enumerate() {
+            let mut recursive = false;
+            let mut ancestor = entry.parent;
+            while let Some(a) = ancestor {
+                if labels[a] == labels[i] {
+                    recursive = true;
+                    break;
+                }
+                ancestor = self.entries[a].parent;
+            }
+            let index = *indices.entry(&labels[i]).or_insert_with(|| {
+                merged.push(ProfileEntry {
+                    parent: None,
+                    count: 0,
+                    total_ms: 0.0,
+                    self_ms: 0.0,
+                    ..entry.clone()
+                });
+                merged.len() - 1
+            });
+            let merged = &mut merged[index];
+            merged.count += entry.count;
+            merged.self_ms += entry.self_ms;
+            if !recursive {
+                merged.total_ms += entry.total_ms;
+            }
+        }
+        merged.sort_by(|a, b| b.self_ms.total_cmp(&a.self_ms));
+        merged.truncate(n);
+        merged
+    }
+}
@@ -0,0 +1,156 @@
+//! Python bindings
+//!
+//! Build the `uiua` Python module with `maturin build --release`.
+
+use ecow::EcoVec;
+use numpy::{
+    ndarray::{ArrayViewD, IxDyn},
+    Complex64, Element, PyArray, PyArrayDescr, PyArrayDyn, PyUntypedArray,
+};
+use pyo3::{
+    create_exception,
+    exceptions::{PyException, PyTypeError},
+    prelude::*,
+    types::{PyDict, PyList, PyString, PyTuple},
+};
+
+use crate::{Array, Boxed, Complex, Uiua, Value};
+
+create_exception!(uiua, UiuaError, PyException, "An error in a Uiua program");
+
+/// Run Uiua code with the given inputs
+///
+/// The inputs are pushed so that the first one is on top of the stack.
+/// The result is `None` if the stack is empty at the end, the value if there is one,
+/// and a tuple of the values from the top of the stack down if there are more.
+#[pyfunction]
+#[pyo3(signature = (src, *inputs))]
+fn run(py: Python, src: &str, inputs: &PyTuple) -> PyResult<PyObject> {
+    let mut env = Uiua::with_native_sys();
+    for input in inputs.iter().rev() {
+        env.push(to_value(py, input)?);
+    }
+    (env.run_str(src)).map_err(|e| UiuaError::new_err(e.to_string()))?;
+    let mut stack = env.take_stack();
+    Ok(match stack.len() {
+        0 => py.None(),
+        1 => from_value(py, stack.pop().unwrap())?,
+        _ => {
+            let values = (stack.into_iter().rev())
+                .map(|val| from_value(py, val))
+                .collect::<PyResult<Vec<_>>>()?;
+            PyTuple::new(py, values).into_py(py)
+        }
+    })
+}
+
+/// Convert a Python object to a value
+///
+/// Strings become character arrays.
+/// Anything else is converted with `numpy.asarray`.
+/// Boolean and 8-bit unsigned arrays become byte arrays, other real arrays become number arrays,
+/// and arrays of strings or objects become box arrays.
+fn to_value(py: Python, obj: &PyAny) -> PyResult<Value> {
+    if let Ok(s) = obj.downcast::<PyString>() {
+        return Ok(s.to_str()?.into());
+    }
+    let numpy = py.import("numpy")?;
+    let arr: &PyUntypedArray = numpy.call_method1("asarray", (obj,))?.downcast()?;
+    let shape = arr.shape().to_vec();
+    let dtype = arr.dtype();
+    fn copy<'py, T: Element + Clone>(
+        numpy: &'py PyModule,
+        arr: &'py PyUntypedArray,
+        dtype: &PyArrayDescr,
+    ) -> PyResult<EcoVec<T>> {
+        let arr: &PyArrayDyn<T> = if dtype.is_equiv_to(T::get_dtype(arr.py())) {
+            arr.downcast()?
+        } else {
+            (numpy.call_method1("asarray", (arr, T::get_dtype(arr.py()))))?.downcast()?
+        };
+        Ok(arr.readonly().as_array().iter().cloned().collect())
+    }
+    Ok(match dtype.kind() {
+        b'b' | b'u' if dtype.itemsize() == 1 => {
+            Array::new(shape.as_slice(), copy::<u8>(numpy, arr, dtype)?).into()
+        }
+        b'b' | b'i' | b'u' | b'f' => {
+            Array::new(shape.as_slice(), copy::<f64>(numpy, arr, dtype)?).into()
+        }
+        b'c' => {
+            let data = copy::<Complex64>(numpy, arr, dtype)?;
+            let data: EcoVec<Complex> = data.iter().map(|c| Complex::new(c.re, c.im)).collect();
+            Array::new(shape.as_slice(), data).into()
+        }
+        b'U' | b'O' => {
+            let items: &PyList = arr
+                .call_method0("ravel")?
+                .call_method0("tolist")?
+                .downcast()?;
+            let data = (items.iter())
+                .map(|item| to_value(py, item).map(Boxed))
+                .collect::<PyResult<EcoVec<_>>>()?;
+            Array::new(shape.as_slice(), data).into()
+        }
+        _ => {
+            return Err(PyTypeError::new_err(format!(
+                "Arrays with dtype {dtype} cannot be converted to Uiua values"
+            )))
+        }
+    })
+}
+
+/// The Uiua array whose memory a NumPy array borrows
+#[pyclass]
+struct ArrayOwner(#[allow(dead_code)] Value);
+
+/// Convert a value to a Python object
+///
+/// Number, byte, and index arrays become read-only NumPy arrays that share the value's memory.
+/// Strings become Python strings, and box arrays and higher-rank character arrays become lists.
+fn from_value(py: Python, value: Value) -> PyResult<PyObject> {
+    fn view<T: Element>(py: Python, arr: &Array<T>, owner: Value) -> PyResult<PyObject> {
+        let shape = IxDyn(arr.shape());
+        // Safety: the owner keeps the data alive for as long as the NumPy array
+        let view = unsafe { ArrayViewD::from_shape_ptr(shape, arr.data.as_ptr()) };
+        let owner = PyCell::new(py, ArrayOwner(owner))?;
+        let array = unsafe { PyArrayDyn::borrow_from_array(&view, owner) };
+        // The data may be shared with other values
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("write", false)?;
+        array.call_method("setflags", (), Some(kwargs))?;
+        Ok(array.into_py(py))
+    }
+    Ok(match value {
+        Value::Num(arr) => view(py, &arr, arr.clone().into())?,
+        #[cfg(feature = "bytes")]
+        Value::Byte(arr) => view(py, &arr, arr.clone().into())?,
+        Value::Int(arr) => view(py, &arr, arr.clone().into())?,
+        Value::Complex(arr) => {
+            let data: Vec<Complex64> = arr
+                .data
+                .iter()
+                .map(|c| Complex64::new(c.re, c.im))
+                .collect();
+            let array = PyArray::from_vec(py, data).reshape(arr.shape().to_vec())?;
+            array.into_py(py)
+        }
+        Value::Char(arr) if arr.rank() <= 1 => arr.data.iter().collect::<String>().into_py(py),
+        Value::Box(arr) if arr.rank() == 0 => from_value(py, arr.data[0].0.clone())?,
+        value => {
+            let rows = (value.rows())
+                .map(|row| from_value(py, row))
+                .collect::<PyResult<Vec<_>>>()?;
+            PyList::new(py, rows).into_py(py)
+        }
+    })
+}
+
+/// The Uiua Python module
+#[pymodule]
+fn uiua(py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(run, m)?)?;
+    m.add("UiuaError", py.get_type::<UiuaError>())?;
+    m.add("__version__", crate::VERSION)?;
+    Ok(())
+}
@@ -1,24 +1,39 @@
 use std::{
     cell::RefCell,
     cmp::Ordering,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fmt,
     hash::Hash,
+    io::{self, BufRead, BufReader, Read},
     mem::{replace, size_of, take},
     panic::{catch_unwind, AssertUnwindSafe},
     path::{Path, PathBuf},
     str::FromStr,
-    sync::Arc,
+    sync::{
+        atomic::{self, AtomicBool, AtomicUsize},
+        Arc,
+    },
 };
 
-use crossbeam_channel::{Receiver, Sender, TryRecvError};
+use crossbeam_channel::{Receiver, RecvError, SendError, Sender, TryRecvError, TrySendError};
 use enum_iterator::{all, Sequence};
 use instant::Duration;
+use parking_lot::Mutex;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder, Yield};
 use thread_local::ThreadLocal;
 
 use crate::{
-    algorithm, array::Array, boxed::Boxed, check::instrs_temp_signatures, function::*, lex::Span,
-    value::Value, Assembly, Compiler, Complex, Global, Ident, Inputs, IntoSysBackend, Primitive,
+    algorithm,
+    array::Array,
+    boxed::Boxed,
+    check::instrs_temp_signatures,
+    function::*,
+    lex::{lex, AsciiToken, CodeSpan, Span, Token},
+    profiler::{Profile, Profiler},
+    random,
+    value::Value,
+    Assembly, Compiler, Complex, Global, Handle, Ident, Inputs, IntoSysBackend, Limit, Primitive,
     SafeSys, SysBackend, SysOp, TraceFrame, UiuaError, UiuaResult,
 };
 
@@ -27,7 +42,9 @@ use crate::{
 pub struct Uiua {
     pub(crate) rt: Runtime,
     /// The compiled assembly
-    pub asm: Assembly,
+    ///
+    /// It is shared with the threads that run functions in parallel
+    pub asm: Arc<Assembly>,
 }
 
 /// Runtime-only data
@@ -46,7 +63,7 @@ pub(crate) struct Runtime {
     /// The call stack
     call_stack: Vec<StackFrame>,
     /// The recur stack
-    this_stack: Vec<usize>,
+    this_stack: Vec<Function>,
     /// The fill stack
     fill_stack: Vec<Value>,
     /// The locals stack
@@ -55,6 +72,22 @@ pub(crate) struct Runtime {
     pub unpack_boxes: bool,
     /// A limit on the execution duration in milliseconds
     execution_limit: Option<f64>,
+    /// The handle for cancelling execution
+    cancel: CancelHandle,
+    /// The maximum number of instructions to execute
+    instruction_limit: Option<usize>,
+    /// The number of instructions executed since execution started
+    ///
+    /// This is shared with the workers of parallel calls.
+    instruction_count: Arc<AtomicUsize>,
+    /// The maximum number of bytes of values on the stacks
+    memory_limit: Option<usize>,
+    /// An upper bound on the bytes of values on the stacks
+    ///
+    /// This grows as values are pushed, and is only measured exactly when it exceeds the limit.
+    memory_estimate: usize,
+    /// The maximum depth of the call stack
+    recursion_limit: Option<usize>,
     /// The time at which execution started
     execution_start: f64,
     /// Whether to print the time taken to execute each instruction
@@ -73,9 +106,98 @@ pub(crate) struct Runtime {
     pub(crate) output_comments: HashMap<usize, Vec<Vec<Value>>>,
     /// Memoized values
     pub(crate) memo: Arc<ThreadLocal<RefCell<MemoMap>>>,
+    /// The maximum number of cached calls for each memoized function
+    memo_limit: Option<usize>,
+    /// Memoized values loaded from a file, not yet checked against the program
+    loaded_memo: Option<Box<LoadedMemo>>,
+    /// A hash of the program, computed when a memoized function is first called
+    memo_program: Option<u64>,
+    /// The seeded random number generator, if any
+    rng: Option<RefCell<SmallRng>>,
+    /// The thread pool for running pure functions in parallel
+    thread_pool: Option<Arc<ThreadPool>>,
+    /// The thread pool that spawned threads run on, if any
+    spawn_pool: Option<Arc<ThreadPool>>,
+    /// The channels shared by all threads
+    channels: Arc<Mutex<Channels>>,
+    /// The functions registered as FFI callbacks with `&ffcb`
+    pub(crate) ffi_callbacks: Vec<Function>,
+    /// The profiler, if profiling is enabled
+    profiler: Option<Profiler>,
+    /// The debugger, if debugging is enabled
+    debugger: Option<DebugState>,
+    /// Whether to record the last values that did not [`Primitive::Match`]
+    pub(crate) track_mismatches: bool,
+    /// The stack height of the result of the last failed [`Primitive::Match`] and its arguments
+    ///
+    /// An [`Primitive::Assert`] keeps it only if it checked that result.
+    pub(crate) last_mismatch: Option<Box<(usize, Value, Value)>>,
+}
+
+type MemoMap = HashMap<FunctionId, MemoCache>;
+
+/// The cached results of calls to a memoized function
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MemoCache {
+    /// The outputs for each set of arguments and when they were last used
+    entries: HashMap<Vec<Value>, (Vec<Value>, u64)>,
+    /// The arguments of each entry, ordered by when they were last used
+    recency: BTreeMap<u64, Vec<Value>>,
+    /// Incremented on every access
+    clock: u64,
+}
+
+impl MemoCache {
+    /// Get the outputs for some arguments, marking them as recently used
+    fn get(&mut self, args: &[Value]) -> Option<Vec<Value>> {
+        let (outputs, used) = self.entries.get_mut(args)?;
+        self.clock += 1;
+        let args = self
+            .recency
+            .remove(used)
+            .expect("memo entry has no recency");
+        *used = self.clock;
+        self.recency.insert(self.clock, args);
+        Some(outputs.clone())
+    }
+    /// Cache the outputs for some arguments
+    ///
+    /// If the cache is full, the least recently used entry is evicted.
+    fn insert(&mut self, args: Vec<Value>, outputs: Vec<Value>, limit: Option<usize>) {
+        if limit == Some(0) {
+            return;
+        }
+        if let Some((_, used)) = self.entries.get(&args) {
+            self.recency.remove(used);
+        } else if limit.is_some_and(|limit| self.entries.len() >= limit) {
+            if let Some((_, oldest)) = self.recency.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.clock += 1;
+        self.recency.insert(self.clock, args.clone());
+        self.entries.insert(args, (outputs, self.clock));
+    }
 }
 
-type MemoMap = HashMap<FunctionId, HashMap<Vec<Value>, Vec<Value>>>;
+/// Memoized values loaded from a file
+#[derive(Clone)]
+struct LoadedMemo {
+    /// A hash of the program that saved the values
+    program: u64,
+    /// The caches, keyed by the name of their function
+    caches: HashMap<String, MemoCache>,
+}
+
+/// Hash a program's instructions so that saved memo caches are only used by the same program
+///
+/// This is FNV-1a over the serialized instructions, so it does not change between runs or builds.
+fn program_hash(asm: &Assembly) -> u64 {
+    let bytes = serde_json::to_vec(&asm.instrs).expect("failed to serialize instructions");
+    (bytes.iter()).fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
 
 impl AsRef<Assembly> for Uiua {
     fn as_ref(&self) -> &Assembly {
@@ -85,7 +207,7 @@ impl AsRef<Assembly> for Uiua {
 
 impl AsMut<Assembly> for Uiua {
     fn as_mut(&mut self) -> &mut Assembly {
-        &mut self.asm
+        Arc::make_mut(&mut self.asm)
     }
 }
 
@@ -109,6 +231,22 @@ struct Channel {
     pub recv: Receiver<Value>,
 }
 
+/// The channels created with `&chan`, shared by all threads
+///
+/// Channel ids are negative so that they are never confused with thread ids.
+#[derive(Debug, Default)]
+struct Channels {
+    next_id: usize,
+    channels: HashMap<usize, SharedChannel>,
+}
+
+#[derive(Debug)]
+struct SharedChannel {
+    /// The sender, which is dropped when the channel is closed
+    send: Option<Sender<Value>>,
+    recv: Receiver<Value>,
+}
+
 #[derive(Debug, Clone)]
 struct ThisThread {
     pub parent: Option<Channel>,
@@ -129,12 +267,78 @@ impl Default for ThisThread {
 #[derive(Debug, Clone)]
 struct Thread {
     #[cfg(not(target_arch = "wasm32"))]
-    pub handle: Arc<std::thread::JoinHandle<UiuaResult<Vec<Value>>>>,
+    pub handle: Arc<ThreadHandle>,
     #[cfg(target_arch = "wasm32")]
     pub result: UiuaResult<Vec<Value>>,
     pub channel: Channel,
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+enum ThreadHandle {
+    /// A thread with its own OS thread
+    Os(std::thread::JoinHandle<UiuaResult<Vec<Value>>>),
+    /// A task on the spawn pool
+    Pooled(Receiver<UiuaResult<Vec<Value>>>, Arc<ThreadPool>),
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ThreadHandle {
+    fn join(self) -> UiuaResult<Vec<Value>> {
+        match self {
+            ThreadHandle::Os(handle) => handle.join().unwrap(),
+            ThreadHandle::Pooled(recv, pool) => {
+                recv_yielding(&recv, Some(&pool)).expect("Pooled thread panicked")
+            }
+        }
+    }
+}
+
+/// Run other tasks of a pool while the current one waits
+///
+/// Returns `false` if the current thread is not in the pool, so it should block instead.
+fn yield_to(pool: Option<&ThreadPool>) -> bool {
+    match pool.and_then(ThreadPool::yield_now) {
+        Some(Yield::Executed) => true,
+        Some(Yield::Idle) => {
+            std::thread::yield_now();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Receive from a channel, running other tasks of a pool while waiting
+///
+/// A pooled task that blocks holds its worker thread,
+/// so tasks waiting on each other could otherwise use up a small pool.
+fn recv_yielding<T>(recv: &Receiver<T>, pool: Option<&ThreadPool>) -> Result<T, RecvError> {
+    loop {
+        match recv.try_recv() {
+            Ok(val) => return Ok(val),
+            Err(TryRecvError::Disconnected) => return Err(RecvError),
+            Err(TryRecvError::Empty) if !yield_to(pool) => return recv.recv(),
+            Err(TryRecvError::Empty) => {}
+        }
+    }
+}
+
+/// Send to a channel, running other tasks of a pool while it is full
+fn send_yielding<T>(
+    send: &Sender<T>,
+    mut val: T,
+    pool: Option<&ThreadPool>,
+) -> Result<(), SendError<T>> {
+    loop {
+        match send.try_send(val) {
+            Ok(()) => return Ok(()),
+            Err(TrySendError::Disconnected(v)) => return Err(SendError(v)),
+            Err(TrySendError::Full(v)) if !yield_to(pool) => return send.send(v),
+            Err(TrySendError::Full(v)) => val = v,
+        }
+    }
+}
+
 impl Default for Uiua {
     fn default() -> Self {
         Self::with_safe_sys()
@@ -167,6 +371,97 @@ impl FromStr for RunMode {
     }
 }
 
+/// The results of [`Uiua::run_asm_tests`]
+#[derive(Debug, Default)]
+pub struct TestResults {
+    /// The number of lines with assertions and `Test*` functions that passed
+    pub passed: usize,
+    /// The lines that failed
+    pub failures: Vec<TestFailure>,
+}
+
+/// A line that failed in [`Uiua::run_asm_tests`]
+#[derive(Debug)]
+pub struct TestFailure {
+    /// The error
+    pub error: UiuaError,
+    /// The values of the failed [`Primitive::Match`] that the failed assertion checked
+    ///
+    /// The first value is the one that was on top of the stack.
+    pub mismatch: Option<(Value, Value)>,
+}
+
+/// A handle for cancelling execution from another thread
+///
+/// See [`Uiua::cancel_handle`]
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle(Arc<AtomicBool>);
+
+impl CancelHandle {
+    /// Cancel the current run
+    ///
+    /// If nothing is running, the next run is cancelled.
+    pub fn cancel(&self) {
+        self.0.store(true, atomic::Ordering::Relaxed);
+    }
+    /// Check if cancellation has been requested but not yet handled
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(atomic::Ordering::Relaxed)
+    }
+}
+
+/// A hook for pausing execution
+///
+/// See [`Uiua::with_debugger`]
+pub trait Debugger: Send + Sync {
+    /// Called when execution pauses before an instruction
+    ///
+    /// The returned action determines how execution continues.
+    fn pause(&self, env: &Uiua, pause: &DebugPause) -> DebugAction;
+}
+
+/// Where execution paused in a [`Debugger`]
+#[derive(Debug, Clone)]
+pub struct DebugPause {
+    /// The span of the instruction about to run, if it has one
+    pub span: Option<CodeSpan>,
+    /// A description of the instruction about to run
+    pub instr: String,
+    /// The number of function calls deep execution is
+    pub call_depth: usize,
+    /// Whether execution paused because of a breakpoint rather than stepping
+    pub breakpoint: bool,
+}
+
+/// What to do after a [`Debugger`] pauses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugAction {
+    /// Run the instruction and pause before the next one
+    Step,
+    /// Run until the next breakpoint
+    Continue,
+    /// Stop execution with an error
+    Abort,
+}
+
+#[derive(Clone)]
+struct DebugState {
+    hook: Arc<dyn Debugger>,
+    breakpoints: Vec<(usize, Option<usize>)>,
+    stepping: bool,
+    /// The line of the last instruction with a span
+    last_line: Option<usize>,
+}
+
+/// The timing of a top-level line in [`Uiua::run_asm_bench`]
+#[derive(Debug, Clone)]
+pub struct BenchTiming {
+    /// The span of the line, if it has one
+    pub span: Option<CodeSpan>,
+    /// The duration of each run in milliseconds
+    pub samples: Vec<f64>,
+}
+
 impl Default for Runtime {
     fn default() -> Self {
         Runtime {
@@ -193,10 +488,28 @@ impl Default for Runtime {
             cli_arguments: Vec::new(),
             cli_file_path: PathBuf::new(),
             execution_limit: None,
+            cancel: CancelHandle::default(),
+            instruction_limit: None,
+            instruction_count: Arc::default(),
+            memory_limit: None,
+            memory_estimate: 0,
+            recursion_limit: None,
             execution_start: 0.0,
             thread: ThisThread::default(),
             output_comments: HashMap::new(),
             memo: Arc::new(ThreadLocal::new()),
+            memo_limit: None,
+            loaded_memo: None,
+            memo_program: None,
+            rng: None,
+            thread_pool: None,
+            spawn_pool: None,
+            channels: Arc::default(),
+            ffi_callbacks: Vec::new(),
+            profiler: None,
+            debugger: None,
+            track_mismatches: false,
+            last_mismatch: None,
         }
     }
 }
@@ -213,17 +526,20 @@ impl Uiua {
     }
     /// Create a new Uiua runtime with a custom IO backend
     pub fn with_backend(backend: impl IntoSysBackend) -> Self {
+        let backend = backend.into_sys_backend();
+        let rng = (backend.rng_seed()).map(|seed| RefCell::new(SmallRng::seed_from_u64(seed)));
         Uiua {
             rt: Runtime {
-                backend: backend.into_sys_backend(),
+                backend,
+                rng,
                 ..Runtime::default()
             },
-            asm: Assembly::default(),
+            asm: Arc::default(),
         }
     }
     /// Build an assembly
     pub fn build(self) -> Assembly {
-        self.asm
+        Arc::unwrap_or_clone(self.asm)
     }
     /// Get a reference to the system backend
     pub fn backend(&self) -> &dyn SysBackend {
@@ -249,11 +565,130 @@ impl Uiua {
         self.rt.time_instrs = time_instrs;
         self
     }
+    /// Set whether to record the time spent in each primitive and function call
+    ///
+    /// The results can be retrieved with [`Uiua::take_profile`]
+    pub fn profile(mut self, profile: bool) -> Self {
+        self.rt.profiler = profile.then(Profiler::default);
+        self
+    }
+    /// Take the results of profiling
+    ///
+    /// Returns `None` if profiling is not enabled. Profiling continues with a new record.
+    pub fn take_profile(&mut self) -> Option<Profile> {
+        let profiler = self.rt.profiler.replace(Profiler::default())?;
+        Some(profiler.finish(&self.asm))
+    }
+    /// Set a debugger to pause execution at breakpoints
+    ///
+    /// Execution pauses before [`Primitive::Breakpoint`] and before the instructions at
+    /// the given breakpoints, which are 1-based lines and optional columns.
+    /// A breakpoint without a column pauses when execution enters its line.
+    /// If `start_paused` is true, execution also pauses before the first instruction.
+    pub fn with_debugger(
+        mut self,
+        debugger: impl Debugger + 'static,
+        breakpoints: impl IntoIterator<Item = (usize, Option<usize>)>,
+        start_paused: bool,
+    ) -> Self {
+        self.rt.debugger = Some(DebugState {
+            hook: Arc::new(debugger),
+            breakpoints: breakpoints.into_iter().collect(),
+            stepping: start_paused,
+            last_line: None,
+        });
+        self
+    }
     /// Limit the execution duration
     pub fn with_execution_limit(mut self, limit: Duration) -> Self {
         self.rt.execution_limit = Some(limit.as_millis() as f64);
         self
     }
+    /// Get a handle for cancelling execution from another thread
+    ///
+    /// Cancelling stops the current run before its next instruction with a
+    /// [`UiuaError::Cancelled`] error, which [`try ⍣`](Primitive::Try) does not catch.
+    /// The environment can be used again afterwards.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.rt.cancel.clone()
+    }
+    /// Limit the number of instructions executed
+    ///
+    /// Exceeding the limit produces a [`UiuaError::LimitExceeded`] error.
+    /// Each thread started by [`spawn`](Primitive::Spawn) has its own count.
+    pub fn with_instruction_limit(mut self, limit: usize) -> Self {
+        self.rt.instruction_limit = Some(limit);
+        self
+    }
+    /// Limit the memory used by values, in bytes
+    ///
+    /// The sizes of values are added up as they are pushed onto the stacks.
+    /// Only when that sum exceeds the limit is the total size of the values still on the stacks
+    /// measured, so the limit costs little to check.
+    /// Exceeding it produces a [`UiuaError::LimitExceeded`] error.
+    /// Values that share data are counted separately, and bound values are not counted.
+    pub fn with_memory_limit(mut self, bytes: usize) -> Self {
+        self.rt.memory_limit = Some(bytes);
+        self
+    }
+    /// Limit the number of calls cached for each [`memo`](Primitive::Memo)ized function
+    ///
+    /// When a cache is full, the least recently used call is evicted.
+    pub fn with_memo_limit(mut self, limit: usize) -> Self {
+        self.rt.memo_limit = Some(limit);
+        self
+    }
+    /// Limit the depth of nested function calls
+    ///
+    /// Exceeding the limit produces a [`UiuaError::LimitExceeded`] error with a trace of the
+    /// calls, which can be caught with [`try ⍣`](Primitive::Try).
+    /// Tail calls do not add to the depth.
+    ///
+    /// Deep calls use the native stack, so a limit that is too high may still overflow it.
+    /// By default, there is no limit.
+    pub fn with_recursion_limit(mut self, limit: usize) -> Self {
+        self.rt.recursion_limit = Some(limit);
+        self
+    }
+    /// Run calls to pure functions in [`rows ≡`](Primitive::Rows), [`each ∵`](Primitive::Each),
+    /// and large [`table ⊞`](Primitive::Table)s in parallel on a pool of the given number of threads
+    ///
+    /// A function is pure if it has no side effects, like IO or random numbers.
+    /// If the pool cannot be created, calls are run on the current thread.
+    pub fn with_thread_pool(mut self, threads: usize) -> Self {
+        self.rt.thread_pool = ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .ok()
+            .map(Arc::new);
+        self
+    }
+    /// Run threads created with [`spawn`](Primitive::Spawn) on a pool of at most the given number of threads
+    ///
+    /// By default, each spawned thread is a new OS thread.
+    /// The pool steals work, so spawning many small tasks is cheap.
+    /// A limit of `0` returns to spawning OS threads.
+    pub fn with_spawn_limit(mut self, threads: usize) -> Self {
+        self.set_spawn_limit(threads);
+        self
+    }
+    /// Get the maximum number of threads that spawned threads run on
+    ///
+    /// Returns `None` if each spawned thread is a new OS thread.
+    pub fn spawn_limit(&self) -> Option<usize> {
+        (self.rt.spawn_pool.as_ref()).map(|pool| pool.current_num_threads())
+    }
+    pub(crate) fn set_spawn_limit(&mut self, threads: usize) {
+        self.rt.spawn_pool = if threads == 0 {
+            None
+        } else {
+            ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .ok()
+                .map(Arc::new)
+        };
+    }
     /// Set the command line arguments
     pub fn with_args(mut self, args: Vec<String>) -> Self {
         self.rt.cli_arguments = args;
@@ -298,12 +733,78 @@ impl Uiua {
     pub fn run_file<P: AsRef<Path>>(&mut self, path: P) -> UiuaResult<Compiler> {
         self.compile_run(|comp| comp.load_file(path))
     }
+    /// Run an assembly that was compiled to the `.uasm` format with [`Assembly::to_uasm`]
+    ///
+    /// The code is not reparsed or recompiled
+    pub fn load_compiled(&mut self, bytes: &[u8]) -> UiuaResult<Assembly> {
+        let asm = Assembly::from_uasm(bytes).map_err(|e| {
+            UiuaError::Load(
+                "<assembly>".into(),
+                io::Error::new(io::ErrorKind::InvalidData, e).into(),
+            )
+        })?;
+        self.run_asm(asm)
+    }
+    /// Run Uiua code read incrementally from a reader
+    ///
+    /// Each top-level item is compiled and run as soon as its last line has been read,
+    /// so code that arrives over time, like from stdin, runs before the reader is exhausted.
+    /// The only exception is an item that ends in a multiline string,
+    /// which runs once the next line shows that the string does not continue.
+    /// The source of every item is kept for error reporting.
+    /// Bindings from earlier items are visible to later ones.
+    ///
+    /// Large data should not be part of the code. Pass it through [`Uiua::open_reader`] instead.
+    ///
+    /// If an error occurs, items that have already run keep their side effects.
+    pub fn run_reader(&mut self, reader: impl Read) -> UiuaResult<Compiler> {
+        let mut comp = Compiler::with_backend(self.rt.backend.clone());
+        let mut chunk = String::new();
+        let mut state = ChunkState::default();
+        for line in BufReader::new(reader).lines() {
+            let line = line.map_err(|e| UiuaError::Load("<reader>".into(), e.into()))?;
+            // Consecutive multiline string lines form a single string
+            if state.ends_in_multiline_string && !line.trim_start().starts_with('$') {
+                self.run_chunk(&mut comp, &chunk)?;
+                chunk.clear();
+                state = ChunkState::default();
+            }
+            state.push_line(&line);
+            chunk.push_str(&line);
+            chunk.push('\n');
+            if state.is_complete() && !state.ends_in_multiline_string {
+                self.run_chunk(&mut comp, &chunk)?;
+                chunk.clear();
+                state = ChunkState::default();
+            }
+        }
+        if !chunk.is_empty() {
+            self.run_chunk(&mut comp, &chunk)?;
+        }
+        Ok(comp)
+    }
+    fn run_chunk(&mut self, comp: &mut Compiler, chunk: &str) -> UiuaResult {
+        comp.load_str(chunk)?;
+        let mut asm = self.run_asm(comp.finish())?;
+        asm.remove_top_level();
+        *comp.assembly_mut() = asm;
+        Ok(())
+    }
+    /// Open a stream that reads data from a reader
+    ///
+    /// Push the returned handle to read the data in chunks with `&rs`, `&rb`, or `&ru`,
+    /// so it never has to be in memory all at once.
+    /// The stream should be closed with `&cl` when it is no longer needed.
+    pub fn open_reader(&self, reader: impl Read + Send + Sync + 'static) -> UiuaResult<Handle> {
+        (self.rt.backend.open_reader(Box::new(reader))).map_err(|e| self.error(e))
+    }
     /// Run a Uiua assembly
     pub fn run_asm(&mut self, asm: impl Into<Assembly>) -> UiuaResult<Assembly> {
         fn run_asm(env: &mut Uiua, asm: Assembly) -> UiuaResult<Assembly> {
-            env.asm = asm;
+            env.asm = Arc::new(asm);
             env.rt.execution_start = instant::now();
-            let top_slices = take(&mut env.asm.top_slices);
+            env.rt.instruction_count = Arc::default();
+            let top_slices = env.asm.top_slices.clone();
             let mut res = Ok(());
             if let Err(e) = env.catching_crash("", |env| {
                 for &slice in &top_slices {
@@ -315,21 +816,205 @@ impl Uiua {
             }) {
                 res = Err(e);
             }
-            env.asm.top_slices = top_slices;
             if res.is_err() {
-                env.rt = Runtime {
-                    backend: env.rt.backend.clone(),
-                    execution_limit: env.rt.execution_limit,
-                    time_instrs: env.rt.time_instrs,
-                    output_comments: env.rt.output_comments.clone(),
-                    ..Runtime::default()
-                };
+                env.reset_runtime();
             }
-            let asm = take(&mut env.asm);
+            let asm = Arc::unwrap_or_clone(take(&mut env.asm));
             res.map(|_| asm)
         }
         run_asm(self, asm.into())
     }
+    /// Run a compiled assembly as tests
+    ///
+    /// Unlike [`Uiua::run_asm`], an error in one top-level line does not stop the following lines from running.
+    /// When a line fails, the stack is cleared before running the next line.
+    ///
+    /// After the top-level lines, every function whose name starts with `Test` and that takes no arguments
+    /// is called as a separate test. Its outputs are discarded.
+    pub fn run_asm_tests(&mut self, asm: impl Into<Assembly>) -> TestResults {
+        self.asm = Arc::new(asm.into());
+        self.rt.execution_start = instant::now();
+        self.rt.instruction_count = Arc::default();
+        let top_slices = self.asm.top_slices.clone();
+        let mut results = TestResults::default();
+        for &slice in &top_slices {
+            let has_assert = (self.instrs(slice).iter())
+                .any(|instr| matches!(instr, Instr::Prim(Primitive::Assert, _)));
+            self.run_test(&mut results, has_assert, |env| env.call_slice(slice));
+        }
+        let test_funcs: Vec<Function> = (self.asm.bindings.iter())
+            .filter_map(|binding| match &binding.global {
+                Global::Func(f) if f.signature().args == 0 => {
+                    let span = binding.span.as_ref()?;
+                    let is_test = span.as_str(&self.asm.inputs, |name| name.starts_with("Test"));
+                    is_test.then(|| f.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        for f in test_funcs {
+            let height = self.rt.stack.len();
+            self.run_test(&mut results, true, |env| env.call(f));
+            self.rt.stack.truncate(height);
+        }
+        self.rt.track_mismatches = false;
+        self.rt.last_mismatch = None;
+        results
+    }
+    fn run_test(
+        &mut self,
+        results: &mut TestResults,
+        counts: bool,
+        f: impl FnOnce(&mut Self) -> UiuaResult,
+    ) {
+        self.rt.track_mismatches = true;
+        self.rt.last_mismatch = None;
+        match self.catching_crash("", f).and_then(|res| res) {
+            Ok(()) => results.passed += counts as usize,
+            Err(error) => {
+                let mismatch = (self.rt.last_mismatch.take())
+                    .filter(|_| error.is_assertion())
+                    .map(|mismatch| (mismatch.1, mismatch.2));
+                results.failures.push(TestFailure { error, mismatch });
+                self.reset_runtime();
+            }
+        }
+    }
+    /// Run a compiled assembly, timing each top-level line
+    ///
+    /// Each line is run `iterations` times, starting from the same stack each time.
+    /// The stack left by the last run is kept for the next line.
+    pub fn run_asm_bench(
+        &mut self,
+        asm: impl Into<Assembly>,
+        iterations: usize,
+    ) -> UiuaResult<Vec<BenchTiming>> {
+        self.asm = Arc::new(asm.into());
+        let top_slices = self.asm.top_slices.clone();
+        let mut timings = Vec::with_capacity(top_slices.len());
+        let res = (|| -> UiuaResult {
+            for &slice in &top_slices {
+                let span = self.slice_span(slice);
+                let start_stack = self.rt.stack.clone();
+                let mut samples = Vec::with_capacity(iterations);
+                for i in 0..iterations.max(1) {
+                    if i > 0 {
+                        self.rt.stack.clone_from(&start_stack);
+                    }
+                    self.rt.execution_start = instant::now();
+                    self.rt.instruction_count = Arc::default();
+                    self.catching_crash("", |env| env.call_slice(slice))??;
+                    samples.push(instant::now() - self.rt.execution_start);
+                }
+                timings.push(BenchTiming { span, samples });
+            }
+            Ok(())
+        })();
+        if res.is_err() {
+            self.reset_runtime();
+        }
+        res.map(|_| timings)
+    }
+    /// Get the span covering the instructions of a slice that have spans
+    fn slice_span(&self, slice: FuncSlice) -> Option<CodeSpan> {
+        let mut span: Option<CodeSpan> = None;
+        for instr in self.instrs(slice) {
+            if let Some(code) = self.instr_span(instr) {
+                span = Some(match span {
+                    Some(span) => span.merge(code),
+                    None => code,
+                });
+            }
+        }
+        span
+    }
+    fn limit_exceeded(&self, limit: Limit) -> UiuaError {
+        UiuaError::LimitExceeded(limit, self.span(), self.inputs().clone().into())
+    }
+    /// Get the approximate number of bytes used by the values on the stacks
+    fn values_size(&self) -> usize {
+        (self.rt.stack.iter())
+            .chain(self.rt.temp_stacks.iter().flatten())
+            .map(value_size)
+            .sum()
+    }
+    /// Count a value that is being added to the stacks toward the memory limit
+    fn count_memory(&mut self, value: &Value) {
+        if self.rt.memory_limit.is_some() {
+            self.rt.memory_estimate += value_size(value);
+        }
+    }
+    /// Get the code span of an instruction, if it has one
+    fn instr_span(&self, instr: &Instr) -> Option<CodeSpan> {
+        let index = match instr {
+            Instr::Prim(_, span)
+            | Instr::ImplPrim(_, span)
+            | Instr::BindGlobal { span, .. }
+            | Instr::EndArray { span, .. } => *span,
+            _ => return None,
+        };
+        match self.get_span(index) {
+            Span::Code(span) => Some(span),
+            Span::Builtin => None,
+        }
+    }
+    /// Pause in the debugger before an instruction if necessary
+    fn debug_pause(&mut self, instr: &Instr) -> UiuaResult {
+        let span = self.instr_span(instr);
+        let debugger = self.rt.debugger.as_mut().unwrap();
+        let is_breakpoint_prim = matches!(instr, Instr::Prim(Primitive::Breakpoint, _));
+        let line = span.as_ref().map(|span| span.start.line as usize);
+        let at_breakpoint = span.as_ref().is_some_and(|span| {
+            let (line, col) = (span.start.line as usize, span.start.col as usize);
+            (debugger.breakpoints.iter()).any(|&(bp_line, bp_col)| match bp_col {
+                Some(bp_col) => bp_line == line && bp_col == col,
+                None => bp_line == line && debugger.last_line != Some(line),
+            })
+        });
+        if line.is_some() {
+            debugger.last_line = line;
+        }
+        if !(debugger.stepping || is_breakpoint_prim || at_breakpoint) {
+            return Ok(());
+        }
+        let hook = debugger.hook.clone();
+        let pause = DebugPause {
+            span,
+            instr: format!("{instr:?}"),
+            call_depth: self.rt.call_stack.len(),
+            breakpoint: is_breakpoint_prim || at_breakpoint,
+        };
+        let action = hook.pause(self, &pause);
+        let debugger = self.rt.debugger.as_mut().unwrap();
+        match action {
+            DebugAction::Step => debugger.stepping = true,
+            DebugAction::Continue => debugger.stepping = false,
+            DebugAction::Abort => return Err(self.error("Execution aborted by the debugger")),
+        }
+        Ok(())
+    }
+    /// Reset the runtime after an error, keeping its configuration
+    fn reset_runtime(&mut self) {
+        self.rt.cancel.0.store(false, atomic::Ordering::Relaxed);
+        self.rt = Runtime {
+            cancel: self.rt.cancel.clone(),
+            backend: self.rt.backend.clone(),
+            execution_limit: self.rt.execution_limit,
+            instruction_limit: self.rt.instruction_limit,
+            memory_limit: self.rt.memory_limit,
+            recursion_limit: self.rt.recursion_limit,
+            memo_limit: self.rt.memo_limit,
+            time_instrs: self.rt.time_instrs,
+            output_comments: self.rt.output_comments.clone(),
+            thread_pool: self.rt.thread_pool.clone(),
+            spawn_pool: self.rt.spawn_pool.clone(),
+            channels: self.rt.channels.clone(),
+            rng: self.rt.rng.take(),
+            profiler: self.rt.profiler.take(),
+            debugger: self.rt.debugger.take(),
+            ..Runtime::default()
+        };
+    }
     fn catching_crash<T>(
         &mut self,
         input: impl fmt::Display,
@@ -377,9 +1062,29 @@ code:
     }
     fn exec(&mut self, frame: StackFrame) -> UiuaResult {
         self.rt.call_stack.push(frame);
+        let depth = self.rt.call_stack.len();
+        if let Some(limit) = self.rt.recursion_limit.filter(|&limit| depth > limit) {
+            let frame = self.rt.call_stack.pop().unwrap();
+            let err = self.limit_exceeded(Limit::Recursion(limit));
+            return Err(self.trace_error(err, frame));
+        }
         let mut formatted_instr = String::new();
         loop {
+            if self.rt.debugger.is_some() {
+                let frame = self.rt.call_stack.last().unwrap();
+                let instr = (self.asm.instrs[frame.slice.start..][..frame.slice.len].get(frame.pc))
+                    .cloned();
+                if let Some(instr) = instr {
+                    if let Err(err) = self.debug_pause(&instr) {
+                        let frame = self.rt.call_stack.pop().unwrap();
+                        return Err(self.trace_error(err, frame));
+                    }
+                }
+            }
             let frame = self.rt.call_stack.last().unwrap();
+            // A call is in tail position if it is the last instruction of its function
+            let tail = frame.pc + 1 == frame.slice.len;
+            let mut jump = None;
             let Some(instr) = self.asm.instrs[frame.slice.start..][..frame.slice.len].get(frame.pc)
             else {
                 self.rt.call_stack.pop().unwrap();
@@ -415,20 +1120,43 @@ code:
                 formatted_instr = format!("{instr:?}");
                 self.rt.last_time = instant::now();
             }
+            let profiled = match &mut self.rt.profiler {
+                Some(profiler) => profiler.enter(instr),
+                None => false,
+            };
             let res = match instr {
                 Instr::Comment(_) => Ok(()),
+                // Tail calls replace the current frame instead of adding a new one
+                &Instr::CallGlobal {
+                    index, call: true, ..
+                } if tail && matches!(self.asm.bindings[index].global, Global::Func(_)) => {
+                    if let Global::Func(f) = &self.asm.bindings[index].global {
+                        jump = Some(f.clone());
+                    }
+                    Ok(())
+                }
+                &Instr::Prim(Primitive::Recur, span) if tail => {
+                    self.with_prim_span(span, Some(Primitive::Recur), |env| {
+                        jump = Some(env.this_function()?);
+                        Ok(())
+                    })
+                }
+                &Instr::Switch { count, sig, span } if tail => self.with_span(span, |env| {
+                    jump = algorithm::switch_select(count, sig, env)?;
+                    Ok(())
+                }),
                 &Instr::Prim(prim, span) => {
                     self.with_prim_span(span, Some(prim), |env| prim.run(env))
                 }
                 &Instr::ImplPrim(prim, span) => self.with_span(span, |env| prim.run(env)),
                 Instr::Push(val) => {
-                    self.rt.stack.push(Value::clone(val));
+                    self.push(Value::clone(val));
                     Ok(())
                 }
                 &Instr::CallGlobal { index, call, .. } => {
                     match self.asm.bindings[index].global.clone() {
                         Global::Const(val) => {
-                            self.rt.stack.push(val);
+                            self.push(val);
                             Ok(())
                         }
                         Global::Func(f) if call => self.call(f),
@@ -449,10 +1177,10 @@ code:
                 &Instr::BindGlobal { span, index } => {
                     if let Some(f) = self.rt.function_stack.pop() {
                         // Binding is an imported function
-                        self.asm.bind_function(index, f, span, None);
+                        Arc::make_mut(&mut self.asm).bind_function(index, f, span, None);
                     } else if let Some(value) = self.rt.stack.pop() {
                         // Binding is a constant
-                        self.asm.bind_const(index, value, span, None);
+                        Arc::make_mut(&mut self.asm).bind_const(index, value, span, None);
                     } else {
                         // Binding is an empty function
                         let id = match self.get_span(span) {
@@ -461,7 +1189,7 @@ code:
                         };
                         let func =
                             Function::new(id, Signature::new(0, 0), FuncSlice { start: 0, len: 0 });
-                        self.asm.bind_function(index, func, span, None);
+                        Arc::make_mut(&mut self.asm).bind_function(index, func, span, None);
                     }
                     Ok(())
                 }
@@ -655,6 +1383,7 @@ code:
                     }
                     for i in 0..count {
                         let value = env.rt.stack[env.rt.stack.len() - i - 1].clone();
+                        env.count_memory(&value);
                         env.rt.temp_stacks[stack as usize].push(value);
                     }
                     Ok(())
@@ -704,6 +1433,11 @@ code:
                     Ok(())
                 }
             };
+            if profiled {
+                if let Some(profiler) = &mut self.rt.profiler {
+                    profiler.exit();
+                }
+            }
             if self.rt.time_instrs {
                 let end_time = instant::now();
                 let padding = self.rt.call_stack.len().saturating_sub(1) * 2;
@@ -720,8 +1454,17 @@ code:
                 let frame = self.rt.call_stack.pop().unwrap();
                 return Err(self.trace_error(err, frame));
             } else {
-                // Go to next instruction
-                self.rt.call_stack.last_mut().unwrap().pc += 1;
+                let frame = self.rt.call_stack.last_mut().unwrap();
+                if let Some(f) = jump {
+                    // Jump to the start of the tail-called function
+                    frame.slice = f.slice();
+                    frame.sig = f.signature();
+                    frame.id = f.id;
+                    frame.pc = 0;
+                } else {
+                    // Go to next instruction
+                    frame.pc += 1;
+                }
                 if let Some(limit) = self.rt.execution_limit {
                     if instant::now() - self.rt.execution_start > limit {
                         return Err(UiuaError::Timeout(
@@ -730,6 +1473,26 @@ code:
                         ));
                     }
                 }
+                if self.rt.cancel.is_cancelled() {
+                    return Err(UiuaError::Cancelled(
+                        self.span(),
+                        self.inputs().clone().into(),
+                    ));
+                }
+                if let Some(limit) = self.rt.instruction_limit {
+                    let count = (self.rt.instruction_count).fetch_add(1, atomic::Ordering::Relaxed);
+                    if count >= limit {
+                        return Err(self.limit_exceeded(Limit::Instructions(limit)));
+                    }
+                }
+                if let Some(limit) = self.rt.memory_limit {
+                    if self.rt.memory_estimate > limit {
+                        self.rt.memory_estimate = self.values_size();
+                        if self.rt.memory_estimate > limit {
+                            return Err(self.limit_exceeded(Limit::Memory(limit)));
+                        }
+                    }
+                }
             }
         }
         Ok(())
@@ -776,11 +1539,6 @@ code:
         };
         self.exec(frame)
     }
-    #[inline]
-    fn call_frame(&mut self, frame: StackFrame) -> UiuaResult {
-        let call_span = self.span_index();
-        self.call_with_frame_span(frame, call_span)
-    }
     /// Call and truncate the stack to before the args were pushed if the call fails
     pub(crate) fn call_clean_stack(&mut self, f: Function) -> UiuaResult {
         let sig = f.signature();
@@ -885,7 +1643,7 @@ code:
     /// Register a span
     pub fn add_span(&mut self, span: impl Into<Span>) -> usize {
         let idx = self.asm.spans.len();
-        self.asm.spans.push(span.into());
+        Arc::make_mut(&mut self.asm).spans.push(span.into());
         idx
     }
     /// Construct an error with the current span
@@ -911,16 +1669,32 @@ code:
                 }
                 Ok(val)
             }
-            None => Err(self.error(format!(
-                "Stack was empty when evaluating {}",
-                arg.arg_name()
-            ))),
+            None => Err(self.stack_underflow_error(arg)),
         };
         for bottom in &mut self.rt.array_stack {
             *bottom = (*bottom).min(self.rt.stack.len());
         }
         res
     }
+    #[cold]
+    fn stack_underflow_error(&self, arg: impl StackArg) -> UiuaError {
+        let prim = self
+            .rt
+            .call_stack
+            .last()
+            .and_then(|frame| frame.spans.last())
+            .and_then(|&(_, prim)| prim);
+        if let Some(message) = prim
+            .zip(arg.arg_index())
+            .and_then(|(prim, index)| stack_underflow_message(prim, index))
+        {
+            return self.error(message);
+        }
+        self.error(format!(
+            "Stack was empty when evaluating {}",
+            arg.arg_name()
+        ))
+    }
     /// Pop a value and try to convert it
     pub fn pop_convert<T>(
         &mut self,
@@ -968,10 +1742,14 @@ code:
     }
     /// Push a value onto the stack
     pub fn push(&mut self, val: impl Into<Value>) {
-        self.rt.stack.push(val.into());
+        let val = val.into();
+        self.count_memory(&val);
+        self.rt.stack.push(val);
     }
     fn push_temp(&mut self, temp: TempStack, val: impl Into<Value>) {
-        self.rt.temp_stacks[temp as usize].push(val.into());
+        let val = val.into();
+        self.count_memory(&val);
+        self.rt.temp_stacks[temp as usize].push(val);
     }
     /// Push a function onto the function stack
     pub fn push_func(&mut self, f: Function) {
@@ -981,6 +1759,124 @@ code:
     pub fn instrs(&self, slice: FuncSlice) -> &[Instr] {
         &self.asm.instrs[slice.start..][..slice.len]
     }
+    /// Generate a random number, using the seeded generator if there is one
+    pub(crate) fn rand(&self) -> f64 {
+        match &self.rt.rng {
+            Some(rng) => rng.borrow_mut().gen(),
+            None => random(),
+        }
+    }
+    /// Call a function with a seeded random number generator
+    pub(crate) fn with_rng_seed<T>(&mut self, seed: f64, f: impl FnOnce(&mut Self) -> T) -> T {
+        let rng = SmallRng::seed_from_u64(seed.to_bits());
+        let outer = self.rt.rng.replace(RefCell::new(rng));
+        let res = f(self);
+        self.rt.rng = outer;
+        res
+    }
+    /// Get the cached outputs of a call to a memoized function
+    pub(crate) fn memo_get(&mut self, f: &Function, args: &[Value]) -> Option<Vec<Value>> {
+        let memo = self.rt.memo.get_or_default();
+        if !memo.borrow().contains_key(&f.id) {
+            let program = *(self.rt.memo_program).get_or_insert_with(|| program_hash(&self.asm));
+            let loaded = (self.rt.loaded_memo.take()).filter(|loaded| loaded.program == program);
+            if let Some(mut loaded) = loaded {
+                if let Some(cache) = loaded.caches.remove(&f.id.to_string()) {
+                    memo.borrow_mut().insert(f.id.clone(), cache);
+                }
+                self.rt.loaded_memo = Some(loaded);
+            }
+        }
+        memo.borrow_mut().get_mut(&f.id)?.get(args)
+    }
+    /// Cache the outputs of a call to a memoized function
+    pub(crate) fn memo_insert(&mut self, id: FunctionId, args: Vec<Value>, outputs: Vec<Value>) {
+        let limit = self.rt.memo_limit;
+        let mut memo = self.rt.memo.get_or_default().borrow_mut();
+        memo.entry(id).or_default().insert(args, outputs, limit);
+    }
+    /// Clear the caches of memoized functions on the current thread
+    pub(crate) fn memo_clear(&mut self) {
+        self.rt.memo.get_or_default().borrow_mut().clear();
+        self.rt.loaded_memo = None;
+    }
+    /// Save the caches of [`memo`](Primitive::Memo)ized functions to a file
+    ///
+    /// The caches can be loaded with [`Uiua::load_memo`] when the same program is run again.
+    pub fn save_memo(&self, path: impl AsRef<Path>) -> UiuaResult {
+        let memo = self.rt.memo.get_or_default().borrow();
+        let loaded = self.rt.loaded_memo.as_deref();
+        let program = (self.rt.memo_program)
+            .or(loaded.map(|loaded| loaded.program))
+            .unwrap_or_else(|| program_hash(&self.asm));
+        // Keep loaded caches that were not used this run
+        let unused = (loaded.filter(|loaded| loaded.program == program))
+            .into_iter()
+            .flat_map(|loaded| &loaded.caches)
+            .map(|(id, cache)| (id.clone(), cache));
+        let boxed = |values: &[Value]| Boxed(values.iter().cloned().map(Boxed).collect());
+        let caches: Value = (memo.iter())
+            .map(|(id, cache)| (id.to_string(), cache))
+            .chain(unused)
+            .map(|(id, cache)| {
+                let entries: Value = (cache.entries.iter())
+                    .map(|(args, (outputs, _))| {
+                        Boxed([boxed(args), boxed(outputs)].into_iter().collect())
+                    })
+                    .collect();
+                Boxed([Boxed(id.into()), Boxed(entries)].into_iter().collect())
+            })
+            .collect();
+        let program = format!("{program:x}");
+        let value: Value = [Boxed(program.into()), Boxed(caches)].into_iter().collect();
+        (self.rt.backend)
+            .file_write_all(path.as_ref(), &value.to_uval())
+            .map_err(|e| self.error(e))
+    }
+    /// Load the caches of [`memo`](Primitive::Memo)ized functions from a file written by
+    /// [`Uiua::save_memo`]
+    ///
+    /// The caches are only used if the program being run is the same as the one that saved them.
+    pub fn load_memo(&mut self, path: impl AsRef<Path>) -> UiuaResult {
+        let bytes = (self.rt.backend)
+            .file_read_all(path.as_ref())
+            .map_err(|e| self.error(e))?;
+        let value = Value::from_uval(&bytes).map_err(|e| self.error(e))?;
+        let invalid = || self.error("Invalid memo file");
+        fn rows(value: &Value) -> Option<Vec<Value>> {
+            match value {
+                Value::Box(arr) if arr.rank() == 1 => {
+                    Some(arr.data.iter().map(|Boxed(v)| v.clone()).collect())
+                }
+                _ => None,
+            }
+        }
+        let [program, caches] =
+            <[Value; 2]>::try_from(rows(&value).ok_or_else(invalid)?).map_err(|_| invalid())?;
+        let program = (program.as_string(self, "").ok())
+            .and_then(|s| u64::from_str_radix(&s, 16).ok())
+            .ok_or_else(invalid)?;
+        let mut loaded = LoadedMemo {
+            program,
+            caches: HashMap::new(),
+        };
+        for cache in rows(&caches).ok_or_else(invalid)? {
+            let [id, entries] =
+                <[Value; 2]>::try_from(rows(&cache).ok_or_else(invalid)?).map_err(|_| invalid())?;
+            let id = id.as_string(self, "Memo function name must be a string")?;
+            let mut cache = MemoCache::default();
+            for entry in rows(&entries).ok_or_else(invalid)? {
+                let [args, outputs] = <[Value; 2]>::try_from(rows(&entry).ok_or_else(invalid)?)
+                    .map_err(|_| invalid())?;
+                let args = rows(&args).ok_or_else(invalid)?;
+                let outputs = rows(&outputs).ok_or_else(invalid)?;
+                cache.insert(args, outputs, self.rt.memo_limit);
+            }
+            loaded.caches.insert(id, cache);
+        }
+        self.rt.loaded_memo = Some(loaded.into());
+        Ok(())
+    }
     /// Take the entire stack
     pub fn take_stack(&mut self) -> Vec<Value> {
         for stack in &mut self.rt.temp_stacks {
@@ -1122,6 +2018,8 @@ code:
             Some(Value::Byte(n)) if n.rank() == 0 => Ok(n.data[0] as f64),
             #[cfg(feature = "bytes")]
             Some(Value::Byte(_)) => Err(self.fill_error(true)),
+            Some(Value::Int(n)) if n.rank() == 0 => Ok(n.data[0] as f64),
+            Some(Value::Int(_)) => Err(self.fill_error(true)),
             _ => Err(self.fill_error(false)),
         }
     }
@@ -1140,6 +2038,29 @@ code:
             Some(Value::Byte(n)) if n.rank() == 0 => Ok(n.data[0]),
             #[cfg(feature = "bytes")]
             Some(Value::Byte(_)) => Err(self.fill_error(true)),
+            Some(Value::Int(n)) if n.rank() == 0 && n.data[0] <= 255 => Ok(n.data[0] as u8),
+            Some(Value::Int(n)) if n.rank() == 0 => Err(self.fill_error(false)),
+            Some(Value::Int(_)) => Err(self.fill_error(true)),
+            _ => Err(self.fill_error(false)),
+        }
+    }
+    pub(crate) fn int_fill(&self) -> Result<u32, &'static str> {
+        match self.rt.fill_stack.last() {
+            Some(Value::Num(n))
+                if n.rank() == 0
+                    && n.data[0].fract() == 0.0
+                    && (0.0..=u32::MAX as f64).contains(&n.data[0]) =>
+            {
+                Ok(n.data[0] as u32)
+            }
+            Some(Value::Num(n)) if n.rank() == 0 => Err(self.fill_error(false)),
+            Some(Value::Num(_)) => Err(self.fill_error(true)),
+            #[cfg(feature = "bytes")]
+            Some(Value::Byte(n)) if n.rank() == 0 => Ok(n.data[0].into()),
+            #[cfg(feature = "bytes")]
+            Some(Value::Byte(_)) => Err(self.fill_error(true)),
+            Some(Value::Int(n)) if n.rank() == 0 => Ok(n.data[0]),
+            Some(Value::Int(_)) => Err(self.fill_error(true)),
             _ => Err(self.fill_error(false)),
         }
     }
@@ -1166,6 +2087,8 @@ code:
             Some(Value::Byte(n)) if n.rank() == 0 => Ok(Complex::new(n.data[0] as f64, 0.0)),
             #[cfg(feature = "bytes")]
             Some(Value::Byte(_)) => Err(self.fill_error(true)),
+            Some(Value::Int(n)) if n.rank() == 0 => Ok(Complex::new(n.data[0] as f64, 0.0)),
+            Some(Value::Int(_)) => Err(self.fill_error(true)),
             Some(Value::Complex(c)) if c.rank() == 0 => Ok(c.data[0]),
             Some(Value::Complex(_)) => Err(self.fill_error(true)),
             _ => Err(self.fill_error(false)),
@@ -1180,6 +2103,7 @@ code:
                 Some(Value::Num(_)) => ". A number fill is set, but is is not a scalar.",
                 #[cfg(feature = "bytes")]
                 Some(Value::Byte(_)) => ". A number fill is set, but is is not a scalar.",
+                Some(Value::Int(_)) => ". A number fill is set, but is is not a scalar.",
                 Some(Value::Char(_)) => ". A character fill is set, but is is not a scalar.",
                 Some(Value::Complex(_)) => ". A complex fill is set, but is is not a scalar.",
                 Some(Value::Box(_)) => ". A box fill is set, but is is not a scalar.",
@@ -1190,6 +2114,7 @@ code:
                 Some(Value::Num(_)) => ". A number fill is set, but the array is not numbers.",
                 #[cfg(feature = "bytes")]
                 Some(Value::Byte(_)) => ". A number fill is set, but the array is not numbers.",
+                Some(Value::Int(_)) => ". A number fill is set, but the array is not numbers.",
                 Some(Value::Char(_)) => {
                     ". A character fill is set, but the array is not characters."
                 }
@@ -1234,19 +2159,18 @@ code:
     pub(crate) fn call_with_this(&mut self, f: Function) -> UiuaResult {
         let call_height = self.rt.call_stack.len();
         let with_height = self.rt.this_stack.len();
-        self.rt.this_stack.push(self.rt.call_stack.len());
+        self.rt.this_stack.push(f.clone());
         let res = self.call(f);
         self.rt.call_stack.truncate(call_height);
         self.rt.this_stack.truncate(with_height);
         res
     }
     pub(crate) fn recur(&mut self) -> UiuaResult {
-        let Some(i) = self.rt.this_stack.last().copied() else {
-            return Err(self.error("No recursion context set"));
-        };
-        let mut frame = self.rt.call_stack[i].clone();
-        frame.pc = 0;
-        self.call_frame(frame)
+        let f = self.this_function()?;
+        self.call(f)
+    }
+    fn this_function(&self) -> UiuaResult<Function> {
+        (self.rt.this_stack.last().cloned()).ok_or_else(|| self.error("No recursion context set"))
     }
     /// Spawn a thread
     pub(crate) fn spawn(
@@ -1291,19 +2215,48 @@ code:
                 cli_file_path: self.rt.cli_file_path.clone(),
                 backend: self.rt.backend.clone(),
                 execution_limit: self.rt.execution_limit,
+                cancel: self.rt.cancel.clone(),
+                instruction_limit: self.rt.instruction_limit,
+                instruction_count: Arc::default(),
+                memory_limit: self.rt.memory_limit,
+                memory_estimate: 0,
+                recursion_limit: self.rt.recursion_limit,
                 execution_start: self.rt.execution_start,
                 output_comments: HashMap::new(),
                 memo: self.rt.memo.clone(),
+                memo_limit: self.rt.memo_limit,
+                loaded_memo: None,
+                memo_program: self.rt.memo_program,
+                thread_pool: self.rt.thread_pool.clone(),
+                spawn_pool: self.rt.spawn_pool.clone(),
+                channels: self.rt.channels.clone(),
+                ffi_callbacks: self.rt.ffi_callbacks.clone(),
+                // Spawned threads get their own generator derived from the seeded one
+                rng: (self.rt.rng.as_ref())
+                    .map(|rng| RefCell::new(SmallRng::seed_from_u64(rng.borrow_mut().gen()))),
+                profiler: None,
+                debugger: None,
+                track_mismatches: false,
+                last_mismatch: None,
                 thread,
             },
         };
         #[cfg(not(target_arch = "wasm32"))]
-        let handle = std::thread::Builder::new()
-            .spawn(move || {
-                f(&mut env)?;
-                Ok(env.take_stack())
-            })
-            .map_err(|e| self.error(format!("Error spawning thread: {e}")))?;
+        let handle = if let Some(pool) = &self.rt.spawn_pool {
+            let (send, recv) = crossbeam_channel::bounded(1);
+            pool.spawn(move || {
+                _ = send.send(f(&mut env).map(|_| env.take_stack()));
+            });
+            ThreadHandle::Pooled(recv, pool.clone())
+        } else {
+            let handle = std::thread::Builder::new()
+                .spawn(move || {
+                    f(&mut env)?;
+                    Ok(env.take_stack())
+                })
+                .map_err(|e| self.error(format!("Error spawning thread: {e}")))?;
+            ThreadHandle::Os(handle)
+        };
         #[cfg(target_arch = "wasm32")]
         let result = f(&mut env).map(|_| env.take_stack());
 
@@ -1325,6 +2278,76 @@ code:
         self.push(id);
         Ok(())
     }
+    /// Check whether some calls to a function can be run in parallel on the thread pool
+    pub(crate) fn can_par_call(&self, f: &Function, calls: usize) -> bool {
+        self.rt.thread_pool.is_some() && calls > 1 && f.is_pure(&self.asm)
+    }
+    /// Call a function in parallel on the thread pool once for each set of arguments
+    ///
+    /// Each set of arguments is pushed in order.
+    /// The outputs of each call are returned in the order they are popped.
+    pub(crate) fn par_call(
+        &self,
+        f: &Function,
+        args: Vec<Vec<Value>>,
+        result_name: &'static str,
+    ) -> UiuaResult<Vec<Vec<Value>>> {
+        let pool = (self.rt.thread_pool.clone()).expect("no thread pool for parallel call");
+        let outputs = f.signature().outputs;
+        let chunk_size = args.len().div_ceil(pool.current_num_threads()).max(1);
+        let mut args = args.into_iter().peekable();
+        let mut chunks = Vec::new();
+        while args.peek().is_some() {
+            let chunk: Vec<_> = args.by_ref().take(chunk_size).collect();
+            chunks.push((self.worker(), chunk));
+        }
+        let results = pool.install(|| {
+            chunks
+                .into_par_iter()
+                .map(|(mut env, chunk)| {
+                    let mut results = Vec::with_capacity(chunk.len());
+                    for args in chunk {
+                        for arg in args {
+                            env.push(arg);
+                        }
+                        env.call(f.clone())?;
+                        let mut outs = Vec::with_capacity(outputs);
+                        for _ in 0..outputs {
+                            outs.push(env.pop(result_name)?);
+                        }
+                        results.push(outs);
+                    }
+                    Ok(results)
+                })
+                .collect::<UiuaResult<Vec<_>>>()
+        })?;
+        Ok(results.into_iter().flatten().collect())
+    }
+    /// Make an environment for running pure functions on another thread
+    fn worker(&self) -> Self {
+        Uiua {
+            asm: self.asm.clone(),
+            rt: Runtime {
+                call_stack: self.rt.call_stack.last().cloned().into_iter().collect(),
+                fill_stack: self.rt.fill_stack.clone(),
+                unpack_boxes: self.rt.unpack_boxes,
+                execution_limit: self.rt.execution_limit,
+                cancel: self.rt.cancel.clone(),
+                instruction_limit: self.rt.instruction_limit,
+                instruction_count: self.rt.instruction_count.clone(),
+                memory_limit: self.rt.memory_limit,
+                recursion_limit: self.rt.recursion_limit,
+                execution_start: self.rt.execution_start,
+                cli_arguments: self.rt.cli_arguments.clone(),
+                cli_file_path: self.rt.cli_file_path.clone(),
+                backend: self.rt.backend.clone(),
+                memo: self.rt.memo.clone(),
+                memo_limit: self.rt.memo_limit,
+                channels: self.rt.channels.clone(),
+                ..Runtime::default()
+            },
+        }
+    }
     /// Wait for a thread to finish
     pub(crate) fn wait(&mut self, id: Value) -> UiuaResult {
         let ids = id.as_natural_array(self, "Thread id must be an array of natural numbers")?;
@@ -1340,8 +2363,7 @@ code:
                     .handle,
             )
             .ok_or_else(|| self.error("Cannot wait on thread spawned in cloned environment"))?
-            .join()
-            .unwrap()?;
+            .join()?;
             #[cfg(target_arch = "wasm32")]
             let thread_stack = self
                 .rt
@@ -1350,7 +2372,9 @@ code:
                 .remove(&handle)
                 .ok_or_else(|| self.error("Invalid thread id"))?
                 .result?;
-            self.rt.stack.extend(thread_stack);
+            for value in thread_stack {
+                self.push(value);
+            }
         } else {
             let mut rows = Vec::new();
             for handle in ids.data {
@@ -1364,8 +2388,7 @@ code:
                         .handle,
                 )
                 .ok_or_else(|| self.error("Cannot wait on thread spawned in cloned environment"))?
-                .join()
-                .unwrap()?;
+                .join()?;
                 #[cfg(target_arch = "wasm32")]
                 let thread_stack = self
                     .rt
@@ -1390,21 +2413,23 @@ code:
         Ok(())
     }
     pub(crate) fn send(&self, id: Value, value: Value) -> UiuaResult {
-        let ids = id.as_natural_array(self, "Thread id must be an array of natural numbers")?;
+        let ids = id.as_integer_array(self, "Thread or channel id must be an array of integers")?;
         for id in ids.data {
-            self.channel(id)?
-                .send
-                .send(value.clone())
+            let pool = self.rt.spawn_pool.as_deref();
+            send_yielding(&self.sender(id)?, value.clone(), pool)
                 .map_err(|_| self.error("Thread channel closed"))?;
         }
         Ok(())
     }
     pub(crate) fn recv(&mut self, id: Value) -> UiuaResult {
-        let ids = id.as_natural_array(self, "Thread id must be an array of natural numbers")?;
+        let ids = id.as_integer_array(self, "Thread or channel id must be an array of integers")?;
         let mut values = Vec::with_capacity(ids.data.len());
         for id in ids.data {
-            values.push(self.channel(id)?.recv.recv().map_err(|_| {
-                if let Err(e) = self.wait(id.into()) {
+            let pool = self.rt.spawn_pool.as_deref();
+            values.push(recv_yielding(&self.receiver(id)?, pool).map_err(|_| {
+                if id < 0 {
+                    self.error("Channel closed")
+                } else if let Err(e) = self.wait((id as usize).into()) {
                     e
                 } else {
                     self.error("Thread channel closed")
@@ -1419,12 +2444,13 @@ code:
         Ok(())
     }
     pub(crate) fn try_recv(&mut self, id: Value) -> UiuaResult {
-        let id = id.as_nat(self, "Thread id must be a natural number")?;
-        let value = match self.channel(id)?.recv.try_recv() {
+        let id = id.as_int(self, "Thread or channel id must be an integer")?;
+        let value = match self.receiver(id)?.try_recv() {
             Ok(value) => value,
             Err(TryRecvError::Empty) => return Err(self.error("No value available")),
+            Err(_) if id < 0 => return Err(self.error("Channel closed")),
             Err(_) => {
-                return Err(if let Err(e) = self.wait(id.into()) {
+                return Err(if let Err(e) = self.wait((id as usize).into()) {
                     e
                 } else {
                     self.error("Thread channel closed")
@@ -1434,6 +2460,52 @@ code:
         self.push(value);
         Ok(())
     }
+    /// Create a channel that can be shared between threads
+    ///
+    /// If there is no capacity, the channel is unbounded.
+    pub(crate) fn create_channel(&self, capacity: Option<usize>) -> isize {
+        let (send, recv) = match capacity {
+            Some(cap) => crossbeam_channel::bounded(cap),
+            None => crossbeam_channel::unbounded(),
+        };
+        let mut channels = self.rt.channels.lock();
+        channels.next_id += 1;
+        let id = channels.next_id;
+        let channel = SharedChannel {
+            send: Some(send),
+            recv,
+        };
+        channels.channels.insert(id, channel);
+        -(id as isize)
+    }
+    /// Close a channel created with [`Uiua::create_channel`]
+    ///
+    /// Values already sent can still be received.
+    pub(crate) fn close_channel(&self, id: isize) -> UiuaResult {
+        let mut channels = self.rt.channels.lock();
+        let channel = (channels.channels.get_mut(&id.unsigned_abs()))
+            .ok_or_else(|| self.error("Invalid channel id"))?;
+        channel.send = None;
+        Ok(())
+    }
+    fn sender(&self, id: isize) -> UiuaResult<Sender<Value>> {
+        if id >= 0 {
+            return Ok(self.channel(id as usize)?.send.clone());
+        }
+        let channels = self.rt.channels.lock();
+        let channel = (channels.channels.get(&id.unsigned_abs()))
+            .ok_or_else(|| self.error("Invalid channel id"))?;
+        (channel.send.clone()).ok_or_else(|| self.error("Channel closed"))
+    }
+    fn receiver(&self, id: isize) -> UiuaResult<Receiver<Value>> {
+        if id >= 0 {
+            return Ok(self.channel(id as usize)?.recv.clone());
+        }
+        let channels = self.rt.channels.lock();
+        let channel = (channels.channels.get(&id.unsigned_abs()))
+            .ok_or_else(|| self.error("Invalid channel id"))?;
+        Ok(channel.recv.clone())
+    }
     fn channel(&self, id: usize) -> UiuaResult<&Channel> {
         Ok(if id == 0 {
             self.rt
@@ -1453,12 +2525,66 @@ code:
     }
 }
 
+/// Get the approximate number of bytes used by a value
+fn value_size(value: &Value) -> usize {
+    match value {
+        Value::Box(arr) => (arr.data.iter())
+            .map(|Boxed(value)| size_of::<Boxed>() + value_size(value))
+            .sum(),
+        value => value.element_count() * value.elem_size(),
+    }
+}
+
+/// Describe a stack underflow of a primitive's numbered argument
+///
+/// Returns `None` if the argument is not one the primitive is known to take.
+fn stack_underflow_message(prim: Primitive, index: usize) -> Option<String> {
+    let present = match index.checked_sub(1)? {
+        0 => "the stack was empty".to_string(),
+        1 => "only 1 was on the stack".to_string(),
+        n => format!("only {n} were on the stack"),
+    };
+    let Some(args) = prim.args().filter(|&n| index <= n) else {
+        // The argument belongs to a modifier's function
+        prim.modifier_args()?;
+        return Some(format!(
+            "{}'s function expects at least {index} argument{}; {present}",
+            prim.format(),
+            if index == 1 { "" } else { "s" }
+        ));
+    };
+    let functions = match prim.modifier_args() {
+        Some(1) => " besides its function",
+        Some(_) => " besides its functions",
+        None => "",
+    };
+    let names = prim.arg_names();
+    let roles = match names {
+        _ if names.len() != args => String::new(),
+        [] => String::new(),
+        [a] => format!(": {a}"),
+        [a, b] => format!(": {a} and {b}"),
+        [init @ .., last] => format!(": {}, and {last}", init.join(", ")),
+    };
+    Some(format!(
+        "{} expects {args} argument{}{functions}{roles}; {present}",
+        prim.format(),
+        if args == 1 { "" } else { "s" }
+    ))
+}
+
 /// A trait for types that can be used as argument specifiers for [`Uiua::pop`]
 ///
-/// If the stack is empty, the error message will be "Stack was empty when evaluating {arg_name}"
+/// If the stack is empty, the error message will be "Stack was empty when evaluating {arg_name}",
+/// unless the argument is numbered and a primitive is being evaluated,
+/// in which case the message describes the primitive's arguments.
 pub trait StackArg {
     /// Get the name of the argument
     fn arg_name(self) -> String;
+    /// Get the 1-based index of the argument, if it is numbered
+    fn arg_index(&self) -> Option<usize> {
+        None
+    }
 }
 
 impl StackArg for () {
@@ -1471,16 +2597,25 @@ impl StackArg for usize {
     fn arg_name(self) -> String {
         format!("argument {self}")
     }
+    fn arg_index(&self) -> Option<usize> {
+        Some(*self)
+    }
 }
 impl StackArg for u8 {
     fn arg_name(self) -> String {
         format!("argument {self}")
     }
+    fn arg_index(&self) -> Option<usize> {
+        Some(*self as usize)
+    }
 }
 impl StackArg for i32 {
     fn arg_name(self) -> String {
         format!("argument {self}")
     }
+    fn arg_index(&self) -> Option<usize> {
+        usize::try_from(*self).ok()
+    }
 }
 impl<'a> StackArg for &'a str {
     fn arg_name(self) -> String {
@@ -1509,3 +2644,37 @@ where
         self().arg_name()
     }
 }
+
+/// The state of the lines of an item read so far by [`Uiua::run_reader`]
+#[derive(Default)]
+struct ChunkState {
+    depth: i32,
+    in_test_scope: bool,
+    ends_in_multiline_string: bool,
+}
+
+impl ChunkState {
+    fn push_line(&mut self, line: &str) {
+        let (tokens, _) = lex(line, (), &mut Inputs::default());
+        for token in &tokens {
+            match &token.value {
+                Token::Simple(
+                    AsciiToken::OpenParen | AsciiToken::OpenBracket | AsciiToken::OpenCurly,
+                ) => self.depth += 1,
+                Token::Simple(
+                    AsciiToken::CloseParen | AsciiToken::CloseBracket | AsciiToken::CloseCurly,
+                ) => self.depth -= 1,
+                Token::Simple(AsciiToken::TripleMinus) => self.in_test_scope = !self.in_test_scope,
+                _ => {}
+            }
+        }
+        self.ends_in_multiline_string = tokens
+            .iter()
+            .rev()
+            .find(|token| !matches!(token.value, Token::Newline | Token::Spaces | Token::Comment))
+            .is_some_and(|token| matches!(token.value, Token::MultilineString(_)));
+    }
+    fn is_complete(&self) -> bool {
+        self.depth <= 0 && !self.in_test_scope
+    }
+}
@@ -16,12 +16,17 @@ use once_cell::sync::Lazy;
 use parking_lot::Mutex;
 use serde::*;
 
+#[cfg(feature = "arrow")]
+use crate::columnar::{arrow_to_value, value_to_arrow};
+#[cfg(feature = "parquet")]
+use crate::columnar::{parquet_to_value, value_to_parquet};
 use crate::{
-    cowslice::cowslice, primitive::PrimDoc, Array, Boxed, FfiType, Signature, Uiua, UiuaResult,
-    Value,
+    algorithm::time::DateTime, cowslice::cowslice, primitive::PrimDoc, Array, Boxed, FfiType,
+    Signature, Uiua, UiuaResult, Value,
 };
 
 /// Access the built-in `example.ua` file
+#[doc(hidden)]
 pub fn example_ua<T>(f: impl FnOnce(&mut String) -> T) -> T {
     static EXAMPLE_UA: Lazy<Mutex<String>> = Lazy::new(|| {
         Mutex::new(
@@ -168,9 +173,26 @@ sys_op! {
     /// Get the command line arguments
     ///
     /// The first element will always be the name of your script
+    ///
+    /// Arguments that look like flags, such as `--verbose`, are passed through as-is.
     (0, Args, Env, "&args", "arguments"),
     /// Get the value of an environment variable
+    ///
+    /// If the variable is not set, an empty string is returned.
+    /// To tell an unset variable apart from an empty one, use [fill] to provide a different value.
+    /// ex: ⬚0&var "SURELY_NOT_SET"
+    /// ex: ⬚(□"unset")&var "SURELY_NOT_SET"
     (1, Var, Env, "&var", "environment variable"),
+    /// Get the text contents of the clipboard
+    ///
+    /// See also: [&clipset]
+    (0, ClipboardGet, Env, "&clip", "clipboard - get"),
+    /// Set the text contents of the clipboard
+    ///
+    /// Expects a string.
+    ///
+    /// See also: [&clip]
+    (1(0), ClipboardSet, Env, "&clipset", "clipboard - set"),
     /// Run a command and wait for it to finish
     ///
     /// Standard IO will be inherited. Returns the exit code of the command.
@@ -183,6 +205,23 @@ sys_op! {
     ///
     /// Expects either a string, a rank `2` character array, or a rank `1` array of [box] strings.
     (1(3), RunCapture, Command, "&runc", "run command capture"),
+    /// Run a command with streaming IO
+    ///
+    /// The command is spawned and runs in the background.
+    /// Pushes a handle to the process, then handles to its stdin, stdout, and stderr.
+    /// The stream handles can be used with [&w], [&rs], [&rb], [&ru], and [&cl].
+    ///
+    /// Close the stdin handle with [&cl] to signal the end of input.
+    /// Use [&wait] to get the exit code or [&kill] to stop the process.
+    ///
+    /// Expects either a string, a rank `2` character array, or a rank `1` array of [box] strings.
+    (1(4), RunStream, Command, "&runs", "run command stream"),
+    /// Wait for a process spawned with [&runs] to finish
+    ///
+    /// Returns the exit code of the process.
+    (1, Wait, Command, "&wait", "wait for process"),
+    /// Kill a process spawned with [&runs]
+    (1(0), Kill, Command, "&kill", "kill process"),
     /// Change the current directory
     (1(0), ChangeDirectory, Filesystem, "&cd", "change directory"),
     /// Sleep for n seconds
@@ -190,6 +229,107 @@ sys_op! {
     /// On the web, this example will hang for 1 second.
     /// ex: ⚂ &sl 1
     (1(0), Sleep, Misc, "&sl", "sleep"),
+    /// Format a time as a string
+    ///
+    /// Expects a format string and a time in seconds since the Unix epoch, like the one returned by [now].
+    /// Times are always in UTC.
+    /// ex: &tmfmt "%Y-%m-%d %H:%M:%S" 1000000000
+    /// ex: &tmfmt "%A, %B %e, %Y at %I:%M %p" 1000000000
+    /// The format string uses `strftime`-style directives:
+    /// - `%Y` - the year
+    /// - `%y` - the last 2 digits of the year
+    /// - `%m` - the month, from `01` to `12`
+    /// - `%B`/`%b` - the full/abbreviated month name
+    /// - `%d` - the day of the month, from `01` to `31`
+    /// - `%e` - the day of the month, padded with a space
+    /// - `%j` - the day of the year, from `001` to `366`
+    /// - `%A`/`%a` - the full/abbreviated weekday name
+    /// - `%u`/`%w` - the weekday number, where Monday is `1`/Sunday is `0`
+    /// - `%H` - the hour, from `00` to `23`
+    /// - `%I` - the hour, from `01` to `12`
+    /// - `%p` - `AM` or `PM`
+    /// - `%M` - the minute
+    /// - `%S` - the second
+    /// - `%f` - the fraction of a second, in microseconds
+    /// - `%s` - the whole number of seconds since the Unix epoch
+    /// - `%z`/`%Z` - the UTC offset, which is always `+0000`/`UTC`
+    /// - `%F` - the same as `%Y-%m-%d`
+    /// - `%T` - the same as `%H:%M:%S`
+    /// - `%D` - the same as `%m/%d/%y`
+    /// - `%%` - a literal `%`
+    (2, TimeFormat, Misc, "&tmfmt", "time - format"),
+    /// Parse a time from a string
+    ///
+    /// Expects a format string and a string to parse.
+    /// Returns the time in seconds since the Unix epoch.
+    /// The format string uses the same directives as [&tmfmt].
+    /// Whitespace in the format string matches any amount of whitespace.
+    /// Omitted fields default to the Unix epoch.
+    /// A `%z` offset like `+01:00` is converted to UTC.
+    /// ex: &tmparse "%Y-%m-%d" "2001-09-09"
+    /// ex: &tmparse "%FT%T%z" "2001-09-09T03:46:40+02:00"
+    /// ex: &tmfmt "%F %T" &tmparse "%d %B %Y" "9 September 2001"
+    (2, TimeParse, Misc, "&tmparse", "time - parse"),
+    /// Decompose times into their calendar components
+    ///
+    /// Expects an array of times in seconds since the Unix epoch.
+    /// Each time is turned into a list of its year, month, day, hour, minute, and second, in UTC.
+    /// The second may have a fractional part.
+    /// ex: &tmdec 1000000000
+    /// ex: &tmdec [0 86400.5]
+    (1, TimeDecompose, Misc, "&tmdec", "time - decompose"),
+    /// Compress a byte array with gzip
+    ///
+    /// ex: &gz utf "Hello, World! Hello, World!"
+    ///
+    /// See also: [&ungz]
+    (1, Gzip, Misc, "&gz", "gzip - compress"),
+    /// Decompress a gzip byte array
+    ///
+    /// Multiple concatenated gzip members are decompressed one after another.
+    /// ex: °utf &ungz &gz utf "Hello, World!"
+    ///
+    /// See also: [&gz]
+    (1, Gunzip, Misc, "&ungz", "gzip - decompress"),
+    /// Generate cryptographically secure random bytes
+    ///
+    /// Expects a count. Unlike [random], the bytes come from the operating system and are suitable for keys and tokens.
+    /// ex: &rndb 16
+    (1, RandomBytes, Misc, "&rndb", "secure random bytes"),
+    /// Clear the caches of [memo]ized functions
+    ///
+    /// Only the caches of the current thread are cleared.
+    /// ex: F ← memo(+⌊×10⚂)
+    ///   : [F 1 F 1 &memoclr F 1]
+    (0(0), MemoClear, Misc, "&memoclr", "memo - clear"),
+    /// Set the maximum number of threads that [spawn]ed threads run on
+    ///
+    /// Expects a thread count. The previous limit is returned.
+    /// With a limit, spawned threads are tasks on a shared pool, so spawning thousands of them is cheap.
+    /// A limit of `∞` means each spawned thread is a new OS thread, which is the default.
+    /// ex: &threads 4
+    ///   : wait≡spawn(/+⇡) ⇡1000
+    /// A count of `0` gets the limit without changing it.
+    /// ex: &threads 0
+    ///
+    /// Tasks that are waiting to [wait] for, [send] to, or [recv] from other threads run other tasks in the meantime, so they cannot use up the pool.
+    /// Other blocking operations, like [&sl] or reading from a stream, hold their thread until they finish.
+    (1, Threads, Misc, "&threads", "spawn thread limit"),
+    /// Create a channel that can be shared between threads
+    ///
+    /// Expects a capacity. A capacity of `∞` makes the channel unbounded.
+    /// The channel id can be passed to [send], [recv], and [tryrecv] in place of a thread id, in any thread.
+    /// [send] blocks while the channel is full. A capacity of `0` means every [send] blocks until the value is [recv]ed.
+    /// Channel ids are negative, so they are never confused with thread ids.
+    /// ex: Ch ← &chan 1
+    ///   : wait ⊙(≡(send:Ch) [1 2 3]) spawn(≡recv ↯3 Ch)
+    /// A channel can be closed with [&cl]. Values already sent can still be received, but [recv]ing from an empty closed channel is an error.
+    /// ex: Ch ← &chan ∞
+    ///   : send 1 Ch
+    ///   : &cl Ch
+    ///   : recv Ch
+    ///   : ⍣recv⋅"closed" Ch
+    (1, Channel, Misc, "&chan", "channel - create"),
     /// Read at most n bytes from a stream
     ///
     /// Expects a count and a stream handle.
@@ -230,6 +370,7 @@ sys_op! {
     /// Close a stream by its handle
     ///
     /// This will close files, tcp listeners, and tcp sockets.
+    /// It will also close channels created with [&chan].
     (1(0), Close, Stream, "&cl", "close handle"),
     /// Open a file and return a handle to it
     ///
@@ -262,6 +403,21 @@ sys_op! {
     (1, FListDir, Filesystem, "&fld", "file - list directory"),
     /// Check if a path is a file
     (1, FIsFile, Filesystem, "&fif", "file - is file"),
+    /// Wait for a file or directory to change
+    ///
+    /// Expects a timeout in seconds and a path.
+    /// Blocks until the file or directory at the path changes, or until the timeout has passed.
+    /// Use `infinity` to wait indefinitely.
+    /// Directories are watched recursively.
+    ///
+    /// Returns the kind of change, which is one of `"create"`, `"modify"`, `"remove"`, or `"other"`.
+    /// Merely reading the file does not count as a change.
+    /// If the timeout passes without a change, an empty string is returned.
+    ///
+    /// ex! &fwatch ∞ "example.ua"
+    /// This can be used to rerun something whenever a file changes.
+    /// ex! ⍢(&p&fras "example.ua" ◌&fwatch ∞ "example.ua")1
+    (2, FWatch, Filesystem, "&fwatch", "file - watch"),
     /// Read all the contents of a file into a string
     ///
     /// Expects a path and returns a rank-`1` character array.
@@ -294,10 +450,73 @@ sys_op! {
     ///   : &fwa Path +@A⇡26
     ///   : &fras Path
     (2(0), FWriteAll, Filesystem, "&fwa", "file - write all"),
+    /// Save a value to a file
+    ///
+    /// Expects a path and any value.
+    /// The value is written in a compact binary format that preserves its type, shape, and contents exactly, including [box]es, maps, and complex numbers.
+    /// This is useful for caching the results of long computations across runs.
+    ///
+    /// Load the value again with [&vload].
+    /// ex: # Experimental!
+    ///   : &vsave "cache.uval" {1_2 "three" map 4 5}
+    ///   : &vload "cache.uval"
+    (2(0), ValueSave, Filesystem, "&vsave", "value - save to file"),
+    /// Load a value saved with [&vsave] from a file
+    ///
+    /// Expects a path.
+    /// It is an error if the file was not written by [&vsave] or was written by an incompatible version of Uiua.
+    ///
+    /// ex: &vsave "pi.uval" π
+    ///   : &vload "pi.uval"
+    /// You can use [under][&vload] to save the value back after modifying it.
+    /// ex: &vsave "counter.uval" 0
+    ///   : ⍜&vload(+1) "counter.uval"
+    ///   : &vload "counter.uval"
+    (1, ValueLoad, Filesystem, "&vload", "value - load from file"),
+    /// Read a table from a Parquet file
+    ///
+    /// Expects a path and returns a table: a rank-`2` array of boxes whose first row is the column names and whose second row is the columns.
+    /// Numeric and date and time columns become numeric arrays, with nulls as `NaN`. Dates and times are in their stored units.
+    /// Boolean and 8-bit unsigned columns become byte arrays.
+    /// String and binary columns become lists of boxed strings and byte arrays.
+    ///
+    /// Unbox the columns with [un][couple] and [content].
+    /// You can use [under][&parquetr] to write back to the file after modifying the table.
+    ///
+    /// See also: [&parquetw] [&arrowr]
+    (1, ParquetRead, Filesystem, "&parquetr", "parquet - read"),
+    /// Write a table to a Parquet file
+    ///
+    /// Expects a path and a table.
+    /// The table must be a rank-`2` array of boxes whose first row is the column names and whose second row is the columns, like the tables returned by [&parquetr].
+    /// Columns must be rank-`1` numeric arrays or lists of boxed strings. A rank-`2` character array is also a column of strings.
+    /// Columns must all be the same length.
+    ///
+    /// See also: [&parquetr] [&arroww]
+    (2(0), ParquetWrite, Filesystem, "&parquetw", "parquet - write"),
+    /// Read a table from an Arrow IPC file
+    ///
+    /// The table is in the same format as for [&parquetr].
+    /// You can use [under][&arrowr] to write back to the file after modifying the table.
+    ///
+    /// See also: [&arroww]
+    (1, ArrowRead, Filesystem, "&arrowr", "arrow - read"),
+    /// Write a table to an Arrow IPC file
+    ///
+    /// The table must be in the same format as for [&parquetw].
+    ///
+    /// See also: [&arrowr]
+    (2(0), ArrowWrite, Filesystem, "&arroww", "arrow - write"),
     /// Decode an image from a byte array
     ///
     /// Supported formats are `jpg`, `png`, `bmp`, `gif`, `ico`, and `qoi`.
     ///
+    /// The result is a rank 3 array of numbers between 0 and 1. Its axes are the rows, columns, and channels of the image.
+    /// The channels match those of the encoded image: 1 for grayscale, 2 for grayscale with alpha, 3 for RGB, and 4 for RGB with alpha.
+    /// This means the result can be transformed with ordinary array functions and encoded again with [&ime].
+    ///
+    /// On the website, image arrays at least 30 pixels wide and tall are shown in the output.
+    ///
     /// See also: [&ime]
     (1, ImDecode, Images, "&imd", "image - decode"),
     /// Encode an image into a byte array with the specified format
@@ -401,6 +620,15 @@ sys_op! {
     /// Pass that to a periodic function, and you get a nice tone!
     /// ex: ÷4○×τ×220 ÷:⇡×, 4 &asr
     (0, AudioSampleRate, Audio, "&asr", "audio - sample rate"),
+    /// Set the sample rate used for encoding and playing audio
+    ///
+    /// This affects [&asr], [&ae], and [&ap].
+    /// Natively, streaming with [&ast] always uses the sample rate of the output device.
+    ///
+    /// The sample rate must be a positive integer.
+    /// ex: &assr 8000
+    ///   : &asr
+    (1(0), AudioSetSampleRate, Audio, "&assr", "audio - set sample rate"),
     /// Synthesize and stream audio
     ///
     /// Expects a function that takes a list of sample times and returns a list of samples.
@@ -432,6 +660,12 @@ sys_op! {
     /// Returns a stream handle
     /// [under][&tcpc] calls [&cl] automatically.
     (1, TcpConnect, Tcp, "&tcpc", "tcp - connect"),
+    /// Create a TCP socket, connect it to an address, and start a TLS session
+    ///
+    /// The host in the address is used to verify the server's certificate.
+    /// Returns a stream handle that can be used like one from [&tcpc].
+    /// [under][&tlsc] calls [&cl] automatically.
+    (1, TlsConnect, Tcp, "&tlsc", "tls - connect"),
     /// Set a TCP socket to non-blocking mode
     (1, TcpSetNonBlocking, Tcp, "&tcpsnb", "tcp - set non-blocking"),
     /// Set the read timeout of a TCP socket in seconds
@@ -461,6 +695,19 @@ sys_op! {
     /// - The HTTP version
     /// - The `Host` header (if not defined)
     (2, HttpsWrite, Tcp, "&httpsw", "http - Make an HTTP request"),
+    /// Send an HTTP request and receive the response
+    ///
+    /// Takes the method, the URL, a map of headers, and the body.
+    /// Returns the status code, a map of response headers, and the body.
+    /// Both `http://` and `https://` URLs are supported. TLS is handled internally.
+    ///
+    /// The body may be a string or a list of bytes. The response body is a string if it is valid UTF-8, and a list of bytes otherwise.
+    /// The headers may be a map or an empty list.
+    /// `Host`, `Content-Length`, and `Connection` headers are added automatically.
+    ///
+    /// ex! &httpreq "GET" "https://example.com" [] ""
+    /// ex! &httpreq "POST" "https://example.com/api" map {"Content-Type"} {"application/json"} json map {"name"} {"Uiua"}
+    (4(3), HttpRequest, Tcp, "&httpreq", "http - request"),
     /// Call a foreign function interface
     ///
     /// *Warning ⚠️: Using FFI is deeply unsafe. Calling a function incorrectly is undefined behavior.*
@@ -490,7 +737,34 @@ sys_op! {
     ///   : Lib ← &ffi ⊂□"example.dll"
     ///   : Add ← Lib {"int" "add" "int" "int"}
     ///   : Add {2 3} # 5
+    ///
+    /// Struct types are written as their field types in braces, like `{int, double}`. A struct value is a list of its fields.
+    /// Structs can be passed and returned by value or through pointers.
+    /// ex! # Experimental!
+    ///   : Lib ← &ffi ⊂□"example.dll"
+    ///   : Lib {"{int, double}" "scale" "{int, double}" "double"} {{2 1.5} 2} # {4 3}
+    ///
+    /// Arguments whose types are non-`const` pointers or lists, other than strings, are out-parameters.
+    /// Their values after the call are returned after the function's return value, all in a boxed list.
+    /// ex! # Experimental!
+    ///   : Lib ← &ffi ⊂□"example.dll"
+    ///   : Lib {"void" "reverse" "int:1" "int"} {[1 2 3]} # [3 2 1]
+    ///
+    /// Function pointer types are written like `int(int, int)`. The argument for one must be a callback created with [&ffcb].
     (2, FFI, Misc, "&ffi", "foreign function interface"),
+    /// Register a function as an FFI callback
+    ///
+    /// Returns a callback id that can be passed to [&ffi] as a function pointer argument.
+    /// When the foreign function calls the pointer, its arguments are pushed to the stack with the first one on top, and the function is called.
+    /// The function's signature must match the function pointer type.
+    /// Errors in the function are reported when the foreign function returns.
+    ///
+    /// Callbacks can only be called during the [&ffi] call they are passed to, on the thread that made it.
+    /// Calling one from another thread is an error. Calling one after the call has returned is undefined behavior.
+    /// ex! # Experimental!
+    ///   : Lib ← &ffi ⊂□"example.dll"
+    ///   : Lib {"int" "apply" "int(int, int)" "int" "int"} {&ffcb+ 2 3} # 5
+    (0[1], FfiCallback, Misc, "&ffcb", "ffi - callback"),
 }
 
 /// A handle to an IO stream
@@ -498,13 +772,13 @@ sys_op! {
 /// 0 is stdin, 1 is stdout, 2 is stderr.
 ///
 /// Other handles can be used by files or sockets.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Handle(pub u64);
 
 impl Handle {
     const STDIN: Self = Self(0);
-    const STDOUT: Self = Self(1);
-    const STDERR: Self = Self(2);
+    pub(crate) const STDOUT: Self = Self(1);
+    pub(crate) const STDERR: Self = Self(2);
     /// The first handle that can be used by the user
     pub const FIRST_UNRESERVED: Self = Self(3);
 }
@@ -521,6 +795,17 @@ impl From<Handle> for Value {
     }
 }
 
+/// The response to an HTTP request made with `&httpreq`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpResponse {
+    /// The status code
+    pub status: u16,
+    /// The response headers
+    pub headers: Vec<(String, String)>,
+    /// The response body
+    pub body: Vec<u8>,
+}
+
 /// The function type passed to `&ast`
 pub type AudioStreamFn = Box<dyn FnMut(&[f64]) -> UiuaResult<Vec<[f64; 2]>> + Send>;
 
@@ -564,6 +849,14 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     fn term_size(&self) -> Result<(usize, usize), String> {
         Err("Getting the terminal size is not supported in this environment".into())
     }
+    /// Get the text contents of the clipboard
+    fn clipboard(&self) -> Result<String, String> {
+        Err("Getting the clipboard is not supported in this environment".into())
+    }
+    /// Set the text contents of the clipboard
+    fn set_clipboard(&self, contents: &str) -> Result<(), String> {
+        Err("Setting the clipboard is not supported in this environment".into())
+    }
     /// Check if a file exists
     fn file_exists(&self, path: &str) -> bool {
         false
@@ -576,6 +869,12 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     fn is_file(&self, path: &str) -> Result<bool, String> {
         Err("This IO operation is not supported in this environment".into())
     }
+    /// Wait for a path to change, returning the kind of change
+    ///
+    /// Returns `None` if the timeout passes first.
+    fn watch_path(&self, path: &str, timeout: Option<Duration>) -> Result<Option<String>, String> {
+        Err("Watching files is not supported in this environment".into())
+    }
     /// Delete a file or directory
     fn delete(&self, path: &str) -> Result<(), String> {
         Err("This IO operation is not supported in this environment".into())
@@ -615,6 +914,12 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     fn open_file(&self, path: &Path) -> Result<Handle, String> {
         Err("This IO operation is not supported in this environment".into())
     }
+    /// Open a stream that reads from an embedder-provided reader
+    ///
+    /// Reading the stream with `&rs`, `&rb`, or `&ru` pulls data from the reader in chunks.
+    fn open_reader(&self, reader: Box<dyn Read + Send + Sync>) -> Result<Handle, String> {
+        Err("Reading streams is not supported in this environment".into())
+    }
     /// Read all bytes from a file
     fn file_read_all(&self, path: &Path) -> Result<Vec<u8>, String> {
         let handle = self.open_file(path)?;
@@ -633,6 +938,20 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     fn sleep(&self, seconds: f64) -> Result<(), String> {
         Err("Sleeping is not supported in this environment".into())
     }
+    /// Get the current time in seconds since the Unix epoch
+    fn now(&self) -> f64 {
+        instant::now() / 1000.0
+    }
+    /// Get a seed for random numbers
+    ///
+    /// If this returns `Some`, the random numbers of a [`Uiua`] created with this backend are reproducible.
+    fn rng_seed(&self) -> Option<u64> {
+        None
+    }
+    /// Generate cryptographically secure random bytes
+    fn random_bytes(&self, count: usize) -> Result<Vec<u8>, String> {
+        Err("Secure random bytes are not supported in this environment".into())
+    }
     /// Show an image
     #[cfg(feature = "image")]
     fn show_image(&self, image: DynamicImage) -> Result<(), String> {
@@ -650,6 +969,10 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     fn audio_sample_rate(&self) -> u32 {
         44100
     }
+    /// Set the audio sample rate
+    fn set_audio_sample_rate(&self, sample_rate: u32) -> Result<(), String> {
+        Err("Setting the audio sample rate is not supported in this environment".into())
+    }
     /// Stream audio
     fn stream_audio(&self, f: AudioStreamFn) -> Result<(), String> {
         Err("Streaming audio not supported in this environment".into())
@@ -666,6 +989,10 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     fn tcp_connect(&self, addr: &str) -> Result<Handle, String> {
         Err("TCP sockets are not supported in this environment".into())
     }
+    /// Create a TCP socket, connect it to an address, and start a TLS session
+    fn tls_connect(&self, addr: &str) -> Result<Handle, String> {
+        Err("TLS sockets are not supported in this environment".into())
+    }
     /// Get the connection address of a TCP socket
     fn tcp_addr(&self, handle: Handle) -> Result<String, String> {
         Err("TCP sockets are not supported in this environment".into())
@@ -710,6 +1037,24 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     ) -> Result<(i32, String, String), String> {
         Err("Running commands is not supported in this environment".into())
     }
+    /// Run a command with streaming IO
+    ///
+    /// Returns a handle to the process and handles to its stdin, stdout, and stderr
+    fn run_command_stream(
+        &self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<(Handle, [Handle; 3]), String> {
+        Err("Running commands is not supported in this environment".into())
+    }
+    /// Wait for a process to finish and get its exit code
+    fn wait_process(&self, handle: Handle) -> Result<i32, String> {
+        Err("Running commands is not supported in this environment".into())
+    }
+    /// Kill a process
+    fn kill_process(&self, handle: Handle) -> Result<(), String> {
+        Err("Running commands is not supported in this environment".into())
+    }
     /// Change the current directory
     fn change_directory(&self, path: &str) -> Result<(), String> {
         Err("Changing directories is not supported in this environment".into())
@@ -718,6 +1063,16 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     fn https_get(&self, request: &str, handle: Handle) -> Result<String, String> {
         Err("Making HTTPS requests is not supported in this environment".into())
     }
+    /// Send an HTTP request and receive the status, headers, and body of the response
+    fn http_request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> Result<HttpResponse, String> {
+        Err("Making HTTP requests is not supported in this environment".into())
+    }
     /// Call a foreign function interface
     fn ffi(
         &self,
@@ -770,6 +1125,60 @@ impl IntoSysBackend for Arc<dyn SysBackend> {
 }
 
 impl SysOp {
+    /// Get the names of the roles of the system function's arguments
+    ///
+    /// The first name is for the argument on the top of the stack.
+    pub fn arg_names(&self) -> &'static [&'static str] {
+        use SysOp::*;
+        const PATH: &[&str] = &["the path"];
+        const BYTES: &[&str] = &["the bytes"];
+        const SOCKET: &[&str] = &["the socket handle"];
+        match self {
+            Show | Prin | Print => &["the value"],
+            ScanLine | TermSize | Args | AudioSampleRate | AudioStream | MemoClear => &[],
+            RawMode => &["whether to enable raw mode"],
+            Var => &["the variable name"],
+            ClipboardGet => &[],
+            ClipboardSet => &["the text"],
+            RunInherit | RunCapture | RunStream => &["the command"],
+            Wait | Kill => &["the process handle"],
+            ChangeDirectory | Invoke => PATH,
+            Sleep => &["the number of seconds"],
+            TimeFormat => &["the format", "the time"],
+            TimeParse => &["the format", "the string"],
+            TimeDecompose => &["the times"],
+            RandomBytes => &["the count"],
+            Threads => &["the thread count"],
+            Channel => &["the capacity"],
+            ReadStr | ReadBytes => &["the count", "the stream handle"],
+            FWatch => &["the timeout", "the path"],
+            ReadUntil => &["the delimiter", "the stream handle"],
+            Write => &["the data", "the stream handle"],
+            Import => &["the path", "the item name"],
+            Close => &["the handle"],
+            FOpen | FCreate | FDelete | FTrash | FExists | FListDir | FIsFile | FReadAllStr
+            | FReadAllBytes | ValueLoad => PATH,
+            FWriteAll => &["the path", "the data"],
+            ValueSave => &["the path", "the value"],
+            ParquetRead | ArrowRead => PATH,
+            ParquetWrite | ArrowWrite => &["the path", "the table"],
+            ImDecode | GifDecode | AudioDecode | Gzip | Gunzip => BYTES,
+            ImEncode => &["the format", "the image"],
+            ImShow => &["the image"],
+            GifEncode | GifShow => &["the framerate", "the gif data"],
+            AudioEncode => &["the format", "the samples"],
+            AudioPlay => &["the samples"],
+            AudioSetSampleRate => &["the sample rate"],
+            TcpListen | TcpConnect | TlsConnect => &["the address"],
+            TcpAccept => &["the listener handle"],
+            TcpSetNonBlocking | TcpAddr => SOCKET,
+            TcpSetReadTimeout | TcpSetWriteTimeout => &["the timeout", "the socket handle"],
+            HttpsWrite => &["the request", "the socket handle"],
+            HttpRequest => &["the method", "the url", "the headers", "the body"],
+            FFI => &["the function signature", "the arguments"],
+            FfiCallback => &[],
+        }
+    }
     pub(crate) fn run(&self, env: &mut Uiua) -> UiuaResult {
         match self {
             SysOp::Show => {
@@ -829,8 +1238,25 @@ impl SysOp {
                 let key = env
                     .pop(1)?
                     .as_string(env, "Augument to var must be a string")?;
-                let var = env.rt.backend.var(&key).unwrap_or_default();
-                env.push(var);
+                match env.rt.backend.var(&key) {
+                    Some(var) => env.push(var),
+                    None => match env.value_fill().cloned() {
+                        Some(fill) => env.push(fill),
+                        None => env.push(String::new()),
+                    },
+                }
+            }
+            SysOp::ClipboardGet => {
+                let contents = env.rt.backend.clipboard().map_err(|e| env.error(e))?;
+                env.push(contents);
+            }
+            SysOp::ClipboardSet => {
+                let contents = env
+                    .pop(1)?
+                    .as_string(env, "Clipboard contents must be a string")?;
+                (env.rt.backend)
+                    .set_clipboard(&contents)
+                    .map_err(|e| env.error(e))?;
             }
             SysOp::FOpen => {
                 let path = env.pop(1)?.as_string(env, "Path must be a string")?;
@@ -902,7 +1328,7 @@ impl SysOp {
                 env.push(Array::from(bytes.as_slice()));
             }
             SysOp::ReadUntil => {
-                let delim = env.pop(1)?;
+                let delim = env.pop(1)?.promote_ints();
                 let handle = env
                     .pop(2)?
                     .as_nat(env, "Handle must be an natural number")?
@@ -985,6 +1411,7 @@ impl SysOp {
                     Value::Num(arr) => arr.data.iter().map(|&x| x as u8).collect(),
                     #[cfg(feature = "bytes")]
                     Value::Byte(arr) => arr.data.into(),
+                    Value::Int(arr) => arr.data.iter().map(|&x| x as u8).collect(),
 
                     Value::Complex(_) => {
                         return Err(env.error("Cannot write complex array to file"))
@@ -1041,6 +1468,99 @@ impl SysOp {
                 let bytes = bytes.into_iter().map(Into::into);
                 env.push(Array::<u8>::from_iter(bytes));
             }
+            SysOp::MemoClear => env.memo_clear(),
+            SysOp::Channel => {
+                let cap = env.pop(1)?;
+                let infinite = cap.as_num(env, "Capacity must be a number")? == f64::INFINITY;
+                let cap = if infinite {
+                    None
+                } else {
+                    Some(cap.as_nat(env, "Capacity must be a natural number or infinity")?)
+                };
+                let id = env.create_channel(cap);
+                env.push(id as f64);
+            }
+            SysOp::Threads => {
+                let count = env.pop(1)?;
+                let infinite = count.as_num(env, "Thread count must be a number")? == f64::INFINITY;
+                let count = if infinite {
+                    0
+                } else {
+                    count.as_nat(env, "Thread count must be a natural number or infinity")?
+                };
+                let prev = env.spawn_limit().map_or(f64::INFINITY, |n| n as f64);
+                if count > 0 || infinite {
+                    env.set_spawn_limit(count);
+                }
+                env.push(prev);
+            }
+            SysOp::ValueSave => {
+                let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                let value = env.pop(2)?;
+                (env.rt.backend)
+                    .file_write_all(path.as_ref(), &value.to_uval())
+                    .map_err(|e| env.error(e))?;
+            }
+            SysOp::ValueLoad => {
+                let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                let bytes = (env.rt.backend)
+                    .file_read_all(path.as_ref())
+                    .map_err(|e| env.error(e))?;
+                let value = Value::from_uval(&bytes).map_err(|e| env.error(e))?;
+                env.push(value);
+            }
+            SysOp::ParquetRead => {
+                #[cfg(feature = "parquet")]
+                {
+                    let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                    let bytes = (env.rt.backend)
+                        .file_read_all(path.as_ref())
+                        .map_err(|e| env.error(e))?;
+                    let table = parquet_to_value(&bytes).map_err(|e| env.error(e))?;
+                    env.push(table);
+                }
+                #[cfg(not(feature = "parquet"))]
+                return Err(env.error("Parquet files are not supported in this environment"));
+            }
+            SysOp::ParquetWrite => {
+                #[cfg(feature = "parquet")]
+                {
+                    let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                    let table = env.pop(2)?;
+                    let bytes = value_to_parquet(&table).map_err(|e| env.error(e))?;
+                    (env.rt.backend)
+                        .file_write_all(path.as_ref(), &bytes)
+                        .map_err(|e| env.error(e))?;
+                }
+                #[cfg(not(feature = "parquet"))]
+                return Err(env.error("Parquet files are not supported in this environment"));
+            }
+            SysOp::ArrowRead => {
+                #[cfg(feature = "arrow")]
+                {
+                    let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                    let bytes = (env.rt.backend)
+                        .file_read_all(path.as_ref())
+                        .map_err(|e| env.error(e))?;
+                    let table = arrow_to_value(&bytes).map_err(|e| env.error(e))?;
+                    env.push(table);
+                }
+                #[cfg(not(feature = "arrow"))]
+                return Err(env.error("Arrow files are not supported in this environment"));
+            }
+            SysOp::ArrowWrite => {
+                #[cfg(feature = "arrow")]
+                {
+                    let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                    let table = env.pop(2)?;
+                    let bytes = value_to_arrow(&table).map_err(|e| env.error(e))?;
+                    (env.rt.backend)
+                        .file_write_all(path.as_ref(), &bytes)
+                        .map_err(|e| env.error(e))?;
+                }
+                #[cfg(not(feature = "arrow"))]
+                return Err(env.error("Arrow files are not supported in this environment"));
+            }
             SysOp::FWriteAll => {
                 let path = env.pop(1)?.as_string(env, "Path must be a string")?;
                 let data = env.pop(2)?;
@@ -1048,6 +1568,7 @@ impl SysOp {
                     Value::Num(arr) => arr.data.iter().map(|&x| x as u8).collect(),
                     #[cfg(feature = "bytes")]
                     Value::Byte(arr) => arr.data.into(),
+                    Value::Int(arr) => arr.data.iter().map(|&x| x as u8).collect(),
 
                     Value::Complex(_) => {
                         return Err(env.error("Cannot write complex array to file"))
@@ -1084,6 +1605,19 @@ impl SysOp {
                 let is_file = env.rt.backend.is_file(&path).map_err(|e| env.error(e))?;
                 env.push(is_file);
             }
+            SysOp::FWatch => {
+                let timeout = env.pop(1)?.as_num(env, "Timeout must be a number")?.abs();
+                let timeout = if timeout.is_infinite() {
+                    None
+                } else {
+                    Some(Duration::from_secs_f64(timeout))
+                };
+                let path = env.pop(2)?.as_string(env, "Path must be a string")?;
+                let kind = (env.rt.backend)
+                    .watch_path(&path, timeout)
+                    .map_err(|e| env.error(e))?;
+                env.push(kind.unwrap_or_default());
+            }
             SysOp::Import => {
                 return Err(env.error(
                     "&i is not valid in this position. \
@@ -1120,15 +1654,20 @@ impl SysOp {
                         _ => return Err(env.error("Image bytes must be a numeric array")),
                     };
                     let image = image::load_from_memory(&bytes)
-                        .map_err(|e| env.error(format!("Failed to read image: {}", e)))?
-                        .into_rgba8();
-                    let shape =
-                        crate::Shape::from([image.height() as usize, image.width() as usize, 4]);
+                        .map_err(|e| env.error(format!("Failed to read image: {}", e)))?;
+                    let (width, height) = (image.width() as usize, image.height() as usize);
+                    // Keep the channels of the source image
+                    let channels = image.color().channel_count().min(4) as usize;
+                    let raw = match channels {
+                        1 => image.into_luma8().into_raw(),
+                        2 => image.into_luma_alpha8().into_raw(),
+                        3 => image.into_rgb8().into_raw(),
+                        _ => image.into_rgba8().into_raw(),
+                    };
+                    let shape = crate::Shape::from([height, width, channels]);
                     let array = Array::<f64>::new(
                         shape,
-                        image
-                            .into_raw()
-                            .into_iter()
+                        raw.into_iter()
                             .map(|b| b as f64 / 255.0)
                             .collect::<crate::cowslice::CowSlice<_>>(),
                     );
@@ -1273,6 +1812,19 @@ impl SysOp {
                 let sample_rate = env.rt.backend.audio_sample_rate();
                 env.push(f64::from(sample_rate));
             }
+            SysOp::AudioSetSampleRate => {
+                let sample_rate = env
+                    .pop(1)?
+                    .as_nat(env, "Sample rate must be a natural number")?;
+                if sample_rate == 0 {
+                    return Err(env.error("Sample rate must be positive"));
+                }
+                let sample_rate = u32::try_from(sample_rate)
+                    .map_err(|_| env.error(format!("Sample rate {sample_rate} is too large")))?;
+                (env.rt.backend)
+                    .set_audio_sample_rate(sample_rate)
+                    .map_err(|e| env.error(e))?;
+            }
             SysOp::AudioStream => {
                 let f = env.pop_function()?;
                 if f.signature() != (1, 1) {
@@ -1314,6 +1866,86 @@ impl SysOp {
                     .max(0.0);
                 env.rt.backend.sleep(seconds).map_err(|e| env.error(e))?;
             }
+            SysOp::TimeFormat => {
+                let format = env.pop(1)?.as_string(env, "Time format must be a string")?;
+                let time = env.pop(2)?.as_num(env, "Time must be a number")?;
+                let formatted = DateTime::from_epoch(time)
+                    .and_then(|time| time.format(&format))
+                    .map_err(|e| env.error(e))?;
+                env.push(formatted);
+            }
+            SysOp::TimeParse => {
+                let format = env.pop(1)?.as_string(env, "Time format must be a string")?;
+                let s = env
+                    .pop(2)?
+                    .as_string(env, "Time to parse must be a string")?;
+                let time = DateTime::parse(&s, &format).map_err(|e| env.error(e))?;
+                env.push(time.to_epoch());
+            }
+            SysOp::TimeDecompose => {
+                let times = env.pop(1)?;
+                let times = times.as_number_array(
+                    env,
+                    "Times must be numbers",
+                    |_| true,
+                    |_| true,
+                    |n| n,
+                )?;
+                let mut data = ecow::EcoVec::with_capacity(times.data.len() * 6);
+                for &time in times.data.iter() {
+                    let t = DateTime::from_epoch(time).map_err(|e| env.error(e))?;
+                    data.extend([
+                        t.year as f64,
+                        t.month as f64,
+                        t.day as f64,
+                        t.hour as f64,
+                        t.minute as f64,
+                        t.second,
+                    ]);
+                }
+                let mut shape = times.shape.clone();
+                shape.push(6);
+                env.push(Array::new(shape, data));
+            }
+            SysOp::Gzip => {
+                #[cfg(feature = "gzip")]
+                {
+                    let bytes = env
+                        .pop(1)?
+                        .as_bytes(env, "Data to compress must be a byte array")?;
+                    let mut encoder =
+                        flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                    encoder
+                        .write_all(&bytes)
+                        .map_err(|e| env.error(e.to_string()))?;
+                    let compressed = encoder.finish().map_err(|e| env.error(e.to_string()))?;
+                    env.push(Array::<u8>::from(compressed.as_slice()));
+                }
+                #[cfg(not(feature = "gzip"))]
+                return Err(env.error("Gzip compression is not supported in this environment"));
+            }
+            SysOp::RandomBytes => {
+                let count = env.pop(1)?.as_nat(env, "Count must be a natural number")?;
+                let bytes = (env.rt.backend)
+                    .random_bytes(count)
+                    .map_err(|e| env.error(e))?;
+                env.push(Array::<u8>::from(bytes.as_slice()));
+            }
+            SysOp::Gunzip => {
+                #[cfg(feature = "gzip")]
+                {
+                    let bytes = env
+                        .pop(1)?
+                        .as_bytes(env, "Data to decompress must be a byte array")?;
+                    let mut decompressed = Vec::new();
+                    flate2::read::MultiGzDecoder::new(bytes.as_slice())
+                        .read_to_end(&mut decompressed)
+                        .map_err(|e| env.error(format!("Failed to decompress gzip data: {e}")))?;
+                    env.push(Array::<u8>::from(decompressed.as_slice()));
+                }
+                #[cfg(not(feature = "gzip"))]
+                return Err(env.error("Gzip compression is not supported in this environment"));
+            }
             SysOp::TcpListen => {
                 let addr = env.pop(1)?.as_string(env, "Address must be a string")?;
                 let handle = env.rt.backend.tcp_listen(&addr).map_err(|e| env.error(e))?;
@@ -1336,6 +1968,13 @@ impl SysOp {
                     .map_err(|e| env.error(e))?;
                 env.push(handle);
             }
+            SysOp::TlsConnect => {
+                let addr = env.pop(1)?.as_string(env, "Address must be a string")?;
+                let handle = (env.rt.backend)
+                    .tls_connect(&addr)
+                    .map_err(|e| env.error(e))?;
+                env.push(handle);
+            }
             SysOp::TcpAddr => {
                 let handle = env
                     .pop(1)?
@@ -1399,12 +2038,62 @@ impl SysOp {
                     .map_err(|e| env.error(e))?;
                 env.push(res);
             }
+            SysOp::HttpRequest => {
+                let method = env.pop(1)?.as_string(env, "HTTP method must be a string")?;
+                let url = env.pop(2)?.as_string(env, "URL must be a string")?;
+                let headers = env.pop(3)?;
+                let headers = if headers.meta().map_len.is_some() {
+                    let (keys, values) = headers.unmap(env)?;
+                    let keys = keys.into_rows().map(Value::unboxed);
+                    let values = values.into_rows().map(Value::unboxed);
+                    keys.zip(values)
+                        .map(|(k, v)| {
+                            Ok((
+                                k.as_string(env, "Header names must be strings")?,
+                                v.as_string(env, "Header values must be strings")?,
+                            ))
+                        })
+                        .collect::<UiuaResult<Vec<_>>>()?
+                } else if headers.row_count() == 0 {
+                    Vec::new()
+                } else {
+                    return Err(env.error(format!(
+                        "HTTP headers must be a map, but they are {}",
+                        headers.type_name_plural()
+                    )));
+                };
+                let body: Vec<u8> = match env.pop(4)? {
+                    Value::Complex(_) => {
+                        return Err(env.error("HTTP body cannot be a complex array"))
+                    }
+                    Value::Char(arr) => arr.data.iter().collect::<String>().into(),
+                    Value::Box(_) => return Err(env.error("HTTP body cannot be a box array")),
+                    body => body.as_bytes(env, "HTTP body must be a string or a list of bytes")?,
+                };
+                let res = (env.rt.backend)
+                    .http_request(&method, &url, &headers, &body)
+                    .map_err(|e| env.error(e))?;
+                let body = match String::from_utf8(res.body) {
+                    Ok(s) => Value::from(s),
+                    Err(e) => e.into_bytes().into_iter().collect(),
+                };
+                let (keys, values): (Vec<_>, Vec<_>) = (res.headers.into_iter())
+                    .map(|(k, v)| (Boxed(k.into()), Boxed(v.into())))
+                    .unzip();
+                let keys: Value = keys.into_iter().collect::<Array<Boxed>>().into();
+                let values: Value = values.into_iter().collect::<Array<Boxed>>().into();
+                env.push(body);
+                env.push(keys.map(values, env)?);
+                env.push(res.status as f64);
+            }
             SysOp::Close => {
-                let handle = env
-                    .pop(1)?
-                    .as_nat(env, "Handle must be an natural number")?
-                    .into();
-                env.rt.backend.close(handle).map_err(|e| env.error(e))?;
+                let handle = env.pop(1)?.as_int(env, "Handle must be an integer")?;
+                if handle < 0 {
+                    env.close_channel(handle)?;
+                } else {
+                    let handle = (handle as usize).into();
+                    env.rt.backend.close(handle).map_err(|e| env.error(e))?;
+                }
             }
             SysOp::RunInherit => {
                 let (command, args) = value_to_command(&env.pop(1)?, env)?;
@@ -1424,6 +2113,36 @@ impl SysOp {
                 env.push(stdout);
                 env.push(code);
             }
+            SysOp::RunStream => {
+                let (command, args) = value_to_command(&env.pop(1)?, env)?;
+                let args: Vec<_> = args.iter().map(|s| s.as_str()).collect();
+                let (handle, [stdin, stdout, stderr]) = (env.rt.backend)
+                    .run_command_stream(&command, &args)
+                    .map_err(|e| env.error(e))?;
+                env.push(stderr);
+                env.push(stdout);
+                env.push(stdin);
+                env.push(handle);
+            }
+            SysOp::Wait => {
+                let handle = env
+                    .pop(1)?
+                    .as_nat(env, "Handle must be an natural number")?
+                    .into();
+                let code = (env.rt.backend)
+                    .wait_process(handle)
+                    .map_err(|e| env.error(e))?;
+                env.push(code);
+            }
+            SysOp::Kill => {
+                let handle = env
+                    .pop(1)?
+                    .as_nat(env, "Handle must be an natural number")?
+                    .into();
+                (env.rt.backend)
+                    .kill_process(handle)
+                    .map_err(|e| env.error(e))?;
+            }
             SysOp::ChangeDirectory => {
                 let path = env.pop(1)?.as_string(env, "Path must be a string")?;
                 env.rt
@@ -1468,11 +2187,18 @@ impl SysOp {
                     .collect::<UiuaResult<Vec<_>>>()?;
                 let args = env.pop(2)?;
                 let args: Vec<Value> = args.into_rows().map(Value::unpacked).collect();
-                let result = (env.rt.backend)
-                    .ffi(&file_name, result_ty, &name, &arg_tys, &args)
-                    .map_err(|e| env.error(e))?;
+                let backend = env.rt.backend.clone();
+                let result = crate::ffi::with_ffi_callbacks(env, || {
+                    backend.ffi(&file_name, result_ty, &name, &arg_tys, &args)
+                })?
+                .map_err(|e| env.error(e))?;
                 env.push(result);
             }
+            SysOp::FfiCallback => {
+                let f = env.pop_function()?;
+                env.rt.ffi_callbacks.push(f);
+                env.push((env.rt.ffi_callbacks.len() - 1) as f64);
+            }
         }
         Ok(())
     }
@@ -1519,7 +2245,7 @@ fn value_to_command(value: &Value, env: &Uiua) -> UiuaResult<(String, Vec<String
                 )))
             }
         },
-        Value::Num(_) => {
+        Value::Num(_) | Value::Int(_) => {
             return Err(env.error(format!(
                 "Command must be a string or box array, but it is {}",
                 value.type_name_plural()
@@ -1581,6 +2307,7 @@ pub fn value_to_image(value: &Value) -> Result<DynamicImage, String> {
             .collect(),
         #[cfg(feature = "bytes")]
         Value::Byte(bytes) => bytes.data.iter().map(|&b| (b > 0) as u8 * 255).collect(),
+        Value::Int(ints) => ints.data.iter().map(|&i| (i > 0) as u8 * 255).collect(),
         _ => return Err("Image must be a numeric array".into()),
     };
     #[allow(clippy::match_ref_pats)]
@@ -1616,6 +2343,7 @@ pub fn value_to_sample(audio: &Value) -> Result<Vec<[f32; 2]>, String> {
         Value::Num(nums) => nums.data.iter().map(|&f| f as f32).collect(),
         #[cfg(feature = "bytes")]
         Value::Byte(byte) => byte.data.iter().map(|&b| b as f32).collect(),
+        Value::Int(int) => int.data.iter().map(|&i| i as f32).collect(),
         _ => return Err("Audio must be a numeric array".into()),
     };
     let (length, mut channels) = match audio.rank() {
@@ -1657,6 +2385,7 @@ pub fn value_to_audio_channels(audio: &Value) -> Result<Vec<Vec<f64>>, String> {
         Value::Num(nums) => nums.data.iter().copied().collect(),
         #[cfg(feature = "bytes")]
         Value::Byte(byte) => byte.data.iter().map(|&b| b as f64).collect(),
+        Value::Int(int) => int.data.iter().map(|&i| i as f64).collect(),
         _ => return Err("Audio must be a numeric array".into()),
     };
     let (length, mut channels) = match audio.rank() {
@@ -1861,11 +2590,13 @@ pub fn value_to_gif_bytes(value: &Value, frame_rate: f64) -> Result<Vec<u8>, Str
         }
         break used_colors;
     };
+    let adder = reduction - 1;
+    let reduce = |color: [u8; 3]| color.map(|p| p.saturating_add(adder) / reduction);
     let mut palette = Vec::with_capacity(used_colors.len() * 3);
-    let mut color_map: HashMap<[u8; 3], usize> = HashMap::new();
+    let mut color_map: HashMap<[u8; 3], u8> = HashMap::new();
     for color in used_colors {
-        color_map.insert(color, palette.len() / 3);
-        palette.extend(color);
+        color_map.insert(color, (palette.len() / 3) as u8);
+        palette.extend(color.map(|p| p.saturating_mul(reduction)));
     }
     let mut encoder = gif::Encoder::new(&mut bytes, width as u16, height as u16, &palette)
         .map_err(|e| e.to_string())?;
@@ -1875,8 +2606,17 @@ pub fn value_to_gif_bytes(value: &Value, frame_rate: f64) -> Result<Vec<u8>, Str
         .set_repeat(gif::Repeat::Infinite)
         .map_err(|e| e.to_string())?;
     for image in frames {
-        let mut frame = gif::Frame::from_rgb(width as u16, height as u16, image.as_raw());
-        frame.delay = delay;
+        // Index into the global palette so that colors are kept exactly when possible
+        let buffer: Vec<u8> = (image.pixels())
+            .map(|pixel| color_map[&reduce(pixel.0)])
+            .collect();
+        let frame = gif::Frame {
+            width: width as u16,
+            height: height as u16,
+            buffer: buffer.into(),
+            delay,
+            ..Default::default()
+        };
         encoder.write_frame(&frame).map_err(|e| e.to_string())?;
     }
     drop(encoder);
@@ -1894,15 +2634,15 @@ pub fn gif_bytes_to_value(bytes: &[u8]) -> Result<(f64, Value), gif::DecodingErr
     let height = first_frame.height;
     let mut data: crate::cowslice::CowSlice<f64> = Default::default();
     let mut frame_count = 1;
-    let mut delay_sum = first_frame.delay as f64 / 100.0;
+    // Delays are in hundredths of a second
+    let mut delay_sum = first_frame.delay as u64;
     data.extend(first_frame.buffer.iter().map(|&b| b as f64 / 255.0));
     while let Some(frame) = decoder.read_next_frame()? {
         data.extend(frame.buffer.iter().map(|&b| b as f64 / 255.0));
         frame_count += 1;
-        delay_sum += frame.delay as f64 / 100.0;
+        delay_sum += frame.delay as u64;
     }
-    let avg_delay = delay_sum / frame_count as f64;
-    let frame_rate = 1.0 / avg_delay;
+    let frame_rate = 100.0 * frame_count as f64 / delay_sum as f64;
     let shape = crate::Shape::from_iter([frame_count, height as usize, width as usize, 4]);
     let mut num = Value::Num(Array::new(shape, data));
     num.compress();
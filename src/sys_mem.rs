@@ -0,0 +1,390 @@
+use std::{
+    any::Any,
+    collections::{BTreeMap, HashMap},
+    io::Read,
+    path::{Component, Path, PathBuf},
+    sync::atomic::{self, AtomicU64},
+    time::Duration,
+};
+
+#[cfg(feature = "image")]
+use image::DynamicImage;
+use parking_lot::Mutex;
+
+use crate::{AudioStreamFn, FfiType, Handle, HttpResponse, SafeSys, SysBackend, Value};
+
+/// A system backend with an in-memory filesystem
+///
+/// Files can be added before running a program and inspected afterwards.
+/// Directories exist implicitly if there are files in them.
+///
+/// Everything other than files is passed through to an inner backend,
+/// which is [`SafeSys`] by default.
+pub struct MemFs<B = SafeSys> {
+    inner: B,
+    files: Mutex<BTreeMap<PathBuf, Vec<u8>>>,
+    open: Mutex<HashMap<Handle, OpenFile>>,
+    next_handle: AtomicU64,
+}
+
+/// A file opened for reading or writing
+struct OpenFile {
+    path: PathBuf,
+    /// The read position, or `None` if the file was created for writing
+    cursor: Option<usize>,
+}
+
+impl Default for MemFs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemFs {
+    /// Create an empty in-memory filesystem
+    pub fn new() -> Self {
+        Self::wrap(SafeSys)
+    }
+}
+
+impl<B: SysBackend> MemFs<B> {
+    /// Create an empty in-memory filesystem that passes non-file operations to a backend
+    pub fn wrap(inner: B) -> Self {
+        Self {
+            inner,
+            files: Mutex::new(BTreeMap::new()),
+            open: Mutex::new(HashMap::new()),
+            next_handle: Handle::FIRST_UNRESERVED.0.into(),
+        }
+    }
+    /// Add a file
+    pub fn with_file(self, path: impl AsRef<Path>, contents: impl Into<Vec<u8>>) -> Self {
+        self.insert_file(path, contents);
+        self
+    }
+    /// Add or replace a file
+    pub fn insert_file(&self, path: impl AsRef<Path>, contents: impl Into<Vec<u8>>) {
+        let path = normalize(path.as_ref());
+        self.files.lock().insert(path, contents.into());
+    }
+    /// Get the contents of a file
+    pub fn file(&self, path: impl AsRef<Path>) -> Option<Vec<u8>> {
+        self.files.lock().get(&normalize(path.as_ref())).cloned()
+    }
+    /// Get the paths of all files, in sorted order
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.files.lock().keys().cloned().collect()
+    }
+    /// Get the inner backend
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+    fn is_dir(&self, path: &Path) -> bool {
+        path.as_os_str().is_empty() || (self.files.lock().keys()).any(|p| p.starts_with(path))
+    }
+    fn new_handle(&self, file: OpenFile) -> Handle {
+        let handle = Handle(self.next_handle.fetch_add(1, atomic::Ordering::Relaxed));
+        self.open.lock().insert(handle, file);
+        handle
+    }
+}
+
+/// Normalize a path so that equivalent paths are the same key
+///
+/// Paths are relative to the root of the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for comp in path.components() {
+        match comp {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::Normal(part) => normalized.push(part),
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+        }
+    }
+    normalized
+}
+
+impl<B: SysBackend> SysBackend for MemFs<B> {
+    fn any(&self) -> &dyn Any {
+        self
+    }
+    fn any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn save_error_color(&self, message: String, colored: String) {
+        self.inner.save_error_color(message, colored)
+    }
+    fn print_str_stdout(&self, s: &str) -> Result<(), String> {
+        self.inner.print_str_stdout(s)
+    }
+    fn print_str_stderr(&self, s: &str) -> Result<(), String> {
+        self.inner.print_str_stderr(s)
+    }
+    fn print_str_trace(&self, s: &str) {
+        self.inner.print_str_trace(s)
+    }
+    fn scan_line_stdin(&self) -> Result<Option<String>, String> {
+        self.inner.scan_line_stdin()
+    }
+    fn set_raw_mode(&self, raw_mode: bool) -> Result<(), String> {
+        self.inner.set_raw_mode(raw_mode)
+    }
+    fn var(&self, name: &str) -> Option<String> {
+        self.inner.var(name)
+    }
+    fn term_size(&self) -> Result<(usize, usize), String> {
+        self.inner.term_size()
+    }
+    fn clipboard(&self) -> Result<String, String> {
+        self.inner.clipboard()
+    }
+    fn set_clipboard(&self, contents: &str) -> Result<(), String> {
+        self.inner.set_clipboard(contents)
+    }
+    fn random_bytes(&self, count: usize) -> Result<Vec<u8>, String> {
+        self.inner.random_bytes(count)
+    }
+    fn file_exists(&self, path: &str) -> bool {
+        let path = normalize(path.as_ref());
+        let is_file = self.files.lock().contains_key(&path);
+        is_file || self.is_dir(&path)
+    }
+    fn list_dir(&self, path: &str) -> Result<Vec<String>, String> {
+        let dir = normalize(path.as_ref());
+        if !self.is_dir(&dir) {
+            return Err(format!("{path} is not a directory"));
+        }
+        let mut entries: Vec<String> = Vec::new();
+        for file in self.files.lock().keys() {
+            let Ok(rest) = file.strip_prefix(&dir) else {
+                continue;
+            };
+            let Some(first) = rest.components().next() else {
+                continue;
+            };
+            let entry = dir.join(first).to_string_lossy().into_owned();
+            if entries.last() != Some(&entry) {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+    fn is_file(&self, path: &str) -> Result<bool, String> {
+        let path = normalize(path.as_ref());
+        if self.files.lock().contains_key(&path) {
+            Ok(true)
+        } else if self.is_dir(&path) {
+            Ok(false)
+        } else {
+            Err(format!("{} does not exist", path.display()))
+        }
+    }
+    fn delete(&self, path: &str) -> Result<(), String> {
+        let path = normalize(path.as_ref());
+        let mut files = self.files.lock();
+        let count = files.len();
+        files.retain(|file, _| !file.starts_with(&path));
+        if files.len() == count {
+            return Err(format!("{} does not exist", path.display()));
+        }
+        Ok(())
+    }
+    fn trash(&self, path: &str) -> Result<(), String> {
+        self.delete(path)
+    }
+    fn read(&self, handle: Handle, count: usize) -> Result<Vec<u8>, String> {
+        let mut open = self.open.lock();
+        let Some(file) = open.get_mut(&handle) else {
+            drop(open);
+            return self.inner.read(handle, count);
+        };
+        let cursor = (file.cursor.as_mut()).ok_or("File was not opened for reading")?;
+        let files = self.files.lock();
+        let contents = files.get(&file.path).map(Vec::as_slice).unwrap_or_default();
+        let start = (*cursor).min(contents.len());
+        let end = start.saturating_add(count).min(contents.len());
+        *cursor = end;
+        Ok(contents[start..end].to_vec())
+    }
+    fn write(&self, handle: Handle, contents: &[u8]) -> Result<(), String> {
+        let open = self.open.lock();
+        let Some(file) = open.get(&handle) else {
+            drop(open);
+            return self.inner.write(handle, contents);
+        };
+        if file.cursor.is_some() {
+            return Err("File was not opened for writing".into());
+        }
+        let mut files = self.files.lock();
+        files
+            .entry(file.path.clone())
+            .or_default()
+            .extend_from_slice(contents);
+        Ok(())
+    }
+    fn create_file(&self, path: &Path) -> Result<Handle, String> {
+        let path = normalize(path);
+        self.files.lock().insert(path.clone(), Vec::new());
+        Ok(self.new_handle(OpenFile { path, cursor: None }))
+    }
+    fn open_file(&self, path: &Path) -> Result<Handle, String> {
+        let path = normalize(path);
+        if !self.files.lock().contains_key(&path) {
+            return Err(format!("{} does not exist", path.display()));
+        }
+        let cursor = Some(0);
+        Ok(self.new_handle(OpenFile { path, cursor }))
+    }
+    fn open_reader(&self, reader: Box<dyn Read + Send + Sync>) -> Result<Handle, String> {
+        self.inner.open_reader(reader)
+    }
+    fn file_read_all(&self, path: &Path) -> Result<Vec<u8>, String> {
+        self.file(path)
+            .ok_or_else(|| format!("{} does not exist", path.display()))
+    }
+    fn file_write_all(&self, path: &Path, contents: &[u8]) -> Result<(), String> {
+        self.insert_file(path, contents);
+        Ok(())
+    }
+    fn sleep(&self, seconds: f64) -> Result<(), String> {
+        self.inner.sleep(seconds)
+    }
+    fn now(&self) -> f64 {
+        self.inner.now()
+    }
+    fn rng_seed(&self) -> Option<u64> {
+        self.inner.rng_seed()
+    }
+    #[cfg(feature = "image")]
+    fn show_image(&self, image: DynamicImage) -> Result<(), String> {
+        self.inner.show_image(image)
+    }
+    fn show_gif(&self, gif_bytes: Vec<u8>) -> Result<(), String> {
+        self.inner.show_gif(gif_bytes)
+    }
+    fn play_audio(&self, wave_bytes: Vec<u8>) -> Result<(), String> {
+        self.inner.play_audio(wave_bytes)
+    }
+    fn audio_sample_rate(&self) -> u32 {
+        self.inner.audio_sample_rate()
+    }
+    fn set_audio_sample_rate(&self, sample_rate: u32) -> Result<(), String> {
+        self.inner.set_audio_sample_rate(sample_rate)
+    }
+    fn stream_audio(&self, f: AudioStreamFn) -> Result<(), String> {
+        self.inner.stream_audio(f)
+    }
+    fn tcp_listen(&self, addr: &str) -> Result<Handle, String> {
+        self.inner.tcp_listen(addr)
+    }
+    fn tcp_accept(&self, handle: Handle) -> Result<Handle, String> {
+        self.inner.tcp_accept(handle)
+    }
+    fn tcp_connect(&self, addr: &str) -> Result<Handle, String> {
+        self.inner.tcp_connect(addr)
+    }
+    fn tls_connect(&self, addr: &str) -> Result<Handle, String> {
+        self.inner.tls_connect(addr)
+    }
+    fn tcp_addr(&self, handle: Handle) -> Result<String, String> {
+        self.inner.tcp_addr(handle)
+    }
+    fn tcp_set_non_blocking(&self, handle: Handle, non_blocking: bool) -> Result<(), String> {
+        self.inner.tcp_set_non_blocking(handle, non_blocking)
+    }
+    fn tcp_set_read_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        self.inner.tcp_set_read_timeout(handle, timeout)
+    }
+    fn tcp_set_write_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        self.inner.tcp_set_write_timeout(handle, timeout)
+    }
+    fn close(&self, handle: Handle) -> Result<(), String> {
+        if self.open.lock().remove(&handle).is_some() {
+            return Ok(());
+        }
+        self.inner.close(handle)
+    }
+    fn invoke(&self, path: &str) -> Result<(), String> {
+        self.inner.invoke(path)
+    }
+    fn run_command_inherit(&self, command: &str, args: &[&str]) -> Result<i32, String> {
+        self.inner.run_command_inherit(command, args)
+    }
+    fn run_command_capture(
+        &self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<(i32, String, String), String> {
+        self.inner.run_command_capture(command, args)
+    }
+    fn run_command_stream(
+        &self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<(Handle, [Handle; 3]), String> {
+        self.inner.run_command_stream(command, args)
+    }
+    fn wait_process(&self, handle: Handle) -> Result<i32, String> {
+        self.inner.wait_process(handle)
+    }
+    fn kill_process(&self, handle: Handle) -> Result<(), String> {
+        self.inner.kill_process(handle)
+    }
+    fn https_get(&self, request: &str, handle: Handle) -> Result<String, String> {
+        self.inner.https_get(request, handle)
+    }
+    fn http_request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> Result<HttpResponse, String> {
+        self.inner.http_request(method, url, headers, body)
+    }
+    fn ffi(
+        &self,
+        file: &str,
+        result_ty: FfiType,
+        name: &str,
+        arg_tys: &[FfiType],
+        args: &[Value],
+    ) -> Result<Value, String> {
+        self.inner.ffi(file, result_ty, name, arg_tys, args)
+    }
+}
+
+#[test]
+fn mem_fs() {
+    use crate::{Array, Boxed, Uiua};
+
+    let fs = MemFs::new()
+        .with_file("data/a.txt", "hello")
+        .with_file("data/sub/b.txt", "");
+    let mut env = Uiua::with_backend(fs);
+    let code = "\
+&fras \"data/a.txt\"
+&fwa \"data/c.txt\" \"world\"
+&fld \"data\"
+&fe \"./data/../data/sub\"";
+    env.run_str(code).unwrap();
+    let entries = ["data/a.txt", "data/c.txt", "data/sub"];
+    let entries = Array::<Boxed>::from_iter(entries.map(String::from));
+    assert_eq!(
+        env.take_stack(),
+        [Value::from("hello"), entries.into(), Value::from(true)]
+    );
+    let fs = env.downcast_backend::<MemFs>().unwrap();
+    assert_eq!(fs.file("data/c.txt"), Some(b"world".to_vec()));
+    assert!(env.run_str("&fras \"data/d.txt\"").is_err());
+}
@@ -2,12 +2,12 @@ use std::{
     any::Any,
     env,
     fs::{self, File},
-    io::{stderr, stdin, stdout, Read, Write},
+    io::{stderr, stdin, stdout, BufReader, Read, Write},
     net::*,
     path::Path,
-    process::Command,
+    process::{Child, ChildStdin, Command, Stdio},
     slice,
-    sync::atomic::{self, AtomicU64},
+    sync::atomic::{self, AtomicU32, AtomicU64},
     thread::sleep,
     time::Duration,
 };
@@ -22,13 +22,26 @@ use once_cell::sync::Lazy;
 pub struct NativeSys;
 
 type Buffered<T> = BufReaderWriterSeq<T>;
+#[cfg(feature = "https")]
+type TlsStream = rustls::StreamOwned<rustls::ClientConnection, TcpStream>;
 
 struct GlobalNativeSys {
     next_handle: AtomicU64,
     files: DashMap<Handle, Buffered<File>>,
     tcp_listeners: DashMap<Handle, TcpListener>,
     tcp_sockets: DashMap<Handle, Buffered<TcpStream>>,
+    #[cfg(feature = "https")]
+    tls_sockets: DashMap<Handle, Buffered<TlsStream>>,
     hostnames: DashMap<Handle, String>,
+    children: DashMap<Handle, Child>,
+    child_stdins: DashMap<Handle, ChildStdin>,
+    child_outputs: DashMap<Handle, BufReader<Box<dyn Read + Send + Sync>>>,
+    readers: DashMap<Handle, BufReader<Box<dyn Read + Send + Sync>>>,
+    /// The sample rate set with `&assr`, or 0 to use the device's
+    audio_sample_rate: AtomicU32,
+    /// Kept alive because some platforms drop the contents with the clipboard
+    #[cfg(feature = "clipboard")]
+    clipboard: parking_lot::Mutex<Option<arboard::Clipboard>>,
     #[cfg(feature = "audio")]
     audio_stream_time: parking_lot::Mutex<Option<f64>>,
     #[cfg(feature = "audio")]
@@ -40,6 +53,11 @@ enum SysStream<'a> {
     File(dashmap::mapref::one::RefMut<'a, Handle, Buffered<File>>),
     TcpListener(dashmap::mapref::one::RefMut<'a, Handle, TcpListener>),
     TcpSocket(dashmap::mapref::one::RefMut<'a, Handle, Buffered<TcpStream>>),
+    #[cfg(feature = "https")]
+    TlsSocket(dashmap::mapref::one::RefMut<'a, Handle, Buffered<TlsStream>>),
+    ChildStdin(dashmap::mapref::one::RefMut<'a, Handle, ChildStdin>),
+    ChildOutput(dashmap::mapref::one::RefMut<'a, Handle, BufReader<Box<dyn Read + Send + Sync>>>),
+    Reader(dashmap::mapref::one::RefMut<'a, Handle, BufReader<Box<dyn Read + Send + Sync>>>),
 }
 
 impl Default for GlobalNativeSys {
@@ -49,7 +67,16 @@ impl Default for GlobalNativeSys {
             files: DashMap::new(),
             tcp_listeners: DashMap::new(),
             tcp_sockets: DashMap::new(),
+            #[cfg(feature = "https")]
+            tls_sockets: DashMap::new(),
             hostnames: DashMap::new(),
+            children: DashMap::new(),
+            child_stdins: DashMap::new(),
+            child_outputs: DashMap::new(),
+            readers: DashMap::new(),
+            audio_sample_rate: AtomicU32::new(0),
+            #[cfg(feature = "clipboard")]
+            clipboard: parking_lot::Mutex::new(None),
             #[cfg(feature = "audio")]
             audio_stream_time: parking_lot::Mutex::new(None),
             #[cfg(feature = "audio")]
@@ -63,22 +90,66 @@ impl GlobalNativeSys {
     fn new_handle(&self) -> Handle {
         for _ in 0..u64::MAX {
             let handle = Handle(self.next_handle.fetch_add(1, atomic::Ordering::Relaxed));
+            #[cfg(feature = "https")]
+            if self.tls_sockets.contains_key(&handle) {
+                continue;
+            }
             if !self.files.contains_key(&handle)
                 && !self.tcp_listeners.contains_key(&handle)
                 && !self.tcp_sockets.contains_key(&handle)
+                && !self.children.contains_key(&handle)
+                && !self.child_stdins.contains_key(&handle)
+                && !self.child_outputs.contains_key(&handle)
+                && !self.readers.contains_key(&handle)
             {
                 return handle;
             }
         }
         panic!("Ran out of file handles");
     }
+    /// Call a function on the TCP stream of a plain or TLS socket
+    fn with_tcp_stream<T>(
+        &self,
+        handle: Handle,
+        f: impl FnOnce(&TcpStream) -> std::io::Result<T>,
+    ) -> Result<T, String> {
+        if let Some(socket) = self.tcp_sockets.get(&handle) {
+            return f(socket.get_ref()).map_err(|e| e.to_string());
+        }
+        #[cfg(feature = "https")]
+        if let Some(socket) = self.tls_sockets.get(&handle) {
+            return f(&socket.get_ref().sock).map_err(|e| e.to_string());
+        }
+        Err("Invalid tcp socket handle".to_string())
+    }
+    #[cfg(feature = "clipboard")]
+    fn with_clipboard<T>(
+        &self,
+        f: impl FnOnce(&mut arboard::Clipboard) -> Result<T, arboard::Error>,
+    ) -> Result<T, String> {
+        let mut clipboard = self.clipboard.lock();
+        if clipboard.is_none() {
+            *clipboard = Some(arboard::Clipboard::new().map_err(|e| e.to_string())?);
+        }
+        f(clipboard.as_mut().unwrap()).map_err(|e| e.to_string())
+    }
     fn get_stream(&self, handle: Handle) -> Result<SysStream, String> {
+        #[cfg(feature = "https")]
+        if let Some(socket) = self.tls_sockets.get_mut(&handle) {
+            return Ok(SysStream::TlsSocket(socket));
+        }
         Ok(if let Some(file) = self.files.get_mut(&handle) {
             SysStream::File(file)
         } else if let Some(listener) = self.tcp_listeners.get_mut(&handle) {
             SysStream::TcpListener(listener)
         } else if let Some(socket) = self.tcp_sockets.get_mut(&handle) {
             SysStream::TcpSocket(socket)
+        } else if let Some(stdin) = self.child_stdins.get_mut(&handle) {
+            SysStream::ChildStdin(stdin)
+        } else if let Some(output) = self.child_outputs.get_mut(&handle) {
+            SysStream::ChildOutput(output)
+        } else if let Some(reader) = self.readers.get_mut(&handle) {
+            SysStream::Reader(reader)
         } else {
             return Err("Invalid file handle".to_string());
         })
@@ -159,6 +230,14 @@ impl SysBackend for NativeSys {
     fn var(&self, name: &str) -> Option<String> {
         env::var(name).ok()
     }
+    #[cfg(feature = "clipboard")]
+    fn clipboard(&self) -> Result<String, String> {
+        NATIVE_SYS.with_clipboard(|clipboard| clipboard.get_text())
+    }
+    #[cfg(feature = "clipboard")]
+    fn set_clipboard(&self, contents: &str) -> Result<(), String> {
+        NATIVE_SYS.with_clipboard(|clipboard| clipboard.set_text(contents))
+    }
     fn file_exists(&self, path: &str) -> bool {
         fs::metadata(path).is_ok()
     }
@@ -167,6 +246,35 @@ impl SysBackend for NativeSys {
             .map(|m| m.is_file())
             .map_err(|e| e.to_string())
     }
+    #[cfg(feature = "notify")]
+    fn watch_path(&self, path: &str, timeout: Option<Duration>) -> Result<Option<String>, String> {
+        use notify::{EventKind, RecursiveMode, Watcher};
+        let (send, recv) = std::sync::mpsc::channel();
+        let mut watcher = notify::recommended_watcher(send).map_err(|e| e.to_string())?;
+        watcher
+            .watch(Path::new(path), RecursiveMode::Recursive)
+            .map_err(|e| e.to_string())?;
+        let deadline = timeout.map(|timeout| std::time::Instant::now() + timeout);
+        loop {
+            let event = if let Some(deadline) = deadline {
+                let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+                match recv.recv_timeout(remaining) {
+                    Ok(event) => event,
+                    Err(_) => return Ok(None),
+                }
+            } else {
+                recv.recv().map_err(|e| e.to_string())?
+            };
+            let kind = match event.map_err(|e| e.to_string())?.kind {
+                EventKind::Access(_) => continue,
+                EventKind::Create(_) => "create",
+                EventKind::Modify(_) => "modify",
+                EventKind::Remove(_) => "remove",
+                EventKind::Any | EventKind::Other => "other",
+            };
+            return Ok(Some(kind.into()));
+        }
+    }
     fn list_dir(&self, path: &str) -> Result<Vec<String>, String> {
         let mut paths = Vec::new();
         for entry in fs::read_dir(path).map_err(|e| e.to_string())? {
@@ -181,6 +289,11 @@ impl SysBackend for NativeSys {
         NATIVE_SYS.files.insert(handle, Buffered::new_reader(file));
         Ok(handle)
     }
+    fn open_reader(&self, reader: Box<dyn Read + Send + Sync>) -> Result<Handle, String> {
+        let handle = NATIVE_SYS.new_handle();
+        NATIVE_SYS.readers.insert(handle, BufReader::new(reader));
+        Ok(handle)
+    }
     fn file_read_all(&self, path: &Path) -> Result<Vec<u8>, String> {
         let handle = self.open_file(path)?;
         let bytes = self.read(handle, usize::MAX)?;
@@ -201,6 +314,11 @@ impl SysBackend for NativeSys {
             fs::remove_file(path).map_err(|e| e.to_string())
         }
     }
+    fn random_bytes(&self, count: usize) -> Result<Vec<u8>, String> {
+        let mut bytes = vec![0; count];
+        getrandom::getrandom(&mut bytes).map_err(|e| e.to_string())?;
+        Ok(bytes)
+    }
     #[cfg(feature = "trash")]
     fn trash(&self, path: &str) -> Result<(), String> {
         trash::delete(path).map_err(|e| e.to_string())
@@ -224,6 +342,31 @@ impl SysBackend for NativeSys {
                     .map_err(|e| e.to_string())?;
                 buf
             }
+            #[cfg(feature = "https")]
+            SysStream::TlsSocket(mut socket) => {
+                let mut buf = Vec::new();
+                // Some servers close the connection without a TLS close_notify
+                if let Err(e) = Write::by_ref(&mut *socket)
+                    .take(len as u64)
+                    .read_to_end(&mut buf)
+                {
+                    if e.kind() != std::io::ErrorKind::UnexpectedEof {
+                        return Err(e.to_string());
+                    }
+                }
+                buf
+            }
+            SysStream::ChildStdin(_) => {
+                return Err("Cannot read from a process's stdin".to_string())
+            }
+            SysStream::ChildOutput(mut output) | SysStream::Reader(mut output) => {
+                let mut buf = Vec::new();
+                Read::by_ref(&mut *output)
+                    .take(len as u64)
+                    .read_to_end(&mut buf)
+                    .map_err(|e| e.to_string())?;
+                buf
+            }
         })
     }
     fn write(&self, handle: Handle, conts: &[u8]) -> Result<(), String> {
@@ -242,6 +385,11 @@ impl SysBackend for NativeSys {
             SysStream::File(mut file) => file.write_all(conts).map_err(|e| e.to_string()),
             SysStream::TcpListener(_) => Err("Cannot write to a tcp listener".to_string()),
             SysStream::TcpSocket(mut socket) => socket.write_all(conts).map_err(|e| e.to_string()),
+            #[cfg(feature = "https")]
+            SysStream::TlsSocket(mut socket) => socket.write_all(conts).map_err(|e| e.to_string()),
+            SysStream::ChildStdin(mut stdin) => stdin.write_all(conts).map_err(|e| e.to_string()),
+            SysStream::ChildOutput(_) => Err("Cannot write to a process's output".to_string()),
+            SysStream::Reader(_) => Err("Cannot write to a read-only stream".to_string()),
         }
     }
     fn sleep(&self, seconds: f64) -> Result<(), String> {
@@ -294,14 +442,26 @@ impl SysBackend for NativeSys {
             Err(e) => Err(format!("Failed to initialize audio output stream: {e}").to_string()),
         }
     }
-    #[cfg(feature = "audio")]
     fn audio_sample_rate(&self) -> u32 {
-        hodaun::default_output_device()
-            .and_then(|device| {
-                hodaun::cpal::traits::DeviceTrait::default_output_config(&device).ok()
-            })
-            .map(|config| config.sample_rate().0)
-            .unwrap_or(44100)
+        match NATIVE_SYS.audio_sample_rate.load(atomic::Ordering::Relaxed) {
+            0 => {}
+            sample_rate => return sample_rate,
+        }
+        #[cfg(feature = "audio")]
+        {
+            hodaun::default_output_device()
+                .and_then(|device| {
+                    hodaun::cpal::traits::DeviceTrait::default_output_config(&device).ok()
+                })
+                .map(|config| config.sample_rate().0)
+                .unwrap_or(44100)
+        }
+        #[cfg(not(feature = "audio"))]
+        44100
+    }
+    fn set_audio_sample_rate(&self, sample_rate: u32) -> Result<(), String> {
+        (NATIVE_SYS.audio_sample_rate).store(sample_rate, atomic::Ordering::Relaxed);
+        Ok(())
     }
     #[cfg(feature = "audio")]
     fn stream_audio(&self, f: crate::AudioStreamFn) -> Result<(), String> {
@@ -388,62 +548,52 @@ impl SysBackend for NativeSys {
         );
         Ok(handle)
     }
+    #[cfg(feature = "https")]
+    fn tls_connect(&self, addr: &str) -> Result<Handle, String> {
+        let host = addr.split_once(':').ok_or("No colon in address")?.0;
+        let server_name =
+            rustls::pki_types::ServerName::try_from(host.to_string()).map_err(|e| e.to_string())?;
+        let conn = rustls::ClientConnection::new(CLIENT_CONFIG.clone(), server_name)
+            .map_err(|e| e.to_string())?;
+        let stream = TcpStream::connect(addr).map_err(|e| e.to_string())?;
+        let handle = NATIVE_SYS.new_handle();
+        NATIVE_SYS.tls_sockets.insert(
+            handle,
+            Buffered::new_writer(rustls::StreamOwned::new(conn, stream)),
+        );
+        Ok(handle)
+    }
     fn tcp_addr(&self, handle: Handle) -> Result<String, String> {
-        let socket = NATIVE_SYS
-            .tcp_sockets
-            .get(&handle)
-            .ok_or_else(|| "Invalid tcp socket handle".to_string())?;
-        Ok(socket
-            .get_ref()
-            .peer_addr()
-            .map_err(|e| e.to_string())?
-            .to_string())
+        NATIVE_SYS.with_tcp_stream(handle, |stream| Ok(stream.peer_addr()?.to_string()))
     }
     fn tcp_set_non_blocking(&self, handle: Handle, non_blocking: bool) -> Result<(), String> {
-        let socket = NATIVE_SYS
-            .tcp_sockets
-            .get(&handle)
-            .ok_or_else(|| "Invalid tcp socket handle".to_string())?;
-        socket
-            .get_ref()
-            .set_nonblocking(non_blocking)
-            .map_err(|e| e.to_string())?;
-        Ok(())
+        NATIVE_SYS.with_tcp_stream(handle, |stream| stream.set_nonblocking(non_blocking))
     }
     fn tcp_set_read_timeout(
         &self,
         handle: Handle,
         timeout: Option<Duration>,
     ) -> Result<(), String> {
-        let socket = NATIVE_SYS
-            .tcp_sockets
-            .get(&handle)
-            .ok_or_else(|| "Invalid tcp socket handle".to_string())?;
-        socket
-            .get_ref()
-            .set_read_timeout(timeout)
-            .map_err(|e| e.to_string())?;
-        Ok(())
+        NATIVE_SYS.with_tcp_stream(handle, |stream| stream.set_read_timeout(timeout))
     }
     fn tcp_set_write_timeout(
         &self,
         handle: Handle,
         timeout: Option<Duration>,
     ) -> Result<(), String> {
-        let socket = NATIVE_SYS
-            .tcp_sockets
-            .get(&handle)
-            .ok_or_else(|| "Invalid tcp socket handle".to_string())?;
-        socket
-            .get_ref()
-            .set_write_timeout(timeout)
-            .map_err(|e| e.to_string())?;
-        Ok(())
+        NATIVE_SYS.with_tcp_stream(handle, |stream| stream.set_write_timeout(timeout))
     }
     fn close(&self, handle: Handle) -> Result<(), String> {
+        #[cfg(feature = "https")]
+        if NATIVE_SYS.tls_sockets.remove(&handle).is_some() {
+            return Ok(());
+        }
         if NATIVE_SYS.files.remove(&handle).is_some()
             || NATIVE_SYS.tcp_listeners.remove(&handle).is_some()
             || NATIVE_SYS.tcp_sockets.remove(&handle).is_some()
+            || NATIVE_SYS.child_stdins.remove(&handle).is_some()
+            || NATIVE_SYS.child_outputs.remove(&handle).is_some()
+            || NATIVE_SYS.readers.remove(&handle).is_some()
         {
             NATIVE_SYS.hostnames.remove(&handle);
             Ok(())
@@ -479,6 +629,46 @@ impl SysBackend for NativeSys {
             String::from_utf8_lossy(&output.stderr).into(),
         ))
     }
+    fn run_command_stream(
+        &self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<(Handle, [Handle; 3]), String> {
+        let mut child = Command::new(command)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| e.to_string())?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout: Box<dyn Read + Send + Sync> = Box::new(child.stdout.take().unwrap());
+        let stderr: Box<dyn Read + Send + Sync> = Box::new(child.stderr.take().unwrap());
+        let stdin_handle = NATIVE_SYS.new_handle();
+        NATIVE_SYS.child_stdins.insert(stdin_handle, stdin);
+        let stdout_handle = NATIVE_SYS.new_handle();
+        (NATIVE_SYS.child_outputs).insert(stdout_handle, BufReader::new(stdout));
+        let stderr_handle = NATIVE_SYS.new_handle();
+        (NATIVE_SYS.child_outputs).insert(stderr_handle, BufReader::new(stderr));
+        let handle = NATIVE_SYS.new_handle();
+        NATIVE_SYS.children.insert(handle, child);
+        Ok((handle, [stdin_handle, stdout_handle, stderr_handle]))
+    }
+    fn wait_process(&self, handle: Handle) -> Result<i32, String> {
+        let (_, mut child) = (NATIVE_SYS.children)
+            .remove(&handle)
+            .ok_or_else(|| "Invalid process handle".to_string())?;
+        let status = child.wait().map_err(|e| e.to_string())?;
+        Ok(status.code().unwrap_or(0))
+    }
+    fn kill_process(&self, handle: Handle) -> Result<(), String> {
+        let (_, mut child) = (NATIVE_SYS.children)
+            .remove(&handle)
+            .ok_or_else(|| "Invalid process handle".to_string())?;
+        child.kill().map_err(|e| e.to_string())?;
+        child.wait().map_err(|e| e.to_string())?;
+        Ok(())
+    }
     fn change_directory(&self, path: &str) -> Result<(), String> {
         env::set_current_dir(path).map_err(|e| e.to_string())
     }
@@ -491,16 +681,6 @@ impl SysBackend for NativeSys {
             .to_string();
         let request = check_http(request.to_string(), &host)?;
 
-        // https://github.com/rustls/rustls/blob/c9cfe3499681361372351a57a00ccd793837ae9c/examples/src/bin/simpleclient.rs
-        static CLIENT_CONFIG: Lazy<std::sync::Arc<rustls::ClientConfig>> = Lazy::new(|| {
-            let mut store = rustls::RootCertStore::empty();
-            store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
-            rustls::ClientConfig::builder()
-                .with_root_certificates(store)
-                .with_no_client_auth()
-                .into()
-        });
-
         let mut socket = NATIVE_SYS
             .tcp_sockets
             .get_mut(&handle)
@@ -523,6 +703,83 @@ impl SysBackend for NativeSys {
 
         Ok(s)
     }
+    #[cfg(feature = "https")]
+    fn http_request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> Result<crate::HttpResponse, String> {
+        let (tls, rest) = if let Some(rest) = url.strip_prefix("https://") {
+            (true, rest)
+        } else if let Some(rest) = url.strip_prefix("http://") {
+            (false, rest)
+        } else {
+            return Err(format!(
+                "URL must start with http:// or https://, but it is {url:?}"
+            ));
+        };
+        let (authority, path) = match rest.find(['/', '?']) {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let path = if path.starts_with('?') {
+            format!("/{path}")
+        } else {
+            path.to_string()
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => (
+                host,
+                port.parse::<u16>()
+                    .map_err(|_| format!("Invalid port in URL: {port:?}"))?,
+            ),
+            _ => (authority, if tls { 443 } else { 80 }),
+        };
+        if host.is_empty() {
+            return Err(format!("URL {url:?} has no host"));
+        }
+
+        let mut request = format!("{method} {path} HTTP/1.1\r\n");
+        let has_header = |name: &str| headers.iter().any(|(k, _)| k.eq_ignore_ascii_case(name));
+        if !has_header("host") {
+            request.push_str(&format!("Host: {authority}\r\n"));
+        }
+        if !has_header("content-length") && (!body.is_empty() || method != "GET") {
+            request.push_str(&format!("Content-Length: {}\r\n", body.len()));
+        }
+        if !has_header("connection") {
+            request.push_str("Connection: close\r\n");
+        }
+        for (name, value) in headers {
+            request.push_str(&format!("{name}: {value}\r\n"));
+        }
+        request.push_str("\r\n");
+        let mut request = request.into_bytes();
+        request.extend_from_slice(body);
+
+        let mut stream = TcpStream::connect((host, port)).map_err(|e| e.to_string())?;
+        let mut buffer = Vec::new();
+        if tls {
+            let server_name = rustls::pki_types::ServerName::try_from(host.to_string())
+                .map_err(|e| e.to_string())?;
+            let mut conn = rustls::ClientConnection::new(CLIENT_CONFIG.clone(), server_name)
+                .map_err(|e| e.to_string())?;
+            let mut tls = rustls::Stream::new(&mut conn, &mut stream);
+            tls.write_all(&request).map_err(|e| e.to_string())?;
+            // Some servers close the connection without a TLS close_notify
+            if let Err(e) = tls.read_to_end(&mut buffer) {
+                if e.kind() != std::io::ErrorKind::UnexpectedEof {
+                    return Err(e.to_string());
+                }
+            }
+        } else {
+            stream.write_all(&request).map_err(|e| e.to_string())?;
+            stream.read_to_end(&mut buffer).map_err(|e| e.to_string())?;
+        }
+        parse_http_response(&buffer)
+    }
     #[cfg(feature = "ffi")]
     fn ffi(
         &self,
@@ -536,6 +793,78 @@ impl SysBackend for NativeSys {
     }
 }
 
+// https://github.com/rustls/rustls/blob/c9cfe3499681361372351a57a00ccd793837ae9c/examples/src/bin/simpleclient.rs
+#[cfg(feature = "https")]
+static CLIENT_CONFIG: Lazy<std::sync::Arc<rustls::ClientConfig>> = Lazy::new(|| {
+    let mut store = rustls::RootCertStore::empty();
+    store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    rustls::ClientConfig::builder()
+        .with_root_certificates(store)
+        .with_no_client_auth()
+        .into()
+});
+
+/// Split a raw HTTP response into its status, headers, and body,
+/// decoding a chunked body if necessary
+#[cfg(feature = "https")]
+fn parse_http_response(bytes: &[u8]) -> Result<crate::HttpResponse, String> {
+    let mut headers = [httparse::EMPTY_HEADER; 64];
+    let mut res = httparse::Response::new(&mut headers);
+    let body_start = match res
+        .parse(bytes)
+        .map_err(|e| format!("Failed to parse HTTP response: {e}"))?
+    {
+        httparse::Status::Complete(n) => n,
+        httparse::Status::Partial => return Err("Incomplete HTTP response".into()),
+    };
+    let status = res.code.ok_or("No status code in HTTP response")?;
+    let headers: Vec<(String, String)> = (res.headers.iter())
+        .map(|h| {
+            let value = String::from_utf8_lossy(h.value).into_owned();
+            (h.name.to_string(), value)
+        })
+        .collect();
+    let header = |name: &str| {
+        (headers.iter())
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.trim())
+    };
+    let mut body = &bytes[body_start..];
+    if let Some(len) = header("content-length").and_then(|len| len.parse::<usize>().ok()) {
+        body = &body[..len.min(body.len())];
+    }
+    let chunked = header("transfer-encoding").is_some_and(|te| te.eq_ignore_ascii_case("chunked"));
+    let body = if chunked {
+        let mut decoded = Vec::new();
+        loop {
+            let line_end = (body.windows(2))
+                .position(|w| w == b"\r\n")
+                .ok_or("Invalid chunked HTTP body")?;
+            let size = String::from_utf8_lossy(&body[..line_end]);
+            let size = size.split(';').next().unwrap_or_default().trim();
+            let size = usize::from_str_radix(size, 16)
+                .map_err(|_| format!("Invalid chunk size in HTTP body: {size:?}"))?;
+            body = &body[line_end + 2..];
+            if size == 0 {
+                break;
+            }
+            if body.len() < size {
+                return Err("Incomplete chunked HTTP body".into());
+            }
+            decoded.extend_from_slice(&body[..size]);
+            body = body[size..].strip_prefix(b"\r\n").unwrap_or(&body[size..]);
+        }
+        decoded
+    } else {
+        body.to_vec()
+    };
+    Ok(crate::HttpResponse {
+        status,
+        headers,
+        body,
+    })
+}
+
 /// Takes an HTTP request, validates it, and fixes it (if possible) by adding
 /// the HTTP version and trailing newlines if they aren't present.
 ///
@@ -0,0 +1,503 @@
+use std::{
+    any::Any,
+    io::Read,
+    path::{Component, Path, PathBuf},
+    sync::atomic::{self, AtomicUsize},
+    time::Duration,
+};
+
+#[cfg(feature = "image")]
+use image::DynamicImage;
+
+use crate::{AudioStreamFn, FfiType, Handle, HttpResponse, SysBackend, Value};
+
+/// A policy for which capabilities a [`SandboxSys`] allows
+///
+/// [`SysPolicy::deny_all`] and [`SysPolicy::allow_all`] are starting points
+/// that can be refined with the `allow_*` and `with_*` methods.
+#[derive(Debug, Clone)]
+pub struct SysPolicy {
+    read_paths: Option<Vec<PathBuf>>,
+    write_paths: Option<Vec<PathBuf>>,
+    write_limit: Option<usize>,
+    network: bool,
+    commands: Option<Vec<String>>,
+    ffi: bool,
+}
+
+impl SysPolicy {
+    /// A policy that allows no reading, writing, networking, commands, or FFI
+    pub fn deny_all() -> Self {
+        Self {
+            read_paths: Some(Vec::new()),
+            write_paths: Some(Vec::new()),
+            write_limit: None,
+            network: false,
+            commands: Some(Vec::new()),
+            ffi: false,
+        }
+    }
+    /// A policy that allows everything the inner backend does
+    pub fn allow_all() -> Self {
+        Self {
+            read_paths: None,
+            write_paths: None,
+            write_limit: None,
+            network: true,
+            commands: None,
+            ffi: true,
+        }
+    }
+    /// Allow reading files in the given directory or the given file
+    pub fn allow_read(mut self, path: impl Into<PathBuf>) -> Self {
+        if let Some(paths) = &mut self.read_paths {
+            paths.push(path.into());
+        }
+        self
+    }
+    /// Allow writing files in the given directory or the given file
+    ///
+    /// Paths that can be written can also be read.
+    pub fn allow_write(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        if let Some(paths) = &mut self.read_paths {
+            paths.push(path.clone());
+        }
+        if let Some(paths) = &mut self.write_paths {
+            paths.push(path);
+        }
+        self
+    }
+    /// Allow or deny TCP sockets and HTTP requests
+    pub fn allow_network(mut self, allow: bool) -> Self {
+        self.network = allow;
+        self
+    }
+    /// Allow running the given command
+    pub fn allow_command(mut self, command: impl Into<String>) -> Self {
+        if let Some(commands) = &mut self.commands {
+            commands.push(command.into());
+        }
+        self
+    }
+    /// Allow or deny calling foreign functions
+    pub fn allow_ffi(mut self, allow: bool) -> Self {
+        self.ffi = allow;
+        self
+    }
+    /// Limit the total number of bytes that can be written to files and sockets
+    pub fn with_write_limit(mut self, bytes: usize) -> Self {
+        self.write_limit = Some(bytes);
+        self
+    }
+}
+
+/// Check whether a path is in one of the allowed paths
+///
+/// Paths with `..` components are never allowed, so they cannot escape an allowed directory.
+/// Symlinks are resolved in both the path and the allowed paths,
+/// so a symlink in an allowed directory cannot point outside of it.
+fn path_allowed(allowed: &Option<Vec<PathBuf>>, path: &Path) -> bool {
+    let Some(allowed) = allowed else {
+        return true;
+    };
+    if (path.components()).any(|comp| comp == Component::ParentDir) {
+        return false;
+    }
+    let path = resolve(path);
+    (allowed.iter()).any(|allowed| path.starts_with(resolve(allowed)))
+}
+
+/// Resolve the symlinks in the longest existing ancestor of a path
+///
+/// The rest of the path does not exist yet, so it cannot contain symlinks.
+/// If no ancestor can be resolved, the path is only normalized.
+fn resolve(path: &Path) -> PathBuf {
+    let path: PathBuf = (path.components())
+        .filter(|comp| *comp != Component::CurDir)
+        .collect();
+    let mut existing = path.as_path();
+    let mut rest = Vec::new();
+    loop {
+        let dir = if existing.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            existing
+        };
+        if let Ok(resolved) = dir.canonicalize() {
+            return rest
+                .iter()
+                .rev()
+                .fold(resolved, |path, name| path.join(name));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name);
+                existing = parent;
+            }
+            _ => return path,
+        }
+    }
+}
+
+/// A system backend that enforces a [`SysPolicy`] on an inner backend
+///
+/// Operations the policy does not allow fail with a permission error
+/// that names the resource that was accessed.
+/// Everything else is passed through to the inner backend.
+pub struct SandboxSys<B> {
+    inner: B,
+    policy: SysPolicy,
+    written: AtomicUsize,
+}
+
+impl<B: SysBackend> SandboxSys<B> {
+    /// Wrap a backend with a policy
+    pub fn new(inner: B, policy: SysPolicy) -> Self {
+        Self {
+            inner,
+            policy,
+            written: AtomicUsize::new(0),
+        }
+    }
+    /// Get the policy
+    pub fn policy(&self) -> &SysPolicy {
+        &self.policy
+    }
+    /// Get the inner backend
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+    /// Unwrap the inner backend
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+    fn check_read(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        if path_allowed(&self.policy.read_paths, path) {
+            Ok(())
+        } else {
+            Err(format!("Permission denied: cannot read {}", path.display()))
+        }
+    }
+    fn check_write(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        if path_allowed(&self.policy.write_paths, path) {
+            Ok(())
+        } else {
+            Err(format!(
+                "Permission denied: cannot write {}",
+                path.display()
+            ))
+        }
+    }
+    fn check_network(&self, resource: &str) -> Result<(), String> {
+        if self.policy.network {
+            Ok(())
+        } else {
+            Err(format!(
+                "Permission denied: cannot access network at {resource}"
+            ))
+        }
+    }
+    fn check_command(&self, command: &str) -> Result<(), String> {
+        match &self.policy.commands {
+            Some(commands) if !commands.iter().any(|c| c == command) => {
+                Err(format!("Permission denied: cannot run {command}"))
+            }
+            _ => Ok(()),
+        }
+    }
+    fn check_written(&self, len: usize) -> Result<(), String> {
+        let Some(limit) = self.policy.write_limit else {
+            return Ok(());
+        };
+        let written = self.written.fetch_add(len, atomic::Ordering::Relaxed);
+        if written + len > limit {
+            self.written.fetch_sub(len, atomic::Ordering::Relaxed);
+            Err(format!(
+                "Permission denied: cannot write {len} more bytes \
+                because the limit is {limit} bytes"
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<B: SysBackend> SysBackend for SandboxSys<B> {
+    fn any(&self) -> &dyn Any {
+        self
+    }
+    fn any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn save_error_color(&self, message: String, colored: String) {
+        self.inner.save_error_color(message, colored)
+    }
+    fn print_str_stdout(&self, s: &str) -> Result<(), String> {
+        self.inner.print_str_stdout(s)
+    }
+    fn print_str_stderr(&self, s: &str) -> Result<(), String> {
+        self.inner.print_str_stderr(s)
+    }
+    fn print_str_trace(&self, s: &str) {
+        self.inner.print_str_trace(s)
+    }
+    fn scan_line_stdin(&self) -> Result<Option<String>, String> {
+        self.inner.scan_line_stdin()
+    }
+    fn set_raw_mode(&self, raw_mode: bool) -> Result<(), String> {
+        self.inner.set_raw_mode(raw_mode)
+    }
+    fn var(&self, name: &str) -> Option<String> {
+        self.inner.var(name)
+    }
+    fn term_size(&self) -> Result<(usize, usize), String> {
+        self.inner.term_size()
+    }
+    fn clipboard(&self) -> Result<String, String> {
+        self.inner.clipboard()
+    }
+    fn set_clipboard(&self, contents: &str) -> Result<(), String> {
+        self.inner.set_clipboard(contents)
+    }
+    fn random_bytes(&self, count: usize) -> Result<Vec<u8>, String> {
+        self.inner.random_bytes(count)
+    }
+    fn file_exists(&self, path: &str) -> bool {
+        self.check_read(path).is_ok() && self.inner.file_exists(path)
+    }
+    fn list_dir(&self, path: &str) -> Result<Vec<String>, String> {
+        self.check_read(path)?;
+        self.inner.list_dir(path)
+    }
+    fn is_file(&self, path: &str) -> Result<bool, String> {
+        self.check_read(path)?;
+        self.inner.is_file(path)
+    }
+    fn watch_path(&self, path: &str, timeout: Option<Duration>) -> Result<Option<String>, String> {
+        self.check_read(path)?;
+        self.inner.watch_path(path, timeout)
+    }
+    fn delete(&self, path: &str) -> Result<(), String> {
+        self.check_write(path)?;
+        self.inner.delete(path)
+    }
+    fn trash(&self, path: &str) -> Result<(), String> {
+        self.check_write(path)?;
+        self.inner.trash(path)
+    }
+    fn read(&self, handle: Handle, count: usize) -> Result<Vec<u8>, String> {
+        self.inner.read(handle, count)
+    }
+    fn read_until(&self, handle: Handle, delim: &[u8]) -> Result<Vec<u8>, String> {
+        self.inner.read_until(handle, delim)
+    }
+    fn write(&self, handle: Handle, contents: &[u8]) -> Result<(), String> {
+        // Only files and sockets count toward the write limit
+        if handle >= Handle::FIRST_UNRESERVED {
+            self.check_written(contents.len())?;
+        }
+        self.inner.write(handle, contents)
+    }
+    fn create_file(&self, path: &Path) -> Result<Handle, String> {
+        self.check_write(path)?;
+        self.inner.create_file(path)
+    }
+    fn open_file(&self, path: &Path) -> Result<Handle, String> {
+        self.check_read(path)?;
+        self.inner.open_file(path)
+    }
+    fn open_reader(&self, reader: Box<dyn Read + Send + Sync>) -> Result<Handle, String> {
+        self.inner.open_reader(reader)
+    }
+    fn file_read_all(&self, path: &Path) -> Result<Vec<u8>, String> {
+        self.check_read(path)?;
+        self.inner.file_read_all(path)
+    }
+    fn file_write_all(&self, path: &Path, contents: &[u8]) -> Result<(), String> {
+        self.check_write(path)?;
+        self.check_written(contents.len())?;
+        self.inner.file_write_all(path, contents)
+    }
+    fn sleep(&self, seconds: f64) -> Result<(), String> {
+        self.inner.sleep(seconds)
+    }
+    fn now(&self) -> f64 {
+        self.inner.now()
+    }
+    fn rng_seed(&self) -> Option<u64> {
+        self.inner.rng_seed()
+    }
+    #[cfg(feature = "image")]
+    fn show_image(&self, image: DynamicImage) -> Result<(), String> {
+        self.inner.show_image(image)
+    }
+    fn show_gif(&self, gif_bytes: Vec<u8>) -> Result<(), String> {
+        self.inner.show_gif(gif_bytes)
+    }
+    fn play_audio(&self, wave_bytes: Vec<u8>) -> Result<(), String> {
+        self.inner.play_audio(wave_bytes)
+    }
+    fn audio_sample_rate(&self) -> u32 {
+        self.inner.audio_sample_rate()
+    }
+    fn set_audio_sample_rate(&self, sample_rate: u32) -> Result<(), String> {
+        self.inner.set_audio_sample_rate(sample_rate)
+    }
+    fn stream_audio(&self, f: AudioStreamFn) -> Result<(), String> {
+        self.inner.stream_audio(f)
+    }
+    fn tcp_listen(&self, addr: &str) -> Result<Handle, String> {
+        self.check_network(addr)?;
+        self.inner.tcp_listen(addr)
+    }
+    fn tcp_accept(&self, handle: Handle) -> Result<Handle, String> {
+        self.inner.tcp_accept(handle)
+    }
+    fn tcp_connect(&self, addr: &str) -> Result<Handle, String> {
+        self.check_network(addr)?;
+        self.inner.tcp_connect(addr)
+    }
+    fn tls_connect(&self, addr: &str) -> Result<Handle, String> {
+        self.check_network(addr)?;
+        self.inner.tls_connect(addr)
+    }
+    fn tcp_addr(&self, handle: Handle) -> Result<String, String> {
+        self.inner.tcp_addr(handle)
+    }
+    fn tcp_set_non_blocking(&self, handle: Handle, non_blocking: bool) -> Result<(), String> {
+        self.inner.tcp_set_non_blocking(handle, non_blocking)
+    }
+    fn tcp_set_read_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        self.inner.tcp_set_read_timeout(handle, timeout)
+    }
+    fn tcp_set_write_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        self.inner.tcp_set_write_timeout(handle, timeout)
+    }
+    fn close(&self, handle: Handle) -> Result<(), String> {
+        self.inner.close(handle)
+    }
+    fn invoke(&self, path: &str) -> Result<(), String> {
+        // Invoking runs an arbitrary program, so it requires all commands to be allowed
+        if self.policy.commands.is_some() {
+            return Err(format!("Permission denied: cannot invoke {path}"));
+        }
+        self.inner.invoke(path)
+    }
+    fn run_command_inherit(&self, command: &str, args: &[&str]) -> Result<i32, String> {
+        self.check_command(command)?;
+        self.inner.run_command_inherit(command, args)
+    }
+    fn run_command_capture(
+        &self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<(i32, String, String), String> {
+        self.check_command(command)?;
+        self.inner.run_command_capture(command, args)
+    }
+    fn run_command_stream(
+        &self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<(Handle, [Handle; 3]), String> {
+        self.check_command(command)?;
+        self.inner.run_command_stream(command, args)
+    }
+    fn wait_process(&self, handle: Handle) -> Result<i32, String> {
+        self.inner.wait_process(handle)
+    }
+    fn kill_process(&self, handle: Handle) -> Result<(), String> {
+        self.inner.kill_process(handle)
+    }
+    fn change_directory(&self, path: &str) -> Result<(), String> {
+        // Relative paths would no longer mean what the policy says
+        if self.policy.read_paths.is_some() || self.policy.write_paths.is_some() {
+            return Err(format!(
+                "Permission denied: cannot change directory to {path}"
+            ));
+        }
+        self.inner.change_directory(path)
+    }
+    fn https_get(&self, request: &str, handle: Handle) -> Result<String, String> {
+        self.inner.https_get(request, handle)
+    }
+    fn http_request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> Result<HttpResponse, String> {
+        self.check_network(url)?;
+        self.inner.http_request(method, url, headers, body)
+    }
+    fn ffi(
+        &self,
+        file: &str,
+        result_ty: FfiType,
+        name: &str,
+        arg_tys: &[FfiType],
+        args: &[Value],
+    ) -> Result<Value, String> {
+        if !self.policy.ffi {
+            return Err(format!("Permission denied: cannot call {name} in {file}"));
+        }
+        self.inner.ffi(file, result_ty, name, arg_tys, args)
+    }
+}
+
+#[test]
+#[cfg(feature = "native_sys")]
+fn sandbox_sys() {
+    let dir = std::env::temp_dir().join(format!("uiua-sandbox-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let policy = SysPolicy::deny_all().allow_write(&dir).with_write_limit(8);
+    let mut env = crate::Uiua::with_backend(SandboxSys::new(crate::NativeSys, policy));
+    let allowed = dir.join("a.txt");
+    env.run_str(&format!("&fwa {allowed:?} \"hello\"\n&fras {allowed:?}"))
+        .unwrap();
+    assert_eq!(env.take_stack(), [Value::from("hello")]);
+    // Writing past the limit, outside the allowed directory, and running commands are denied
+    for code in [
+        format!("&fwa {allowed:?} \"hello\""),
+        format!("&fwa {:?} 1", dir.join("..").join("b.txt")),
+        "&runc \"ls\"".into(),
+    ] {
+        match env.run_str(&code) {
+            Ok(_) => panic!("{code} should be denied"),
+            Err(err) => assert!(err.to_string().contains("Permission denied"), "{err}"),
+        }
+    }
+    // Symlinks cannot escape the allowed directory
+    #[cfg(unix)]
+    {
+        let outside = std::env::temp_dir().join(format!("uiua-outside-{}", std::process::id()));
+        std::fs::create_dir_all(&outside).unwrap();
+        let link = dir.join("link");
+        _ = std::fs::remove_file(&link);
+        std::os::unix::fs::symlink(&outside, &link).unwrap();
+        for code in [
+            format!("&fwa {:?} 1", link.join("c.txt")),
+            format!("&fras {:?}", link.join("c.txt")),
+        ] {
+            match env.run_str(&code) {
+                Ok(_) => panic!("{code} should be denied"),
+                Err(err) => assert!(err.to_string().contains("Permission denied"), "{err}"),
+            }
+        }
+        assert!(!outside.join("c.txt").exists());
+        _ = std::fs::remove_dir_all(outside);
+    }
+    _ = std::fs::remove_dir_all(dir);
+}
@@ -0,0 +1,567 @@
+use std::{any::Any, collections::VecDeque, fmt, fs, io::Read, path::Path, time::Duration};
+
+#[cfg(feature = "image")]
+use image::DynamicImage;
+use parking_lot::Mutex;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{AudioStreamFn, FfiType, Handle, HttpResponse, SafeSys, SysBackend, Value};
+
+/// A recorded call to a system backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SysCall {
+    op: String,
+    args: String,
+    result: serde_json::Value,
+}
+
+/// A system backend that records every call to an inner backend and its result
+///
+/// The recording can be saved and served by a [`ReplaySys`]
+/// to re-run a program deterministically.
+/// Because the recording includes the time and a seed for random numbers,
+/// programs that use [`now`](crate::Primitive::Now) and [`random`](crate::Primitive::Rand)
+/// are reproducible too.
+///
+/// Output, like printing and showing images, is passed through without being recorded.
+pub struct RecordSys<B> {
+    inner: B,
+    calls: Mutex<Vec<SysCall>>,
+}
+
+impl<B: SysBackend> RecordSys<B> {
+    /// Record the calls to a backend
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            calls: Mutex::new(Vec::new()),
+        }
+    }
+    /// Get the inner backend
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+    /// Get the recording as JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&*self.calls.lock()).unwrap()
+    }
+    /// Save the recording to a file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        fs::write(path, self.to_json()).map_err(|e| e.to_string())
+    }
+    fn record<T: Serialize>(&self, op: &str, args: impl fmt::Debug, result: T) -> T {
+        let call = SysCall {
+            op: op.into(),
+            args: format!("{args:?}"),
+            result: serde_json::to_value(&result).unwrap_or_default(),
+        };
+        self.calls.lock().push(call);
+        result
+    }
+}
+
+/// A system backend that serves the results recorded by a [`RecordSys`]
+///
+/// If a program makes a different call than was recorded next, the call fails,
+/// or returns a default value if it cannot fail.
+///
+/// Output, like printing and showing images, is passed through to an inner backend,
+/// which is [`SafeSys`] by default.
+pub struct ReplaySys<B = SafeSys> {
+    inner: B,
+    calls: Mutex<VecDeque<SysCall>>,
+}
+
+impl ReplaySys {
+    /// Replay a recording made by [`RecordSys::to_json`]
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        Self::wrap(SafeSys, json)
+    }
+    /// Replay a recording saved by [`RecordSys::save`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        Self::from_json(&fs::read_to_string(path).map_err(|e| e.to_string())?)
+    }
+}
+
+impl<B: SysBackend> ReplaySys<B> {
+    /// Replay a recording made by [`RecordSys::to_json`], passing output to a backend
+    pub fn wrap(inner: B, json: &str) -> Result<Self, String> {
+        let calls = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        Ok(Self {
+            inner,
+            calls: Mutex::new(calls),
+        })
+    }
+    /// Get the inner backend
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+    /// Get the number of recorded calls that have not been replayed
+    pub fn remaining(&self) -> usize {
+        self.calls.lock().len()
+    }
+    fn replay<T: DeserializeOwned>(&self, op: &str, args: impl fmt::Debug) -> Result<T, String> {
+        let args = format!("{args:?}");
+        let mut calls = self.calls.lock();
+        let Some(call) = calls.pop_front() else {
+            return Err(format!("Replay has no recorded result for {op}{args}"));
+        };
+        if call.op != op || call.args != args {
+            return Err(format!(
+                "Replay diverged: expected {}{}, but got {op}{args}",
+                call.op, call.args
+            ));
+        }
+        serde_json::from_value(call.result).map_err(|e| e.to_string())
+    }
+    fn replay_result<T: DeserializeOwned>(
+        &self,
+        op: &str,
+        args: impl fmt::Debug,
+    ) -> Result<T, String> {
+        self.replay::<Result<T, String>>(op, args)?
+    }
+}
+
+impl<B: SysBackend> SysBackend for RecordSys<B> {
+    fn any(&self) -> &dyn Any {
+        self
+    }
+    fn any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn save_error_color(&self, message: String, colored: String) {
+        self.inner.save_error_color(message, colored)
+    }
+    fn print_str_stdout(&self, s: &str) -> Result<(), String> {
+        self.inner.print_str_stdout(s)
+    }
+    fn print_str_stderr(&self, s: &str) -> Result<(), String> {
+        self.inner.print_str_stderr(s)
+    }
+    fn print_str_trace(&self, s: &str) {
+        self.inner.print_str_trace(s)
+    }
+    fn scan_line_stdin(&self) -> Result<Option<String>, String> {
+        self.record("scan_line_stdin", (), self.inner.scan_line_stdin())
+    }
+    fn set_raw_mode(&self, raw_mode: bool) -> Result<(), String> {
+        self.record("set_raw_mode", raw_mode, self.inner.set_raw_mode(raw_mode))
+    }
+    fn var(&self, name: &str) -> Option<String> {
+        self.record("var", name, self.inner.var(name))
+    }
+    fn term_size(&self) -> Result<(usize, usize), String> {
+        self.record("term_size", (), self.inner.term_size())
+    }
+    fn clipboard(&self) -> Result<String, String> {
+        self.record("clipboard", (), self.inner.clipboard())
+    }
+    fn set_clipboard(&self, contents: &str) -> Result<(), String> {
+        let res = self.inner.set_clipboard(contents);
+        self.record("set_clipboard", contents, res)
+    }
+    fn random_bytes(&self, count: usize) -> Result<Vec<u8>, String> {
+        self.record("random_bytes", count, self.inner.random_bytes(count))
+    }
+    fn file_exists(&self, path: &str) -> bool {
+        self.record("file_exists", path, self.inner.file_exists(path))
+    }
+    fn list_dir(&self, path: &str) -> Result<Vec<String>, String> {
+        self.record("list_dir", path, self.inner.list_dir(path))
+    }
+    fn is_file(&self, path: &str) -> Result<bool, String> {
+        self.record("is_file", path, self.inner.is_file(path))
+    }
+    fn watch_path(&self, path: &str, timeout: Option<Duration>) -> Result<Option<String>, String> {
+        let res = self.inner.watch_path(path, timeout);
+        self.record("watch_path", (path, timeout), res)
+    }
+    fn delete(&self, path: &str) -> Result<(), String> {
+        self.record("delete", path, self.inner.delete(path))
+    }
+    fn trash(&self, path: &str) -> Result<(), String> {
+        self.record("trash", path, self.inner.trash(path))
+    }
+    fn read(&self, handle: Handle, count: usize) -> Result<Vec<u8>, String> {
+        let res = self.inner.read(handle, count);
+        self.record("read", (handle, count), res)
+    }
+    fn read_until(&self, handle: Handle, delim: &[u8]) -> Result<Vec<u8>, String> {
+        let res = self.inner.read_until(handle, delim);
+        self.record("read_until", (handle, delim), res)
+    }
+    fn write(&self, handle: Handle, contents: &[u8]) -> Result<(), String> {
+        let res = self.inner.write(handle, contents);
+        self.record("write", (handle, contents), res)
+    }
+    fn create_file(&self, path: &Path) -> Result<Handle, String> {
+        self.record("create_file", path, self.inner.create_file(path))
+    }
+    fn open_file(&self, path: &Path) -> Result<Handle, String> {
+        self.record("open_file", path, self.inner.open_file(path))
+    }
+    fn open_reader(&self, reader: Box<dyn Read + Send + Sync>) -> Result<Handle, String> {
+        self.record("open_reader", (), self.inner.open_reader(reader))
+    }
+    fn file_read_all(&self, path: &Path) -> Result<Vec<u8>, String> {
+        self.record("file_read_all", path, self.inner.file_read_all(path))
+    }
+    fn file_write_all(&self, path: &Path, contents: &[u8]) -> Result<(), String> {
+        let res = self.inner.file_write_all(path, contents);
+        self.record("file_write_all", (path, contents), res)
+    }
+    fn sleep(&self, seconds: f64) -> Result<(), String> {
+        self.record("sleep", seconds, self.inner.sleep(seconds))
+    }
+    fn now(&self) -> f64 {
+        self.record("now", (), self.inner.now())
+    }
+    fn rng_seed(&self) -> Option<u64> {
+        let seed = self.inner.rng_seed().unwrap_or_else(rand::random);
+        self.record("rng_seed", (), Some(seed))
+    }
+    #[cfg(feature = "image")]
+    fn show_image(&self, image: DynamicImage) -> Result<(), String> {
+        self.inner.show_image(image)
+    }
+    fn show_gif(&self, gif_bytes: Vec<u8>) -> Result<(), String> {
+        self.inner.show_gif(gif_bytes)
+    }
+    fn play_audio(&self, wave_bytes: Vec<u8>) -> Result<(), String> {
+        self.inner.play_audio(wave_bytes)
+    }
+    fn audio_sample_rate(&self) -> u32 {
+        self.record("audio_sample_rate", (), self.inner.audio_sample_rate())
+    }
+    fn set_audio_sample_rate(&self, sample_rate: u32) -> Result<(), String> {
+        let res = self.inner.set_audio_sample_rate(sample_rate);
+        self.record("set_audio_sample_rate", sample_rate, res)
+    }
+    fn stream_audio(&self, f: AudioStreamFn) -> Result<(), String> {
+        self.inner.stream_audio(f)
+    }
+    fn tcp_listen(&self, addr: &str) -> Result<Handle, String> {
+        self.record("tcp_listen", addr, self.inner.tcp_listen(addr))
+    }
+    fn tcp_accept(&self, handle: Handle) -> Result<Handle, String> {
+        self.record("tcp_accept", handle, self.inner.tcp_accept(handle))
+    }
+    fn tcp_connect(&self, addr: &str) -> Result<Handle, String> {
+        self.record("tcp_connect", addr, self.inner.tcp_connect(addr))
+    }
+    fn tls_connect(&self, addr: &str) -> Result<Handle, String> {
+        self.record("tls_connect", addr, self.inner.tls_connect(addr))
+    }
+    fn tcp_addr(&self, handle: Handle) -> Result<String, String> {
+        self.record("tcp_addr", handle, self.inner.tcp_addr(handle))
+    }
+    fn tcp_set_non_blocking(&self, handle: Handle, non_blocking: bool) -> Result<(), String> {
+        let res = self.inner.tcp_set_non_blocking(handle, non_blocking);
+        self.record("tcp_set_non_blocking", (handle, non_blocking), res)
+    }
+    fn tcp_set_read_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        let res = self.inner.tcp_set_read_timeout(handle, timeout);
+        self.record("tcp_set_read_timeout", (handle, timeout), res)
+    }
+    fn tcp_set_write_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        let res = self.inner.tcp_set_write_timeout(handle, timeout);
+        self.record("tcp_set_write_timeout", (handle, timeout), res)
+    }
+    fn close(&self, handle: Handle) -> Result<(), String> {
+        self.record("close", handle, self.inner.close(handle))
+    }
+    fn invoke(&self, path: &str) -> Result<(), String> {
+        self.record("invoke", path, self.inner.invoke(path))
+    }
+    fn run_command_inherit(&self, command: &str, args: &[&str]) -> Result<i32, String> {
+        let res = self.inner.run_command_inherit(command, args);
+        self.record("run_command_inherit", (command, args), res)
+    }
+    fn run_command_capture(
+        &self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<(i32, String, String), String> {
+        let res = self.inner.run_command_capture(command, args);
+        self.record("run_command_capture", (command, args), res)
+    }
+    fn run_command_stream(
+        &self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<(Handle, [Handle; 3]), String> {
+        let res = self.inner.run_command_stream(command, args);
+        self.record("run_command_stream", (command, args), res)
+    }
+    fn wait_process(&self, handle: Handle) -> Result<i32, String> {
+        self.record("wait_process", handle, self.inner.wait_process(handle))
+    }
+    fn kill_process(&self, handle: Handle) -> Result<(), String> {
+        self.record("kill_process", handle, self.inner.kill_process(handle))
+    }
+    fn change_directory(&self, path: &str) -> Result<(), String> {
+        self.record("change_directory", path, self.inner.change_directory(path))
+    }
+    fn https_get(&self, request: &str, handle: Handle) -> Result<String, String> {
+        let res = self.inner.https_get(request, handle);
+        self.record("https_get", (request, handle), res)
+    }
+    fn http_request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> Result<HttpResponse, String> {
+        let res = self.inner.http_request(method, url, headers, body);
+        self.record("http_request", (method, url, headers, body), res)
+    }
+    fn ffi(
+        &self,
+        file: &str,
+        result_ty: FfiType,
+        name: &str,
+        arg_tys: &[FfiType],
+        args: &[Value],
+    ) -> Result<Value, String> {
+        let res = self.inner.ffi(file, result_ty.clone(), name, arg_tys, args);
+        self.record("ffi", (file, result_ty, name, arg_tys, args), res)
+    }
+}
+
+impl<B: SysBackend> SysBackend for ReplaySys<B> {
+    fn any(&self) -> &dyn Any {
+        self
+    }
+    fn any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn save_error_color(&self, message: String, colored: String) {
+        self.inner.save_error_color(message, colored)
+    }
+    fn print_str_stdout(&self, s: &str) -> Result<(), String> {
+        self.inner.print_str_stdout(s)
+    }
+    fn print_str_stderr(&self, s: &str) -> Result<(), String> {
+        self.inner.print_str_stderr(s)
+    }
+    fn print_str_trace(&self, s: &str) {
+        self.inner.print_str_trace(s)
+    }
+    fn scan_line_stdin(&self) -> Result<Option<String>, String> {
+        self.replay_result("scan_line_stdin", ())
+    }
+    fn set_raw_mode(&self, raw_mode: bool) -> Result<(), String> {
+        self.replay_result("set_raw_mode", raw_mode)
+    }
+    fn var(&self, name: &str) -> Option<String> {
+        self.replay("var", name).unwrap_or_default()
+    }
+    fn term_size(&self) -> Result<(usize, usize), String> {
+        self.replay_result("term_size", ())
+    }
+    fn clipboard(&self) -> Result<String, String> {
+        self.replay_result("clipboard", ())
+    }
+    fn set_clipboard(&self, contents: &str) -> Result<(), String> {
+        self.replay_result("set_clipboard", contents)
+    }
+    fn random_bytes(&self, count: usize) -> Result<Vec<u8>, String> {
+        self.replay_result("random_bytes", count)
+    }
+    fn file_exists(&self, path: &str) -> bool {
+        self.replay("file_exists", path).unwrap_or_default()
+    }
+    fn list_dir(&self, path: &str) -> Result<Vec<String>, String> {
+        self.replay_result("list_dir", path)
+    }
+    fn is_file(&self, path: &str) -> Result<bool, String> {
+        self.replay_result("is_file", path)
+    }
+    fn watch_path(&self, path: &str, timeout: Option<Duration>) -> Result<Option<String>, String> {
+        self.replay_result("watch_path", (path, timeout))
+    }
+    fn delete(&self, path: &str) -> Result<(), String> {
+        self.replay_result("delete", path)
+    }
+    fn trash(&self, path: &str) -> Result<(), String> {
+        self.replay_result("trash", path)
+    }
+    fn read(&self, handle: Handle, count: usize) -> Result<Vec<u8>, String> {
+        self.replay_result("read", (handle, count))
+    }
+    fn read_until(&self, handle: Handle, delim: &[u8]) -> Result<Vec<u8>, String> {
+        self.replay_result("read_until", (handle, delim))
+    }
+    fn write(&self, handle: Handle, contents: &[u8]) -> Result<(), String> {
+        self.replay_result("write", (handle, contents))
+    }
+    fn create_file(&self, path: &Path) -> Result<Handle, String> {
+        self.replay_result("create_file", path)
+    }
+    fn open_file(&self, path: &Path) -> Result<Handle, String> {
+        self.replay_result("open_file", path)
+    }
+    fn open_reader(&self, _reader: Box<dyn Read + Send + Sync>) -> Result<Handle, String> {
+        self.replay_result("open_reader", ())
+    }
+    fn file_read_all(&self, path: &Path) -> Result<Vec<u8>, String> {
+        self.replay_result("file_read_all", path)
+    }
+    fn file_write_all(&self, path: &Path, contents: &[u8]) -> Result<(), String> {
+        self.replay_result("file_write_all", (path, contents))
+    }
+    fn sleep(&self, seconds: f64) -> Result<(), String> {
+        self.replay_result("sleep", seconds)
+    }
+    fn now(&self) -> f64 {
+        self.replay("now", ()).unwrap_or_default()
+    }
+    fn rng_seed(&self) -> Option<u64> {
+        self.replay("rng_seed", ()).unwrap_or_default()
+    }
+    #[cfg(feature = "image")]
+    fn show_image(&self, image: DynamicImage) -> Result<(), String> {
+        self.inner.show_image(image)
+    }
+    fn show_gif(&self, gif_bytes: Vec<u8>) -> Result<(), String> {
+        self.inner.show_gif(gif_bytes)
+    }
+    fn play_audio(&self, wave_bytes: Vec<u8>) -> Result<(), String> {
+        self.inner.play_audio(wave_bytes)
+    }
+    fn audio_sample_rate(&self) -> u32 {
+        self.replay("audio_sample_rate", ()).unwrap_or_default()
+    }
+    fn set_audio_sample_rate(&self, sample_rate: u32) -> Result<(), String> {
+        self.replay_result("set_audio_sample_rate", sample_rate)
+    }
+    fn stream_audio(&self, f: AudioStreamFn) -> Result<(), String> {
+        self.inner.stream_audio(f)
+    }
+    fn tcp_listen(&self, addr: &str) -> Result<Handle, String> {
+        self.replay_result("tcp_listen", addr)
+    }
+    fn tcp_accept(&self, handle: Handle) -> Result<Handle, String> {
+        self.replay_result("tcp_accept", handle)
+    }
+    fn tcp_connect(&self, addr: &str) -> Result<Handle, String> {
+        self.replay_result("tcp_connect", addr)
+    }
+    fn tls_connect(&self, addr: &str) -> Result<Handle, String> {
+        self.replay_result("tls_connect", addr)
+    }
+    fn tcp_addr(&self, handle: Handle) -> Result<String, String> {
+        self.replay_result("tcp_addr", handle)
+    }
+    fn tcp_set_non_blocking(&self, handle: Handle, non_blocking: bool) -> Result<(), String> {
+        self.replay_result("tcp_set_non_blocking", (handle, non_blocking))
+    }
+    fn tcp_set_read_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        self.replay_result("tcp_set_read_timeout", (handle, timeout))
+    }
+    fn tcp_set_write_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        self.replay_result("tcp_set_write_timeout", (handle, timeout))
+    }
+    fn close(&self, handle: Handle) -> Result<(), String> {
+        self.replay_result("close", handle)
+    }
+    fn invoke(&self, path: &str) -> Result<(), String> {
+        self.replay_result("invoke", path)
+    }
+    fn run_command_inherit(&self, command: &str, args: &[&str]) -> Result<i32, String> {
+        self.replay_result("run_command_inherit", (command, args))
+    }
+    fn run_command_capture(
+        &self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<(i32, String, String), String> {
+        self.replay_result("run_command_capture", (command, args))
+    }
+    fn run_command_stream(
+        &self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<(Handle, [Handle; 3]), String> {
+        self.replay_result("run_command_stream", (command, args))
+    }
+    fn wait_process(&self, handle: Handle) -> Result<i32, String> {
+        self.replay_result("wait_process", handle)
+    }
+    fn kill_process(&self, handle: Handle) -> Result<(), String> {
+        self.replay_result("kill_process", handle)
+    }
+    fn change_directory(&self, path: &str) -> Result<(), String> {
+        self.replay_result("change_directory", path)
+    }
+    fn https_get(&self, request: &str, handle: Handle) -> Result<String, String> {
+        self.replay_result("https_get", (request, handle))
+    }
+    fn http_request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> Result<HttpResponse, String> {
+        self.replay_result("http_request", (method, url, headers, body))
+    }
+    fn ffi(
+        &self,
+        file: &str,
+        result_ty: FfiType,
+        name: &str,
+        arg_tys: &[FfiType],
+        args: &[Value],
+    ) -> Result<Value, String> {
+        self.replay_result("ffi", (file, &result_ty, name, arg_tys, args))
+    }
+}
+
+#[test]
+fn record_replay() {
+    use crate::{MemFs, Uiua};
+
+    let fs = MemFs::new().with_file("a.txt", "hello");
+    let mut env = Uiua::with_backend(RecordSys::new(fs));
+    let code = "&fras \"a.txt\"\n⚂\nnow\n&fe \"b.txt\"";
+    env.run_str(code).unwrap();
+    let recorded = env.take_stack();
+    let json = env
+        .downcast_backend::<RecordSys<MemFs>>()
+        .unwrap()
+        .to_json();
+    let mut env = Uiua::with_backend(ReplaySys::from_json(&json).unwrap());
+    env.run_str(code).unwrap();
+    assert_eq!(env.take_stack(), recorded);
+    assert_eq!(env.downcast_backend::<ReplaySys>().unwrap().remaining(), 0);
+    // A different program diverges from the recording
+    let mut env = Uiua::with_backend(ReplaySys::from_json(&json).unwrap());
+    assert!(env.run_str("&fras \"b.txt\"").is_err());
+    // Times are replayed exactly
+    let json = r#"[
+        {"op": "rng_seed", "args": "()", "result": 0},
+        {"op": "now", "args": "()", "result": 1792111120.2995327}
+    ]"#;
+    let mut env = Uiua::with_backend(ReplaySys::from_json(json).unwrap());
+    env.run_str("now").unwrap();
+    assert_eq!(env.take_stack(), [Value::from(1792111120.2995327)]);
+}
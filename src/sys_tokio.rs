@@ -0,0 +1,280 @@
+use std::{any::Any, future::Future, io::Read, path::Path, time::Duration};
+
+use tokio::{runtime::Handle as RuntimeHandle, task::block_in_place};
+
+use crate::{AudioStreamFn, FfiType, Handle, HttpResponse, NativeSys, SysBackend, Value};
+
+/// A blocking adapter for running [`NativeSys`] inside a [tokio] runtime
+///
+/// It has the same capabilities as [`NativeSys`].
+/// The interpreter is synchronous, so this does not do asynchronous IO.
+/// Instead, operations that can block, like sleeping, file IO, and TCP, are run with
+/// [`block_in_place`], which hands the runtime's other tasks to another worker thread
+/// while the current one blocks. Sleeping and reading and writing whole files
+/// wait on tokio's own timers and file IO.
+///
+/// This keeps a Uiua program from stalling the other tasks of the runtime,
+/// but every program that is blocked still occupies a thread.
+///
+/// Uiua programs using this backend must not be run on a current-thread runtime.
+#[derive(Debug, Clone)]
+pub struct TokioSys {
+    runtime: RuntimeHandle,
+}
+
+impl TokioSys {
+    /// Create a backend that uses the given runtime
+    pub fn new(runtime: RuntimeHandle) -> Self {
+        Self { runtime }
+    }
+    /// Create a backend that uses the runtime of the current task
+    ///
+    /// # Panics
+    /// Panics if called outside of a tokio runtime
+    pub fn current() -> Self {
+        Self::new(RuntimeHandle::current())
+    }
+    /// Run a blocking operation
+    fn block<T>(&self, f: impl FnOnce() -> T) -> T {
+        if RuntimeHandle::try_current().is_ok() {
+            block_in_place(f)
+        } else {
+            f()
+        }
+    }
+    /// Wait for a future to complete
+    fn block_on<F: Future>(&self, fut: F) -> F::Output {
+        self.block(|| self.runtime.block_on(fut))
+    }
+}
+
+impl SysBackend for TokioSys {
+    fn any(&self) -> &dyn Any {
+        self
+    }
+    fn any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn save_error_color(&self, message: String, colored: String) {
+        NativeSys.save_error_color(message, colored)
+    }
+    fn print_str_stdout(&self, s: &str) -> Result<(), String> {
+        NativeSys.print_str_stdout(s)
+    }
+    fn print_str_stderr(&self, s: &str) -> Result<(), String> {
+        NativeSys.print_str_stderr(s)
+    }
+    fn print_str_trace(&self, s: &str) {
+        NativeSys.print_str_trace(s)
+    }
+    fn scan_line_stdin(&self) -> Result<Option<String>, String> {
+        self.block(|| NativeSys.scan_line_stdin())
+    }
+    fn set_raw_mode(&self, raw_mode: bool) -> Result<(), String> {
+        NativeSys.set_raw_mode(raw_mode)
+    }
+    fn var(&self, name: &str) -> Option<String> {
+        NativeSys.var(name)
+    }
+    fn term_size(&self) -> Result<(usize, usize), String> {
+        NativeSys.term_size()
+    }
+    fn clipboard(&self) -> Result<String, String> {
+        NativeSys.clipboard()
+    }
+    fn set_clipboard(&self, contents: &str) -> Result<(), String> {
+        NativeSys.set_clipboard(contents)
+    }
+    fn random_bytes(&self, count: usize) -> Result<Vec<u8>, String> {
+        NativeSys.random_bytes(count)
+    }
+    fn file_exists(&self, path: &str) -> bool {
+        NativeSys.file_exists(path)
+    }
+    fn list_dir(&self, path: &str) -> Result<Vec<String>, String> {
+        self.block(|| NativeSys.list_dir(path))
+    }
+    fn is_file(&self, path: &str) -> Result<bool, String> {
+        NativeSys.is_file(path)
+    }
+    fn watch_path(&self, path: &str, timeout: Option<Duration>) -> Result<Option<String>, String> {
+        self.block(|| NativeSys.watch_path(path, timeout))
+    }
+    fn delete(&self, path: &str) -> Result<(), String> {
+        self.block(|| NativeSys.delete(path))
+    }
+    fn trash(&self, path: &str) -> Result<(), String> {
+        self.block(|| NativeSys.trash(path))
+    }
+    fn read(&self, handle: Handle, count: usize) -> Result<Vec<u8>, String> {
+        self.block(|| NativeSys.read(handle, count))
+    }
+    fn read_until(&self, handle: Handle, delim: &[u8]) -> Result<Vec<u8>, String> {
+        self.block(|| NativeSys.read_until(handle, delim))
+    }
+    fn write(&self, handle: Handle, contents: &[u8]) -> Result<(), String> {
+        self.block(|| NativeSys.write(handle, contents))
+    }
+    fn create_file(&self, path: &Path) -> Result<Handle, String> {
+        self.block(|| NativeSys.create_file(path))
+    }
+    fn open_file(&self, path: &Path) -> Result<Handle, String> {
+        self.block(|| NativeSys.open_file(path))
+    }
+    fn open_reader(&self, reader: Box<dyn Read + Send + Sync>) -> Result<Handle, String> {
+        NativeSys.open_reader(reader)
+    }
+    fn file_read_all(&self, path: &Path) -> Result<Vec<u8>, String> {
+        self.block_on(tokio::fs::read(path))
+            .map_err(|e| format!("{e} {}", path.display()))
+    }
+    fn file_write_all(&self, path: &Path, contents: &[u8]) -> Result<(), String> {
+        self.block_on(tokio::fs::write(path, contents))
+            .map_err(|e| e.to_string())
+    }
+    fn sleep(&self, seconds: f64) -> Result<(), String> {
+        self.block_on(tokio::time::sleep(Duration::from_secs_f64(seconds)));
+        Ok(())
+    }
+    fn now(&self) -> f64 {
+        NativeSys.now()
+    }
+    fn rng_seed(&self) -> Option<u64> {
+        NativeSys.rng_seed()
+    }
+    #[cfg(feature = "image")]
+    fn show_image(&self, image: image::DynamicImage) -> Result<(), String> {
+        NativeSys.show_image(image)
+    }
+    fn show_gif(&self, gif_bytes: Vec<u8>) -> Result<(), String> {
+        NativeSys.show_gif(gif_bytes)
+    }
+    fn play_audio(&self, wave_bytes: Vec<u8>) -> Result<(), String> {
+        self.block(|| NativeSys.play_audio(wave_bytes))
+    }
+    fn audio_sample_rate(&self) -> u32 {
+        NativeSys.audio_sample_rate()
+    }
+    fn set_audio_sample_rate(&self, sample_rate: u32) -> Result<(), String> {
+        NativeSys.set_audio_sample_rate(sample_rate)
+    }
+    fn stream_audio(&self, f: AudioStreamFn) -> Result<(), String> {
+        self.block(|| NativeSys.stream_audio(f))
+    }
+    fn tcp_listen(&self, addr: &str) -> Result<Handle, String> {
+        self.block(|| NativeSys.tcp_listen(addr))
+    }
+    fn tcp_accept(&self, handle: Handle) -> Result<Handle, String> {
+        self.block(|| NativeSys.tcp_accept(handle))
+    }
+    fn tcp_connect(&self, addr: &str) -> Result<Handle, String> {
+        self.block(|| NativeSys.tcp_connect(addr))
+    }
+    fn tls_connect(&self, addr: &str) -> Result<Handle, String> {
+        self.block(|| NativeSys.tls_connect(addr))
+    }
+    fn tcp_addr(&self, handle: Handle) -> Result<String, String> {
+        NativeSys.tcp_addr(handle)
+    }
+    fn tcp_set_non_blocking(&self, handle: Handle, non_blocking: bool) -> Result<(), String> {
+        NativeSys.tcp_set_non_blocking(handle, non_blocking)
+    }
+    fn tcp_set_read_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        NativeSys.tcp_set_read_timeout(handle, timeout)
+    }
+    fn tcp_set_write_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        NativeSys.tcp_set_write_timeout(handle, timeout)
+    }
+    fn close(&self, handle: Handle) -> Result<(), String> {
+        self.block(|| NativeSys.close(handle))
+    }
+    fn invoke(&self, path: &str) -> Result<(), String> {
+        NativeSys.invoke(path)
+    }
+    fn run_command_inherit(&self, command: &str, args: &[&str]) -> Result<i32, String> {
+        self.block(|| NativeSys.run_command_inherit(command, args))
+    }
+    fn run_command_capture(
+        &self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<(i32, String, String), String> {
+        self.block(|| NativeSys.run_command_capture(command, args))
+    }
+    fn run_command_stream(
+        &self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<(Handle, [Handle; 3]), String> {
+        NativeSys.run_command_stream(command, args)
+    }
+    fn wait_process(&self, handle: Handle) -> Result<i32, String> {
+        self.block(|| NativeSys.wait_process(handle))
+    }
+    fn kill_process(&self, handle: Handle) -> Result<(), String> {
+        NativeSys.kill_process(handle)
+    }
+    fn change_directory(&self, path: &str) -> Result<(), String> {
+        NativeSys.change_directory(path)
+    }
+    fn https_get(&self, request: &str, handle: Handle) -> Result<String, String> {
+        self.block(|| NativeSys.https_get(request, handle))
+    }
+    fn http_request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> Result<HttpResponse, String> {
+        self.block(|| NativeSys.http_request(method, url, headers, body))
+    }
+    fn ffi(
+        &self,
+        file: &str,
+        result_ty: FfiType,
+        name: &str,
+        arg_tys: &[FfiType],
+        args: &[Value],
+    ) -> Result<Value, String> {
+        NativeSys.ffi(file, result_ty, name, arg_tys, args)
+    }
+}
+
+#[test]
+#[cfg(feature = "tokio_sys")]
+fn tokio_sys() {
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap();
+    // More programs than worker threads can sleep at the same time
+    let stacks = rt.block_on(async {
+        let tasks: Vec<_> = (0..8)
+            .map(|i| {
+                tokio::spawn(async move {
+                    let mut env = crate::Uiua::with_backend(TokioSys::current());
+                    env.run_str(&format!("&sl 0.01 {i}")).unwrap();
+                    env.take_stack()
+                })
+            })
+            .collect();
+        let mut stacks = Vec::new();
+        for task in tasks {
+            stacks.push(task.await.unwrap());
+        }
+        stacks
+    });
+    for (i, stack) in stacks.into_iter().enumerate() {
+        assert_eq!(stack, [Value::from(i as f64)]);
+    }
+}
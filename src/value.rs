@@ -3,7 +3,7 @@ use std::{
     cmp::Ordering,
     fmt,
     hash::{Hash, Hasher},
-    iter::repeat,
+    iter::{once, repeat},
     mem::{size_of, take},
 };
 
@@ -17,7 +17,7 @@ use crate::{
         FillContext,
     },
     array::*,
-    cowslice::CowSlice,
+    cowslice::{cowslice, CowSlice},
     grid_fmt::GridFmt,
     Boxed, Complex, Shape, Uiua, UiuaResult,
 };
@@ -34,6 +34,11 @@ pub enum Value {
     /// Byte array used for some boolean operations and for I/O
     #[cfg(feature = "bytes")]
     Byte(Array<u8>),
+    /// Array of natural numbers produced by index primitives like [`Primitive::Range`]
+    ///
+    /// It takes half the memory of a number array, and its elements need no rounding
+    /// when used as indices. Most other operations promote it to a number array.
+    Int(Array<u32>),
     /// Complex number array
     Complex(Array<Complex>),
     /// Common character array
@@ -49,6 +54,7 @@ impl Default for Value {
 }
 
 /// A combination of [`ExactSizeIterator`] and [`DoubleEndedIterator`]
+#[doc(hidden)]
 pub trait ExactDoubleIterator: ExactSizeIterator + DoubleEndedIterator {}
 impl<T: ExactSizeIterator + DoubleEndedIterator> ExactDoubleIterator for T {}
 
@@ -61,6 +67,7 @@ impl Value {
             Self::Num(_) => f64::TYPE_ID,
             #[cfg(feature = "bytes")]
             Self::Byte(_) => u8::TYPE_ID,
+            Self::Int(_) => u32::TYPE_ID,
             Self::Complex(_) => Complex::TYPE_ID,
             Self::Char(_) => char::TYPE_ID,
             Self::Box(_) => Boxed::TYPE_ID,
@@ -105,6 +112,7 @@ impl Value {
             Self::Num(array) => Box::new(array.rows().map(Value::from)),
             #[cfg(feature = "bytes")]
             Self::Byte(array) => Box::new(array.rows().map(Value::from)),
+            Self::Int(array) => Box::new(array.rows().map(Value::from)),
             Self::Complex(array) => Box::new(array.rows().map(Value::from)),
             Self::Char(array) => Box::new(array.rows().map(Value::from)),
             Self::Box(array) => Box::new(array.rows().map(Value::from)),
@@ -119,6 +127,7 @@ impl Value {
             Self::Num(array) => Box::new(array.row_shaped_slices(row_shape).map(Value::from)),
             #[cfg(feature = "bytes")]
             Self::Byte(array) => Box::new(array.row_shaped_slices(row_shape).map(Value::from)),
+            Self::Int(array) => Box::new(array.row_shaped_slices(row_shape).map(Value::from)),
             Self::Complex(array) => Box::new(array.row_shaped_slices(row_shape).map(Value::from)),
             Self::Char(array) => Box::new(array.row_shaped_slices(row_shape).map(Value::from)),
             Self::Box(array) => Box::new(array.row_shaped_slices(row_shape).map(Value::from)),
@@ -133,6 +142,7 @@ impl Value {
             Self::Num(array) => Box::new(array.into_row_shaped_slices(row_shape).map(Value::from)),
             #[cfg(feature = "bytes")]
             Self::Byte(array) => Box::new(array.into_row_shaped_slices(row_shape).map(Value::from)),
+            Self::Int(array) => Box::new(array.into_row_shaped_slices(row_shape).map(Value::from)),
             Self::Complex(array) => {
                 Box::new(array.into_row_shaped_slices(row_shape).map(Value::from))
             }
@@ -146,6 +156,7 @@ impl Value {
             Self::Num(array) => Box::new(array.into_rows().map(Value::from)),
             #[cfg(feature = "bytes")]
             Self::Byte(array) => Box::new(array.into_rows().map(Value::from)),
+            Self::Int(array) => Box::new(array.into_rows().map(Value::from)),
             Self::Complex(array) => Box::new(array.into_rows().map(Value::from)),
             Self::Char(array) => Box::new(array.into_rows().map(Value::from)),
             Self::Box(array) => Box::new(array.into_rows().map(Value::from)),
@@ -157,6 +168,7 @@ impl Value {
             Self::Num(array) => Box::new(array.data.into_iter().map(Value::from)),
             #[cfg(feature = "bytes")]
             Self::Byte(array) => Box::new(array.data.into_iter().map(Value::from)),
+            Self::Int(array) => Box::new(array.data.into_iter().map(Value::from)),
             Self::Complex(array) => Box::new(array.data.into_iter().map(Value::from)),
             Self::Char(array) => Box::new(array.data.into_iter().map(Value::from)),
             Self::Box(array) => Box::new(array.data.into_iter().map(Value::from)),
@@ -168,6 +180,7 @@ impl Value {
             Self::Num(_) => "number",
             #[cfg(feature = "bytes")]
             Self::Byte(_) => "number",
+            Self::Int(_) => "number",
             Self::Complex(_) => "complex",
             Self::Char(_) => "character",
             Self::Box(_) => "box",
@@ -179,6 +192,7 @@ impl Value {
             Self::Num(_) => "numbers",
             #[cfg(feature = "bytes")]
             Self::Byte(_) => "numbers",
+            Self::Int(_) => "numbers",
             Self::Complex(_) => "complexes",
             Self::Char(_) => "characters",
             Self::Box(_) => "boxes",
@@ -197,6 +211,7 @@ impl Value {
             Self::Num(_) => env.num_fill().unwrap_or_else(|_| f64::proxy()).into(),
             #[cfg(feature = "bytes")]
             Self::Byte(_) => env.byte_fill().unwrap_or_else(|_| u8::proxy()).into(),
+            Self::Int(_) => env.int_fill().unwrap_or_else(|_| u32::proxy()).into(),
             Self::Complex(_) => env
                 .complex_fill()
                 .unwrap_or_else(|_| Complex::proxy())
@@ -227,6 +242,11 @@ impl Value {
                     .collect::<CowSlice<_>>(),
             )
             .into(),
+            Self::Int(_) => Array::new(
+                shape,
+                cowslice![env.int_fill().unwrap_or_else(|_| u32::proxy()); elem_count],
+            )
+            .into(),
             Self::Complex(_) => Array::new(
                 shape,
                 repeat(env.complex_fill().unwrap_or_else(|_| Complex::proxy()))
@@ -255,6 +275,7 @@ impl Value {
             Self::Num(array) => array.first_dim_zero().into(),
             #[cfg(feature = "bytes")]
             Self::Byte(array) => array.first_dim_zero().into(),
+            Self::Int(array) => array.first_dim_zero().into(),
             Self::Complex(array) => array.first_dim_zero().into(),
             Self::Char(array) => array.first_dim_zero().into(),
             Self::Box(array) => array.first_dim_zero().into(),
@@ -269,6 +290,7 @@ impl Value {
             Self::Num(array) => array.pop_row().map(Value::from),
             #[cfg(feature = "bytes")]
             Self::Byte(array) => array.pop_row().map(Value::from),
+            Self::Int(array) => array.pop_row().map(Value::from),
             Self::Complex(array) => array.pop_row().map(Value::from),
             Self::Char(array) => array.pop_row().map(Value::from),
             Self::Box(array) => array.pop_row().map(Value::from),
@@ -279,6 +301,7 @@ impl Value {
             Self::Num(_) => size_of::<f64>(),
             #[cfg(feature = "bytes")]
             Self::Byte(_) => size_of::<u8>(),
+            Self::Int(_) => size_of::<u32>(),
             Self::Complex(_) => size_of::<Complex>(),
             Self::Char(_) => size_of::<char>(),
             Self::Box(_) => size_of::<Boxed>(),
@@ -346,6 +369,7 @@ impl Value {
             Array::validate_shape,
             Array::validate_shape,
             Array::validate_shape,
+            Array::validate_shape,
         )
     }
     /// Get the row at the given index
@@ -356,12 +380,14 @@ impl Value {
             |arr| arr.row(i).into(),
             |arr| arr.row(i).into(),
             |arr| arr.row(i).into(),
+            |arr| arr.row(i).into(),
         )
     }
     pub(crate) fn generic_into<T>(
         self,
         n: impl FnOnce(Array<f64>) -> T,
         _b: impl FnOnce(Array<u8>) -> T,
+        i: impl FnOnce(Array<u32>) -> T,
         _co: impl FnOnce(Array<Complex>) -> T,
         ch: impl FnOnce(Array<char>) -> T,
         f: impl FnOnce(Array<Boxed>) -> T,
@@ -370,6 +396,7 @@ impl Value {
             Self::Num(array) => n(array),
             #[cfg(feature = "bytes")]
             Self::Byte(array) => _b(array),
+            Self::Int(array) => i(array),
             Self::Complex(array) => _co(array),
             Self::Char(array) => ch(array),
             Self::Box(array) => f(array),
@@ -379,6 +406,7 @@ impl Value {
         &'a self,
         n: impl FnOnce(&'a Array<f64>) -> T,
         _b: impl FnOnce(&'a Array<u8>) -> T,
+        i: impl FnOnce(&'a Array<u32>) -> T,
         _co: impl FnOnce(&'a Array<Complex>) -> T,
         ch: impl FnOnce(&'a Array<char>) -> T,
         f: impl FnOnce(&'a Array<Boxed>) -> T,
@@ -387,15 +415,18 @@ impl Value {
             Self::Num(array) => n(array),
             #[cfg(feature = "bytes")]
             Self::Byte(array) => _b(array),
+            Self::Int(array) => i(array),
             Self::Complex(array) => _co(array),
             Self::Char(array) => ch(array),
             Self::Box(array) => f(array),
         }
     }
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn generic_ref_env<'a, T: 'a>(
         &'a self,
         n: impl FnOnce(&'a Array<f64>, &Uiua) -> UiuaResult<T>,
         b: impl FnOnce(&'a Array<u8>, &Uiua) -> UiuaResult<T>,
+        i: impl FnOnce(&'a Array<u32>, &Uiua) -> UiuaResult<T>,
         co: impl FnOnce(&'a Array<Complex>, &Uiua) -> UiuaResult<T>,
         ch: impl FnOnce(&'a Array<char>, &Uiua) -> UiuaResult<T>,
         f: impl FnOnce(&'a Array<Boxed>, &Uiua) -> UiuaResult<T>,
@@ -404,6 +435,7 @@ impl Value {
         self.generic_ref(
             |a| n(a, env),
             |a| b(a, env),
+            |a| i(a, env),
             |a| co(a, env),
             |a| ch(a, env),
             |a| f(a, env),
@@ -413,6 +445,7 @@ impl Value {
         &mut self,
         n: impl FnOnce(&mut Array<f64>) -> T,
         _b: impl FnOnce(&mut Array<u8>) -> T,
+        i: impl FnOnce(&mut Array<u32>) -> T,
         _co: impl FnOnce(&mut Array<Complex>) -> T,
         ch: impl FnOnce(&mut Array<char>) -> T,
         f: impl FnOnce(&mut Array<Boxed>) -> T,
@@ -421,6 +454,7 @@ impl Value {
             Self::Num(array) => n(array),
             #[cfg(feature = "bytes")]
             Self::Byte(array) => _b(array),
+            Self::Int(array) => i(array),
             Self::Complex(array) => _co(array),
             Self::Char(array) => ch(array),
             Self::Box(array) => f(array),
@@ -430,6 +464,7 @@ impl Value {
         &mut self,
         n: impl FnOnce(&mut Array<f64>) -> T,
         _b: impl FnOnce(&mut Array<u8>) -> T,
+        i: impl FnOnce(&mut Array<u32>) -> T,
         _co: impl FnOnce(&mut Array<Complex>) -> T,
         ch: impl FnOnce(&mut Array<char>) -> T,
         f: impl FnOnce(&mut Array<Boxed>) -> T,
@@ -438,11 +473,12 @@ impl Value {
             Self::Num(array) => n(array),
             #[cfg(feature = "bytes")]
             Self::Byte(array) => _b(array),
+            Self::Int(array) => i(array),
             Self::Complex(array) => _co(array),
             Self::Char(array) => ch(array),
             Self::Box(array) => {
                 if let Some(Boxed(value)) = array.as_scalar_mut() {
-                    value.generic_mut_deep(n, _b, _co, ch, f)
+                    value.generic_mut_deep(n, _b, i, _co, ch, f)
                 } else {
                     f(array)
                 }
@@ -455,6 +491,7 @@ impl Value {
         other: Self,
         n: impl FnOnce(Array<f64>, Array<f64>) -> Result<T, E>,
         _b: impl FnOnce(Array<u8>, Array<u8>) -> Result<T, E>,
+        i: impl FnOnce(Array<u32>, Array<u32>) -> Result<T, E>,
         _co: impl FnOnce(Array<Complex>, Array<Complex>) -> Result<T, E>,
         ch: impl FnOnce(Array<char>, Array<char>) -> Result<T, E>,
         f: impl FnOnce(Array<Boxed>, Array<Boxed>) -> Result<T, E>,
@@ -468,6 +505,13 @@ impl Value {
             (Self::Byte(a), Self::Num(b)) => n(a.convert(), b),
             #[cfg(feature = "bytes")]
             (Self::Num(a), Self::Byte(b)) => n(a, b.convert()),
+            (Self::Int(a), Self::Int(b)) => i(a, b),
+            (Self::Int(a), Self::Num(b)) => n(a.convert(), b),
+            (Self::Num(a), Self::Int(b)) => n(a, b.convert()),
+            #[cfg(feature = "bytes")]
+            (Self::Int(a), Self::Byte(b)) => n(a.convert(), b.convert()),
+            #[cfg(feature = "bytes")]
+            (Self::Byte(a), Self::Int(b)) => n(a.convert(), b.convert()),
             (Self::Complex(a), Self::Complex(b)) => _co(a, b),
             (Self::Complex(a), Self::Num(b)) => _co(a, b.convert()),
             (Self::Num(a), Self::Complex(b)) => _co(a.convert(), b),
@@ -475,6 +519,8 @@ impl Value {
             (Self::Complex(a), Self::Byte(b)) => _co(a, b.convert()),
             #[cfg(feature = "bytes")]
             (Self::Byte(a), Self::Complex(b)) => _co(a.convert(), b),
+            (Self::Complex(a), Self::Int(b)) => _co(a, b.convert()),
+            (Self::Int(a), Self::Complex(b)) => _co(a.convert(), b),
             (Self::Char(a), Self::Char(b)) => ch(a, b),
             (Self::Box(a), Self::Box(b)) => f(a, b),
             (Self::Box(a), b) => f(a, b.coerce_to_boxes()),
@@ -488,6 +534,7 @@ impl Value {
         other: &Self,
         n: impl FnOnce(&Array<f64>, &Array<f64>) -> Result<T, E>,
         _b: impl FnOnce(&Array<u8>, &Array<u8>) -> Result<T, E>,
+        i: impl FnOnce(&Array<u32>, &Array<u32>) -> Result<T, E>,
         _co: impl FnOnce(&Array<Complex>, &Array<Complex>) -> Result<T, E>,
         ch: impl FnOnce(&Array<char>, &Array<char>) -> Result<T, E>,
         f: impl FnOnce(&Array<Boxed>, &Array<Boxed>) -> Result<T, E>,
@@ -501,6 +548,13 @@ impl Value {
             (Self::Byte(a), Self::Num(b)) => n(&a.convert_ref(), b),
             #[cfg(feature = "bytes")]
             (Self::Num(a), Self::Byte(b)) => n(a, &b.convert_ref()),
+            (Self::Int(a), Self::Int(b)) => i(a, b),
+            (Self::Int(a), Self::Num(b)) => n(&a.convert_ref(), b),
+            (Self::Num(a), Self::Int(b)) => n(a, &b.convert_ref()),
+            #[cfg(feature = "bytes")]
+            (Self::Int(a), Self::Byte(b)) => n(&a.convert_ref(), &b.convert_ref()),
+            #[cfg(feature = "bytes")]
+            (Self::Byte(a), Self::Int(b)) => n(&a.convert_ref(), &b.convert_ref()),
             (Self::Complex(a), Self::Complex(b)) => _co(a, b),
             (Self::Complex(a), Self::Num(b)) => _co(a, &b.convert_ref()),
             (Self::Num(a), Self::Complex(b)) => _co(&a.convert_ref(), b),
@@ -508,6 +562,8 @@ impl Value {
             (Self::Complex(a), Self::Byte(b)) => _co(a, &b.convert_ref()),
             #[cfg(feature = "bytes")]
             (Self::Byte(a), Self::Complex(b)) => _co(&a.convert_ref(), b),
+            (Self::Complex(a), Self::Int(b)) => _co(a, &b.convert_ref()),
+            (Self::Int(a), Self::Complex(b)) => _co(&a.convert_ref(), b),
             (Self::Char(a), Self::Char(b)) => ch(a, b),
             (Self::Box(a), Self::Box(b)) => f(a, b),
             (Self::Box(a), b) => f(a, &b.coerce_as_boxes()),
@@ -521,6 +577,7 @@ impl Value {
             Self::Num(arr) => arr.data.reserve_min(min),
             #[cfg(feature = "bytes")]
             Self::Byte(arr) => arr.data.reserve_min(min),
+            Self::Int(arr) => arr.data.reserve_min(min),
             Self::Complex(arr) => arr.data.reserve_min(min),
             Self::Char(arr) => arr.data.reserve_min(min),
             Self::Box(arr) => arr.data.reserve_min(min),
@@ -542,6 +599,11 @@ impl Value {
     ///
     /// The `requirement` parameter is used in error messages.
     pub fn as_ints(&self, env: &Uiua, requirement: &'static str) -> UiuaResult<Vec<isize>> {
+        if let Value::Int(ints) = self {
+            if ints.rank() <= 1 {
+                return Ok(ints.data.iter().map(|&i| i as isize).collect());
+            }
+        }
         self.as_number_list(env, requirement, |f| f.fract() == 0.0, |f| f as isize)
     }
     /// Attempt to convert the array to a single boolean
@@ -583,6 +645,21 @@ impl Value {
                     return Err(env.error(format!("{requirement}, but it is {num}")));
                 }
             }
+            Value::Int(ints) => {
+                if ints.rank() > 0 {
+                    return Err(
+                        env.error(format!("{requirement}, but its rank is {}", ints.rank()))
+                    );
+                }
+                let num = ints.data[0];
+                if num == 0 {
+                    false
+                } else if num == 1 {
+                    true
+                } else {
+                    return Err(env.error(format!("{requirement}, but it is {num}")));
+                }
+            }
             value => {
                 return Err(env.error(format!("{requirement}, but it is {}", value.type_name())))
             }
@@ -620,6 +697,14 @@ impl Value {
                 }
                 bytes.data[0] as usize
             }
+            Value::Int(ints) => {
+                if ints.rank() > 0 {
+                    return Err(
+                        env.error(format!("{requirement}, but its rank is {}", ints.rank()))
+                    );
+                }
+                ints.data[0] as usize
+            }
             value => {
                 return Err(env.error(format!("{requirement}, but it is {}", value.type_name())))
             }
@@ -654,6 +739,14 @@ impl Value {
                 }
                 bytes.data[0] as isize
             }
+            Value::Int(ints) => {
+                if ints.rank() > 0 {
+                    return Err(
+                        env.error(format!("{requirement}, but its rank is {}", ints.rank()))
+                    );
+                }
+                ints.data[0] as isize
+            }
             value => {
                 return Err(env.error(format!("{requirement}, but it is {}", value.type_name())))
             }
@@ -684,6 +777,14 @@ impl Value {
                 }
                 bytes.data[0] as f64
             }
+            Value::Int(ints) => {
+                if ints.rank() > 0 {
+                    return Err(
+                        env.error(format!("{requirement}, but its rank is {}", ints.rank()))
+                    );
+                }
+                ints.data[0] as f64
+            }
             value => {
                 return Err(env.error(format!("{requirement}, but it is {}", value.type_name())))
             }
@@ -699,6 +800,11 @@ impl Value {
     ///
     /// The `requirement` parameter is used in error messages.
     pub fn as_nats(&self, env: &Uiua, requirement: &'static str) -> UiuaResult<Vec<usize>> {
+        if let Value::Int(ints) = self {
+            if ints.rank() <= 1 {
+                return Ok(ints.data.iter().map(|&i| i as usize).collect());
+            }
+        }
         self.as_number_list(
             env,
             requirement,
@@ -783,6 +889,22 @@ impl Value {
                 }
                 result
             }
+            Value::Int(ints) => {
+                if ints.rank() > 1 {
+                    return Err(
+                        env.error(format!("{requirement}, but its rank is {}", ints.rank()))
+                    );
+                }
+                let mut result = Vec::with_capacity(ints.row_count());
+                for &int in ints.data() {
+                    let num = int as f64;
+                    if !test(num) {
+                        return Err(env.error(requirement));
+                    }
+                    result.push(convert(num));
+                }
+                result
+            }
             value => {
                 return Err(env.error(format!(
                     "{requirement}, but it is {}",
@@ -796,6 +918,10 @@ impl Value {
         env: &Uiua,
         requirement: &'static str,
     ) -> UiuaResult<Array<isize>> {
+        if let Value::Int(ints) = self {
+            let data: EcoVec<isize> = ints.data.iter().map(|&i| i as isize).collect();
+            return Ok(Array::new(ints.shape.clone(), data));
+        }
         self.as_number_array(
             env,
             requirement,
@@ -809,6 +935,10 @@ impl Value {
         env: &Uiua,
         requirement: &'static str,
     ) -> UiuaResult<Array<usize>> {
+        if let Value::Int(ints) = self {
+            let data: EcoVec<usize> = ints.data.iter().map(|&i| i as usize).collect();
+            return Ok(Array::new(ints.shape.clone(), data));
+        }
         self.as_number_array(
             env,
             requirement,
@@ -858,6 +988,22 @@ impl Value {
                 }
                 Array::new(self.shape().clone(), result)
             }
+            Value::Int(ints) => {
+                if !test_shape(self.shape()) {
+                    return Err(
+                        env.error(format!("{requirement}, but its shape is {}", ints.shape()))
+                    );
+                }
+                let mut result = EcoVec::with_capacity(ints.element_count());
+                for &int in ints.data() {
+                    let num = int as f64;
+                    if !test_num(num) {
+                        return Err(env.error(requirement));
+                    }
+                    result.push(convert_num(num));
+                }
+                Array::new(self.shape().clone(), result)
+            }
             value => {
                 return Err(env.error(format!(
                     "{requirement}, but its type is {}",
@@ -915,6 +1061,12 @@ impl Value {
                 }
                 a.data.into_iter().map(|f| f as u8).collect()
             }
+            Value::Int(a) => {
+                if a.rank() != 1 {
+                    return Err(env.error(format!("{requirement}, but its rank is {}", a.rank())));
+                }
+                a.data.into_iter().map(|i| i as u8).collect()
+            }
             Value::Char(a) => {
                 if a.rank() != 1 {
                     return Err(env.error(format!("{requirement}, but its rank is {}", a.rank())));
@@ -1055,12 +1207,42 @@ impl Value {
             _ => {}
         }
     }
+    /// Make an array of indices
+    ///
+    /// The result is an [`Value::Int`] array unless some index does not fit in a [`u32`].
+    pub(crate) fn from_indices(
+        shape: impl Into<Shape>,
+        indices: impl IntoIterator<Item = usize>,
+    ) -> Self {
+        let shape = shape.into();
+        let mut indices = indices.into_iter();
+        let mut ints = EcoVec::with_capacity(shape.iter().product());
+        while let Some(i) = indices.next() {
+            let Ok(int) = u32::try_from(i) else {
+                let nums: EcoVec<f64> = (ints.iter().map(|&i| i as f64))
+                    .chain(once(i as f64))
+                    .chain(indices.map(|i| i as f64))
+                    .collect();
+                return Array::new(shape, nums).into();
+            };
+            ints.push(int);
+        }
+        Array::new(shape, ints).into()
+    }
+    /// Promote an index array to a number array, leaving other values unchanged
+    pub(crate) fn promote_ints(self) -> Self {
+        match self {
+            Value::Int(arr) => Value::Num(arr.convert()),
+            val => val,
+        }
+    }
     /// Convert to a box array by boxing every element
     pub fn coerce_to_boxes(self) -> Array<Boxed> {
         match self {
             Value::Num(arr) => arr.convert_with(|v| Boxed(Value::from(v))),
             #[cfg(feature = "bytes")]
             Value::Byte(arr) => arr.convert_with(|v| Boxed(Value::from(v))),
+            Value::Int(arr) => arr.convert_with(|v| Boxed(Value::from(v))),
             Value::Complex(arr) => arr.convert_with(|v| Boxed(Value::from(v))),
             Value::Char(arr) => arr.convert_with(|v| Boxed(Value::from(v))),
             Value::Box(arr) => arr,
@@ -1072,6 +1254,7 @@ impl Value {
             Value::Num(arr) => Cow::Owned(arr.convert_ref_with(|v| Boxed(Value::from(v)))),
             #[cfg(feature = "bytes")]
             Value::Byte(arr) => Cow::Owned(arr.convert_ref_with(|v| Boxed(Value::from(v)))),
+            Value::Int(arr) => Cow::Owned(arr.convert_ref_with(|v| Boxed(Value::from(v)))),
             Value::Complex(arr) => Cow::Owned(arr.convert_ref_with(|v| Boxed(Value::from(v)))),
             Value::Char(arr) => Cow::Owned(arr.convert_ref_with(|v| Boxed(Value::from(v)))),
             Value::Box(arr) => Cow::Borrowed(arr),
@@ -1145,6 +1328,7 @@ macro_rules! value_from {
 value_from!(f64, Num);
 #[cfg(feature = "bytes")]
 value_from!(u8, Byte);
+value_from!(u32, Int);
 value_from!(char, Char);
 value_from!(Boxed, Box);
 value_from!(Complex, Complex);
@@ -1204,15 +1388,41 @@ impl From<i32> for Value {
     }
 }
 
+impl Value {
+    /// Convert a real array to a complex one if any of its elements satisfy a predicate
+    ///
+    /// This lets math that leaves the real domain produce complex results instead of `NaN`.
+    fn complex_if(self, f: impl Fn(f64) -> bool) -> Self {
+        let mut array: Array<Complex> = match self {
+            Value::Num(array) if array.data.iter().any(|&n| f(n)) => array.convert(),
+            #[cfg(feature = "bytes")]
+            Value::Byte(array) if array.data.iter().any(|&n| f(n.into())) => array.convert(),
+            Value::Int(array) if array.data.iter().any(|&n| f(n.into())) => array.convert(),
+            val => return val,
+        };
+        array.reset_meta_flags();
+        array.into()
+    }
+    fn has_fractional(&self) -> bool {
+        match self {
+            Value::Num(array) => array.data.iter().any(|n| n.fract() != 0.0),
+            Value::Complex(_) => true,
+            _ => false,
+        }
+    }
+}
+
 macro_rules! value_un_impl {
-    ($name:ident, $(
+    ($name:ident, $(promote($promote:expr),)? $(
         $([$($feature1:literal,)* $(|$meta:ident| $pred:expr,)* $in_place:ident, $f:ident])?
         $(($($feature2:literal,)* $make_new:ident, $f2:ident))?
     ),* $(,)?) => {
         impl Value {
             #[allow(clippy::redundant_closure_call)]
             pub(crate) fn $name(self, env: &Uiua) -> UiuaResult<Self> {
-                self.keep_label(|val| Ok(match val {
+                let val = self;
+                $(let val = val.complex_if($promote);)?
+                val.keep_label(|val| Ok(match val {
                     $($($(#[cfg(feature = $feature1)])* Self::$in_place(mut array) $(if (|$meta: &ArrayMeta| $pred)(array.meta()))* => {
                         for val in &mut array.data {
                             *val = $name::$f(*val);
@@ -1226,6 +1436,7 @@ macro_rules! value_un_impl {
                         }
                         (array.shape, new).into()
                     },)*)*
+                    Value::Int(array) => Value::$name(array.convert::<f64>().into(), env)?,
                     Value::Box(mut array) => {
                         let mut new_data = EcoVec::with_capacity(array.flat_len());
                         for b in array.data {
@@ -1250,10 +1461,11 @@ value_un_impl!(
     ("bytes", Byte, byte),
     [Complex, com]
 );
-value_un_impl!(abs, [Num, num], ("bytes", Byte, byte), (Complex, com));
+value_un_impl!(abs, [Num, num], ["bytes", Byte, byte], (Complex, com));
 value_un_impl!(sign, [Num, num], ["bytes", Byte, byte], [Complex, com]);
 value_un_impl!(
     sqrt,
+    promote(|n| n < 0.0),
     [Num, num],
     ["bytes", |meta| meta.flags.is_boolean(), Byte, bool],
     ("bytes", Byte, byte),
@@ -1261,8 +1473,20 @@ value_un_impl!(
 );
 value_un_impl!(sin, [Num, num], ("bytes", Byte, byte), [Complex, com]);
 value_un_impl!(cos, [Num, num], ("bytes", Byte, byte), [Complex, com]);
-value_un_impl!(asin, [Num, num], ("bytes", Byte, byte), [Complex, com]);
-value_un_impl!(acos, [Num, num], ("bytes", Byte, byte), [Complex, com]);
+value_un_impl!(
+    asin,
+    promote(|n| n.abs() > 1.0),
+    [Num, num],
+    ("bytes", Byte, byte),
+    [Complex, com]
+);
+value_un_impl!(
+    acos,
+    promote(|n| n.abs() > 1.0),
+    [Num, num],
+    ("bytes", Byte, byte),
+    [Complex, com]
+);
 value_un_impl!(floor, [Num, num], ["bytes", Byte, byte], [Complex, com]);
 value_un_impl!(ceil, [Num, num], ["bytes", Byte, byte], [Complex, com]);
 value_un_impl!(round, [Num, num], ["bytes", Byte, byte], [Complex, com]);
@@ -1284,20 +1508,26 @@ macro_rules! val_retry {
     (Byte, $env:expr) => {
         $env.num_fill().is_ok()
     };
+    (Int, $env:expr) => {
+        $env.num_fill().is_ok()
+    };
     ($variant:ident, $env:expr) => {
         false
     };
 }
 
 macro_rules! value_bin_impl {
-    ($name:ident, $(
+    ($name:ident, $(promote($promote:expr),)? $(
         $(($($feature1:literal,)* $na:ident, $nb:ident, $f1:ident $(, $retry:ident)? ))*
         $([$($feature2:literal,)* $(|$meta:ident| $pred:expr,)* $ip:ident, $f2:ident $(, $retry2:ident)? $(, $reset_meta:literal)?])*
+        $({$($feature3:literal,)* $ra:ident, $rb:ident, $f3:ident, $reuse:ident $(, $retry3:ident)?})*
     ),* ) => {
         impl Value {
-            #[allow(unreachable_patterns, unused_mut, clippy::wrong_self_convention)]
+            #[allow(unreachable_patterns, unused_mut, clippy::wrong_self_convention, clippy::redundant_closure_call)]
             pub(crate) fn $name(self, other: Self, a_depth: usize, b_depth: usize, env: &Uiua) -> UiuaResult<Self> {
-                self.keep_labels(other, |a, b| { Ok(match (a, b) {
+                let (a, b) = (self, other);
+                $(let (a, b) = $promote(a, b);)?
+                a.keep_labels(b, |a, b| { Ok(match (a, b) {
                     $($($(#[cfg(feature = $feature2)])* (Value::$ip(mut a), Value::$ip(b)) $(if {
                         let f = |$meta: &ArrayMeta| $pred;
                         f(a.meta()) && f(b.meta())
@@ -1345,6 +1575,24 @@ macro_rules! value_bin_impl {
                         val.reset_meta_flags();
                         val
                     },)*)*
+                    $($($(#[cfg(feature = $feature3)])* (Value::$ra(a), Value::$rb(b)) => {
+                        let mut val: Value = if val_retry!($ra, env) || val_retry!($rb, env) {
+                            let res = $reuse(a.clone(), b.clone(), a_depth, b_depth, env, $name::$f3);
+                            match res {
+                                Ok(arr) => arr.into(),
+                                #[allow(unreachable_code, unused_variables)]
+                                Err(e) if e.is_fill() => {
+                                    $(return bin_pervade(a.convert::<f64>(), b.convert::<f64>(), a_depth, b_depth, env, InfalliblePervasiveFn::new($name::$retry3)).map(Into::into);)?
+                                    return Err(e);
+                                }
+                                Err(e) => return Err(e),
+                            }
+                        } else {
+                            $reuse(a, b, a_depth, b_depth, env, $name::$f3)?.into()
+                        };
+                        val.reset_meta_flags();
+                        val
+                    },)*)*
                     (Value::Box(a), Value::Box(b)) => {
                         let (a, b) = match (a.into_unboxed(), b.into_unboxed()) {
                             (Ok(a), Ok(b)) => return Ok(Boxed(Value::$name(a, b, a_depth, b_depth, env)?).into()),
@@ -1384,6 +1632,9 @@ macro_rules! value_bin_impl {
                         val.reset_meta_flags();
                         val
                     },
+                    // Index arrays are promoted to numbers for anything without a dedicated implementation
+                    (Value::Int(a), b) => Value::$name(a.convert::<f64>().into(), b, a_depth, b_depth, env)?,
+                    (a, Value::Int(b)) => Value::$name(a, b.convert::<f64>().into(), a_depth, b_depth, env)?,
                     (a, b) => return Err($name::error(a.type_name(), b.type_name(), env)),
                 })})
             }
@@ -1398,8 +1649,8 @@ macro_rules! value_bin_math_impl {
             $($($tt)*)?
             [Num, num_num],
             ("bytes", Byte, Byte, byte_byte, num_num),
-            ("bytes", Byte, Num, byte_num, num_num),
-            ("bytes", Num, Byte, num_byte, num_num),
+            {"bytes", Byte, Num, byte_num, bin_pervade_reuse_right, num_num},
+            {"bytes", Num, Byte, num_byte, bin_pervade_reuse_left, num_num},
             [Complex, com_x],
             (Complex, Num, com_x),
             (Num, Complex, x_com),
@@ -1442,8 +1693,22 @@ value_bin_math_impl!(
 );
 value_bin_math_impl!(div);
 value_bin_math_impl!(modulus, (Complex, Complex, com_com));
-value_bin_math_impl!(pow);
-value_bin_math_impl!(log);
+value_bin_math_impl!(
+    pow,
+    promote(|a: Value, b: Value| {
+        // A negative base to a fractional power is complex
+        let b = if a.has_fractional() {
+            b.complex_if(|n| n < 0.0)
+        } else {
+            b
+        };
+        (a, b)
+    }),
+);
+value_bin_math_impl!(
+    log,
+    promote(|a: Value, b: Value| (a.complex_if(|n| n < 0.0), b.complex_if(|n| n < 0.0))),
+);
 value_bin_math_impl!(atan2);
 value_bin_math_impl!(
     min,
@@ -1491,6 +1756,7 @@ macro_rules! eq_impls {
                 [Complex, same_type],
                 (Box, Box, generic),
                 ("bytes", Byte, Byte, same_type, num_num),
+                (Int, Int, generic, num_num),
                 (Char, Char, generic),
                 ("bytes", Num, Byte, num_byte, num_num),
                 ("bytes", Byte, Num, byte_num, num_num),
@@ -1518,6 +1784,7 @@ macro_rules! cmp_impls {
                 [Complex, com_x],
                 (Box, Box, generic),
                 ("bytes", Byte, Byte, same_type, num_num),
+                (Int, Int, generic, num_num),
                 (Char, Char, generic),
                 ("bytes", Num, Byte, num_byte, num_num),
                 ("bytes", Byte, Num, byte_num, num_num),
@@ -1551,6 +1818,13 @@ impl PartialEq for Value {
             (Value::Num(a), Value::Byte(b)) => a == b,
             #[cfg(feature = "bytes")]
             (Value::Byte(a), Value::Num(b)) => a == b,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Num(a), Value::Int(b)) => a == b,
+            (Value::Int(a), Value::Num(b)) => a == b,
+            #[cfg(feature = "bytes")]
+            (Value::Byte(a), Value::Int(b)) => a.convert_ref::<u32>() == *b,
+            #[cfg(feature = "bytes")]
+            (Value::Int(a), Value::Byte(b)) => *a == b.convert_ref::<u32>(),
             _ => false,
         }
     }
@@ -1581,12 +1855,21 @@ impl Ord for Value {
             (Value::Num(a), Value::Byte(b)) => a.partial_cmp(b).unwrap(),
             #[cfg(feature = "bytes")]
             (Value::Byte(a), Value::Num(b)) => a.partial_cmp(b).unwrap(),
+            (Value::Int(a), Value::Int(b)) => a.cmp(b),
+            (Value::Num(a), Value::Int(b)) => a.partial_cmp(b).unwrap(),
+            (Value::Int(a), Value::Num(b)) => a.partial_cmp(b).unwrap(),
+            #[cfg(feature = "bytes")]
+            (Value::Byte(a), Value::Int(b)) => a.convert_ref::<u32>().cmp(b),
+            #[cfg(feature = "bytes")]
+            (Value::Int(a), Value::Byte(b)) => a.cmp(&b.convert_ref()),
             (Value::Num(_), _) => Ordering::Less,
             (_, Value::Num(_)) => Ordering::Greater,
             #[cfg(feature = "bytes")]
             (Value::Byte(_), _) => Ordering::Less,
             #[cfg(feature = "bytes")]
             (_, Value::Byte(_)) => Ordering::Greater,
+            (Value::Int(_), _) => Ordering::Less,
+            (_, Value::Int(_)) => Ordering::Greater,
             (Value::Complex(_), _) => Ordering::Less,
             (_, Value::Complex(_)) => Ordering::Greater,
             (Value::Char(_), _) => Ordering::Less,
@@ -1601,6 +1884,7 @@ impl Hash for Value {
             Value::Num(arr) => arr.hash(state),
             #[cfg(feature = "bytes")]
             Value::Byte(arr) => arr.hash(state),
+            Value::Int(arr) => arr.hash(state),
             Value::Complex(arr) => arr.hash(state),
             Value::Char(arr) => arr.hash(state),
             Value::Box(arr) => arr.hash(state),
@@ -1614,6 +1898,7 @@ impl fmt::Debug for Value {
             Self::Num(array) => array.fmt(f),
             #[cfg(feature = "bytes")]
             Self::Byte(array) => array.fmt(f),
+            Self::Int(array) => array.fmt(f),
             Self::Complex(array) => array.fmt(f),
             Self::Char(array) => array.fmt(f),
             Self::Box(array) => array.fmt(f),
@@ -1749,6 +2034,8 @@ impl From<Value> for ValueRep {
             #[cfg(feature = "bytes")]
             Value::Byte(arr) => Self::Byte(arr),
             Value::Num(arr) => Self::Num(arr.convert_with(MapNumRep::from)),
+            // Index arrays are stored as numbers so that the format does not change
+            Value::Int(arr) => Self::Num(arr.convert_with(|i| MapNumRep::Num(i.into()))),
             Value::Complex(arr) => Self::Complex(arr),
             Value::Char(arr) => Self::Char {
                 shape: arr.shape().clone(),
@@ -1759,3 +2046,35 @@ impl From<Value> for ValueRep {
         }
     }
 }
+
+#[test]
+fn compact_indices() {
+    let run = |code: &str| {
+        let mut env = Uiua::with_safe_sys();
+        env.run_str(code).unwrap();
+        env.pop("result").unwrap()
+    };
+    for code in [
+        "⇡10",
+        "⇡300",
+        "⇡2_3",
+        "⊚[1 0 2]",
+        "⊚[1_0 0_2]",
+        "⊛\"abac\"",
+        "⍏[2 0 1]",
+        "⍖[2 0 1]",
+        "↙3⇡10",
+        "⊡¯1⇡10",
+    ] {
+        let val = run(code);
+        assert!(matches!(val, Value::Int(_)), "{code} gave {val:?}");
+    }
+    // Anything that may leave the naturals promotes to numbers
+    for code in ["-1⇡3", "¯⇡3", "÷2⇡4", "⊂¯1⇡3", "/+⇡4"] {
+        let val = run(code);
+        assert!(matches!(val, Value::Num(_)), "{code} gave {val:?}");
+    }
+    assert_eq!(run("-1⇡3"), run("[¯1 0 1]"));
+    assert_eq!(run("⇡3"), run("[0 1 2]"));
+    assert_eq!(run("⊏⇡3 \"abc\""), Value::from("abc"));
+}
@@ -0,0 +1,95 @@
+//! Check that monadic primitives reuse the memory of arrays that nothing else references
+//!
+//! This is its own test binary so that its allocator counts only the allocations of this test.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use uiua::{Compiler, Uiua, Value};
+
+struct CountingAlloc;
+
+/// The minimum size of the allocations that are counted
+static THRESHOLD: AtomicUsize = AtomicUsize::new(usize::MAX);
+/// The number of allocations at least [`THRESHOLD`] bytes long
+static LARGE_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if layout.size() >= THRESHOLD.load(Ordering::Relaxed) {
+            LARGE_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if new_size >= THRESHOLD.load(Ordering::Relaxed) {
+            LARGE_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Run a primitive on a value and count the allocations at least as large as the value's data
+fn run_counting(prim: &str, val: Value, data_bytes: usize) -> (Value, usize) {
+    let asm = Compiler::new().load_str(prim).unwrap().finish();
+    let mut env = Uiua::with_safe_sys();
+    env.push(val);
+    THRESHOLD.store(data_bytes, Ordering::Relaxed);
+    let before = LARGE_ALLOCATIONS.load(Ordering::Relaxed);
+    env.run_asm(asm).unwrap();
+    let count = LARGE_ALLOCATIONS.load(Ordering::Relaxed) - before;
+    THRESHOLD.store(usize::MAX, Ordering::Relaxed);
+    (env.pop(1).unwrap(), count)
+}
+
+#[test]
+fn in_place_allocations() {
+    // Each value has 10000 elements
+    let nums = "-3.5 ↯100_100 ⇡1e4";
+    let chars = "+@a ◿26 ↯100_100 ⇡1e4";
+    let mut cases = vec![
+        ("¯", nums, 8),
+        ("¬", nums, 8),
+        ("±", nums, 8),
+        ("⌵", nums, 8),
+        ("⇌", nums, 8),
+        ("⍉", nums, 8),
+        ("⇌", chars, 4),
+        ("⍉", chars, 4),
+    ];
+    if cfg!(feature = "bytes") {
+        let bytes = "=0 ◿3 ↯100_100 ⇡1e4";
+        cases.extend([
+            ("±", bytes, 1),
+            ("⌵", bytes, 1),
+            ("⇌", bytes, 1),
+            ("⍉", bytes, 1),
+        ]);
+    }
+    for (prim, init, elem_bytes) in cases {
+        let mut env = Uiua::with_safe_sys();
+        env.run_str(init).unwrap();
+        let val = env.pop(1).unwrap();
+        let data_bytes = val.element_count() * elem_bytes;
+
+        // Shared data must be copied
+        let (copied, count) = run_counting(prim, val.clone(), data_bytes);
+        assert!(count > 0, "{prim} {init} did not copy shared data");
+
+        // Uniquely owned data must be mutated in place
+        let (mutated, count) = run_counting(prim, val, data_bytes);
+        assert_eq!(count, 0, "{prim} {init} allocated for unique data");
+        assert_eq!(
+            mutated, copied,
+            "{prim} {init} in place differs from copying"
+        );
+    }
+}
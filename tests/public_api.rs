@@ -0,0 +1,526 @@
+//! Check the public API of the crate against `public_api.txt`
+//!
+//! The API is read from the JSON output of rustdoc, which is unstable,
+//! so this only runs with the pinned nightly toolchain that CI uses.
+
+use std::{collections::BTreeSet, path::Path, process::Command};
+
+use serde_json::Value as Json;
+
+/// The nightly toolchain whose rustdoc JSON output this understands
+const NIGHTLY: &str = "nightly-2026-05-19";
+/// The rustdoc JSON format version produced by [`NIGHTLY`]
+const FORMAT_VERSION: u64 = 57;
+
+#[test]
+#[ignore = "requires a pinned nightly toolchain"]
+fn public_api() {
+    let toolchain = std::env::var("PUBLIC_API_TOOLCHAIN").unwrap_or_else(|_| NIGHTLY.into());
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let target_dir = manifest_dir.join("target").join("public_api");
+    let status = Command::new("cargo")
+        .arg(format!("+{toolchain}"))
+        .args(["rustdoc", "--lib", "-p", "uiua", "--"])
+        .args(["-Z", "unstable-options", "--output-format", "json"])
+        .env("CARGO_TARGET_DIR", &target_dir)
+        .current_dir(manifest_dir)
+        .status()
+        .expect("Failed to run rustdoc");
+    assert!(status.success(), "rustdoc failed");
+    let json = std::fs::read_to_string(target_dir.join("doc").join("uiua.json")).unwrap();
+    let doc: Json = serde_json::from_str(&json).unwrap();
+    let format_version = doc["format_version"].as_u64().unwrap_or_default();
+    assert_eq!(
+        format_version, FORMAT_VERSION,
+        "{toolchain} produces rustdoc JSON format version {format_version}, \
+        but only version {FORMAT_VERSION} from {NIGHTLY} is supported"
+    );
+
+    // Walk the public items reachable from the crate root
+    struct Walker<'a> {
+        index: &'a Json,
+        /// Entries are sorted by the path of the item they belong to,
+        /// then with the item first, its members next, and its impls last
+        entries: BTreeSet<(String, u8, String)>,
+    }
+    fn item_kind(item: &Json) -> Option<(&str, &Json)> {
+        let (kind, inner) = item["inner"].as_object()?.iter().next()?;
+        Some((kind.as_str(), inner))
+    }
+    fn ids(list: &Json) -> impl Iterator<Item = &Json> {
+        list.as_array().into_iter().flatten()
+    }
+    fn join<'a>(list: &'a Json, sep: &str, f: impl Fn(&'a Json) -> String) -> String {
+        ids(list).map(f).collect::<Vec<_>>().join(sep)
+    }
+    fn str(json: &Json) -> &str {
+        json.as_str().unwrap_or_default()
+    }
+
+    // Render the types, bounds, and generics of items as they would be written in Rust
+    fn ty(t: &Json) -> String {
+        let Some((kind, inner)) = t.as_object().and_then(|t| t.iter().next()) else {
+            return "_".into();
+        };
+        match kind.as_str() {
+            "resolved_path" => path(inner),
+            "generic" | "primitive" => str(inner).into(),
+            "tuple" => format!("({})", join(inner, ", ", ty)),
+            "slice" => format!("[{}]", ty(inner)),
+            "array" => format!("[{}; {}]", ty(&inner["type"]), str(&inner["len"])),
+            "borrowed_ref" | "raw_pointer" => {
+                let mutable = inner["is_mutable"] == true;
+                let prefix = match (kind.as_str(), inner["lifetime"].as_str()) {
+                    ("raw_pointer", _) if mutable => "*mut ".into(),
+                    ("raw_pointer", _) => "*const ".into(),
+                    (_, Some(lifetime)) if mutable => format!("&{lifetime} mut "),
+                    (_, Some(lifetime)) => format!("&{lifetime} "),
+                    _ if mutable => "&mut ".into(),
+                    _ => "&".into(),
+                };
+                format!("{prefix}{}", ty(&inner["type"]))
+            }
+            "impl_trait" => format!("impl {}", bounds(inner)),
+            "dyn_trait" => {
+                let mut dyn_trait = format!("dyn {}", join(&inner["traits"], " + ", poly_trait));
+                if let Some(lifetime) = inner["lifetime"].as_str() {
+                    dyn_trait = format!("{dyn_trait} + {lifetime}");
+                }
+                dyn_trait
+            }
+            "function_pointer" => {
+                let sig = &inner["sig"];
+                let inputs = join(&sig["inputs"], ", ", |input| ty(&input[1]));
+                format!("{}fn({inputs}){}", header(&inner["header"]), output(sig))
+            }
+            "qualified_path" => {
+                let self_type = ty(&inner["self_type"]);
+                let name = str(&inner["name"]);
+                match &inner["trait"] {
+                    tr if str(&tr["path"]).is_empty() => format!("{self_type}::{name}"),
+                    tr => format!("<{self_type} as {}>::{name}", path(tr)),
+                }
+            }
+            kind => kind.into(),
+        }
+    }
+    fn path(path: &Json) -> String {
+        // Paths in derived impls start from the crate of the derive macro
+        let name = match str(&path["path"]) {
+            name if name.starts_with("$crate::") => name.rsplit("::").next().unwrap(),
+            name => name,
+        };
+        format!("{name}{}", generic_args(&path["args"]))
+    }
+    fn generic_args(args: &Json) -> String {
+        if let Some(args) = args.get("angle_bracketed") {
+            let mut list: Vec<String> = ids(&args["args"])
+                .map(
+                    |arg| match arg.as_object().and_then(|arg| arg.iter().next()) {
+                        Some((kind, arg)) if kind == "type" => ty(arg),
+                        Some((kind, arg)) if kind == "lifetime" => str(arg).into(),
+                        Some((kind, arg)) if kind == "const" => str(&arg["expr"]).into(),
+                        _ => "_".into(),
+                    },
+                )
+                .collect();
+            list.extend(ids(&args["constraints"]).map(|constraint| {
+                let name = format!(
+                    "{}{}",
+                    str(&constraint["name"]),
+                    generic_args(&constraint["args"])
+                );
+                let binding = &constraint["binding"];
+                if let Some(equality) = binding.get("equality") {
+                    match equality.get("type") {
+                        Some(t) => format!("{name} = {}", ty(t)),
+                        None => format!("{name} = {}", str(&equality["constant"]["expr"])),
+                    }
+                } else {
+                    format!("{name}: {}", bounds(&binding["constraint"]))
+                }
+            }));
+            if list.is_empty() {
+                String::new()
+            } else {
+                format!("<{}>", list.join(", "))
+            }
+        } else if let Some(args) = args.get("parenthesized") {
+            let output = match &args["output"] {
+                Json::Null => String::new(),
+                output => format!(" -> {}", ty(output)),
+            };
+            format!("({}){output}", join(&args["inputs"], ", ", ty))
+        } else {
+            String::new()
+        }
+    }
+    fn bounds(bounds: &Json) -> String {
+        join(bounds, " + ", |bound| {
+            if let Some(bound) = bound.get("trait_bound") {
+                let maybe = if bound["modifier"] == "maybe" {
+                    "?"
+                } else {
+                    ""
+                };
+                format!("{maybe}{}", poly_trait(bound))
+            } else if let Some(lifetime) = bound.get("outlives") {
+                str(lifetime).into()
+            } else {
+                format!("use<{}>", join(&bound["use"], ", ", |arg| str(arg).into()))
+            }
+        })
+    }
+    fn poly_trait(poly: &Json) -> String {
+        let params = join(&poly["generic_params"], ", ", |param| {
+            str(&param["name"]).into()
+        });
+        let for_params = if params.is_empty() {
+            String::new()
+        } else {
+            format!("for<{params}> ")
+        };
+        format!("{for_params}{}", path(&poly["trait"]))
+    }
+    /// Get a declaration's generic parameters and where clause
+    fn generics(generics: &Json) -> (String, String) {
+        let params: Vec<String> = ids(&generics["params"])
+            .filter_map(|param| {
+                let name = str(&param["name"]);
+                let (kind, inner) = param["kind"].as_object()?.iter().next()?;
+                Some(match kind.as_str() {
+                    "lifetime" => match join(&inner["outlives"], " + ", |l| str(l).into()) {
+                        outlives if outlives.is_empty() => name.into(),
+                        outlives => format!("{name}: {outlives}"),
+                    },
+                    "type" if inner["is_synthetic"] == true => return None,
+                    "type" => {
+                        let mut param = name.to_string();
+                        if ids(&inner["bounds"]).next().is_some() {
+                            param = format!("{param}: {}", bounds(&inner["bounds"]));
+                        }
+                        if !inner["default"].is_null() {
+                            param = format!("{param} = {}", ty(&inner["default"]));
+                        }
+                        param
+                    }
+                    _ => format!("const {name}: {}", ty(&inner["type"])),
+                })
+            })
+            .collect();
+        let params = if params.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", params.join(", "))
+        };
+        let predicates = join(&generics["where_predicates"], ", ", |pred| {
+            if let Some(pred) = pred.get("bound_predicate") {
+                format!("{}: {}", ty(&pred["type"]), bounds(&pred["bounds"]))
+            } else if let Some(pred) = pred.get("lifetime_predicate") {
+                let outlives = join(&pred["outlives"], " + ", |l| str(l).into());
+                format!("{}: {outlives}", str(&pred["lifetime"]))
+            } else {
+                let pred = &pred["eq_predicate"];
+                let rhs = match pred["rhs"].get("type") {
+                    Some(t) => ty(t),
+                    None => str(&pred["rhs"]["constant"]["expr"]).into(),
+                };
+                format!("{} = {rhs}", ty(&pred["lhs"]))
+            }
+        });
+        let where_clause = if predicates.is_empty() {
+            String::new()
+        } else {
+            format!(" where {predicates}")
+        };
+        (params, where_clause)
+    }
+    fn header(header: &Json) -> String {
+        let mut s = String::new();
+        for (key, word) in [
+            ("is_const", "const "),
+            ("is_async", "async "),
+            ("is_unsafe", "unsafe "),
+        ] {
+            if header[key] == true {
+                s.push_str(word);
+            }
+        }
+        match &header["abi"] {
+            Json::String(abi) if abi == "Rust" => {}
+            Json::String(abi) => s.push_str(&format!("extern {abi:?} ")),
+            abi => {
+                let abi = abi.as_object().and_then(|abi| abi.keys().next());
+                s.push_str(&format!("extern {:?} ", abi.map_or("", |abi| abi.as_str())));
+            }
+        }
+        s
+    }
+    fn output(sig: &Json) -> String {
+        match &sig["output"] {
+            Json::Null => String::new(),
+            output => format!(" -> {}", ty(output)),
+        }
+    }
+    fn function(path: &str, f: &Json) -> String {
+        let (params, where_clause) = generics(&f["generics"]);
+        let inputs = join(&f["sig"]["inputs"], ", ", |input| {
+            let name = str(&input[0]);
+            match (name, ty(&input[1]).as_str()) {
+                ("self", "Self") => "self".into(),
+                ("self", "&Self") => "&self".into(),
+                ("self", "&mut Self") => "&mut self".into(),
+                (name, t) => format!("{name}: {t}"),
+            }
+        });
+        let (header, output) = (header(&f["header"]), output(&f["sig"]));
+        format!("pub {header}fn {path}{params}({inputs}){output}{where_clause}")
+    }
+
+    impl<'a> Walker<'a> {
+        fn get(&self, id: &Json) -> Option<&'a Json> {
+            let index = self.index;
+            match id {
+                Json::String(id) => index.get(id),
+                id => index.get(id.to_string()),
+            }
+        }
+        fn add(&mut self, path: &str, order: u8, entry: String) {
+            self.entries.insert((path.into(), order, entry));
+        }
+        fn item(&mut self, id: &Json, parent: &str, rename: Option<&str>) {
+            let Some(item) = self.get(id) else {
+                return;
+            };
+            if item["visibility"] != "public" {
+                return;
+            }
+            let Some((kind, inner)) = item_kind(item) else {
+                return;
+            };
+            if kind == "use" {
+                match inner.get("id").filter(|id| !id.is_null()) {
+                    Some(target) if inner["is_glob"] == true => {
+                        if let Some(("module", module)) = self.get(target).and_then(item_kind) {
+                            for child in ids(&module["items"]) {
+                                self.item(child, parent, None);
+                            }
+                        }
+                    }
+                    Some(target) => self.item(target, parent, inner["name"].as_str()),
+                    None => {}
+                }
+                return;
+            }
+            let name = rename.or(item["name"].as_str()).unwrap_or_default();
+            let path = format!("{parent}::{name}");
+            let (params, where_clause) = generics(&inner["generics"]);
+            let entry = match kind {
+                "module" => format!("pub mod {path}"),
+                "function" => function(&path, inner),
+                "constant" => format!("pub const {path}: {}", ty(&inner["type"])),
+                "static" => {
+                    let mutable = if inner["is_mutable"] == true {
+                        "mut "
+                    } else {
+                        ""
+                    };
+                    format!("pub static {mutable}{path}: {}", ty(&inner["type"]))
+                }
+                "type_alias" => {
+                    let t = ty(&inner["type"]);
+                    format!("pub type {path}{params} = {t}{where_clause}")
+                }
+                "trait" => {
+                    let mut prefix = String::new();
+                    for (key, word) in [("is_unsafe", "unsafe "), ("is_auto", "auto ")] {
+                        if inner[key] == true {
+                            prefix.push_str(word);
+                        }
+                    }
+                    let supertraits = match bounds(&inner["bounds"]) {
+                        bounds if bounds.is_empty() => bounds,
+                        bounds => format!(": {bounds}"),
+                    };
+                    format!("pub {prefix}trait {path}{params}{supertraits}{where_clause}")
+                }
+                "macro" | "proc_macro" => format!("pub macro {path}!"),
+                kind => format!("pub {kind} {path}{params}{where_clause}"),
+            };
+            self.add(&path, 0, entry);
+            match kind {
+                "module" => {
+                    for child in ids(&inner["items"]) {
+                        self.item(child, &path, None);
+                    }
+                }
+                "trait" => {
+                    self.members(&inner["items"], &path, false);
+                    // Implementations for types that are not listed on their own
+                    for imp in ids(&inner["implementations"]) {
+                        let Some(imp) = self.get(imp) else {
+                            continue;
+                        };
+                        let Some(("impl", imp)) = item_kind(imp) else {
+                            continue;
+                        };
+                        let local = (imp["for"]["resolved_path"].get("id"))
+                            .and_then(|id| self.get(id))
+                            .is_some_and(|item| item["crate_id"] == 0);
+                        if !local && imp["blanket_impl"].is_null() {
+                            let for_type = ty(&imp["for"]);
+                            self.add(&path, 2, impl_entry(imp, &for_type));
+                        }
+                    }
+                }
+                "struct" | "enum" | "union" => {
+                    self.fields(inner, &path);
+                    let impls: Vec<&Json> =
+                        ids(&inner["impls"]).filter_map(|id| self.get(id)).collect();
+                    for imp in impls {
+                        let Some(("impl", imp)) = item_kind(imp) else {
+                            continue;
+                        };
+                        if !imp["blanket_impl"].is_null() {
+                            continue;
+                        }
+                        if imp["trait"].is_null() {
+                            self.members(&imp["items"], &path, true);
+                        } else {
+                            let args = generic_args(&imp["for"]["resolved_path"]["args"]);
+                            self.add(&path, 2, impl_entry(imp, &format!("{path}{args}")));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        /// Add the fields of a struct or union, or the variants of an enum
+        fn fields(&mut self, inner: &Json, path: &str) {
+            let field_type = |walker: &Self, id: &Json| {
+                walker
+                    .get(id)
+                    .map_or("_".into(), |field| ty(&field["inner"]["struct_field"]))
+            };
+            let kind = &inner["kind"];
+            if let Some(fields) = kind.get("tuple") {
+                for (i, field) in ids(fields).enumerate() {
+                    let Some(field) = self.get(field) else {
+                        continue;
+                    };
+                    if field["visibility"] == "public" {
+                        let t = ty(&field["inner"]["struct_field"]);
+                        self.add(path, 1, format!("pub {path}::{i}: {t}"));
+                    }
+                }
+            }
+            for field in ids(&kind["plain"]["fields"]).chain(ids(&inner["fields"])) {
+                let Some(field) = self.get(field) else {
+                    continue;
+                };
+                if field["visibility"] == "public" {
+                    let (name, t) = (str(&field["name"]), ty(&field["inner"]["struct_field"]));
+                    self.add(path, 1, format!("pub {path}::{name}: {t}"));
+                }
+            }
+            let variants: Vec<&Json> = ids(&inner["variants"])
+                .filter_map(|id| self.get(id))
+                .collect();
+            for variant in variants {
+                let name = str(&variant["name"]);
+                let variant_kind = &variant["inner"]["variant"]["kind"];
+                let shape = if let Some(fields) = variant_kind.get("tuple") {
+                    format!("({})", join(fields, ", ", |id| field_type(self, id)))
+                } else if let Some(fields) = variant_kind.get("struct") {
+                    let fields = join(&fields["fields"], ", ", |id| {
+                        let name = self.get(id).map_or("_", |field| str(&field["name"]));
+                        format!("{name}: {}", field_type(self, id))
+                    });
+                    format!(" {{ {fields} }}")
+                } else {
+                    String::new()
+                };
+                self.add(path, 1, format!("pub {path}::{name}{shape}"));
+            }
+        }
+        fn members(&mut self, list: &Json, parent: &str, public_only: bool) {
+            let members: Vec<&Json> = ids(list).filter_map(|id| self.get(id)).collect();
+            for member in members {
+                if public_only && member["visibility"] != "public" {
+                    continue;
+                }
+                let (Some((kind, inner)), Some(name)) =
+                    (item_kind(member), member["name"].as_str())
+                else {
+                    continue;
+                };
+                let path = format!("{parent}::{name}");
+                let entry = match kind {
+                    "function" => function(&path, inner),
+                    "assoc_const" => format!("pub const {path}: {}", ty(&inner["type"])),
+                    "assoc_type" => {
+                        let mut entry = format!("pub type {path}");
+                        if ids(&inner["bounds"]).next().is_some() {
+                            entry = format!("{entry}: {}", bounds(&inner["bounds"]));
+                        }
+                        if !inner["type"].is_null() {
+                            entry = format!("{entry} = {}", ty(&inner["type"]));
+                        }
+                        entry
+                    }
+                    kind => format!("pub {kind} {path}"),
+                };
+                self.add(parent, 1, entry);
+            }
+        }
+    }
+    fn impl_entry(imp: &Json, for_type: &str) -> String {
+        let (params, where_clause) = generics(&imp["generics"]);
+        let unsafety = if imp["is_unsafe"] == true {
+            "unsafe "
+        } else {
+            ""
+        };
+        let negative = if imp["is_negative"] == true { "!" } else { "" };
+        let tr = path(&imp["trait"]);
+        format!("{unsafety}impl{params} {negative}{tr} for {for_type}{where_clause}")
+    }
+
+    let mut walker = Walker {
+        index: &doc["index"],
+        entries: BTreeSet::new(),
+    };
+    let root = walker.get(&doc["root"]).expect("No root module");
+    for child in ids(&root["inner"]["module"]["items"]) {
+        walker.item(child, "uiua", None);
+    }
+    let mut api = String::new();
+    for (.., entry) in walker.entries {
+        api.push_str(&entry);
+        api.push('\n');
+    }
+
+    let snapshot_path = manifest_dir.join("public_api.txt");
+    if std::env::var("UPDATE_PUBLIC_API").is_ok() {
+        std::fs::write(&snapshot_path, api).unwrap();
+        return;
+    }
+    let snapshot = std::fs::read_to_string(&snapshot_path).unwrap_or_default();
+    if api != snapshot {
+        let old: BTreeSet<&str> = snapshot.lines().collect();
+        let new: BTreeSet<&str> = api.lines().collect();
+        let mut message = String::from("The public API has changed:\n");
+        for removed in old.difference(&new) {
+            message.push_str(&format!("- {removed}\n"));
+        }
+        for added in new.difference(&old) {
+            message.push_str(&format!("+ {added}\n"));
+        }
+        message.push_str(
+            "If this is intentional, run \
+            `UPDATE_PUBLIC_API=1 cargo test --test public_api -- --ignored` \
+            and commit public_api.txt",
+        );
+        panic!("{message}");
+    }
+}
@@ -24,7 +24,43 @@ pub unsafe extern "C" fn reversed(list: *const i32, len: i32) -> *const i32 {
     let slice = std::slice::from_raw_parts(list, len as usize);
     let mut reversed = slice.to_vec();
     reversed.reverse();
-    reversed.as_ptr()
+    // The caller reads the list after this returns, so it must not be freed
+    Box::leak(reversed.into_boxed_slice()).as_ptr()
+}
+
+#[repr(C)]
+pub struct Pair {
+    pub a: i32,
+    pub b: f64,
+}
+
+#[no_mangle]
+pub extern "C" fn scale(pair: Pair, factor: f64) -> Pair {
+    Pair {
+        a: (pair.a as f64 * factor) as i32,
+        b: pair.b * factor,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pair_sum(pair: *const Pair) -> f64 {
+    (*pair).a as f64 + (*pair).b
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn divmod(a: i32, b: i32, rem: *mut i32) -> i32 {
+    *rem = a % b;
+    a / b
+}
+
+#[no_mangle]
+pub extern "C" fn apply(f: extern "C" fn(i32, i32) -> i32, a: i32, b: i32) -> i32 {
+    f(a, b)
+}
+
+#[no_mangle]
+pub extern "C" fn apply_on_thread(f: extern "C" fn(i32, i32) -> i32, a: i32, b: i32) -> i32 {
+    std::thread::spawn(move || f(a, b)).join().unwrap()
 }
 
 #[test]